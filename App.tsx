@@ -1340,6 +1340,24 @@ const App: React.FC = () => {
             }
         };
         input.click();
+    } else if (action === 'import-tradingview') {
+        const electron = window.electronAPI;
+        if (!electron?.importTradingViewDrawings || !activeTab) return;
+        const result = await electron.importTradingViewDrawings();
+        if (result.canceled) return;
+        if (!result.success) {
+            alert(`TradingView import failed: ${result.error}`);
+            setLastError(result.error || 'TradingView import failed');
+            return;
+        }
+        const imported = result.drawings || [];
+        updateActiveTab({
+            drawings: [...activeTab.drawings, ...imported],
+            folders: [...activeTab.folders, ...(result.folders || [])]
+        });
+        const skippedCount = Object.values(result.skipped || {}).reduce((a, b) => a + b, 0);
+        alert(`Imported ${imported.length} drawings from TradingView${skippedCount ? ` (${skippedCount} unsupported objects skipped)` : ''}.`);
+        debugLog('Data', 'TradingView drawings imported', { imported: imported.length, skipped: result.skipped });
     }
   };
 
@@ -407,6 +407,7 @@ export const Toolbar: React.FC<ToolbarProps> = ({
                 <button onClick={() => handleLayoutClick('save')} className="w-full text-left px-4 py-2 text-sm text-slate-400 hover:text-white hover:bg-[#334155] flex items-center gap-3"><Save size={16} className="text-emerald-400" /><span>Save Layout to DB</span></button>
                 <button onClick={() => handleLayoutClick('export-layout')} className="w-full text-left px-4 py-2 text-sm text-slate-400 hover:text-white hover:bg-[#334155] flex items-center gap-3"><FileDown size={16} className="text-blue-400" /><span>Export Layout (.json)</span></button>
                 <button onClick={() => handleLayoutClick('import-layout')} className="w-full text-left px-4 py-2 text-sm text-slate-400 hover:text-white hover:bg-[#334155] flex items-center gap-3"><FileInput size={16} className="text-amber-400" /><span>Import Layout (.json)</span></button>
+                <button onClick={() => handleLayoutClick('import-tradingview')} className="w-full text-left px-4 py-2 text-sm text-slate-400 hover:text-white hover:bg-[#334155] flex items-center gap-3"><FileInput size={16} className="text-purple-400" /><span>Import TradingView Drawings</span></button>
                 
                 <div className="h-px bg-[#334155] my-1 mx-2"></div>
                 <button onClick={() => handleLayoutClick('save-csv')} className="w-full text-left px-4 py-2 text-sm text-slate-400 hover:text-white hover:bg-[#334155] flex items-center gap-3"><Download size={16} className="text-slate-400" /><span>Save Chart Data as CSV</span></button>
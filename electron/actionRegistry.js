@@ -0,0 +1,54 @@
+
+// --- ACTION REGISTRY ---
+// Single list of user-invokable actions for the command palette and menus.
+// Backend actions carry a `run(args)`; renderer-owned actions (open a panel,
+// focus the chart) have none and are forwarded to the windows instead, so the
+// palette can list everything from one place. Ids match keybinding action ids
+// where a shortcut exists.
+
+const createActionRegistry = ({ getShortcut = () => null, forwardToRenderer }) => {
+    const actions = new Map();
+
+    /**
+     * def: { title, category, description?, args?: { name: 'string'|'number'|..., ... }, run?(args), when?() }
+     * `when` hides the action while it can't run (e.g. no database).
+     */
+    const register = (id, def) => {
+        if (actions.has(id)) throw new Error(`Action already registered: ${id}`);
+        actions.set(id, { id, ...def });
+    };
+
+    const describe = (a) => ({
+        id: a.id,
+        title: a.title,
+        category: a.category || 'General',
+        description: a.description || null,
+        args: a.args || null,
+        shortcut: getShortcut(a.id),
+        handledBy: a.run ? 'backend' : 'renderer'
+    });
+
+    const list = () => Array.from(actions.values())
+        .filter(a => !a.when || a.when())
+        .map(describe)
+        .sort((a, b) => a.category.localeCompare(b.category) || a.title.localeCompare(b.title));
+
+    const execute = async (id, args = {}) => {
+        const action = actions.get(id);
+        if (!action) throw new Error(`Unknown action: ${id}`);
+        if (action.when && !action.when()) throw new Error(`Action unavailable: ${id}`);
+        Object.entries(action.args || {}).forEach(([name, spec]) => {
+            const required = !String(spec).endsWith('?');
+            if (required && (args[name] === undefined || args[name] === null)) throw new Error(`Missing argument "${name}" for ${id}`);
+        });
+        if (!action.run) {
+            forwardToRenderer({ id, args });
+            return { forwarded: true };
+        }
+        return { forwarded: false, result: await action.run(args) };
+    };
+
+    return { register, list, execute };
+};
+
+module.exports = { createActionRegistry };
@@ -0,0 +1,110 @@
+
+// --- ACTIVITY TIMELINE ---
+// A lightweight diary of what happened in the workspace — charts opened,
+// notes created, alerts fired, trades logged or imported — one short row per
+// event in `activity_log`, so a trading day can be reconstructed afterwards
+// and printed with the daily journal report. Rows carry a display title and a
+// small detail object, not copies of the items they refer to. A chart counts
+// as opened once per CHART_REOPEN_MS (charts reload their state on every tab
+// switch), and rows older than RETENTION_DAYS are pruned on startup.
+
+const KINDS = ['chart_opened', 'note_created', 'alert_fired', 'trade_logged', 'trades_imported'];
+const CHART_REOPEN_MS = 15 * 60 * 1000;
+const RETENTION_DAYS = 365;
+const DAY_MS = 86400000;
+const MAX_TITLE_LENGTH = 200;
+const DEFAULT_LIMIT = 2000;
+
+const initializeActivityTable = (db) => {
+    db.exec(`
+        CREATE TABLE IF NOT EXISTS activity_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            at INTEGER,
+            kind TEXT,
+            ref TEXT,
+            symbol TEXT,
+            title TEXT,
+            detail TEXT
+        );
+        CREATE INDEX IF NOT EXISTS idx_activity_log_at ON activity_log (at);
+    `);
+};
+
+const mapActivityRow = (row) => ({
+    id: row.id,
+    at: row.at,
+    kind: row.kind,
+    ref: row.ref,
+    symbol: row.symbol,
+    title: row.title,
+    detail: row.detail ? JSON.parse(row.detail) : null
+});
+
+/**
+ * fields: { ref?, symbol?, title, detail?, at? }. Returns the stored event,
+ * or null when it was folded into a recent identical chart open.
+ */
+const recordActivity = (db, kind, { ref = null, symbol = null, title = '', detail = null, at = Date.now() } = {}) => {
+    if (!KINDS.includes(kind)) throw new Error(`kind must be one of ${KINDS.join(', ')}`);
+    if (kind === 'chart_opened' && ref != null) {
+        const last = db.prepare("SELECT at FROM activity_log WHERE kind = 'chart_opened' AND ref = ? ORDER BY at DESC LIMIT 1").get(String(ref));
+        if (last && at - last.at < CHART_REOPEN_MS) return null;
+    }
+    const info = db.prepare('INSERT INTO activity_log (at, kind, ref, symbol, title, detail) VALUES (?, ?, ?, ?, ?, ?)')
+        .run(at, kind, ref == null ? null : String(ref), symbol || null, String(title || '').slice(0, MAX_TITLE_LENGTH), detail ? JSON.stringify(detail) : null);
+    return mapActivityRow(db.prepare('SELECT * FROM activity_log WHERE id = ?').get(info.lastInsertRowid));
+};
+
+const pruneActivity = (db, now = Date.now()) => db.prepare('DELETE FROM activity_log WHERE at < ?').run(now - RETENTION_DAYS * DAY_MS).changes;
+
+// Local midnight of `day` ('YYYY-MM-DD') or of today
+const dayBounds = (day = null) => {
+    const start = day ? new Date(`${day}T00:00:00`) : new Date();
+    if (Number.isNaN(start.getTime())) throw new Error(`Invalid day: ${day}`);
+    start.setHours(0, 0, 0, 0);
+    const end = new Date(start);
+    end.setDate(end.getDate() + 1);
+    return { from: start.getTime(), to: end.getTime() - 1 };
+};
+
+/**
+ * range: { from?, to? } (epoch ms) or { day?: 'YYYY-MM-DD' } — today by
+ * default; plus { kinds?, symbol?, limit? }. Returns { from, to, events
+ * (oldest first), counts: { kind: n }, firstAt, lastAt, symbols: [{ symbol,
+ * events }] (busiest first), truncated }.
+ */
+const getActivityTimeline = (db, range = {}) => {
+    const { kinds = null, symbol = null, limit = DEFAULT_LIMIT } = range || {};
+    const bounds = range && (range.from != null || range.to != null)
+        ? { from: range.from != null ? Number(range.from) : 0, to: range.to != null ? Number(range.to) : Date.now() }
+        : dayBounds(range && range.day);
+    if (!(bounds.from <= bounds.to)) throw new Error('from must not be after to');
+    const where = ['at BETWEEN ? AND ?'];
+    const params = [bounds.from, bounds.to];
+    if (Array.isArray(kinds) && kinds.length) {
+        where.push(`kind IN (${kinds.map(() => '?').join(', ')})`);
+        params.push(...kinds.map(String));
+    }
+    if (symbol) { where.push('symbol = ?'); params.push(String(symbol)); }
+    const max = Math.max(1, Math.min(20000, Number(limit) || DEFAULT_LIMIT));
+    const rows = db.prepare(`SELECT * FROM activity_log WHERE ${where.join(' AND ')} ORDER BY at ASC, id ASC LIMIT ?`).all(...params, max + 1);
+    const events = rows.slice(0, max).map(mapActivityRow);
+    const counts = Object.fromEntries(KINDS.map(k => [k, 0]));
+    const bySymbol = new Map();
+    events.forEach((e) => {
+        counts[e.kind] = (counts[e.kind] || 0) + 1;
+        if (e.symbol) bySymbol.set(e.symbol, (bySymbol.get(e.symbol) || 0) + 1);
+    });
+    return {
+        from: bounds.from,
+        to: bounds.to,
+        events,
+        counts,
+        firstAt: events.length ? events[0].at : null,
+        lastAt: events.length ? events[events.length - 1].at : null,
+        symbols: Array.from(bySymbol, ([s, n]) => ({ symbol: s, events: n })).sort((a, b) => b.events - a.events || a.symbol.localeCompare(b.symbol)),
+        truncated: rows.length > max
+    };
+};
+
+module.exports = { KINDS, initializeActivityTable, recordActivity, pruneActivity, dayBounds, getActivityTimeline };
@@ -0,0 +1,342 @@
+
+const crypto = require('crypto');
+const { parseExpression, evaluateExpression } = require('./expressions');
+
+// --- PRICE ALERTS ---
+// Alerts live in the `alerts` table and are evaluated in the main process
+// against the live feed, so they keep firing while the window is hidden.
+// Crossing conditions compare against the previous price seen for the symbol;
+// the first tick after startup only primes that state.
+//
+// Besides a fixed price, the level can be a drawing from a saved chart state
+// (options.drawing = { sourceId, drawingId }: horizontal lines and rays, trend
+// lines, rays), read at the tick's time so a sloped trendline is followed.
+// 'expression' alerts use an indicator expression (options.expression, e.g.
+// "rsi(14) crosses_above 70" or "macd crosses_above macd_signal") on the bars
+// of options.timeframe, evaluated when a bar closes (options.intrabar: also on
+// forming bars) and fired when it turns true. Chart replay feeds the same
+// conditions through evaluateReplay, which keeps its own state and never
+// disarms or stamps the stored alerts.
+//
+// exportAlerts / importAlerts move alerts between installs as a
+// "redpill.alerts" document. Imports always get fresh ids (the old -> new map is
+// returned), skip alerts identical to one already stored, and can rename
+// symbols or chart sources on the way in.
+
+const CONDITIONS = ['crosses_above', 'crosses_below', 'above', 'below', 'touches', 'expression'];
+const LEVEL_CONDITIONS = ['above', 'below', 'touches'];
+const DRAWING_TYPES = ['horizontal_line', 'horizontal_ray', 'trend_line', 'arrow_line', 'ray'];
+const ALERT_FILE_FORMAT = 'redpill.alerts';
+const ALERT_FILE_VERSION = 1;
+
+const initializeAlertTables = (db) => {
+    db.exec(`
+        CREATE TABLE IF NOT EXISTS alerts (
+            id TEXT PRIMARY KEY,
+            symbol TEXT,
+            condition TEXT,
+            price REAL,
+            message TEXT,
+            options TEXT,
+            enabled INTEGER,
+            once INTEGER,
+            triggered_at INTEGER,
+            created_at INTEGER
+        );
+        CREATE INDEX IF NOT EXISTS idx_alerts_symbol ON alerts (symbol);
+    `);
+};
+
+const mapAlertRow = (row) => row ? ({
+    id: row.id,
+    symbol: row.symbol,
+    condition: row.condition,
+    price: row.price,
+    message: row.message,
+    options: row.options ? JSON.parse(row.options) : {},
+    enabled: !!row.enabled,
+    once: !!row.once,
+    triggeredAt: row.triggered_at,
+    createdAt: row.created_at
+}) : null;
+
+// What makes two alerts the same for duplicate detection on import
+const alertFingerprint = (alert) => {
+    const options = alert.options || {};
+    return JSON.stringify([
+        String(alert.symbol || '').toUpperCase(),
+        alert.condition,
+        alert.condition === 'expression' || options.drawing ? null : Number(alert.price),
+        options.expression || null,
+        options.timeframe || null,
+        options.drawing ? [options.drawing.sourceId, options.drawing.drawingId] : null
+    ]);
+};
+
+const isMet = (condition, target, price, previous, tolerance = 0) => {
+    switch (condition) {
+        case 'above': return price >= target;
+        case 'below': return price <= target;
+        case 'crosses_above': return previous != null && previous < target && price >= target;
+        case 'crosses_below': return previous != null && previous > target && price <= target;
+        // Within the tolerance, or jumped across the level since the last tick
+        case 'touches': return Math.abs(price - target) <= tolerance
+            || (previous != null && (previous - target) * (price - target) < 0);
+        default: return false;
+    }
+};
+
+/**
+ * Price of a drawing at time ts, or null where it has no price (before a
+ * ray starts, outside a trend line's segment unless extended).
+ */
+const drawingLevel = (drawing, ts, extend = false) => {
+    const [p1, p2] = drawing.points || [];
+    if (!p1) return null;
+    if (drawing.type === 'horizontal_line') return p1.price;
+    if (drawing.type === 'horizontal_ray') return ts >= p1.time ? p1.price : null;
+    if (!p2 || p2.time === p1.time) return null;
+    const slope = (p2.price - p1.price) / (p2.time - p1.time);
+    const value = p1.price + slope * (ts - p1.time);
+    if (drawing.type === 'ray') return (ts - p1.time) * (p2.time - p1.time) >= 0 ? value : null;
+    const lo = Math.min(p1.time, p2.time);
+    const hi = Math.max(p1.time, p2.time);
+    return extend || (ts >= lo && ts <= hi) ? value : null;
+};
+
+// Evaluation state; live and replay each have their own
+const createContext = () => ({ lastPrice: new Map(), inZone: new Set(), exprMet: new Map(), lastTs: null, hits: [] });
+
+/**
+ * `onTrigger(alert, { price, previous, level, values?, timestamp, replay? })`
+ * is called once per firing. Level conditions ('above'/'below'/'touches')
+ * re-arm only after price leaves the zone so they don't fire on every tick.
+ * loadBars(symbol, timeframe, limit, toTime?) -> bars (oldest first) and
+ * loadChartState(sourceId) -> { drawings } feed expression and drawing alerts.
+ */
+const createAlertEngine = ({ db, onTrigger, loadBars = () => [], loadChartState = () => null }) => {
+    const live = createContext();
+    let replay = createContext();
+    const drawingCache = new Map(); // sourceId -> drawings by id
+    const compiled = new Map();     // expression source -> parsed
+
+    const getAlert = (id) => mapAlertRow(db.prepare('SELECT * FROM alerts WHERE id = ?').get(id));
+
+    const getDrawing = (ref) => {
+        if (!drawingCache.has(ref.sourceId)) {
+            const state = loadChartState(ref.sourceId);
+            drawingCache.set(ref.sourceId, new Map(((state && state.drawings) || []).map(d => [d.id, d])));
+        }
+        return drawingCache.get(ref.sourceId).get(ref.drawingId) || null;
+    };
+
+    const parsed = (source) => {
+        if (!compiled.has(source)) compiled.set(source, parseExpression(source));
+        return compiled.get(source);
+    };
+
+    // Throws on anything the engine could not evaluate
+    const validateAlert = (alert) => {
+        if (!alert.symbol) throw new Error('Alert symbol is required');
+        if (!CONDITIONS.includes(alert.condition)) throw new Error(`Unknown alert condition: ${alert.condition}`);
+        const options = alert.options || {};
+        if (alert.condition === 'expression') {
+            if (!options.timeframe) throw new Error('Expression alerts need options.timeframe');
+            return { price: null, options: { ...options, expression: parseExpression(options.expression).source } };
+        }
+        if (options.drawing) {
+            const { sourceId, drawingId } = options.drawing;
+            if (!sourceId || !drawingId) throw new Error('options.drawing needs sourceId and drawingId');
+            drawingCache.delete(sourceId);
+            const drawing = getDrawing(options.drawing);
+            if (!drawing) throw new Error(`Drawing ${drawingId} not found in ${sourceId}`);
+            if (!DRAWING_TYPES.includes(drawing.type)) throw new Error(`Alerts can't follow a ${drawing.type} drawing`);
+            // The stored price is the level at creation, for display
+            return { price: drawingLevel(drawing, Date.now(), true), options: { ...options, drawing: { sourceId, drawingId } } };
+        }
+        const price = Number(alert.price);
+        if (!isFinite(price)) throw new Error('Alert price must be a number');
+        return { price, options };
+    };
+
+    const saveAlert = (alert) => {
+        const { price, options } = validateAlert(alert);
+        const id = alert.id || crypto.randomUUID();
+        const existing = getAlert(id);
+        db.prepare(`
+            INSERT OR REPLACE INTO alerts (id, symbol, condition, price, message, options, enabled, once, triggered_at, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        `).run(
+            id,
+            String(alert.symbol).toUpperCase(),
+            alert.condition,
+            price,
+            alert.message || '',
+            JSON.stringify(options),
+            alert.enabled === false ? 0 : 1,
+            alert.once === false ? 0 : 1,
+            existing?.triggeredAt || null,
+            existing?.createdAt || Date.now()
+        );
+        live.inZone.delete(id);
+        live.exprMet.delete(id);
+        return getAlert(id);
+    };
+
+    const fire = (ctx, alert, hit) => {
+        if (ctx === live) {
+            db.prepare('UPDATE alerts SET triggered_at = ?, enabled = ? WHERE id = ?').run(hit.timestamp, alert.once ? 0 : 1, alert.id);
+            onTrigger({ ...alert, triggeredAt: hit.timestamp }, hit);
+        } else {
+            ctx.hits.push({ alertId: alert.id, condition: alert.condition, ...hit });
+            onTrigger(alert, { ...hit, replay: true });
+        }
+    };
+
+    const armed = (key) => db.prepare('SELECT * FROM alerts WHERE symbol = ? AND enabled = 1').all(key).map(mapAlertRow);
+
+    const evaluatePrice = (ctx, symbol, price, timestamp) => {
+        if (!isFinite(price)) return;
+        const key = String(symbol).toUpperCase();
+        const previous = ctx.lastPrice.get(key);
+        ctx.lastPrice.set(key, price);
+
+        armed(key).filter(alert => alert.condition !== 'expression').forEach((alert) => {
+            let level = alert.price;
+            if (alert.options.drawing) {
+                const drawing = getDrawing(alert.options.drawing);
+                level = drawing ? drawingLevel(drawing, timestamp, !!alert.options.extend) : null;
+            }
+            const met = level != null && isMet(alert.condition, level, price, previous, Number(alert.options.tolerance) || 0);
+            const isLevel = LEVEL_CONDITIONS.includes(alert.condition);
+            if (!met) {
+                ctx.inZone.delete(alert.id);
+                return;
+            }
+            if (isLevel && ctx.inZone.has(alert.id)) return;
+            if (isLevel) ctx.inZone.add(alert.id);
+            fire(ctx, alert, { price, previous, level, timestamp });
+        });
+    };
+
+    // bars: history of symbol / timeframe ending with the bar being evaluated
+    const evaluateExpressions = (ctx, symbol, timeframe, bars, isClosed) => {
+        if (!bars.length) return;
+        const key = String(symbol).toUpperCase();
+        armed(key).filter(alert => alert.condition === 'expression' && alert.options.timeframe === timeframe).forEach((alert) => {
+            if (!isClosed && !alert.options.intrabar) return;
+            let result;
+            try {
+                const expr = parsed(alert.options.expression);
+                result = evaluateExpression(expr, bars.slice(-expr.lookback));
+            } catch (e) {
+                return;
+            }
+            const was = ctx.exprMet.get(alert.id) || false;
+            ctx.exprMet.set(alert.id, result.matched);
+            if (!result.matched || was) return;
+            const last = bars[bars.length - 1];
+            fire(ctx, alert, { price: last.close, previous: bars.length > 1 ? bars[bars.length - 2].close : null, level: null, values: result.values, timestamp: ctx === live ? Date.now() : last.timestamp });
+        });
+    };
+
+    const needsLookback = (key, timeframe) => Math.max(0, ...armed(key)
+        .filter(alert => alert.condition === 'expression' && alert.options.timeframe === timeframe)
+        .map((alert) => { try { return parsed(alert.options.expression).lookback; } catch (e) { return 0; } }));
+
+    // Live bar from the feed: history comes from the store, the bar itself from the event
+    const evaluateBar = ({ symbol, timeframe, bar, isClosed }) => {
+        const limit = needsLookback(String(symbol).toUpperCase(), timeframe);
+        if (!limit || !bar) return;
+        const history = loadBars(symbol, timeframe, limit + 1).filter(b => b.timestamp < bar.timestamp);
+        evaluateExpressions(live, symbol, timeframe, [...history, bar], isClosed !== false);
+    };
+
+    /**
+     * Replay step at `timestamp` (the bar playback just revealed): price and
+     * drawing conditions see its close, expression alerts the stored bars up
+     * to it. Seeking backwards starts from fresh state. Returns the hits.
+     */
+    const evaluateReplay = (symbol, timeframe, timestamp) => {
+        if (replay.lastTs != null && timestamp < replay.lastTs) replay = createContext();
+        replay.lastTs = timestamp;
+        const key = String(symbol).toUpperCase();
+        const bars = loadBars(symbol, timeframe, Math.max(2, needsLookback(key, timeframe)), timestamp);
+        if (!bars.length) return [];
+        const last = bars[bars.length - 1];
+        // The previous bar primes crossings on the first step of a replay
+        if (!replay.lastPrice.has(key) && bars.length > 1) replay.lastPrice.set(key, bars[bars.length - 2].close);
+        replay.hits = [];
+        evaluatePrice(replay, key, last.close, last.timestamp);
+        evaluateExpressions(replay, key, timeframe, bars, true);
+        return replay.hits;
+    };
+
+    // ids: alerts to include (null = all). Runtime state (triggered, created) is left out.
+    const exportAlerts = (ids = null) => {
+        const rows = db.prepare('SELECT * FROM alerts ORDER BY symbol, created_at').all().map(mapAlertRow)
+            .filter(alert => !ids || ids.includes(alert.id));
+        return {
+            format: ALERT_FILE_FORMAT,
+            version: ALERT_FILE_VERSION,
+            exportedAt: new Date().toISOString(),
+            alerts: rows.map(({ id, symbol, condition, price, message, options, enabled, once }) => ({ id, symbol, condition, price, message, options, enabled, once }))
+        };
+    };
+
+    /**
+     * document: an exportAlerts document. options: { symbolMap?, sourceMap?, disabled? }
+     * — symbolMap / sourceMap rename symbols and drawing chart sources, disabled
+     * imports everything switched off. Returns { imported, duplicates, rejected, idMap }.
+     */
+    const importAlerts = (document, { symbolMap = {}, sourceMap = {}, disabled = false } = {}) => {
+        if (!document || document.format !== ALERT_FILE_FORMAT) throw new Error('Not a Red Pill alerts file');
+        if (document.version > ALERT_FILE_VERSION) throw new Error(`Unsupported alerts file version: ${document.version}`);
+        const seen = new Set(db.prepare('SELECT * FROM alerts').all().map(row => alertFingerprint(mapAlertRow(row))));
+        const result = { imported: [], duplicates: [], rejected: [], idMap: {} };
+        (Array.isArray(document.alerts) ? document.alerts : []).forEach((entry) => {
+            const options = { ...(entry.options || {}) };
+            if (options.drawing) options.drawing = { ...options.drawing, sourceId: sourceMap[options.drawing.sourceId] || options.drawing.sourceId };
+            const symbol = symbolMap[entry.symbol] || entry.symbol;
+            const alert = { symbol, condition: entry.condition, price: entry.price, message: entry.message, options, enabled: disabled ? false : entry.enabled !== false, once: entry.once };
+            const fingerprint = alertFingerprint(alert);
+            if (seen.has(fingerprint)) {
+                result.duplicates.push({ id: entry.id, symbol, condition: entry.condition });
+                return;
+            }
+            try {
+                const saved = saveAlert(alert);
+                seen.add(fingerprint);
+                result.idMap[entry.id] = saved.id;
+                result.imported.push(saved);
+            } catch (err) {
+                result.rejected.push({ id: entry.id, symbol, condition: entry.condition, error: err.message });
+            }
+        });
+        return result;
+    };
+
+    return {
+        listAlerts: (symbol = null) => (symbol
+            ? db.prepare('SELECT * FROM alerts WHERE symbol = ? ORDER BY created_at').all(String(symbol).toUpperCase())
+            : db.prepare('SELECT * FROM alerts ORDER BY symbol, created_at').all()
+        ).map(mapAlertRow),
+        getAlert,
+        saveAlert,
+        validateAlert: (alert) => { validateAlert(alert); return true; },
+        deleteAlert: (id) => {
+            live.inZone.delete(id);
+            live.exprMet.delete(id);
+            return db.prepare('DELETE FROM alerts WHERE id = ?').run(id).changes > 0;
+        },
+        // Chart states changed: drawing levels are re-read on the next tick
+        invalidateDrawings: (sourceId) => drawingCache.delete(sourceId),
+        evaluate: (symbol, price, timestamp = Date.now()) => evaluatePrice(live, symbol, price, timestamp),
+        evaluateBar,
+        evaluateReplay,
+        exportAlerts,
+        importAlerts
+    };
+};
+
+module.exports = { CONDITIONS, initializeAlertTables, createAlertEngine, drawingLevel };
@@ -0,0 +1,124 @@
+
+// --- ANNOTATION INDEX ---
+// Text-bearing drawings (labels, callouts, any drawing with properties.text)
+// pulled out of the saved chart states into `chart_annotations`, so searching
+// trade rationale doesn't mean parsing every chart's JSON. A chart's rows are
+// replaced whenever its state changes; the index is built from scratch the
+// first time the table is created and can be rebuilt on demand. Each row keeps
+// the anchor point (first and last) for time / price context.
+
+const SNIPPET_RADIUS = 60;
+const DEFAULT_LIMIT = 100;
+const MAX_LIMIT = 1000;
+
+const initializeAnnotationIndexTable = (db) => {
+    const exists = db.prepare("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'chart_annotations'").get();
+    db.exec(`
+        CREATE TABLE IF NOT EXISTS chart_annotations (
+            source_id TEXT,
+            drawing_id TEXT,
+            drawing_type TEXT,
+            text TEXT,
+            folder TEXT,
+            time INTEGER,
+            price REAL,
+            end_time INTEGER,
+            end_price REAL,
+            PRIMARY KEY (source_id, drawing_id)
+        );
+    `);
+    if (!exists) rebuildAnnotationIndex(db);
+};
+
+const extractAnnotations = (state) => {
+    if (!state) return [];
+    const folders = new Map((state.folders || []).map(f => [f.id, f.name]));
+    return (state.drawings || [])
+        .filter(d => d && d.id && typeof d.properties?.text === 'string' && d.properties.text.trim())
+        .map((d) => {
+            const points = Array.isArray(d.points) ? d.points : [];
+            const first = points[0] || {};
+            const last = points.length > 1 ? points[points.length - 1] : {};
+            return {
+                drawingId: d.id,
+                type: d.type,
+                text: d.properties.text,
+                folder: d.folderId ? folders.get(d.folderId) || null : null,
+                time: first.time ?? null,
+                price: first.price ?? null,
+                endTime: last.time ?? null,
+                endPrice: last.price ?? null
+            };
+        });
+};
+
+// Replaces the indexed annotations of one chart; state null drops them
+const indexChartState = (db, sourceId, state) => {
+    const insert = db.prepare(`INSERT OR REPLACE INTO chart_annotations (source_id, drawing_id, drawing_type, text, folder, time, price, end_time, end_price)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)`);
+    db.transaction(() => {
+        db.prepare('DELETE FROM chart_annotations WHERE source_id = ?').run(sourceId);
+        extractAnnotations(state).forEach(a => insert.run(sourceId, a.drawingId, a.type, a.text, a.folder, a.time, a.price, a.endTime, a.endPrice));
+    })();
+};
+
+// Returns the number of charts indexed
+const rebuildAnnotationIndex = (db) => {
+    const rows = db.prepare('SELECT symbol, data FROM drawings').all();
+    db.transaction(() => {
+        db.prepare('DELETE FROM chart_annotations').run();
+        rows.forEach((row) => {
+            let state;
+            try { state = JSON.parse(row.data); } catch (e) { return; }
+            indexChartState(db, row.symbol, state);
+        });
+    })();
+    return rows.length;
+};
+
+// All indexed annotations of one chart, oldest anchor first
+const listChartAnnotations = (db, sourceId) => db.prepare('SELECT * FROM chart_annotations WHERE source_id = ? ORDER BY time').all(sourceId)
+    .map(row => ({ drawingId: row.drawing_id, type: row.drawing_type, text: row.text, folder: row.folder, time: row.time, price: row.price }));
+
+const snippet = (text, term) => {
+    const idx = text.toLowerCase().indexOf(term);
+    if (idx === -1 || text.length <= SNIPPET_RADIUS * 2) return text;
+    const start = Math.max(0, idx - SNIPPET_RADIUS);
+    const end = Math.min(text.length, idx + term.length + SNIPPET_RADIUS);
+    return `${start > 0 ? '…' : ''}${text.slice(start, end)}${end < text.length ? '…' : ''}`;
+};
+
+/**
+ * Every whitespace-separated term must appear (case-insensitive) in the text.
+ * options: { sourceId?, limit? }. Returns [{ sourceId, drawingId, type, text,
+ * snippet, folder, time, price, endTime, endPrice }], whole-phrase matches first,
+ * then newest anchor.
+ */
+const searchChartAnnotations = (db, query, { sourceId = null, limit = DEFAULT_LIMIT } = {}) => {
+    const phrase = String(query || '').trim().toLowerCase();
+    const terms = phrase.split(/\s+/).filter(Boolean);
+    if (!terms.length) return [];
+    const escape = (term) => term.replace(/[\\%_]/g, m => `\\${m}`);
+    const where = terms.map(() => "lower(text) LIKE ? ESCAPE '\\'");
+    const params = terms.map(term => `%${escape(term)}%`);
+    if (sourceId) { where.push('source_id = ?'); params.push(sourceId); }
+    const rows = db.prepare(`SELECT * FROM chart_annotations WHERE ${where.join(' AND ')}`).all(...params);
+    return rows
+        .map(row => ({ row, exact: row.text.toLowerCase().includes(phrase) ? 1 : 0 }))
+        .sort((a, b) => b.exact - a.exact || (b.row.time ?? 0) - (a.row.time ?? 0))
+        .slice(0, Math.min(MAX_LIMIT, Number(limit) || DEFAULT_LIMIT))
+        .map(({ row }) => ({
+            sourceId: row.source_id,
+            drawingId: row.drawing_id,
+            type: row.drawing_type,
+            text: row.text,
+            snippet: snippet(row.text, row.text.toLowerCase().includes(phrase) ? phrase : terms[0]),
+            folder: row.folder,
+            time: row.time,
+            price: row.price,
+            endTime: row.end_time,
+            endPrice: row.end_price
+        }));
+};
+
+module.exports = { initializeAnnotationIndexTable, indexChartState, rebuildAnnotationIndex, listChartAnnotations, searchChartAnnotations };
@@ -0,0 +1,118 @@
+
+// --- APP ERRORS ---
+// Every failed command answers { success: false, error, code, details? }:
+// `error` stays the human-readable message (what the UI already shows) and
+// `code` is one of ERROR_CODES so the renderer can react to the kind of
+// failure — offer "locate file" on NOT_FOUND, "choose another folder" on
+// PERMISSION_DENIED, "the file is damaged" on INVALID_JSON — without parsing
+// messages. Code that knows what went wrong throws an AppError; anything else
+// is classified from the Node / SQLite error code and, as a last resort, the
+// message. `details` carries what the code alone doesn't (path, syscall, ...).
+
+const ERROR_CODES = [
+    'NOT_FOUND',          // file, record, provider, ... doesn't exist
+    'ALREADY_EXISTS',
+    'PERMISSION_DENIED',
+    'INVALID_INPUT',      // arguments failed validation
+    'INVALID_JSON',       // a file or payload isn't parseable
+    'CORRUPT_DATA',       // parseable but damaged (database, archive, ...)
+    'VALIDATION_FAILED',  // a document was checked and rejected (chart state, ...)
+    'CONFLICT',           // stale precondition (patch test, concurrent edit)
+    'LOCKED',             // held by someone else (file, database, locked drawing)
+    'UNAVAILABLE',        // database or service not ready
+    'UNSUPPORTED',        // the provider / format / platform can't do it
+    'NETWORK',
+    'TIMEOUT',
+    'DISK_FULL',
+    'DATABASE',
+    'READ_ONLY',
+    'CANCELED',
+    'RATE_LIMITED', 'PAYLOAD_TOO_LARGE', 'PAYLOAD_TOO_DEEP', // ipcGuards.js
+    'INTERNAL'            // a bug or anything unclassified
+];
+
+class AppError extends Error {
+    constructor(code, message, details = null) {
+        super(message);
+        this.name = 'AppError';
+        this.code = ERROR_CODES.includes(code) ? code : 'INTERNAL';
+        this.details = details;
+    }
+}
+
+// Node / SQLite / app error codes -> ERROR_CODES
+const CODE_MAP = {
+    ENOENT: 'NOT_FOUND', ENOTDIR: 'NOT_FOUND', EISDIR: 'INVALID_INPUT',
+    EACCES: 'PERMISSION_DENIED', EPERM: 'PERMISSION_DENIED', EROFS: 'PERMISSION_DENIED',
+    EEXIST: 'ALREADY_EXISTS', ENOTEMPTY: 'ALREADY_EXISTS',
+    ENOSPC: 'DISK_FULL', EDQUOT: 'DISK_FULL',
+    EBUSY: 'LOCKED', EADDRINUSE: 'LOCKED',
+    ETIMEDOUT: 'TIMEOUT',
+    ECONNREFUSED: 'NETWORK', ECONNRESET: 'NETWORK', ENOTFOUND: 'NETWORK', EAI_AGAIN: 'NETWORK', EHOSTUNREACH: 'NETWORK', ENETUNREACH: 'NETWORK',
+    NETWORK_DISABLED: 'NETWORK', CERT_PIN_MISMATCH: 'NETWORK', TLS_POLICY_PLAINTEXT: 'NETWORK',
+    SQLITE_CORRUPT: 'CORRUPT_DATA', SQLITE_NOTADB: 'CORRUPT_DATA',
+    SQLITE_BUSY: 'LOCKED', SQLITE_LOCKED: 'LOCKED',
+    SQLITE_READONLY: 'READ_ONLY', SQLITE_FULL: 'DISK_FULL', SQLITE_CONSTRAINT_UNIQUE: 'ALREADY_EXISTS', SQLITE_CONSTRAINT_PRIMARYKEY: 'ALREADY_EXISTS',
+    PATCH_FAILED: 'CONFLICT', ORDER_NOT_CONFIRMED: 'CANCELED'
+};
+
+// Message fallbacks for plain Errors thrown by validation code
+const MESSAGE_RULES = [
+    [/\bnot found\b|^unknown \w+( \w+)?:|no such /i, 'NOT_FOUND'],
+    [/already exists|already used/i, 'ALREADY_EXISTS'],
+    [/not initialized|not running|not ready|not connected/i, 'UNAVAILABLE'],
+    [/not supported|does not support|unsupported|can't be|cannot be/i, 'UNSUPPORTED'],
+    [/\bJSON\b|unexpected token|unexpected end of/i, 'INVALID_JSON'],
+    [/\bmust\b|\brequired\b|\binvalid\b|\bneeds?\b|\bat most\b|\bbetween\b|\bexpected\b/i, 'INVALID_INPUT']
+];
+
+const classify = (err) => {
+    if (!err) return 'INTERNAL';
+    if (err instanceof AppError) return err.code;
+    if (err.code && CODE_MAP[err.code]) return CODE_MAP[err.code];
+    if (typeof err.code === 'string' && err.code.startsWith('SQLITE_')) return 'DATABASE';
+    if (err.name === 'TimeoutError') return 'TIMEOUT';
+    if (err.name === 'AbortError') return 'CANCELED';
+    if (err.name === 'SyntaxError') return 'INVALID_JSON';
+    const message = String(err.message || '');
+    const rule = MESSAGE_RULES.find(([pattern]) => pattern.test(message));
+    return rule ? rule[1] : 'INTERNAL';
+};
+
+// What the code doesn't say: AppError details, or the system error's path / syscall
+const detailsOf = (err) => {
+    if (!err) return null;
+    if (err instanceof AppError) return err.details;
+    const details = {};
+    ['path', 'dest', 'syscall', 'errno'].forEach((k) => { if (err[k] != null) details[k] = err[k]; });
+    if (typeof err.code === 'string' && !(err.code in details)) details.systemCode = err.code;
+    return Object.keys(details).length ? details : null;
+};
+
+/**
+ * The answer for a failed command: { success: false, error, code, details?, ...extra }.
+ */
+const errorResult = (err, extra = {}) => {
+    const details = detailsOf(err);
+    return { success: false, error: err && err.message ? err.message : String(err), code: classify(err), ...(details ? { details } : {}), ...extra };
+};
+
+// A failure the handler detected itself (no thrown error)
+const failure = (code, message, extra = {}) => ({ success: false, error: message, code, ...extra });
+
+/**
+ * Wraps ipcMain.handle like instrumentIpc does, as the outermost layer: a
+ * { success: false } answer without a code gets one (CANCELED for dialogs
+ * the user closed, READ_ONLY, otherwise classified from the message).
+ */
+const installErrorCodes = (ipcMain) => {
+    const originalHandle = ipcMain.handle.bind(ipcMain);
+    ipcMain.handle = (channel, handler) => originalHandle(channel, async (event, ...args) => {
+        const result = await handler(event, ...args);
+        if (!result || typeof result !== 'object' || result.success !== false || result.code) return result;
+        const code = result.canceled ? 'CANCELED' : result.readOnly ? 'READ_ONLY' : classify({ message: result.error });
+        return { ...result, code };
+    });
+};
+
+module.exports = { ERROR_CODES, AppError, classify, errorResult, failure, installErrorCodes };
@@ -0,0 +1,75 @@
+
+// --- OS APPEARANCE ---
+// Follows the OS dark/light preference, accent color and high-contrast flag
+// through nativeTheme / systemPreferences. The user's choice ('system',
+// 'dark' or 'light') is forwarded to nativeTheme.themeSource, so menus,
+// dialogs and prefers-color-scheme in renderers agree with the app. When the
+// effective scheme flips, main switches to the palette configured for it.
+// Accent color is only reported on Windows and macOS.
+
+const APPEARANCE_MODES = ['system', 'dark', 'light'];
+
+const FALLBACK_COLORS = {
+    dark: { background: '#0f172a', symbol: '#94a3b8' },
+    light: { background: '#ffffff', symbol: '#475569' }
+};
+
+// Window background and titlebar overlay colors for a theme (themes.js shape)
+const windowColors = (theme, scheme = 'dark') => {
+    const fallback = FALLBACK_COLORS[scheme] || FALLBACK_COLORS.dark;
+    const ui = (theme && theme.ui) || {};
+    const chart = (theme && theme.chart) || {};
+    return { background: ui.surfaceAlt || chart.backgroundColor || fallback.background, symbol: ui.textMuted || chart.textColor || fallback.symbol };
+};
+
+const createAppearanceTracker = ({ nativeTheme, systemPreferences, platform = process.platform, getMode = () => 'system', onChange = () => {} }) => {
+    let current = null;
+
+    // systemPreferences returns RRGGBBAA
+    const accentColor = () => {
+        if (platform !== 'win32' && platform !== 'darwin') return null;
+        try {
+            const value = systemPreferences.getAccentColor();
+            return /^[0-9a-f]{6,8}$/i.test(value || '') ? `#${value.slice(0, 6).toLowerCase()}` : null;
+        } catch (e) {
+            return null;
+        }
+    };
+
+    const currentMode = () => (APPEARANCE_MODES.includes(getMode()) ? getMode() : 'system');
+
+    const evaluate = (mode = currentMode()) => ({
+        mode,
+        scheme: nativeTheme.shouldUseDarkColors ? 'dark' : 'light',
+        accentColor: accentColor(),
+        highContrast: !!nativeTheme.shouldUseHighContrastColors,
+        invertedColorScheme: !!nativeTheme.shouldUseInvertedColorScheme
+    });
+
+    const refresh = () => {
+        const mode = currentMode();
+        if (nativeTheme.themeSource !== mode) nativeTheme.themeSource = mode;
+        const previous = current;
+        const next = evaluate(mode);
+        const changed = !previous || ['mode', 'scheme', 'accentColor', 'highContrast', 'invertedColorScheme'].some(key => previous[key] !== next[key]);
+        current = next;
+        if (changed) onChange(current, previous);
+        return current;
+    };
+
+    // Must run after app 'ready'
+    const start = () => {
+        nativeTheme.on('updated', refresh);
+        if (platform === 'win32') systemPreferences.on('accent-color-changed', refresh);
+        if (platform === 'darwin' && typeof systemPreferences.subscribeNotification === 'function') {
+            systemPreferences.subscribeNotification('NSSystemColorsDidChangeNotification', refresh);
+        }
+        return refresh();
+    };
+
+    const state = () => current || evaluate();
+
+    return { start, refresh, state };
+};
+
+module.exports = { APPEARANCE_MODES, windowColors, createAppearanceTracker };
@@ -0,0 +1,101 @@
+
+const crypto = require('crypto');
+
+// --- ATTACHMENTS ---
+// Image blobs (clipboard pastes, screenshots) kept in their own table so notes
+// embed a short reference instead of a base64 string. A reference is
+// `redpill-attachment://<id>`, served to the renderer by the protocol handler
+// in main.js. Identical bytes are stored once. Attachments no note or dossier
+// refers to any more can be pruned after a grace period (a paste may not be saved yet).
+
+const SCHEME = 'redpill-attachment';
+const MAX_ATTACHMENT_BYTES = 25 * 1024 * 1024;
+const PRUNE_GRACE_MS = 24 * 60 * 60 * 1000;
+const MIME_TYPES = ['image/png', 'image/jpeg'];
+
+const initializeAttachmentTable = (db) => {
+    db.exec(`
+        CREATE TABLE IF NOT EXISTS attachments (
+            id TEXT PRIMARY KEY,
+            sha256 TEXT UNIQUE,
+            mime TEXT,
+            data BLOB,
+            width INTEGER,
+            height INTEGER,
+            bytes INTEGER,
+            source TEXT,
+            created_at INTEGER
+        );
+    `);
+};
+
+const attachmentRef = (id) => `${SCHEME}://${id}`;
+
+const mapInfoRow = (row) => (row ? {
+    id: row.id,
+    ref: attachmentRef(row.id),
+    mime: row.mime,
+    width: row.width,
+    height: row.height,
+    bytes: row.bytes,
+    source: row.source,
+    createdAt: row.created_at
+} : null);
+
+const INFO_COLUMNS = 'id, mime, width, height, bytes, source, created_at';
+
+/**
+ * Stores image bytes: { data: Buffer, mime, width?, height?, source? }.
+ * Returns the attachment info; `existing: true` when the same bytes were already stored.
+ */
+const saveAttachment = (db, { data, mime, width = null, height = null, source = null }) => {
+    if (!Buffer.isBuffer(data) || data.length === 0) throw new Error('Attachment data is empty');
+    if (data.length > MAX_ATTACHMENT_BYTES) throw new Error(`Attachments are limited to ${MAX_ATTACHMENT_BYTES / (1024 * 1024)} MB`);
+    if (!MIME_TYPES.includes(mime)) throw new Error(`Unsupported attachment type: ${mime}`);
+    const sha256 = crypto.createHash('sha256').update(data).digest('hex');
+    const found = db.prepare(`SELECT ${INFO_COLUMNS} FROM attachments WHERE sha256 = ?`).get(sha256);
+    if (found) return { ...mapInfoRow(found), existing: true };
+    const id = crypto.randomUUID();
+    db.prepare('INSERT INTO attachments (id, sha256, mime, data, width, height, bytes, source, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)')
+        .run(id, sha256, mime, data, width, height, data.length, source, Date.now());
+    return { ...getAttachmentInfo(db, id), existing: false };
+};
+
+const getAttachmentInfo = (db, id) => mapInfoRow(db.prepare(`SELECT ${INFO_COLUMNS} FROM attachments WHERE id = ?`).get(id));
+
+// { mime, data } or null
+const readAttachment = (db, id) => {
+    const row = db.prepare('SELECT mime, data FROM attachments WHERE id = ?').get(id);
+    return row ? { mime: row.mime, data: Buffer.from(row.data) } : null;
+};
+
+const listAttachments = (db, { limit = 500 } = {}) => db.prepare(`SELECT ${INFO_COLUMNS} FROM attachments ORDER BY created_at DESC LIMIT ?`)
+    .all(Math.min(5000, Number(limit) || 500)).map(mapInfoRow);
+
+const deleteAttachment = (db, id) => db.prepare('DELETE FROM attachments WHERE id = ?').run(id).changes > 0;
+
+// Ids referenced from sticky notes (archived ones included) and symbol dossiers
+const referencedIds = (db) => {
+    const ids = new Set();
+    const re = new RegExp(`${SCHEME}://([0-9a-f-]{36})`, 'gi');
+    db.prepare(`SELECT text FROM sticky_notes WHERE text LIKE '%${SCHEME}://%'`).all().forEach((row) => {
+        for (const m of row.text.matchAll(re)) ids.add(m[1].toLowerCase());
+    });
+    // Dossiers list attachment ids and may embed refs in the thesis
+    db.prepare('SELECT data FROM symbol_dossiers').all().forEach((row) => {
+        const dossier = JSON.parse(row.data);
+        (dossier.attachments || []).forEach(a => ids.add(a.id));
+        for (const m of String(dossier.thesis || '').matchAll(re)) ids.add(m[1].toLowerCase());
+    });
+    return ids;
+};
+
+// Deletes unreferenced attachments older than the grace period; returns { deleted, bytes }
+const pruneAttachments = (db, now = Date.now()) => {
+    const used = referencedIds(db);
+    const stale = db.prepare('SELECT id, bytes FROM attachments WHERE created_at < ?').all(now - PRUNE_GRACE_MS).filter(row => !used.has(row.id));
+    db.transaction(() => stale.forEach(row => deleteAttachment(db, row.id)))();
+    return { deleted: stale.length, bytes: stale.reduce((n, row) => n + row.bytes, 0) };
+};
+
+module.exports = { SCHEME, initializeAttachmentTable, attachmentRef, saveAttachment, getAttachmentInfo, readAttachment, listAttachments, deleteAttachment, pruneAttachments };
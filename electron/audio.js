@@ -0,0 +1,133 @@
+
+const path = require('path');
+const fs = require('fs');
+const { spawn } = require('child_process');
+
+// --- AUDIO ALERTS ---
+// Plays sounds from the main process through the OS player, so alerts ring
+// even when the window is hidden and the renderer's AudioContext is suspended.
+// Built-in sounds are synthesised to WAV on first use (no binary assets in the
+// repo); custom sounds are .wav files copied into userData/sounds.
+
+const SAMPLE_RATE = 22050;
+
+// id -> [frequencyHz, durationMs][] (0 Hz = silence)
+const BUILTIN_SOUNDS = {
+    chime: [[880, 120], [0, 40], [1320, 220]],
+    beep: [[1000, 180]],
+    alarm: [[950, 150], [0, 60], [700, 150], [0, 60], [950, 150], [0, 60], [700, 150]],
+    soft: [[523, 160], [660, 160], [784, 260]]
+};
+
+const DEFAULT_SOUND = 'chime';
+
+const synthesizeWav = (segments) => {
+    const samples = [];
+    segments.forEach(([freq, ms]) => {
+        const count = Math.floor(SAMPLE_RATE * ms / 1000);
+        for (let i = 0; i < count; i++) {
+            // Short linear fade at both ends avoids clicks between tones
+            const envelope = Math.min(1, i / 200, (count - i) / 200);
+            samples.push(freq ? Math.sin(2 * Math.PI * freq * i / SAMPLE_RATE) * 0.5 * envelope : 0);
+        }
+    });
+    const data = Buffer.alloc(samples.length * 2);
+    samples.forEach((s, i) => data.writeInt16LE(Math.round(s * 32767), i * 2));
+
+    const header = Buffer.alloc(44);
+    header.write('RIFF', 0);
+    header.writeUInt32LE(36 + data.length, 4);
+    header.write('WAVE', 8);
+    header.write('fmt ', 12);
+    header.writeUInt32LE(16, 16);
+    header.writeUInt16LE(1, 20);                // PCM
+    header.writeUInt16LE(1, 22);                // mono
+    header.writeUInt32LE(SAMPLE_RATE, 24);
+    header.writeUInt32LE(SAMPLE_RATE * 2, 28);  // byte rate
+    header.writeUInt16LE(2, 32);                // block align
+    header.writeUInt16LE(16, 34);               // bits per sample
+    header.write('data', 36);
+    header.writeUInt32LE(data.length, 40);
+    return Buffer.concat([header, data]);
+};
+
+const playerCommand = (filePath) => {
+    switch (process.platform) {
+        case 'win32':
+            return ['powershell', ['-NoProfile', '-NonInteractive', '-Command', `(New-Object Media.SoundPlayer '${filePath.replace(/'/g, "''")}').PlaySync()`]];
+        case 'darwin':
+            return ['afplay', [filePath]];
+        default:
+            return ['paplay', [filePath]];
+    }
+};
+
+const createAudioService = ({ soundsDir, onLog }) => {
+    const builtinDir = path.join(soundsDir, 'builtin');
+
+    const ensureBuiltin = (id) => {
+        const file = path.join(builtinDir, `${id}.wav`);
+        if (!fs.existsSync(file)) {
+            fs.mkdirSync(builtinDir, { recursive: true });
+            fs.writeFileSync(file, synthesizeWav(BUILTIN_SOUNDS[id]));
+        }
+        return file;
+    };
+
+    const customPath = (id) => path.join(soundsDir, `${id}.wav`);
+
+    const resolveSound = (soundId = DEFAULT_SOUND) => {
+        if (BUILTIN_SOUNDS[soundId]) return ensureBuiltin(soundId);
+        if (!/^[\w-]+$/.test(soundId)) throw new Error(`Invalid sound id: ${soundId}`);
+        const file = customPath(soundId);
+        if (!fs.existsSync(file)) throw new Error(`Sound not found: ${soundId}`);
+        return file;
+    };
+
+    const spawnPlayer = (file, [cmd, args]) => new Promise((resolve, reject) => {
+        const child = spawn(cmd, args, { stdio: 'ignore', windowsHide: true });
+        child.on('error', reject);
+        child.on('exit', (code) => (code === 0 ? resolve() : reject(new Error(`${cmd} exited with code ${code}`))));
+    });
+
+    return {
+        playSound: async (soundId) => {
+            const file = resolveSound(soundId || DEFAULT_SOUND);
+            try {
+                await spawnPlayer(file, playerCommand(file));
+            } catch (err) {
+                // Not every Linux desktop has PulseAudio/PipeWire; fall back to ALSA
+                if (process.platform === 'linux') return spawnPlayer(file, ['aplay', ['-q', file]]);
+                if (onLog) onLog('WARN', 'AUDIO_PLAYBACK_FAILED', { soundId, error: err.message });
+                throw err;
+            }
+        },
+
+        listSounds: () => {
+            const builtin = Object.keys(BUILTIN_SOUNDS).map(id => ({ id, name: id, builtin: true }));
+            const custom = fs.existsSync(soundsDir)
+                ? fs.readdirSync(soundsDir).filter(f => f.toLowerCase().endsWith('.wav')).map(f => ({ id: path.basename(f, '.wav'), name: path.basename(f, '.wav'), builtin: false }))
+                : [];
+            return [...builtin, ...custom];
+        },
+
+        importSound: (filePath) => {
+            if (path.extname(filePath).toLowerCase() !== '.wav') throw new Error('Custom sounds must be .wav files');
+            const id = path.basename(filePath, path.extname(filePath)).replace(/[^\w-]+/g, '_').slice(0, 64);
+            if (BUILTIN_SOUNDS[id]) throw new Error(`"${id}" is a built-in sound name`);
+            fs.mkdirSync(soundsDir, { recursive: true });
+            fs.copyFileSync(filePath, customPath(id));
+            return { id, name: id, builtin: false };
+        },
+
+        deleteSound: (soundId) => {
+            if (BUILTIN_SOUNDS[soundId] || !/^[\w-]+$/.test(soundId)) return false;
+            const file = customPath(soundId);
+            if (!fs.existsSync(file)) return false;
+            fs.unlinkSync(file);
+            return true;
+        }
+    };
+};
+
+module.exports = { DEFAULT_SOUND, createAudioService };
@@ -0,0 +1,128 @@
+
+// --- BACKGROUND JOBS ---
+// Opportunistic work (time indexes for big files, annotation index repairs,
+// the missing-thumbnail sweep) goes through one in-memory queue instead of
+// starting wherever it was noticed. Jobs run by priority ('high' > 'normal' >
+// 'low', oldest first within a priority) under a total concurrency limit and
+// optional per-type limits. Before each start the queue asks shouldPause();
+// a reason ('user-active', 'user-away', 'power-saver', ...) holds every
+// pending job and the queue looks again after PAUSE_RECHECK_MS. Running jobs
+// are not interrupted. A job is identified by type + key: enqueueing one that
+// is already pending only raises its priority. Nothing is persisted — whatever
+// is still pending at quit is noticed again on the next run.
+
+const PRIORITIES = { high: 0, normal: 1, low: 2 };
+const PAUSE_RECHECK_MS = 5000;
+const MAX_RECENT = 50;
+
+/**
+ * handlers: { [type]: async (params) -> summary? }
+ * concurrency: { total, perType: { [type]: n } }
+ * onJobFinished(job) is called for done and failed jobs.
+ */
+const createBackgroundQueue = ({ handlers, concurrency = {}, shouldPause = () => null, onJobFinished = () => {} }) => {
+    const limits = { total: concurrency.total || 2, perType: concurrency.perType || {} };
+    const pending = [];
+    const running = new Map(); // id -> job
+    const recent = [];
+    let nextId = 1;
+    let timer = null;
+    let pauseReason = null;
+    let stopped = false;
+
+    const publicJob = (job) => {
+        const { params, ...rest } = job;
+        return rest;
+    };
+
+    const byPriority = (a, b) => PRIORITIES[a.priority] - PRIORITIES[b.priority] || a.queuedAt - b.queuedAt;
+
+    const runningOfType = (type) => Array.from(running.values()).filter(j => j.type === type).length;
+
+    const schedule = (delayMs) => {
+        if (timer || stopped) return;
+        timer = setTimeout(() => { timer = null; pump(); }, delayMs);
+    };
+
+    const finish = (job, status, message) => {
+        running.delete(job.id);
+        Object.assign(job, { status, message: message == null ? null : String(message), finishedAt: Date.now() });
+        recent.unshift(job);
+        recent.length = Math.min(recent.length, MAX_RECENT);
+        onJobFinished(publicJob(job));
+        pump();
+    };
+
+    const pump = () => {
+        if (stopped || !pending.length) return;
+        pauseReason = shouldPause() || null;
+        if (pauseReason) {
+            schedule(PAUSE_RECHECK_MS);
+            return;
+        }
+        pending.sort(byPriority);
+        for (let i = 0; i < pending.length && running.size < limits.total;) {
+            const job = pending[i];
+            const typeLimit = limits.perType[job.type];
+            if (typeLimit && runningOfType(job.type) >= typeLimit) { i++; continue; }
+            pending.splice(i, 1);
+            Object.assign(job, { status: 'running', startedAt: Date.now() });
+            running.set(job.id, job);
+            Promise.resolve()
+                .then(() => handlers[job.type](job.params))
+                .then(summary => finish(job, 'done', summary), err => finish(job, 'failed', err && err.message ? err.message : err));
+        }
+    };
+
+    /**
+     * job: { type, key?, priority?: 'high' | 'normal' | 'low', label?, params? }
+     */
+    const enqueue = ({ type, key = '', priority = 'normal', label = null, params = {} }) => {
+        if (!handlers[type]) throw new Error(`Unknown background job type: ${type}`);
+        if (!(priority in PRIORITIES)) throw new Error(`Unknown priority: ${priority}`);
+        const jobKey = String(key);
+        const runningMatch = Array.from(running.values()).find(j => j.type === type && j.key === jobKey);
+        if (runningMatch) return publicJob(runningMatch);
+        const existing = pending.find(j => j.type === type && j.key === jobKey);
+        if (existing) {
+            if (PRIORITIES[priority] < PRIORITIES[existing.priority]) existing.priority = priority;
+            return publicJob(existing);
+        }
+        const job = { id: nextId++, type, key: jobKey, priority, label: label || (jobKey ? `${type} ${jobKey}` : type), params, status: 'pending', queuedAt: Date.now(), startedAt: null, finishedAt: null, message: null };
+        pending.push(job);
+        // Start on the next tick so a burst of enqueues is ordered by priority first
+        schedule(0);
+        return publicJob(job);
+    };
+
+    const cancel = (id) => {
+        const index = pending.findIndex(j => j.id === Number(id));
+        if (index === -1) return false;
+        const [job] = pending.splice(index, 1);
+        Object.assign(job, { status: 'canceled', finishedAt: Date.now() });
+        recent.unshift(job);
+        recent.length = Math.min(recent.length, MAX_RECENT);
+        return true;
+    };
+
+    const list = () => ({
+        paused: !!pauseReason && pending.length > 0,
+        pauseReason: pending.length ? pauseReason : null,
+        concurrency: { total: limits.total, perType: { ...limits.perType } },
+        running: Array.from(running.values()).map(publicJob),
+        pending: pending.slice().sort(byPriority).map(publicJob),
+        recent: recent.map(publicJob)
+    });
+
+    // Drops pending jobs; running ones finish on their own
+    const stop = () => {
+        stopped = true;
+        if (timer) clearTimeout(timer);
+        timer = null;
+        pending.length = 0;
+    };
+
+    return { enqueue, cancel, list, stop, pump };
+};
+
+module.exports = { PRIORITIES, createBackgroundQueue };
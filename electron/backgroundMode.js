@@ -0,0 +1,128 @@
+
+const { Tray, Menu, nativeImage } = require('electron');
+
+// --- BACKGROUND MODE ---
+// Keeps the app useful with no visible window: closed live bars are recorded
+// into the bar store by the main process (not the chart), and a tray icon shows
+// what is still running. Feeds and alerts already live in the main process, so
+// hiding or closing windows never stops them; this module only adds the
+// recorder and the tray.
+
+const FLUSH_INTERVAL_MS = 15000;
+
+/**
+ * Buffers closed live bars and writes them in batches.
+ * persist(provider, symbol, timeframe, bars) does the actual write.
+ */
+const createBarRecorder = ({ persist, onError = () => {} }) => {
+    const buffers = new Map(); // 'provider|symbol|timeframe' -> bar[]
+    const streams = new Map(); // same key -> { provider, symbol, timeframe, recorded, lastBarAt }
+    let enabled = true;
+    let totalRecorded = 0;
+    let lastFlushAt = null;
+
+    const handleBar = ({ provider, symbol, timeframe, bar, isClosed }) => {
+        if (!enabled || !isClosed || !bar || bar.timestamp == null) return;
+        const key = `${provider}|${symbol}|${timeframe}`;
+        if (!buffers.has(key)) buffers.set(key, []);
+        buffers.get(key).push(bar);
+        if (!streams.has(key)) streams.set(key, { provider, symbol, timeframe, recorded: 0, lastBarAt: null });
+        streams.get(key).lastBarAt = bar.timestamp;
+    };
+
+    const flush = () => {
+        buffers.forEach((bars, key) => {
+            if (bars.length === 0) return;
+            const stream = streams.get(key);
+            buffers.set(key, []);
+            try {
+                persist(stream.provider, stream.symbol, stream.timeframe, bars);
+                stream.recorded += bars.length;
+                totalRecorded += bars.length;
+            } catch (err) {
+                onError(stream, err);
+            }
+        });
+        lastFlushAt = Date.now();
+    };
+
+    const timer = setInterval(flush, FLUSH_INTERVAL_MS);
+
+    const setEnabled = (value) => {
+        if (!value) flush();
+        enabled = !!value;
+    };
+
+    const status = () => ({
+        enabled,
+        streams: Array.from(streams.values()),
+        barsRecorded: totalRecorded,
+        buffered: Array.from(buffers.values()).reduce((n, b) => n + b.length, 0),
+        lastFlushAt
+    });
+
+    const stop = () => { clearInterval(timer); flush(); };
+
+    return { handleBar, flush, setEnabled, status, stop };
+};
+
+// 16x16 red disc, so the tray works without a bundled icon file
+const createTrayIcon = () => {
+    const size = 16;
+    const bitmap = Buffer.alloc(size * size * 4);
+    for (let y = 0; y < size; y++) {
+        for (let x = 0; x < size; x++) {
+            const d = Math.hypot(x - 7.5, y - 7.5);
+            const alpha = Math.max(0, Math.min(1, 7.5 - d));
+            const o = (y * size + x) * 4;
+            bitmap[o] = 0x44; bitmap[o + 1] = 0x44; bitmap[o + 2] = 0xef; // BGRA
+            bitmap[o + 3] = Math.round(alpha * 255);
+        }
+    }
+    return nativeImage.createFromBitmap(bitmap, { width: size, height: size });
+};
+
+/**
+ * getStatus() -> background status (see main.js); menu callbacks act on the app.
+ */
+const createTrayController = ({ getStatus, onShow, onTogglePause, onQuit }) => {
+    let tray = null;
+
+    const describe = (status) => {
+        const parts = [];
+        parts.push(status.liveFeed.paused ? 'Feeds paused' : `${status.liveFeed.subscriptions} live stream(s)`);
+        if (status.recording.enabled) parts.push(`recording ${status.recording.streams.length}`);
+        parts.push(`${status.alerts.armed} alert(s) armed`);
+        return `Red Pill Charting — ${parts.join(' · ')}`;
+    };
+
+    const refresh = () => {
+        if (!tray) return;
+        const status = getStatus();
+        tray.setToolTip(describe(status));
+        tray.setContextMenu(Menu.buildFromTemplate([
+            { label: 'Show Red Pill', click: onShow },
+            { type: 'separator' },
+            { label: describe(status).replace(/^Red Pill Charting — /, ''), enabled: false },
+            { label: status.liveFeed.paused ? 'Resume live feeds' : 'Pause live feeds', click: () => { onTogglePause(); refresh(); } },
+            { type: 'separator' },
+            { label: 'Quit', click: onQuit }
+        ]));
+    };
+
+    const show = (icon = null) => {
+        if (tray && !tray.isDestroyed()) return refresh();
+        tray = new Tray(icon && !icon.isEmpty() ? icon : createTrayIcon());
+        tray.on('click', onShow);
+        refresh();
+    };
+
+    const destroy = () => {
+        if (tray && !tray.isDestroyed()) tray.destroy();
+        tray = null;
+    };
+
+    return { show, refresh, destroy, isActive: () => !!tray && !tray.isDestroyed() };
+};
+
+module.exports = { createBarRecorder, createTrayController };
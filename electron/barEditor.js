@@ -0,0 +1,174 @@
+
+const { AppError } = require('./appErrors');
+const { datasetId, parseDatasetId, getDataset } = require('./datasets');
+
+// --- BAR EDITOR ---
+// Fixes bad prints in the bar store one bar at a time. Every edit and delete
+// is recorded in `bar_edits` with the bar as it was and as it became, so the
+// original values survive and an edit can be reverted. A revert only applies
+// while the bar still looks the way the edit left it; after a later edit, or a
+// re-import that replaced it, it fails with CONFLICT instead of clobbering
+// newer data. Derived series (continuous, merged, daily roll-ups) are not
+// edited directly — they are rebuilt from their sources, so edits there are
+// refused; main.js rebuilds them after a source bar changes.
+
+const FIELDS = ['open', 'high', 'low', 'close', 'volume'];
+const DERIVED_SOURCES = ['continuous', 'merged', 'rollup'];
+const MAX_REASON_LENGTH = 500;
+const DEFAULT_LIST_LIMIT = 200;
+
+const initializeBarEditTable = (db) => {
+    db.exec(`
+        CREATE TABLE IF NOT EXISTS bar_edits (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            dataset_id TEXT,
+            timestamp INTEGER,
+            action TEXT,
+            before TEXT,
+            after TEXT,
+            reason TEXT,
+            created_at INTEGER,
+            reverted_at INTEGER,
+            revert_of INTEGER
+        );
+        CREATE INDEX IF NOT EXISTS idx_bar_edits_dataset ON bar_edits (dataset_id, timestamp);
+    `);
+};
+
+const mapEditRow = (row) => (row ? {
+    id: row.id,
+    datasetId: row.dataset_id,
+    timestamp: row.timestamp,
+    action: row.action,
+    before: row.before ? JSON.parse(row.before) : null,
+    after: row.after ? JSON.parse(row.after) : null,
+    reason: row.reason,
+    createdAt: row.created_at,
+    revertedAt: row.reverted_at,
+    revertOf: row.revert_of
+} : null);
+
+const editableDataset = (db, id) => {
+    const dataset = getDataset(db, id);
+    if (!dataset) throw new AppError('NOT_FOUND', `Dataset not found: ${id}`);
+    if (DERIVED_SOURCES.includes(dataset.source)) throw new AppError('UNSUPPORTED', `${id} is built from other datasets (${dataset.source}); edit its sources instead`);
+    return dataset;
+};
+
+const readBar = (db, id, timestamp) => {
+    const { symbol, timeframe } = parseDatasetId(id);
+    return db.prepare('SELECT open, high, low, close, volume FROM market_data WHERE symbol = ? AND timeframe = ? AND timestamp = ?').get(symbol, timeframe, timestamp) || null;
+};
+
+const writeBar = (db, id, timestamp, bar) => {
+    const { symbol, timeframe } = parseDatasetId(id);
+    if (!bar) {
+        db.prepare('DELETE FROM market_data WHERE symbol = ? AND timeframe = ? AND timestamp = ?').run(symbol, timeframe, timestamp);
+        return;
+    }
+    db.prepare('INSERT OR REPLACE INTO market_data (symbol, timeframe, timestamp, open, high, low, close, volume) VALUES (?, ?, ?, ?, ?, ?, ?, ?)')
+        .run(symbol, timeframe, timestamp, bar.open, bar.high, bar.low, bar.close, bar.volume || 0);
+};
+
+const sameBar = (a, b) => (!a && !b) || (!!a && !!b && FIELDS.every(f => Number(a[f] || 0) === Number(b[f] || 0)));
+
+const cleanReason = reason => (reason == null ? null : String(reason).slice(0, MAX_REASON_LENGTH));
+
+const record = (db, { id, timestamp, action, before, after, reason, revertOf = null }) => {
+    const info = db.prepare('INSERT INTO bar_edits (dataset_id, timestamp, action, before, after, reason, created_at, revert_of) VALUES (?, ?, ?, ?, ?, ?, ?, ?)')
+        .run(id, timestamp, action, before ? JSON.stringify(before) : null, after ? JSON.stringify(after) : null, reason, Date.now(), revertOf);
+    return mapEditRow(db.prepare('SELECT * FROM bar_edits WHERE id = ?').get(info.lastInsertRowid));
+};
+
+/**
+ * changes: any of { open, high, low, close, volume }. The edited bar must stay
+ * a bar (low <= open, close <= high). Returns { edit, bar }.
+ */
+const editBar = (db, id, timestamp, changes = {}, { reason = null } = {}) => {
+    editableDataset(db, id);
+    const ts = Number(timestamp);
+    const before = readBar(db, id, ts);
+    if (!before) throw new AppError('NOT_FOUND', `No bar at ${new Date(ts).toISOString()} in ${id}`);
+    const unknown = Object.keys(changes || {}).filter(k => !FIELDS.includes(k));
+    if (unknown.length) throw new AppError('INVALID_INPUT', `Unknown bar field: ${unknown.join(', ')}`);
+    const after = { ...before };
+    Object.entries(changes || {}).forEach(([field, value]) => {
+        const n = Number(value);
+        if (!Number.isFinite(n) || (field === 'volume' && n < 0)) throw new AppError('INVALID_INPUT', `${field} must be a ${field === 'volume' ? 'non-negative ' : ''}number`);
+        after[field] = n;
+    });
+    if (after.low > Math.min(after.open, after.close) || after.high < Math.max(after.open, after.close) || after.low > after.high) {
+        throw new AppError('INVALID_INPUT', 'The bar must satisfy low <= open, close <= high');
+    }
+    if (sameBar(before, after)) throw new AppError('INVALID_INPUT', 'Nothing to change');
+    let edit;
+    db.transaction(() => {
+        writeBar(db, id, ts, after);
+        edit = record(db, { id, timestamp: ts, action: 'edit', before, after, reason: cleanReason(reason) });
+    })();
+    return { edit, bar: { timestamp: ts, ...after } };
+};
+
+const deleteBar = (db, id, timestamp, { reason = null } = {}) => {
+    editableDataset(db, id);
+    const ts = Number(timestamp);
+    const before = readBar(db, id, ts);
+    if (!before) throw new AppError('NOT_FOUND', `No bar at ${new Date(ts).toISOString()} in ${id}`);
+    let edit;
+    db.transaction(() => {
+        writeBar(db, id, ts, null);
+        edit = record(db, { id, timestamp: ts, action: 'delete', before, after: null, reason: cleanReason(reason) });
+    })();
+    return { edit };
+};
+
+/**
+ * Puts the bar back the way it was before edit `editId`. Returns { edit (the
+ * revert record), reverted (the original record), bar | null }.
+ */
+const revertBarEdit = (db, editId, { reason = null } = {}) => {
+    const original = mapEditRow(db.prepare('SELECT * FROM bar_edits WHERE id = ?').get(Number(editId)));
+    if (!original) throw new AppError('NOT_FOUND', `Bar edit not found: ${editId}`);
+    if (original.action === 'revert') throw new AppError('INVALID_INPUT', 'A revert can\'t be reverted; edit the bar again instead');
+    if (original.revertedAt) throw new AppError('CONFLICT', `Edit ${editId} was already reverted`);
+    editableDataset(db, original.datasetId);
+    const current = readBar(db, original.datasetId, original.timestamp);
+    if (!sameBar(current, original.after)) throw new AppError('CONFLICT', 'The bar changed after this edit; revert the later edits first', { current });
+    let edit;
+    db.transaction(() => {
+        writeBar(db, original.datasetId, original.timestamp, original.before);
+        edit = record(db, { id: original.datasetId, timestamp: original.timestamp, action: 'revert', before: current, after: original.before, reason: cleanReason(reason), revertOf: original.id });
+        db.prepare('UPDATE bar_edits SET reverted_at = ? WHERE id = ?').run(edit.createdAt, original.id);
+    })();
+    return { edit, reverted: { ...original, revertedAt: edit.createdAt }, bar: original.before ? { timestamp: original.timestamp, ...original.before } : null };
+};
+
+/**
+ * options: { datasetId?, from?, to?, limit? } -> edits, newest first.
+ */
+const listBarEdits = (db, { datasetId: id = null, from = null, to = null, limit = DEFAULT_LIST_LIMIT } = {}) => {
+    const where = [];
+    const params = [];
+    if (id) { where.push('dataset_id = ?'); params.push(id); }
+    if (from != null) { where.push('timestamp >= ?'); params.push(Number(from)); }
+    if (to != null) { where.push('timestamp <= ?'); params.push(Number(to)); }
+    params.push(Math.max(1, Math.min(5000, Number(limit) || DEFAULT_LIST_LIMIT)));
+    return db.prepare(`SELECT * FROM bar_edits${where.length ? ` WHERE ${where.join(' AND ')}` : ''} ORDER BY id DESC LIMIT ?`).all(...params).map(mapEditRow);
+};
+
+// Derived datasets built from `id`: continuous series and merges that list it,
+// and the symbol's rolled-up 1D series when `id` is intraday
+const dependentDatasets = (db, id) => {
+    const { symbol, timeframe } = parseDatasetId(id);
+    const daily = datasetId(symbol, '1D');
+    return db.prepare("SELECT id, source, meta FROM datasets WHERE source IN ('continuous', 'merged', 'rollup')").all()
+        .filter((row) => {
+            const meta = row.meta ? JSON.parse(row.meta) : {};
+            if (row.source === 'continuous') return ((meta.continuous && meta.continuous.contracts) || []).some(c => c.datasetId === id);
+            if (row.source === 'merged') return ((meta.merge && meta.merge.datasets) || []).includes(id);
+            return row.id === daily && timeframe !== '1D';
+        })
+        .map(row => ({ id: row.id, source: row.source }));
+};
+
+module.exports = { initializeBarEditTable, editBar, deleteBar, revertBarEdit, listBarEdits, dependentDatasets };
@@ -0,0 +1,359 @@
+
+const crypto = require('crypto');
+const { localToUtc } = require('./sessions');
+
+// --- BROKER STATEMENTS ---
+// Turns a broker's trade statement export into journal trades. Supported:
+//
+//   ibkr      Interactive Brokers Flex Query CSV (Trades section). Execution
+//             rows are used when the query has several levels of detail;
+//             HEADER / DATA prefixed files (with header and trailer) are fine.
+//   generic   any CSV of fills with a header naming time, symbol, side (or a
+//             signed quantity), quantity and price; commission, currency,
+//             multiplier and an execution id are picked up when present.
+//
+// The broker is detected from the header when not given. Fills become trades
+// by FIFO lot matching per symbol: every closing quantity closes the oldest
+// open lot, giving one closed trade (entry, exit, P&L net of commissions);
+// what remains open is imported as an open trade and is closed by a later
+// statement. Each fill has a key (the broker's execution id, else a hash of
+// its fields); fills already imported are skipped, and a trade that looks
+// like one entered by hand (same symbol, side, quantity and price within a
+// minute) is reported as a likely duplicate instead of being added twice.
+
+const BROKERS = ['ibkr', 'generic'];
+const IBKR_TIMEZONE = 'America/New_York'; // Flex reports use the account's zone, New York by default
+const DUPLICATE_WINDOW_MS = 60 * 1000;
+const QTY_EPSILON = 1e-9;
+
+// RFC 4180 rows: quoted cells may hold delimiters, quotes ("") and newlines
+const parseCsv = (text, delimiter = ',') => {
+    const rows = [];
+    let row = [];
+    let cell = '';
+    let quoted = false;
+    for (let i = 0; i < text.length; i++) {
+        const ch = text[i];
+        if (quoted) {
+            if (ch === '"' && text[i + 1] === '"') { cell += '"'; i++; } else if (ch === '"') quoted = false;
+            else cell += ch;
+        } else if (ch === '"') quoted = true;
+        else if (ch === delimiter) { row.push(cell.trim()); cell = ''; } else if (ch === '\n' || ch === '\r') {
+            if (ch === '\r' && text[i + 1] === '\n') i++;
+            row.push(cell.trim());
+            if (row.some(c => c !== '')) rows.push(row);
+            row = [];
+            cell = '';
+        } else cell += ch;
+    }
+    row.push(cell.trim());
+    if (row.some(c => c !== '')) rows.push(row);
+    return rows;
+};
+
+const guessDelimiter = (text) => {
+    const head = text.split(/\r?\n/, 1)[0] || '';
+    return [',', ';', '\t'].map(d => [d, head.split(d).length]).sort((a, b) => b[1] - a[1])[0][0];
+};
+
+const headerKey = name => String(name).toLowerCase().replace(/[^a-z0-9]/g, '');
+
+// generic columns: field -> accepted header names (normalized)
+const GENERIC_COLUMNS = {
+    time: ['datetime', 'time', 'timestamp', 'date', 'executiontime', 'filltime', 'tradetime', 'tradedate'],
+    clock: ['clock', 'timeofday'],
+    symbol: ['symbol', 'ticker', 'instrument', 'contract', 'underlying'],
+    side: ['side', 'action', 'buysell', 'direction', 'type'],
+    qty: ['quantity', 'qty', 'shares', 'size', 'filledqty', 'contracts', 'amount'],
+    price: ['price', 'fillprice', 'tradeprice', 'executionprice', 'avgprice'],
+    commission: ['commission', 'commissions', 'fee', 'fees', 'comm'],
+    currency: ['currency', 'ccy'],
+    multiplier: ['multiplier', 'pointvalue', 'contractmultiplier'],
+    id: ['id', 'fillid', 'executionid', 'execid', 'tradeid', 'dealid', 'orderid']
+};
+
+const IBKR_MARKERS = ['tradeprice', 'buysell', 'ibcommission', 'ibexecid', 'clientaccountid', 'fifopnlrealized'];
+
+const locateColumns = (header) => {
+    const keys = header.map(headerKey);
+    const columns = {};
+    Object.entries(GENERIC_COLUMNS).forEach(([field, names]) => {
+        const at = names.map(n => keys.indexOf(n)).find(i => i >= 0);
+        if (at !== undefined) columns[field] = at;
+    });
+    // Separate date and time-of-day columns
+    const date = ['date', 'tradedate'].map(n => keys.indexOf(n)).find(i => i >= 0);
+    const clock = keys.indexOf('time');
+    if (date !== undefined && clock >= 0 && columns.clock === undefined) {
+        columns.time = date;
+        columns.clock = clock;
+    }
+    return columns;
+};
+
+// Strips the HEADER / DATA / BOF prefixes of Flex files exported with header and trailer
+const unwrapFlexRows = rows => rows
+    .filter(r => !['BOF', 'EOF', 'BOA', 'EOA', 'BOS', 'EOS', 'TRAILER'].includes(r[0]))
+    .map(r => (r[0] === 'HEADER' || r[0] === 'DATA' ? r.slice(2) : r));
+
+/**
+ * The broker format of statement `text`: 'ibkr', 'generic' or null when the
+ * header isn't recognized.
+ */
+const detectBroker = (text) => {
+    const rows = unwrapFlexRows(parseCsv(String(text).slice(0, 64 * 1024), guessDelimiter(text))).slice(0, 20);
+    for (const row of rows) {
+        const keys = row.map(headerKey);
+        if (IBKR_MARKERS.filter(m => keys.includes(m)).length >= 2) return 'ibkr';
+        const cols = locateColumns(row);
+        if (cols.time !== undefined && cols.symbol !== undefined && cols.qty !== undefined && cols.price !== undefined) return 'generic';
+    }
+    return null;
+};
+
+const toNumber = (text) => {
+    if (text == null || text === '' || text === '--') return null;
+    const n = Number(String(text).replace(/[,\s$]/g, ''));
+    return Number.isFinite(n) ? n : null;
+};
+
+// IBKR: "20240115;093012", "20240115 093012", "2024-01-15, 09:30:12", "20240115"; generic: also ISO with a zone
+const parseTime = (text, clockText, timezone) => {
+    const raw = `${String(text || '').trim()}${clockText ? ` ${clockText}` : ''}`;
+    if (/[zZ]$|[+-]\d{2}:?\d{2}$/.test(raw) && !Number.isNaN(Date.parse(raw))) return Date.parse(raw);
+    const m = /^(\d{4})-?(\d{2})-?(\d{2})(?:[T;,\s]+(\d{1,2}):?(\d{2}):?(\d{2})?(?:\.\d+)?)?$/.exec(raw.replace(/\s+/g, ' '));
+    if (!m) {
+        const epoch = toNumber(raw);
+        if (epoch != null) return epoch < 1e11 ? epoch * 1000 : epoch;
+        return null;
+    }
+    const dayUtc = Date.UTC(Number(m[1]), Number(m[2]) - 1, Number(m[3]));
+    const minutes = (Number(m[4] || 0) * 60) + Number(m[5] || 0) + (Number(m[6] || 0) / 60);
+    return localToUtc(dayUtc, minutes, timezone);
+};
+
+const sideOf = (text, qty) => {
+    const s = String(text || '').toLowerCase();
+    if (/^(buy|b|bot|long|cover)/.test(s)) return 'buy';
+    if (/^(sell|s|sld|short)/.test(s)) return 'sell';
+    if (qty != null && qty !== 0) return qty > 0 ? 'buy' : 'sell';
+    return null;
+};
+
+const fillKey = (broker, fill) => (fill.execId
+    ? `${broker}:${fill.execId}`
+    : `${broker}:${crypto.createHash('sha1').update([fill.timestamp, fill.symbol, fill.side, fill.qty, fill.price].join('|')).digest('hex').slice(0, 16)}`);
+
+/**
+ * Fills of a statement. options: { broker? ('auto'), timezone? (zone of
+ * times without one; New York for ibkr, UTC otherwise) }. Returns
+ * { broker, fills: [{ key, execId, timestamp, symbol, side, qty, price,
+ * commission, currency, multiplier, pnl }], skipped: [{ line, reason }] }.
+ */
+const parseStatement = (text, { broker = 'auto', timezone = null } = {}) => {
+    const content = String(text || '').replace(/^\uFEFF/, '');
+    const format = !broker || broker === 'auto' ? detectBroker(content) : broker;
+    if (!format) throw new Error('Unrecognized statement format; choose the broker explicitly');
+    if (!BROKERS.includes(format)) throw new Error(`broker must be one of ${BROKERS.join(', ')}`);
+    const zone = timezone || (format === 'ibkr' ? IBKR_TIMEZONE : 'UTC');
+    const rows = parseCsv(content, guessDelimiter(content));
+    const fills = [];
+    const skipped = [];
+    let header = null;
+    let columns = null;
+    let ibkr = null;
+
+    (format === 'ibkr' ? unwrapFlexRows(rows) : rows).forEach((row, index) => {
+        const keys = row.map(headerKey);
+        if (format === 'ibkr' && keys.includes('tradeprice') && keys.includes('symbol')) {
+            header = keys;
+            ibkr = name => header.indexOf(name);
+            return;
+        }
+        if (format === 'generic' && !header) {
+            const cols = locateColumns(row);
+            if (cols.time !== undefined && cols.symbol !== undefined && cols.qty !== undefined && cols.price !== undefined) {
+                header = keys;
+                columns = cols;
+            }
+            return;
+        }
+        if (!header) return;
+        if (format === 'ibkr' && keys.join('') === header.join('')) return; // repeated header of another account
+
+        let fill;
+        if (format === 'ibkr') {
+            const cell = name => (ibkr(name) >= 0 ? row[ibkr(name)] : undefined);
+            const level = String(cell('levelofdetail') || 'EXECUTION').toUpperCase();
+            if (level !== 'EXECUTION' && level !== 'ORDER') return; // closed lots, summaries
+            const rawQty = toNumber(cell('quantity'));
+            const dateTime = cell('datetime') || cell('tradedate');
+            fill = {
+                execId: cell('ibexecid') || cell('tradeid') || null,
+                timestamp: parseTime(dateTime, cell('datetime') ? null : cell('tradetime'), zone),
+                symbol: cell('symbol'),
+                side: sideOf(cell('buysell'), rawQty),
+                qty: rawQty == null ? null : Math.abs(rawQty),
+                price: toNumber(cell('tradeprice')),
+                commission: Math.abs(toNumber(cell('ibcommission')) || 0),
+                currency: cell('currencyprimary') || cell('currency') || null,
+                multiplier: toNumber(cell('multiplier')),
+                pnl: toNumber(cell('fifopnlrealized'))
+            };
+        } else {
+            const cell = field => (columns[field] !== undefined ? row[columns[field]] : undefined);
+            const rawQty = toNumber(cell('qty'));
+            fill = {
+                execId: cell('id') || null,
+                timestamp: parseTime(cell('time'), cell('clock'), zone),
+                symbol: cell('symbol'),
+                side: sideOf(cell('side'), rawQty),
+                qty: rawQty == null ? null : Math.abs(rawQty),
+                price: toNumber(cell('price')),
+                commission: Math.abs(toNumber(cell('commission')) || 0),
+                currency: cell('currency') || null,
+                multiplier: toNumber(cell('multiplier')),
+                pnl: null
+            };
+        }
+        const missing = ['timestamp', 'symbol', 'side', 'qty', 'price'].filter(f => fill[f] == null || fill[f] === '' || Number.isNaN(fill[f]));
+        if (missing.length || !(fill.qty > 0)) {
+            skipped.push({ line: index + 1, reason: missing.length ? `missing ${missing.join(', ')}` : 'zero quantity' });
+            return;
+        }
+        fill.symbol = String(fill.symbol).trim().toUpperCase();
+        fill.key = fillKey(format, fill);
+        fills.push(fill);
+    });
+    if (!header) throw new Error(format === 'ibkr' ? 'No Trades section found in the Flex statement' : 'No header with time, symbol, quantity and price columns');
+    fills.sort((a, b) => a.timestamp - b.timestamp);
+    return { broker: format, fills, skipped };
+};
+
+const tradeId = (broker, keys) => `stmt-${broker}-${crypto.createHash('sha1').update(keys.join(',')).digest('hex').slice(0, 16)}`;
+
+/**
+ * FIFO-matches fills into journal trades. options: { broker, sourceId,
+ * multiplierFor(symbol) -> point value for fills without a multiplier,
+ * openTrades: journal trades still open from earlier imports (they are
+ * closed first, oldest first) }. Returns { trades, updated } — `updated` are
+ * earlier open trades now closed or reduced.
+ */
+const buildTrades = (fills, { broker, sourceId, multiplierFor = () => 1, openTrades = [] }) => {
+    const lots = new Map(); // symbol -> [{ side, qty, price, timestamp, commission, keys, currency, multiplier, tradeId? }]
+    openTrades.slice().sort((a, b) => a.timestamp - b.timestamp).forEach((trade) => {
+        if (!lots.has(trade.symbol)) lots.set(trade.symbol, []);
+        lots.get(trade.symbol).push({
+            side: trade.side, qty: trade.qty, price: trade.price, timestamp: trade.timestamp, commission: trade.commission || 0,
+            keys: (trade.import && trade.import.fills) || [], currency: trade.currency || null, multiplier: trade.multiplier || null, trade
+        });
+    });
+    const trades = [];
+    const updated = new Map();
+
+    const journalTrade = (lot, fields) => ({
+        sourceId: lot.trade ? lot.trade.sourceId : sourceId,
+        symbol: fields.symbol,
+        side: lot.side,
+        type: 'market',
+        price: lot.price,
+        value: lot.price * fields.qty * fields.multiplier,
+        timestamp: lot.timestamp,
+        mode: 'live',
+        currency: lot.currency,
+        multiplier: fields.multiplier,
+        ...fields.extra
+    });
+
+    fills.forEach((fill) => {
+        const multiplier = fill.multiplier || multiplierFor(fill.symbol) || 1;
+        const queue = lots.get(fill.symbol) || [];
+        lots.set(fill.symbol, queue);
+        let remaining = fill.qty;
+        while (remaining > QTY_EPSILON && queue.length && queue[0].side !== fill.side) {
+            const lot = queue[0];
+            const qty = Math.min(lot.qty, remaining);
+            const share = qty / lot.qty;
+            const entryCommission = lot.commission * share;
+            const exitCommission = fill.commission * (qty / fill.qty);
+            const direction = lot.side === 'buy' ? 1 : -1;
+            const keys = [...lot.keys, fill.key];
+            const closed = journalTrade(lot, {
+                symbol: fill.symbol,
+                qty,
+                multiplier: lot.multiplier || multiplier,
+                extra: {
+                    qty,
+                    status: 'filled',
+                    exitPrice: fill.price,
+                    exitTimestamp: fill.timestamp,
+                    commission: entryCommission + exitCommission,
+                    pnl: (fill.price - lot.price) * qty * direction * (lot.multiplier || multiplier) - entryCommission - exitCommission,
+                    currency: lot.currency || fill.currency
+                }
+            });
+            if (lot.trade && qty >= lot.qty - QTY_EPSILON) {
+                // The earlier open trade closes as a whole
+                updated.set(lot.trade.id, { ...lot.trade, ...closed, id: lot.trade.id, import: { ...lot.trade.import, fills: keys } });
+            } else {
+                trades.push({ ...closed, id: tradeId(broker, [...keys, String(qty)]), import: { broker, fills: keys } });
+                if (lot.trade) updated.set(lot.trade.id, { ...lot.trade, qty: lot.qty - qty, value: lot.price * (lot.qty - qty) * (lot.multiplier || multiplier), commission: lot.commission - entryCommission });
+            }
+            lot.qty -= qty;
+            lot.commission -= entryCommission;
+            remaining -= qty;
+            if (lot.qty <= QTY_EPSILON) queue.shift();
+        }
+        if (remaining > QTY_EPSILON) {
+            queue.push({ side: fill.side, qty: remaining, price: fill.price, timestamp: fill.timestamp, commission: fill.commission * (remaining / fill.qty), keys: [fill.key], currency: fill.currency, multiplier });
+        }
+    });
+
+    // Lots still open become open trades
+    lots.forEach((queue, symbol) => queue.filter(lot => !lot.trade).forEach((lot) => {
+        trades.push({
+            ...journalTrade(lot, { symbol, qty: lot.qty, multiplier: lot.multiplier, extra: { qty: lot.qty, status: 'open', commission: lot.commission } }),
+            id: tradeId(broker, lot.keys),
+            import: { broker, fills: lot.keys }
+        });
+    }));
+    return { trades: trades.sort((a, b) => a.timestamp - b.timestamp), updated: Array.from(updated.values()) };
+};
+
+// An existing hand-entered trade this one is probably a copy of
+const findLikelyDuplicate = (trade, existing) => existing.find(other => !other.import
+    && other.symbol && String(other.symbol).toUpperCase() === trade.symbol
+    && other.side === trade.side
+    && Math.abs(Number(other.qty) - trade.qty) <= QTY_EPSILON
+    && Math.abs(Number(other.price) - trade.price) <= 1e-6 * Math.max(1, Math.abs(trade.price))
+    && Math.abs(Number(other.timestamp) - trade.timestamp) <= DUPLICATE_WINDOW_MS);
+
+/**
+ * Plans an import against the journal. existing: all journal trades.
+ * Returns { broker, fills, duplicateFills, trades (new), updated (earlier open
+ * trades now closed), likelyDuplicates: [{ trade, existingId }], skipped }.
+ */
+const planStatementImport = (text, existing, { broker = 'auto', timezone = null, sourceId = null, multiplierFor } = {}) => {
+    const parsed = parseStatement(text, { broker, timezone });
+    const seen = new Set(existing.flatMap(t => (t.import && t.import.fills) || []));
+    const fresh = parsed.fills.filter(f => !seen.has(f.key));
+    const openTrades = existing.filter(t => t.import && t.import.broker === parsed.broker && t.status === 'open');
+    const { trades, updated } = buildTrades(fresh, { broker: parsed.broker, sourceId: sourceId || `statement:${parsed.broker}`, multiplierFor, openTrades });
+    const likelyDuplicates = [];
+    const added = trades.filter((trade) => {
+        const match = findLikelyDuplicate(trade, existing);
+        if (match) likelyDuplicates.push({ trade, existingId: match.id });
+        return !match;
+    });
+    return {
+        broker: parsed.broker,
+        fills: parsed.fills.length,
+        duplicateFills: parsed.fills.length - fresh.length,
+        trades: added,
+        updated,
+        likelyDuplicates,
+        skipped: parsed.skipped
+    };
+};
+
+module.exports = { BROKERS, parseCsv, detectBroker, parseStatement, buildTrades, planStatementImport };
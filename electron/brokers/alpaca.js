@@ -0,0 +1,188 @@
+
+const WebSocket = require('ws');
+const { request } = require('../network');
+const { wsOptions } = require('../proxy');
+
+// --- ALPACA TRADING BROKER ---
+// Orders go through the Alpaca trading API (paper-api / api.alpaca.markets),
+// status updates arrive on its trade_updates stream. Paper is the default;
+// live trading has to be switched on explicitly with configure({ paper: false }).
+// Order writes are sent exactly once (no network-layer retries) and carry a
+// client_order_id, so a timeout never turns into a duplicate order.
+
+const TRADING_URLS = { paper: 'https://paper-api.alpaca.markets', live: 'https://api.alpaca.markets' };
+const STREAM_URLS = { paper: 'wss://paper-api.alpaca.markets/stream', live: 'wss://api.alpaca.markets/stream' };
+
+const ORDER_TYPES = { market: 'market', limit: 'limit', stop: 'stop', stop_limit: 'stop_limit' };
+
+const num = (value) => (value == null || value === '' ? null : Number(value));
+
+const mapOrder = (o) => ({
+    id: o.id,
+    clientOrderId: o.client_order_id,
+    symbol: o.symbol,
+    side: o.side,
+    type: o.type || o.order_type,
+    qty: num(o.qty),
+    filledQty: num(o.filled_qty) || 0,
+    avgFillPrice: num(o.filled_avg_price),
+    limitPrice: num(o.limit_price),
+    stopPrice: num(o.stop_price),
+    timeInForce: o.time_in_force,
+    status: o.status,
+    replacedBy: o.replaced_by || null,
+    submittedAt: o.submitted_at ? Date.parse(o.submitted_at) : null,
+    updatedAt: o.updated_at ? Date.parse(o.updated_at) : Date.now()
+});
+
+/**
+ * `getCredentials()` returns { keyId, secretKey } (the same keys as the data
+ * provider; paper and live accounts have separate keys in Alpaca).
+ */
+const createAlpacaBroker = ({ getCredentials, onLog } = {}) => {
+    const state = { status: 'disconnected', message: null, paper: true };
+    let socket = null;
+    let onUpdate = null;
+
+    const log = (level, message) => onLog && onLog({ level, message });
+    const mode = () => (state.paper ? 'paper' : 'live');
+    const baseUrl = () => TRADING_URLS[mode()];
+
+    const credentials = () => {
+        const creds = getCredentials ? getCredentials() : null;
+        if (!creds || !creds.keyId || !creds.secretKey) throw new Error('Alpaca API keys are not configured');
+        return creds;
+    };
+
+    const headers = () => {
+        const { keyId, secretKey } = credentials();
+        return { 'APCA-API-KEY-ID': keyId, 'APCA-API-SECRET-KEY': secretKey, 'Content-Type': 'application/json' };
+    };
+
+    // Alpaca explains rejections in { code, message }
+    const call = async (method, pathname, body = undefined) => {
+        const init = { method, headers: headers() };
+        if (body !== undefined) init.body = JSON.stringify(body);
+        const response = await request('alpaca', `${baseUrl()}${pathname}`, init, { retries: method === 'GET' ? undefined : 0 });
+        if (response.status === 204) return null;
+        const text = await response.text();
+        const payload = text ? JSON.parse(text) : null;
+        if (!response.ok) throw new Error(`Alpaca ${response.status}: ${(payload && payload.message) || response.statusText}`);
+        return payload;
+    };
+
+    const closeSocket = () => {
+        if (!socket) return;
+        const current = socket;
+        socket = null;
+        current.removeAllListeners('close');
+        current.close();
+    };
+
+    const openSocket = () => {
+        const url = STREAM_URLS[mode()];
+        const current = new WebSocket(url, wsOptions(url, 'alpaca'));
+        socket = current;
+
+        current.on('open', () => {
+            const { keyId, secretKey } = credentials();
+            current.send(JSON.stringify({ action: 'auth', key: keyId, secret: secretKey }));
+        });
+
+        current.on('message', (raw) => {
+            let msg;
+            try { msg = JSON.parse(raw.toString()); } catch (e) { return; }
+            if (msg.stream === 'authorization') {
+                if (msg.data && msg.data.status === 'authorized') {
+                    state.status = 'connected';
+                    state.message = null;
+                    current.send(JSON.stringify({ action: 'listen', data: { streams: ['trade_updates'] } }));
+                } else {
+                    state.status = 'degraded';
+                    state.message = 'Trade update stream rejected the API keys';
+                    log('ERROR', `Alpaca ${mode()} trade updates: authorization failed`);
+                }
+            } else if (msg.stream === 'trade_updates' && msg.data && msg.data.order && onUpdate) {
+                onUpdate({ event: msg.data.event, order: mapOrder(msg.data.order) });
+            }
+        });
+
+        current.on('error', (err) => log('ERROR', `Alpaca trade updates: ${err.message}`));
+        current.on('close', () => {
+            if (socket !== current) return;
+            socket = null;
+            state.status = 'degraded';
+            state.message = 'Trade update stream closed, reconnecting';
+            setTimeout(() => { if (onUpdate && !socket) openSocket(); }, 3000);
+        });
+    };
+
+    return {
+        id: 'alpaca',
+        name: 'Alpaca',
+
+        getStatus: () => ({ status: state.status, mode: mode(), message: state.message }),
+
+        configure: (config = {}) => {
+            if (typeof config.paper === 'boolean' && config.paper !== state.paper) {
+                state.paper = config.paper;
+                // The stream is per account: reconnect to the new one
+                if (onUpdate) {
+                    closeSocket();
+                    openSocket();
+                }
+            }
+            return { paper: state.paper };
+        },
+
+        testCredentials: async () => {
+            const response = await request('alpaca', `${baseUrl()}/v2/account`, { headers: headers() });
+            if (response.status === 401 || response.status === 403) return { valid: false, message: `Alpaca rejected the API keys for the ${mode()} account (${response.status})` };
+            if (!response.ok) throw new Error(`Alpaca API ${response.status}: ${response.statusText}`);
+            return { valid: true, message: null };
+        },
+
+        placeOrder: async (order) => mapOrder(await call('POST', '/v2/orders', {
+            symbol: order.symbol,
+            qty: String(order.qty),
+            side: order.side,
+            type: ORDER_TYPES[order.type],
+            time_in_force: order.timeInForce,
+            limit_price: order.limitPrice != null ? String(order.limitPrice) : undefined,
+            stop_price: order.stopPrice != null ? String(order.stopPrice) : undefined,
+            extended_hours: order.extendedHours || undefined,
+            client_order_id: order.clientOrderId
+        })),
+
+        // Alpaca replaces the order: the response is the new order, the old one becomes 'replaced'
+        modifyOrder: async (id, changes) => mapOrder(await call('PATCH', `/v2/orders/${encodeURIComponent(id)}`, {
+            qty: changes.qty != null ? String(changes.qty) : undefined,
+            limit_price: changes.limitPrice != null ? String(changes.limitPrice) : undefined,
+            stop_price: changes.stopPrice != null ? String(changes.stopPrice) : undefined,
+            time_in_force: changes.timeInForce || undefined
+        })),
+
+        cancelOrder: async (id) => {
+            await call('DELETE', `/v2/orders/${encodeURIComponent(id)}`);
+        },
+
+        listOrders: async ({ status = 'all', limit = 100 } = {}) => {
+            const params = new URLSearchParams({ status, limit: String(limit), direction: 'desc' });
+            return ((await call('GET', `/v2/orders?${params}`)) || []).map(mapOrder);
+        },
+
+        startUpdates: (callback) => {
+            onUpdate = callback;
+            if (!socket) openSocket();
+        },
+
+        stopUpdates: () => {
+            onUpdate = null;
+            closeSocket();
+            state.status = 'disconnected';
+            state.message = null;
+        }
+    };
+};
+
+module.exports = { createAlpacaBroker };
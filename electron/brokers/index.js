@@ -0,0 +1,37 @@
+
+// --- BROKER REGISTRY ---
+// Order routing counterpart of the market data provider registry. Every broker
+// implements the same shape so the order manager (orderRouting.js) can gate and
+// track orders the same way whoever executes them:
+//
+//   id, name
+//   getStatus()                       -> { status, mode: 'paper' | 'live', ... }
+//   configure(config)                 -> config
+//   placeOrder(order)                 -> Promise<brokerOrder>   (order normalized by orderRouting)
+//   modifyOrder(id, changes)          -> Promise<brokerOrder>   (may return a replacement order)
+//   cancelOrder(id)                   -> Promise<void>
+//   listOrders({ status, limit })     -> Promise<brokerOrder[]>
+//   startUpdates(onUpdate)            -> void   (onUpdate({ event, order }) for every status change)
+//   stopUpdates()                     -> void
+//   testCredentials()                 -> Promise<{ valid, message }>
+//
+// brokerOrder: { id, clientOrderId, symbol, side, type, qty, filledQty,
+// avgFillPrice, limitPrice, stopPrice, timeInForce, status, submittedAt, updatedAt }
+
+const brokers = new Map();
+
+const registerBroker = (broker) => {
+    if (!broker || !broker.id) throw new Error('Broker must have an id');
+    brokers.set(broker.id, broker);
+    return broker;
+};
+
+const getBroker = (id) => {
+    const broker = brokers.get(id);
+    if (!broker) throw new Error(`Unknown broker: ${id}`);
+    return broker;
+};
+
+const listBrokers = () => Array.from(brokers.values()).map(b => ({ id: b.id, name: b.name, ...b.getStatus() }));
+
+module.exports = { registerBroker, getBroker, listBrokers };
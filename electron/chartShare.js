@@ -0,0 +1,141 @@
+
+const crypto = require('crypto');
+const zlib = require('zlib');
+const { AppError } = require('./appErrors');
+const { TIMEFRAME_MS, parseDatasetId } = require('./datasets');
+
+// --- CHART SHARING ---
+// A .rpshare file carries everything needed to look at one chart on another
+// machine: the chart state, its thumbnail and a slice of each dataset around
+// the drawings (padBars either side, at most maxBars), so the recipient needs
+// none of the sender's data files. The file is
+//
+//   'RPSHARE\0' | header length (uint32 BE) | header JSON | ciphertext
+//
+// with the gzipped payload encrypted as AES-256-GCM under a scrypt key and
+// the header authenticated with it. With a password the content is private;
+// without one the key comes from an empty password, so the file is only
+// sealed against tampering — anyone with the app can open it. An opened share
+// is handed to the renderer as-is and never written to the database.
+
+const MAGIC = Buffer.from('RPSHARE\0', 'latin1');
+const FORMAT = 'redpill.share';
+const VERSION = 1;
+const KDF = { name: 'scrypt', N: 32768, r: 8, p: 1 };
+const SCRYPT_MAXMEM = 64 * 1024 * 1024;
+const MAX_FILE_BYTES = 64 * 1024 * 1024;
+const MAX_HEADER_BYTES = 4096;
+const DEFAULT_PAD_BARS = 200;
+const DEFAULT_MAX_BARS = 5000;
+const EMPTY_CHART_BARS = 500; // no drawings to frame: the latest bars
+const BAR_COLUMNS = ['timestamp', 'open', 'high', 'low', 'close', 'volume'];
+
+const deriveKey = (password, salt, kdf) => crypto.scryptSync(String(password || ''), salt, 32, { N: kdf.N, r: kdf.r, p: kdf.p, maxmem: SCRYPT_MAXMEM });
+
+// { min, max } over every drawing point, null without any; one pass, since a
+// long brush stroke is too big to spread into Math.min
+const drawingTimeRange = (state) => {
+    let min = Infinity;
+    let max = -Infinity;
+    ((state && state.drawings) || []).forEach((d) => {
+        if (!d || !Array.isArray(d.points)) return;
+        d.points.forEach((p) => {
+            const time = Number(p && p.time);
+            if (!Number.isFinite(time)) return;
+            if (time < min) min = time;
+            if (time > max) max = time;
+        });
+    });
+    return min <= max ? { min, max } : null;
+};
+
+/**
+ * Bars of each dataset around the drawings of `state`. options: { padBars?, maxBars? }.
+ * Returns [{ id, symbol, timeframe, columns, bars: [[timestamp, open, high, low, close, volume]] }],
+ * skipping datasets without bars in range.
+ */
+const collectDatasetSlices = (db, datasetIds, state, { padBars = DEFAULT_PAD_BARS, maxBars = DEFAULT_MAX_BARS } = {}) => {
+    const range = drawingTimeRange(state);
+    return datasetIds.map((id) => {
+        const { symbol, timeframe } = parseDatasetId(id);
+        const step = TIMEFRAME_MS[timeframe];
+        if (!step) throw new Error(`Unknown timeframe: ${timeframe}`);
+        let rows;
+        if (range) {
+            const from = range.min - padBars * step;
+            const to = range.max + padBars * step;
+            rows = db.prepare('SELECT timestamp, open, high, low, close, volume FROM market_data WHERE symbol = ? AND timeframe = ? AND timestamp >= ? AND timestamp <= ? ORDER BY timestamp DESC LIMIT ?')
+                .all(symbol, timeframe, from, to, maxBars);
+        } else {
+            rows = db.prepare('SELECT timestamp, open, high, low, close, volume FROM market_data WHERE symbol = ? AND timeframe = ? ORDER BY timestamp DESC LIMIT ?')
+                .all(symbol, timeframe, Math.min(maxBars, EMPTY_CHART_BARS));
+        }
+        return { id, symbol, timeframe, columns: BAR_COLUMNS, bars: rows.reverse().map(r => BAR_COLUMNS.map(c => r[c])) };
+    }).filter(slice => slice.bars.length > 0);
+};
+
+/**
+ * payload: { sourceId, state, thumbnail?, datasets, appVersion? } -> .rpshare Buffer.
+ */
+const encodeShare = (payload, password = null) => {
+    const salt = crypto.randomBytes(16);
+    const iv = crypto.randomBytes(12);
+    const header = Buffer.from(JSON.stringify({
+        format: FORMAT,
+        version: VERSION,
+        cipher: 'aes-256-gcm',
+        kdf: { ...KDF, salt: salt.toString('base64') },
+        iv: iv.toString('base64'),
+        protected: !!password
+    }), 'utf8');
+    const cipher = crypto.createCipheriv('aes-256-gcm', deriveKey(password, salt, KDF), iv);
+    cipher.setAAD(header);
+    const body = zlib.gzipSync(Buffer.from(JSON.stringify({ format: FORMAT, version: VERSION, createdAt: new Date().toISOString(), ...payload }), 'utf8'));
+    const ciphertext = Buffer.concat([cipher.update(body), cipher.final(), cipher.getAuthTag()]);
+    const length = Buffer.alloc(4);
+    length.writeUInt32BE(header.length);
+    return Buffer.concat([MAGIC, length, header, ciphertext]);
+};
+
+// The plaintext header: whether a password is needed, without decrypting anything
+const readShareHeader = (buffer) => {
+    if (buffer.length > MAX_FILE_BYTES) throw new AppError('INVALID_INPUT', `Share files are limited to ${MAX_FILE_BYTES / 1024 / 1024} MB`);
+    if (buffer.length < MAGIC.length + 4 || !buffer.subarray(0, MAGIC.length).equals(MAGIC)) throw new AppError('CORRUPT_DATA', 'Not a Red Pill share file');
+    const length = buffer.readUInt32BE(MAGIC.length);
+    const start = MAGIC.length + 4;
+    if (length > MAX_HEADER_BYTES || start + length + 16 > buffer.length) throw new AppError('CORRUPT_DATA', 'The share file is truncated or damaged');
+    const raw = buffer.subarray(start, start + length);
+    let header;
+    try { header = JSON.parse(raw.toString('utf8')); } catch (e) { throw new AppError('CORRUPT_DATA', 'The share file header is damaged'); }
+    if (header.format !== FORMAT || header.cipher !== 'aes-256-gcm' || !header.kdf || header.kdf.name !== 'scrypt') throw new AppError('UNSUPPORTED', 'Unrecognized share file format');
+    if (header.version > VERSION) throw new AppError('UNSUPPORTED', `Share file version ${header.version} needs a newer version of the app`);
+    return { header, raw, body: buffer.subarray(start + length) };
+};
+
+/**
+ * .rpshare Buffer -> { protected, payload }. A protected share without a
+ * password, or with the wrong one, throws PERMISSION_DENIED with
+ * details.passwordRequired.
+ */
+const decodeShare = (buffer, password = null) => {
+    const { header, raw, body } = readShareHeader(buffer);
+    if (header.protected && !password) throw new AppError('PERMISSION_DENIED', 'This chart is password-protected', { passwordRequired: true });
+    const { N, r, p } = header.kdf;
+    if (![N, r, p].every(Number.isInteger) || N > KDF.N * 4 || r > 16 || p > 4) throw new AppError('UNSUPPORTED', 'Unsupported key derivation parameters');
+    const decipher = crypto.createDecipheriv('aes-256-gcm', deriveKey(header.protected ? password : null, Buffer.from(header.kdf.salt, 'base64'), { N, r, p }), Buffer.from(header.iv, 'base64'));
+    decipher.setAAD(raw);
+    decipher.setAuthTag(body.subarray(body.length - 16));
+    let plain;
+    try {
+        plain = Buffer.concat([decipher.update(body.subarray(0, body.length - 16)), decipher.final()]);
+    } catch (err) {
+        if (header.protected) throw new AppError('PERMISSION_DENIED', 'Wrong password for this chart', { passwordRequired: true });
+        throw new AppError('CORRUPT_DATA', 'The share file has been damaged or altered');
+    }
+    let payload;
+    try { payload = JSON.parse(zlib.gunzipSync(plain, { maxOutputLength: MAX_FILE_BYTES * 4 }).toString('utf8')); } catch (e) { throw new AppError('CORRUPT_DATA', 'The share file content is damaged'); }
+    if (!payload || payload.format !== FORMAT || !payload.state) throw new AppError('CORRUPT_DATA', 'The share file holds no chart');
+    return { protected: !!header.protected, payload };
+};
+
+module.exports = { FORMAT, collectDatasetSlices, encodeShare, readShareHeader, decodeShare };
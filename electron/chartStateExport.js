@@ -0,0 +1,125 @@
+
+// --- CHART STATE EXPORT ---
+// Converts a saved chart state into files other tools can read.
+//
+// 'redpill' — the open interchange schema (format "redpill.drawings", version 1):
+//   { format, version, exportedAt (ISO-8601), sourceId,
+//     folders: [{ id, name }],
+//     drawings: [{ id, type, folderId | null, timeframe | null,
+//                  points: [{ time (Unix ms, UTC), price }],
+//                  style: { color, lineWidth, lineStyle: solid|dashed|dotted, backgroundColor?, filled? },
+//                  text?: { content, fontSize?, align? }, visible, locked }] }
+//   `type` is one of DRAWING_TYPES; readers should skip types they don't know.
+//
+// 'tradingview' — a drawing export shaped like TradingView's (flat sources[] of
+// LineTool objects, point times in seconds). tradingViewImport.js reads it back.
+
+const SCHEMA_FORMAT = 'redpill.drawings';
+const SCHEMA_VERSION = 1;
+
+// Native type -> TradingView line tool
+const TRADINGVIEW_TYPES = {
+    trend_line: 'LineToolTrendLine',
+    ray: 'LineToolRay',
+    arrow_line: 'LineToolArrow',
+    horizontal_line: 'LineToolHorzLine',
+    horizontal_ray: 'LineToolHorzRay',
+    vertical_line: 'LineToolVertLine',
+    rectangle: 'LineToolRectangle',
+    rotated_rectangle: 'LineToolRotatedRectangle',
+    triangle: 'LineToolTriangle',
+    circle: 'LineToolCircle',
+    brush: 'LineToolBrush',
+    date_range: 'LineToolDateRange',
+    measure: 'LineToolDateAndPriceRange',
+    text: 'LineToolText',
+};
+
+const DRAWING_TYPES = Object.keys(TRADINGVIEW_TYPES);
+const LINE_STYLES = { solid: 0, dotted: 1, dashed: 2 };
+
+const cleanPoints = (points) => (Array.isArray(points) ? points : [])
+    .filter(p => p && isFinite(Number(p.time)) && isFinite(Number(p.price)))
+    .map(p => ({ time: Number(p.time), price: Number(p.price) }));
+
+const toOpenDrawing = (d) => {
+    const props = d.properties || {};
+    const style = { color: props.color || null, lineWidth: props.lineWidth ?? null, lineStyle: props.lineStyle || 'solid' };
+    if (props.backgroundColor) style.backgroundColor = props.backgroundColor;
+    if (props.filled !== undefined) style.filled = !!props.filled;
+    const out = {
+        id: d.id,
+        type: d.type,
+        folderId: d.folderId || null,
+        timeframe: d.creationTimeframe || null,
+        points: cleanPoints(d.points),
+        style,
+        visible: props.visible !== false,
+        locked: !!props.locked,
+    };
+    if (typeof props.text === 'string' && props.text) {
+        out.text = { content: props.text };
+        if (props.fontSize) out.text.fontSize = props.fontSize;
+        if (props.textAlign) out.text.align = props.textAlign;
+    }
+    return out;
+};
+
+const toTradingViewSource = (d) => {
+    const props = d.properties || {};
+    const state = {
+        linecolor: props.color,
+        linewidth: props.lineWidth,
+        linestyle: LINE_STYLES[props.lineStyle] ?? 0,
+        visible: props.visible !== false,
+        frozen: !!props.locked,
+    };
+    if (props.text) state.text = props.text;
+    if (d.type === 'text') {
+        state.color = props.color;
+        if (props.fontSize) state.fontsize = props.fontSize;
+    }
+    if (props.backgroundColor) state.backgroundColor = props.backgroundColor;
+    if (props.filled !== undefined) state.fillBackground = !!props.filled;
+    return {
+        type: TRADINGVIEW_TYPES[d.type],
+        id: d.id,
+        points: cleanPoints(d.points).map(p => ({ time_t: Math.floor(p.time / 1000), price: p.price })),
+        state,
+    };
+};
+
+/**
+ * state: a saved chart state ({ drawings, folders }). format: 'redpill' | 'tradingview'.
+ * Returns { document, exported, skipped } — skipped counts drawings per type
+ * the format can't express (no points, or no TradingView equivalent).
+ */
+const exportChartState = (sourceId, state, format = 'redpill') => {
+    const drawings = (state && state.drawings) || [];
+    const skipped = {};
+    const skip = (d) => { skipped[d.type] = (skipped[d.type] || 0) + 1; };
+    const usable = drawings.filter((d) => {
+        const ok = d && d.id && cleanPoints(d.points).length > 0 && (format !== 'tradingview' || TRADINGVIEW_TYPES[d.type]);
+        if (!ok && d) skip(d);
+        return ok;
+    });
+
+    if (format === 'redpill') {
+        const document = {
+            format: SCHEMA_FORMAT,
+            version: SCHEMA_VERSION,
+            exportedAt: new Date().toISOString(),
+            sourceId,
+            folders: ((state && state.folders) || []).map(f => ({ id: f.id, name: f.name })),
+            drawings: usable.map(toOpenDrawing),
+        };
+        return { document, exported: usable.length, skipped };
+    }
+    if (format === 'tradingview') {
+        const document = { name: sourceId, symbol: sourceId, sources: usable.map(toTradingViewSource) };
+        return { document, exported: usable.length, skipped };
+    }
+    throw new Error(`Unsupported export format: ${format}`);
+};
+
+module.exports = { SCHEMA_FORMAT, SCHEMA_VERSION, DRAWING_TYPES, exportChartState };
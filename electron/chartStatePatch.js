@@ -0,0 +1,212 @@
+
+// --- CHART STATE PATCHES ---
+// JSON Patch (RFC 6902) operations against a stored chart state, so moving one
+// drawing on a chart with thousands sends one op instead of the whole state:
+//
+//   { op: 'add' | 'remove' | 'replace' | 'move' | 'copy' | 'test', path, value?, from? }
+//
+// Paths are JSON Pointers; inside an array a segment '@<id>' addresses the
+// element with that id, which keeps ops valid while other windows reorder or
+// delete drawings (e.g. /drawings/@d-42/points/1/price). '-' appends. Ops
+// apply in order and all-or-nothing: containers along a path are copied,
+// untouched drawings are shared with the original, and the original state is
+// never modified. A failed 'test' rejects the whole patch, which is how a
+// caller guards against a concurrent edit.
+//
+// Locks are enforced on the result: a drawing that was locked before the patch
+// (properties.locked, or its group's locked flag) may be restyled, but a
+// patch that changes its points or removes it is rejected. Unlocking is an
+// edit of its own, so an unlock and a move can't ride in the same patch.
+
+const MAX_OPS = 5000;
+
+class PatchError extends Error {
+    constructor(message, index, locked = null) {
+        super(index != null ? `Patch op ${index}: ${message}` : message);
+        this.code = 'PATCH_FAILED';
+        this.index = index;
+        this.locked = locked;
+    }
+}
+
+const parsePointer = (path) => {
+    if (path === '') return [];
+    if (typeof path !== 'string' || path[0] !== '/') throw new Error(`Invalid path '${path}'`);
+    return path.slice(1).split('/').map(s => s.replace(/~1/g, '/').replace(/~0/g, '~'));
+};
+
+const isContainer = (value) => value != null && typeof value === 'object';
+
+const clone = (value) => (Array.isArray(value) ? value.slice() : { ...value });
+
+// Index of a segment within an array; allowEnd accepts '-' / length for inserts
+const arrayIndex = (array, segment, allowEnd) => {
+    if (segment === '-') {
+        if (!allowEnd) throw new Error("'-' can only be used to append");
+        return array.length;
+    }
+    if (segment[0] === '@') {
+        const id = segment.slice(1);
+        const index = array.findIndex(item => item && String(item.id) === id);
+        if (index === -1) throw new Error(`No element with id ${id}`);
+        return index;
+    }
+    if (!/^(0|[1-9]\d*)$/.test(segment)) throw new Error(`Invalid array index '${segment}'`);
+    const index = Number(segment);
+    if (index > array.length || (!allowEnd && index === array.length)) throw new Error(`Index ${index} is out of range`);
+    return index;
+};
+
+const getAt = (doc, tokens) => tokens.reduce((node, segment) => {
+    if (!isContainer(node)) throw new Error(`Path goes through a non-container at '${segment}'`);
+    if (Array.isArray(node)) return node[arrayIndex(node, segment, false)];
+    if (!Object.prototype.hasOwnProperty.call(node, segment)) throw new Error(`Missing member '${segment}'`);
+    return node[segment];
+}, doc);
+
+// Returns a new root with containers along `tokens` copied; edit(parent, last) changes the copy
+const editAt = (root, tokens, edit) => {
+    if (!tokens.length) throw new Error('The root of the state cannot be patched');
+    const top = clone(root);
+    let node = top;
+    for (let k = 0; k < tokens.length - 1; k++) {
+        const segment = tokens[k];
+        const key = Array.isArray(node) ? arrayIndex(node, segment, false) : segment;
+        if (!Array.isArray(node) && !Object.prototype.hasOwnProperty.call(node, key)) throw new Error(`Missing member '${segment}'`);
+        if (!isContainer(node[key])) throw new Error(`Path goes through a non-container at '${segment}'`);
+        node[key] = clone(node[key]);
+        node = node[key];
+    }
+    edit(node, tokens[tokens.length - 1]);
+    return top;
+};
+
+const addAt = (doc, tokens, value) => editAt(doc, tokens, (parent, last) => {
+    if (Array.isArray(parent)) parent.splice(arrayIndex(parent, last, true), 0, value);
+    else parent[last] = value;
+});
+
+const removeAt = (doc, tokens) => editAt(doc, tokens, (parent, last) => {
+    if (Array.isArray(parent)) parent.splice(arrayIndex(parent, last, false), 1);
+    else {
+        if (!Object.prototype.hasOwnProperty.call(parent, last)) throw new Error(`Missing member '${last}'`);
+        delete parent[last];
+    }
+});
+
+const replaceAt = (doc, tokens, value) => editAt(doc, tokens, (parent, last) => {
+    if (Array.isArray(parent)) parent[arrayIndex(parent, last, false)] = value;
+    else {
+        if (!Object.prototype.hasOwnProperty.call(parent, last)) throw new Error(`Missing member '${last}'`);
+        parent[last] = value;
+    }
+});
+
+const deepEqual = (a, b) => {
+    if (a === b) return true;
+    if (!isContainer(a) || !isContainer(b) || Array.isArray(a) !== Array.isArray(b)) return false;
+    const keys = Object.keys(a);
+    return keys.length === Object.keys(b).length && keys.every(k => deepEqual(a[k], b[k]));
+};
+
+// Ids of drawings locked in state, directly or through a locked group
+const lockedIds = (state) => {
+    const lockedGroups = new Set((Array.isArray(state.groups) ? state.groups : []).filter(g => g && g.locked).map(g => String(g.id)));
+    return new Set((Array.isArray(state.drawings) ? state.drawings : [])
+        .filter(d => d && d.id != null && ((d.properties && d.properties.locked) || (d.groupId != null && lockedGroups.has(String(d.groupId)))))
+        .map(d => String(d.id)));
+};
+
+/**
+ * Locked drawings of previous that next moved or deleted:
+ * [{ id, change: 'move' | 'delete' }].
+ */
+const lockViolations = (previous, next) => {
+    const locked = lockedIds(previous);
+    if (!locked.size) return [];
+    const after = new Map((Array.isArray(next.drawings) ? next.drawings : []).filter(d => d && d.id != null).map(d => [String(d.id), d]));
+    return (previous.drawings || []).filter(d => d && locked.has(String(d.id))).reduce((out, d) => {
+        const now = after.get(String(d.id));
+        if (!now) out.push({ id: String(d.id), change: 'delete' });
+        else if (!deepEqual(d.points, now.points)) out.push({ id: String(d.id), change: 'move' });
+        return out;
+    }, []);
+};
+
+// JSON Pointer from raw segments
+const pointer = (...segments) => `/${segments.map(s => String(s).replace(/~/g, '~0').replace(/\//g, '~1')).join('/')}`;
+
+// Ops that set properties.locked on the given drawings
+const lockOps = (state, drawingIds, locked) => {
+    const ids = new Set(drawingIds.map(String));
+    return (state.drawings || []).filter(d => d && ids.has(String(d.id))).map(d => (
+        d.properties && typeof d.properties === 'object'
+            ? { op: 'add', path: pointer('drawings', `@${d.id}`, 'properties', 'locked'), value: Boolean(locked) }
+            : { op: 'add', path: pointer('drawings', `@${d.id}`, 'properties'), value: { locked: Boolean(locked) } }
+    ));
+};
+
+// Ops that put the given drawings in group ({ id, name?, locked? }, created if
+// missing), or take them out of their groups when group is null
+const groupOps = (state, drawingIds, group) => {
+    const ops = [];
+    if (group) {
+        const id = String(group.id);
+        const groups = Array.isArray(state.groups) ? state.groups : null;
+        if (!groups) ops.push({ op: 'add', path: '/groups', value: [] });
+        if (!groups || !groups.some(g => g && String(g.id) === id)) {
+            ops.push({ op: 'add', path: '/groups/-', value: { id, name: group.name == null ? '' : String(group.name), locked: Boolean(group.locked) } });
+        }
+    }
+    const ids = new Set(drawingIds.map(String));
+    (state.drawings || []).filter(d => d && ids.has(String(d.id))).forEach((d) => {
+        ops.push({ op: 'add', path: pointer('drawings', `@${d.id}`, 'groupId'), value: group ? String(group.id) : null });
+    });
+    return ops;
+};
+
+/**
+ * Applies ops to state and returns the patched copy. Throws PatchError with
+ * the index of the first op that could not be applied, or with `locked`
+ * listing the violations when the result moves or deletes locked drawings.
+ */
+const applyChartPatch = (state, ops) => {
+    if (!isContainer(state) || Array.isArray(state)) throw new PatchError('No chart state to patch');
+    if (!Array.isArray(ops)) throw new PatchError('Patch must be an array of operations');
+    if (ops.length > MAX_OPS) throw new PatchError(`At most ${MAX_OPS} operations per patch`);
+    const result = ops.reduce((doc, op, index) => {
+        try {
+            if (!isContainer(op)) throw new Error('Operation must be an object');
+            const tokens = parsePointer(op.path);
+            const needsValue = ['add', 'replace', 'test'].includes(op.op);
+            if (needsValue && !('value' in op)) throw new Error(`'${op.op}' needs a value`);
+            switch (op.op) {
+                case 'add': return addAt(doc, tokens, op.value);
+                case 'remove': return removeAt(doc, tokens);
+                case 'replace': return replaceAt(doc, tokens, op.value);
+                case 'move': {
+                    const from = parsePointer(op.from);
+                    if (op.path.startsWith(`${op.from}/`)) throw new Error('Cannot move a value into itself');
+                    const value = getAt(doc, from);
+                    return addAt(removeAt(doc, from), tokens, value);
+                }
+                case 'copy': return addAt(doc, tokens, getAt(doc, parsePointer(op.from)));
+                case 'test':
+                    if (!deepEqual(getAt(doc, tokens), op.value)) throw new Error(`Test failed at ${op.path}`);
+                    return doc;
+                default: throw new Error(`Unknown op '${op.op}'`);
+            }
+        } catch (err) {
+            if (err instanceof PatchError) throw err;
+            throw new PatchError(err.message, index);
+        }
+    }, state);
+    const violations = lockViolations(state, result);
+    if (violations.length) {
+        const first = violations[0];
+        throw new PatchError(`Drawing ${first.id} is locked and can't be ${first.change === 'move' ? 'moved' : 'deleted'}${violations.length > 1 ? ` (${violations.length} locked drawings affected)` : ''}`, null, violations);
+    }
+    return result;
+};
+
+module.exports = { PatchError, applyChartPatch, lockViolations, pointer, lockOps, groupOps };
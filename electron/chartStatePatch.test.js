@@ -0,0 +1,81 @@
+
+const test = require('node:test');
+const assert = require('node:assert');
+const { PatchError, applyChartPatch, lockOps, groupOps } = require('./chartStatePatch');
+
+const drawing = (id, price, properties = {}) => ({ id, type: 'trend_line', points: [{ time: 1, price }, { time: 2, price: price + 1 }], properties });
+
+const chart = () => ({
+    version: 1,
+    drawings: [drawing('d-1', 10), drawing('d-2', 20, { locked: true }), drawing('d-3', 30)],
+    groups: [{ id: 'g-1', name: 'levels', locked: true }]
+});
+
+const patchError = (fn) => {
+    try {
+        fn();
+    } catch (err) {
+        assert.ok(err instanceof PatchError, err.message);
+        return err;
+    }
+    assert.fail('expected a PatchError');
+};
+
+test('ops address drawings by id and leave the original state alone', () => {
+    const state = chart();
+    const next = applyChartPatch(state, [
+        { op: 'test', path: '/drawings/@d-3/points/0/price', value: 30 },
+        { op: 'replace', path: '/drawings/@d-3/points/0/price', value: 31 }
+    ]);
+    assert.strictEqual(next.drawings[2].points[0].price, 31);
+    assert.strictEqual(state.drawings[2].points[0].price, 30);
+    assert.strictEqual(next.drawings[0], state.drawings[0]); // untouched drawings are shared
+});
+
+test('a failed test op rejects the whole patch with its index', () => {
+    const state = chart();
+    const err = patchError(() => applyChartPatch(state, [
+        { op: 'replace', path: '/drawings/@d-1/points/0/price', value: 11 },
+        { op: 'test', path: '/drawings/@d-3/points/0/price', value: 29 }
+    ]));
+    assert.strictEqual(err.code, 'PATCH_FAILED');
+    assert.strictEqual(err.index, 1);
+    assert.match(err.message, /Test failed at \/drawings\/@d-3\/points\/0\/price/);
+    assert.strictEqual(state.drawings[0].points[0].price, 10);
+});
+
+test('a test op compares objects structurally', () => {
+    const state = chart();
+    assert.doesNotThrow(() => applyChartPatch(state, [{ op: 'test', path: '/drawings/@d-1/points/1', value: { price: 11, time: 2 } }]));
+    patchError(() => applyChartPatch(state, [{ op: 'test', path: '/drawings/@d-1/points/1', value: { time: 2, price: 11, extra: true } }]));
+});
+
+test('moving or deleting a locked drawing is refused with the violations', () => {
+    const moved = patchError(() => applyChartPatch(chart(), [{ op: 'replace', path: '/drawings/@d-2/points/0/price', value: 21 }]));
+    assert.deepStrictEqual(moved.locked, [{ id: 'd-2', change: 'move' }]);
+    assert.strictEqual(moved.index, null);
+
+    const deleted = patchError(() => applyChartPatch(chart(), [{ op: 'remove', path: '/drawings/@d-2' }]));
+    assert.deepStrictEqual(deleted.locked, [{ id: 'd-2', change: 'delete' }]);
+});
+
+test('locked drawings may still be restyled', () => {
+    const next = applyChartPatch(chart(), [{ op: 'add', path: '/drawings/@d-2/properties/color', value: '#ff0000' }]);
+    assert.strictEqual(next.drawings[1].properties.color, '#ff0000');
+});
+
+test('an unlock and a move in the same patch are refused', () => {
+    const state = chart();
+    const ops = [...lockOps(state, ['d-2'], false), { op: 'replace', path: '/drawings/@d-2/points/0/price', value: 21 }];
+    const err = patchError(() => applyChartPatch(state, ops));
+    assert.deepStrictEqual(err.locked, [{ id: 'd-2', change: 'move' }]);
+    const unlocked = applyChartPatch(state, lockOps(state, ['d-2'], false));
+    assert.doesNotThrow(() => applyChartPatch(unlocked, [{ op: 'replace', path: '/drawings/@d-2/points/0/price', value: 21 }]));
+});
+
+test('drawings in a locked group are locked', () => {
+    const grouped = applyChartPatch(chart(), groupOps(chart(), ['d-1', 'd-3'], { id: 'g-1' }));
+    const err = patchError(() => applyChartPatch(grouped, [{ op: 'remove', path: '/drawings/@d-3' }, { op: 'remove', path: '/drawings/@d-1' }]));
+    assert.deepStrictEqual(err.locked.map(v => v.id).sort(), ['d-1', 'd-3']);
+    assert.match(err.message, /2 locked drawings affected/);
+});
@@ -0,0 +1,206 @@
+
+const { DRAWING_TYPES } = require('./chartStateExport');
+
+// --- CHART STATE SCHEMA ---
+// What a saved chart state may contain, checked on every save so a renderer
+// bug can't persist garbage:
+//
+//   { drawings: [{ id, type, points: [{ time, price }], properties, creationTimeframe?, folderId?, groupId? }],
+//     folders?: [{ id, name, isExpanded? }], groups?: [{ id, name, locked? }],
+//     visibleRange: { from, to } | null, config?, ... }
+//
+// properties.locked and a group's locked flag are enforced by patches
+// (chartStatePatch.js): a locked drawing can be restyled but not moved or
+// deleted until it is unlocked.
+//
+// Errors reject the save: a state or drawing that isn't an object, missing or
+// duplicate ids, and non-finite numbers (NaN / Infinity coordinates, widths,
+// sizes, visible range). Everything else is normalized and reported as a
+// warning: numeric strings become numbers, an unknown line style becomes
+// solid, a folderId or groupId without its folder or group is cleared, a
+// non-boolean locked flag becomes a boolean, and a point count that
+// doesn't fit the type is noted. Drawings of types this version doesn't know
+// are kept as they are and listed in unknownTypes. Extra fields pass through.
+
+const MAX_DRAWINGS = 20000;
+const MAX_POINTS = 100000; // per drawing (long brush strokes)
+const LINE_STYLES = ['solid', 'dashed', 'dotted'];
+const NUMERIC_PROPERTIES = ['lineWidth', 'fontSize', 'smoothing'];
+
+// [min, max] points per type (max null = unbounded)
+const POINT_COUNTS = {
+    text: [1, 1],
+    horizontal_line: [1, 1],
+    vertical_line: [1, 1],
+    horizontal_ray: [1, 1],
+    triangle: [3, 3],
+    rotated_rectangle: [3, 3],
+    brush: [1, null]
+};
+const pointRange = (type) => POINT_COUNTS[type] || [2, 2];
+
+const isObject = (value) => value != null && typeof value === 'object' && !Array.isArray(value);
+
+/**
+ * Returns { state, report } where state is the normalized copy and report is
+ * { valid, errors: [{ path, message }], warnings: [{ path, message }],
+ * unknownTypes, drawings }. Only save when report.valid.
+ */
+const validateChartState = (input) => {
+    const errors = [];
+    const warnings = [];
+    const unknownTypes = new Set();
+    const error = (path, message) => errors.push({ path, message });
+    const warn = (path, message) => warnings.push({ path, message });
+    const report = () => ({ valid: errors.length === 0, errors, warnings, unknownTypes: Array.from(unknownTypes), drawings: drawings.length });
+
+    let drawings = [];
+    if (!isObject(input)) {
+        error('', 'Chart state must be an object');
+        return { state: input, report: report() };
+    }
+    const state = { ...input };
+
+    // A finite number, or a numeric string converted with a warning
+    const number = (value, path) => {
+        if (typeof value === 'string' && value.trim() !== '' && Number.isFinite(Number(value))) {
+            warn(path, 'Numeric string converted to a number');
+            return Number(value);
+        }
+        if (typeof value !== 'number' || !Number.isFinite(value)) {
+            error(path, `Must be a finite number (got ${value === null ? 'null' : typeof value === 'number' ? String(value) : typeof value})`);
+            return value;
+        }
+        return value;
+    };
+
+    const flag = (value, path) => {
+        if (typeof value === 'boolean') return value;
+        warn(path, 'Converted to a boolean');
+        return value === 'true' || value === 1;
+    };
+
+    const folderIds = new Set();
+    if (state.folders !== undefined) {
+        if (!Array.isArray(state.folders)) error('folders', 'Must be an array');
+        else {
+            state.folders = state.folders.map((folder, i) => {
+                if (!isObject(folder) || folder.id == null || folder.id === '') {
+                    error(`folders[${i}]`, 'Folder needs an id');
+                    return folder;
+                }
+                const out = { ...folder, id: String(folder.id), name: folder.name == null ? '' : String(folder.name) };
+                if (folderIds.has(out.id)) error(`folders[${i}].id`, `Duplicate folder id ${out.id}`);
+                folderIds.add(out.id);
+                return out;
+            });
+        }
+    }
+
+    const groupIds = new Set();
+    if (state.groups !== undefined) {
+        if (!Array.isArray(state.groups)) error('groups', 'Must be an array');
+        else {
+            state.groups = state.groups.map((group, i) => {
+                if (!isObject(group) || group.id == null || group.id === '') {
+                    error(`groups[${i}]`, 'Group needs an id');
+                    return group;
+                }
+                const out = { ...group, id: String(group.id), name: group.name == null ? '' : String(group.name) };
+                if (out.locked !== undefined) out.locked = flag(out.locked, `groups[${i}].locked`);
+                if (groupIds.has(out.id)) error(`groups[${i}].id`, `Duplicate group id ${out.id}`);
+                groupIds.add(out.id);
+                return out;
+            });
+        }
+    }
+
+    if (state.drawings === undefined || state.drawings === null) state.drawings = [];
+    if (!Array.isArray(state.drawings)) {
+        error('drawings', 'Must be an array');
+        return { state, report: report() };
+    }
+    if (state.drawings.length > MAX_DRAWINGS) error('drawings', `At most ${MAX_DRAWINGS} drawings per chart`);
+
+    const ids = new Set();
+    drawings = state.drawings.map((drawing, i) => {
+        const at = `drawings[${i}]`;
+        if (!isObject(drawing)) {
+            error(at, 'Drawing must be an object');
+            return drawing;
+        }
+        const out = { ...drawing };
+        if (out.id == null || out.id === '') error(`${at}.id`, 'Drawing needs an id');
+        else {
+            if (typeof out.id !== 'string') { warn(`${at}.id`, 'Id converted to a string'); out.id = String(out.id); }
+            if (ids.has(out.id)) error(`${at}.id`, `Duplicate drawing id ${out.id}`);
+            ids.add(out.id);
+        }
+
+        const known = DRAWING_TYPES.includes(out.type);
+        if (typeof out.type !== 'string' || !out.type) error(`${at}.type`, 'Drawing needs a type');
+        else if (!known) {
+            unknownTypes.add(out.type);
+            warn(`${at}.type`, `Unknown drawing type '${out.type}' kept as is`);
+        }
+
+        if (!Array.isArray(out.points)) error(`${at}.points`, 'Must be an array');
+        else {
+            if (out.points.length > MAX_POINTS) error(`${at}.points`, `At most ${MAX_POINTS} points per drawing`);
+            out.points = out.points.map((point, j) => {
+                if (!isObject(point)) {
+                    error(`${at}.points[${j}]`, 'Point must be an object with time and price');
+                    return point;
+                }
+                return { ...point, time: number(point.time, `${at}.points[${j}].time`), price: number(point.price, `${at}.points[${j}].price`) };
+            });
+            if (known) {
+                const [min, max] = pointRange(out.type);
+                if (out.points.length < min || (max != null && out.points.length > max)) {
+                    warn(`${at}.points`, `${out.type} expects ${max == null ? `at least ${min}` : min === max ? min : `${min} to ${max}`} point${max === 1 ? '' : 's'}, has ${out.points.length}`);
+                }
+            }
+        }
+
+        if (out.properties === undefined || out.properties === null) {
+            warn(`${at}.properties`, 'Missing properties, defaulted to {}');
+            out.properties = {};
+        } else if (!isObject(out.properties)) {
+            error(`${at}.properties`, 'Must be an object');
+        } else {
+            const props = { ...out.properties };
+            NUMERIC_PROPERTIES.forEach((key) => {
+                if (props[key] !== undefined && props[key] !== null) props[key] = number(props[key], `${at}.properties.${key}`);
+            });
+            if (typeof props.lineWidth === 'number' && props.lineWidth <= 0) error(`${at}.properties.lineWidth`, 'Must be positive');
+            if (props.lineStyle !== undefined && !LINE_STYLES.includes(props.lineStyle)) {
+                warn(`${at}.properties.lineStyle`, `Unknown line style '${props.lineStyle}', using solid`);
+                props.lineStyle = 'solid';
+            }
+            if (props.locked !== undefined && props.locked !== null) props.locked = flag(props.locked, `${at}.properties.locked`);
+            if (props.color !== undefined && props.color !== null && typeof props.color !== 'string') error(`${at}.properties.color`, 'Must be a string');
+            out.properties = props;
+        }
+
+        if (out.folderId != null && state.folders !== undefined && !folderIds.has(String(out.folderId))) {
+            warn(`${at}.folderId`, `Folder ${out.folderId} doesn't exist, drawing moved out of it`);
+            out.folderId = null;
+        }
+        if (out.groupId != null && !groupIds.has(String(out.groupId))) {
+            warn(`${at}.groupId`, `Group ${out.groupId} doesn't exist, drawing removed from it`);
+            out.groupId = null;
+        }
+        return out;
+    });
+    state.drawings = drawings;
+
+    if (state.visibleRange != null) {
+        if (!isObject(state.visibleRange)) error('visibleRange', 'Must be { from, to } or null');
+        else state.visibleRange = { ...state.visibleRange, from: number(state.visibleRange.from, 'visibleRange.from'), to: number(state.visibleRange.to, 'visibleRange.to') };
+    }
+    if (state.config !== undefined && state.config !== null && !isObject(state.config)) error('config', 'Must be an object');
+
+    return { state, report: report() };
+};
+
+module.exports = { validateChartState };
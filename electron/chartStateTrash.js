@@ -0,0 +1,57 @@
+
+// --- CHART-STATE TRASH ---
+// Chart states removed by garbage collection are moved here instead of being
+// deleted outright, so a mistaken cleanup can be undone. Entries older than
+// the retention window are purged when the trash is emptied.
+
+const DEFAULT_RETENTION_DAYS = 30;
+
+const initializeChartTrashTable = (db) => {
+    db.exec('CREATE TABLE IF NOT EXISTS drawings_trash (symbol TEXT PRIMARY KEY, data TEXT, reason TEXT, deleted_at INTEGER);');
+};
+
+/**
+ * Moves the given chart states to the trash in one transaction. Returns the symbols actually moved.
+ */
+const moveChartStatesToTrash = (db, symbols, reason = 'manual') => {
+    const moved = [];
+    const select = db.prepare('SELECT data FROM drawings WHERE symbol = ?');
+    const insert = db.prepare('INSERT OR REPLACE INTO drawings_trash (symbol, data, reason, deleted_at) VALUES (?, ?, ?, ?)');
+    const remove = db.prepare('DELETE FROM drawings WHERE symbol = ?');
+    const tx = db.transaction(() => {
+        symbols.forEach((symbol) => {
+            const row = select.get(symbol);
+            if (!row) return;
+            insert.run(symbol, row.data, reason, Date.now());
+            remove.run(symbol);
+            moved.push(symbol);
+        });
+    });
+    tx();
+    return moved;
+};
+
+const listChartTrash = (db) => db.prepare('SELECT symbol, length(data) as bytes, reason, deleted_at as deletedAt FROM drawings_trash ORDER BY deleted_at DESC').all();
+
+// Restores unless a newer chart state already exists for the symbol
+const restoreChartStates = (db, symbols) => {
+    const restored = [];
+    const skipped = [];
+    const tx = db.transaction(() => {
+        symbols.forEach((symbol) => {
+            const row = db.prepare('SELECT data FROM drawings_trash WHERE symbol = ?').get(symbol);
+            if (!row) return;
+            if (db.prepare('SELECT 1 FROM drawings WHERE symbol = ?').get(symbol)) { skipped.push(symbol); return; }
+            db.prepare('INSERT INTO drawings (symbol, data) VALUES (?, ?)').run(symbol, row.data);
+            db.prepare('DELETE FROM drawings_trash WHERE symbol = ?').run(symbol);
+            restored.push(symbol);
+        });
+    });
+    tx();
+    return { restored, skipped };
+};
+
+const emptyChartTrash = (db, olderThanDays = DEFAULT_RETENTION_DAYS) =>
+    db.prepare('DELETE FROM drawings_trash WHERE deleted_at < ?').run(Date.now() - olderThanDays * 86400000).changes;
+
+module.exports = { initializeChartTrashTable, moveChartStatesToTrash, listChartTrash, restoreChartStates, emptyChartTrash };
@@ -0,0 +1,45 @@
+
+// --- CHART THUMBNAILS ---
+// Small PNG previews captured by the renderer when a chart state is saved, kept
+// in their own table next to `drawings` so loading a chart state never pulls
+// image bytes along. The saved-charts browser and layout picker read them back.
+
+const MAX_THUMBNAIL_BYTES = 512 * 1024;
+const PNG_SIGNATURE = Buffer.from([0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a]);
+
+const initializeThumbnailTable = (db) => {
+    db.exec('CREATE TABLE IF NOT EXISTS chart_thumbnails (symbol TEXT PRIMARY KEY, png BLOB, width INTEGER, height INTEGER, updated_at INTEGER);');
+};
+
+/**
+ * Accepts a data URL, base64 string, Uint8Array or Buffer and returns the PNG
+ * bytes plus dimensions from the IHDR chunk. Throws on anything that isn't a PNG.
+ */
+const decodeThumbnail = (input) => {
+    let buf;
+    if (Buffer.isBuffer(input)) buf = input;
+    else if (input instanceof Uint8Array) buf = Buffer.from(input.buffer, input.byteOffset, input.byteLength);
+    else if (typeof input === 'string') buf = Buffer.from(input.replace(/^data:image\/png;base64,/, ''), 'base64');
+    else throw new Error('Thumbnail must be a PNG data URL or byte array');
+
+    if (buf.length > MAX_THUMBNAIL_BYTES) throw new Error(`Thumbnail exceeds ${MAX_THUMBNAIL_BYTES / 1024} KB`);
+    if (buf.length < 24 || !buf.subarray(0, 8).equals(PNG_SIGNATURE)) throw new Error('Thumbnail is not a PNG image');
+    return { png: buf, width: buf.readUInt32BE(16), height: buf.readUInt32BE(20) };
+};
+
+const saveThumbnail = (db, symbol, input) => {
+    const { png, width, height } = decodeThumbnail(input);
+    db.prepare('INSERT OR REPLACE INTO chart_thumbnails (symbol, png, width, height, updated_at) VALUES (?, ?, ?, ?, ?)')
+        .run(symbol, png, width, height, Date.now());
+    return { width, height, bytes: png.length };
+};
+
+const getThumbnail = (db, symbol) => {
+    const row = db.prepare('SELECT png, width, height, updated_at FROM chart_thumbnails WHERE symbol = ?').get(symbol);
+    if (!row) return null;
+    return { dataUrl: `data:image/png;base64,${Buffer.from(row.png).toString('base64')}`, width: row.width, height: row.height, updatedAt: row.updated_at };
+};
+
+const deleteThumbnail = (db, symbol) => db.prepare('DELETE FROM chart_thumbnails WHERE symbol = ?').run(symbol).changes > 0;
+
+module.exports = { initializeThumbnailTable, decodeThumbnail, saveThumbnail, getThumbnail, deleteThumbnail };
@@ -0,0 +1,84 @@
+
+// --- CLIPBOARD TICKER WATCHER ---
+// Opt-in: polls the clipboard text (Electron has no change event) and, when
+// a copy looks like a ticker, checks it against the symbol search before
+// reporting it, so "the" or an order id never turns into a prompt. Accepted
+// shapes: NVDA, $NVDA, NASDAQ:NVDA, BRK.B, BTC/USDT, EURUSD. Lowercase text
+// only counts with a leading '$'. Only the text's shape is inspected locally;
+// the candidate reaches a provider only when useProviders is on.
+
+const DEFAULT_CLIPBOARD_CONFIG = { enabled: false, useProviders: false, pollMs: 1000 };
+const MAX_TEXT_LENGTH = 40;
+const TICKER_PATTERN = /^(?:([A-Z0-9_]{1,12}):)?([A-Z][A-Z0-9]{0,9}(?:[.\-][A-Z0-9]{1,4})?(?:\/[A-Z0-9]{2,6})?)$/;
+
+const normalizeClipboardConfig = (config = {}) => {
+    const next = { ...DEFAULT_CLIPBOARD_CONFIG, ...config };
+    next.enabled = !!next.enabled;
+    next.useProviders = !!next.useProviders;
+    next.pollMs = Math.round(Number(next.pollMs));
+    if (!Number.isFinite(next.pollMs) || next.pollMs < 250 || next.pollMs > 10000) throw new Error('pollMs must be between 250 and 10000');
+    return next;
+};
+
+/**
+ * A ticker-shaped candidate from copied text: { text, exchange, symbol } or null.
+ */
+const extractTicker = (text) => {
+    const trimmed = String(text || '').trim();
+    if (!trimmed || trimmed.length > MAX_TEXT_LENGTH || /\s/.test(trimmed)) return null;
+    const cashtag = trimmed.startsWith('$');
+    const body = cashtag ? trimmed.slice(1).toUpperCase() : trimmed;
+    const match = TICKER_PATTERN.exec(body);
+    if (!match) return null;
+    // Bare numbers and single letters are too ambiguous without a cashtag
+    if (!cashtag && !match[1] && match[2].length < 2) return null;
+    return { text: trimmed, exchange: match[1] || null, symbol: match[2] };
+};
+
+/**
+ * resolve(candidate, config) -> symbol search match or null.
+ * onSymbol({ ...match, copiedText }) fires once per distinct clipboard text.
+ */
+const createClipboardWatcher = ({ clipboard, getConfig = () => DEFAULT_CLIPBOARD_CONFIG, resolve, onSymbol = () => {}, shouldSkip = () => false }) => {
+    let timer = null;
+    let lastText = null;
+    let seq = 0;
+
+    const config = () => {
+        try { return normalizeClipboardConfig(getConfig()); } catch (e) { return DEFAULT_CLIPBOARD_CONFIG; }
+    };
+
+    const check = async () => {
+        let text;
+        try { text = clipboard.readText(); } catch (e) { return; }
+        if (text === lastText) return;
+        lastText = text;
+        const candidate = extractTicker(text);
+        if (!candidate || shouldSkip()) return;
+        const mine = ++seq;
+        try {
+            const match = await resolve(candidate, config());
+            // A newer copy finished first or replaced this one
+            if (match && mine === seq && lastText === text) onSymbol({ ...match, copiedText: candidate.text });
+        } catch (e) { /* validation failed: stay quiet */ }
+    };
+
+    const stop = () => {
+        if (timer) clearInterval(timer);
+        timer = null;
+    };
+
+    // Applies the current config; the text already on the clipboard is not reported
+    const restart = () => {
+        stop();
+        const cfg = config();
+        if (!cfg.enabled) return false;
+        try { lastText = clipboard.readText(); } catch (e) { lastText = null; }
+        timer = setInterval(check, cfg.pollMs);
+        return true;
+    };
+
+    return { restart, stop, check, isRunning: () => !!timer };
+};
+
+module.exports = { DEFAULT_CLIPBOARD_CONFIG, normalizeClipboardConfig, extractTicker, createClipboardWatcher };
@@ -0,0 +1,198 @@
+
+const crypto = require('crypto');
+const fs = require('fs');
+const net = require('net');
+const path = require('path');
+const { TIMEFRAME_MS } = require('./datasets');
+const { hashToken, normalizeSignal } = require('./signalInbox');
+
+// --- COMPANION IPC ---
+// A local socket for signal daemons and other companion processes: a Unix
+// domain socket (companion.sock in userData, owner-only) or a named pipe on
+// Windows (\\.\pipe\redpill-companion-<user>). Cheaper than the HTTP inbox
+// for a process that streams bars all day: one connection, one JSON object
+// per line each way.
+//
+//   -> { id?, op: 'hello', token }                       the signal inbox token; required first
+//   -> { id?, op: 'push-bars', symbol, timeframe, bars: [{ time, open, high, low, close, volume? }], closed?, source? }
+//   -> { id?, op: 'push-signals', signals: [...] }      same objects as POST /signals
+//   -> { id?, op: 'list-alerts', symbol? }
+//   -> { id?, op: 'subscribe', events: ['alerts'] }     triggered alerts arrive as events
+//   -> { id?, op: 'ping' }
+//   <- { id, ok: true, ... } | { id, ok: false, error, code }
+//   <- { event: 'alert', data: { ...alert payload } }
+//
+// Pushed bars go into the live feed as provider 'companion' (or
+// 'companion:<source>'), so charts, alerts and the bar recorder see them like
+// any provider's; closed bars (closed defaults to true) are recorded.
+
+const MAX_LINE_BYTES = 1024 * 1024;
+const MAX_BARS = 5000;
+const MAX_SIGNALS = 100;
+const MAX_CLIENTS = 16;
+const EVENTS = { alerts: 'alert' }; // subscription -> event name on the wire
+
+const defaultSocketPath = (userDataDir) => (process.platform === 'win32'
+    ? `\\\\.\\pipe\\redpill-companion-${(process.env.USERNAME || 'user').replace(/[^A-Za-z0-9_-]/g, '')}`
+    : path.join(userDataDir, 'companion.sock'));
+
+class ProtocolError extends Error {
+    constructor(code, message) {
+        super(message);
+        this.code = code;
+    }
+}
+
+// Bar time: ms, seconds or an ISO string, like a signal's time
+const normalizeBar = (input, i) => {
+    if (!input || typeof input !== 'object') throw new ProtocolError('INVALID_INPUT', `bars[${i}] must be an object`);
+    const raw = input.timestamp ?? input.time;
+    const parsed = typeof raw === 'number' ? raw : Date.parse(raw);
+    if (!Number.isFinite(parsed)) throw new ProtocolError('INVALID_INPUT', `bars[${i}].time must be a timestamp or an ISO date`);
+    const bar = { timestamp: parsed < 1e11 ? parsed * 1000 : parsed };
+    ['open', 'high', 'low', 'close'].forEach((key) => {
+        bar[key] = Number(input[key]);
+        if (!Number.isFinite(bar[key])) throw new ProtocolError('INVALID_INPUT', `bars[${i}].${key} must be a number`);
+    });
+    bar.volume = input.volume == null ? 0 : Number(input.volume);
+    if (!Number.isFinite(bar.volume)) throw new ProtocolError('INVALID_INPUT', `bars[${i}].volume must be a number`);
+    return bar;
+};
+
+/**
+ * getTokenHash() -> the signal inbox token hash (none: every hello is refused).
+ * pushBars(provider, symbol, timeframe, bars, closed), acceptSignals(signals) -> stored rows,
+ * listAlerts(symbol) -> alerts.
+ */
+const createCompanionServer = ({ getTokenHash, pushBars, acceptSignals, listAlerts, onLog = () => {} }) => {
+    let server = null;
+    let socketPath = null;
+    const clients = new Set();
+
+    const authorized = (token) => {
+        const expected = getTokenHash();
+        if (!expected || !token) return false;
+        const a = Buffer.from(hashToken(token), 'hex');
+        const b = Buffer.from(expected, 'hex');
+        return a.length === b.length && crypto.timingSafeEqual(a, b);
+    };
+
+    const ops = {
+        hello: (client, msg) => {
+            if (!authorized(msg.token)) throw new ProtocolError('UNAUTHORIZED', 'Missing or invalid token');
+            client.authorized = true;
+            return { version: 1, events: Object.keys(EVENTS) };
+        },
+        ping: () => ({ pong: Date.now() }),
+        'push-bars': (client, msg) => {
+            const symbol = String(msg.symbol || '').trim();
+            if (!symbol || symbol.length > 64) throw new ProtocolError('INVALID_INPUT', 'symbol is required');
+            if (!TIMEFRAME_MS[msg.timeframe]) throw new ProtocolError('INVALID_INPUT', `Unknown timeframe: ${msg.timeframe}`);
+            if (!Array.isArray(msg.bars) || !msg.bars.length) throw new ProtocolError('INVALID_INPUT', 'bars must be a non-empty array');
+            if (msg.bars.length > MAX_BARS) throw new ProtocolError('INVALID_INPUT', `At most ${MAX_BARS} bars per message`);
+            const bars = msg.bars.map(normalizeBar).sort((a, b) => a.timestamp - b.timestamp);
+            const source = msg.source ? String(msg.source).replace(/[^A-Za-z0-9._-]/g, '').slice(0, 40) : '';
+            pushBars(source ? `companion:${source}` : 'companion', symbol, msg.timeframe, bars, msg.closed !== false);
+            return { accepted: bars.length };
+        },
+        'push-signals': (client, msg) => {
+            const items = Array.isArray(msg.signals) ? msg.signals : [];
+            if (!items.length) throw new ProtocolError('INVALID_INPUT', 'signals must be a non-empty array');
+            if (items.length > MAX_SIGNALS) throw new ProtocolError('INVALID_INPUT', `At most ${MAX_SIGNALS} signals per message`);
+            const receivedAt = Date.now();
+            const signals = items.map((item, i) => {
+                try { return normalizeSignal(item, receivedAt); } catch (err) { throw new ProtocolError('INVALID_INPUT', `Signal ${i}: ${err.message}`); }
+            });
+            const stored = acceptSignals(signals);
+            return { accepted: stored.length, ids: stored.map(s => s.id) };
+        },
+        'list-alerts': (client, msg) => ({ alerts: listAlerts(msg.symbol || null) }),
+        subscribe: (client, msg) => {
+            const events = Array.isArray(msg.events) ? msg.events : [];
+            const unknown = events.filter(e => !EVENTS[e]);
+            if (unknown.length) throw new ProtocolError('INVALID_INPUT', `Unknown event: ${unknown.join(', ')}`);
+            client.events = new Set(events);
+            return { events };
+        }
+    };
+
+    const send = (client, payload) => {
+        if (!client.socket.destroyed) client.socket.write(`${JSON.stringify(payload)}\n`);
+    };
+
+    const handleLine = (client, line) => {
+        let msg;
+        try { msg = JSON.parse(line); } catch (e) { send(client, { id: null, ok: false, error: 'Invalid JSON', code: 'INVALID_JSON' }); return; }
+        const id = msg && msg.id !== undefined ? msg.id : null;
+        try {
+            const op = msg && ops[msg.op];
+            if (!op) throw new ProtocolError('INVALID_INPUT', `Unknown op: ${msg && msg.op}`);
+            if (msg.op !== 'hello' && !client.authorized) throw new ProtocolError('UNAUTHORIZED', 'Send hello with the token first');
+            send(client, { id, ok: true, ...op(client, msg) });
+        } catch (err) {
+            if (!(err instanceof ProtocolError)) onLog('WARN', 'COMPANION_IPC_ERROR', { op: msg && msg.op, error: err.message });
+            send(client, { id, ok: false, error: err.message, code: err.code || 'INTERNAL' });
+        }
+    };
+
+    const accept = (socket) => {
+        if (clients.size >= MAX_CLIENTS) { socket.end(`${JSON.stringify({ id: null, ok: false, error: 'Too many connections', code: 'UNAVAILABLE' })}\n`); return; }
+        const client = { socket, authorized: false, events: new Set(), buffer: '' };
+        clients.add(client);
+        socket.setEncoding('utf8');
+        socket.on('data', (chunk) => {
+            client.buffer += chunk;
+            if (client.buffer.length > MAX_LINE_BYTES && !client.buffer.includes('\n')) {
+                send(client, { id: null, ok: false, error: `Messages are limited to ${MAX_LINE_BYTES} bytes`, code: 'INVALID_INPUT' });
+                socket.destroy();
+                return;
+            }
+            let newline;
+            while ((newline = client.buffer.indexOf('\n')) !== -1) {
+                const line = client.buffer.slice(0, newline).trim();
+                client.buffer = client.buffer.slice(newline + 1);
+                if (line) handleLine(client, line);
+            }
+        });
+        socket.on('error', () => {});
+        socket.on('close', () => clients.delete(client));
+    };
+
+    // Pushes an event to every authorized client subscribed to it
+    const publish = (event, data) => {
+        clients.forEach((client) => {
+            if (client.authorized && client.events.has(event)) send(client, { event: EVENTS[event], data });
+        });
+    };
+
+    const start = (target) => new Promise((resolve, reject) => {
+        if (server) { resolve(socketPath); return; }
+        // A socket file left by a crash would make listen fail with EADDRINUSE
+        if (process.platform !== 'win32' && fs.existsSync(target)) fs.rmSync(target, { force: true });
+        const next = net.createServer(accept);
+        next.once('error', reject);
+        next.listen(target, () => {
+            if (process.platform !== 'win32') fs.chmodSync(target, 0o600);
+            server = next;
+            socketPath = target;
+            onLog('INFO', 'COMPANION_IPC_LISTENING', { path: target });
+            resolve(target);
+        });
+    });
+
+    const stop = () => new Promise((resolve) => {
+        if (!server) { resolve(); return; }
+        const closing = server;
+        server = null;
+        socketPath = null;
+        clients.forEach(c => c.socket.destroy());
+        clients.clear();
+        closing.close(() => resolve());
+    });
+
+    const status = () => ({ listening: !!server, path: socketPath, clients: clients.size, authorized: Array.from(clients).filter(c => c.authorized).length });
+
+    return { start, stop, publish, status };
+};
+
+module.exports = { defaultSocketPath, createCompanionServer };
@@ -0,0 +1,278 @@
+
+const crypto = require('crypto');
+const fs = require('fs');
+const path = require('path');
+const zlib = require('zlib');
+const { parseDatasetId } = require('./datasets');
+
+// --- COMPUTE CACHE ---
+// Keeps indicator, custom-series, scan and seasonality results on disk so an
+// identical request — after a restart, or a screen re-run over unchanged data —
+// is answered without touching the compute pool. An entry's key hashes the
+// code version (the compute modules' source plus the app version), the job's
+// parameters and the content of the bars it reads, so nothing has to be
+// invalidated when data changes: an edited, imported or live-appended bar
+// changes the content hash and the next run simply misses. Jobs reading the
+// last `limit` bars hash exactly those bars; full-history jobs hash an
+// aggregate fingerprint (count, range, per-field sums and a time-weighted
+// close sum) computed in SQL, which avoids pulling millions of rows into the
+// main process. Entries are one gzipped JSON file each under `dir`, named
+// after the dataset so a dataset's entries can be dropped together; the
+// least recently used go first once the directory exceeds `maxBytes`.
+// Failed results are never stored.
+
+const DEFAULT_MAX_BYTES = 256 * 1024 * 1024;
+const DEFAULT_LIST_LIMIT = 200;
+const KINDS = ['indicator', 'series', 'scan', 'seasonality'];
+const FILE_SUFFIX = '.json.gz';
+
+const sha1 = text => crypto.createHash('sha1').update(text).digest('hex');
+
+// Hash of the source files that produce results; any change to them retires old entries
+const codeVersion = (files = [], appVersion = '') => {
+    const hash = crypto.createHash('sha1').update(String(appVersion));
+    files.forEach((file) => {
+        try { hash.update(fs.readFileSync(file)); } catch (e) { hash.update(`missing:${path.basename(file)}`); }
+    });
+    return hash.digest('hex').slice(0, 16);
+};
+
+/**
+ * Content hash of the bars a job reads: the last `limit` bars, or (no limit)
+ * a fingerprint of the whole dataset.
+ */
+const datasetContentHash = (db, id, limit = null) => {
+    const { symbol, timeframe } = parseDatasetId(id);
+    if (limit) {
+        const rows = db.prepare('SELECT timestamp, open, high, low, close, volume FROM market_data WHERE symbol = ? AND timeframe = ? ORDER BY timestamp DESC LIMIT ?')
+            .all(symbol, timeframe, Number(limit));
+        const hash = crypto.createHash('sha1');
+        rows.forEach(r => hash.update(`${r.timestamp},${r.open},${r.high},${r.low},${r.close},${r.volume}\n`));
+        return `${rows.length}:${hash.digest('hex')}`;
+    }
+    const f = db.prepare(`
+        SELECT COUNT(*) as n, MIN(timestamp) as first, MAX(timestamp) as last, TOTAL(open) as o, TOTAL(high) as h,
+               TOTAL(low) as l, TOTAL(close) as c, TOTAL(volume) as v, TOTAL((timestamp % 1000003) * close) as w
+        FROM market_data WHERE symbol = ? AND timeframe = ?
+    `).get(symbol, timeframe);
+    return `${f.n}:${sha1([f.n, f.first, f.last, f.o, f.h, f.l, f.c, f.v, f.w].join('|'))}`;
+};
+
+// Which cache kind a compute-pool job belongs to; heatmap cells are live and stay uncached
+const jobKind = (job) => {
+    if (job.heatmap) return null;
+    if (job.expression) return 'scan';
+    if (job.series) return 'series';
+    if (job.indicator) return 'indicator';
+    return null;
+};
+
+const datasetTag = id => sha1(String(id)).slice(0, 12);
+
+/**
+ * dir: cache directory; getDb() -> the open database; version: codeVersion();
+ * persist: false reads the cache but never writes it (read-only mode).
+ */
+const createComputeCache = ({ dir, getDb, version, maxBytes = DEFAULT_MAX_BYTES, persist = () => true }) => {
+    let index = null; // file name -> { bytes, usedAt }
+    const meta = new Map(); // file name -> entry without result (filled as entries are read)
+    let totalBytes = 0;
+    const counters = { hits: 0, misses: 0, writes: 0, evictions: 0 };
+
+    const loadIndex = () => {
+        if (index) return index;
+        index = new Map();
+        totalBytes = 0;
+        if (fs.existsSync(dir)) {
+            fs.readdirSync(dir).filter(f => f.endsWith(FILE_SUFFIX)).forEach((name) => {
+                try {
+                    const stat = fs.statSync(path.join(dir, name));
+                    index.set(name, { bytes: stat.size, usedAt: stat.mtimeMs });
+                    totalBytes += stat.size;
+                } catch (e) {}
+            });
+        }
+        return index;
+    };
+
+    const fileName = (datasetId, key) => `${datasetTag(datasetId)}-${key}${FILE_SUFFIX}`;
+
+    const removeFile = (name) => {
+        const entry = loadIndex().get(name);
+        try { fs.unlinkSync(path.join(dir, name)); } catch (e) {}
+        if (entry) totalBytes -= entry.bytes;
+        index.delete(name);
+        meta.delete(name);
+    };
+
+    const readEntry = (name) => {
+        try {
+            const entry = JSON.parse(zlib.gunzipSync(fs.readFileSync(path.join(dir, name))).toString('utf8'));
+            const { result, ...info } = entry;
+            meta.set(name, info);
+            return entry;
+        } catch (e) {
+            removeFile(name); // unreadable or truncated
+            return null;
+        }
+    };
+
+    const evict = () => {
+        if (totalBytes <= maxBytes) return;
+        const oldest = Array.from(index.entries()).sort((a, b) => a[1].usedAt - b[1].usedAt);
+        for (const [name] of oldest) {
+            if (totalBytes <= maxBytes) break;
+            removeFile(name);
+            counters.evictions++;
+        }
+    };
+
+    const keyFor = (kind, datasetId, params, contentHash) => sha1(JSON.stringify({ version, kind, datasetId, contentHash, params }));
+
+    const get = (kind, datasetId, params, contentHash) => {
+        const name = fileName(datasetId, keyFor(kind, datasetId, params, contentHash));
+        const known = loadIndex().get(name);
+        const entry = known ? readEntry(name) : null;
+        if (!entry) { counters.misses++; return null; }
+        counters.hits++;
+        known.usedAt = Date.now();
+        try { fs.utimesSync(path.join(dir, name), new Date(), new Date()); } catch (e) {}
+        return entry.result;
+    };
+
+    const set = (kind, datasetId, params, contentHash, result) => {
+        if (!persist()) return false;
+        const key = keyFor(kind, datasetId, params, contentHash);
+        const name = fileName(datasetId, key);
+        const info = { key, kind, datasetId, params, contentHash, version, createdAt: Date.now() };
+        const data = zlib.gzipSync(JSON.stringify({ ...info, result }));
+        fs.mkdirSync(dir, { recursive: true });
+        const tmp = path.join(dir, `${name}.tmp`);
+        fs.writeFileSync(tmp, data);
+        fs.renameSync(tmp, path.join(dir, name));
+        const previous = loadIndex().get(name);
+        if (previous) totalBytes -= previous.bytes;
+        index.set(name, { bytes: data.length, usedAt: Date.now() });
+        totalBytes += data.length;
+        meta.set(name, info);
+        counters.writes++;
+        evict();
+        return true;
+    };
+
+    /**
+     * Drop-in wrapper for a compute-pool run: answers what it can from the
+     * cache, sends the rest to runJobs and stores their successful results.
+     * Cached results carry `cached: true`.
+     */
+    const runJobs = async (jobs, run) => {
+        const db = getDb();
+        const hashes = new Map(); // `${datasetId}|${limit}` -> content hash (one read per dataset)
+        const hashFor = (job) => {
+            const k = `${job.datasetId}|${job.limit || 0}`;
+            if (!hashes.has(k)) {
+                try { hashes.set(k, datasetContentHash(db, job.datasetId, job.limit)); } catch (e) { hashes.set(k, null); }
+            }
+            return hashes.get(k);
+        };
+        const results = new Array(jobs.length);
+        const pending = [];
+        jobs.forEach((job, i) => {
+            const kind = db ? jobKind(job) : null;
+            const contentHash = kind ? hashFor(job) : null;
+            if (!contentHash) { pending.push({ i, job }); return; }
+            const { id, ...params } = job;
+            const hit = get(kind, job.datasetId, params, contentHash);
+            if (hit) results[i] = { ...hit, id, cached: true };
+            else pending.push({ i, job, kind, params, contentHash });
+        });
+        if (pending.length) {
+            const fresh = await run(pending.map(p => p.job));
+            pending.forEach((p, n) => {
+                const result = fresh[n];
+                results[p.i] = result;
+                if (p.kind && result && result.success) {
+                    const { id, ...stored } = result;
+                    try { set(p.kind, p.job.datasetId, p.params, p.contentHash, stored); } catch (e) {}
+                }
+            });
+        }
+        return results;
+    };
+
+    /**
+     * Caches one full-history computation over a dataset: `compute()` runs
+     * only on a miss and its result is stored. Returns { result, cached }.
+     */
+    const memoize = async (kind, datasetId, params, compute) => {
+        const db = getDb();
+        let contentHash = null;
+        try { contentHash = db ? datasetContentHash(db, datasetId) : null; } catch (e) {}
+        const hit = contentHash ? get(kind, datasetId, params, contentHash) : null;
+        if (hit) return { result: hit, cached: true };
+        const result = await compute();
+        if (contentHash && result != null) {
+            try { set(kind, datasetId, params, contentHash, result); } catch (e) {}
+        }
+        return { result, cached: false };
+    };
+
+    const describe = (name) => {
+        const info = meta.get(name) || (readEntry(name) && meta.get(name));
+        if (!info) return null;
+        const entry = index.get(name);
+        return { key: info.key, kind: info.kind, datasetId: info.datasetId, params: info.params, current: info.version === version, bytes: entry.bytes, createdAt: info.createdAt, usedAt: Math.round(entry.usedAt) };
+    };
+
+    /**
+     * options: { datasetId?, kind?, limit? } -> entries, most recently used first.
+     */
+    const list = ({ datasetId = null, kind = null, limit = DEFAULT_LIST_LIMIT } = {}) => {
+        const tag = datasetId ? `${datasetTag(datasetId)}-` : null;
+        const names = Array.from(loadIndex().entries())
+            .filter(([name]) => !tag || name.startsWith(tag))
+            .sort((a, b) => b[1].usedAt - a[1].usedAt)
+            .map(([name]) => name);
+        const max = Math.max(1, Math.min(5000, Number(limit) || DEFAULT_LIST_LIMIT));
+        const out = [];
+        for (const name of names) {
+            if (out.length >= max) break;
+            const entry = describe(name);
+            if (entry && (!kind || entry.kind === kind) && (!datasetId || entry.datasetId === datasetId)) out.push(entry);
+        }
+        return out;
+    };
+
+    const stats = () => {
+        loadIndex();
+        return { dir, version, entries: index.size, bytes: totalBytes, maxBytes, ...counters };
+    };
+
+    /**
+     * Removes entries. options: { datasetId?, kind?, key?, stale? } — stale
+     * drops only entries written by another code version; no options clears
+     * everything. Returns the number removed.
+     */
+    const invalidate = ({ datasetId = null, kind = null, key = null, stale = false } = {}) => {
+        const tag = datasetId ? `${datasetTag(datasetId)}-` : null;
+        let removed = 0;
+        Array.from(loadIndex().keys()).forEach((name) => {
+            if (tag && !name.startsWith(tag)) return;
+            if (key && !name.endsWith(`-${key}${FILE_SUFFIX}`)) return;
+            if (kind || stale || datasetId) {
+                const info = meta.get(name) || (readEntry(name) && meta.get(name));
+                if (!info) { removed++; return; } // unreadable entries were dropped by readEntry
+                if (kind && info.kind !== kind) return;
+                if (stale && info.version === version) return;
+                if (datasetId && info.datasetId !== datasetId) return;
+            }
+            removeFile(name);
+            removed++;
+        });
+        return removed;
+    };
+
+    return { runJobs, memoize, get, set, list, stats, invalidate };
+};
+
+module.exports = { KINDS, DEFAULT_MAX_BYTES, codeVersion, datasetContentHash, createComputeCache };
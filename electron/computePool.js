@@ -0,0 +1,124 @@
+
+const os = require('os');
+const { Worker } = require('worker_threads');
+
+// --- COMPUTE POOL ---
+// Fixed-size set of compute workers with a shared batch queue. Workers are
+// spawned lazily and kept warm between calls; `resize` drains idle workers so
+// a new thread limit takes effect without interrupting running batches.
+
+const defaultThreadCount = () => Math.max(1, os.cpus().length - 1);
+
+// batchOptions() -> extra fields sent with every batch (e.g. feature flags)
+const createComputePool = ({ workerPath, dbPath, threads = defaultThreadCount(), batchOptions = () => ({}) }) => {
+    let size = threads;
+    const idle = [];
+    const busy = new Set();
+    const queue = []; // { jobs, resolve, reject }
+    let nextBatchId = 1;
+    const memoryRequests = new Map(); // memoryId -> resolve
+    let nextMemoryId = 1;
+
+    const spawn = () => {
+        const worker = new Worker(workerPath);
+        worker.on('error', (err) => {
+            busy.delete(worker);
+            const slot = idle.indexOf(worker);
+            if (slot !== -1) idle.splice(slot, 1);
+            const pending = worker.pending;
+            worker.pending = null;
+            if (pending) pending.reject(err);
+            pump();
+        });
+        worker.on('message', ({ batchId, results, memoryId, memory }) => {
+            if (memoryId != null) {
+                const answer = memoryRequests.get(memoryId);
+                if (answer) answer(memory);
+                return;
+            }
+            const pending = worker.pending;
+            if (!pending || pending.batchId !== batchId) return;
+            worker.pending = null;
+            busy.delete(worker);
+            if (idle.length + busy.size < size) idle.push(worker);
+            else worker.terminate();
+            pending.resolve(results);
+            pump();
+        });
+        return worker;
+    };
+
+    const pump = () => {
+        while (queue.length && (idle.length || busy.size < size)) {
+            const worker = idle.pop() || spawn();
+            const batch = queue.shift();
+            batch.batchId = nextBatchId++;
+            worker.pending = batch;
+            busy.add(worker);
+            worker.postMessage({ ...batchOptions(), batchId: batch.batchId, dbPath, jobs: batch.jobs });
+        }
+    };
+
+    const runBatch = (jobs) => new Promise((resolve, reject) => {
+        queue.push({ jobs, resolve, reject });
+        pump();
+    });
+
+    /**
+     * Runs jobs across the pool. Jobs for the same dataset stay in one batch
+     * so its bars are read once; batches are then dealt out round-robin.
+     */
+    const run = async (jobs) => {
+        const byDataset = new Map();
+        jobs.forEach((job) => {
+            if (!byDataset.has(job.datasetId)) byDataset.set(job.datasetId, []);
+            byDataset.get(job.datasetId).push(job);
+        });
+        const groups = Array.from(byDataset.values());
+        const batchCount = Math.max(1, Math.min(size, groups.length));
+        const batches = Array.from({ length: batchCount }, () => []);
+        groups.sort((a, b) => b.length - a.length).forEach((group, i) => batches[i % batchCount].push(...group));
+
+        const settled = await Promise.all(batches.filter(b => b.length).map(batch => runBatch(batch)
+            .catch(err => batch.map(j => ({ id: j.id, success: false, error: err.message })))));
+        const byId = new Map(settled.flat().map(r => [r.id, r]));
+        return jobs.map(j => byId.get(j.id) || { id: j.id, success: false, error: 'No result returned' });
+    };
+
+    /**
+     * Heap usage of every live worker. Idle workers are asked directly; busy
+     * ones would only answer after their batch, so they're listed without
+     * figures. Workers that don't answer within timeoutMs report null.
+     */
+    const memory = (timeoutMs = 1000) => Promise.all([
+        ...idle.map(worker => new Promise((resolve) => {
+            const memoryId = nextMemoryId++;
+            const done = (usage) => {
+                clearTimeout(timer);
+                memoryRequests.delete(memoryId);
+                resolve({ threadId: worker.threadId, busy: false, ...(usage || { heapUsed: null, heapTotal: null }) });
+            };
+            const timer = setTimeout(() => done(null), timeoutMs);
+            memoryRequests.set(memoryId, done);
+            worker.postMessage({ memoryId });
+        })),
+        ...Array.from(busy, worker => ({ threadId: worker.threadId, busy: true, heapUsed: null, heapTotal: null }))
+    ]);
+
+    const resize = (threadCount) => {
+        size = Math.max(1, Math.floor(threadCount));
+        while (idle.length + busy.size > size && idle.length) idle.pop().terminate();
+        pump();
+    };
+
+    const destroy = () => {
+        idle.splice(0).forEach(w => w.terminate());
+        busy.forEach(w => w.terminate());
+        busy.clear();
+        queue.splice(0).forEach(b => b.reject(new Error('Compute pool shut down')));
+    };
+
+    return { run, resize, destroy, memory, getSize: () => size, stats: () => ({ threads: size, idle: idle.length, busy: busy.size, queued: queue.length }) };
+};
+
+module.exports = { createComputePool, defaultThreadCount };
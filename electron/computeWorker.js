@@ -0,0 +1,100 @@
+
+const v8 = require('v8');
+const { parentPort } = require('worker_threads');
+const Database = require('better-sqlite3');
+const { computeIndicator } = require('./indicators');
+const { parseExpression, evaluateExpression, evaluateSeries } = require('./expressions');
+const { computeHeatmapCell } = require('./heatmap');
+const { setFastAggregate } = require('./fastAggregate');
+
+// Compute worker: receives { batchId, dbPath, jobs: [{ id, datasetId, indicator, params, limit? }] }
+// and answers { batchId, results } with one entry per job. Bars are loaded once
+// per (dataset, limit) within a batch; the pool groups jobs by dataset for that reason.
+// Screen jobs ({ id, datasetId, expression, limit }) evaluate an indicator
+// expression on the last bar instead and answer { matched, timestamp, values }.
+// Custom series jobs ({ id, datasetId, series, limit? }) evaluate a value
+// expression at every bar and answer a series like indicator jobs.
+// Heatmap jobs ({ id, datasetId, heatmap, limit, tail? }) answer one cell
+// { value, close, timestamp }, plus the bars themselves when tail is set.
+
+let db = null;
+let dbPathOpen = null;
+
+const openDb = (dbPath) => {
+    if (db && dbPathOpen === dbPath) return db;
+    if (db) db.close();
+    db = new Database(dbPath, { readonly: true, fileMustExist: true });
+    dbPathOpen = dbPath;
+    return db;
+};
+
+const loadBars = (conn, datasetId, limit) => {
+    const idx = String(datasetId).lastIndexOf(':');
+    if (idx <= 0) throw new Error(`Invalid dataset id: ${datasetId}`);
+    const symbol = datasetId.slice(0, idx);
+    const timeframe = datasetId.slice(idx + 1);
+    if (limit) {
+        return conn.prepare('SELECT timestamp, open, high, low, close, volume FROM market_data WHERE symbol = ? AND timeframe = ? ORDER BY timestamp DESC LIMIT ?')
+            .all(symbol, timeframe, limit).reverse();
+    }
+    return conn.prepare('SELECT timestamp, open, high, low, close, volume FROM market_data WHERE symbol = ? AND timeframe = ? ORDER BY timestamp ASC')
+        .all(symbol, timeframe);
+};
+
+// Batches also carry `fastAggregate` (fastAggregate.js, default on)
+
+// { memoryId } asks for this thread's heap (for the memory report); bars are
+// only held for the length of a batch, so the heap and the open connection are all there is
+const answerMemory = (memoryId) => {
+    const heap = v8.getHeapStatistics();
+    parentPort.postMessage({ memoryId, memory: { heapUsed: heap.used_heap_size, heapTotal: heap.total_heap_size, external: heap.external_memory, dbOpen: !!db } });
+};
+
+parentPort.on('message', ({ batchId, dbPath, jobs, memoryId, fastAggregate }) => {
+    if (memoryId != null) return answerMemory(memoryId);
+    setFastAggregate(fastAggregate);
+    let conn;
+    try {
+        conn = openDb(dbPath);
+    } catch (err) {
+        parentPort.postMessage({ batchId, results: jobs.map(j => ({ id: j.id, success: false, error: `Database open failed: ${err.message}` })) });
+        return;
+    }
+
+    const barCache = new Map();
+    const compiled = new Map(); // expression source -> parsed (shared by every dataset of a screen)
+    const results = jobs.map((job) => {
+        const start = Date.now();
+        try {
+            const key = `${job.datasetId}|${job.limit || 0}`;
+            if (!barCache.has(key)) barCache.set(key, loadBars(conn, job.datasetId, job.limit));
+            const bars = barCache.get(key);
+            if (job.heatmap) {
+                const source = job.heatmap.script;
+                const key = `value:${source}`;
+                if (source && !compiled.has(key)) compiled.set(key, parseExpression(source, { condition: false }));
+                const cell = computeHeatmapCell(bars, job.heatmap, source ? compiled.get(key) : null);
+                return { id: job.id, success: true, ...cell, tail: job.tail ? bars : undefined, durationMs: Date.now() - start };
+            }
+            if (job.expression) {
+                if (!compiled.has(job.expression)) compiled.set(job.expression, parseExpression(job.expression));
+                return { id: job.id, success: true, ...evaluateExpression(compiled.get(job.expression), bars), bars: bars.length, durationMs: Date.now() - start };
+            }
+            let values;
+            if (job.series) {
+                const key = `value:${job.series}`;
+                if (!compiled.has(key)) compiled.set(key, parseExpression(job.series, { condition: false }));
+                values = evaluateSeries(compiled.get(key), bars);
+            } else {
+                values = computeIndicator(job.indicator, bars, job.params);
+            }
+            // [timestamp, value] pairs, warm-up nulls dropped
+            const series = [];
+            for (let i = 0; i < bars.length; i++) if (values[i] != null) series.push([bars[i].timestamp, values[i]]);
+            return { id: job.id, success: true, series, durationMs: Date.now() - start };
+        } catch (err) {
+            return { id: job.id, success: false, error: err.message };
+        }
+    });
+    parentPort.postMessage({ batchId, results });
+});
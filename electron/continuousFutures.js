@@ -0,0 +1,199 @@
+
+const { datasetId, parseDatasetId, insertBars, registerDataset, getDataset } = require('./datasets');
+
+// --- CONTINUOUS FUTURES ---
+// Stitches individual contract datasets (ESH4, ESM4, ...) into one continuous
+// series stored as a derived dataset (source 'continuous'). Each contract is
+// used until its roll, chosen by rule:
+//   date           N days before the front contract's expiry
+//   volume         the first day the next contract trades more than the front
+//   open_interest  the same with open interest (supplied with the spec:
+//                  market_data has no OI column)
+// Crossover rules need `confirmDays` consecutive days and fall back to the
+// date rule when the next contract never takes over. Back-adjustment removes
+// the roll gaps from history: 'difference' shifts older bars by the price gap,
+// 'ratio' scales them (keeps percentage moves, needs positive prices), 'none'
+// leaves the raw prices. Roll markers and the spec are kept in the dataset
+// meta so the chart can draw them and the series can be rebuilt.
+
+const DAY_MS = 86400000;
+const RULES = ['date', 'volume', 'open_interest'];
+const ADJUSTMENTS = ['none', 'difference', 'ratio'];
+const DEFAULT_ROLL = { rule: 'volume', daysBeforeExpiry: 5, confirmDays: 1 };
+
+const loadBars = (db, id) => {
+    const { symbol, timeframe } = parseDatasetId(id);
+    return db.prepare('SELECT timestamp, open, high, low, close, volume FROM market_data WHERE symbol = ? AND timeframe = ? ORDER BY timestamp')
+        .all(symbol, timeframe);
+};
+
+const dayOf = (ts) => Math.floor(ts / DAY_MS);
+
+const dailyVolume = (bars) => {
+    const days = new Map();
+    bars.forEach(bar => days.set(dayOf(bar.timestamp), (days.get(dayOf(bar.timestamp)) || 0) + (bar.volume || 0)));
+    return days;
+};
+
+// [[timestamp, value], ...] -> day -> last value of the day
+const dailySeries = (pairs = []) => {
+    const days = new Map();
+    pairs.slice().sort((a, b) => a[0] - b[0]).forEach(([ts, value]) => days.set(dayOf(ts), Number(value)));
+    return days;
+};
+
+const normalizeSpec = (db, spec = {}) => {
+    if (!Array.isArray(spec.contracts) || spec.contracts.length < 2) throw new Error('At least two contract datasets are required');
+    const roll = { ...DEFAULT_ROLL, ...(spec.roll || {}) };
+    if (!RULES.includes(roll.rule)) throw new Error(`Unknown roll rule: ${roll.rule}`);
+    const adjustment = spec.adjustment || 'difference';
+    if (!ADJUSTMENTS.includes(adjustment)) throw new Error(`Unknown adjustment: ${adjustment}`);
+
+    let timeframe = null;
+    const contracts = spec.contracts.map((entry) => {
+        const contract = typeof entry === 'string' ? { datasetId: entry } : { ...entry };
+        const dataset = getDataset(db, contract.datasetId);
+        if (!dataset) throw new Error(`Dataset not found: ${contract.datasetId}`);
+        if (timeframe && dataset.timeframe !== timeframe) throw new Error(`All contracts must share one timeframe (${contract.datasetId} is ${dataset.timeframe})`);
+        timeframe = dataset.timeframe;
+        const meta = dataset.meta || {};
+        // The last traded bar stands in for an unknown expiry
+        contract.expiry = Number(contract.expiry ?? meta.expiry ?? dataset.lastTimestamp);
+        contract.openInterest = contract.openInterest || meta.openInterest || null;
+        if (roll.rule === 'open_interest' && !contract.openInterest) throw new Error(`Open interest is missing for ${contract.datasetId}`);
+        return contract;
+    }).sort((a, b) => a.expiry - b.expiry);
+
+    const root = parseDatasetId(contracts[0].datasetId).symbol.replace(/[FGHJKMNQUVXZ]\d{1,2}$/, '');
+    return { symbol: spec.symbol || `${root}1!`, timeframe, contracts, roll, adjustment };
+};
+
+// First bar timestamp of `next` at which it takes over from `front`
+const findRoll = (front, next, roll, notBefore) => {
+    const dateRoll = front.expiry - roll.daysBeforeExpiry * DAY_MS;
+    const firstAt = (ts) => {
+        const bar = next.bars.find(b => b.timestamp >= Math.max(ts, notBefore));
+        return bar ? bar.timestamp : null;
+    };
+    if (roll.rule === 'date') return { timestamp: firstAt(dateRoll), reason: 'date' };
+
+    const frontDays = roll.rule === 'volume' ? dailyVolume(front.bars) : dailySeries(front.openInterest);
+    const nextDays = roll.rule === 'volume' ? dailyVolume(next.bars) : dailySeries(next.openInterest);
+    const days = Array.from(new Set([...frontDays.keys(), ...nextDays.keys()])).sort((a, b) => a - b)
+        .filter(day => day * DAY_MS >= notBefore - DAY_MS && day * DAY_MS <= front.expiry);
+    let streak = 0;
+    for (const day of days) {
+        streak = (nextDays.get(day) || 0) > (frontDays.get(day) || 0) ? streak + 1 : 0;
+        // Decided on the close of the confirming day: switch from the next day on
+        if (streak >= Math.max(1, roll.confirmDays)) return { timestamp: firstAt((day + 1) * DAY_MS), reason: roll.rule };
+    }
+    return { timestamp: firstAt(Math.min(dateRoll, front.expiry)), reason: 'date (fallback)' };
+};
+
+// Close of a series at or before ts
+const closeAt = (bars, ts) => {
+    let found = null;
+    for (const bar of bars) {
+        if (bar.timestamp > ts) break;
+        found = bar;
+    }
+    return found ? found.close : null;
+};
+
+/**
+ * Builds the continuous series without storing it.
+ * Returns { symbol, timeframe, bars, rolls, spec }.
+ */
+const buildContinuous = (db, rawSpec) => {
+    const spec = normalizeSpec(db, rawSpec);
+    const contracts = spec.contracts.map(c => ({ ...c, bars: loadBars(db, c.datasetId) })).filter(c => c.bars.length);
+    if (contracts.length < 2) throw new Error('At least two contracts with data are required');
+
+    const segments = [];
+    const rolls = [];
+    let start = contracts[0].bars[0].timestamp;
+    for (let i = 0; i < contracts.length; i++) {
+        const front = contracts[i];
+        const next = contracts[i + 1];
+        const found = next ? findRoll(front, next, spec.roll, start) : null;
+        const end = found && found.timestamp != null ? found.timestamp : Infinity;
+        segments.push({ contract: front, from: start, to: end });
+        if (end === Infinity) break;
+        // Gap measured on the last bar both contracts traded before the roll
+        const ref = front.bars.filter(b => b.timestamp < end).pop();
+        const frontClose = ref ? ref.close : null;
+        const nextClose = ref ? closeAt(next.bars, ref.timestamp) : null;
+        rolls.push({
+            timestamp: end,
+            from: front.datasetId,
+            to: next.datasetId,
+            reason: found.reason,
+            frontClose,
+            nextClose,
+            gap: frontClose != null && nextClose != null ? nextClose - frontClose : 0,
+            ratio: frontClose > 0 && nextClose > 0 ? nextClose / frontClose : 1
+        });
+        start = end;
+    }
+
+    // Adjust older segments by every roll after them
+    let offset = 0;
+    let factor = 1;
+    const adjustments = new Array(segments.length).fill(null);
+    for (let i = segments.length - 1; i >= 0; i--) {
+        adjustments[i] = { offset, factor };
+        const roll = rolls[i - 1];
+        if (!roll) continue;
+        if (spec.adjustment === 'difference') offset += roll.gap;
+        if (spec.adjustment === 'ratio') {
+            if (!(roll.frontClose > 0 && roll.nextClose > 0)) throw new Error(`Ratio adjustment needs positive prices at the ${roll.from} roll`);
+            factor *= roll.ratio;
+        }
+    }
+
+    const bars = [];
+    segments.forEach((segment, i) => {
+        const { offset: add, factor: mult } = adjustments[i];
+        const adjust = (price) => price * mult + add;
+        segment.contract.bars.forEach((bar) => {
+            if (bar.timestamp < segment.from || bar.timestamp >= segment.to) return;
+            bars.push({ timestamp: bar.timestamp, open: adjust(bar.open), high: adjust(bar.high), low: adjust(bar.low), close: adjust(bar.close), volume: bar.volume });
+        });
+    });
+
+    const storedSpec = {
+        contracts: spec.contracts.map(({ datasetId, expiry, openInterest }) => ({ datasetId, expiry, ...(openInterest ? { openInterest } : {}) })),
+        roll: spec.roll,
+        adjustment: spec.adjustment,
+        symbol: spec.symbol
+    };
+    return { symbol: spec.symbol, timeframe: spec.timeframe, bars, rolls, spec: storedSpec };
+};
+
+/**
+ * Builds and stores the continuous dataset, replacing a previous build; real
+ * data under the same symbol / timeframe is never overwritten.
+ */
+const stitchContinuous = (db, rawSpec) => {
+    const result = buildContinuous(db, rawSpec);
+    const existing = getDataset(db, datasetId(result.symbol, result.timeframe));
+    if (existing && existing.source !== 'continuous') throw new Error(`${existing.id} already holds ${existing.source} data`);
+    db.transaction(() => {
+        db.prepare('DELETE FROM market_data WHERE symbol = ? AND timeframe = ?').run(result.symbol, result.timeframe);
+        insertBars(db, result.symbol, result.timeframe, result.bars);
+    })();
+    const dataset = registerDataset(db, result.symbol, result.timeframe, 'continuous', {
+        continuous: result.spec,
+        rolls: result.rolls.map(({ timestamp, from, to, reason, gap, ratio }) => ({ timestamp, from, to, reason, gap, ratio }))
+    });
+    return { dataset, rolls: result.rolls, bars: result.bars.length };
+};
+
+// Re-stitches from the spec saved with the dataset (e.g. after new contract data arrived)
+const rebuildContinuous = (db, id) => {
+    const dataset = getDataset(db, id);
+    if (!dataset || !dataset.meta || !dataset.meta.continuous) throw new Error(`Not a continuous dataset: ${id}`);
+    return stitchContinuous(db, dataset.meta.continuous);
+};
+
+module.exports = { buildContinuous, stitchContinuous, rebuildContinuous };
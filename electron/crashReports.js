@@ -0,0 +1,151 @@
+
+const crypto = require('crypto');
+const fs = require('fs');
+const path = require('path');
+const { request } = require('./network');
+const { redact } = require('./diagnostics');
+
+// --- CRASH REPORTS ---
+// Main-process exceptions, renderer / child process crashes and native
+// minidumps (Crashpad, written to crashDumpsDir with uploading off) become
+// JSON reports in reportsDir. A report holds the error and stack, app and OS
+// versions and the last log lines as event names and levels only: log data,
+// charts, trades and settings never go in. Nothing leaves the machine unless
+// the user agrees to send a report to the configured endpoint; declined
+// reports are deleted.
+
+const LOG_LINES = 50;
+const MAX_REPORTS = 20;
+const MAX_DUMP_BYTES = 20 * 1024 * 1024;
+const DEFAULT_CONFIG = { endpoint: '', promptOnStart: true };
+
+/**
+ * reportsDir / crashDumpsDir: where reports and minidumps live. getLogs()
+ * returns recent log entries, newest first. environment: { appVersion, platform, ... }.
+ */
+const createCrashReporter = ({ reportsDir, crashDumpsDir, getLogs = () => [], environment = {}, loadConfig = () => null, saveConfig = () => {} }) => {
+    const getConfig = () => ({ ...DEFAULT_CONFIG, ...(loadConfig() || {}) });
+
+    const reportPath = (id) => path.join(reportsDir, `${id}.json`);
+
+    const readReport = (id) => {
+        try { return JSON.parse(fs.readFileSync(reportPath(id), 'utf8')); } catch (e) { return null; }
+    };
+
+    const saveReport = (report) => {
+        fs.mkdirSync(reportsDir, { recursive: true });
+        fs.writeFileSync(reportPath(report.id), JSON.stringify(report, null, 2));
+        // Keep the newest MAX_REPORTS
+        listReports().slice(MAX_REPORTS).forEach(old => deleteReport(old.id));
+        return report;
+    };
+
+    const lastLogLines = () => getLogs().slice(0, LOG_LINES).map(entry => ({ event: entry.event, level: entry.level, timestamp: entry.timestamp }));
+
+    /**
+     * kind: 'main-exception' | 'unhandled-rejection' | 'renderer-gone' | 'child-gone' | 'native'.
+     * Synchronous so it still lands on disk when called from a dying process.
+     */
+    const recordCrash = (kind, error = null, details = {}) => {
+        const report = {
+            id: crypto.randomUUID(),
+            kind,
+            timestamp: Date.now(),
+            message: error ? String(error.message || error) : null,
+            stack: error && error.stack ? redact(String(error.stack)) : null,
+            details: redact(details),
+            environment: { ...environment, uptimeSec: Math.round(process.uptime()) },
+            logs: lastLogLines(),
+            minidump: details.minidump || null,
+            status: 'pending'
+        };
+        try {
+            return saveReport(report);
+        } catch (e) {
+            return null;
+        }
+    };
+
+    const listDumps = () => {
+        const found = [];
+        const walk = (dir, depth) => {
+            let entries = [];
+            try { entries = fs.readdirSync(dir, { withFileTypes: true }); } catch (e) { return; }
+            entries.forEach((entry) => {
+                const full = path.join(dir, entry.name);
+                if (entry.isDirectory() && depth < 2) walk(full, depth + 1);
+                else if (entry.isFile() && entry.name.endsWith('.dmp')) found.push(full);
+            });
+        };
+        if (crashDumpsDir) walk(crashDumpsDir, 0);
+        return found;
+    };
+
+    // Minidumps no report points at yet become 'native' reports
+    const collectMinidumps = () => {
+        const known = new Set(listReports().map(r => r.minidump).filter(Boolean));
+        return listDumps().filter(file => !known.has(path.basename(file))).map((file) => {
+            const stat = fs.statSync(file);
+            return recordCrash('native', null, { minidump: path.basename(file), dumpBytes: stat.size, crashedAt: stat.mtimeMs });
+        }).filter(Boolean);
+    };
+
+    // Newest first, without the log lines
+    const listReports = () => {
+        let files = [];
+        try { files = fs.readdirSync(reportsDir).filter(name => name.endsWith('.json')); } catch (e) { return []; }
+        return files.map(name => readReport(name.slice(0, -5))).filter(Boolean)
+            .sort((a, b) => b.timestamp - a.timestamp)
+            .map(({ logs, ...summary }) => ({ ...summary, logLines: logs ? logs.length : 0 }));
+    };
+
+    const pendingReports = () => listReports().filter(r => r.status === 'pending');
+
+    const minidumpFile = (name) => listDumps().find(file => path.basename(file) === name) || null;
+
+    const deleteReport = (id) => {
+        const report = readReport(id);
+        if (!report) return false;
+        const dump = report.minidump ? minidumpFile(report.minidump) : null;
+        if (dump) fs.rmSync(dump, { force: true });
+        fs.rmSync(reportPath(id), { force: true });
+        return true;
+    };
+
+    // Exactly what submitReport would send
+    const previewReport = (id) => {
+        const report = readReport(id);
+        if (!report) throw new Error(`Crash report not found: ${id}`);
+        const { status, ...payload } = report;
+        return payload;
+    };
+
+    // Only on explicit user consent; the minidump goes along base64-encoded
+    const submitReport = async (id) => {
+        const { endpoint } = getConfig();
+        if (!endpoint) throw new Error('No crash report endpoint is configured');
+        const payload = previewReport(id);
+        const dump = payload.minidump ? minidumpFile(payload.minidump) : null;
+        if (dump && fs.statSync(dump).size <= MAX_DUMP_BYTES) payload.minidumpBase64 = fs.readFileSync(dump).toString('base64');
+        const response = await request('crash-reports', endpoint, {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify(payload)
+        }, { retries: 1 });
+        if (!response.ok) throw new Error(`Crash report upload failed: HTTP ${response.status}`);
+        deleteReport(id);
+        return true;
+    };
+
+    const configure = (updates = {}) => {
+        const next = { ...getConfig(), ...updates };
+        if (next.endpoint && !/^https:\/\//i.test(next.endpoint)) throw new Error('The crash report endpoint must be an https:// URL');
+        Object.keys(next).forEach((key) => { if (!(key in DEFAULT_CONFIG)) delete next[key]; });
+        saveConfig(next);
+        return next;
+    };
+
+    return { recordCrash, collectMinidumps, listReports, pendingReports, previewReport, submitReport, deleteReport, getConfig, configure };
+};
+
+module.exports = { createCrashReporter };
@@ -0,0 +1,240 @@
+
+const { AppError } = require('./appErrors');
+const { TIMEFRAME_MS, datasetId, parseDatasetId } = require('./datasets');
+const { INDICATORS, resolveParams, computeIndicator } = require('./indicators');
+const { parseExpression, evaluateSeries } = require('./expressions');
+
+// --- CROSSHAIR CONTEXT ---
+// Answers "what is under the cursor on every timeframe" for linked
+// multi-timeframe charts in one call: for a symbol and a timestamp, the bar
+// containing it on each timeframe plus the requested indicator values at that
+// bar. Each dataset keeps a window of bars around the last cursor position
+// (WARMUP_BARS before, AHEAD_BARS after) with its indicator series computed
+// once, so a mouse move is a binary search, not a query; the window reloads
+// when the cursor leaves it, when the registry row changes (an import or bar
+// edit) or when fewer than MIN_WARMUP bars precede the cursor. Recursive
+// indicators (EMA, RSI, MACD) therefore start from the window, not the first
+// bar of history — over at least MIN_WARMUP bars the difference is far below
+// display precision. Live bars extend windows that reach the end of history.
+//
+// Subscriptions are the streaming variant: the renderer posts cursor moves
+// fire-and-forget and contexts are pushed back, coalesced so only the latest
+// position per subscription is computed; a subscription whose cursor is on the
+// latest bar (timestamp null follows it) is re-pushed as live bars arrive.
+
+const WARMUP_BARS = 1000;
+const MIN_WARMUP = 500;
+const AHEAD_BARS = 1000;
+const MAX_WINDOWS = 64;
+const MAX_INDICATORS = 20;
+
+// Last index with bars[i].timestamp <= ts, -1 when ts precedes them all
+const barIndexAt = (bars, ts) => {
+    let lo = 0;
+    let hi = bars.length - 1;
+    let found = -1;
+    while (lo <= hi) {
+        const mid = (lo + hi) >> 1;
+        if (bars[mid].timestamp <= ts) { found = mid; lo = mid + 1; } else hi = mid - 1;
+    }
+    return found;
+};
+
+/**
+ * indicators: [{ id?, indicator, params? } | { id?, series }] for every
+ * timeframe, or { [timeframe]: [...] } per timeframe. `id` names the value in
+ * the result (default: indicator or series source).
+ */
+const normalizeIndicators = (list) => {
+    if (!Array.isArray(list)) return [];
+    if (list.length > MAX_INDICATORS) throw new AppError('INVALID_INPUT', `At most ${MAX_INDICATORS} indicators per timeframe`);
+    return list.map((spec) => {
+        if (spec && spec.series) {
+            const compiled = parseExpression(String(spec.series), { condition: false });
+            return { id: String(spec.id || compiled.source), key: `series:${compiled.source}`, compiled };
+        }
+        if (!spec || !INDICATORS[spec.indicator]) throw new AppError('NOT_FOUND', `Unknown indicator: ${spec && spec.indicator}`);
+        const params = resolveParams(spec.indicator, spec.params || {});
+        return { id: String(spec.id || spec.indicator), key: `${spec.indicator}|${JSON.stringify(params)}`, indicator: spec.indicator, params };
+    });
+};
+
+/**
+ * getDb() -> the open database; onContext(owner, context) delivers pushed
+ * contexts for subscriptions.
+ */
+const createCrosshairService = ({ getDb, onContext = () => {} }) => {
+    const windows = new Map(); // dataset id -> { signature, bars, atStart, atEnd, series: Map }
+    const subscriptions = new Map(); // id -> { id, owner, symbol, options, timestamp, scheduled }
+    let nextSubscriptionId = 1;
+
+    const registrySignature = (db, id) => {
+        const row = db.prepare('SELECT row_count, last_ts, updated_at FROM datasets WHERE id = ?').get(id);
+        return row ? `${row.row_count}|${row.last_ts}|${row.updated_at}` : null;
+    };
+
+    const loadWindow = (db, id, ts, signature) => {
+        const { symbol, timeframe } = parseDatasetId(id);
+        const before = db.prepare('SELECT timestamp, open, high, low, close, volume FROM market_data WHERE symbol = ? AND timeframe = ? AND timestamp <= ? ORDER BY timestamp DESC LIMIT ?')
+            .all(symbol, timeframe, ts, WARMUP_BARS).reverse();
+        const after = db.prepare('SELECT timestamp, open, high, low, close, volume FROM market_data WHERE symbol = ? AND timeframe = ? AND timestamp > ? ORDER BY timestamp ASC LIMIT ?')
+            .all(symbol, timeframe, ts, AHEAD_BARS);
+        const win = { signature, bars: before.concat(after), atStart: before.length < WARMUP_BARS, atEnd: after.length < AHEAD_BARS, series: new Map() };
+        windows.delete(id);
+        windows.set(id, win);
+        if (windows.size > MAX_WINDOWS) windows.delete(windows.keys().next().value);
+        return win;
+    };
+
+    const covers = (win, ts) => {
+        const i = barIndexAt(win.bars, ts);
+        if (i < MIN_WARMUP && !win.atStart) return false;
+        return i < win.bars.length - 1 || win.atEnd;
+    };
+
+    const windowFor = (db, id, ts) => {
+        const signature = registrySignature(db, id);
+        const win = windows.get(id);
+        if (win && win.signature === signature && covers(win, ts)) {
+            windows.delete(id);
+            windows.set(id, win);
+            return win;
+        }
+        return loadWindow(db, id, ts, signature);
+    };
+
+    const seriesFor = (win, spec) => {
+        if (!win.series.has(spec.key)) {
+            win.series.set(spec.key, spec.compiled ? evaluateSeries(spec.compiled, win.bars) : computeIndicator(spec.indicator, win.bars, spec.params));
+        }
+        return win.series.get(spec.key);
+    };
+
+    const timeframesOf = (db, symbol, requested) => {
+        const available = db.prepare('SELECT timeframe FROM datasets WHERE symbol = ?').all(symbol).map(r => r.timeframe);
+        const list = Array.isArray(requested) && requested.length ? requested.map(String).filter(tf => available.includes(tf)) : available;
+        return Array.from(new Set(list)).sort((a, b) => (TIMEFRAME_MS[a] || Infinity) - (TIMEFRAME_MS[b] || Infinity));
+    };
+
+    const specsFor = (indicators, timeframe, cache) => {
+        const list = Array.isArray(indicators) ? indicators : ((indicators && indicators[timeframe]) || []);
+        const k = Array.isArray(indicators) ? '*' : timeframe;
+        if (!cache.has(k)) cache.set(k, normalizeIndicators(list));
+        return cache.get(k);
+    };
+
+    /**
+     * timestamp: epoch ms, or null for the latest bar. options: { timeframes?,
+     * indicators? }. Returns { symbol, timestamp, timeframes: [{ timeframe,
+     * datasetId, bar | null, inBar, isLatest, values: { id: number | null } }] }
+     * from the finest timeframe up. inBar is false when the timestamp falls in
+     * a gap after the bar (a closed session, a missing print).
+     */
+    const getContext = (symbol, timestamp, { timeframes = null, indicators = [] } = {}) => {
+        const db = getDb();
+        if (!db) throw new AppError('UNAVAILABLE', 'Database not initialized');
+        if (!symbol) throw new AppError('INVALID_INPUT', 'symbol is required');
+        const ts = timestamp == null ? Number.MAX_SAFE_INTEGER : Number(timestamp);
+        if (!Number.isFinite(ts)) throw new AppError('INVALID_INPUT', 'timestamp must be a number or null');
+        const specCache = new Map();
+        const rows = timeframesOf(db, String(symbol), timeframes).map((timeframe) => {
+            const id = datasetId(String(symbol), timeframe);
+            const specs = specsFor(indicators, timeframe, specCache);
+            const win = windowFor(db, id, ts);
+            const i = barIndexAt(win.bars, ts);
+            const bar = i >= 0 ? win.bars[i] : null;
+            const values = {};
+            specs.forEach((spec) => {
+                const v = i >= 0 ? seriesFor(win, spec)[i] : null;
+                values[spec.id] = v == null || Number.isNaN(v) ? null : v;
+            });
+            const span = TIMEFRAME_MS[timeframe];
+            return {
+                timeframe,
+                datasetId: id,
+                bar: bar ? { ...bar } : null,
+                inBar: !!bar && (timestamp == null || !span || ts < bar.timestamp + span),
+                isLatest: !!bar && win.atEnd && i === win.bars.length - 1,
+                values
+            };
+        });
+        return { symbol: String(symbol), timestamp: timestamp == null ? null : ts, timeframes: rows };
+    };
+
+    const invalidate = (id = null) => {
+        if (id) windows.delete(id);
+        else windows.clear();
+    };
+
+    const flush = (sub) => {
+        sub.scheduled = false;
+        if (!subscriptions.has(sub.id)) return;
+        try {
+            onContext(sub.owner, { subscriptionId: sub.id, ...getContext(sub.symbol, sub.timestamp, sub.options) });
+        } catch (err) {
+            onContext(sub.owner, { subscriptionId: sub.id, symbol: sub.symbol, timestamp: sub.timestamp, error: err.message });
+        }
+    };
+
+    // Only the latest position is computed, however many moves arrive in between
+    const schedule = (sub, timestamp) => {
+        sub.timestamp = timestamp == null ? null : Number(timestamp);
+        if (sub.scheduled) return;
+        sub.scheduled = true;
+        setImmediate(() => flush(sub));
+    };
+
+    // liveFeed 'bar' event: extends windows that reach the latest bar, then
+    // re-pushes subscriptions whose cursor sits on or after it
+    const handleBar = ({ symbol, timeframe, bar }) => {
+        const id = datasetId(symbol, timeframe);
+        const win = windows.get(id);
+        if (win && win.atEnd) {
+            const last = win.bars[win.bars.length - 1];
+            const fresh = { timestamp: bar.timestamp, open: bar.open, high: bar.high, low: bar.low, close: bar.close, volume: bar.volume || 0 };
+            if (last && last.timestamp === bar.timestamp) win.bars[win.bars.length - 1] = fresh;
+            else if (!last || bar.timestamp > last.timestamp) win.bars.push(fresh);
+            win.series.clear();
+        }
+        subscriptions.forEach((sub) => {
+            if (sub.symbol === symbol && (sub.timestamp == null || sub.timestamp >= bar.timestamp)) schedule(sub, sub.timestamp);
+        });
+    };
+
+    /**
+     * owner: who receives the pushes (a webContents id). Returns the
+     * subscription id and the context at options.timestamp (null = latest).
+     */
+    const subscribe = (owner, symbol, options = {}) => {
+        const { timestamp = null, ...rest } = options || {};
+        const context = getContext(symbol, timestamp, rest);
+        const id = `xh${nextSubscriptionId++}`;
+        subscriptions.set(id, { id, owner, symbol: String(symbol), options: rest, timestamp: context.timestamp, scheduled: false });
+        return { subscriptionId: id, context };
+    };
+
+    const move = (id, timestamp) => {
+        const sub = subscriptions.get(id);
+        if (!sub) return false;
+        schedule(sub, timestamp);
+        return true;
+    };
+
+    const unsubscribe = id => subscriptions.delete(id);
+
+    const dropOwner = (owner) => {
+        subscriptions.forEach((sub, id) => { if (sub.owner === owner) subscriptions.delete(id); });
+    };
+
+    // What the service keeps resident, for the memory report
+    const stats = () => {
+        let bars = 0;
+        let series = 0;
+        windows.forEach((win) => { bars += win.bars.length; series += win.series.size; });
+        return { windows: windows.size, maxWindows: MAX_WINDOWS, bars, series, subscriptions: subscriptions.size };
+    };
+
+    return { getContext, subscribe, move, unsubscribe, dropOwner, handleBar, invalidate, stats };
+};
+
+module.exports = { createCrosshairService };
@@ -0,0 +1,161 @@
+
+const crypto = require('crypto');
+const { TIMEFRAME_MS } = require('./datasets');
+
+// --- DATASET ANNOTATIONS ---
+// Marks on the data rather than on a chart: "earnings", "stopped out here",
+// "halted" are attached to (symbol, time) — optionally a span to endTime and
+// a price — not to a drawing's pixel space, so they show on every chart and
+// timeframe of the symbol. projectAnnotations() snaps them to the bars of one
+// timeframe: the stored bar that contains the time when the series has bars,
+// otherwise the timeframe's bucket (1W from Monday, 1mo from the 1st, UTC).
+
+const KINDS = ['note', 'earnings', 'dividend', 'split', 'news', 'trade', 'event'];
+const MAX_LABEL = 120;
+const MAX_TEXT = 4000;
+const DEFAULT_LIMIT = 1000;
+const MAX_LIMIT = 10000;
+const WEEK_OFFSET_MS = 4 * 86400000; // 1970-01-01 was a Thursday; weeks start on Monday
+
+const initializeDatasetAnnotationTable = (db) => {
+    db.exec(`
+        CREATE TABLE IF NOT EXISTS dataset_annotations (
+            id TEXT PRIMARY KEY,
+            symbol TEXT,
+            time INTEGER,
+            end_time INTEGER,
+            price REAL,
+            kind TEXT,
+            label TEXT,
+            text TEXT,
+            color TEXT,
+            created_at INTEGER,
+            updated_at INTEGER
+        );
+        CREATE INDEX IF NOT EXISTS idx_dataset_annotations ON dataset_annotations (symbol, time);
+    `);
+};
+
+const fromRow = (r) => ({
+    id: r.id, symbol: r.symbol, time: r.time, endTime: r.end_time, price: r.price, kind: r.kind,
+    label: r.label, text: r.text, color: r.color, createdAt: r.created_at, updatedAt: r.updated_at
+});
+
+const cleanSymbol = (symbol) => {
+    const value = String(symbol || '').trim().toUpperCase();
+    if (!value) throw new Error('Symbol is required');
+    return value;
+};
+
+// Validates a full annotation (after merging an update onto the stored one)
+const validate = (a) => {
+    if (!Number.isFinite(a.time)) throw new Error('time must be a timestamp in ms');
+    if (a.endTime != null && (!Number.isFinite(a.endTime) || a.endTime < a.time)) throw new Error('endTime must be a timestamp at or after time');
+    if (a.price != null && !Number.isFinite(a.price)) throw new Error('price must be a number');
+    if (!KINDS.includes(a.kind)) throw new Error(`kind must be one of ${KINDS.join(', ')}`);
+    if (!a.label) throw new Error('label is required');
+    if (a.label.length > MAX_LABEL) throw new Error(`label is limited to ${MAX_LABEL} characters`);
+    if (a.text && a.text.length > MAX_TEXT) throw new Error(`text is limited to ${MAX_TEXT} characters`);
+    if (a.color != null && !/^#[0-9a-f]{6}([0-9a-f]{2})?$/i.test(a.color)) throw new Error('color must be a #rrggbb hex color');
+};
+
+const numberOrNull = (value) => (value == null || value === '' ? null : Number(value));
+
+const getDatasetAnnotation = (db, id) => {
+    const row = db.prepare('SELECT * FROM dataset_annotations WHERE id = ?').get(id);
+    return row ? fromRow(row) : null;
+};
+
+// input: { symbol, time, endTime?, price?, kind?, label, text?, color? }
+const createDatasetAnnotation = (db, input = {}) => {
+    const now = Date.now();
+    const annotation = {
+        id: crypto.randomUUID(),
+        symbol: cleanSymbol(input.symbol),
+        time: Number(input.time),
+        endTime: numberOrNull(input.endTime),
+        price: numberOrNull(input.price),
+        kind: input.kind || 'note',
+        label: String(input.label || '').trim(),
+        text: input.text ? String(input.text) : null,
+        color: input.color || null,
+        createdAt: now,
+        updatedAt: now
+    };
+    validate(annotation);
+    db.prepare(`INSERT INTO dataset_annotations (id, symbol, time, end_time, price, kind, label, text, color, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)`)
+        .run(annotation.id, annotation.symbol, annotation.time, annotation.endTime, annotation.price, annotation.kind, annotation.label, annotation.text, annotation.color, now, now);
+    return annotation;
+};
+
+// patch: any of time, endTime, price, kind, label, text, color (null clears the optional ones)
+const updateDatasetAnnotation = (db, id, patch = {}) => {
+    const current = getDatasetAnnotation(db, id);
+    if (!current) throw new Error(`Annotation not found: ${id}`);
+    const next = { ...current, updatedAt: Date.now() };
+    if ('time' in patch) next.time = Number(patch.time);
+    if ('endTime' in patch) next.endTime = numberOrNull(patch.endTime);
+    if ('price' in patch) next.price = numberOrNull(patch.price);
+    if ('kind' in patch) next.kind = patch.kind;
+    if ('label' in patch) next.label = String(patch.label || '').trim();
+    if ('text' in patch) next.text = patch.text ? String(patch.text) : null;
+    if ('color' in patch) next.color = patch.color || null;
+    validate(next);
+    db.prepare('UPDATE dataset_annotations SET time = ?, end_time = ?, price = ?, kind = ?, label = ?, text = ?, color = ?, updated_at = ? WHERE id = ?')
+        .run(next.time, next.endTime, next.price, next.kind, next.label, next.text, next.color, next.updatedAt, id);
+    return next;
+};
+
+const deleteDatasetAnnotation = (db, id) => db.prepare('DELETE FROM dataset_annotations WHERE id = ?').run(id).changes > 0;
+
+/**
+ * Annotations of a symbol, oldest first. options: { from?, to?, kinds?, limit? };
+ * spans overlapping [from, to] are included.
+ */
+const listDatasetAnnotations = (db, symbol, { from = null, to = null, kinds = null, limit = DEFAULT_LIMIT } = {}) => {
+    const where = ['symbol = ?'];
+    const params = [cleanSymbol(symbol)];
+    if (from != null) { where.push('COALESCE(end_time, time) >= ?'); params.push(Number(from)); }
+    if (to != null) { where.push('time <= ?'); params.push(Number(to)); }
+    if (Array.isArray(kinds) && kinds.length) { where.push(`kind IN (${kinds.map(() => '?').join(', ')})`); params.push(...kinds); }
+    params.push(Math.max(1, Math.min(MAX_LIMIT, Number(limit) || DEFAULT_LIMIT)));
+    return db.prepare(`SELECT * FROM dataset_annotations WHERE ${where.join(' AND ')} ORDER BY time, created_at LIMIT ?`).all(...params).map(fromRow);
+};
+
+// Start of the timeframe bucket holding `time` when there are no stored bars
+const bucketStart = (time, timeframe) => {
+    if (timeframe === '1mo') {
+        const d = new Date(time);
+        return Date.UTC(d.getUTCFullYear(), d.getUTCMonth(), 1);
+    }
+    const step = TIMEFRAME_MS[timeframe];
+    if (timeframe === '1W') return Math.floor((time - WEEK_OFFSET_MS) / step) * step + WEEK_OFFSET_MS;
+    return Math.floor(time / step) * step;
+};
+
+/**
+ * Annotations of `symbol` in [from, to] with the bar they land on in `timeframe`:
+ * barTime (and barEndTime for spans), null when the time is before the first stored bar.
+ */
+const projectAnnotations = (db, symbol, timeframe, options = {}) => {
+    if (!TIMEFRAME_MS[timeframe]) throw new Error(`Unknown timeframe: ${timeframe}`);
+    const key = cleanSymbol(symbol);
+    const annotations = listDatasetAnnotations(db, key, options);
+    // Bars may be stored under another spelling of the symbol
+    const stored = db.prepare('SELECT symbol FROM market_data WHERE symbol = ? COLLATE NOCASE AND timeframe = ? LIMIT 1').get(key, timeframe);
+    const hasBars = !!stored;
+    const barAt = db.prepare('SELECT MAX(timestamp) AS t FROM market_data WHERE symbol = ? AND timeframe = ? AND timestamp <= ?');
+    const snap = (time) => (hasBars ? barAt.get(stored.symbol, timeframe, time).t : bucketStart(time, timeframe));
+    return {
+        symbol: key,
+        timeframe,
+        snappedTo: hasBars ? 'bars' : 'buckets',
+        annotations: annotations.map(a => ({ ...a, barTime: snap(a.time), barEndTime: a.endTime != null ? snap(a.endTime) : null }))
+    };
+};
+
+module.exports = {
+    KINDS, initializeDatasetAnnotationTable, getDatasetAnnotation, createDatasetAnnotation, updateDatasetAnnotation,
+    deleteDatasetAnnotation, listDatasetAnnotations, projectAnnotations
+};
@@ -0,0 +1,143 @@
+
+const { datasetId, parseDatasetId, insertBars, registerDataset, getDataset } = require('./datasets');
+
+// --- DATASET MERGING ---
+// Combines overlapping histories of one instrument from several sources (a
+// CSV archive, two providers, ...) into one canonical series stored as a
+// derived dataset (source 'merged'). Bars are matched by timestamp; where
+// several sources have one, the highest-priority source wins and the others
+// are compared with it: prices (open/high/low/close) further apart than
+// `tolerance.price` (relative, default 0.1%) or volumes further than
+// `tolerance.volume` (relative; null, the default, skips volume) are reported
+// as conflicts. Bars only a lower-priority source has fill the gaps. The spec
+// and per-source coverage are kept in the dataset meta so it can be rebuilt.
+
+const PRICE_FIELDS = ['open', 'high', 'low', 'close'];
+const DEFAULT_TOLERANCE = { price: 0.001, volume: null };
+const MAX_CONFLICTS = 1000;
+
+const loadBars = (db, id) => {
+    const { symbol, timeframe } = parseDatasetId(id);
+    return db.prepare('SELECT timestamp, open, high, low, close, volume FROM market_data WHERE symbol = ? AND timeframe = ? ORDER BY timestamp')
+        .all(symbol, timeframe);
+};
+
+const checkTolerance = (value, name) => {
+    if (value == null) return null;
+    const n = Number(value);
+    if (!(n >= 0)) throw new Error(`tolerance.${name} must be a non-negative number`);
+    return n;
+};
+
+/**
+ * spec: { datasets: [id], priority?: [dataset id or source name], symbol?, tolerance? }.
+ * priority ranks the datasets (first wins); datasets it doesn't name follow in
+ * the order given. Returns the spec with datasets sorted by priority.
+ */
+const normalizeMergeSpec = (db, spec = {}) => {
+    const ids = Array.isArray(spec.datasets) ? Array.from(new Set(spec.datasets.map(String))) : [];
+    if (ids.length < 2) throw new Error('At least two datasets are required');
+    let timeframe = null;
+    const datasets = ids.map((id) => {
+        const dataset = getDataset(db, id);
+        if (!dataset) throw new Error(`Dataset not found: ${id}`);
+        if (timeframe && dataset.timeframe !== timeframe) throw new Error(`All datasets must share one timeframe (${id} is ${dataset.timeframe})`);
+        timeframe = dataset.timeframe;
+        return dataset;
+    });
+    const priority = Array.isArray(spec.priority) ? spec.priority.map(String) : [];
+    const rank = (dataset) => {
+        const byId = priority.indexOf(dataset.id);
+        const bySource = priority.indexOf(dataset.source);
+        const found = [byId, bySource].filter(i => i >= 0);
+        return found.length ? Math.min(...found) : priority.length + ids.indexOf(dataset.id);
+    };
+    const ordered = datasets.slice().sort((a, b) => rank(a) - rank(b));
+    const symbol = String(spec.symbol || `${ordered[0].symbol}-merged`).trim();
+    if (!symbol) throw new Error('symbol is required');
+    if (ids.includes(datasetId(symbol, timeframe))) throw new Error('The merged dataset can\'t replace one of its sources');
+    const tolerance = {
+        price: checkTolerance(spec.tolerance && spec.tolerance.price !== undefined ? spec.tolerance.price : DEFAULT_TOLERANCE.price, 'price'),
+        volume: checkTolerance(spec.tolerance ? spec.tolerance.volume : DEFAULT_TOLERANCE.volume, 'volume')
+    };
+    return { datasets: ordered.map(d => d.id), sources: ordered.map(d => d.source), priority, symbol, timeframe, tolerance };
+};
+
+const differs = (a, b, tolerance) => Math.abs(a - b) > tolerance * Math.max(Math.abs(a), Math.abs(b));
+
+/**
+ * Merges without storing. Returns { symbol, timeframe, bars, coverage:
+ * { id: bars taken }, overlaps, conflicts: [{ timestamp, winner, other, fields:
+ * { field: [winnerValue, otherValue] } }] (first MAX_CONFLICTS), conflictCount,
+ * conflictsBySource: { id: count }, spec }.
+ */
+const buildMerged = (db, rawSpec) => {
+    const spec = normalizeMergeSpec(db, rawSpec);
+    const merged = new Map(); // timestamp -> { bar, from }
+    const coverage = {};
+    const conflictsBySource = {};
+    const conflicts = [];
+    let conflictCount = 0;
+    let overlaps = 0;
+
+    spec.datasets.forEach((id) => {
+        coverage[id] = 0;
+        conflictsBySource[id] = 0;
+        loadBars(db, id).forEach((bar) => {
+            const existing = merged.get(bar.timestamp);
+            if (!existing) {
+                merged.set(bar.timestamp, { bar, from: id });
+                coverage[id]++;
+                return;
+            }
+            overlaps++;
+            const fields = {};
+            if (spec.tolerance.price != null) {
+                PRICE_FIELDS.forEach((f) => { if (differs(existing.bar[f], bar[f], spec.tolerance.price)) fields[f] = [existing.bar[f], bar[f]]; });
+            }
+            if (spec.tolerance.volume != null && differs(existing.bar.volume || 0, bar.volume || 0, spec.tolerance.volume)) fields.volume = [existing.bar.volume, bar.volume];
+            if (!Object.keys(fields).length) return;
+            conflictCount++;
+            conflictsBySource[id]++;
+            if (conflicts.length < MAX_CONFLICTS) conflicts.push({ timestamp: bar.timestamp, winner: existing.from, other: id, fields });
+        });
+    });
+
+    const bars = Array.from(merged.values()).map(m => m.bar).sort((a, b) => a.timestamp - b.timestamp);
+    conflicts.sort((a, b) => a.timestamp - b.timestamp);
+    const storedSpec = { datasets: spec.datasets, priority: spec.priority, symbol: spec.symbol, tolerance: spec.tolerance };
+    return { symbol: spec.symbol, timeframe: spec.timeframe, bars, coverage, overlaps, conflicts, conflictCount, conflictsBySource, spec: storedSpec };
+};
+
+/**
+ * Builds and stores the merged dataset, replacing a previous merge; real data
+ * under the same symbol / timeframe is never overwritten.
+ */
+const storeMerged = (db, rawSpec) => {
+    const result = buildMerged(db, rawSpec);
+    if (!result.bars.length) throw new Error('The source datasets have no bars');
+    const existing = getDataset(db, datasetId(result.symbol, result.timeframe));
+    if (existing && existing.source !== 'merged') throw new Error(`${existing.id} already holds ${existing.source} data`);
+    db.transaction(() => {
+        db.prepare('DELETE FROM market_data WHERE symbol = ? AND timeframe = ?').run(result.symbol, result.timeframe);
+        insertBars(db, result.symbol, result.timeframe, result.bars);
+    })();
+    const dataset = registerDataset(db, result.symbol, result.timeframe, 'merged', {
+        merge: result.spec,
+        coverage: result.coverage,
+        overlaps: result.overlaps,
+        conflicts: result.conflictCount,
+        mergedAt: Date.now()
+    });
+    const { bars, ...report } = result;
+    return { ...report, dataset, bars: bars.length };
+};
+
+// Re-merges from the spec saved with the dataset (e.g. after a source got new bars)
+const rebuildMerged = (db, id) => {
+    const dataset = getDataset(db, id);
+    if (!dataset || !dataset.meta || !dataset.meta.merge) throw new Error(`Not a merged dataset: ${id}`);
+    return storeMerged(db, dataset.meta.merge);
+};
+
+module.exports = { normalizeMergeSpec, buildMerged, storeMerged, rebuildMerged };
@@ -0,0 +1,146 @@
+
+// --- DATASET REGISTRY ---
+// A dataset is one (symbol, timeframe) series in market_data. The registry table
+// records where it came from and summary stats so other subsystems (providers,
+// importers, the library UI) can enumerate series without scanning market_data.
+
+const TIMEFRAME_MS = {
+    '1mn': 60000,
+    '3m': 180000,
+    '5m': 300000,
+    '15m': 900000,
+    '30m': 1800000,
+    '1h': 3600000,
+    '2h': 7200000,
+    '4h': 14400000,
+    '12h': 43200000,
+    '1D': 86400000,
+    '1W': 604800000,
+    '1mo': 2592000000,
+    '3mo': 7776000000,
+    '6mo': 15552000000,
+    '12M': 31536000000
+};
+
+const datasetId = (symbol, timeframe) => `${symbol}:${timeframe}`;
+
+const parseDatasetId = (id) => {
+    const idx = String(id).lastIndexOf(':');
+    if (idx <= 0) throw new Error(`Invalid dataset id: ${id}`);
+    return { symbol: id.slice(0, idx), timeframe: id.slice(idx + 1) };
+};
+
+const initializeDatasetTables = (db) => {
+    db.exec(`
+        CREATE TABLE IF NOT EXISTS datasets (
+            id TEXT PRIMARY KEY,
+            symbol TEXT,
+            timeframe TEXT,
+            source TEXT,
+            row_count INTEGER,
+            first_ts INTEGER,
+            last_ts INTEGER,
+            meta TEXT,
+            created_at INTEGER,
+            updated_at INTEGER
+        );
+    `);
+};
+
+/**
+ * Recomputes stats for a series and upserts its registry row.
+ * `source` identifies the origin ('csv', 'metatrader', 'ibkr', ...); `meta` is free-form.
+ */
+const registerDataset = (db, symbol, timeframe, source, meta = null) => {
+    const stats = db.prepare(
+        'SELECT COUNT(*) as count, MIN(timestamp) as first, MAX(timestamp) as last FROM market_data WHERE symbol = ? AND timeframe = ?'
+    ).get(symbol, timeframe);
+    const id = datasetId(symbol, timeframe);
+    const now = Date.now();
+    const existing = db.prepare('SELECT created_at, meta FROM datasets WHERE id = ?').get(id);
+    const mergedMeta = { ...(existing?.meta ? JSON.parse(existing.meta) : {}), ...(meta || {}) };
+
+    db.prepare(`
+        INSERT OR REPLACE INTO datasets (id, symbol, timeframe, source, row_count, first_ts, last_ts, meta, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+    `).run(id, symbol, timeframe, source, stats.count, stats.first, stats.last, JSON.stringify(mergedMeta), existing?.created_at || now, now);
+
+    return getDataset(db, id);
+};
+
+const mapDatasetRow = (row) => row ? ({
+    id: row.id,
+    symbol: row.symbol,
+    timeframe: row.timeframe,
+    source: row.source,
+    rowCount: row.row_count,
+    firstTimestamp: row.first_ts,
+    lastTimestamp: row.last_ts,
+    meta: row.meta ? JSON.parse(row.meta) : {},
+    createdAt: row.created_at,
+    updatedAt: row.updated_at
+}) : null;
+
+const getDataset = (db, id) => mapDatasetRow(db.prepare('SELECT * FROM datasets WHERE id = ?').get(id));
+
+const findDatasetsBySymbol = (db, symbol) =>
+    db.prepare('SELECT * FROM datasets WHERE symbol = ? ORDER BY timeframe').all(symbol).map(mapDatasetRow);
+
+const listDatasets = (db) => db.prepare('SELECT * FROM datasets ORDER BY symbol, timeframe').all().map(mapDatasetRow);
+
+/**
+ * Writes bars ({ timestamp, open, high, low, close, volume }) into market_data.
+ * Existing bars at the same timestamp are replaced so provider corrections win.
+ */
+const insertBars = (db, symbol, timeframe, bars) => {
+    if (!bars || bars.length === 0) return 0;
+    const stmt = db.prepare(`
+        INSERT OR REPLACE INTO market_data (symbol, timeframe, timestamp, open, high, low, close, volume)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+    `);
+    const tx = db.transaction((rows) => {
+        for (const r of rows) stmt.run(symbol, timeframe, r.timestamp, r.open, r.high, r.low, r.close, r.volume || 0);
+    });
+    tx(bars);
+    return bars.length;
+};
+
+const PAGE_COLUMNS = ['timestamp', 'open', 'high', 'low', 'close', 'volume'];
+const MAX_PAGE_SIZE = 5000;
+
+/**
+ * One page of a series for virtualized table views, sorted in SQLite.
+ * `sort` is { column, direction: 'asc' | 'desc' }; ties fall back to timestamp
+ * so paging stays stable. Rows come back in array format (PAGE_COLUMNS order).
+ */
+const getRowsPage = (db, id, offset = 0, limit = 200, sort = null) => {
+    const { symbol, timeframe } = parseDatasetId(id);
+    const column = sort && PAGE_COLUMNS.includes(sort.column) ? sort.column : 'timestamp';
+    const direction = sort && String(sort.direction).toLowerCase() === 'desc' ? 'DESC' : 'ASC';
+    const pageSize = Math.max(1, Math.min(MAX_PAGE_SIZE, Math.floor(limit) || 200));
+    const start = Math.max(0, Math.floor(offset) || 0);
+
+    const orderBy = column === 'timestamp' ? `timestamp ${direction}` : `${column} ${direction}, timestamp ASC`;
+    const rows = db.prepare(`
+        SELECT ${PAGE_COLUMNS.join(', ')} FROM market_data
+        WHERE symbol = ? AND timeframe = ?
+        ORDER BY ${orderBy}
+        LIMIT ? OFFSET ?
+    `).raw().all(symbol, timeframe, pageSize, start);
+    const total = db.prepare('SELECT COUNT(*) as n FROM market_data WHERE symbol = ? AND timeframe = ?').get(symbol, timeframe).n;
+
+    return { columns: PAGE_COLUMNS, rows, offset: start, limit: pageSize, total, sort: { column, direction: direction.toLowerCase() } };
+};
+
+module.exports = {
+    TIMEFRAME_MS,
+    datasetId,
+    parseDatasetId,
+    initializeDatasetTables,
+    registerDataset,
+    getDataset,
+    listDatasets,
+    findDatasetsBySymbol,
+    insertBars,
+    getRowsPage
+};
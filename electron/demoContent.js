@@ -0,0 +1,103 @@
+
+// --- DEMO CONTENT ---
+// A complete example workspace built from one synthetic series, so a new user
+// (or a screenshot, or a test) has something real to look at:
+//
+//   dataset     DEMO 1h and 1D bars (seeded, so every install looks the same)
+//   chart       trend line over two swing lows, support / resistance lines,
+//               the last consolidation box and a label, in a folder and a group
+//   notes       a few sticky notes on a "Demo" board
+//   watchlist   DEMO plus a handful of well-known symbols
+//   alerts      one following the resistance line, one at the support level
+//
+// Everything carries fixed ids derived from DEMO_PREFIX, so installing again
+// replaces the previous demo content instead of duplicating it.
+
+const DEMO_SYMBOL = 'DEMO';
+const DEMO_PREFIX = 'demo-';
+const DEMO_BOARD_NAME = 'Demo';
+const DEMO_DATASETS = [
+    { symbol: DEMO_SYMBOL, timeframe: '1h', bars: 3000, model: 'gbm', volatility: 0.008, trend: 0.0002, seed: 7 },
+    { symbol: DEMO_SYMBOL, timeframe: '1D', bars: 750, model: 'gbm', volatility: 0.015, trend: 0.0005, seed: 7 }
+];
+const DEMO_WATCHLIST = [DEMO_SYMBOL, 'SPY', 'QQQ', 'AAPL', 'NVDA', 'BTCUSDT', 'EURUSD'];
+const SWING_WINDOW = 10;
+
+const id = (name) => `${DEMO_PREFIX}${name}`;
+const round = (price) => Math.round(price * 100) / 100;
+
+// Bars that are the lowest (or highest) within SWING_WINDOW on each side
+const swings = (bars, field, lowest) => bars.filter((bar, i) => {
+    if (i < SWING_WINDOW || i >= bars.length - SWING_WINDOW) return false;
+    for (let j = i - SWING_WINDOW; j <= i + SWING_WINDOW; j++) {
+        if (j !== i && (lowest ? bars[j][field] < bar[field] : bars[j][field] > bar[field])) return false;
+    }
+    return true;
+});
+
+/**
+ * Chart state for `bars` ([{ timestamp, open, high, low, close }], oldest
+ * first; the last 300 are used). Returns { state, levels: { support, resistance } }.
+ */
+const buildDemoChartState = (bars) => {
+    if (!Array.isArray(bars) || bars.length < 4 * SWING_WINDOW) throw new Error('Not enough bars for the demo chart');
+    const recent = bars.slice(-300);
+    const first = recent[0];
+    const last = recent[recent.length - 1];
+    const lows = swings(recent, 'low', true);
+    const highs = swings(recent, 'high', false);
+    const support = round(Math.min(...recent.slice(-100).map(b => b.low)));
+    const resistance = round(Math.max(...recent.slice(-100).map(b => b.high)));
+    // Trend line through the two lowest swing lows, in time order
+    const anchors = (lows.length >= 2 ? lows.slice().sort((a, b) => a.low - b.low).slice(0, 2) : [first, last])
+        .sort((a, b) => a.timestamp - b.timestamp);
+    const box = recent.slice(-40);
+    const boxHigh = round(Math.max(...box.map(b => b.high)));
+    const boxLow = round(Math.min(...box.map(b => b.low)));
+    const lastHigh = highs.length ? highs[highs.length - 1] : last;
+
+    const style = (color, extra = {}) => ({ color, lineWidth: 2, lineStyle: 'solid', visible: true, ...extra });
+    const drawings = [
+        { id: id('trend'), type: 'trend_line', folderId: id('analysis'), properties: style('#22C55E'),
+            points: anchors.map(b => ({ time: b.timestamp, price: round(b.low) })) },
+        { id: id('resistance'), type: 'horizontal_line', folderId: id('analysis'), groupId: id('levels'), properties: style('#EF4444', { locked: true }),
+            points: [{ time: last.timestamp, price: resistance }] },
+        { id: id('support'), type: 'horizontal_line', folderId: id('analysis'), groupId: id('levels'), properties: style('#3B82F6', { locked: true }),
+            points: [{ time: last.timestamp, price: support }] },
+        { id: id('range'), type: 'rectangle', folderId: id('analysis'), properties: style('#A855F7', { lineWidth: 1, lineStyle: 'dashed', filled: true }),
+            points: [{ time: box[0].timestamp, price: boxHigh }, { time: last.timestamp, price: boxLow }] },
+        { id: id('label'), type: 'text', folderId: id('analysis'), properties: { color: '#E5E7EB', fontSize: 14, visible: true, text: 'Last swing high' },
+            points: [{ time: lastHigh.timestamp, price: round(lastHigh.high) }] }
+    ];
+    return {
+        state: {
+            drawings,
+            folders: [{ id: id('analysis'), name: 'Demo analysis', isExpanded: true }],
+            groups: [{ id: id('levels'), name: 'Key levels', locked: true }],
+            visibleRange: { from: first.timestamp, to: last.timestamp }
+        },
+        levels: { support, resistance }
+    };
+};
+
+// Sticky note 'create' ops for the demo board
+const buildDemoNotes = ({ boardId, levels }) => [
+    { name: 'welcome', color: '#F59E0B', tags: ['demo'], layout: { x: 40, y: 40, width: 260, height: 160 },
+        text: 'Welcome! This board, the DEMO chart and its alerts were created by the demo installer. Install it again at any time to reset them.' },
+    { name: 'plan', color: '#22C55E', tags: ['demo', 'plan'], layout: { x: 320, y: 40, width: 260, height: 160 },
+        text: `Plan: buy a pullback to ${levels.support} while the trend line holds. Target the ${levels.resistance} resistance.` },
+    { name: 'review', color: '#3B82F6', tags: ['demo', 'review'], layout: { x: 600, y: 40, width: 260, height: 160 },
+        text: 'Review: Key levels are locked. Unlock the group to move them.' }
+].map(({ name, ...note }) => ({ op: 'create', note: { ...note, id: id(`note-${name}`), symbol: DEMO_SYMBOL, boardId } }));
+
+// Alerts for alertEngine.saveAlert; the resistance alert follows the drawing
+const buildDemoAlerts = ({ levels }) => [
+    { id: id('alert-breakout'), symbol: DEMO_SYMBOL, condition: 'crosses_above', message: 'DEMO broke above resistance',
+        options: { drawing: { sourceId: DEMO_SYMBOL, drawingId: id('resistance') } } },
+    { id: id('alert-support'), symbol: DEMO_SYMBOL, condition: 'crosses_below', price: levels.support, message: 'DEMO lost support' }
+];
+
+module.exports = {
+    DEMO_SYMBOL, DEMO_PREFIX, DEMO_BOARD_NAME, DEMO_DATASETS, DEMO_WATCHLIST,
+    buildDemoChartState, buildDemoNotes, buildDemoAlerts
+};
@@ -0,0 +1,179 @@
+
+const os = require('os');
+const fs = require('fs');
+const path = require('path');
+const { createZip } = require('./zipWriter');
+const { scrubSecrets } = require('./secrets');
+
+// --- DIAGNOSTICS & SUPPORT BUNDLES ---
+// Health checks for bug reports. Each check returns
+// { id, status: 'ok' | 'warn' | 'fail', message, details? }. The support bundle
+// packs the checks with logs and perf metrics, redacted: no drawing or trade
+// contents, no secrets, and the home directory replaced with "~".
+
+const LOW_DISK_BYTES = 1024 * 1024 * 1024; // 1 GB
+const SENSITIVE_KEY = /token|secret|password|api[-_]?key|webhook|chat[-_]?id|authorization/i;
+
+const check = (id, status, message, details = undefined) => ({ id, status, message, ...(details ? { details } : {}) });
+
+const checkWritable = (id, dir) => {
+    try {
+        fs.accessSync(dir, fs.constants.R_OK | fs.constants.W_OK);
+        return check(id, 'ok', `${dir} is readable and writable`);
+    } catch (err) {
+        return check(id, 'fail', `${dir} is not writable: ${err.code || err.message}`);
+    }
+};
+
+const checkDiskSpace = (dir) => {
+    try {
+        const stats = fs.statfsSync(dir);
+        const free = stats.bavail * stats.bsize;
+        const freeGb = (free / 1024 ** 3).toFixed(2);
+        return check('disk_space', free < LOW_DISK_BYTES ? 'warn' : 'ok', `${freeGb} GB free on the data volume`, { freeBytes: free, totalBytes: stats.blocks * stats.bsize });
+    } catch (err) {
+        return check('disk_space', 'warn', `Could not read free space: ${err.message}`);
+    }
+};
+
+const checkDatabaseIntegrity = (db) => {
+    try {
+        const result = db.pragma('quick_check', { simple: true });
+        const indexes = db.prepare("SELECT name FROM sqlite_master WHERE type = 'index' AND name = 'idx_market_data'").all();
+        if (result !== 'ok') return check('db_integrity', 'fail', `SQLite quick_check reported: ${result}`);
+        if (indexes.length === 0) return check('db_integrity', 'warn', 'market_data index idx_market_data is missing');
+        return check('db_integrity', 'ok', 'SQLite quick_check passed and indexes are present', { journalMode: db.pragma('journal_mode', { simple: true }) });
+    } catch (err) {
+        return check('db_integrity', 'fail', `Integrity check failed: ${err.message}`);
+    }
+};
+
+// Rows whose JSON payload no longer parses, per table
+const checkCorruptRows = (db) => {
+    const corrupt = {};
+    [['drawings', 'symbol'], ['trades', 'id'], ['settings', 'key']].forEach(([table, key]) => {
+        const column = table === 'settings' ? 'value' : 'data';
+        db.prepare(`SELECT ${key} as k, ${column} as v FROM ${table}`).all().forEach((row) => {
+            try { JSON.parse(row.v); } catch (e) { (corrupt[table] = corrupt[table] || []).push(row.k); }
+        });
+    });
+    const count = Object.values(corrupt).reduce((n, list) => n + list.length, 0);
+    return count === 0
+        ? check('corrupt_rows', 'ok', 'All stored JSON payloads parse')
+        : check('corrupt_rows', 'fail', `${count} stored record(s) contain invalid JSON`, corrupt);
+};
+
+// Strip extension and a trailing timeframe token: "BTCUSDT_1h.csv" -> "BTCUSDT"
+const baseSymbolFromFile = (name) => name
+    .replace(/\.(csv|txt|json|hst)$/i, '')
+    .replace(/[_\-\s]?(1mn|1m|3m|5m|15m|30m|60m|1h|2h|4h|12h|1d|1w|1mo|12m|m1|m5|m15|m30|h1|h4|d1|w1|mn1)$/i, '')
+    .toUpperCase();
+
+/**
+ * Symbols that still have data behind them (market_data, registry, Assets
+ * library). `extraNames` are file names or symbols the renderer knows about,
+ * e.g. its recent-files list.
+ */
+const knownSymbols = (db, libraryFiles = [], extraNames = []) => {
+    const known = new Set();
+    db.prepare('SELECT DISTINCT symbol FROM market_data').all().forEach(r => known.add(String(r.symbol).toUpperCase()));
+    db.prepare('SELECT DISTINCT symbol FROM datasets').all().forEach(r => known.add(String(r.symbol).toUpperCase()));
+    libraryFiles.forEach(f => known.add(baseSymbolFromFile(f.name)));
+    extraNames.forEach(name => known.add(baseSymbolFromFile(path.basename(String(name)))));
+    return known;
+};
+
+const findOrphanedChartStates = (db, libraryFiles = [], extraNames = []) => {
+    const known = knownSymbols(db, libraryFiles, extraNames);
+    return db.prepare('SELECT symbol, length(data) as bytes, data FROM drawings').all()
+        .filter(r => !known.has(String(r.symbol).toUpperCase()))
+        .map((r) => {
+            let drawings = 0, savedAt = null;
+            try {
+                const state = JSON.parse(r.data);
+                drawings = (state.drawings || []).length;
+                savedAt = state.timestamp || null;
+            } catch (e) {}
+            return { symbol: r.symbol, bytes: r.bytes, drawings, savedAt };
+        });
+};
+
+const checkOrphanedDrawings = (db, libraryFiles) => {
+    const orphans = findOrphanedChartStates(db, libraryFiles);
+    return orphans.length === 0
+        ? check('orphaned_drawings', 'ok', 'Every chart state maps to a known symbol')
+        : check('orphaned_drawings', 'warn', `${orphans.length} chart state(s) have no matching data source`, { symbols: orphans.map(o => o.symbol) });
+};
+
+const checkProviders = (providers) => {
+    const details = providers.map(p => ({ id: p.id, status: p.status, message: p.message || null }));
+    const degraded = details.filter(p => p.status === 'degraded');
+    return check('providers', degraded.length ? 'warn' : 'ok',
+        degraded.length ? `${degraded.length} provider(s) degraded` : `${details.filter(p => p.status === 'connected').length} provider(s) connected`,
+        { providers: details });
+};
+
+const runDiagnostics = ({ db, dbPath, userDataPath, assetsPath, libraryFiles = [], providers = [] }) => {
+    const checks = [
+        checkWritable('user_data_dir', userDataPath),
+        checkWritable('assets_dir', assetsPath),
+        checkDiskSpace(userDataPath)
+    ];
+    if (db) {
+        checks.push(checkDatabaseIntegrity(db), checkCorruptRows(db), checkOrphanedDrawings(db, libraryFiles));
+    } else {
+        checks.push(check('db_integrity', 'fail', 'Database is not open'));
+    }
+    checks.push(checkProviders(providers));
+
+    let dbSize = null;
+    try { dbSize = fs.statSync(dbPath).size; } catch (e) {}
+
+    const worst = checks.some(c => c.status === 'fail') ? 'fail' : checks.some(c => c.status === 'warn') ? 'warn' : 'ok';
+    return {
+        generatedAt: Date.now(),
+        status: worst,
+        environment: {
+            platform: process.platform,
+            arch: process.arch,
+            osRelease: os.release(),
+            electron: process.versions.electron,
+            node: process.versions.node,
+            memoryMB: Math.round(process.memoryUsage().rss / 1024 / 1024),
+            dbSizeBytes: dbSize
+        },
+        checks
+    };
+};
+
+/**
+ * Deep-copies `value`, masking sensitive keys, known secret values and
+ * replacing the home directory.
+ */
+const redact = (value) => {
+    const home = os.homedir();
+    const walk = (v, key) => {
+        if (key && SENSITIVE_KEY.test(key) && v) return '[redacted]';
+        if (typeof v === 'string') return home ? v.split(home).join('~') : v;
+        if (Array.isArray(v)) return v.map(item => walk(item));
+        if (v && typeof v === 'object') return Object.fromEntries(Object.entries(v).map(([k, val]) => [k, walk(val, k)]));
+        return v;
+    };
+    return walk(scrubSecrets(value));
+};
+
+const writeSupportBundle = (targetPath, { diagnostics, logs, perfMetrics, settings }) => {
+    const json = (data) => JSON.stringify(redact(data), null, 2);
+    const zip = createZip([
+        { name: 'diagnostics.json', data: json(diagnostics) },
+        { name: 'system-log.json', data: json(logs) },
+        { name: 'perf-metrics.json', data: json(perfMetrics) },
+        { name: 'settings.json', data: json(settings) },
+        { name: 'README.txt', data: 'Red Pill Charting support bundle.\nContains diagnostics, recent logs and settings with secrets and paths redacted.\nChart drawings, trades and market data are not included.\n' }
+    ]);
+    fs.mkdirSync(path.dirname(targetPath), { recursive: true });
+    fs.writeFileSync(targetPath, zip);
+    return zip.length;
+};
+
+module.exports = { runDiagnostics, writeSupportBundle, findOrphanedChartStates, knownSymbols, redact };
@@ -0,0 +1,208 @@
+
+const crypto = require('crypto');
+const { TIMEFRAME_MS } = require('./datasets');
+
+// --- HISTORICAL DOWNLOAD MANAGER ---
+// Queue of per-symbol history downloads (years of 1-minute data) fetched from
+// providers in time-ordered chunks. Each chunk is stored as soon as it arrives
+// and the job's cursor is saved with it, so a quit, crash or pause resumes from
+// the last stored chunk rather than from the start. The bandwidth cap paces
+// chunks: providers don't expose wire sizes, so a chunk's size is estimated
+// from its JSON encoding, which is close to what JSON APIs actually send.
+
+const DEFAULT_CONFIG = { concurrency: 1, bandwidthKbps: 0, chunkBars: 5000 }; // bandwidthKbps 0 = unlimited
+
+const initializeDownloadTables = (db) => {
+    db.exec(`
+        CREATE TABLE IF NOT EXISTS download_jobs (
+            id TEXT PRIMARY KEY,
+            provider TEXT,
+            symbol TEXT,
+            timeframe TEXT,
+            start_ts INTEGER,
+            end_ts INTEGER,
+            cursor INTEGER,
+            status TEXT,
+            bars INTEGER DEFAULT 0,
+            bytes INTEGER DEFAULT 0,
+            error TEXT,
+            options TEXT,
+            created_at INTEGER,
+            updated_at INTEGER
+        );
+    `);
+};
+
+const mapJobRow = (row) => {
+    const span = Math.max(1, row.end_ts - row.start_ts);
+    return {
+        id: row.id,
+        provider: row.provider,
+        symbol: row.symbol,
+        timeframe: row.timeframe,
+        start: row.start_ts,
+        end: row.end_ts,
+        cursor: row.cursor,
+        status: row.status,
+        percent: row.status === 'done' ? 100 : Math.min(100, Math.round(((row.cursor - row.start_ts) / span) * 1000) / 10),
+        bars: row.bars,
+        bytes: row.bytes,
+        error: row.error,
+        options: row.options ? JSON.parse(row.options) : {},
+        createdAt: row.created_at,
+        updatedAt: row.updated_at
+    };
+};
+
+const sleep = (ms) => new Promise(resolve => setTimeout(resolve, ms));
+
+/**
+ * getProvider(id) -> provider with fetchHistory(symbol, timeframe, { start, end, limit })
+ * persist(provider, symbol, timeframe, bars) stores a chunk.
+ * onProgress(job) after each chunk and status change.
+ */
+const createDownloadManager = ({ db, getProvider, persist, getConfig = () => ({}), onProgress = () => {} }) => {
+    const active = new Map(); // id -> { cancelled: bool }
+    let nextSendAt = 0; // shared pacing clock for the bandwidth cap
+    let started = false;
+
+    const config = () => ({ ...DEFAULT_CONFIG, ...(getConfig() || {}) });
+
+    const getJob = (id) => {
+        const row = db.prepare('SELECT * FROM download_jobs WHERE id = ?').get(id);
+        return row ? mapJobRow(row) : null;
+    };
+
+    const update = (id, fields) => {
+        const keys = Object.keys(fields);
+        db.prepare(`UPDATE download_jobs SET ${keys.map(k => `${k} = ?`).join(', ')}, updated_at = ? WHERE id = ?`)
+            .run(...keys.map(k => fields[k]), Date.now(), id);
+        const job = getJob(id);
+        if (job) onProgress(job);
+        return job;
+    };
+
+    // Waits long enough that throughput stays under the cap, averaged over all workers
+    const pace = async (bytes) => {
+        const { bandwidthKbps } = config();
+        if (!bandwidthKbps || bandwidthKbps <= 0) return;
+        const now = Date.now();
+        nextSendAt = Math.max(now, nextSendAt) + (bytes / (bandwidthKbps * 1024 / 8)) * 1000;
+        if (nextSendAt > now) await sleep(nextSendAt - now);
+    };
+
+    const runJob = async (id) => {
+        const control = { cancelled: false };
+        active.set(id, control);
+        let job = update(id, { status: 'running', error: null });
+        try {
+            const provider = getProvider(job.provider);
+            const tfMs = TIMEFRAME_MS[job.timeframe];
+            const { chunkBars } = config();
+            let cursor = job.cursor;
+            while (cursor <= job.end) {
+                if (control.cancelled) return;
+                const chunkEnd = Math.min(job.end, cursor + chunkBars * tfMs);
+                // Transient failures are retried by the provider's network layer; what reaches here fails the job (resumable)
+                const bars = (await provider.fetchHistory(job.symbol, job.timeframe, { ...job.options, start: cursor, end: chunkEnd, limit: chunkBars }))
+                    .filter(b => b.timestamp >= cursor && b.timestamp <= chunkEnd);
+                if (control.cancelled) return;
+                if (bars.length) persist(job.provider, job.symbol, job.timeframe, bars);
+                // A full chunk may have been cut short by the provider's page size: continue after its last bar
+                const next = bars.length >= chunkBars ? bars[bars.length - 1].timestamp + tfMs : chunkEnd + 1;
+                const bytes = bars.length ? JSON.stringify(bars).length : 0;
+                job = update(id, { cursor: Math.max(next, cursor + 1), bars: job.bars + bars.length, bytes: job.bytes + bytes });
+                cursor = job.cursor;
+                await pace(bytes);
+            }
+            update(id, { status: 'done', cursor: job.end });
+        } catch (err) {
+            update(id, { status: 'failed', error: err.message });
+        } finally {
+            active.delete(id);
+            pump();
+        }
+    };
+
+    // Starts queued jobs (oldest first) up to the concurrency limit
+    const pump = () => {
+        if (!started) return;
+        const free = Math.max(1, config().concurrency) - active.size;
+        if (free <= 0) return;
+        db.prepare("SELECT id FROM download_jobs WHERE status = 'queued' ORDER BY created_at LIMIT ?").all(free)
+            .forEach(({ id }) => { if (!active.has(id)) runJob(id); });
+    };
+
+    // Jobs that were mid-download when the app quit go back in the queue
+    const start = () => {
+        db.prepare("UPDATE download_jobs SET status = 'queued' WHERE status = 'running'").run();
+        started = true;
+        pump();
+    };
+
+    /**
+     * spec: { provider, symbol, timeframe, start, end?, options? } — start/end in ms
+     */
+    const enqueue = (spec) => {
+        if (!spec || !spec.provider || !spec.symbol) throw new Error('provider and symbol are required');
+        if (!TIMEFRAME_MS[spec.timeframe]) throw new Error(`Unknown timeframe: ${spec.timeframe}`);
+        const end = spec.end != null ? Number(spec.end) : Date.now();
+        const startTs = Number(spec.start);
+        if (!Number.isFinite(startTs) || startTs >= end) throw new Error('start must be before end');
+        getProvider(spec.provider); // unknown providers fail now, not at run time
+        const id = crypto.randomUUID();
+        const now = Date.now();
+        db.prepare('INSERT INTO download_jobs (id, provider, symbol, timeframe, start_ts, end_ts, cursor, status, options, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)')
+            .run(id, spec.provider, spec.symbol, spec.timeframe, startTs, end, startTs, 'queued', JSON.stringify(spec.options || {}), now, now);
+        pump();
+        return getJob(id);
+    };
+
+    const stopActive = (id) => {
+        const control = active.get(id);
+        if (control) control.cancelled = true;
+    };
+
+    const pause = (id) => {
+        const job = getJob(id);
+        if (!job) throw new Error(`Download not found: ${id}`);
+        if (job.status !== 'queued' && job.status !== 'running') return job;
+        stopActive(id);
+        return update(id, { status: 'paused' });
+    };
+
+    // Resumes a paused or failed job from its cursor
+    const resume = (id) => {
+        const job = getJob(id);
+        if (!job) throw new Error(`Download not found: ${id}`);
+        if (job.status !== 'paused' && job.status !== 'failed') return job;
+        const next = update(id, { status: 'queued', error: null });
+        pump();
+        return next;
+    };
+
+    const cancel = (id) => {
+        if (!getJob(id)) throw new Error(`Download not found: ${id}`);
+        stopActive(id);
+        return update(id, { status: 'canceled' });
+    };
+
+    const remove = (id) => {
+        stopActive(id);
+        return db.prepare('DELETE FROM download_jobs WHERE id = ?').run(id).changes > 0;
+    };
+
+    // Quit: in-flight jobs keep status 'running' and are requeued by the next start()
+    const stop = () => {
+        started = false;
+        active.forEach(control => { control.cancelled = true; });
+    };
+
+    const list = () => db.prepare('SELECT * FROM download_jobs ORDER BY created_at').all().map(mapJobRow);
+
+    const clearFinished = () => db.prepare("DELETE FROM download_jobs WHERE status IN ('done', 'canceled')").run().changes;
+
+    return { start, stop, enqueue, pause, resume, cancel, remove, list, clearFinished, pump };
+};
+
+module.exports = { DEFAULT_DOWNLOAD_CONFIG: DEFAULT_CONFIG, initializeDownloadTables, createDownloadManager };
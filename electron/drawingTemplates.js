@@ -0,0 +1,84 @@
+
+const crypto = require('crypto');
+
+// --- DRAWING TEMPLATES ---
+// Named sets of drawing objects (session lines, fib setups, text labels) that
+// can be stamped onto any chart. Templates store the objects as saved; applying
+// one gives every object a fresh id, files them under a folder named after the
+// template and, when an anchor is given, shifts all points so the template's
+// earliest point lands on it.
+
+const MAX_TEMPLATE_OBJECTS = 2000;
+
+const initializeTemplateTable = (db) => {
+    db.exec('CREATE TABLE IF NOT EXISTS drawing_templates (name TEXT PRIMARY KEY, objects TEXT, description TEXT, created_at INTEGER, updated_at INTEGER);');
+};
+
+const cleanName = (name) => {
+    const value = String(name || '').trim();
+    if (!value) throw new Error('Template name is required');
+    if (value.length > 100) throw new Error('Template name is too long');
+    return value;
+};
+
+const saveDrawingTemplate = (db, name, objects, description = null) => {
+    const key = cleanName(name);
+    if (!Array.isArray(objects) || objects.length === 0) throw new Error('A template needs at least one drawing');
+    if (objects.length > MAX_TEMPLATE_OBJECTS) throw new Error(`Templates are limited to ${MAX_TEMPLATE_OBJECTS} drawings`);
+    objects.forEach((o) => {
+        if (!o || typeof o.type !== 'string' || !Array.isArray(o.points)) throw new Error('Template objects must be drawings with a type and points');
+    });
+    // Folder membership is per chart, so it isn't part of a template
+    const stored = objects.map(({ folderId, ...rest }) => rest);
+    const now = Date.now();
+    const existing = db.prepare('SELECT created_at FROM drawing_templates WHERE name = ?').get(key);
+    db.prepare('INSERT OR REPLACE INTO drawing_templates (name, objects, description, created_at, updated_at) VALUES (?, ?, ?, ?, ?)')
+        .run(key, JSON.stringify(stored), description, existing ? existing.created_at : now, now);
+    return { name: key, count: stored.length, updatedAt: now };
+};
+
+const listDrawingTemplates = (db) => db.prepare('SELECT name, objects, description, created_at, updated_at FROM drawing_templates ORDER BY name COLLATE NOCASE').all()
+    .map((r) => {
+        const objects = JSON.parse(r.objects);
+        return { name: r.name, description: r.description, count: objects.length, types: Array.from(new Set(objects.map(o => o.type))), createdAt: r.created_at, updatedAt: r.updated_at };
+    });
+
+const getDrawingTemplate = (db, name) => {
+    const row = db.prepare('SELECT name, objects, description FROM drawing_templates WHERE name = ?').get(name);
+    return row ? { name: row.name, description: row.description, objects: JSON.parse(row.objects) } : null;
+};
+
+const deleteDrawingTemplate = (db, name) => db.prepare('DELETE FROM drawing_templates WHERE name = ?').run(name).changes > 0;
+
+/**
+ * Appends the template's objects to the chart state of `sourceId` and returns
+ * { state, added, folderId }. `anchor` is { time?, price? } for the template's earliest point.
+ */
+const applyDrawingTemplate = (db, sourceId, name, { anchor = null } = {}) => {
+    const template = getDrawingTemplate(db, name);
+    if (!template) throw new Error(`Template not found: ${name}`);
+
+    const row = db.prepare('SELECT data FROM drawings WHERE symbol = ?').get(sourceId);
+    const state = row ? JSON.parse(row.data) : { sourceId, timestamp: Date.now(), drawings: [], folders: [], config: {}, visibleRange: null };
+
+    const points = template.objects.flatMap(o => o.points);
+    const origin = points.reduce((best, p) => (best == null || p.time < best.time ? p : best), null);
+    const dt = anchor && anchor.time != null && origin ? anchor.time - origin.time : 0;
+    const dp = anchor && anchor.price != null && origin ? anchor.price - origin.price : 0;
+
+    const folderId = `tpl_${crypto.randomUUID()}`;
+    const added = template.objects.map(o => ({
+        ...o,
+        id: crypto.randomUUID(),
+        folderId,
+        points: o.points.map(p => ({ time: p.time + dt, price: p.price + dp }))
+    }));
+
+    state.drawings = [...(state.drawings || []), ...added];
+    state.folders = [...(state.folders || []), { id: folderId, name: template.name, isExpanded: true }];
+    state.timestamp = Date.now();
+    db.prepare('INSERT OR REPLACE INTO drawings (symbol, data) VALUES (?, ?)').run(sourceId, JSON.stringify(state));
+    return { state, added: added.length, folderId };
+};
+
+module.exports = { initializeTemplateTable, saveDrawingTemplate, listDrawingTemplates, getDrawingTemplate, deleteDrawingTemplate, applyDrawingTemplate };
@@ -0,0 +1,386 @@
+
+const crypto = require('crypto');
+const fs = require('fs');
+const path = require('path');
+const { request } = require('./network');
+
+// --- ECONOMIC CALENDAR ---
+// Macro events (FOMC, NFP, CPI...) stored in `econ_events` for chart markers
+// and pre-event warnings. Events come from CSV or ICS files or the weekly
+// Forex Factory feed; re-importing the same event (source, time, currency,
+// title) updates it, so refreshed actuals replace the forecasts. Impact is
+// normalized to high / medium / low / holiday / none. A minute timer warns
+// once per event when a matching event is `minutesBefore` away.
+
+const FEEDS = {
+    forexfactory: 'https://nfs.faireconomy.media/ff_calendar_thisweek.json'
+};
+
+const IMPACTS = ['none', 'holiday', 'low', 'medium', 'high'];
+const MAX_EVENTS_PER_QUERY = 5000;
+const WARNING_CHECK_MS = 60000;
+
+const DEFAULT_CONFIG = {
+    provider: null,          // 'forexfactory' to fetch the weekly feed
+    refreshHours: 6,
+    warnings: { enabled: true, minutesBefore: 15, impact: ['high'], currencies: [], notify: true }
+};
+
+const initializeCalendarTable = (db) => {
+    db.exec(`
+        CREATE TABLE IF NOT EXISTS econ_events (
+            id TEXT PRIMARY KEY,
+            source TEXT,
+            timestamp INTEGER,
+            all_day INTEGER,
+            currency TEXT,
+            title TEXT,
+            impact TEXT,
+            actual TEXT,
+            forecast TEXT,
+            previous TEXT,
+            imported_at INTEGER
+        );
+        CREATE INDEX IF NOT EXISTS idx_econ_events_time ON econ_events (timestamp);
+    `);
+};
+
+const mapEventRow = (row) => ({
+    id: row.id,
+    source: row.source,
+    timestamp: row.timestamp,
+    allDay: !!row.all_day,
+    currency: row.currency,
+    title: row.title,
+    impact: row.impact,
+    actual: row.actual,
+    forecast: row.forecast,
+    previous: row.previous
+});
+
+const normalizeImpact = (value) => {
+    const text = String(value == null ? '' : value).trim().toLowerCase();
+    if (!text) return 'none';
+    if (/holiday|non-economic/.test(text)) return 'holiday';
+    if (/high|red|^3$|\*\*\*/.test(text)) return 'high';
+    if (/med|orange|^2$|\*\*/.test(text)) return 'medium';
+    if (/low|yellow|^1$|\*/.test(text)) return 'low';
+    return 'none';
+};
+
+// Values come in as 'USD', 'usd' or 'ALL' (Forex Factory for global events)
+const normalizeCurrency = (value) => {
+    const code = String(value || '').trim().toUpperCase();
+    return /^[A-Z]{3}$/.test(code) ? code : null;
+};
+
+// --- CSV ---
+const splitCsvLine = (line, delimiter) => {
+    const out = [];
+    let field = '';
+    let quoted = false;
+    for (let i = 0; i < line.length; i++) {
+        const c = line[i];
+        if (quoted) {
+            if (c === '"' && line[i + 1] === '"') { field += '"'; i++; }
+            else if (c === '"') quoted = false;
+            else field += c;
+        } else if (c === '"') quoted = true;
+        else if (c === delimiter) { out.push(field.trim()); field = ''; }
+        else field += c;
+    }
+    out.push(field.trim());
+    return out;
+};
+
+const COLUMN_ALIASES = {
+    datetime: ['datetime', 'timestamp', 'date_time', 'start'],
+    date: ['date', 'day'],
+    time: ['time'],
+    title: ['event', 'title', 'name', 'description'],
+    currency: ['currency', 'ccy', 'country', 'cur'],
+    impact: ['impact', 'importance', 'volatility', 'priority'],
+    actual: ['actual'],
+    forecast: ['forecast', 'consensus', 'expected'],
+    previous: ['previous', 'prior', 'prev']
+};
+
+// "8:30am", "14:00", "All Day", "Tentative" -> minutes after midnight or null (all day)
+const parseClock = (text) => {
+    const m = /^(\d{1,2}):(\d{2})(?::\d{2})?\s*(am|pm)?$/i.exec(String(text || '').trim());
+    if (!m) return null;
+    let hours = Number(m[1]) % (m[3] ? 12 : 24);
+    if (m[3] && m[3].toLowerCase() === 'pm') hours += 12;
+    return hours * 60 + Number(m[2]);
+};
+
+// "2024-01-05", "01/05/2024" (US order), "05.01.2024" -> midnight UTC
+const parseDay = (text) => {
+    const value = String(text || '').trim();
+    let m = /^(\d{4})-(\d{2})-(\d{2})/.exec(value);
+    if (m) return Date.UTC(Number(m[1]), Number(m[2]) - 1, Number(m[3]));
+    m = /^(\d{1,2})\/(\d{1,2})\/(\d{4})$/.exec(value);
+    if (m) return Date.UTC(Number(m[3]), Number(m[1]) - 1, Number(m[2]));
+    m = /^(\d{1,2})\.(\d{1,2})\.(\d{4})$/.exec(value);
+    if (m) return Date.UTC(Number(m[3]), Number(m[2]) - 1, Number(m[1]));
+    return null;
+};
+
+/**
+ * options.utcOffsetMinutes: offset of times without a zone (e.g. -300 for EST).
+ * Returns { events, skipped }.
+ */
+const parseCalendarCsv = (text, { utcOffsetMinutes = 0 } = {}) => {
+    const lines = String(text).replace(/^﻿/, '').split(/\r?\n/).filter(line => line.trim());
+    if (lines.length < 2) return { events: [], skipped: 0 };
+    const delimiter = [';', '\t', ','].find(d => lines[0].includes(d)) || ',';
+    const header = splitCsvLine(lines[0], delimiter).map(h => h.toLowerCase());
+    const column = {};
+    Object.entries(COLUMN_ALIASES).forEach(([key, names]) => {
+        const index = header.findIndex(h => names.includes(h));
+        if (index !== -1) column[key] = index;
+    });
+    if (column.title == null || (column.datetime == null && column.date == null)) {
+        throw new Error('Calendar CSV needs an event/title column and a date or datetime column');
+    }
+    const events = [];
+    let skipped = 0;
+    let lastDay = null; // some exports only print the date on the first row of a day
+    lines.slice(1).forEach((line) => {
+        const cells = splitCsvLine(line, delimiter);
+        const cell = (key) => (column[key] != null ? cells[column[key]] || '' : '');
+        let timestamp = null;
+        let allDay = false;
+        if (column.datetime != null && cell('datetime')) {
+            const value = cell('datetime');
+            timestamp = /^\d{10,13}$/.test(value) ? Number(value) * (value.length === 10 ? 1000 : 1)
+                : /[zZ]|[+-]\d{2}:?\d{2}$/.test(value) ? Date.parse(value)
+                    : Date.parse(`${value.replace(' ', 'T')}Z`) - utcOffsetMinutes * 60000;
+        } else {
+            const day = parseDay(cell('date')) ?? lastDay;
+            lastDay = day;
+            const minutes = parseClock(cell('time'));
+            allDay = minutes == null;
+            if (day != null) timestamp = day + (allDay ? 0 : minutes * 60000 - utcOffsetMinutes * 60000);
+        }
+        if (!Number.isFinite(timestamp) || !cell('title')) { skipped += 1; return; }
+        events.push({
+            timestamp,
+            allDay,
+            currency: normalizeCurrency(cell('currency')),
+            title: cell('title'),
+            impact: normalizeImpact(cell('impact')),
+            actual: cell('actual') || null,
+            forecast: cell('forecast') || null,
+            previous: cell('previous') || null
+        });
+    });
+    return { events, skipped };
+};
+
+// --- ICS ---
+const unescapeIcs = (value) => value.replace(/\\n/gi, '\n').replace(/\\([,;\\])/g, '$1');
+
+// 20240105T133000Z, 20240105T083000 (floating / TZID: utcOffsetMinutes), 20240105 (all day)
+const parseIcsDate = (value, params, utcOffsetMinutes) => {
+    const m = /^(\d{4})(\d{2})(\d{2})(?:T(\d{2})(\d{2})(\d{2})?(Z)?)?$/.exec(value.trim());
+    if (!m) return null;
+    const day = Date.UTC(Number(m[1]), Number(m[2]) - 1, Number(m[3]));
+    if (!m[4] || /VALUE=DATE(;|$)/.test(params)) return { timestamp: day, allDay: true };
+    const local = day + (Number(m[4]) * 60 + Number(m[5])) * 60000 + Number(m[6] || 0) * 1000;
+    return { timestamp: m[7] ? local : local - utcOffsetMinutes * 60000, allDay: false };
+};
+
+const parseCalendarIcs = (text, { utcOffsetMinutes = 0 } = {}) => {
+    const lines = String(text).replace(/\r\n[ \t]/g, '').replace(/\n[ \t]/g, '').split(/\r?\n/);
+    const events = [];
+    let skipped = 0;
+    let current = null;
+    lines.forEach((line) => {
+        if (line === 'BEGIN:VEVENT') { current = {}; return; }
+        if (line === 'END:VEVENT') {
+            if (current) {
+                const when = current.DTSTART ? parseIcsDate(current.DTSTART.value, current.DTSTART.params, utcOffsetMinutes) : null;
+                const summary = current.SUMMARY ? unescapeIcs(current.SUMMARY.value) : '';
+                if (!when || !summary) skipped += 1;
+                else {
+                    // "USD - Non-Farm Payrolls", "[EUR] CPI", "USD: FOMC"
+                    const prefix = /^\s*\[?([A-Z]{3})\]?\s*[-:|]?\s+(.*)$/.exec(summary);
+                    const currency = normalizeCurrency(current['X-CURRENCY'] && current['X-CURRENCY'].value) || (prefix ? prefix[1] : null);
+                    const priority = current.PRIORITY ? Number(current.PRIORITY.value) : null;
+                    const impactText = (current['X-IMPACT'] && current['X-IMPACT'].value)
+                        || (current.CATEGORIES && current.CATEGORIES.value)
+                        || (priority ? (priority <= 4 ? 'high' : priority === 5 ? 'medium' : 'low') : '')
+                        || (/high impact/i.test(summary) ? 'high' : '');
+                    events.push({
+                        timestamp: when.timestamp,
+                        allDay: when.allDay,
+                        currency,
+                        title: prefix && currency === prefix[1] ? prefix[2] : summary,
+                        impact: normalizeImpact(impactText),
+                        actual: null,
+                        forecast: null,
+                        previous: null
+                    });
+                }
+            }
+            current = null;
+            return;
+        }
+        if (!current) return;
+        const m = /^([A-Z-]+)((?:;[^:]*)?):(.*)$/.exec(line);
+        if (m) current[m[1]] = { params: m[2], value: m[3] };
+    });
+    return { events, skipped };
+};
+
+const detectFormat = (filePath, text) => {
+    if (/\.ics$/i.test(filePath || '') || /^BEGIN:VCALENDAR/m.test(text)) return 'ics';
+    return 'csv';
+};
+
+/**
+ * onWarning(event, minutesUntil) fires once per event entering the warning window.
+ */
+const createEconomicCalendar = ({ db, loadConfig, saveConfig, onWarning = () => {}, onLog, getIntervalScale = () => 1 }) => {
+    const readConfig = () => {
+        const stored = loadConfig() || {};
+        return { ...DEFAULT_CONFIG, ...stored, warnings: { ...DEFAULT_CONFIG.warnings, ...(stored.warnings || {}) } };
+    };
+    let config = readConfig();
+    let warningTimer = null;
+    let refreshTimer = null;
+    const warned = new Set();
+
+    const log = (level, message, data) => onLog && onLog(level, message, data);
+
+    const upsert = db.prepare(`
+        INSERT OR REPLACE INTO econ_events (id, source, timestamp, all_day, currency, title, impact, actual, forecast, previous, imported_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+    `);
+
+    const storeEvents = (source, events) => {
+        const now = Date.now();
+        db.transaction(() => events.forEach((event) => {
+            const id = crypto.createHash('sha1').update(`${source}|${event.timestamp}|${event.currency || ''}|${event.title}`).digest('hex');
+            upsert.run(id, source, event.timestamp, event.allDay ? 1 : 0, event.currency, event.title, event.impact, event.actual, event.forecast, event.previous, now);
+        }))();
+        return events.length;
+    };
+
+    // text or filePath; options: { format?: 'csv' | 'ics', source?, utcOffsetMinutes? }
+    const importFile = (filePath, options = {}) => {
+        const text = options.text != null ? options.text : fs.readFileSync(filePath, 'utf8');
+        const format = options.format || detectFormat(filePath, text);
+        const { events, skipped } = format === 'ics' ? parseCalendarIcs(text, options) : parseCalendarCsv(text, options);
+        const source = options.source || (filePath ? `file:${path.basename(filePath)}` : format);
+        const imported = storeEvents(source, events);
+        log('INFO', 'CALENDAR_IMPORTED', { source, format, imported, skipped });
+        return { source, format, imported, skipped };
+    };
+
+    const refresh = async () => {
+        const url = FEEDS[config.provider];
+        if (!url) throw new Error('No calendar provider configured');
+        const response = await request('calendar', url, { headers: { Accept: 'application/json' } });
+        if (!response.ok) throw new Error(`Calendar feed ${response.status} ${response.statusText}`);
+        const rows = await response.json();
+        const events = (Array.isArray(rows) ? rows : []).map(row => ({
+            timestamp: Date.parse(row.date),
+            allDay: false,
+            currency: normalizeCurrency(row.country),
+            title: String(row.title || '').trim(),
+            impact: normalizeImpact(row.impact),
+            actual: row.actual || null,
+            forecast: row.forecast || null,
+            previous: row.previous || null
+        })).filter(event => Number.isFinite(event.timestamp) && event.title);
+        const imported = storeEvents(config.provider, events);
+        log('INFO', 'CALENDAR_REFRESHED', { provider: config.provider, imported });
+        return { source: config.provider, imported };
+    };
+
+    /**
+     * filter: { from?, to?, currencies?: string[], impact?: minimum level | levels[], limit? }
+     */
+    const getEvents = ({ from = null, to = null, currencies = null, impact = null, limit = MAX_EVENTS_PER_QUERY } = {}) => {
+        const where = [];
+        const params = [];
+        if (from != null) { where.push('timestamp >= ?'); params.push(Number(from)); }
+        if (to != null) { where.push('timestamp <= ?'); params.push(Number(to)); }
+        const codes = (currencies || []).map(normalizeCurrency).filter(Boolean);
+        if (codes.length) {
+            // Global events ('ALL') concern every currency
+            where.push(`(currency IN (${codes.map(() => '?').join(', ')}) OR currency = 'ALL')`);
+            params.push(...codes);
+        }
+        const levels = Array.isArray(impact) ? impact.map(normalizeImpact)
+            : impact ? IMPACTS.slice(IMPACTS.indexOf(normalizeImpact(impact))) : null;
+        if (levels && levels.length) {
+            where.push(`impact IN (${levels.map(() => '?').join(', ')})`);
+            params.push(...levels);
+        }
+        const sql = `SELECT * FROM econ_events${where.length ? ` WHERE ${where.join(' AND ')}` : ''} ORDER BY timestamp LIMIT ?`;
+        return db.prepare(sql).all(...params, Math.min(MAX_EVENTS_PER_QUERY, Number(limit) || MAX_EVENTS_PER_QUERY)).map(mapEventRow);
+    };
+
+    // filter: { source?, before? }
+    const deleteEvents = ({ source = null, before = null } = {}) => {
+        const where = [];
+        const params = [];
+        if (source) { where.push('source = ?'); params.push(source); }
+        if (before != null) { where.push('timestamp < ?'); params.push(Number(before)); }
+        if (!where.length) throw new Error('Pass a source or a cut-off time');
+        return db.prepare(`DELETE FROM econ_events WHERE ${where.join(' AND ')}`).run(...params).changes;
+    };
+
+    const checkWarnings = () => {
+        const { warnings } = config;
+        if (!warnings.enabled) return;
+        const now = Date.now();
+        const ahead = Number(warnings.minutesBefore) * 60000;
+        getEvents({ from: now, to: now + ahead, currencies: warnings.currencies, impact: warnings.impact }).forEach((event) => {
+            if (event.allDay || warned.has(event.id)) return;
+            warned.add(event.id);
+            onWarning(event, Math.max(0, Math.round((event.timestamp - now) / 60000)));
+        });
+        if (warned.size > 1000) warned.clear();
+    };
+
+    const scheduleRefresh = () => {
+        if (refreshTimer) clearTimeout(refreshTimer);
+        refreshTimer = null;
+        if (!config.provider || !(config.refreshHours > 0)) return;
+        refreshTimer = setTimeout(() => {
+            refresh().catch(err => log('WARN', 'CALENDAR_REFRESH_FAILED', { error: err.message })).finally(scheduleRefresh);
+        }, config.refreshHours * 3600000 * getIntervalScale());
+    };
+
+    const start = () => {
+        if (!warningTimer) warningTimer = setInterval(checkWarnings, WARNING_CHECK_MS);
+        if (config.provider) refresh().catch(err => log('WARN', 'CALENDAR_REFRESH_FAILED', { error: err.message }));
+        scheduleRefresh();
+    };
+
+    const stop = () => {
+        if (warningTimer) clearInterval(warningTimer);
+        if (refreshTimer) clearTimeout(refreshTimer);
+        warningTimer = null;
+        refreshTimer = null;
+    };
+
+    // Accepts any subset of { provider, refreshHours, warnings }
+    const configure = (updates = {}) => {
+        if (updates.provider != null && !FEEDS[updates.provider]) throw new Error(`Unknown calendar provider: ${updates.provider}`);
+        config = { ...config, ...updates, warnings: { ...config.warnings, ...(updates.warnings || {}) } };
+        config.warnings.impact = (config.warnings.impact || []).map(normalizeImpact);
+        config.warnings.currencies = (config.warnings.currencies || []).map(normalizeCurrency).filter(Boolean);
+        saveConfig(config);
+        scheduleRefresh();
+        return config;
+    };
+
+    return { start, stop, configure, getConfig: () => config, importFile, refresh, getEvents, deleteEvents, checkWarnings, providers: () => Object.keys(FEEDS) };
+};
+
+module.exports = { initializeCalendarTable, createEconomicCalendar, parseCalendarCsv, parseCalendarIcs, normalizeImpact };
@@ -0,0 +1,127 @@
+
+// --- CHART EDIT JOURNAL ---
+// Persistent undo/redo per chart. Every chart-state save is diffed against the
+// stored state and the drawing-level changes (add / update / delete, with
+// before and after snapshots) are appended as one journal entry. Undo applies
+// an entry's inverse to the current state; redo re-applies it. Recording a new
+// entry drops anything that was undone, like an editor's redo stack.
+
+const MAX_ENTRIES_PER_CHART = 200;
+
+const initializeJournalTable = (db) => {
+    db.exec(`
+        CREATE TABLE IF NOT EXISTS chart_journal (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            source_id TEXT,
+            ops TEXT,
+            undone INTEGER DEFAULT 0,
+            created_at INTEGER
+        );
+        CREATE INDEX IF NOT EXISTS idx_chart_journal ON chart_journal (source_id, id);
+    `);
+};
+
+/**
+ * Drawing-level diff between two chart states:
+ * [{ op: 'add' | 'update' | 'delete', drawingId, before, after, index }]
+ */
+const diffDrawings = (prevState, nextState) => {
+    const prev = (prevState && prevState.drawings) || [];
+    const next = (nextState && nextState.drawings) || [];
+    const prevById = new Map(prev.map((d, i) => [d.id, { d, i }]));
+    const nextIds = new Set(next.map(d => d.id));
+    const ops = [];
+    // Deletes first: add indices refer to the final list, so replaying in order restores positions
+    prev.forEach((d, i) => {
+        if (!nextIds.has(d.id)) ops.push({ op: 'delete', drawingId: d.id, before: d, after: null, index: i });
+    });
+    next.forEach((d, i) => {
+        const old = prevById.get(d.id);
+        if (!old) ops.push({ op: 'add', drawingId: d.id, before: null, after: d, index: i });
+        else if (JSON.stringify(old.d) !== JSON.stringify(d)) ops.push({ op: 'update', drawingId: d.id, before: old.d, after: d, index: i });
+    });
+    return ops;
+};
+
+const trim = (db, sourceId) => {
+    db.prepare(`DELETE FROM chart_journal WHERE source_id = ? AND id NOT IN (
+        SELECT id FROM chart_journal WHERE source_id = ? ORDER BY id DESC LIMIT ?)`).run(sourceId, sourceId, MAX_ENTRIES_PER_CHART);
+};
+
+/**
+ * Journals the change from prevState to nextState. Returns the entry id, or
+ * null when no drawing changed (view-only saves don't pollute history).
+ */
+const recordChartEdit = (db, sourceId, prevState, nextState) => {
+    const ops = diffDrawings(prevState, nextState);
+    if (ops.length === 0) return null;
+    db.prepare('DELETE FROM chart_journal WHERE source_id = ? AND undone = 1').run(sourceId);
+    const { lastInsertRowid } = db.prepare('INSERT INTO chart_journal (source_id, ops, created_at) VALUES (?, ?, ?)').run(sourceId, JSON.stringify(ops), Date.now());
+    trim(db, sourceId);
+    return Number(lastInsertRowid);
+};
+
+// Applies ops to a drawings array; `direction` 'undo' runs them inverted. Undo
+// removes adds and reverts updates first, then re-inserts deletes by ascending
+// original index so every drawing lands back in its old slot.
+const applyOps = (drawings, ops, direction) => {
+    const list = drawings.slice();
+    const sequence = direction === 'undo'
+        ? [...ops.filter(o => o.op !== 'delete').reverse(), ...ops.filter(o => o.op === 'delete')]
+        : ops;
+    sequence.forEach(({ op, drawingId, before, after, index }) => {
+        const removeId = (direction === 'undo' && op === 'add') || (direction === 'redo' && op === 'delete');
+        const insertValue = direction === 'undo' ? before : after;
+        const at = list.findIndex(d => d.id === drawingId);
+        if (removeId) {
+            if (at !== -1) list.splice(at, 1);
+        } else if (at !== -1) {
+            list[at] = insertValue;
+        } else {
+            list.splice(Math.min(index, list.length), 0, insertValue);
+        }
+    });
+    return list;
+};
+
+const step = (db, sourceId, direction) => {
+    const entry = direction === 'undo'
+        ? db.prepare('SELECT id, ops FROM chart_journal WHERE source_id = ? AND undone = 0 ORDER BY id DESC LIMIT 1').get(sourceId)
+        : db.prepare('SELECT id, ops FROM chart_journal WHERE source_id = ? AND undone = 1 ORDER BY id ASC LIMIT 1').get(sourceId);
+    if (!entry) return null;
+
+    const row = db.prepare('SELECT data FROM drawings WHERE symbol = ?').get(sourceId);
+    const state = row ? JSON.parse(row.data) : { sourceId, drawings: [], folders: [] };
+    const ops = JSON.parse(entry.ops);
+    state.drawings = applyOps(state.drawings || [], ops, direction);
+    state.timestamp = Date.now();
+
+    db.transaction(() => {
+        db.prepare('INSERT OR REPLACE INTO drawings (symbol, data) VALUES (?, ?)').run(sourceId, JSON.stringify(state));
+        db.prepare('UPDATE chart_journal SET undone = ? WHERE id = ?').run(direction === 'undo' ? 1 : 0, entry.id);
+    })();
+    return { state, entryId: entry.id, ops: ops.map(o => ({ op: o.op, drawingId: o.drawingId })) };
+};
+
+const undoChartEdit = (db, sourceId) => step(db, sourceId, 'undo');
+const redoChartEdit = (db, sourceId) => step(db, sourceId, 'redo');
+
+const journalStatus = (db, sourceId) => {
+    const counts = db.prepare('SELECT SUM(undone = 0) as undo, SUM(undone = 1) as redo FROM chart_journal WHERE source_id = ?').get(sourceId);
+    return { canUndo: (counts.undo || 0) > 0, canRedo: (counts.redo || 0) > 0, undoDepth: counts.undo || 0, redoDepth: counts.redo || 0 };
+};
+
+/**
+ * Newest-first journal entries for history views, backups and versioning.
+ * With `full`, entries carry the before/after snapshots.
+ */
+const listChartJournal = (db, sourceId, { limit = 50, full = false } = {}) => db.prepare('SELECT id, ops, undone, created_at FROM chart_journal WHERE source_id = ? ORDER BY id DESC LIMIT ?')
+    .all(sourceId, limit)
+    .map((r) => {
+        const ops = JSON.parse(r.ops);
+        return { id: r.id, undone: !!r.undone, createdAt: r.created_at, ops: full ? ops : ops.map(o => ({ op: o.op, drawingId: o.drawingId, type: (o.after || o.before || {}).type })) };
+    });
+
+const clearChartJournal = (db, sourceId) => db.prepare('DELETE FROM chart_journal WHERE source_id = ?').run(sourceId).changes;
+
+module.exports = { initializeJournalTable, diffDrawings, recordChartEdit, undoChartEdit, redoChartEdit, journalStatus, listChartJournal, clearChartJournal };
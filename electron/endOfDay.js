@@ -0,0 +1,114 @@
+
+const fs = require('fs');
+const path = require('path');
+const { TIMEFRAME_MS, insertBars } = require('./datasets');
+const { computeSessionStats } = require('./sessions');
+
+// --- END-OF-DAY MAINTENANCE ---
+// The routine the scheduler runs after the close ('end-of-day' job type). Its
+// steps run in this order, each on its own — a failed step is reported and
+// the rest still run:
+//
+//   finalize-bars    flush the live bar recorder and refresh the recorded datasets' stats
+//   roll-daily       build 1D bars from the intraday sessions of the last rollDays days
+//   rotate-logs      append the system log to logs/system-YYYY-MM-DD.jsonl, drop old files
+//   prune-caches     purge rebuildable caches (storage.purgeCaches targets)
+//   backup           the scheduler's database backup
+//   journal-report   today's trades and activity diary as an HTML (and PDF) report under reports/
+//
+// main.js supplies the step handlers; this module runs them and holds the
+// parts that don't need the app (the daily roll-up and log rotation).
+
+const END_OF_DAY_STEPS = ['finalize-bars', 'roll-daily', 'rotate-logs', 'prune-caches', 'backup', 'journal-report'];
+const ROLLUP_SOURCE = 'rollup';
+const LOG_FILE = /^system-(\d{4}-\d{2}-\d{2})\.jsonl$/;
+const DAY_MS = 86400000;
+
+const cleanSteps = (steps) => {
+    if (steps == null) return END_OF_DAY_STEPS;
+    if (!Array.isArray(steps)) throw new Error('steps must be an array');
+    const unknown = steps.filter(s => !END_OF_DAY_STEPS.includes(s));
+    if (unknown.length) throw new Error(`Unknown end-of-day step: ${unknown.join(', ')}`);
+    return END_OF_DAY_STEPS.filter(s => steps.includes(s));
+};
+
+/**
+ * Runs `steps` in routine order with handlers[step](params) -> summary string.
+ * Returns { startedAt, finishedAt, status: 'success' | 'partial' | 'failed',
+ * steps: [{ step, status: 'done' | 'failed', message, ms }] }.
+ */
+const runEndOfDay = async (handlers, params = {}) => {
+    const steps = cleanSteps(params.steps);
+    const startedAt = Date.now();
+    const results = [];
+    for (const step of steps) {
+        const started = Date.now();
+        try {
+            const message = await handlers[step](params);
+            results.push({ step, status: 'done', message: message == null ? null : String(message), ms: Date.now() - started });
+        } catch (err) {
+            results.push({ step, status: 'failed', message: err.message, ms: Date.now() - started });
+        }
+    }
+    const failed = results.filter(r => r.status === 'failed').length;
+    const status = !failed ? 'success' : failed === results.length ? 'failed' : 'partial';
+    return { startedAt, finishedAt: Date.now(), status, steps: results };
+};
+
+// One line per step, for the notification and the job log
+const formatEndOfDaySummary = (result) => {
+    const failed = result.steps.filter(s => s.status === 'failed').length;
+    const head = failed ? `End of day: ${failed} of ${result.steps.length} steps failed` : `End of day: ${result.steps.length} steps done`;
+    return [head, ...result.steps.map(s => `${s.status === 'failed' ? '✗' : '✓'} ${s.step}${s.message ? ` — ${s.message}` : ''}`)].join('\n');
+};
+
+/**
+ * Daily bars for `symbol` from its finest intraday series: one bar per regular
+ * session that closed in [from, now], stamped at UTC midnight of the session's
+ * day, by the session `calendar` of the symbol. A 1D series imported
+ * from elsewhere is left alone (its bars may be stamped differently).
+ * Returns { symbol, source, bars } or { symbol, skipped }.
+ */
+const rollDailyBars = (db, symbol, { from, now = Date.now(), calendar }) => {
+    const daily = db.prepare("SELECT source FROM datasets WHERE symbol = ? AND timeframe = '1D'").get(symbol);
+    if (daily && daily.source !== ROLLUP_SOURCE) return { symbol, skipped: `1D series comes from ${daily.source}` };
+    const intraday = db.prepare('SELECT timeframe FROM datasets WHERE symbol = ?').all(symbol)
+        .map(r => r.timeframe)
+        .filter(tf => TIMEFRAME_MS[tf] && TIMEFRAME_MS[tf] < DAY_MS)
+        .sort((a, b) => TIMEFRAME_MS[a] - TIMEFRAME_MS[b]);
+    if (!intraday.length) return { symbol, skipped: 'no intraday series' };
+    const source = intraday[0];
+    // From the day before `from` so a session that opened the previous evening is whole
+    const bars = db.prepare('SELECT timestamp, open, high, low, close, volume FROM market_data WHERE symbol = ? AND timeframe = ? AND timestamp >= ? AND timestamp <= ? ORDER BY timestamp')
+        .all(symbol, source, from - DAY_MS, now);
+    const rolled = computeSessionStats(bars, { calendar }).days
+        .filter(d => d.close != null && d.sessionEnd >= from && d.sessionEnd <= now)
+        .map(d => ({ timestamp: Date.parse(`${d.day}T00:00:00Z`), open: d.open, high: d.high, low: d.low, close: d.close, volume: d.volume }));
+    if (rolled.length) insertBars(db, symbol, '1D', rolled);
+    return { symbol, source, bars: rolled.length };
+};
+
+/**
+ * Appends `entries` (system log entries, any order) to one JSONL file per local
+ * day in `dir` and deletes files older than keepDays. Returns { written, files, deleted }.
+ */
+const rotateLogFiles = (dir, entries, { keepDays = 14, now = Date.now() } = {}) => {
+    fs.mkdirSync(dir, { recursive: true });
+    const byDay = new Map();
+    entries.slice().sort((a, b) => a.timestamp - b.timestamp).forEach((entry) => {
+        const d = new Date(entry.timestamp);
+        const day = `${d.getFullYear()}-${String(d.getMonth() + 1).padStart(2, '0')}-${String(d.getDate()).padStart(2, '0')}`;
+        if (!byDay.has(day)) byDay.set(day, []);
+        byDay.get(day).push(JSON.stringify(entry));
+    });
+    byDay.forEach((lines, day) => fs.appendFileSync(path.join(dir, `system-${day}.jsonl`), `${lines.join('\n')}\n`));
+    const cutoff = now - keepDays * DAY_MS;
+    const deleted = fs.readdirSync(dir).filter((f) => {
+        const m = LOG_FILE.exec(f);
+        return m && Date.parse(`${m[1]}T23:59:59`) < cutoff;
+    });
+    deleted.forEach(f => fs.rmSync(path.join(dir, f), { force: true }));
+    return { written: entries.length, files: byDay.size, deleted: deleted.length };
+};
+
+module.exports = { END_OF_DAY_STEPS, ROLLUP_SOURCE, cleanSteps, runEndOfDay, formatEndOfDaySummary, rollDailyBars, rotateLogFiles };
@@ -0,0 +1,326 @@
+
+const crypto = require('crypto');
+const { AppError } = require('./appErrors');
+
+// --- EXPORT TEMPLATES ---
+// User-defined output formats for the long tail of "export to X": a template
+// names a data source (notes, journal, scan, activity), an output format
+// (markdown, html, csv, text) and a body in a small mustache-style language
+// that extends the note templates' {{placeholders}}:
+//
+//   {{path}}                a value; dotted paths reach into objects
+//   {{path|fallback}}       fallback when the value is empty
+//   {{path:fmt}}            a formatter: date, datetime, time, iso, fixed0..fixed8,
+//                           percent, upper, lower, oneline, firstline, join, count, json
+//   {{{path}}}              the value without escaping
+//   {{#each path}}..{{/each}}        repeat per item ({{this}}, {{@index}},
+//                                    {{@number}}, {{@first}}, {{@last}})
+//   {{#if path}}..{{else}}..{{/if}}  and {{#unless path}}..{{/unless}}
+//   {{! comment }}
+//
+// Values are escaped for the format (HTML entities, CSV quoting); names
+// resolve in the innermost #each item first, then outwards to the top level,
+// which holds the source's fields plus items, count, generatedAt and query.
+// Bodies are parsed when saved, so a broken template is refused with the
+// position of the error. main.js supplies the source loaders. Three starter
+// templates are seeded when the table is first created.
+
+const SOURCES = ['notes', 'journal', 'scan', 'activity'];
+const FORMATS = { markdown: 'md', html: 'html', csv: 'csv', text: 'txt' };
+const MAX_BODY_LENGTH = 100000;
+const MAX_NESTING = 16;
+const MAX_OUTPUT_CHARS = 20 * 1024 * 1024;
+const TAG_RE = /\{\{\{\s*([^}]*?)\s*\}\}\}|\{\{\s*([^}]*?)\s*\}\}/g;
+const FORMATTERS = ['date', 'datetime', 'time', 'iso', 'percent', 'upper', 'lower', 'oneline', 'firstline', 'join', 'count', 'json'];
+
+const STARTER_TEMPLATES = [
+    {
+        name: 'Notes as Markdown',
+        source: 'notes',
+        format: 'markdown',
+        body: '# Notes — {{generatedAt:date}}\n\n{{#each items}}## {{text:firstline|Untitled}}\n'
+            + '{{#if symbol}}*{{symbol}}* · {{/if}}updated {{updatedAt:datetime}}{{#if tags}} · {{#each tags}}#{{this}} {{/each}}{{/if}}\n\n'
+            + '{{text}}\n\n{{/each}}'
+    },
+    {
+        name: 'Journal CSV',
+        source: 'journal',
+        format: 'csv',
+        body: 'Date,Symbol,Side,Qty,Entry,Exit,P&L,Tags\n'
+            + '{{#each items}}{{timestamp:date}},{{symbol}},{{side}},{{qty}},{{price}},{{exitPrice}},{{pnl:fixed2}},{{tags:join}}\n{{/each}}'
+    },
+    {
+        name: 'Scan results HTML',
+        source: 'scan',
+        format: 'html',
+        body: '<!DOCTYPE html><html><head><meta charset="utf-8"><title>{{name|Scan}}</title></head><body>\n'
+            + '<h1>{{name|Scan}} — {{ranAt:datetime}}</h1>\n<p><code>{{expression}}</code> · {{count}} of {{scanned}} matched</p>\n'
+            + '<table><thead><tr><th>Symbol</th><th>Bar</th></tr></thead><tbody>\n'
+            + '{{#each items}}<tr><td>{{symbol}}</td><td>{{timestamp:datetime}}</td></tr>\n{{/each}}</tbody></table>\n</body></html>\n'
+    }
+];
+
+const templateError = (message, position) => new AppError('INVALID_INPUT', position == null ? message : `${message} (at character ${position})`, { position });
+
+// {{path:fmt|fallback}} -> { path, format, fallback }
+const parseVariable = (expr, position) => {
+    const pipe = expr.indexOf('|');
+    const head = pipe >= 0 ? expr.slice(0, pipe) : expr;
+    const fallback = pipe >= 0 ? expr.slice(pipe + 1).trim() : undefined;
+    const [path, format = null] = head.split(':').map(s => s.trim());
+    if (!/^(@?[A-Za-z_][A-Za-z0-9_]*|this|\.)(\.[A-Za-z0-9_]+)*$/.test(path)) throw templateError(`Invalid placeholder "${expr}"`, position);
+    if (format && !FORMATTERS.includes(format) && !/^fixed[0-8]$/.test(format)) throw templateError(`Unknown formatter "${format}"`, position);
+    return { path, format, fallback };
+};
+
+/**
+ * Parses a body into nodes: { type: 'text', value } | { type: 'var', path,
+ * format, fallback, raw } | { type: 'each' | 'if' | 'unless', path, body, alt }.
+ */
+const parseTemplate = (source) => {
+    const text = String(source || '');
+    const root = { body: [] };
+    const stack = [];
+    let current = root;
+    let target = root.body;
+    let last = 0;
+    for (const match of text.matchAll(TAG_RE)) {
+        const position = match.index;
+        if (position > last) target.push({ type: 'text', value: text.slice(last, position) });
+        last = position + match[0].length;
+        if (match[1] !== undefined) {
+            target.push({ type: 'var', raw: true, ...parseVariable(match[1], position) });
+            continue;
+        }
+        const tag = match[2];
+        if (tag.startsWith('!')) continue;
+        const open = /^#(each|if|unless)\s+(.+)$/.exec(tag);
+        if (open) {
+            if (stack.length >= MAX_NESTING) throw templateError(`Sections nest deeper than ${MAX_NESTING}`, position);
+            const node = { type: open[1], path: parseVariable(open[2], position).path, body: [], alt: [], position };
+            target.push(node);
+            stack.push({ node: current, target });
+            current = node;
+            target = node.body;
+            continue;
+        }
+        if (tag === 'else') {
+            if (!stack.length || current.type === 'each') throw templateError('{{else}} outside {{#if}} / {{#unless}}', position);
+            target = current.alt;
+            continue;
+        }
+        const close = /^\/(each|if|unless)$/.exec(tag);
+        if (close) {
+            if (!stack.length || current.type !== close[1]) throw templateError(`Unexpected {{/${close[1]}}}`, position);
+            ({ node: current, target } = stack.pop());
+            continue;
+        }
+        if (tag.startsWith('#') || tag.startsWith('/')) throw templateError(`Unknown section "${tag}"`, position);
+        target.push({ type: 'var', raw: false, ...parseVariable(tag, position) });
+    }
+    if (stack.length) throw templateError(`{{#${current.type}}} is never closed`, current.position);
+    if (last < text.length) target.push({ type: 'text', value: text.slice(last) });
+    return root.body;
+};
+
+const fieldsOf = (nodes, out = new Set()) => {
+    nodes.forEach((node) => {
+        if (node.type === 'text') return;
+        if (!node.path.startsWith('@') && node.path !== 'this' && node.path !== '.') out.add(node.path);
+        if (node.body) { fieldsOf(node.body, out); fieldsOf(node.alt, out); }
+    });
+    return Array.from(out);
+};
+
+const ESCAPERS = {
+    html: s => s.replace(/[&<>"']/g, c => ({ '&': '&amp;', '<': '&lt;', '>': '&gt;', '"': '&quot;', "'": '&#39;' }[c])),
+    csv: s => (/[",\r\n]/.test(s) ? `"${s.replace(/"/g, '""')}"` : s),
+    markdown: s => s,
+    text: s => s
+};
+
+const pad = n => String(n).padStart(2, '0');
+const localDate = d => `${d.getFullYear()}-${pad(d.getMonth() + 1)}-${pad(d.getDate())}`;
+const localTime = d => `${pad(d.getHours())}:${pad(d.getMinutes())}`;
+
+const applyFormat = (value, format) => {
+    if (format === 'count') return Array.isArray(value) ? value.length : (value == null ? 0 : 1);
+    if (value == null || value === '') return value;
+    if (format === 'date' || format === 'datetime' || format === 'time' || format === 'iso') {
+        const d = new Date(typeof value === 'number' ? value : String(value));
+        if (Number.isNaN(d.getTime())) return value;
+        if (format === 'iso') return d.toISOString();
+        return format === 'date' ? localDate(d) : format === 'time' ? localTime(d) : `${localDate(d)} ${localTime(d)}`;
+    }
+    const fixed = /^fixed([0-8])$/.exec(format || '');
+    if (fixed) return Number.isFinite(Number(value)) ? Number(value).toFixed(Number(fixed[1])) : value;
+    if (format === 'percent') return Number.isFinite(Number(value)) ? `${(Number(value) * 100).toFixed(1)}%` : value;
+    if (format === 'upper') return String(value).toUpperCase();
+    if (format === 'lower') return String(value).toLowerCase();
+    if (format === 'oneline') return String(value).replace(/\s+/g, ' ').trim();
+    if (format === 'firstline') return String(value).split(/\r?\n/).find(line => line.trim()) || '';
+    if (format === 'join') return Array.isArray(value) ? value.join(', ') : value;
+    if (format === 'json') return JSON.stringify(value);
+    return value;
+};
+
+const stringify = (value) => {
+    if (value == null) return '';
+    if (Array.isArray(value)) return value.map(stringify).join(', ');
+    if (typeof value === 'object') return JSON.stringify(value);
+    return String(value);
+};
+
+// scopes: innermost last; each { data, meta: { index, first, last } }
+const resolve = (scopes, path) => {
+    const scope = scopes[scopes.length - 1];
+    if (path === 'this' || path === '.') return { found: true, value: scope.data };
+    if (path.startsWith('@')) {
+        const meta = scope.meta || {};
+        const key = path.slice(1);
+        return { found: key in meta, value: meta[key] };
+    }
+    const [head, ...rest] = path.split('.');
+    for (let i = scopes.length - 1; i >= 0; i--) {
+        const data = scopes[i].data;
+        if (data != null && typeof data === 'object' && head in data) {
+            let value = data[head];
+            for (const key of rest) value = value != null && typeof value === 'object' ? value[key] : undefined;
+            return { found: true, value };
+        }
+    }
+    return { found: false, value: undefined };
+};
+
+const truthy = value => (Array.isArray(value) ? value.length > 0 : !!value);
+
+/**
+ * Renders parsed nodes against data. Returns { text, missing } where missing
+ * lists placeholders no scope ever defined (likely typos).
+ */
+const renderNodes = (nodes, data, format) => {
+    const escape = ESCAPERS[format] || ESCAPERS.text;
+    const seen = new Set();
+    const unseen = new Set();
+    let out = '';
+    const walk = (list, scopes) => {
+        list.forEach((node) => {
+            if (out.length > MAX_OUTPUT_CHARS) throw new AppError('PAYLOAD_TOO_LARGE', `The export is larger than ${MAX_OUTPUT_CHARS} characters`);
+            if (node.type === 'text') { out += node.value; return; }
+            const { found, value } = resolve(scopes, node.path);
+            (found ? seen : unseen).add(node.path);
+            if (node.type === 'var') {
+                let text = stringify(applyFormat(value, node.format));
+                if (text === '' && node.fallback !== undefined) text = node.fallback;
+                out += node.raw ? text : escape(text);
+                return;
+            }
+            if (node.type === 'each') {
+                const items = Array.isArray(value) ? value : (value == null ? [] : [value]);
+                items.forEach((item, index) => walk(node.body, scopes.concat({ data: item, meta: { index, number: index + 1, first: index === 0, last: index === items.length - 1 } })));
+                return;
+            }
+            const show = node.type === 'if' ? truthy(value) : !truthy(value);
+            walk(show ? node.body : node.alt, scopes);
+        });
+    };
+    walk(nodes, [{ data, meta: {} }]);
+    return { text: out, missing: Array.from(unseen).filter(p => !seen.has(p)) };
+};
+
+const renderTemplateBody = (body, data, format = 'text') => renderNodes(parseTemplate(body), data, format);
+
+const initializeExportTemplateTable = (db) => {
+    const exists = db.prepare("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'export_templates'").get();
+    db.exec(`
+        CREATE TABLE IF NOT EXISTS export_templates (
+            id TEXT PRIMARY KEY,
+            name TEXT,
+            source TEXT,
+            format TEXT,
+            extension TEXT,
+            body TEXT,
+            created_at INTEGER,
+            updated_at INTEGER
+        );
+    `);
+    if (!exists) STARTER_TEMPLATES.forEach(template => saveExportTemplate(db, template));
+};
+
+const mapTemplateRow = (row) => {
+    if (!row) return null;
+    let fields = [];
+    try { fields = fieldsOf(parseTemplate(row.body)); } catch (e) {}
+    return {
+        id: row.id,
+        name: row.name,
+        source: row.source,
+        format: row.format,
+        extension: row.extension,
+        body: row.body,
+        fields,
+        createdAt: row.created_at,
+        updatedAt: row.updated_at
+    };
+};
+
+const listExportTemplates = (db, { source = null } = {}) => (source
+    ? db.prepare('SELECT * FROM export_templates WHERE source = ? ORDER BY name COLLATE NOCASE').all(source)
+    : db.prepare('SELECT * FROM export_templates ORDER BY name COLLATE NOCASE').all()).map(mapTemplateRow);
+
+const getExportTemplate = (db, id) => mapTemplateRow(db.prepare('SELECT * FROM export_templates WHERE id = ?').get(id));
+
+// Checks a template (saved or draft) without storing it; returns the cleaned fields
+const normalizeExportTemplate = (template = {}) => {
+    const name = String(template.name || '').trim();
+    if (!name) throw new AppError('INVALID_INPUT', 'Template name is required');
+    if (name.length > 100) throw new AppError('INVALID_INPUT', 'Template name is too long');
+    if (!SOURCES.includes(template.source)) throw new AppError('INVALID_INPUT', `source must be one of ${SOURCES.join(', ')}`);
+    if (!FORMATS[template.format]) throw new AppError('INVALID_INPUT', `format must be one of ${Object.keys(FORMATS).join(', ')}`);
+    const body = String(template.body == null ? '' : template.body);
+    if (body.length > MAX_BODY_LENGTH) throw new AppError('INVALID_INPUT', `Template body is longer than ${MAX_BODY_LENGTH} characters`);
+    parseTemplate(body);
+    const extension = String(template.extension || FORMATS[template.format]).replace(/^\./, '').trim();
+    if (!/^[A-Za-z0-9]{1,10}$/.test(extension)) throw new AppError('INVALID_INPUT', `Invalid file extension: ${extension}`);
+    return { name, source: template.source, format: template.format, extension, body };
+};
+
+// template: { id?, name, source, format, extension?, body }; saving an existing id updates it
+const saveExportTemplate = (db, template = {}) => {
+    const existing = template.id ? getExportTemplate(db, template.id) : null;
+    const clean = normalizeExportTemplate({ ...(existing || {}), ...template });
+    const id = existing ? existing.id : crypto.randomUUID();
+    if (db.prepare('SELECT 1 FROM export_templates WHERE name = ? COLLATE NOCASE AND id != ?').get(clean.name, id)) throw new AppError('CONFLICT', `An export template named "${clean.name}" already exists`);
+    const now = Date.now();
+    db.prepare('INSERT OR REPLACE INTO export_templates (id, name, source, format, extension, body, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)')
+        .run(id, clean.name, clean.source, clean.format, clean.extension, clean.body, existing ? existing.createdAt : now, now);
+    return getExportTemplate(db, id);
+};
+
+const deleteExportTemplate = (db, id) => db.prepare('DELETE FROM export_templates WHERE id = ?').run(id).changes > 0;
+
+/**
+ * Renders `template` (a saved template or a draft) with loadSource(source,
+ * query) -> { items, ...fields }. Returns { text, missing, count }.
+ */
+const renderExport = async (template, query = {}, { loadSource, now = Date.now() }) => {
+    const clean = normalizeExportTemplate(template);
+    const loaded = (await loadSource(clean.source, query || {})) || {};
+    const items = Array.isArray(loaded.items) ? loaded.items : [];
+    const data = { ...loaded, items, count: items.length, generatedAt: now, query: query || {}, template: { name: clean.name } };
+    const { text, missing } = renderNodes(parseTemplate(clean.body), data, clean.format);
+    return { text, missing, count: items.length };
+};
+
+module.exports = {
+    SOURCES,
+    FORMATS,
+    parseTemplate,
+    renderTemplateBody,
+    initializeExportTemplateTable,
+    listExportTemplates,
+    getExportTemplate,
+    saveExportTemplate,
+    deleteExportTemplate,
+    renderExport
+};
@@ -0,0 +1,370 @@
+
+const { INDICATORS, DEFAULT_PARAMS } = require('./indicators');
+
+// --- INDICATOR EXPRESSIONS ---
+// Small language for screens, alerts, heatmap scripts and custom series, e.g.
+//   rsi(14) < 30 and close > sma(200)
+//   ema(20) crosses_above ema(50) or close[1] * 1.05 < close
+//   ema(close, 20) - ema(close, 50)
+//   sma(rsi(14), 5) > 50 and sma(high - low, 10) > atr(14)
+// Values: numbers, bar fields (open, high, low, close, volume), indicator
+// calls (sma, ema, rsi, atr, stddev with an optional period; macd,
+// macd_signal, macd_hist with optional fast, slow, signal) and x[n] for n
+// bars ago. Every indicator but atr takes an optional source series first
+// (default close), which can be any value expression, including other
+// calls. Operators: + - * /, < <= > >= == !=, crosses_above /
+// crosses_below, and / or / not. Screens and alerts need a condition;
+// heatmap scripts and custom series are values ({ condition: false }).
+// Compiled once into a plain-object tree (it crosses worker boundaries) and
+// evaluated against bar arrays, or bar by bar on live data with
+// createExpressionStream; a missing value (warm-up) makes a comparison
+// false. A null in a source series after its warm-up (e.g. a division by
+// zero) repeats the previous value for the indicator reading it.
+// Kept free of Electron and SQLite so compute workers can load it.
+
+const FIELDS = ['open', 'high', 'low', 'close', 'volume'];
+const COMPARISONS = ['<', '<=', '>', '>=', '==', '!='];
+const CROSSES = ['crosses_above', 'crosses_below'];
+const MAX_LENGTH = 2000;
+const MAX_SHIFT = 1000;
+const MAX_CALLS = 50;
+// atr reads high, low and close itself
+const SOURCE_INDICATORS = Object.keys(INDICATORS).filter(name => name !== 'atr');
+const KEYWORDS = ['and', 'or', 'not', ...CROSSES];
+
+class ExpressionError extends Error {
+    constructor(message, position) {
+        super(position != null ? `${message} (at ${position + 1})` : message);
+        this.code = 'EXPRESSION_INVALID';
+        this.position = position;
+    }
+}
+
+const editDistance = (a, b) => {
+    const row = Array.from({ length: b.length + 1 }, (_, j) => j);
+    for (let i = 1; i <= a.length; i++) {
+        let prev = row[0];
+        row[0] = i;
+        for (let j = 1; j <= b.length; j++) {
+            const next = Math.min(row[j] + 1, row[j - 1] + 1, prev + (a[i - 1] === b[j - 1] ? 0 : 1));
+            prev = row[j];
+            row[j] = next;
+        }
+    }
+    return row[b.length];
+};
+
+// " (did you mean 'ema'?)" for near misses of known names
+const suggestion = (name) => {
+    let best = null;
+    [...FIELDS, ...Object.keys(INDICATORS), ...KEYWORDS].forEach((candidate) => {
+        const d = editDistance(name, candidate);
+        if (d <= Math.max(1, Math.floor(candidate.length / 3)) && (!best || d < best.d)) best = { candidate, d };
+    });
+    return best ? ` (did you mean '${best.candidate}'?)` : '';
+};
+
+// Canonical text of a value, used to key indicator calls on derived sources
+const printNode = (node) => {
+    const wrap = (n) => (['math', 'compare', 'cross', 'logic'].includes(n.type) ? `(${printNode(n)})` : printNode(n));
+    switch (node.type) {
+        case 'num': return String(node.value);
+        case 'field': return node.name;
+        case 'call': return node.key;
+        case 'shift': return `${wrap(node.arg)}[${node.bars}]`;
+        case 'neg': return `-${wrap(node.arg)}`;
+        case 'not': return `not ${wrap(node.arg)}`;
+        default: return `${wrap(node.left)} ${node.op} ${wrap(node.right)}`;
+    }
+};
+
+const tokenize = (text) => {
+    const tokens = [];
+    const re = /(\d+(?:\.\d+)?(?:e[+-]?\d+)?)|([A-Za-z_][A-Za-z0-9_]*)|(<=|>=|==|!=|&&|\|\||[-+*/<>()[\],!])/y;
+    const aliases = { '&&': 'and', '||': 'or', '!': 'not' };
+    let pos = 0;
+    while (pos < text.length) {
+        if (/\s/.test(text[pos])) { pos++; continue; }
+        re.lastIndex = pos;
+        const m = re.exec(text);
+        if (!m) throw new ExpressionError(`Unexpected character '${text[pos]}'`, pos);
+        if (m[1]) tokens.push({ type: 'num', value: Number(m[1]), pos });
+        else if (m[2]) tokens.push({ type: 'ident', value: m[2].toLowerCase(), pos });
+        else if (aliases[m[3]]) tokens.push({ type: 'ident', value: aliases[m[3]], pos });
+        else tokens.push({ type: 'op', value: m[3], pos });
+        pos = re.lastIndex;
+    }
+    tokens.push({ type: 'end', pos: text.length });
+    return tokens;
+};
+
+/**
+ * Parses an expression into { ast, source, calls, fields, lookback }.
+ * options.condition (default true) requires a condition rather than a value.
+ * Throws ExpressionError with the 0-based position of the problem.
+ */
+const parseExpression = (source, { condition = true } = {}) => {
+    const text = String(source == null ? '' : source).trim();
+    if (!text) throw new ExpressionError('Expression is empty');
+    if (text.length > MAX_LENGTH) throw new ExpressionError(`Expression is longer than ${MAX_LENGTH} characters`);
+    const tokens = tokenize(text);
+    let i = 0;
+    const calls = new Map(); // key -> { key, indicator, params, source, lookback }, inner calls first
+    const fields = new Set();
+    const peek = () => tokens[i];
+    const isWord = (word) => peek().type === 'ident' && peek().value === word;
+    const isOp = (op) => peek().type === 'op' && peek().value === op;
+    const expect = (op) => {
+        if (!isOp(op)) throw new ExpressionError(`Expected '${op}'`, peek().pos);
+        i++;
+    };
+
+    const parseOr = () => {
+        let left = parseAnd();
+        while (isWord('or')) { i++; left = { type: 'logic', op: 'or', left, right: parseAnd() }; }
+        return left;
+    };
+    const parseAnd = () => {
+        let left = parseNot();
+        while (isWord('and')) { i++; left = { type: 'logic', op: 'and', left, right: parseNot() }; }
+        return left;
+    };
+    const parseNot = () => {
+        if (isWord('not')) { i++; return { type: 'not', arg: parseNot() }; }
+        return parseComparison();
+    };
+    const parseComparison = () => {
+        const left = parseSum();
+        const token = peek();
+        if (token.type === 'op' && COMPARISONS.includes(token.value)) { i++; return { type: 'compare', op: token.value, left, right: parseSum() }; }
+        if (token.type === 'ident' && CROSSES.includes(token.value)) { i++; return { type: 'cross', op: token.value, left, right: parseSum() }; }
+        return left;
+    };
+    const parseSum = () => {
+        let left = parseProduct();
+        while (isOp('+') || isOp('-')) { const op = tokens[i++].value; left = { type: 'math', op, left, right: parseProduct() }; }
+        return left;
+    };
+    const parseProduct = () => {
+        let left = parseUnary();
+        while (isOp('*') || isOp('/')) { const op = tokens[i++].value; left = { type: 'math', op, left, right: parseUnary() }; }
+        return left;
+    };
+    const parseUnary = () => {
+        if (isOp('-')) { i++; return { type: 'neg', arg: parseUnary() }; }
+        let node = parsePrimary();
+        while (isOp('[')) {
+            i++;
+            const token = peek();
+            if (token.type !== 'num' || !Number.isInteger(token.value) || token.value > MAX_SHIFT) throw new ExpressionError(`Bars ago must be a whole number up to ${MAX_SHIFT}`, token.pos);
+            i++;
+            expect(']');
+            node = { type: 'shift', arg: node, bars: token.value };
+        }
+        return node;
+    };
+    const parsePrimary = () => {
+        const token = peek();
+        if (token.type === 'num') { i++; return { type: 'num', value: token.value }; }
+        if (isOp('(')) {
+            i++;
+            const inner = parseOr();
+            expect(')');
+            return inner;
+        }
+        if (token.type !== 'ident') throw new ExpressionError(token.type === 'end' ? 'Unexpected end of expression' : `Unexpected '${token.value}'`, token.pos);
+        i++;
+        if (FIELDS.includes(token.value)) {
+            fields.add(token.value);
+            return { type: 'field', name: token.value };
+        }
+        if (!INDICATORS[token.value]) {
+            const kind = isOp('(') ? 'function' : 'name';
+            throw new ExpressionError(`Unknown ${kind} '${token.value}'${suggestion(token.value)}`, token.pos);
+        }
+        const args = [];
+        let source = null;
+        if (isOp('(')) {
+            i++;
+            // A leading plain number is a parameter; anything else is the source series
+            const next = tokens[i + 1];
+            if (!isOp(')') && !(peek().type === 'num' && next.type === 'op' && (next.value === ',' || next.value === ')'))) {
+                if (!SOURCE_INDICATORS.includes(token.value)) throw new ExpressionError(`${token.value}() reads high, low and close itself and takes no source series`, peek().pos);
+                source = parseSum();
+                if (isOp(',')) i++;
+                else if (!isOp(')')) throw new ExpressionError(`Expected ',' or ')' after the source of ${token.value}()`, peek().pos);
+            }
+            while (!isOp(')')) {
+                const arg = peek();
+                if (arg.type !== 'num') throw new ExpressionError(`${token.value}() parameters must be plain numbers${source ? '' : ' (a source series goes first)'}`, arg.pos);
+                args.push(arg.value);
+                i++;
+                if (!isOp(',')) break;
+                i++;
+            }
+            expect(')');
+        }
+        if (source && source.type === 'field' && source.name === 'close') source = null;
+        // Arguments fill the indicator's parameters in order
+        const names = Object.keys(DEFAULT_PARAMS[token.value]);
+        if (args.length > names.length) throw new ExpressionError(`${token.value}() takes ${source ? 'a source series and ' : ''}${names.length === 1 ? 'one period' : `up to ${names.length} numbers (${names.join(', ')})`}`, token.pos);
+        const params = {};
+        names.forEach((name, n) => {
+            const value = n < args.length ? args[n] : DEFAULT_PARAMS[token.value][name];
+            if (!Number.isInteger(value) || value < 1 || value > 5000) throw new ExpressionError(`${token.value}() ${name} must be a whole number from 1 to 5000`, token.pos);
+            params[name] = value;
+        });
+        const key = `${token.value}(${[...(source ? [printNode(source)] : []), ...names.map(name => params[name])].join(',')})`;
+        if (!calls.has(key)) {
+            if (calls.size >= MAX_CALLS) throw new ExpressionError(`At most ${MAX_CALLS} different indicator calls per expression`, token.pos);
+            const call = { key, indicator: token.value, params, source };
+            call.lookback = settle(call) + (source ? depth(source) : 0);
+            calls.set(key, call);
+        }
+        return { type: 'call', key };
+    };
+
+    // Bars needed before the last one: each call's period (EMA-style
+    // indicators need a few periods to settle) on top of what its source
+    // needs, plus shifts and the bar a cross looks back on
+    const settle = (c) => {
+        if (c.indicator === 'sma' || c.indicator === 'stddev') return c.params.period;
+        if (c.params.period) return c.params.period * 4;
+        return c.params.slow * 4 + c.params.signal;
+    };
+    const depth = (node) => {
+        switch (node.type) {
+            case 'call': return calls.get(node.key).lookback;
+            case 'shift': return node.bars + depth(node.arg);
+            case 'cross': return 1 + Math.max(depth(node.left), depth(node.right));
+            case 'compare': case 'logic': case 'math': return Math.max(depth(node.left), depth(node.right));
+            case 'not': case 'neg': return depth(node.arg);
+            default: return 0;
+        }
+    };
+
+    const ast = parseOr();
+    if (peek().type !== 'end') {
+        const token = peek();
+        const hint = token.type === 'ident' && !FIELDS.includes(token.value) && !INDICATORS[token.value] ? suggestion(token.value) : '';
+        throw new ExpressionError(`Unexpected '${token.value}'${hint || (token.value === ')' ? ' (unbalanced parentheses)' : ' (missing an operator?)')}`, token.pos);
+    }
+    if (condition && !['compare', 'cross', 'logic', 'not'].includes(ast.type)) throw new ExpressionError('Expression must be a condition (use a comparison such as < or crosses_above)');
+    return { ast, source: text, condition: ['compare', 'cross', 'logic', 'not'].includes(ast.type), calls: Array.from(calls.values()), fields: Array.from(fields), lookback: depth(ast) + 1 };
+};
+
+// { valid, error?, position?, indicators?, lookback? }, never throws
+const validateExpression = (source, options = {}) => {
+    try {
+        const { calls, fields, lookback } = parseExpression(source, options);
+        return { valid: true, indicators: calls.map(c => c.key), fields, lookback };
+    } catch (err) {
+        if (!(err instanceof ExpressionError)) throw err;
+        return { valid: false, error: err.message, position: err.position ?? null };
+    }
+};
+
+const num = (v) => (v == null || Number.isNaN(v) ? null : v);
+const toNumber = (v) => (typeof v === 'boolean' ? (v ? 1 : 0) : num(v));
+
+// Indicator over a derived series: starts where the source does, holding its last value over later gaps
+const indicatorOnValues = (call, values) => {
+    const first = values.findIndex(v => v != null);
+    if (first === -1) return new Array(values.length).fill(null);
+    let last = null;
+    const pseudo = values.slice(first).map((v) => { if (v != null) last = v; return { close: last }; });
+    return new Array(first).fill(null).concat(INDICATORS[call.indicator](pseudo, call.params));
+};
+
+// Computes every call's series once (inner calls first) and returns at(node, i)
+const createEvaluator = (expr, bars) => {
+    const series = {};
+    const at = (node, i) => {
+        if (i < 0) return null;
+        switch (node.type) {
+            case 'num': return node.value;
+            case 'field': return num(bars[i][node.name]);
+            case 'call': return num(series[node.key][i]);
+            case 'shift': return at(node.arg, i - node.bars);
+            case 'neg': { const v = at(node.arg, i); return v == null ? null : -v; }
+            case 'math': {
+                const a = toNumber(at(node.left, i));
+                const b = toNumber(at(node.right, i));
+                if (a == null || b == null) return null;
+                if (node.op === '+') return a + b;
+                if (node.op === '-') return a - b;
+                if (node.op === '*') return a * b;
+                return b === 0 ? null : a / b;
+            }
+            case 'compare': {
+                const a = toNumber(at(node.left, i));
+                const b = toNumber(at(node.right, i));
+                if (a == null || b == null) return false;
+                return { '<': a < b, '<=': a <= b, '>': a > b, '>=': a >= b, '==': a === b, '!=': a !== b }[node.op];
+            }
+            case 'cross': {
+                const a = at(node.left, i); const b = at(node.right, i);
+                const pa = at(node.left, i - 1); const pb = at(node.right, i - 1);
+                if ([a, b, pa, pb].some(v => v == null)) return false;
+                return node.op === 'crosses_above' ? pa <= pb && a > b : pa >= pb && a < b;
+            }
+            case 'logic': return node.op === 'and' ? !!at(node.left, i) && !!at(node.right, i) : !!at(node.left, i) || !!at(node.right, i);
+            case 'not': return !at(node.arg, i);
+            default: return null;
+        }
+    };
+    expr.calls.forEach((call) => {
+        series[call.key] = call.source
+            ? indicatorOnValues(call, bars.map((bar, i) => toNumber(at(call.source, i))))
+            : INDICATORS[call.indicator](bars, call.params);
+    });
+    return { at, series };
+};
+
+/**
+ * Evaluates a parsed expression (or its source) over bars, oldest first.
+ * index: bar to evaluate (default the last). Returns { matched, timestamp,
+ * values, value } where values holds every indicator and field at that bar
+ * and value is the result of a value expression (null for conditions).
+ */
+const evaluateExpression = (compiled, bars, index = bars.length - 1) => {
+    const expr = typeof compiled === 'string' ? parseExpression(compiled) : compiled;
+    if (!bars.length || index < 0) return { matched: false, timestamp: null, values: {}, value: null };
+    const { at, series } = createEvaluator(expr, bars);
+    const values = {};
+    expr.fields.forEach((field) => { values[field] = num(bars[index][field]); });
+    expr.calls.forEach((call) => { values[call.key] = num(series[call.key][index]); });
+    const result = at(expr.ast, index);
+    return { matched: !!result, timestamp: bars[index].timestamp, values, value: typeof result === 'number' ? result : null };
+};
+
+// Value at every bar (conditions give 1 / 0), aligned with bars
+const evaluateSeries = (compiled, bars) => {
+    const expr = typeof compiled === 'string' ? parseExpression(compiled, { condition: false }) : compiled;
+    const { at } = createEvaluator(expr, bars);
+    return bars.map((bar, i) => toNumber(at(expr.ast, i)));
+};
+
+/**
+ * Live evaluation with the incremental calculators' interface: next(bar,
+ * commit) returns the value at `bar`; only commit=true (a closed bar) keeps
+ * it. Each call re-evaluates the last `lookback` bars, so the cost per bar
+ * is bounded by the expression rather than by the history. Seed by
+ * replaying history with commit=true.
+ */
+const createExpressionStream = (compiled) => {
+    const expr = typeof compiled === 'string' ? parseExpression(compiled, { condition: false }) : compiled;
+    const tail = [];
+    return (bar, commit) => {
+        const window = tail.length ? [...tail, bar] : [bar];
+        if (window.length > expr.lookback) window.shift();
+        if (commit) {
+            tail.push(bar);
+            if (tail.length > expr.lookback) tail.shift();
+        }
+        const { at } = createEvaluator(expr, window);
+        return toNumber(at(expr.ast, window.length - 1));
+    };
+};
+
+module.exports = { ExpressionError, parseExpression, validateExpression, evaluateExpression, evaluateSeries, createExpressionStream };
@@ -0,0 +1,175 @@
+
+const crypto = require('crypto');
+const fs = require('fs');
+const path = require('path');
+
+// --- EXTERNAL DATABASE CHANGES ---
+// Notices when something other than this process writes the profile's
+// database: a sync client bringing in another machine's edits, a second
+// instance, a manual edit with a SQLite tool. Two triggers feed one check —
+// a watch on the profile directory (redpill.db and its -wal) and a poll,
+// since watches are unreliable on synced and network folders. The check
+// reads PRAGMA data_version, which moves only when another connection
+// commits, and only then diffs the watched tables against the last snapshot
+// (retaken whenever total_changes() shows this connection wrote, so the app's
+// own saves aren't reported back to it):
+//
+//   drawings       sourceId -> the stored chart state text
+//   sticky_notes   id -> the row
+//
+// A database file swapped for a different one (a sync client replacing it
+// rather than writing into it) can't be followed by the open connection; it
+// is reported once as `replaced` and the app has to be restarted.
+
+const DEFAULT_POLL_MS = 5000;
+const DEBOUNCE_MS = 300;
+const MIN_CHECK_INTERVAL_MS = 2000; // a worker importing bars bumps data_version constantly
+
+const digest = text => crypto.createHash('sha1').update(text).digest('hex');
+
+// table -> key -> { hash, text }
+const SNAPSHOTS = {
+    drawings: db => new Map(db.prepare('SELECT symbol, data FROM drawings').all()
+        .map(r => [r.symbol, { hash: digest(r.data || ''), text: r.data }])),
+    notes: db => new Map(db.prepare('SELECT * FROM sticky_notes').all()
+        .map((r) => { const text = JSON.stringify(r); return [r.id, { hash: digest(text), text }]; }))
+};
+
+// [{ key, op: 'add' | 'update' | 'delete', before, after }] with the stored texts
+const diffSnapshot = (before, after) => {
+    const changes = [];
+    after.forEach((entry, key) => {
+        const previous = before.get(key);
+        if (!previous) changes.push({ key, op: 'add', before: null, after: entry.text });
+        else if (previous.hash !== entry.hash) changes.push({ key, op: 'update', before: previous.text, after: entry.text });
+    });
+    before.forEach((entry, key) => { if (!after.has(key)) changes.push({ key, op: 'delete', before: entry.text, after: null }); });
+    return changes;
+};
+
+const fileIdentity = (file) => {
+    try {
+        const stat = fs.statSync(file);
+        return `${stat.dev}:${stat.ino}`;
+    } catch (e) {
+        return null;
+    }
+};
+
+/**
+ * dbFile: path of the open database. getDb() -> the connection (null while closed).
+ * onChanges({ drawings: [...], notes: [...] }) gets the diff of each table with
+ * changes (entries { key, op: 'add' | 'update' | 'delete', before, after });
+ * onReplaced() runs once when the file isn't the one that was opened anymore.
+ */
+const createExternalChangeMonitor = ({ dbFile, getDb, onChanges, onReplaced = () => {}, onLog = () => {}, pollMs = DEFAULT_POLL_MS }) => {
+    const dir = path.dirname(dbFile);
+    const watchedNames = new Set([path.basename(dbFile), `${path.basename(dbFile)}-wal`]);
+    let watcher = null;
+    let pollTimer = null;
+    let debounceTimer = null;
+    let identity = null;
+    let dataVersion = null;
+    let ownChanges = null;
+    let snapshots = null;
+    let lastCheckAt = 0;
+    let lastChangeAt = null;
+    let changesSeen = 0;
+    let replaced = false;
+
+    const takeSnapshots = (db) => {
+        const next = {};
+        Object.entries(SNAPSHOTS).forEach(([table, read]) => { next[table] = read(db); });
+        return next;
+    };
+
+    const check = ({ force = false } = {}) => {
+        const db = getDb();
+        if (!db || replaced) return { checked: false, changes: 0 };
+        if (!force && Date.now() - lastCheckAt < MIN_CHECK_INTERVAL_MS) {
+            schedule(MIN_CHECK_INTERVAL_MS);
+            return { checked: false, changes: 0 };
+        }
+        lastCheckAt = Date.now();
+        const current = fileIdentity(dbFile);
+        if (current && identity && current !== identity) {
+            replaced = true;
+            onLog('CRITICAL', 'DATABASE_FILE_REPLACED', { file: dbFile });
+            onReplaced();
+            return { checked: true, changes: 0, replaced: true };
+        }
+        const version = db.pragma('data_version', { simple: true });
+        const own = db.prepare('SELECT total_changes() AS n').get().n;
+        if (version === dataVersion) {
+            if (own !== ownChanges) rebase();
+            return { checked: true, changes: 0 };
+        }
+        dataVersion = version;
+        ownChanges = own;
+        const next = takeSnapshots(db);
+        const diff = {};
+        let count = 0;
+        Object.keys(next).forEach((table) => {
+            const changes = diffSnapshot(snapshots[table], next[table]);
+            if (changes.length) { diff[table] = changes; count += changes.length; }
+        });
+        snapshots = next;
+        if (count) {
+            lastChangeAt = Date.now();
+            changesSeen += count;
+            onChanges(diff);
+        }
+        return { checked: true, changes: count };
+    };
+
+    const safeCheck = (options) => {
+        try {
+            return check(options);
+        } catch (err) {
+            onLog('WARN', 'EXTERNAL_CHANGE_CHECK_FAILED', { error: err.message });
+            return { checked: false, changes: 0, error: err.message };
+        }
+    };
+
+    function schedule(delay = DEBOUNCE_MS) {
+        if (debounceTimer) return;
+        debounceTimer = setTimeout(() => { debounceTimer = null; safeCheck(); }, delay);
+    }
+
+    // The baseline: whatever is stored now
+    function rebase() {
+        const db = getDb();
+        if (!db) return;
+        identity = identity || fileIdentity(dbFile);
+        dataVersion = db.pragma('data_version', { simple: true });
+        ownChanges = db.prepare('SELECT total_changes() AS n').get().n;
+        snapshots = takeSnapshots(db);
+    }
+
+    const start = () => {
+        if (pollTimer) return;
+        rebase();
+        try {
+            watcher = fs.watch(dir, (eventType, filename) => { if (!filename || watchedNames.has(String(filename))) schedule(); });
+            watcher.on('error', () => { if (watcher) watcher.close(); watcher = null; });
+        } catch (err) {
+            onLog('WARN', 'EXTERNAL_CHANGE_WATCH_FAILED', { dir, error: err.message });
+        }
+        pollTimer = setInterval(safeCheck, pollMs);
+    };
+
+    const stop = () => {
+        if (watcher) watcher.close();
+        watcher = null;
+        clearInterval(pollTimer);
+        clearTimeout(debounceTimer);
+        pollTimer = null;
+        debounceTimer = null;
+    };
+
+    const status = () => ({ running: !!pollTimer, watching: !!watcher, dataVersion, lastChangeAt, changesSeen, replaced });
+
+    return { start, stop, check: () => safeCheck({ force: true }), rebase, status };
+};
+
+module.exports = { createExternalChangeMonitor };
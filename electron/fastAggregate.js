@@ -0,0 +1,147 @@
+
+// --- FAST RANGE AGGREGATION ---
+// Session levels, extended-hours ranges and heatmap cells spend their time in
+// high / low / volume loops over bar ranges. The old shape sliced the range
+// and ran forEach or reduce over the copy, and took min / max by spreading
+// into Math.min / Math.max (which also throws past ~120k arguments). The
+// fast shape walks the index range in place, with no copy and no callback:
+//
+//   rangeStats   highest high / lowest low (first bar wins a tie), volume and
+//                typical-price x volume sums over bars[from, to)
+//   sumField     sum of one numeric field over bars[from, to), missing = 0
+//   extent       min / max of a number list, nulls skipped
+//
+// Sums run in bar order on both paths, so the results are identical.
+// On unless the `compute.fastAggregate` setting is false: main calls
+// setFastAggregate, compute workers get the flag with every batch.
+// runAggregateBenchmark (part of the hidden debug:benchmark-parse report)
+// times both paths on generated bars and checks they agree.
+
+let enabled = true;
+
+const setFastAggregate = (on) => { enabled = on !== false; };
+
+const sliceRangeStats = (bars, from, to) => {
+    const slice = bars.slice(from, to);
+    if (!slice.length) return null;
+    let highIndex = 0;
+    let lowIndex = 0;
+    let volume = 0;
+    let pv = 0;
+    slice.forEach((bar, i) => {
+        if (bar.high > slice[highIndex].high) highIndex = i;
+        if (bar.low < slice[lowIndex].low) lowIndex = i;
+        volume += bar.volume || 0;
+        pv += ((bar.high + bar.low + bar.close) / 3) * (bar.volume || 0);
+    });
+    return { highIndex: from + highIndex, lowIndex: from + lowIndex, volume, pv, count: slice.length };
+};
+
+const loopRangeStats = (bars, from, to) => {
+    if (to <= from) return null;
+    let highIndex = from;
+    let lowIndex = from;
+    let high = bars[from].high;
+    let low = bars[from].low;
+    let volume = 0;
+    let pv = 0;
+    for (let i = from; i < to; i++) {
+        const bar = bars[i];
+        const h = bar.high, l = bar.low, v = bar.volume || 0;
+        if (h > high) { high = h; highIndex = i; }
+        if (l < low) { low = l; lowIndex = i; }
+        volume += v;
+        pv += ((h + l + bar.close) / 3) * v;
+    }
+    return { highIndex, lowIndex, volume, pv, count: to - from };
+};
+
+/**
+ * bars[from, to) -> { highIndex, lowIndex, volume, pv, count } (indexes into
+ * bars), or null for an empty range.
+ */
+const rangeStats = (bars, from = 0, to = bars.length) => {
+    const start = Math.max(0, from), end = Math.min(bars.length, to);
+    return enabled ? loopRangeStats(bars, start, end) : sliceRangeStats(bars, start, end);
+};
+
+const sumField = (bars, field, from = 0, to = bars.length) => {
+    const start = Math.max(0, from), end = Math.min(bars.length, to);
+    if (!enabled) return bars.slice(start, end).reduce((sum, b) => sum + (b[field] || 0), 0);
+    let sum = 0;
+    for (let i = start; i < end; i++) sum += bars[i][field] || 0;
+    return sum;
+};
+
+// { min, max } of the non-null values, both null when there are none
+const extent = (values) => {
+    if (!enabled) {
+        const present = values.filter(v => v != null);
+        return { min: present.length ? Math.min(...present) : null, max: present.length ? Math.max(...present) : null };
+    }
+    let min = null, max = null;
+    for (let i = 0; i < values.length; i++) {
+        const v = values[i];
+        if (v == null) continue;
+        if (min === null || v < min) min = v;
+        if (max === null || v > max) max = v;
+    }
+    return { min, max };
+};
+
+// Minute bars with a drifting price, like a day of intraday data repeated
+const buildSampleBars = (count) => {
+    const bars = new Array(count);
+    let price = 100;
+    for (let i = 0; i < count; i++) {
+        price = Math.max(1, price + Math.sin(i * 0.37) * 0.05);
+        bars[i] = { timestamp: 1577836800000 + i * 60000, open: price, high: price + 0.03, low: price - 0.02, close: price + 0.01, volume: 100 + (i % 900) };
+    }
+    return bars;
+};
+
+// Every window of `width` bars stepped by width / 4: the shape of per-session and per-cell ranges
+const aggregateWindows = (bars, width) => {
+    const out = [];
+    const step = Math.max(1, Math.floor(width / 4));
+    for (let from = 0; from + width <= bars.length; from += step) {
+        const r = rangeStats(bars, from, from + width);
+        out.push(r.highIndex, r.lowIndex, r.volume, r.pv, sumField(bars, 'volume', from, from + width));
+    }
+    const { min, max } = extent(out);
+    out.push(min, max);
+    return out;
+};
+
+/**
+ * Returns { bars, window, iterations, slice: { meanMs, windowsPerSec }, fast: {...}, speedup, identical }.
+ */
+const runAggregateBenchmark = ({ bars: count = 200000, window = 390, iterations = 5 } = {}) => {
+    const bars = buildSampleBars(count);
+    const windows = Math.max(0, Math.floor((count - window) / Math.max(1, Math.floor(window / 4))) + 1);
+    const timeIt = (fast) => {
+        setFastAggregate(fast);
+        const samples = [];
+        let result = null;
+        for (let i = 0; i < iterations; i++) {
+            const start = process.hrtime.bigint();
+            result = aggregateWindows(bars, window);
+            samples.push(Number(process.hrtime.bigint() - start) / 1e6);
+        }
+        const meanMs = samples.reduce((n, v) => n + v, 0) / samples.length;
+        return { result, stats: { meanMs: Math.round(meanMs * 1000) / 1000, windowsPerSec: Math.round(windows / (meanMs / 1000)) } };
+    };
+    const previous = enabled;
+    try {
+        setFastAggregate(true); aggregateWindows(bars.slice(0, 5000), window); // warm-up
+        setFastAggregate(false); aggregateWindows(bars.slice(0, 5000), window);
+        const slice = timeIt(false);
+        const fast = timeIt(true);
+        const identical = slice.result.length === fast.result.length && slice.result.every((v, i) => Object.is(v, fast.result[i]));
+        return { bars: count, window, iterations, slice: slice.stats, fast: fast.stats, speedup: Math.round((slice.stats.meanMs / fast.stats.meanMs) * 100) / 100, identical };
+    } finally {
+        setFastAggregate(previous);
+    }
+};
+
+module.exports = { setFastAggregate, rangeStats, sumField, extent, runAggregateBenchmark };
@@ -0,0 +1,48 @@
+
+const test = require('node:test');
+const assert = require('node:assert');
+const { setFastAggregate, rangeStats, sumField, extent } = require('./fastAggregate');
+
+const bar = (high, low, volume, close = (high + low) / 2) => ({ timestamp: 0, open: close, high, low, close, volume });
+
+const bothPaths = (fn) => {
+    try {
+        setFastAggregate(true);
+        const fast = fn();
+        setFastAggregate(false);
+        return { fast, slice: fn() };
+    } finally {
+        setFastAggregate(true);
+    }
+};
+
+test('rangeStats finds the first highest high and lowest low of the range', () => {
+    const bars = [bar(5, 1, 10), bar(7, 2, 20), bar(7, 1, 30), bar(6, 3, 40)];
+    const { fast, slice } = bothPaths(() => rangeStats(bars, 1, 4));
+    assert.deepStrictEqual(fast, slice);
+    assert.strictEqual(fast.highIndex, 1);
+    assert.strictEqual(fast.lowIndex, 2);
+    assert.strictEqual(fast.volume, 90);
+    assert.strictEqual(fast.count, 3);
+});
+
+test('empty ranges give null on both paths', () => {
+    const bars = [bar(5, 1, 10)];
+    assert.deepStrictEqual(bothPaths(() => rangeStats(bars, 1, 1)), { fast: null, slice: null });
+    assert.deepStrictEqual(bothPaths(() => rangeStats([])), { fast: null, slice: null });
+});
+
+test('sums add in bar order, so both paths agree to the bit', () => {
+    const bars = Array.from({ length: 5000 }, (_, i) => bar(1 + i * 0.001, 0.5, i % 13 ? 0.1 * i : undefined));
+    const { fast, slice } = bothPaths(() => [rangeStats(bars, 17, 4711), sumField(bars, 'volume', 100)]);
+    assert.ok(Object.is(fast[0].pv, slice[0].pv));
+    assert.ok(Object.is(fast[1], slice[1]));
+});
+
+test('extent skips nulls and handles lists too long to spread', () => {
+    assert.deepStrictEqual(extent([null, 3, -2, null, 8]), { min: -2, max: 8 });
+    assert.deepStrictEqual(extent([null]), { min: null, max: null });
+    const long = Array.from({ length: 500000 }, (_, i) => Math.sin(i));
+    const { min, max } = extent(long);
+    assert.ok(min >= -1 && min < -0.99 && max <= 1 && max > 0.99);
+});
@@ -0,0 +1,153 @@
+
+const { isMainThread, parentPort, workerData } = require('worker_threads');
+const { runAggregateBenchmark } = require('./fastAggregate');
+
+// --- FAST FIELD PARSING ---
+// Big CSV imports spend most of their time in parseFloat and in
+// `new Date(string)` for the Date,Time columns. These parsers take the common
+// shapes with char-code loops and hand anything else to the builtin, so the
+// result is always bit-identical to the slow path:
+//
+//   parseNumber      [-+]digits[.digits] with at most 15 significant digits:
+//                    an exact integer mantissa divided by an exact power of ten
+//                    is one correctly rounded operation, same as parseFloat
+//   parseDateTime    YYYYMMDD / YYYY-MM-DD (or . /) + HH:MM[:SS], local time
+//                    like the ISO string parse (the Date constructor rolls over
+//                    out-of-range days the same way)
+//
+// The ingest worker uses them unless the `import.fastParse` setting is false.
+// runParseBenchmark (hidden debug:benchmark-parse) times both paths on
+// generated rows and checks they agree; the worker entry adds the
+// fastAggregate.js benchmark over as many bars.
+
+const POW10 = [1, 1e1, 1e2, 1e3, 1e4, 1e5, 1e6, 1e7, 1e8, 1e9, 1e10, 1e11, 1e12, 1e13, 1e14, 1e15];
+const MAX_DIGITS = 15;
+
+const parseNumber = (text) => {
+    let i = 0;
+    let end = text.length;
+    while (i < end && text.charCodeAt(i) === 32) i++;
+    while (end > i && text.charCodeAt(end - 1) <= 32) end--;
+    let negative = false;
+    const first = text.charCodeAt(i);
+    if (first === 45 || first === 43) { negative = first === 45; i++; }
+    let mantissa = 0, digits = 0, scale = 0, seenDot = false, any = false;
+    for (; i < end; i++) {
+        const c = text.charCodeAt(i);
+        if (c >= 48 && c <= 57) {
+            any = true;
+            if (digits === 0 && c === 48) { if (seenDot) scale++; continue; } // leading zeros
+            if (++digits > MAX_DIGITS) return parseFloat(text);
+            mantissa = mantissa * 10 + (c - 48);
+            if (seenDot) scale++;
+        } else if (c === 46 && !seenDot) {
+            seenDot = true;
+        } else {
+            return parseFloat(text); // exponent, thousands separator, trailing text, ...
+        }
+    }
+    if (!any) return parseFloat(text);
+    if (scale > MAX_DIGITS) return parseFloat(text);
+    const value = scale ? mantissa / POW10[scale] : mantissa;
+    return negative ? -value : value;
+};
+
+// Two-digit field at `at`, or -1
+const twoDigits = (text, at) => {
+    const a = text.charCodeAt(at) - 48, b = text.charCodeAt(at + 1) - 48;
+    return a >= 0 && a <= 9 && b >= 0 && b <= 9 ? a * 10 + b : -1;
+};
+
+/**
+ * Milliseconds for a date column + time column, or null when the shape isn't
+ * one of the fast ones (the caller then uses the string parse).
+ */
+const parseDateTime = (dateText, timeText) => {
+    let y, mo, d;
+    if (dateText.length === 8) {
+        y = twoDigits(dateText, 0) * 100 + twoDigits(dateText, 2);
+        mo = twoDigits(dateText, 4);
+        d = twoDigits(dateText, 6);
+        if (twoDigits(dateText, 0) < 0 || twoDigits(dateText, 2) < 0) return null;
+    } else if (dateText.length === 10) {
+        const sep = dateText.charCodeAt(4);
+        if ((sep !== 45 && sep !== 46 && sep !== 47) || dateText.charCodeAt(7) !== sep) return null;
+        if (twoDigits(dateText, 0) < 0 || twoDigits(dateText, 2) < 0) return null;
+        y = twoDigits(dateText, 0) * 100 + twoDigits(dateText, 2);
+        mo = twoDigits(dateText, 5);
+        d = twoDigits(dateText, 8);
+    } else return null;
+    if (timeText.length !== 5 && timeText.length !== 8) return null;
+    if (timeText.charCodeAt(2) !== 58 || (timeText.length === 8 && timeText.charCodeAt(5) !== 58)) return null;
+    const h = twoDigits(timeText, 0), mi = twoDigits(timeText, 3);
+    const s = timeText.length === 8 ? twoDigits(timeText, 6) : 0;
+    if (y < 1000 || mo < 1 || mo > 12 || d < 1 || d > 31 || h < 0 || h > 23 || mi < 0 || mi > 59 || s < 0 || s > 59) return null;
+    return new Date(y, mo - 1, d, h, mi, s).getTime();
+};
+
+// Date,Time,O,H,L,C,V rows shaped like broker exports
+const buildSampleLines = (rows) => {
+    const lines = [];
+    let price = 1.08543;
+    let t = Date.UTC(2020, 0, 1);
+    for (let i = 0; i < rows; i++) {
+        price = Math.max(0.0001, price + Math.sin(i * 0.37) * 0.0004);
+        const date = new Date(t);
+        const pad = (n) => String(n).padStart(2, '0');
+        const day = `${date.getUTCFullYear()}.${pad(date.getUTCMonth() + 1)}.${pad(date.getUTCDate())}`;
+        const time = `${pad(date.getUTCHours())}:${pad(date.getUTCMinutes())}`;
+        const o = price.toFixed(5), h = (price + 0.0003).toFixed(5), l = (price - 0.0002).toFixed(5), c = (price + 0.0001).toFixed(5);
+        lines.push(`${day},${time},${o},${h},${l},${c},${100 + (i % 900)}`);
+        t += 60000;
+    }
+    return lines;
+};
+
+const parseRows = (lines, fast) => {
+    const out = new Float64Array(lines.length * 6);
+    for (let i = 0; i < lines.length; i++) {
+        const parts = lines[i].split(',');
+        let ts = fast ? parseDateTime(parts[0], parts[1]) : null;
+        if (ts == null) ts = new Date(`${parts[0].replace(/[.\-/]/g, '-')}T${parts[1]}`).getTime();
+        out[i * 6] = ts;
+        for (let k = 0; k < 5; k++) out[i * 6 + 1 + k] = fast ? parseNumber(parts[2 + k]) : parseFloat(parts[2 + k]);
+    }
+    return out;
+};
+
+/**
+ * Returns { rows, iterations, builtin: { meanMs, rowsPerSec }, fast: {...}, speedup, identical }.
+ */
+const runParseBenchmark = ({ rows = 200000, iterations = 5 } = {}) => {
+    const lines = buildSampleLines(rows);
+    const timeIt = (fast) => {
+        const samples = [];
+        let result = null;
+        for (let i = 0; i < iterations; i++) {
+            const start = process.hrtime.bigint();
+            result = parseRows(lines, fast);
+            samples.push(Number(process.hrtime.bigint() - start) / 1e6);
+        }
+        const meanMs = samples.reduce((n, v) => n + v, 0) / samples.length;
+        return { result, stats: { meanMs: Math.round(meanMs * 1000) / 1000, rowsPerSec: Math.round(rows / (meanMs / 1000)) } };
+    };
+    parseRows(lines.slice(0, 1000), true); // warm-up
+    parseRows(lines.slice(0, 1000), false);
+    const builtin = timeIt(false);
+    const fast = timeIt(true);
+    const identical = builtin.result.every((v, i) => Object.is(v, fast.result[i]));
+    return { rows, iterations, builtin: builtin.stats, fast: fast.stats, speedup: Math.round((builtin.stats.meanMs / fast.stats.meanMs) * 100) / 100, identical };
+};
+
+if (!isMainThread && workerData && workerData.parseBenchmark) {
+    try {
+        const options = workerData.parseBenchmark.options || {};
+        const report = runParseBenchmark(options);
+        report.aggregation = runAggregateBenchmark({ bars: options.rows, iterations: options.iterations });
+        parentPort.postMessage({ success: true, report });
+    } catch (err) {
+        parentPort.postMessage({ success: false, error: err.message });
+    }
+}
+
+module.exports = { parseNumber, parseDateTime, runParseBenchmark };
@@ -0,0 +1,47 @@
+
+const test = require('node:test');
+const assert = require('node:assert');
+const { parseNumber, parseDateTime, runParseBenchmark } = require('./fastParse');
+
+const sameAsBuiltin = (text) => assert.ok(Object.is(parseNumber(text), parseFloat(text)), `${JSON.stringify(text)}: ${parseNumber(text)} vs ${parseFloat(text)}`);
+
+test('common price and volume shapes match parseFloat bit for bit', () => {
+    ['0', '1', '-1', '+2.5', '1.08543', '0.00001', '000123.4500', '-0.0', '123456789012345', '99999.99999',
+        ' 42.1', '42.1 ', '.5', '5.', '1.7976931348623157'].forEach(sameAsBuiltin);
+});
+
+test('random decimals with up to 15 digits match parseFloat', () => {
+    let seed = 12345;
+    const next = () => { seed = (Math.imul(seed, 1103515245) + 12345) >>> 0; return seed; };
+    for (let i = 0; i < 20000; i++) {
+        const intPart = String(next() % 1000000);
+        const frac = String(next()).slice(0, next() % 9);
+        sameAsBuiltin(frac ? `${intPart}.${frac}` : intPart);
+    }
+});
+
+test('shapes outside the fast path fall back to parseFloat', () => {
+    ['1e5', '1,234.5', '12abc', '', '-', 'NaN', 'Infinity', '1234567890.1234567', '0.0000000000000001'].forEach(sameAsBuiltin);
+});
+
+test('date and time columns parse like the ISO string parse', () => {
+    const iso = (d, t) => new Date(`${d.replace(/[.\-/]/g, '-')}T${t}`).getTime();
+    [['2024.01.02', '09:30'], ['2024-03-10', '02:30:15'], ['2024/12/31', '23:59:59']].forEach(([d, t]) => {
+        assert.strictEqual(parseDateTime(d, t), iso(d, t));
+    });
+    assert.strictEqual(parseDateTime('20240102', '09:30:00'), iso('2024-01-02', '09:30:00'));
+});
+
+test('unusual date or time shapes return null for the caller to handle', () => {
+    assert.strictEqual(parseDateTime('02/01/2024', '09:30'), null);
+    assert.strictEqual(parseDateTime('2024.01-02', '09:30'), null);
+    assert.strictEqual(parseDateTime('2024.13.02', '09:30'), null);
+    assert.strictEqual(parseDateTime('2024.01.02', '9:30'), null);
+    assert.strictEqual(parseDateTime('2024.01.02', '24:00'), null);
+});
+
+test('the benchmark reports both paths agreeing', () => {
+    const report = runParseBenchmark({ rows: 2000, iterations: 1 });
+    assert.strictEqual(report.identical, true);
+    assert.strictEqual(report.rows, 2000);
+});
@@ -0,0 +1,208 @@
+
+const { request } = require('./network');
+
+// --- FX CONVERSION ---
+// Rolls P&L in instrument currencies (EUR futures, JPY stocks...) up into one
+// base currency. Rates are stored per UTC day in fx_rates, either entered by
+// hand or fetched from the ECB reference rates (frankfurter.app, no key); a
+// manual rate is never replaced by a fetched one. A lookup uses the latest
+// rate on or before the requested day, so past trades convert at the rate of
+// their day rather than today's. Missing pairs are derived from the inverse
+// or crossed through a currency both sides have rates against.
+
+const FETCH_URL = 'https://api.frankfurter.app';
+const DAY_MS = 86400000;
+const MAX_AGE_DAYS = 7;   // an older rate still converts but is flagged stale
+const CROSS_VIA = ['USD', 'EUR'];
+
+const dayKey = (ts) => new Date(ts == null ? Date.now() : ts).toISOString().slice(0, 10);
+
+const normalizeCurrency = (code) => {
+    const value = String(code || '').trim().toUpperCase();
+    if (!/^[A-Z]{3}$/.test(value)) throw new Error(`Invalid currency code: ${code}`);
+    return value;
+};
+
+const initializeFxTable = (db) => {
+    db.exec(`
+        CREATE TABLE IF NOT EXISTS fx_rates (
+            base TEXT,
+            quote TEXT,
+            day TEXT,
+            rate REAL,
+            source TEXT,
+            updated_at INTEGER,
+            PRIMARY KEY (base, quote, day)
+        );
+    `);
+};
+
+/**
+ * Stores quote-currency units per one base unit for a day (default today).
+ * source 'manual' overrides fetched rates; fetched rates never override manual ones.
+ */
+const saveRate = (db, base, quote, rate, day = null, source = 'manual') => {
+    const from = normalizeCurrency(base);
+    const to = normalizeCurrency(quote);
+    const value = Number(rate);
+    if (from === to) throw new Error('Base and quote currency must differ');
+    if (!(value > 0)) throw new Error('FX rate must be positive');
+    const key = day ? dayKey(typeof day === 'string' ? Date.parse(day) : day) : dayKey();
+    if (source !== 'manual') {
+        const existing = db.prepare('SELECT source FROM fx_rates WHERE base = ? AND quote = ? AND day = ?').get(from, to, key);
+        if (existing && existing.source === 'manual') return false;
+    }
+    db.prepare('INSERT OR REPLACE INTO fx_rates (base, quote, day, rate, source, updated_at) VALUES (?, ?, ?, ?, ?, ?)')
+        .run(from, to, key, value, source, Date.now());
+    return true;
+};
+
+const deleteRate = (db, base, quote, day) => db.prepare('DELETE FROM fx_rates WHERE base = ? AND quote = ? AND day = ?')
+    .run(normalizeCurrency(base), normalizeCurrency(quote), day).changes > 0;
+
+// filter: { base?, quote?, from?, to?, limit? } (from / to as timestamps)
+const listRates = (db, filter = {}) => {
+    const where = [];
+    const params = [];
+    if (filter.base) { where.push('base = ?'); params.push(normalizeCurrency(filter.base)); }
+    if (filter.quote) { where.push('quote = ?'); params.push(normalizeCurrency(filter.quote)); }
+    if (filter.from != null) { where.push('day >= ?'); params.push(dayKey(filter.from)); }
+    if (filter.to != null) { where.push('day <= ?'); params.push(dayKey(filter.to)); }
+    const sql = `SELECT base, quote, day, rate, source, updated_at FROM fx_rates${where.length ? ` WHERE ${where.join(' AND ')}` : ''} ORDER BY day DESC, base, quote LIMIT ?`;
+    return db.prepare(sql).all(...params, Math.min(10000, Number(filter.limit) || 1000))
+        .map(row => ({ base: row.base, quote: row.quote, day: row.day, rate: row.rate, source: row.source, updatedAt: row.updated_at }));
+};
+
+// Stored rate for a direct pair at or before the day
+const storedRate = (db, base, quote, day) => {
+    const row = db.prepare('SELECT day, rate, source FROM fx_rates WHERE base = ? AND quote = ? AND day <= ? ORDER BY day DESC LIMIT 1')
+        .get(base, quote, day);
+    return row ? { rate: row.rate, day: row.day, source: row.source } : null;
+};
+
+const pairRate = (db, base, quote, day) => {
+    const direct = storedRate(db, base, quote, day);
+    const inverse = storedRate(db, quote, base, day);
+    // The more recent of the two wins
+    if (direct && (!inverse || direct.day >= inverse.day)) return direct;
+    if (inverse) return { ...inverse, rate: 1 / inverse.rate };
+    return null;
+};
+
+/**
+ * Quote units per one base unit at timestamp ts, from stored rates.
+ * Returns { rate, day, source, via, stale } or null when no rate is known.
+ */
+const lookupRate = (db, base, quote, ts = Date.now()) => {
+    const from = normalizeCurrency(base);
+    const to = normalizeCurrency(quote);
+    if (from === to) return { rate: 1, day: dayKey(ts), source: 'identity', via: null, stale: false };
+    const day = dayKey(ts);
+    const withAge = (found) => ({ ...found, stale: (Date.parse(day) - Date.parse(found.day)) / DAY_MS > MAX_AGE_DAYS });
+
+    const direct = pairRate(db, from, to, day);
+    if (direct) return withAge({ ...direct, via: null });
+    for (const via of CROSS_VIA) {
+        if (via === from || via === to) continue;
+        const leg1 = pairRate(db, from, via, day);
+        const leg2 = leg1 && pairRate(db, via, to, day);
+        if (leg2) {
+            return withAge({ rate: leg1.rate * leg2.rate, day: leg1.day < leg2.day ? leg1.day : leg2.day, source: leg1.source === leg2.source ? leg1.source : 'mixed', via });
+        }
+    }
+    return null;
+};
+
+const FX_DEFAULTS = { baseCurrency: 'USD', autoFetch: true };
+
+/**
+ * Rate lookups with on-demand fetching. getDb / loadConfig are read lazily so
+ * settings changes apply without a restart. onLog(event, data, level).
+ */
+const createFxService = ({ getDb, loadConfig, onLog } = {}) => {
+    const config = () => ({ ...FX_DEFAULTS, ...((loadConfig && loadConfig()) || {}) });
+    const inflight = new Map(); // 'EUR:2024-01-02..2024-01-31' -> promise
+
+    // Daily ECB rates for a range, saved against `base`
+    const fetchRange = async (base, quotes, fromDay, toDay) => {
+        const key = `${base}:${quotes.join(',')}:${fromDay}..${toDay}`;
+        if (inflight.has(key)) return inflight.get(key);
+        const job = (async () => {
+            const range = fromDay === toDay ? fromDay : `${fromDay}..${toDay}`;
+            const query = new URLSearchParams({ from: base, to: quotes.join(',') });
+            const response = await request('fx', `${FETCH_URL}/${range}?${query}`);
+            const body = await response.json().catch(() => null);
+            if (!response.ok || !body || !body.rates) throw new Error(`FX rates ${response.status}: ${(body && body.message) || response.statusText}`);
+            // A single day answers { date, rates: { EUR: .. } }, a range { rates: { day: { EUR: .. } } }
+            const days = body.date && fromDay === toDay ? { [body.date]: body.rates } : body.rates;
+            const db = getDb();
+            let saved = 0;
+            Object.entries(days).forEach(([day, rates]) => Object.entries(rates).forEach(([quote, rate]) => {
+                if (saveRate(db, base, quote, rate, day, 'ecb')) saved += 1;
+            }));
+            if (onLog) onLog('FX_RATES_FETCHED', { base, quotes, from: fromDay, to: toDay, saved });
+            return saved;
+        })().finally(() => inflight.delete(key));
+        inflight.set(key, job);
+        return job;
+    };
+
+    /**
+     * Makes sure rates exist for each { from, to, timestamp }: conversions
+     * lacking a fresh rate are fetched in one range request per currency pair.
+     */
+    const prefetch = async (items = []) => {
+        const db = getDb();
+        if (!db || !config().autoFetch) return { fetched: 0, errors: [] };
+        const ranges = new Map(); // 'EUR>USD' -> { min, max }
+        items.forEach(({ from, to, timestamp }) => {
+            if (!from || !to || from === to) return;
+            const found = lookupRate(db, from, to, timestamp);
+            // Weekends and holidays have no ECB fix: a rate from a few days before is fine
+            if (found && (Date.parse(dayKey(timestamp)) - Date.parse(found.day)) / DAY_MS <= 3) return;
+            const pair = `${normalizeCurrency(from)}>${normalizeCurrency(to)}`;
+            const range = ranges.get(pair) || { min: timestamp, max: timestamp };
+            range.min = Math.min(range.min, timestamp);
+            range.max = Math.max(range.max, timestamp);
+            ranges.set(pair, range);
+        });
+        let fetched = 0;
+        const errors = [];
+        for (const [pair, range] of ranges) {
+            const [base, quote] = pair.split('>');
+            try {
+                // A few days of lead-in cover a range starting on a weekend
+                fetched += await fetchRange(base, [quote], dayKey(range.min - 4 * DAY_MS), dayKey(Math.min(Date.now(), range.max)));
+            } catch (err) {
+                errors.push({ pair: `${base}/${quote}`, error: err.message });
+                if (onLog) onLog('FX_FETCH_FAILED', { pair: `${base}/${quote}`, error: err.message }, 'WARN');
+            }
+        }
+        return { fetched, errors };
+    };
+
+    // Like lookupRate, fetching the rate first when allowed; throws when none is available
+    const getRate = async (from, to, timestamp = Date.now()) => {
+        const db = getDb();
+        const ts = timestamp == null ? Date.now() : timestamp;
+        await prefetch([{ from, to, timestamp: ts }]);
+        const found = lookupRate(db, from, to, ts);
+        if (!found) throw new Error(`No FX rate for ${normalizeCurrency(from)}/${normalizeCurrency(to)} on ${dayKey(ts)}: add one manually${config().autoFetch ? '' : ' or enable auto-fetch'}`);
+        return found;
+    };
+
+    const convert = async (amount, from, to, timestamp) => {
+        const found = await getRate(from, to, timestamp);
+        return { amount: Number(amount) * found.rate, ...found };
+    };
+
+    // Synchronous converter over stored rates: (from, to, ts) -> rate | null
+    const rateFor = (from, to, ts) => {
+        const found = lookupRate(getDb(), from, to, ts);
+        return found ? found.rate : null;
+    };
+
+    return { config, prefetch, getRate, convert, rateFor, baseCurrency: () => normalizeCurrency(config().baseCurrency) };
+};
+
+module.exports = { initializeFxTable, saveRate, deleteRate, listRates, lookupRate, createFxService, normalizeCurrency };
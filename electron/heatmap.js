@@ -0,0 +1,186 @@
+
+const crypto = require('crypto');
+const { TIMEFRAME_MS, datasetId } = require('./datasets');
+const { parseExpression, evaluateExpression } = require('./expressions');
+const { sumField, extent } = require('./fastAggregate');
+
+// --- MARKET HEATMAP ---
+// One metric for every symbol of a watchlist at one timeframe, returned as a
+// compact matrix for the market-overview dashboard:
+//
+//   change          % change of the last close over `periods` bars
+//   relativeVolume  volume of the last `periods` bars / average volume of the
+//                   `baseline` windows of the same length before them
+//   { script }      value of an indicator expression on the last bar
+//                   (expressions.js with { condition: false }; conditions give 1 / 0)
+//
+// Cells are computed in parallel on the compute pool. A live heatmap keeps the
+// bars each cell needs and is advanced by liveFeed bars for its symbols, so
+// refreshes only recompute the cells that moved and are pushed in throttled
+// batches. computeHeatmapCell is kept free of Electron and SQLite so compute
+// workers can load it.
+
+const HEATMAP_METRICS = ['change', 'relativeVolume', 'script'];
+const MAX_SYMBOLS = 2000;
+const MAX_PERIODS = 1000;
+const MAX_BASELINE = 200;
+const MAX_LIVE_HEATMAPS = 20;
+const UPDATE_THROTTLE_MS = 1000;
+
+/**
+ * metric: 'change' | 'relativeVolume' | { script }; range: { timeframe, periods?, baseline? }.
+ * Returns { symbols, metric, script, timeframe, periods, baseline, lookback }.
+ */
+const normalizeHeatmapRequest = (symbols, metric, range = {}) => {
+    const list = Array.from(new Set((Array.isArray(symbols) ? symbols : []).map(s => String(s).trim()).filter(Boolean)));
+    if (!list.length) throw new Error('A heatmap needs at least one symbol');
+    if (list.length > MAX_SYMBOLS) throw new Error(`At most ${MAX_SYMBOLS} symbols per heatmap`);
+    const timeframe = String((range && range.timeframe) || '1D');
+    if (!TIMEFRAME_MS[timeframe]) throw new Error(`Unsupported timeframe: ${timeframe}`);
+    const periods = Math.round(Number(range.periods ?? 1));
+    if (!Number.isInteger(periods) || periods < 1 || periods > MAX_PERIODS) throw new Error(`periods must be a whole number from 1 to ${MAX_PERIODS}`);
+    const baseline = Math.round(Number(range.baseline ?? 20));
+    if (!Number.isInteger(baseline) || baseline < 1 || baseline > MAX_BASELINE) throw new Error(`baseline must be a whole number from 1 to ${MAX_BASELINE}`);
+
+    const name = metric && typeof metric === 'object' ? 'script' : String(metric || '');
+    if (!HEATMAP_METRICS.includes(name)) throw new Error(`Unknown heatmap metric: ${name}`);
+    const script = name === 'script' ? parseExpression(metric.script, { condition: false }) : null;
+    const lookback = name === 'change' ? periods + 1 : name === 'relativeVolume' ? periods * (baseline + 1) : script.lookback;
+    return { symbols: list, metric: name, script: script ? script.source : null, timeframe, periods, baseline: name === 'relativeVolume' ? baseline : null, lookback };
+};
+
+const compileScript = (request) => parseExpression(request.script, { condition: false });
+
+/**
+ * Cell for bars (oldest first): { value, close, timestamp }. value is null
+ * while there is not enough history. compiled: the parsed script, if any.
+ */
+const computeHeatmapCell = (bars, request, compiled = null) => {
+    const last = bars[bars.length - 1];
+    const cell = { value: null, close: last ? last.close : null, timestamp: last ? last.timestamp : null };
+    if (!last) return cell;
+    if (request.metric === 'change') {
+        const reference = bars[bars.length - 1 - request.periods];
+        if (reference && reference.close) cell.value = (last.close / reference.close - 1) * 100;
+    } else if (request.metric === 'relativeVolume') {
+        if (bars.length < request.lookback) return cell;
+        const recent = sumField(bars, 'volume', bars.length - request.periods);
+        const before = sumField(bars, 'volume', bars.length - request.lookback, bars.length - request.periods);
+        const average = before / request.baseline;
+        if (average > 0) cell.value = recent / average;
+    } else {
+        const result = evaluateExpression(compiled || compileScript(request), bars);
+        cell.value = result.value != null ? result.value : (result.matched ? 1 : 0);
+        if (!Number.isFinite(cell.value)) cell.value = null;
+    }
+    return cell;
+};
+
+const valueRange = (cells) => extent(Array.from(cells, c => c.value));
+
+/**
+ * runJobs(jobs) -> results runs heatmap jobs on the compute pool.
+ * onUpdate({ id, computedAt, cells: [[symbol, value, close, timestamp]], min, max })
+ * gets the cells of a live heatmap that changed since the last push.
+ */
+const createHeatmapService = ({ runJobs, onUpdate = () => {} }) => {
+    const live = new Map(); // id -> { id, request, compiled, cells: Map(symbol -> { tail, value, close, timestamp }), changed, timer, lastSentAt }
+
+    const matrix = (id, request, symbols, cellFor, computedAt, errors) => {
+        const rows = symbols.map(symbol => cellFor(symbol));
+        return {
+            id,
+            metric: request.metric,
+            script: request.script,
+            timeframe: request.timeframe,
+            periods: request.periods,
+            baseline: request.baseline,
+            computedAt,
+            columns: ['symbol', 'value', 'close', 'timestamp'],
+            rows: rows.map((c, i) => [symbols[i], c ? c.value : null, c ? c.close : null, c ? c.timestamp : null]),
+            ...valueRange(rows.filter(Boolean)),
+            errors
+        };
+    };
+
+    /**
+     * Computes every cell; options.live keeps the heatmap updating from the
+     * live feed under the returned id until stop(id).
+     */
+    const compute = async (symbols, metric, range, { live: keepLive = false } = {}) => {
+        const request = normalizeHeatmapRequest(symbols, metric, range);
+        if (keepLive && live.size >= MAX_LIVE_HEATMAPS) throw new Error(`At most ${MAX_LIVE_HEATMAPS} live heatmaps`);
+        const start = Date.now();
+        const jobs = request.symbols.map(symbol => ({ id: symbol, datasetId: datasetId(symbol, request.timeframe), heatmap: request, limit: request.lookback, tail: keepLive }));
+        const results = await runJobs(jobs);
+        const cells = new Map();
+        const errors = {};
+        results.forEach((result) => {
+            if (!result.success) errors[result.id] = result.error;
+            else cells.set(result.id, { tail: result.tail || [], value: result.value, close: result.close, timestamp: result.timestamp });
+        });
+        const id = keepLive ? crypto.randomUUID() : null;
+        if (keepLive) {
+            live.set(id, { id, request, compiled: request.script ? compileScript(request) : null, cells, changed: new Set(), timer: null, lastSentAt: 0 });
+        }
+        return { ...matrix(id, request, request.symbols, symbol => cells.get(symbol), start, errors), durationMs: Date.now() - start };
+    };
+
+    const flush = (entry) => {
+        entry.timer = null;
+        entry.lastSentAt = Date.now();
+        const symbols = Array.from(entry.changed);
+        entry.changed.clear();
+        if (!symbols.length) return;
+        const all = Array.from(entry.cells.values());
+        onUpdate({
+            id: entry.id,
+            computedAt: entry.lastSentAt,
+            cells: symbols.map((s) => { const c = entry.cells.get(s); return [s, c.value, c.close, c.timestamp]; }),
+            ...valueRange(all)
+        });
+    };
+
+    // liveFeed 'bar' event: { provider, symbol, timeframe, bar, isClosed }
+    const handleBar = ({ symbol, timeframe, bar }) => {
+        if (!live.size || !bar) return;
+        live.forEach((entry) => {
+            if (entry.request.timeframe !== timeframe) return;
+            const cell = entry.cells.get(symbol);
+            if (!cell) return;
+            const { tail } = cell;
+            const last = tail[tail.length - 1];
+            if (last && bar.timestamp < last.timestamp) return;
+            if (last && bar.timestamp === last.timestamp) tail[tail.length - 1] = { ...bar };
+            else {
+                tail.push({ ...bar });
+                if (tail.length > entry.request.lookback) tail.shift();
+            }
+            Object.assign(cell, computeHeatmapCell(tail, entry.request, entry.compiled));
+            entry.changed.add(symbol);
+            if (!entry.timer) entry.timer = setTimeout(() => flush(entry), Math.max(0, entry.lastSentAt + UPDATE_THROTTLE_MS - Date.now()));
+        });
+    };
+
+    // Current matrix of a live heatmap
+    const get = (id) => {
+        const entry = live.get(id);
+        if (!entry) return null;
+        return matrix(id, entry.request, entry.request.symbols, symbol => entry.cells.get(symbol), entry.lastSentAt || null, {});
+    };
+
+    const stop = (id) => {
+        const entry = live.get(id);
+        if (!entry) return false;
+        if (entry.timer) clearTimeout(entry.timer);
+        return live.delete(id);
+    };
+
+    const list = () => Array.from(live.values()).map(e => ({ id: e.id, metric: e.request.metric, timeframe: e.request.timeframe, symbols: e.request.symbols.length }));
+
+    const stopAll = () => Array.from(live.keys()).forEach(stop);
+
+    return { compute, handleBar, get, stop, list, stopAll };
+};
+
+module.exports = { HEATMAP_METRICS, normalizeHeatmapRequest, computeHeatmapCell, createHeatmapService };
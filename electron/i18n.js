@@ -0,0 +1,65 @@
+
+const fs = require('fs');
+const path = require('path');
+
+// --- LOCALIZATION ---
+// Flat JSON string tables in electron/locales/<lang>.json, keyed like
+// "storage.category.bar_store", with {name} placeholders. English is the
+// fallback for missing keys, so a partial translation never shows raw keys.
+// The active language is process-wide; main.js persists it and tells windows.
+
+const LOCALES_DIR = path.join(__dirname, 'locales');
+const FALLBACK_LANGUAGE = 'en';
+
+const cache = new Map();
+let currentLanguage = FALLBACK_LANGUAGE;
+
+const supportedLanguages = () => fs.readdirSync(LOCALES_DIR)
+    .filter(f => f.endsWith('.json'))
+    .map(f => path.basename(f, '.json'))
+    .sort();
+
+const loadTable = (lang) => {
+    if (!cache.has(lang)) {
+        const file = path.join(LOCALES_DIR, `${lang}.json`);
+        cache.set(lang, fs.existsSync(file) ? JSON.parse(fs.readFileSync(file, 'utf8')) : null);
+    }
+    return cache.get(lang);
+};
+
+// "de-AT" -> "de" when only the base language ships
+const resolveLanguage = (lang) => {
+    const wanted = String(lang || '').trim();
+    const available = supportedLanguages();
+    if (available.includes(wanted)) return wanted;
+    const base = wanted.split(/[-_]/)[0].toLowerCase();
+    return available.includes(base) ? base : null;
+};
+
+/**
+ * Full string table for `lang` with English filling the gaps.
+ */
+const getLocaleStrings = (lang = currentLanguage) => {
+    const resolved = resolveLanguage(lang);
+    if (!resolved) throw new Error(t('errors.unknownLanguage', { lang }));
+    return { ...loadTable(FALLBACK_LANGUAGE), ...loadTable(resolved) };
+};
+
+const setLanguage = (lang) => {
+    const resolved = resolveLanguage(lang);
+    if (!resolved) throw new Error(t('errors.unknownLanguage', { lang }));
+    currentLanguage = resolved;
+    return resolved;
+};
+
+const getLanguage = () => currentLanguage;
+
+const t = (key, vars = null) => {
+    const table = loadTable(currentLanguage) || {};
+    const template = table[key] ?? (loadTable(FALLBACK_LANGUAGE) || {})[key] ?? key;
+    return vars ? template.replace(/\{(\w+)\}/g, (m, name) => (vars[name] != null ? String(vars[name]) : m)) : template;
+};
+
+const listLanguages = () => supportedLanguages().map(code => ({ code, name: (loadTable(code) || {})['language.name'] || code }));
+
+module.exports = { FALLBACK_LANGUAGE, t, getLocaleStrings, setLanguage, getLanguage, resolveLanguage, listLanguages };
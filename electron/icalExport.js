@@ -0,0 +1,96 @@
+
+const { parseCron, nextRunTime } = require('./scheduler');
+
+// --- ICALENDAR EXPORT ---
+// Builds an RFC 5545 calendar from scheduled jobs (each upcoming run within the
+// horizon is its own event, since five-field cron doesn't map onto RRULE) and
+// stored economic events. UIDs are stable across exports, so re-importing or a
+// subscribed calendar app updates entries instead of duplicating them.
+
+const PRODID = '-//Red Pill//Calendar Export//EN';
+const UID_DOMAIN = 'redpill.local';
+const MAX_RUNS_PER_JOB = 200;
+const JOB_DURATION = 'PT15M';
+
+const escapeText = (value) => String(value == null ? '' : value)
+    .replace(/\\/g, '\\\\').replace(/;/g, '\\;').replace(/,/g, '\\,').replace(/\r?\n/g, '\\n');
+
+const utcStamp = (ts) => new Date(ts).toISOString().replace(/[-:]/g, '').replace(/\.\d{3}/, '');
+const utcDate = (ts) => new Date(ts).toISOString().slice(0, 10).replace(/-/g, '');
+
+// Content lines are folded at 75 octets
+const fold = (line) => {
+    const out = [];
+    let current = '';
+    let bytes = 0;
+    for (const ch of line) {
+        const size = Buffer.byteLength(ch);
+        if (bytes + size > (out.length ? 74 : 75)) {
+            out.push(current);
+            current = '';
+            bytes = 0;
+        }
+        current += ch;
+        bytes += size;
+    }
+    out.push(current);
+    return out.join('\r\n ');
+};
+
+const jobEvents = (jobs, from, to) => jobs.filter(job => job.enabled).flatMap((job) => {
+    const runs = [];
+    const cron = parseCron(job.schedule);
+    let next = job.nextRunAt && job.nextRunAt >= from ? job.nextRunAt : nextRunTime(cron, from);
+    while (next != null && next <= to && runs.length < MAX_RUNS_PER_JOB) {
+        runs.push(next);
+        next = nextRunTime(cron, next);
+    }
+    return runs.map(ts => [
+        'BEGIN:VEVENT',
+        `UID:job-${job.id}-${ts}@${UID_DOMAIN}`,
+        `DTSTART:${utcStamp(ts)}`,
+        `DURATION:${JOB_DURATION}`,
+        `SUMMARY:${escapeText(job.name)}`,
+        `DESCRIPTION:${escapeText(`Scheduled ${job.type} job (${job.schedule})`)}`,
+        'CATEGORIES:Red Pill,Scheduled job',
+        'END:VEVENT'
+    ]);
+});
+
+const econEvents = (events) => events.map((event) => {
+    const details = [['Actual', event.actual], ['Forecast', event.forecast], ['Previous', event.previous]]
+        .filter(([, value]) => value != null && value !== '').map(([label, value]) => `${label}: ${value}`);
+    return [
+        'BEGIN:VEVENT',
+        `UID:econ-${event.id}@${UID_DOMAIN}`,
+        event.allDay ? `DTSTART;VALUE=DATE:${utcDate(event.timestamp)}` : `DTSTART:${utcStamp(event.timestamp)}`,
+        event.allDay ? `DTEND;VALUE=DATE:${utcDate(event.timestamp + 86400000)}` : 'DURATION:PT0M',
+        `SUMMARY:${escapeText(`${event.currency ? `[${event.currency}] ` : ''}${event.title}`)}`,
+        ...(details.length ? [`DESCRIPTION:${escapeText(details.join('\n'))}`] : []),
+        `CATEGORIES:Red Pill,Economic event,${escapeText(event.impact || 'none')}`,
+        'TRANSP:TRANSPARENT',
+        'END:VEVENT'
+    ];
+});
+
+/**
+ * jobs: scheduler jobs; events: economic events. options: { from, to, name }.
+ * Returns { ics, counts: { jobs, events } }.
+ */
+const buildICalendar = ({ jobs = [], events = [] }, { from = Date.now(), to = from + 30 * 86400000, name = 'Red Pill' } = {}) => {
+    const jobEntries = jobEvents(jobs, from, to);
+    const eventEntries = econEvents(events);
+    const stamp = `DTSTAMP:${utcStamp(Date.now())}`;
+    const lines = [
+        'BEGIN:VCALENDAR',
+        'VERSION:2.0',
+        `PRODID:${PRODID}`,
+        'CALSCALE:GREGORIAN',
+        `X-WR-CALNAME:${escapeText(name)}`,
+        ...[...jobEntries, ...eventEntries].flatMap(entry => [entry[0], entry[1], stamp, ...entry.slice(2)]),
+        'END:VCALENDAR'
+    ];
+    return { ics: `${lines.map(fold).join('\r\n')}\r\n`, counts: { jobs: jobEntries.length, events: eventEntries.length } };
+};
+
+module.exports = { buildICalendar };
@@ -0,0 +1,107 @@
+
+// --- IDLE DETECTION ---
+// Polls the OS idle time (powerMonitor.getSystemIdleTime) and turns it into
+// two states. 'away' starts after `awayMinutes` without input, or at once
+// when the screen locks or the machine suspends; while away, main holds back
+// non-critical work (index building, scheduled scans, slower polling). The
+// workspace auto-locks after `autoLockMinutes` (0 = never) or, optionally,
+// with the OS screen lock; it stays locked until unlock() even after the user
+// is back. Coming back reports how long the user was away so views can refresh.
+
+const POLL_MS = 15 * 1000;
+const DEFAULT_IDLE_CONFIG = { awayMinutes: 5, autoLockMinutes: 0, lockOnScreenLock: false, pauseBackgroundWork: true };
+
+const normalizeIdleConfig = (config = {}) => {
+    const next = { ...DEFAULT_IDLE_CONFIG, ...config };
+    next.awayMinutes = Number(next.awayMinutes);
+    if (!Number.isFinite(next.awayMinutes) || next.awayMinutes < 1 || next.awayMinutes > 240) throw new Error('awayMinutes must be between 1 and 240');
+    next.autoLockMinutes = Number(next.autoLockMinutes);
+    if (!Number.isFinite(next.autoLockMinutes) || next.autoLockMinutes < 0 || next.autoLockMinutes > 1440) throw new Error('autoLockMinutes must be between 0 (off) and 1440');
+    next.lockOnScreenLock = !!next.lockOnScreenLock;
+    next.pauseBackgroundWork = next.pauseBackgroundWork !== false;
+    return next;
+};
+
+/**
+ * onAway({ since, reason }), onReturn({ awayMs }) and onLock({ reason })
+ * report transitions; reasons are 'idle', 'screen-lock', 'suspend' or 'manual'.
+ */
+const createIdleMonitor = ({ powerMonitor, getConfig = () => DEFAULT_IDLE_CONFIG, onAway = () => {}, onReturn = () => {}, onLock = () => {} }) => {
+    let timer = null;
+    let away = null; // { since, reason }
+    let locked = null; // { since, reason }
+    let idleSeconds = 0;
+    let osLocked = false;
+
+    const config = () => {
+        try { return normalizeIdleConfig(getConfig()); } catch (e) { return DEFAULT_IDLE_CONFIG; }
+    };
+
+    const goAway = (reason, since = Date.now()) => {
+        if (away) return;
+        away = { since, reason };
+        onAway(away);
+    };
+
+    const lock = (reason) => {
+        if (locked) return false;
+        locked = { since: Date.now(), reason };
+        onLock(locked);
+        return true;
+    };
+
+    const check = () => {
+        const cfg = config();
+        idleSeconds = powerMonitor.getSystemIdleTime();
+        if (!away && idleSeconds >= cfg.awayMinutes * 60) goAway('idle', Date.now() - idleSeconds * 1000);
+        if (cfg.autoLockMinutes > 0 && idleSeconds >= cfg.autoLockMinutes * 60) lock('idle');
+        // Back: input after being away, but not while the OS screen is still locked
+        if (away && !osLocked && idleSeconds < cfg.awayMinutes * 60) {
+            const awayMs = Date.now() - away.since;
+            const previous = away;
+            away = null;
+            onReturn({ awayMs, reason: previous.reason });
+        }
+    };
+
+    const start = () => {
+        powerMonitor.on('lock-screen', () => {
+            osLocked = true;
+            goAway('screen-lock');
+            if (config().lockOnScreenLock) lock('screen-lock');
+        });
+        powerMonitor.on('unlock-screen', () => { osLocked = false; check(); });
+        powerMonitor.on('suspend', () => goAway('suspend'));
+        powerMonitor.on('resume', check);
+        timer = setInterval(check, POLL_MS);
+        check();
+    };
+
+    const stop = () => {
+        if (timer) clearInterval(timer);
+        timer = null;
+    };
+
+    const unlock = () => {
+        if (!locked) return false;
+        locked = null;
+        return true;
+    };
+
+    const state = () => ({
+        idleSeconds,
+        away: !!away,
+        awaySince: away ? away.since : null,
+        awayReason: away ? away.reason : null,
+        locked: !!locked,
+        lockedSince: locked ? locked.since : null,
+        lockReason: locked ? locked.reason : null
+    });
+
+    // Whether non-critical background work should wait
+    const shouldPause = () => !!away && config().pauseBackgroundWork;
+
+    return { start, stop, check, lock: () => lock('manual'), unlock, state, shouldPause };
+};
+
+module.exports = { DEFAULT_IDLE_CONFIG, normalizeIdleConfig, createIdleMonitor };
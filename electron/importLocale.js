@@ -0,0 +1,197 @@
+
+const fs = require('fs');
+const { parseNumber } = require('./fastParse');
+
+// --- IMPORT LOCALE ---
+// CSV exports follow the locale of whatever wrote them: `1.234,56` with `;`
+// between fields and `31/01/2024` dates in much of Europe, `1,234.56` and
+// `01/31/2024` in the US. An import locale says how to read a file:
+//
+//   delimiter   ',' | ';' | '\t' | '|' | null (per line: ';' if present, else ',')
+//   decimal     '.' | ','
+//   thousands   null | ',' | '.' | ' ' | "'"
+//   dateOrder   'ymd' | 'dmy' | 'mdy' (the order of the date column's parts)
+//
+// probeLines() guesses one from the head of a file and says which guesses
+// it couldn't settle (`ambiguous`); market:probe-file returns that as hints
+// and the read / import commands take an explicit `locale` that overrides them.
+// The default locale is the historical parse (see ingestWorker.parseLine).
+
+const DEFAULT_IMPORT_LOCALE = { delimiter: null, decimal: '.', thousands: null, dateOrder: 'ymd' };
+const DELIMITERS = [',', ';', '\t', '|'];
+const THOUSANDS = [',', '.', ' ', "'"];
+const DATE_ORDERS = ['ymd', 'dmy', 'mdy'];
+const PROBE_BYTES = 64 * 1024;
+const PROBE_LINES = 200;
+
+const normalizeImportLocale = (locale = {}) => {
+    const next = { ...DEFAULT_IMPORT_LOCALE, ...(locale || {}) };
+    if (next.delimiter === '') next.delimiter = null;
+    if (next.delimiter !== null && !DELIMITERS.includes(next.delimiter)) throw new Error(`delimiter must be one of ${DELIMITERS.map(d => JSON.stringify(d)).join(', ')}`);
+    if (!['.', ','].includes(next.decimal)) throw new Error("decimal must be '.' or ','");
+    if (next.thousands === '') next.thousands = null;
+    if (next.thousands !== null && !THOUSANDS.includes(next.thousands)) throw new Error(`thousands must be null or one of ${THOUSANDS.map(d => JSON.stringify(d)).join(', ')}`);
+    if (next.thousands === next.decimal) throw new Error('thousands and decimal separators must differ');
+    if (next.decimal === ',' && next.delimiter === ',') throw new Error("A decimal comma needs a delimiter other than ','");
+    if (!DATE_ORDERS.includes(next.dateOrder)) throw new Error(`dateOrder must be one of ${DATE_ORDERS.join(', ')}`);
+    return next;
+};
+
+const isDefaultLocale = (locale) => !locale || (locale.delimiter == null && locale.decimal === '.' && locale.thousands == null && locale.dateOrder === 'ymd');
+
+// A number parser for the locale; null means the default parse applies
+const makeNumberParser = (locale) => {
+    if (!locale || (locale.decimal === '.' && !locale.thousands)) return null;
+    const { decimal, thousands } = locale;
+    return (text) => {
+        if (text == null) return NaN;
+        let value = String(text).trim();
+        if (thousands) value = value.split(thousands).join('');
+        if (decimal === ',') value = value.replace(',', '.');
+        return parseNumber(value);
+    };
+};
+
+const DAY_FIRST_DATE = /^(\d{1,2})[./-](\d{1,2})[./-](\d{2}|\d{4})$/;
+const TIME_OF_DAY = /^(\d{1,2}):(\d{2})(?::(\d{2}))?$/;
+
+/**
+ * Local-time ms for a d/m/y or m/d/y date (separators / . -, 2- or 4-digit
+ * year) and an optional HH:MM[:SS]; null if it doesn't parse or isn't a real date.
+ */
+const parseOrderedDate = (dateText, timeText, order) => {
+    const m = DAY_FIRST_DATE.exec(String(dateText).trim());
+    if (!m) return null;
+    const day = Number(order === 'mdy' ? m[2] : m[1]);
+    const month = Number(order === 'mdy' ? m[1] : m[2]);
+    let year = Number(m[3]);
+    if (m[3].length === 2) year += year < 70 ? 2000 : 1900;
+    let h = 0, mi = 0, s = 0;
+    if (timeText) {
+        const t = TIME_OF_DAY.exec(String(timeText).trim());
+        if (!t) return null;
+        [h, mi, s] = [Number(t[1]), Number(t[2]), Number(t[3] || 0)];
+        if (h > 23 || mi > 59 || s > 59) return null;
+    }
+    const date = new Date(year, month - 1, day, h, mi, s);
+    // Reject 31/02 and friends instead of rolling over
+    if (date.getMonth() !== month - 1 || date.getDate() !== day) return null;
+    return date.getTime();
+};
+
+// --- Probe ---
+
+const splitLine = (line, delimiter) => line.split(delimiter).map(p => p.trim().replace(/^"(.*)"$/, '$1'));
+
+const guessDelimiter = (lines) => {
+    let best = null;
+    DELIMITERS.forEach((d) => {
+        const counts = lines.map(l => l.split(d).length - 1);
+        const first = counts[0];
+        if (first < 4) return; // date + OHLC at least
+        const consistent = counts.filter(c => c === first).length / counts.length;
+        if (consistent >= 0.9 && (!best || consistent > best.consistent || (consistent === best.consistent && first > best.fields))) best = { delimiter: d, consistent, fields: first };
+    });
+    return best ? best.delimiter : null;
+};
+
+// Spellings of a number: [pattern, decimal mark, thousands separator]
+const NUMBER_SHAPES = [
+    [/^\d{1,3}([.,' ]\d{3})+$/, null, null],   // 12.345: grouped integer or three decimals, no evidence either way
+    [/^\d{1,3}(\.\d{3})+,\d+$/, ',', '.'],   // 1.234,56
+    [/^\d{1,3}(,\d{3})+\.\d+$/, '.', ','],   // 1,234.56
+    [/^\d{1,3}( \d{3})+,\d+$/, ',', ' '],     // 1 234,56
+    [/^\d{1,3}( \d{3})+\.\d+$/, '.', ' '],   // 1 234.56
+    [/^\d{1,3}('\d{3})+\.\d+$/, '.', "'"],   // 1'234.56
+    [/^\d+,\d+$/, ',', null],                // 1234,56
+    [/^\d+\.\d+$/, '.', null]                // 1234.56
+];
+
+// Decimal / thousands from the numeric columns' spellings
+const guessNumberFormat = (values, delimiter) => {
+    const decimals = { '.': 0, ',': 0 };
+    const groups = new Map();
+    values.forEach((raw) => {
+        const shape = NUMBER_SHAPES.find(([pattern]) => pattern.test(raw.replace(/^[-+]/, '')));
+        if (!shape || !shape[1]) return;
+        decimals[shape[1]]++;
+        if (shape[2]) groups.set(shape[2], (groups.get(shape[2]) || 0) + 1);
+    });
+    // A comma can't be the decimal mark when it separates the fields
+    const decimal = delimiter !== ',' && decimals[','] > decimals['.'] ? ',' : '.';
+    const thousands = Array.from(groups.entries())
+        .filter(([sep]) => sep !== decimal && sep !== delimiter)
+        .sort((a, b) => b[1] - a[1])
+        .map(([sep]) => sep)[0] || null;
+    // "1.234" next to "5,6" could be a thousand or one and a bit
+    const ambiguous = decimals[','] && decimals['.'] ? ['decimal'] : [];
+    return { decimal, thousands, ambiguous };
+};
+
+const guessDateOrder = (dates) => {
+    let dayFirst = 0, monthFirst = 0, yearFirst = 0, matched = 0;
+    dates.forEach((text) => {
+        if (/^\d{4}[./-]?\d{2}[./-]?\d{2}([ T]|$)/.test(text) || /^\d{8}$/.test(text)) { yearFirst++; matched++; return; }
+        const m = DAY_FIRST_DATE.exec(text.split(/[ T]/)[0]);
+        if (!m) return;
+        matched++;
+        if (Number(m[1]) > 12) dayFirst++;
+        else if (Number(m[2]) > 12) monthFirst++;
+    });
+    if (!matched) return { dateOrder: 'ymd', ambiguous: [] };
+    if (yearFirst >= matched / 2) return { dateOrder: 'ymd', ambiguous: [] };
+    if (dayFirst && !monthFirst) return { dateOrder: 'dmy', ambiguous: [] };
+    if (monthFirst && !dayFirst) return { dateOrder: 'mdy', ambiguous: [] };
+    // Every day was <= 12 (or the file contradicts itself): a guess
+    return { dateOrder: null, ambiguous: ['dateOrder'] };
+};
+
+/**
+ * Hints for the first lines of a file: { locale, ambiguous, hasHeader, header,
+ * dateColumns, sample }. `ambiguous` lists fields the caller should confirm;
+ * where the date order can't be told, day-first is assumed for decimal-comma
+ * files and month-first otherwise.
+ */
+const probeLines = (rawLines) => {
+    const lines = rawLines.map(l => l.replace(/\r$/, '')).filter(l => l.trim()).slice(0, PROBE_LINES);
+    if (!lines.length) throw new Error('The file is empty');
+    const hasHeader = !/^\s*"?\d/.test(lines[0]);
+    const data = hasHeader ? lines.slice(1) : lines;
+    if (!data.length) throw new Error('The file has no data rows');
+    const delimiter = guessDelimiter(data);
+    const fieldDelimiter = delimiter || (data[0].includes(';') ? ';' : ',');
+    const rows = data.map(l => splitLine(l, fieldDelimiter));
+    const dateColumns = rows[0].length > 1 && TIME_OF_DAY.test(rows[0][1]) ? 2 : 1;
+    const numbers = [];
+    rows.forEach(r => r.slice(dateColumns, dateColumns + 5).forEach(v => { if (v) numbers.push(v); }));
+    const numberFormat = guessNumberFormat(numbers, fieldDelimiter);
+    const dates = guessDateOrder(rows.map(r => r[0]));
+    const dateOrder = dates.dateOrder || (numberFormat.decimal === ',' ? 'dmy' : 'mdy');
+    const locale = normalizeImportLocale({ delimiter, decimal: numberFormat.decimal, thousands: numberFormat.thousands, dateOrder });
+    return {
+        locale,
+        ambiguous: [...numberFormat.ambiguous, ...dates.ambiguous],
+        hasHeader,
+        header: hasHeader ? splitLine(lines[0], fieldDelimiter) : null,
+        dateColumns,
+        sample: rows.slice(0, 5)
+    };
+};
+
+// probeLines over the first PROBE_BYTES of a file (a cut-off last line is dropped)
+const probeFile = (filePath) => {
+    const fd = fs.openSync(filePath, 'r');
+    try {
+        const buf = Buffer.alloc(PROBE_BYTES);
+        const bytes = fs.readSync(fd, buf, 0, PROBE_BYTES, 0);
+        const lines = buf.subarray(0, bytes).toString('utf8').replace(/^\uFEFF/, '').split('\n');
+        if (bytes === PROBE_BYTES) lines.pop();
+        return probeLines(lines);
+    } finally {
+        fs.closeSync(fd);
+    }
+};
+
+module.exports = {
+    DEFAULT_IMPORT_LOCALE, PROBE_BYTES, normalizeImportLocale, isDefaultLocale, makeNumberParser, parseOrderedDate, probeLines, probeFile
+};
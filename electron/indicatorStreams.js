@@ -0,0 +1,94 @@
+
+const { createIncrementalIndicator, resolveParams } = require('./indicators');
+const { parseExpression, createExpressionStream } = require('./expressions');
+const { datasetId, parseDatasetId } = require('./datasets');
+
+// --- STREAMING INDICATORS ---
+// One incremental calculator per (dataset, indicator, params), shared by every
+// subscriber. Seeded from market_data on first subscribe, then advanced by live
+// bars: forming-bar updates are previews, closed bars commit. Bars at or before
+// the last committed timestamp are ignored so history and feed never double count.
+// indicator 'expression' with params { source } streams a custom series
+// (expressions.js); it only needs its lookback, so it is seeded from that many bars.
+
+const streamKey = (id, indicator, params) => `${id}|${indicator}|${JSON.stringify(params)}`;
+
+const createIndicatorStreams = ({ db, onUpdate }) => {
+    const streams = new Map(); // key -> { key, datasetId, indicator, params, next, lastTs, lastValue, refs }
+
+    const seed = (stream, limit = null) => {
+        const { symbol, timeframe } = parseDatasetId(stream.datasetId);
+        const iter = limit
+            ? db.prepare('SELECT timestamp, open, high, low, close, volume FROM market_data WHERE symbol = ? AND timeframe = ? ORDER BY timestamp DESC LIMIT ?').all(symbol, timeframe, limit).reverse()
+            : db.prepare('SELECT timestamp, open, high, low, close, volume FROM market_data WHERE symbol = ? AND timeframe = ? ORDER BY timestamp ASC').iterate(symbol, timeframe);
+        for (const bar of iter) {
+            stream.lastValue = stream.next(bar, true);
+            stream.lastTs = bar.timestamp;
+        }
+    };
+
+    /**
+     * Returns the stream key plus the latest committed value. Repeat calls with
+     * the same arguments share the calculator and bump its reference count.
+     */
+    const subscribe = (id, indicator, params = {}) => {
+        parseDatasetId(id);
+        const compiled = indicator === 'expression' ? parseExpression(params.source, { condition: false }) : null;
+        const resolved = compiled ? { source: compiled.source } : resolveParams(indicator, params);
+        const key = streamKey(id, indicator, resolved);
+        let stream = streams.get(key);
+        if (!stream) {
+            const next = compiled ? createExpressionStream(compiled) : createIncrementalIndicator(indicator, resolved);
+            stream = { key, datasetId: id, indicator, params: resolved, next, lastTs: -Infinity, lastValue: null, refs: 0 };
+            seed(stream, compiled ? compiled.lookback : null);
+            streams.set(key, stream);
+        }
+        stream.refs++;
+        return { subscriptionId: key, datasetId: id, indicator, params: resolved, timestamp: Number.isFinite(stream.lastTs) ? stream.lastTs : null, value: stream.lastValue };
+    };
+
+    const unsubscribe = (key) => {
+        const stream = streams.get(key);
+        if (!stream) return false;
+        if (--stream.refs <= 0) streams.delete(key);
+        return true;
+    };
+
+    // liveFeed 'bar' event: { provider, symbol, timeframe, bar, isClosed }
+    const handleBar = ({ symbol, timeframe, bar, isClosed }) => {
+        if (streams.size === 0) return;
+        const id = datasetId(symbol, timeframe);
+        streams.forEach((stream) => {
+            if (stream.datasetId !== id || bar.timestamp <= stream.lastTs) return;
+            const value = stream.next(bar, isClosed);
+            if (isClosed) {
+                stream.lastTs = bar.timestamp;
+                stream.lastValue = value;
+            }
+            onUpdate({ subscriptionId: stream.key, datasetId: id, indicator: stream.indicator, params: stream.params, timestamp: bar.timestamp, value, isClosed });
+        });
+    };
+
+    // History under a dataset changed (a bar edited or deleted): recompute its
+    // streams from scratch and push the new latest values
+    const reseed = (id) => {
+        let count = 0;
+        streams.forEach((stream) => {
+            if (stream.datasetId !== id) return;
+            const compiled = stream.indicator === 'expression' ? parseExpression(stream.params.source, { condition: false }) : null;
+            stream.next = compiled ? createExpressionStream(compiled) : createIncrementalIndicator(stream.indicator, stream.params);
+            stream.lastTs = -Infinity;
+            stream.lastValue = null;
+            seed(stream, compiled ? compiled.lookback : null);
+            count++;
+            onUpdate({ subscriptionId: stream.key, datasetId: id, indicator: stream.indicator, params: stream.params, timestamp: Number.isFinite(stream.lastTs) ? stream.lastTs : null, value: stream.lastValue, isClosed: true, reseeded: true });
+        });
+        return count;
+    };
+
+    const list = () => Array.from(streams.values()).map(s => ({ subscriptionId: s.key, datasetId: s.datasetId, indicator: s.indicator, params: s.params, refs: s.refs, timestamp: Number.isFinite(s.lastTs) ? s.lastTs : null, value: s.lastValue }));
+
+    return { subscribe, unsubscribe, handleBar, reseed, list, size: () => streams.size };
+};
+
+module.exports = { createIndicatorStreams };
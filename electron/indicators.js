@@ -0,0 +1,279 @@
+
+// --- INDICATOR MATH ---
+// Batch calculators over bar arrays ({ timestamp, open, high, low, close, volume }).
+// Every function returns an array aligned with `bars`, null until the
+// lookback is filled. Kept free of Electron and SQLite so compute workers can
+// load it directly.
+
+const DEFAULT_PARAMS = {
+    sma: { period: 20 },
+    ema: { period: 20 },
+    rsi: { period: 14 },
+    atr: { period: 14 },
+    stddev: { period: 20 },
+    macd: { fast: 12, slow: 26, signal: 9 },
+    macd_signal: { fast: 12, slow: 26, signal: 9 },
+    macd_hist: { fast: 12, slow: 26, signal: 9 }
+};
+
+const sma = (bars, { period }) => {
+    const out = new Array(bars.length).fill(null);
+    let sum = 0;
+    for (let i = 0; i < bars.length; i++) {
+        sum += bars[i].close;
+        if (i >= period) sum -= bars[i - period].close;
+        if (i >= period - 1) out[i] = sum / period;
+    }
+    return out;
+};
+
+// Seeded with the SMA of the first `period` closes
+const ema = (bars, { period }) => {
+    const out = new Array(bars.length).fill(null);
+    const k = 2 / (period + 1);
+    let value = null;
+    let seed = 0;
+    for (let i = 0; i < bars.length; i++) {
+        if (value == null) {
+            seed += bars[i].close;
+            if (i === period - 1) value = seed / period;
+        } else {
+            value = bars[i].close * k + value * (1 - k);
+        }
+        out[i] = value;
+    }
+    return out;
+};
+
+// Wilder smoothing of gains and losses
+const rsi = (bars, { period }) => {
+    const out = new Array(bars.length).fill(null);
+    let avgGain = 0, avgLoss = 0;
+    for (let i = 1; i < bars.length; i++) {
+        const change = bars[i].close - bars[i - 1].close;
+        const gain = Math.max(change, 0);
+        const loss = Math.max(-change, 0);
+        if (i <= period) {
+            avgGain += gain / period;
+            avgLoss += loss / period;
+            if (i < period) continue;
+        } else {
+            avgGain = (avgGain * (period - 1) + gain) / period;
+            avgLoss = (avgLoss * (period - 1) + loss) / period;
+        }
+        out[i] = avgLoss === 0 ? 100 : 100 - 100 / (1 + avgGain / avgLoss);
+    }
+    return out;
+};
+
+const trueRange = (bar, prevClose) => prevClose == null
+    ? bar.high - bar.low
+    : Math.max(bar.high - bar.low, Math.abs(bar.high - prevClose), Math.abs(bar.low - prevClose));
+
+const atr = (bars, { period }) => {
+    const out = new Array(bars.length).fill(null);
+    let value = null;
+    let seed = 0;
+    for (let i = 0; i < bars.length; i++) {
+        const tr = trueRange(bars[i], i > 0 ? bars[i - 1].close : null);
+        if (value == null) {
+            seed += tr;
+            if (i === period - 1) value = seed / period;
+        } else {
+            value = (value * (period - 1) + tr) / period;
+        }
+        out[i] = value;
+    }
+    return out;
+};
+
+// Population standard deviation of closes over the window
+const stddev = (bars, { period }) => {
+    const out = new Array(bars.length).fill(null);
+    let sum = 0, sumSq = 0;
+    for (let i = 0; i < bars.length; i++) {
+        const c = bars[i].close;
+        sum += c;
+        sumSq += c * c;
+        if (i >= period) {
+            const old = bars[i - period].close;
+            sum -= old;
+            sumSq -= old * old;
+        }
+        if (i >= period - 1) {
+            const mean = sum / period;
+            out[i] = Math.sqrt(Math.max(0, sumSq / period - mean * mean));
+        }
+    }
+    return out;
+};
+
+// EMA over a derived series that starts with nulls (e.g. the MACD line)
+const emaOfValues = (values, period) => {
+    const out = new Array(values.length).fill(null);
+    const k = 2 / (period + 1);
+    let value = null;
+    let seed = 0;
+    let count = 0;
+    for (let i = 0; i < values.length; i++) {
+        const v = values[i];
+        if (v == null) continue;
+        if (value == null) {
+            seed += v;
+            count++;
+            if (count === period) value = seed / period;
+        } else {
+            value = v * k + value * (1 - k);
+        }
+        out[i] = value;
+    }
+    return out;
+};
+
+// MACD line (fast EMA - slow EMA), its signal EMA and the histogram between them
+const macdSeries = (bars, { fast, slow, signal }) => {
+    const f = ema(bars, { period: fast });
+    const s = ema(bars, { period: slow });
+    const line = f.map((v, i) => (v == null || s[i] == null ? null : v - s[i]));
+    const sig = emaOfValues(line, signal);
+    return { line, sig, hist: line.map((v, i) => (v == null || sig[i] == null ? null : v - sig[i])) };
+};
+
+const macd = (bars, params) => macdSeries(bars, params).line;
+const macdSignal = (bars, params) => macdSeries(bars, params).sig;
+const macdHist = (bars, params) => macdSeries(bars, params).hist;
+
+const INDICATORS = { sma, ema, rsi, atr, stddev, macd, macd_signal: macdSignal, macd_hist: macdHist };
+
+const resolveParams = (indicator, params = {}) => {
+    if (!INDICATORS[indicator]) throw new Error(`Unknown indicator: ${indicator}`);
+    const merged = { ...DEFAULT_PARAMS[indicator], ...params };
+    Object.keys(DEFAULT_PARAMS[indicator]).forEach((key) => {
+        if (!Number.isInteger(merged[key]) || merged[key] < 1) throw new Error(`Invalid ${key} for ${indicator}: ${merged[key]}`);
+    });
+    return merged;
+};
+
+const computeIndicator = (indicator, bars, params) => INDICATORS[indicator](bars, resolveParams(indicator, params));
+
+// --- INCREMENTAL CALCULATORS ---
+// Stateful versions for streaming bars: next(bar, commit) returns the value for
+// `bar` in O(1). Live feeds update the forming bar many times before it closes,
+// so only commit=true (a closed bar) advances the state; uncommitted calls are
+// previews against the last closed state. Seeded by replaying history with commit=true.
+
+// Fixed-size ring of the last `size` values
+const createWindow = (size) => {
+    const values = new Array(size);
+    let head = 0;
+    let count = 0;
+    return {
+        get count() { return count; },
+        oldest: () => values[head],
+        push: (v) => {
+            values[head] = v;
+            head = (head + 1) % size;
+            count = Math.min(count + 1, size);
+        }
+    };
+};
+
+const INCREMENTAL = {
+    sma: ({ period }) => {
+        const win = createWindow(period);
+        let sum = 0;
+        return (bar, commit) => {
+            const full = win.count === period;
+            const next = sum + bar.close - (full ? win.oldest() : 0);
+            const value = win.count + (full ? 0 : 1) >= period ? next / period : null;
+            if (commit) { sum = next; win.push(bar.close); }
+            return value;
+        };
+    },
+    ema: ({ period }) => {
+        const k = 2 / (period + 1);
+        let value = null, seed = 0, count = 0;
+        return (bar, commit) => {
+            let v;
+            if (value == null) v = count + 1 === period ? (seed + bar.close) / period : null;
+            else v = bar.close * k + value * (1 - k);
+            if (commit) {
+                count++;
+                if (value == null) seed += bar.close;
+                value = v;
+            }
+            return v;
+        };
+    },
+    rsi: ({ period }) => {
+        let prevClose = null, avgGain = 0, avgLoss = 0, count = 0;
+        return (bar, commit) => {
+            if (prevClose == null) {
+                if (commit) prevClose = bar.close;
+                return null;
+            }
+            const change = bar.close - prevClose;
+            const gain = Math.max(change, 0);
+            const loss = Math.max(-change, 0);
+            const n = count + 1;
+            const g = n <= period ? avgGain + gain / period : (avgGain * (period - 1) + gain) / period;
+            const l = n <= period ? avgLoss + loss / period : (avgLoss * (period - 1) + loss) / period;
+            if (commit) { avgGain = g; avgLoss = l; count = n; prevClose = bar.close; }
+            if (n < period) return null;
+            return l === 0 ? 100 : 100 - 100 / (1 + g / l);
+        };
+    },
+    atr: ({ period }) => {
+        let prevClose = null, value = null, seed = 0, count = 0;
+        return (bar, commit) => {
+            const tr = trueRange(bar, prevClose);
+            let v;
+            if (value == null) v = count + 1 === period ? (seed + tr) / period : null;
+            else v = (value * (period - 1) + tr) / period;
+            if (commit) {
+                count++;
+                if (value == null) seed += tr;
+                value = v;
+                prevClose = bar.close;
+            }
+            return v;
+        };
+    },
+    stddev: ({ period }) => {
+        const win = createWindow(period);
+        let sum = 0, sumSq = 0;
+        return (bar, commit) => {
+            const c = bar.close;
+            const full = win.count === period;
+            const old = full ? win.oldest() : 0;
+            const s = sum + c - old;
+            const sq = sumSq + c * c - old * old;
+            const n = full ? period : win.count + 1;
+            if (commit) { sum = s; sumSq = sq; win.push(c); }
+            if (n < period) return null;
+            const mean = s / period;
+            return Math.sqrt(Math.max(0, sq / period - mean * mean));
+        };
+    }
+};
+
+// `pick` selects the line, signal or histogram
+const incrementalMacd = (pick) => ({ fast, slow, signal }) => {
+    const f = INCREMENTAL.ema({ period: fast });
+    const s = INCREMENTAL.ema({ period: slow });
+    const sig = INCREMENTAL.ema({ period: signal });
+    return (bar, commit) => {
+        const a = f(bar, commit);
+        const b = s(bar, commit);
+        const line = a == null || b == null ? null : a - b;
+        const sv = line == null ? null : sig({ close: line }, commit);
+        return pick(line, sv);
+    };
+};
+INCREMENTAL.macd = incrementalMacd(line => line);
+INCREMENTAL.macd_signal = incrementalMacd((line, sv) => sv);
+INCREMENTAL.macd_hist = incrementalMacd((line, sv) => (line == null || sv == null ? null : line - sv));
+
+const createIncrementalIndicator = (indicator, params) => INCREMENTAL[indicator](resolveParams(indicator, params));
+
+module.exports = { INDICATORS, DEFAULT_PARAMS, resolveParams, computeIndicator, trueRange, createIncrementalIndicator };
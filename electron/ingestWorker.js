@@ -3,12 +3,40 @@ const { parentPort } = require('worker_threads');
 const Database = require('better-sqlite3');
 const fs = require('fs');
 const readline = require('readline');
+const { readHstHeader, parseHstRecords, parseMetaTraderCsvLine, HST_HEADER_SIZE, HST_V400_RECORD, HST_V401_RECORD } = require('./metaTrader');
+const { parseNumber, parseDateTime } = require('./fastParse');
+const { isDefaultLocale, makeNumberParser, parseOrderedDate } = require('./importLocale');
 
-// Helper: Parse a single CSV line
-const parseLine = (line) => {
+const OHLCV_FIELDS = ['open', 'high', 'low', 'close', 'volume'];
+
+// Set per task from `fastParse` (default on); off means the builtin parsers only
+let useFastParse = true;
+// Set per task from `locale` (importLocale.js); null is the historical parse
+let importLocale = null;
+let localeNumber = null;
+
+// d/m/y or m/d/y first column(s) under a non-ymd locale: { timestamp, offset },
+// false for an impossible date (31/02) and null when the column isn't shaped like one
+const orderedTimestamp = (p0, p1) => {
+    if (!importLocale || importLocale.dateOrder === 'ymd' || !/^\d{1,2}[./-]\d{1,2}[./-]\d{2,4}/.test(p0)) return null;
+    if (p1.includes(':')) {
+        const timestamp = parseOrderedDate(p0, p1, importLocale.dateOrder);
+        return timestamp == null ? false : { timestamp, offset: 2 };
+    }
+    const [date, time = null] = p0.split(/[ T]/);
+    const timestamp = parseOrderedDate(date, time, importLocale.dateOrder);
+    return timestamp == null ? false : { timestamp, offset: 1 };
+};
+
+
+// Helper: Parse a single CSV line.
+// `filter` ({ from, to, columns }) is pushed down into the parse: the timestamp
+// is resolved first, out-of-range rows return { outOfRange, timestamp } before
+// any price column is touched, and only the requested columns are converted.
+const parseLine = (line, filter = null) => {
     if (!line || !line.trim() || !/^\d/.test(line.trim())) return null;
 
-    const delimiter = line.indexOf(';') > -1 ? ';' : ',';
+    const delimiter = importLocale && importLocale.delimiter ? importLocale.delimiter : line.indexOf(';') > -1 ? ';' : ',';
     const parts = line.split(delimiter);
 
     // Minimum columns check
@@ -17,48 +45,36 @@ const parseLine = (line) => {
     try {
         let dateStr = '';
         let timestamp = 0;
-        let open = 0, high = 0, low = 0, close = 0, volume = 0;
+        let offset = 1; // index of the open column
 
         const p0 = parts[0].trim();
         const p1 = parts[1].trim();
+        const ordered = orderedTimestamp(p0, p1);
+        if (ordered === false) return null;
 
         // Heuristics for Date+Time vs DateTime
         const isDateColumn = /^\d{8}$/.test(p0) || /^\d{4}[\.\-\/]\d{2}[\.\-\/]\d{2}$/.test(p0);
         const isTimeColumn = p1.includes(':');
 
-        if (isDateColumn && isTimeColumn) {
+        if (ordered) {
+            timestamp = ordered.timestamp;
+            offset = ordered.offset;
+        } else if (isDateColumn && isTimeColumn) {
             // Format: YYYYMMDD or YYYY/MM/DD + HH:MM:SS
             let cleanDate = p0.replace(/[\.\-\/]/g, '');
             if (cleanDate.length === 8) {
                 cleanDate = `${cleanDate.substring(0,4)}-${cleanDate.substring(4,6)}-${cleanDate.substring(6,8)}`;
             }
             dateStr = `${cleanDate}T${p1}`;
-            
             // OHLCV indices shifted
-            open = parseFloat(parts[2]);
-            high = parseFloat(parts[3]);
-            low = parseFloat(parts[4]);
-            close = parseFloat(parts[5]);
-            if (parts.length > 6) {
-                const v = parseFloat(parts[6]);
-                volume = isNaN(v) ? 0 : v;
-            }
+            offset = 2;
+            if (useFastParse) timestamp = parseDateTime(p0, p1) || 0;
         } else {
              // Standard Format: Date, Open, High, Low, Close, Volume
              dateStr = p0;
-             open = parseFloat(parts[1]);
-             high = parseFloat(parts[2]);
-             low = parseFloat(parts[3]);
-             close = parseFloat(parts[4]);
-             if (parts.length > 5) {
-                 const v = parseFloat(parts[5]);
-                 volume = isNaN(v) ? 0 : v;
-             }
         }
 
-        if (isNaN(open) || isNaN(close)) return null;
-
-        timestamp = new Date(dateStr).getTime();
+        if (!timestamp) timestamp = new Date(dateStr).getTime();
         
         // Fallback for raw unix timestamps
         if (isNaN(timestamp)) {
@@ -69,22 +85,213 @@ const parseLine = (line) => {
 
         if (isNaN(timestamp) || timestamp <= 0) return null;
 
-        return { timestamp, open, high, low, close, volume };
+        if (filter && filter.from != null && timestamp < filter.from) return { outOfRange: 'before', timestamp };
+        if (filter && filter.to != null && timestamp > filter.to) return { outOfRange: 'after', timestamp };
+
+        const row = { timestamp };
+        const wanted = filter && filter.columns ? filter.columns : OHLCV_FIELDS;
+        const toNumber = localeNumber || (useFastParse ? parseNumber : parseFloat);
+        for (const field of wanted) {
+            const i = offset + OHLCV_FIELDS.indexOf(field);
+            if (field === 'volume') {
+                const v = parts.length > i ? toNumber(parts[i]) : NaN;
+                row.volume = isNaN(v) ? 0 : v;
+            } else {
+                row[field] = parts.length > i ? toNumber(parts[i]) : NaN;
+            }
+        }
+
+        if (('open' in row && isNaN(row.open)) || ('close' in row && isNaN(row.close))) return null;
+
+        return row;
     } catch (e) {
         return null;
     }
 };
 
+// Progress is throttled so multi-GB imports don't flood the main process
+const PROGRESS_INTERVAL_MS = 500;
+const MAX_REPORTED_ERRORS = 20;
+
+const createProgressReporter = (taskId, totalBytes) => {
+    const startedAt = Date.now();
+    let lastSent = 0;
+    const stats = { rowsParsed: 0, rowsRejected: 0, errors: [] };
+
+    const send = (bytesProcessed, force = false) => {
+        const now = Date.now();
+        if (!force && now - lastSent < PROGRESS_INTERVAL_MS) return;
+        lastSent = now;
+        parentPort.postMessage({ type: 'progress', taskId, bytesProcessed, totalBytes, rowsParsed: stats.rowsParsed, rowsRejected: stats.rowsRejected, elapsedMs: now - startedAt });
+    };
+
+    const reject = (lineNumber, text, reason) => {
+        stats.rowsRejected++;
+        if (stats.errors.length < MAX_REPORTED_ERRORS) stats.errors.push({ line: lineNumber, text: text.slice(0, 200), reason });
+    };
+
+    const summary = (count) => ({ taskId, count, rowsParsed: stats.rowsParsed, rowsRejected: stats.rowsRejected, errors: stats.errors, totalBytes, durationMs: Date.now() - startedAt });
+
+    return { stats, send, reject, summary };
+};
+
+// Read mode: parse straight from the file into flat rows [t, ...columns] without
+// touching the database. Sorted files stop streaming once past `to`.
+const runRead = (task) => {
+    const { filePath, format = 'csv', brokerOffset = 0, from = null, to = null, limit = null } = task;
+    const columns = (task.columns && task.columns.length ? task.columns : OHLCV_FIELDS).filter(c => OHLCV_FIELDS.includes(c));
+    const filter = { from, to, columns };
+    const rows = [];
+    let scanned = 0;
+    let ascending = true;
+    let lastTs = -Infinity;
+    let seen = 0;
+
+    const take = (row) => {
+        rows.push([row.timestamp, ...columns.map(c => row[c])]);
+        return limit != null && rows.length >= limit;
+    };
+
+    const finish = (stoppedEarly) => parentPort.postMessage({ success: true, columns: ['time', ...columns], data: rows, format: 'array', scanned, stoppedEarly, startOffset: task.startOffset || 0 });
+
+    // Non-generic formats parse fully first, then filter
+    const inRange = (row) => (from == null || row.timestamp >= from) && (to == null || row.timestamp <= to);
+
+    if (format === 'hst') {
+        const fileBuffer = fs.readFileSync(filePath);
+        const header = readHstHeader(fileBuffer);
+        for (const row of parseHstRecords(fileBuffer, header, brokerOffset)) {
+            scanned++;
+            if (inRange(row) && take(row)) return finish(true);
+        }
+        return finish(false);
+    }
+
+    // startOffset comes from the persistent time index and always points at a line start
+    const fileStream = fs.createReadStream(filePath, task.startOffset ? { start: task.startOffset } : undefined);
+    const rl = readline.createInterface({ input: fileStream, crlfDelay: Infinity });
+    let done = false;
+
+    const stop = () => {
+        done = true;
+        rl.close();
+        fileStream.destroy();
+    };
+
+    rl.on('line', (line) => {
+        if (done) return;
+        scanned++;
+        let row;
+        if (format === 'mt-csv') {
+            row = parseMetaTraderCsvLine(line, brokerOffset);
+            if (row && !inRange(row)) row = { outOfRange: row.timestamp > to ? 'after' : 'before', timestamp: row.timestamp };
+        } else {
+            row = parseLine(line, filter);
+        }
+        if (!row) return;
+
+        // Ordering is tracked across every dated row so an early stop is only taken on sorted files
+        if (row.timestamp < lastTs) ascending = false;
+        lastTs = row.timestamp;
+        seen++;
+
+        if (row.outOfRange === 'after') {
+            if (ascending && seen > 1) { stop(); finish(true); }
+            return;
+        }
+        if (row.outOfRange) return;
+        if (take(row)) { stop(); finish(true); }
+    });
+
+    rl.on('close', () => { if (!done) { done = true; finish(false); } });
+    rl.on('error', (err) => parentPort.postMessage({ success: false, error: `Stream read error: ${err.message}` }));
+};
+
+// Index mode: one pass recording [timestamp, byteOffset] for the first line
+// after every `stride` bytes. Offsets are tracked on raw bytes (not readline)
+// so they can be handed straight to createReadStream({ start }).
+const runIndex = (task) => {
+    const { filePath, format = 'csv', brokerOffset = 0, stride = 1024 * 1024 } = task;
+    const timestampOf = format === 'mt-csv'
+        ? (line) => { const r = parseMetaTraderCsvLine(line, brokerOffset); return r ? r.timestamp : null; }
+        : (line) => { const r = parseLine(line, { columns: [] }); return r ? r.timestamp : null; };
+
+    const entries = [];
+    let ascending = true;
+    let lastTs = -Infinity;
+    let firstTs = null;
+    let rows = 0;
+    let position = 0;        // absolute offset of `pending`
+    let nextMark = 0;        // record the first line starting at or after this offset
+    let pending = Buffer.alloc(0);
+
+    const onLine = (lineBuf, lineStart) => {
+        const ts = timestampOf(lineBuf.toString('utf8'));
+        if (ts == null) return;
+        rows++;
+        if (firstTs == null) firstTs = ts;
+        if (ts < lastTs) ascending = false;
+        lastTs = ts;
+        if (lineStart >= nextMark) {
+            entries.push([ts, lineStart]);
+            nextMark = lineStart + stride;
+        }
+    };
+
+    const stream = fs.createReadStream(filePath, { highWaterMark: 256 * 1024 });
+    stream.on('data', (chunk) => {
+        const buf = pending.length ? Buffer.concat([pending, chunk]) : chunk;
+        let lineStart = 0;
+        let nl;
+        while ((nl = buf.indexOf(10, lineStart)) !== -1) {
+            onLine(buf.subarray(lineStart, nl), position + lineStart);
+            lineStart = nl + 1;
+        }
+        pending = Buffer.from(buf.subarray(lineStart));
+        position += lineStart;
+    });
+    stream.on('end', () => {
+        if (pending.length) onLine(pending, position);
+        parentPort.postMessage({ success: true, entries, ascending, rows, firstTs, lastTs: rows ? lastTs : null, stride });
+    });
+    stream.on('error', (err) => parentPort.postMessage({ success: false, error: `Index build failed: ${err.message}` }));
+};
+
 parentPort.on('message', (task) => {
-    const { dbPath, filePath, symbol, timeframe } = task;
+    useFastParse = task.fastParse !== false;
+    importLocale = isDefaultLocale(task.locale) ? null : task.locale;
+    localeNumber = makeNumberParser(importLocale);
+    if (task.mode === 'index') {
+        try {
+            runIndex(task);
+        } catch (err) {
+            parentPort.postMessage({ success: false, error: `Worker index failed: ${err.message}` });
+        }
+        return;
+    }
+
+    if (task.mode === 'read') {
+        try {
+            runRead(task);
+        } catch (err) {
+            parentPort.postMessage({ success: false, error: `Worker read failed: ${err.message}` });
+        }
+        return;
+    }
+
+    const { dbPath, filePath, symbol, timeframe, format = 'csv', brokerOffset = 0, taskId = null } = task;
     
     // 1. Connect to DB
     let db;
     try {
-        db = new Database(dbPath);
-        // Optimization Pragmas
-        db.pragma('journal_mode = WAL');
-        db.pragma('synchronous = NORMAL');
+        if (task.readOnly) {
+            db = new Database(dbPath, { readonly: true });
+        } else {
+            db = new Database(dbPath);
+            // Optimization Pragmas
+            db.pragma('journal_mode = WAL');
+            db.pragma('synchronous = NORMAL');
+        }
     } catch (err) {
         parentPort.postMessage({ success: false, error: `Worker DB connection failed: ${err.message}` });
         return;
@@ -109,26 +316,68 @@ parentPort.on('message', (task) => {
         let buffer = [];
         let totalRows = 0;
 
-        const fileStream = fs.createReadStream(filePath);
-        const rl = readline.createInterface({
-            input: fileStream,
-            crlfDelay: Infinity
-        });
+        const totalBytes = fs.statSync(filePath).size;
+        const progress = createProgressReporter(taskId, totalBytes);
 
-        rl.on('line', (line) => {
-            const row = parseLine(line);
-            if (row) {
+        // MetaTrader HST: fixed-size binary records, no line streaming needed
+        if (format === 'hst') {
+            const fileBuffer = fs.readFileSync(filePath);
+            const header = readHstHeader(fileBuffer);
+            const recordSize = header.version === 401 ? HST_V401_RECORD : HST_V400_RECORD;
+            for (const row of parseHstRecords(fileBuffer, header, brokerOffset)) {
                 buffer.push(row);
-                
-                // Flush buffer when full
+                progress.stats.rowsParsed++;
                 if (buffer.length >= BATCH_SIZE) {
-                    // Sort buffer to improve index locality (optional but recommended)
-                    buffer.sort((a, b) => a.timestamp - b.timestamp);
                     insertBatch(buffer);
                     totalRows += buffer.length;
                     buffer = [];
+                    progress.send(Math.min(totalBytes, HST_HEADER_SIZE + progress.stats.rowsParsed * recordSize));
                 }
             }
+            if (buffer.length > 0) {
+                insertBatch(buffer);
+                totalRows += buffer.length;
+            }
+            db.close();
+            progress.send(totalBytes, true);
+            parentPort.postMessage({ success: true, ...progress.summary(totalRows) });
+            return;
+        }
+
+        const lineParser = format === 'mt-csv'
+            ? (line) => parseMetaTraderCsvLine(line, brokerOffset)
+            : parseLine;
+
+        const fileStream = fs.createReadStream(filePath);
+        const rl = readline.createInterface({
+            input: fileStream,
+            crlfDelay: Infinity
+        });
+
+        let lineNumber = 0;
+        let sawData = false;
+
+        rl.on('line', (line) => {
+            lineNumber++;
+            const row = lineParser(line);
+            if (!row) {
+                // Blank lines and a leading header row are expected, anything else is a rejected row
+                if (line.trim() && (sawData || /^\d/.test(line.trim()))) progress.reject(lineNumber, line, 'Unparseable row');
+                return;
+            }
+            sawData = true;
+            progress.stats.rowsParsed++;
+            progress.send(fileStream.bytesRead);
+            buffer.push(row);
+
+            // Flush buffer when full
+            if (buffer.length >= BATCH_SIZE) {
+                // Sort buffer to improve index locality (optional but recommended)
+                buffer.sort((a, b) => a.timestamp - b.timestamp);
+                insertBatch(buffer);
+                totalRows += buffer.length;
+                buffer = [];
+            }
         });
 
         rl.on('close', () => {
@@ -140,7 +389,8 @@ parentPort.on('message', (task) => {
             }
             
             db.close();
-            parentPort.postMessage({ success: true, count: totalRows });
+            progress.send(totalBytes, true);
+            parentPort.postMessage({ success: true, ...progress.summary(totalRows) });
         });
 
         rl.on('error', (err) => {
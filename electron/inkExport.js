@@ -0,0 +1,118 @@
+
+const { BrowserWindow } = require('electron');
+
+// --- INK EXPORT ---
+// Renders ink to standalone SVG, or PNG via an offscreen window: freehand
+// 'brush' drawings of a chart, or the handwritten strokes of a sticky note.
+// Brush strokes are stored in chart space (time, price), so they are mapped
+// linearly onto the export canvas; time gaps (weekends, sessions) that the
+// live chart collapses are not collapsed here. Note ink is in note-local
+// pixels and keeps its proportions, cropped to the strokes.
+
+const BASE_WIDTH = 800;
+const PADDING = 16;
+
+const escapeAttr = (value) => String(value).replace(/&/g, '&amp;').replace(/"/g, '&quot;').replace(/</g, '&lt;');
+
+const dashArray = (style, width) => {
+    if (style === 'dashed') return `${width * 4} ${width * 3}`;
+    if (style === 'dotted') return `${width} ${width * 2}`;
+    return null;
+};
+
+// One pass over every point; long strokes are too big to spread into Math.min
+const strokeBounds = (strokes) => {
+    let minX = Infinity, maxX = -Infinity, minY = Infinity, maxY = -Infinity;
+    strokes.forEach(s => s.points.forEach(([x, y]) => {
+        if (x < minX) minX = x;
+        if (x > maxX) maxX = x;
+        if (y < minY) minY = y;
+        if (y > maxY) maxY = y;
+    }));
+    return { minX, maxX, minY, maxY };
+};
+
+// strokes: [{ points: [[x, y]], color, width, lineStyle? }] already in canvas units
+const buildSvg = (strokes, { outW, outH, scale, background }) => {
+    const paths = strokes.map((s) => {
+        const strokeWidth = s.width * scale;
+        const dash = dashArray(s.lineStyle, strokeWidth);
+        const points = s.points.map(([x, y]) => [(x * scale).toFixed(2), (y * scale).toFixed(2)]);
+        const pathData = `M${points[0].join(' ')}` + points.slice(1).map(([x, y]) => ` L${x} ${y}`).join('');
+        return `  <path d="${pathData}" fill="none" stroke="${escapeAttr(s.color)}" stroke-width="${strokeWidth}" stroke-linecap="round" stroke-linejoin="round"${dash ? ` stroke-dasharray="${dash}"` : ''}/>`;
+    });
+    const bg = background ? `  <rect width="100%" height="100%" fill="${escapeAttr(background)}"/>\n` : '';
+    return `<?xml version="1.0" encoding="UTF-8"?>\n<svg xmlns="http://www.w3.org/2000/svg" width="${outW}" height="${outH}" viewBox="0 0 ${outW} ${outH}">\n${bg}${paths.join('\n')}\n</svg>\n`;
+};
+
+/**
+ * Builds an SVG document for the given brush drawings.
+ * Returns { svg, width, height } in output pixels (already multiplied by scale).
+ */
+const renderInkSvg = (drawings, { scale = 1, width = BASE_WIDTH, height = Math.round(width * 0.6), background = null } = {}) => {
+    const brushes = drawings.filter(d => d.type === 'brush' && d.points && d.points.length > 1);
+    if (brushes.length === 0) throw new Error('No ink strokes to export');
+
+    const raw = brushes.map(d => ({ d, points: d.points.map(p => [p.time, p.price]) }));
+    const { minX: minT, maxX: maxT, minY: minP, maxY: maxP } = strokeBounds(raw);
+    const spanT = maxT - minT || 1;
+    const spanP = maxP - minP || 1;
+    const innerW = width - PADDING * 2;
+    const innerH = height - PADDING * 2;
+
+    const strokes = raw.map(({ d, points }) => {
+        const props = d.properties || {};
+        return {
+            points: points.map(([t, p]) => [PADDING + ((t - minT) / spanT) * innerW, PADDING + (1 - (p - minP) / spanP) * innerH]),
+            color: props.color || '#3b82f6',
+            width: props.lineWidth || 2,
+            lineStyle: props.lineStyle
+        };
+    });
+    const outW = Math.round(width * scale);
+    const outH = Math.round(height * scale);
+    return { svg: buildSvg(strokes, { outW, outH, scale, background }), width: outW, height: outH };
+};
+
+/**
+ * Builds an SVG document for a sticky note's inkData. The canvas is the
+ * strokes' extent plus padding, at `scale` output pixels per note pixel;
+ * strokes without a colour take `color`. Returns { svg, width, height }.
+ */
+const renderNoteInkSvg = (inkData, { scale = 1, color = '#111827', background = null } = {}) => {
+    const raw = (Array.isArray(inkData) ? inkData : []).filter(s => s && Array.isArray(s.points) && s.points.length > 0);
+    if (raw.length === 0) throw new Error('No ink strokes to export');
+    if (!(scale > 0)) throw new Error('scale must be positive');
+
+    const withXY = raw.map(s => ({ s, points: s.points.map(p => [p.x, p.y]) }));
+    const { minX, maxX, minY, maxY } = strokeBounds(withXY);
+    const widest = raw.reduce((w, s) => Math.max(w, s.width || 2), 0);
+    const margin = PADDING + widest / 2;
+    const strokes = withXY.map(({ s, points }) => ({
+        // A single tap still draws a dot
+        points: (points.length === 1 ? [points[0], points[0]] : points).map(([x, y]) => [x - minX + margin, y - minY + margin]),
+        color: s.color || color,
+        width: s.width || 2
+    }));
+    const outW = Math.max(1, Math.round((maxX - minX + margin * 2) * scale));
+    const outH = Math.max(1, Math.round((maxY - minY + margin * 2) * scale));
+    return { svg: buildSvg(strokes, { outW, outH, scale, background }), width: outW, height: outH };
+};
+
+// Chromium does the rasterising; the window is never shown
+const rasterizeSvg = async (svg, width, height) => {
+    const win = new BrowserWindow({
+        width, height, show: false, frame: false, transparent: true,
+        webPreferences: { offscreen: true, javascript: false }
+    });
+    try {
+        const html = `<html><body style="margin:0;background:transparent">${svg.replace(/^<\?xml[^>]*>/, '')}</body></html>`;
+        await win.loadURL(`data:text/html;charset=utf-8,${encodeURIComponent(html)}`);
+        const image = await win.webContents.capturePage({ x: 0, y: 0, width, height });
+        return image.toPNG();
+    } finally {
+        win.destroy();
+    }
+};
+
+module.exports = { renderInkSvg, renderNoteInkSvg, rasterizeSvg };
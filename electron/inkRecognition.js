@@ -0,0 +1,26 @@
+
+// --- INK RECOGNITION HOOK ---
+// Extension point for handwriting-to-text on ink notes. A recognizer is
+// (strokes) -> Promise<string | null>, strokes being a note's inkData. Neither
+// Electron nor the current dependencies include an offline handwriting model,
+// so nothing is registered by default and ink notes keep recognizedText null;
+// a recognizer module only has to call setInkRecognizer() at startup. Its
+// output lands in the note's recognized_text column, which note search covers.
+
+let recognizer = null;
+
+const setInkRecognizer = (fn) => {
+    if (fn !== null && typeof fn !== 'function') throw new Error('An ink recognizer must be a function or null');
+    recognizer = fn;
+};
+
+const hasInkRecognizer = () => !!recognizer;
+
+// Resolves to the trimmed text, or null (no recognizer, nothing recognized)
+const recognizeInk = async (strokes) => {
+    if (!recognizer || !Array.isArray(strokes) || strokes.length === 0) return null;
+    const text = await recognizer(strokes);
+    return text == null ? null : String(text).trim() || null;
+};
+
+module.exports = { setInkRecognizer, hasInkRecognizer, recognizeInk };
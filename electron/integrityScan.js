@@ -0,0 +1,161 @@
+
+const fs = require('fs');
+const path = require('path');
+
+// --- STARTUP INTEGRITY SCAN ---
+// Runs once the database is open. SQLite's quick_check covers the file itself
+// (a failing index is rebuilt with REINDEX); then every JSON payload that
+// charts, notes, trades and settings depend on is parse-checked. A broken
+// record is restored from the newest backup holding a valid copy (for chart
+// states, the trash too), otherwise notes get their broken fields reset and
+// other records are removed so the app loads without them; the original bytes
+// always go to `integrity_quarantine` first. The summary says what was found
+// and recovered so the UI can tell the user.
+
+const MAX_BACKUPS_TRIED = 5;
+
+// table, key column, JSON columns, fallback when nothing valid can be restored
+// (null = remove the row), and whether chart-state trash holds copies
+const CHECKS = [
+    { table: 'drawings', key: 'symbol', columns: ['data'], reset: null, trash: true },
+    { table: 'drawings_trash', key: 'symbol', columns: ['data'], reset: null },
+    { table: 'trades', key: 'id', columns: ['data'], reset: null },
+    { table: 'settings', key: 'key', columns: ['value'], reset: null },
+    { table: 'sticky_notes', key: 'id', columns: ['tags', 'layout'], reset: { tags: '[]', layout: '{}' } },
+    { table: 'note_templates', key: 'id', columns: ['tags', 'layout'], reset: { tags: '[]', layout: '{}' } }
+];
+
+const initializeQuarantineTable = (db) => {
+    db.exec(`
+        CREATE TABLE IF NOT EXISTS integrity_quarantine (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            source_table TEXT,
+            record_key TEXT,
+            data TEXT,
+            problem TEXT,
+            quarantined_at INTEGER
+        );
+    `);
+};
+
+const tableExists = (db, table) => !!db.prepare("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?").get(table);
+
+// null when the value parses; 'truncated' when it ends early, else 'invalid'
+const jsonProblem = (value) => {
+    if (value == null || value === '') return null;
+    try {
+        JSON.parse(value);
+        return null;
+    } catch (err) {
+        return /end of (json )?input|unterminated/i.test(err.message) ? 'truncated' : 'invalid';
+    }
+};
+
+const rowProblem = (row, columns) => {
+    for (const column of columns) {
+        const problem = jsonProblem(row[column]);
+        if (problem) return `${column}: ${problem}`;
+    }
+    return null;
+};
+
+// Newest first; openBackup(file) -> read-only database handle
+const listBackups = (backupsDir) => {
+    try {
+        return fs.readdirSync(backupsDir).filter(f => /^redpill-.*\.db$/.test(f)).sort().reverse()
+            .slice(0, MAX_BACKUPS_TRIED).map(f => path.join(backupsDir, f));
+    } catch (e) {
+        return [];
+    }
+};
+
+/**
+ * db: the open database; backupsDir: where scheduled backups are written;
+ * openBackup(file): read-only handle on a backup. Returns { status, checkedAt,
+ * ms, sqlite, checked, repaired, reset, removed, problems }: status is 'repaired'
+ * when every problem was restored from a copy, 'warn' when something had to
+ * be reset or removed, 'fail' when SQLite itself still reports damage.
+ */
+const runIntegrityScan = ({ db, backupsDir = null, openBackup = null }) => {
+    const started = Date.now();
+    const summary = { status: 'ok', checkedAt: started, ms: 0, sqlite: 'ok', checked: 0, repaired: [], reset: [], removed: [], problems: [] };
+
+    try {
+        const result = db.pragma('quick_check', { simple: true });
+        if (result !== 'ok') {
+            summary.sqlite = String(result);
+            summary.problems.push(`quick_check: ${result}`);
+            db.exec('REINDEX');
+            const after = db.pragma('quick_check', { simple: true });
+            summary.sqlite = after === 'ok' ? 'reindexed' : String(after);
+        }
+    } catch (err) {
+        summary.sqlite = `failed: ${err.message}`;
+        summary.problems.push(summary.sqlite);
+    }
+
+    initializeQuarantineTable(db);
+    const backups = [];
+    const backupHandles = () => {
+        if (!backups.length && backupsDir && openBackup) {
+            listBackups(backupsDir).forEach((file) => {
+                try { backups.push({ file: path.basename(file), db: openBackup(file) }); } catch (e) {}
+            });
+            if (!backups.length) backups.push(null); // tried, none usable
+        }
+        return backups.filter(Boolean);
+    };
+
+    // A valid copy of the record: backups (newest first), then the chart-state trash
+    const findCopy = (check, key) => {
+        for (const backup of backupHandles()) {
+            try {
+                if (!tableExists(backup.db, check.table)) continue;
+                const row = backup.db.prepare(`SELECT * FROM ${check.table} WHERE ${check.key} = ?`).get(key);
+                if (row && !rowProblem(row, check.columns)) return { row, from: `backup ${backup.file}` };
+            } catch (e) {}
+        }
+        if (check.trash && tableExists(db, 'drawings_trash')) {
+            const row = db.prepare('SELECT data FROM drawings_trash WHERE symbol = ?').get(key);
+            if (row && !jsonProblem(row.data)) return { row: { data: row.data }, from: 'trash' };
+        }
+        return null;
+    };
+
+    const quarantine = db.prepare('INSERT INTO integrity_quarantine (source_table, record_key, data, problem, quarantined_at) VALUES (?, ?, ?, ?, ?)');
+
+    CHECKS.filter(check => tableExists(db, check.table)).forEach((check) => {
+        const rows = db.prepare(`SELECT ${check.key} AS k, ${check.columns.join(', ')} FROM ${check.table}`).all();
+        summary.checked += rows.length;
+        rows.forEach((row) => {
+            const problem = rowProblem(row, check.columns);
+            if (!problem) return;
+            const entry = { table: check.table, key: row.k, problem };
+            summary.problems.push(`${check.table}/${row.k}: ${problem}`);
+            db.transaction(() => {
+                quarantine.run(check.table, String(row.k), JSON.stringify(Object.fromEntries(check.columns.map(c => [c, row[c]]))), problem, Date.now());
+                const copy = findCopy(check, row.k);
+                const assign = (values) => db.prepare(`UPDATE ${check.table} SET ${Object.keys(values).map(c => `${c} = ?`).join(', ')} WHERE ${check.key} = ?`)
+                    .run(...Object.values(values), row.k);
+                if (copy) {
+                    assign(Object.fromEntries(check.columns.map(c => [c, copy.row[c]])));
+                    summary.repaired.push({ ...entry, from: copy.from });
+                } else if (check.reset) {
+                    assign(Object.fromEntries(check.columns.filter(c => jsonProblem(row[c])).map(c => [c, check.reset[c]])));
+                    summary.reset.push(entry);
+                } else {
+                    db.prepare(`DELETE FROM ${check.table} WHERE ${check.key} = ?`).run(row.k);
+                    summary.removed.push(entry);
+                }
+            })();
+        });
+    });
+
+    backups.filter(Boolean).forEach((backup) => { try { backup.db.close(); } catch (e) {} });
+    const sqliteBad = summary.sqlite !== 'ok' && summary.sqlite !== 'reindexed';
+    summary.status = sqliteBad ? 'fail' : summary.removed.length || summary.reset.length ? 'warn' : summary.problems.length ? 'repaired' : 'ok';
+    summary.ms = Date.now() - started;
+    return summary;
+};
+
+module.exports = { initializeQuarantineTable, runIntegrityScan };
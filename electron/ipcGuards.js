@@ -0,0 +1,110 @@
+
+const { estimateSize } = require('./perfMetrics');
+const { failure } = require('./appErrors');
+
+// --- IPC GUARDS ---
+// A renderer bug (a save on every keystroke, a runaway loop, a cyclic or
+// absurdly nested object) shouldn't be able to stall the main process. Every
+// command is checked before its handler runs:
+//
+//   size   estimated argument bytes against the channel's limit (DEFAULT_MAX_BYTES
+//          unless PAYLOAD_LIMITS says otherwise)
+//   shape  nesting deeper than MAX_DEPTH is refused (it's a bug, and recursive
+//          code further down would overflow the stack)
+//   rate   a token bucket per window and channel: RATE_LIMITS for the chatty
+//          commands, DEFAULT_RATE for the rest
+//
+// A refused call answers { success: false, code, error, limit, retryAfterMs? }
+// with code PAYLOAD_TOO_LARGE, PAYLOAD_TOO_DEEP or RATE_LIMITED, so the
+// renderer can tell a guard from a handler failure and retry a save later.
+
+const DEFAULT_MAX_BYTES = 16 * 1024 * 1024;
+const MAX_DEPTH = 64;
+const DEPTH_WALK_LIMIT = 20000; // nodes visited by the depth check before it stops looking
+
+// Channel -> max argument bytes
+const PAYLOAD_LIMITS = {
+    'drawings:save-state': 64 * 1024 * 1024,
+    'drawings:import-tradingview': 64 * 1024 * 1024,
+    'attachments:paste-image': 32 * 1024 * 1024,
+    'print:document': 32 * 1024 * 1024,
+    'drawings:patch-state': 8 * 1024 * 1024,
+    'notes:bulk-update': 4 * 1024 * 1024,
+    'trades:save': 1024 * 1024,
+    'alerts:save': 256 * 1024,
+    'alerts:validate': 256 * 1024,
+    'symbols:search': 4 * 1024,
+    'search:global': 4 * 1024
+};
+
+// { perSecond, burst }: sustained rate and how many calls may arrive at once
+const DEFAULT_RATE = { perSecond: 200, burst: 400 };
+const RATE_LIMITS = {
+    'drawings:save-state': { perSecond: 10, burst: 20 },
+    'drawings:patch-state': { perSecond: 60, burst: 120 },
+    'notes:bulk-update': { perSecond: 20, burst: 40 },
+    'trades:save': { perSecond: 20, burst: 40 },
+    'symbols:search': { perSecond: 20, burst: 40 },
+    'search:global': { perSecond: 20, burst: 40 },
+    'alerts:save': { perSecond: 10, burst: 20 },
+    'settings:export': { perSecond: 1, burst: 3 },
+    'diagnostics:create-support-bundle': { perSecond: 0.2, burst: 2 }
+};
+
+// Whether a value nests deeper than maxDepth (within DEPTH_WALK_LIMIT nodes)
+const tooDeep = (value, maxDepth = MAX_DEPTH) => {
+    const stack = [[value, 0]];
+    let visited = 0;
+    while (stack.length && ++visited <= DEPTH_WALK_LIMIT) {
+        const [v, depth] = stack.pop();
+        if (v == null || typeof v !== 'object' || Buffer.isBuffer(v) || ArrayBuffer.isView(v)) continue;
+        if (depth > maxDepth) return true;
+        if (Array.isArray(v)) for (let i = 0; i < v.length; i++) stack.push([v[i], depth + 1]);
+        else for (const key in v) stack.push([v[key], depth + 1]);
+    }
+    return false;
+};
+
+/**
+ * Checks one call; returns null when it may run, or the answer to send back.
+ * buckets: Map of sender|channel -> { tokens, at }.
+ */
+const checkCall = (buckets, senderId, channel, args, now = Date.now()) => {
+    const limit = PAYLOAD_LIMITS[channel] || DEFAULT_MAX_BYTES;
+    const bytes = estimateSize(args);
+    if (bytes > limit) return failure('PAYLOAD_TOO_LARGE', `${channel}: payload of ~${bytes} bytes is over the ${limit} byte limit`, { limit, bytes });
+    if (tooDeep(args, MAX_DEPTH + 1)) return failure('PAYLOAD_TOO_DEEP', `${channel}: payload nests deeper than ${MAX_DEPTH} levels`, { limit: MAX_DEPTH });
+
+    const rate = RATE_LIMITS[channel] || DEFAULT_RATE;
+    const key = `${senderId}|${channel}`;
+    const bucket = buckets.get(key) || { tokens: rate.burst, at: now };
+    bucket.tokens = Math.min(rate.burst, bucket.tokens + ((now - bucket.at) / 1000) * rate.perSecond);
+    bucket.at = now;
+    buckets.set(key, bucket);
+    if (bucket.tokens < 1) {
+        const retryAfterMs = Math.ceil(((1 - bucket.tokens) / rate.perSecond) * 1000);
+        return failure('RATE_LIMITED', `${channel}: more than ${rate.perSecond} calls per second`, { limit: rate.perSecond, retryAfterMs });
+    }
+    bucket.tokens -= 1;
+    return null;
+};
+
+/**
+ * Wraps ipcMain.handle like instrumentIpc does. onReject(channel, answer) is
+ * called for every refused call (for logging).
+ */
+const installIpcGuards = (ipcMain, { onReject = () => {} } = {}) => {
+    const buckets = new Map();
+    const originalHandle = ipcMain.handle.bind(ipcMain);
+    ipcMain.handle = (channel, handler) => originalHandle(channel, async (event, ...args) => {
+        const senderId = event && event.sender ? event.sender.id : 0;
+        const refused = checkCall(buckets, senderId, channel, args);
+        if (!refused) return handler(event, ...args);
+        onReject(channel, refused);
+        return refused;
+    });
+    // Buckets of closed windows
+    return { forgetSender: (senderId) => Array.from(buckets.keys()).filter(k => k.startsWith(`${senderId}|`)).forEach(k => buckets.delete(k)) };
+};
+
+module.exports = { DEFAULT_MAX_BYTES, MAX_DEPTH, PAYLOAD_LIMITS, RATE_LIMITS, DEFAULT_RATE, tooDeep, checkCall, installIpcGuards };
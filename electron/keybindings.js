@@ -0,0 +1,80 @@
+
+// --- KEYBINDINGS ---
+// Action -> shortcut map. Defaults live here; the user's changes are stored as
+// overrides (setting 'keybindings') so new actions pick up their defaults on
+// upgrade. Shortcuts use Electron accelerator syntax. Actions flagged `global`
+// are registered with globalShortcut and work while the app is in the background.
+
+const ACTIONS = {
+    'search.open': { title: 'Open search palette', accelerator: 'CommandOrControl+K' },
+    'devtools.toggle': { title: 'Toggle developer tools', accelerator: 'CommandOrControl+D' },
+    'tool.cancel': { title: 'Cancel tool / deselect', accelerator: 'Escape' },
+    'drawings.lockAll': { title: 'Lock all drawings', accelerator: 'CommandOrControl+Shift+L' },
+    'drawings.hideAll': { title: 'Hide all drawings', accelerator: 'CommandOrControl+Shift+H' },
+    'chart.undo': { title: 'Undo', accelerator: 'CommandOrControl+Z' },
+    'chart.redo': { title: 'Redo', accelerator: 'CommandOrControl+Shift+Z' },
+    'note.quick': { title: 'Quick note', accelerator: 'CommandOrControl+Alt+N', global: true },
+    'feeds.togglePause': { title: 'Pause / resume live feeds', accelerator: 'CommandOrControl+Alt+P', global: true }
+};
+
+const MODIFIER_ORDER = ['CommandOrControl', 'Command', 'Control', 'Alt', 'Option', 'AltGr', 'Shift', 'Super', 'Meta'];
+const MODIFIER_ALIASES = { cmdorctrl: 'CommandOrControl', commandorcontrol: 'CommandOrControl', ctrl: 'Control', control: 'Control', cmd: 'Command', command: 'Command', alt: 'Alt', option: 'Option', altgr: 'AltGr', shift: 'Shift', super: 'Super', meta: 'Meta' };
+const NAMED_KEYS = ['Escape', 'Esc', 'Enter', 'Return', 'Space', 'Tab', 'Backspace', 'Delete', 'Insert', 'Home', 'End', 'PageUp', 'PageDown', 'Up', 'Down', 'Left', 'Right', 'Plus'];
+
+/**
+ * Canonical form ("shift+ctrl+k" -> "Control+Shift+K") so equal shortcuts
+ * compare equal. Throws on malformed input.
+ */
+const normalizeAccelerator = (accelerator) => {
+    const parts = String(accelerator || '').split('+').map(p => p.trim()).filter(Boolean);
+    if (parts.length === 0) throw new Error('Shortcut is empty');
+    const modifiers = new Set();
+    let key = null;
+    parts.forEach((part) => {
+        const mod = MODIFIER_ALIASES[part.toLowerCase()];
+        if (mod) { modifiers.add(mod); return; }
+        if (key) throw new Error(`Shortcut has more than one key: ${accelerator}`);
+        const named = NAMED_KEYS.find(k => k.toLowerCase() === part.toLowerCase());
+        if (named) key = named === 'Esc' ? 'Escape' : named === 'Return' ? 'Enter' : named;
+        else if (/^F([1-9]|1\d|2[0-4])$/i.test(part)) key = part.toUpperCase();
+        else if (part.length === 1) key = part.toUpperCase();
+        else throw new Error(`Unknown key "${part}" in ${accelerator}`);
+    });
+    if (!key) throw new Error(`Shortcut needs a non-modifier key: ${accelerator}`);
+    return [...MODIFIER_ORDER.filter(m => modifiers.has(m)), key].join('+');
+};
+
+// CommandOrControl resolves per platform, so "Control+Z" and "CommandOrControl+Z" clash on Windows/Linux
+const comparisonKey = (accelerator) => {
+    const platformMod = process.platform === 'darwin' ? 'Command' : 'Control';
+    const parts = normalizeAccelerator(accelerator).split('+').map(p => (p === 'CommandOrControl' ? platformMod : p));
+    const key = parts.pop();
+    return [...Array.from(new Set(parts)).sort(), key].join('+');
+};
+
+// Effective bindings: [{ action, title, accelerator|null, global, isDefault }]
+const resolveKeybindings = (overrides = {}) => Object.entries(ACTIONS).map(([action, def]) => {
+    const custom = Object.prototype.hasOwnProperty.call(overrides, action);
+    return { action, title: def.title, accelerator: custom ? overrides[action] : def.accelerator, global: !!def.global, isDefault: !custom };
+});
+
+/**
+ * Returns { overrides } with the change applied, or { conflict } naming the
+ * action that already uses the shortcut. `accelerator` null unbinds the action.
+ */
+const updateKeybinding = (overrides, action, accelerator) => {
+    if (!ACTIONS[action]) throw new Error(`Unknown action: ${action}`);
+    const next = { ...overrides };
+    if (accelerator == null) {
+        next[action] = null;
+        return { overrides: next };
+    }
+    const normalized = normalizeAccelerator(accelerator);
+    const clash = resolveKeybindings(overrides).find(b => b.action !== action && b.accelerator && comparisonKey(b.accelerator) === comparisonKey(normalized));
+    if (clash) return { conflict: { action: clash.action, title: clash.title, accelerator: normalized } };
+    if (normalized === normalizeAccelerator(ACTIONS[action].accelerator)) delete next[action];
+    else next[action] = normalized;
+    return { overrides: next };
+};
+
+module.exports = { ACTIONS, normalizeAccelerator, resolveKeybindings, updateKeybinding };
@@ -0,0 +1,187 @@
+
+const crypto = require('crypto');
+const fs = require('fs');
+const path = require('path');
+const { buildXlsx } = require('./xlsxWriter');
+
+// --- LIVE EXPORT ---
+// Keeps a CSV or XLSX file current for spreadsheets that read from it (Excel
+// data connections, linked workbooks). Each export rewrites its file every
+// intervalSeconds from its sources:
+//
+//   { kind: 'series', datasetId, bars? }      last `bars` bars (default 100), newest last,
+//                                             including the forming live bar
+//   { kind: 'positions', accountId? }         the paper account's open positions
+//
+// A CSV holds one source; an XLSX gets one sheet per source. The file is
+// written to a temp file next to it and renamed over it, so a reader never
+// sees half a file. Excel locks a workbook it has open (EBUSY / EPERM /
+// EACCES on Windows): the write is retried a few times with backoff and
+// otherwise left for the next interval, with the export reported as locked.
+// Unchanged content isn't rewritten, so Excel doesn't reload for nothing.
+
+const FORMATS = ['csv', 'xlsx'];
+const SOURCE_KINDS = ['series', 'positions'];
+const MIN_INTERVAL_SECONDS = 1;
+const DEFAULT_INTERVAL_SECONDS = 5;
+const MAX_EXPORTS = 20;
+const MAX_SERIES_BARS = 10000;
+const LOCK_CODES = ['EBUSY', 'EPERM', 'EACCES'];
+const LOCK_RETRY_MS = [100, 250, 500];
+
+const wait = ms => new Promise(resolve => setTimeout(resolve, ms));
+
+/**
+ * def: { id?, name, filePath, format?, intervalSeconds?, enabled?, sources } -> normalized definition
+ */
+const normalizeExport = (def = {}) => {
+    const name = String(def.name || '').trim();
+    if (!name) throw new Error('Export name is required');
+    const filePath = String(def.filePath || '');
+    if (!path.isAbsolute(filePath)) throw new Error('filePath must be an absolute path');
+    const format = def.format || (path.extname(filePath).toLowerCase() === '.xlsx' ? 'xlsx' : 'csv');
+    if (!FORMATS.includes(format)) throw new Error(`format must be one of ${FORMATS.join(', ')}`);
+    const intervalSeconds = def.intervalSeconds == null ? DEFAULT_INTERVAL_SECONDS : Number(def.intervalSeconds);
+    if (!Number.isFinite(intervalSeconds) || intervalSeconds < MIN_INTERVAL_SECONDS) throw new Error(`intervalSeconds must be at least ${MIN_INTERVAL_SECONDS}`);
+    if (!Array.isArray(def.sources) || !def.sources.length) throw new Error('An export needs at least one source');
+    if (format === 'csv' && def.sources.length > 1) throw new Error('A CSV export holds one source; use XLSX for several');
+    const sources = def.sources.map((source, i) => {
+        if (!source || !SOURCE_KINDS.includes(source.kind)) throw new Error(`sources[${i}].kind must be one of ${SOURCE_KINDS.join(', ')}`);
+        if (source.kind === 'series') {
+            if (!source.datasetId) throw new Error(`sources[${i}].datasetId is required`);
+            const bars = source.bars == null ? 100 : Math.round(Number(source.bars));
+            if (!(bars >= 1 && bars <= MAX_SERIES_BARS)) throw new Error(`sources[${i}].bars must be between 1 and ${MAX_SERIES_BARS}`);
+            return { kind: 'series', datasetId: String(source.datasetId), bars, name: source.name || String(source.datasetId) };
+        }
+        return { kind: 'positions', accountId: source.accountId || null, name: source.name || 'Positions' };
+    });
+    return { id: def.id || crypto.randomUUID(), name, filePath, format, intervalSeconds, enabled: def.enabled !== false, sources };
+};
+
+const csvValue = (value) => {
+    if (value == null) return '';
+    const text = value instanceof Date ? value.toISOString() : String(value);
+    return /[",\r\n]/.test(text) ? `"${text.replace(/"/g, '""')}"` : text;
+};
+
+const renderExport = (def, tables) => (def.format === 'xlsx'
+    ? buildXlsx(tables)
+    : Buffer.from(`${[tables[0].columns, ...tables[0].rows].map(row => row.map(csvValue).join(',')).join('\r\n')}\r\n`, 'utf8'));
+
+// Temp file + rename, retried while the target is locked; returns { locked }
+const writeReplacing = async (filePath, data) => {
+    const temp = path.join(path.dirname(filePath), `.${path.basename(filePath)}.${process.pid}.tmp`);
+    fs.writeFileSync(temp, data);
+    for (let attempt = 0; ; attempt++) {
+        try {
+            fs.renameSync(temp, filePath);
+            return { locked: false };
+        } catch (err) {
+            if (!LOCK_CODES.includes(err.code)) {
+                fs.rmSync(temp, { force: true });
+                throw err;
+            }
+            if (attempt >= LOCK_RETRY_MS.length) {
+                fs.rmSync(temp, { force: true });
+                return { locked: true };
+            }
+            await wait(LOCK_RETRY_MS[attempt]);
+        }
+    }
+};
+
+/**
+ * buildTable(source) -> { name, columns, rows } for one source.
+ * onLog(level, message, data) gets lock and failure events.
+ */
+const createLiveExportService = ({ buildTable, onLog = () => {} }) => {
+    const exports = new Map(); // id -> { def, timer, status }
+
+    const freshStatus = () => ({ running: false, writes: 0, lastWriteAt: null, lastCheckAt: null, lockedSince: null, lockedSkips: 0, lastError: null, hash: null });
+
+    const publicStatus = ({ def, status }) => {
+        const { hash, ...rest } = status;
+        return { ...def, status: { ...rest, locked: status.lockedSince != null } };
+    };
+
+    // One pass for an export; `force` writes even when nothing changed
+    const runOnce = async (entry, force = false) => {
+        const { def, status } = entry;
+        if (status.running) return publicStatus(entry);
+        status.running = true;
+        status.lastCheckAt = Date.now();
+        try {
+            const tables = def.sources.map(buildTable);
+            const data = renderExport(def, tables);
+            // XLSX zips carry a timestamp, so compare the tables instead of the bytes
+            const hash = crypto.createHash('sha1').update(def.format === 'xlsx' ? JSON.stringify(tables) : data).digest('hex');
+            if (!force && hash === status.hash && fs.existsSync(def.filePath)) return publicStatus(entry);
+            const { locked } = await writeReplacing(def.filePath, data);
+            if (locked) {
+                if (status.lockedSince == null) {
+                    status.lockedSince = Date.now();
+                    onLog('WARN', 'LIVE_EXPORT_LOCKED', { id: def.id, filePath: def.filePath });
+                }
+                status.lockedSkips++;
+            } else {
+                status.hash = hash;
+                status.writes++;
+                status.lastWriteAt = Date.now();
+                status.lockedSince = null;
+            }
+            status.lastError = null;
+        } catch (err) {
+            if (status.lastError !== err.message) onLog('ERROR', 'LIVE_EXPORT_FAILED', { id: def.id, filePath: def.filePath, error: err.message });
+            status.lastError = err.message;
+        } finally {
+            status.running = false;
+        }
+        return publicStatus(entry);
+    };
+
+    const arm = (entry) => {
+        clearTimeout(entry.timer);
+        entry.timer = null;
+        if (!entry.def.enabled) return;
+        entry.timer = setTimeout(async () => {
+            await runOnce(entry);
+            if (exports.get(entry.def.id) === entry) arm(entry);
+        }, entry.def.intervalSeconds * 1000);
+    };
+
+    // Replaces the running set; status is kept for exports whose definition is unchanged
+    const setExports = (defs) => {
+        if (defs.length > MAX_EXPORTS) throw new Error(`At most ${MAX_EXPORTS} live exports`);
+        const next = new Map();
+        defs.forEach((def) => {
+            const previous = exports.get(def.id);
+            const keepStatus = previous && JSON.stringify(previous.def) === JSON.stringify(def);
+            if (previous) clearTimeout(previous.timer);
+            next.set(def.id, { def, timer: null, status: keepStatus ? previous.status : freshStatus() });
+        });
+        exports.forEach((entry, id) => { if (!next.has(id)) clearTimeout(entry.timer); });
+        exports.clear();
+        next.forEach((entry, id) => {
+            exports.set(id, entry);
+            arm(entry);
+            if (entry.def.enabled) runOnce(entry);
+        });
+    };
+
+    const runNow = (id) => {
+        const entry = exports.get(id);
+        if (!entry) throw new Error(`Live export not found: ${id}`);
+        return runOnce(entry, true);
+    };
+
+    const list = () => Array.from(exports.values()).map(publicStatus);
+
+    const stop = () => {
+        exports.forEach(entry => clearTimeout(entry.timer));
+        exports.clear();
+    };
+
+    return { setExports, runNow, list, stop };
+};
+
+module.exports = { FORMATS, SOURCE_KINDS, normalizeExport, renderExport, createLiveExportService };
@@ -0,0 +1,70 @@
+
+const { EventEmitter } = require('events');
+const { TIMEFRAME_MS } = require('./datasets');
+
+// --- LIVE FEED PIPELINE ---
+// Providers publish bars here; main.js forwards them to every window and any
+// backend consumer (recording, alerts) subscribes to the same emitter.
+//
+// Events:
+//   'bar'    { provider, symbol, timeframe, bar, isClosed }
+//   'quote'  { provider, symbol, bid, ask, bidSize, askSize, timestamp }
+//   'trade'  { provider, symbol, price, size, timestamp }
+//   'depth'  { provider, symbol, type: 'snapshot' | 'update', bids, asks, sequence?, firstSequence?, timestamp }
+//   'status' { provider, status, message?, timestamp }
+
+const liveFeed = new EventEmitter();
+liveFeed.setMaxListeners(50);
+
+const publishBar = (provider, symbol, timeframe, bar, isClosed = false) => {
+    liveFeed.emit('bar', { provider, symbol, timeframe, bar, isClosed });
+};
+
+const publishQuote = (provider, symbol, quote) => {
+    liveFeed.emit('quote', { provider, symbol, ...quote });
+};
+
+const publishTrade = (provider, symbol, trade) => {
+    liveFeed.emit('trade', { provider, symbol, ...trade });
+};
+
+// Level 2 changes; orderBook.js keeps the books
+const publishDepth = (provider, symbol, depth) => {
+    liveFeed.emit('depth', { provider, symbol, ...depth });
+};
+
+const publishStatus = (provider, status, message = null) => {
+    liveFeed.emit('status', { provider, status, message, timestamp: Date.now() });
+};
+
+/**
+ * Rolls small bars (e.g. 5s real-time bars, trades) up into a target timeframe.
+ * `onBar(bar, isClosed)` fires for every update of the forming bar and once
+ * more with isClosed=true when a bar from the next bucket arrives.
+ */
+const createBarAggregator = (timeframe, onBar) => {
+    const bucketMs = TIMEFRAME_MS[timeframe];
+    if (!bucketMs) throw new Error(`Unsupported timeframe for live aggregation: ${timeframe}`);
+    let current = null;
+
+    return (tick) => {
+        const bucket = Math.floor(tick.timestamp / bucketMs) * bucketMs;
+        if (current && bucket > current.timestamp) {
+            onBar(current, true);
+            current = null;
+        }
+        if (!current) {
+            current = { timestamp: bucket, open: tick.open, high: tick.high, low: tick.low, close: tick.close, volume: tick.volume || 0 };
+        } else if (bucket === current.timestamp) {
+            current.high = Math.max(current.high, tick.high);
+            current.low = Math.min(current.low, tick.low);
+            current.close = tick.close;
+            current.volume += tick.volume || 0;
+        } else {
+            return; // late tick for an already closed bucket
+        }
+        onBar({ ...current }, false);
+    };
+};
+
+module.exports = { liveFeed, publishBar, publishQuote, publishTrade, publishDepth, publishStatus, createBarAggregator };
@@ -1,13 +1,191 @@
 
-const { app, BrowserWindow, ipcMain, dialog } = require('electron');
+const { app, BrowserWindow, ipcMain, dialog, powerMonitor, globalShortcut, Notification, clipboard, protocol, desktopCapturer, screen, crashReporter, nativeTheme, systemPreferences, session } = require('electron');
 const { Worker } = require('worker_threads');
+const { execFile } = require('child_process');
+const { DEFAULT_PROFILE, parseProfileArg, stripProfileArg, createProfileStore } = require('./profiles');
+const { READ_ONLY_FLAG, installReadOnlyGuard } = require('./readOnlyMode');
+const { installIpcGuards } = require('./ipcGuards');
+const { AppError, errorResult, failure, installErrorCodes } = require('./appErrors');
+const { createWorkspaceLock } = require('./workspaceLock');
+const { parseTestModeArgs, createTestDataDir, testDataPaths, createTestClock, installClock, trackChannels, isExternalUrl } = require('./testMode');
 
 // 1. INCREASE HEAP TO 500MB (Phase 1: Memory Power-Up)
 app.commandLine.appendSwitch('js-flags', '--max-old-space-size=500');
 
+// --test-mode: throwaway userData, frozen clock, mock providers (see testMode.js).
+// Runs first so nothing below ever sees the real workspace or the real time.
+const testMode = parseTestModeArgs(process.argv.slice(1));
+const testClock = testMode ? createTestClock(testMode.startTime) : null;
+const listTestChannels = testMode ? trackChannels(ipcMain) : null;
+if (testMode) {
+    testMode.dataDir = createTestDataDir(testMode.dataDir);
+    Object.entries(testDataPaths(testMode.dataDir)).forEach(([name, dir]) => app.setPath(name, dir));
+    installClock(testClock);
+}
+
+// Profiles pick the userData directory, so this runs before anything reads it (see profiles.js).
+// A --read-only launch leaves the profiles config alone: an unknown --profile
+// isn't created and the last-used profile isn't updated.
+const profiles = createProfileStore(app.getPath('userData'));
+const startedReadOnly = process.argv.includes(READ_ONLY_FLAG);
+const requestedProfile = parseProfileArg(process.argv.slice(1));
+const activeProfile = (requestedProfile && (profiles.resolve(requestedProfile) || (!startedReadOnly && profiles.create(requestedProfile))))
+    || profiles.resolve(profiles.getConfig().lastUsed) || DEFAULT_PROFILE;
+app.setPath('userData', profiles.dirFor(activeProfile.id));
+if (!startedReadOnly) profiles.setLastUsed(activeProfile.id);
+
+// Native crashes are captured as minidumps locally; crashReports.js decides what gets sent
+crashReporter.start({ uploadToServer: false });
+
 const path = require('path');
 const fs = require('fs');
+const os = require('os');
+const crypto = require('crypto');
 const Database = require('better-sqlite3');
+const { convertTradingViewLayout } = require('./tradingViewImport');
+const { exportChartState } = require('./chartStateExport');
+const { validateChartState } = require('./chartStateSchema');
+const { applyChartPatch, pointer, lockOps, groupOps } = require('./chartStatePatch');
+const { buildWatchlistDocument, parseWatchlistFile } = require('./watchlistFiles');
+const { readHstHeader, periodToTimeframe, HST_HEADER_SIZE } = require('./metaTrader');
+const { TIMEFRAME_MS, initializeDatasetTables, registerDataset, listDatasets, findDatasetsBySymbol, insertBars, getRowsPage, getDataset, datasetId, parseDatasetId } = require('./datasets');
+const { liveFeed, publishBar } = require('./liveFeed');
+const { networkEvents, getHealth: getNetworkHealth, resetBreakers } = require('./network');
+const { configureProxy, normalizeProxyConfig, testProxy, agentFor, setNetworkGuard } = require('./proxy');
+const { configureTlsPolicy, getTlsPolicy, inspectCertificates } = require('./tlsPolicy');
+const { initializeSecretsTable, setSecret, getSecret, deleteSecret, listSecretKeys, setSecretMeta, recordValidation, expiringSecrets, createSecretScope, scrubSecrets } = require('./secrets');
+const { plugin: mockProviderPlugin } = require('./providers/mock');
+const { BUILTIN_PLUGINS, loadProviderPlugins, getProvider, getProviderDescriptor, listProviders, normalizeProviderSettings, resolveProviderSettings } = require('./providers');
+const { fredSymbol } = require('./providers/fred');
+const { initializeNewsTables, createNewsService } = require('./news');
+const { initializeAlertTables, createAlertEngine } = require('./alerts');
+const { postJson, validateWebhookUrl } = require('./webhooks');
+const { defaultSocketPath, createCompanionServer } = require('./companionIpc');
+const { DEFAULT_PORT: SIGNAL_INBOX_PORT, initializeSignalInboxTable, hashToken, generateToken, recordSignal, listSignals, markSignalsRead, deleteSignals, createSignalInbox } = require('./signalInbox');
+const { CHANNELS, createNotifiers, formatAlertMessage } = require('./notifiers');
+const { createAudioService } = require('./audio');
+const { renderInkSvg, renderNoteInkSvg, rasterizeSvg } = require('./inkExport');
+const { globalSearch } = require('./search');
+const { initializeSwitchUsageTable, createQuickSwitch } = require('./quickSwitch');
+const { PLUGIN_SCHEME, CAPABILITIES: PLUGIN_CAPABILITIES, WRITE_COMMANDS: PLUGIN_WRITE_COMMANDS, initializePluginStorageTable, discoverPanelPlugins, readPanelFile, createPluginCommands, invokePluginCommand, installPluginIpcGuard } = require('./pluginSandbox');
+const { createSymbolSearch } = require('./symbolSearch');
+const { normalizeClipboardConfig, createClipboardWatcher } = require('./clipboardWatcher');
+const { createDepthService } = require('./orderBook');
+const { ORDER_FLOW_DEFAULTS, initializeTradePrintTable, createTradeRecorder, readPrints, computeDeltaBars, computeFootprint } = require('./orderFlow');
+const { initializeAnnotationIndexTable, indexChartState, rebuildAnnotationIndex, listChartAnnotations, searchChartAnnotations } = require('./annotationIndex');
+const { initializeDossierTable, getDossier, updateDossier, deleteDossier, listDossiers } = require('./symbolDossier');
+const { initializeDatasetAnnotationTable, createDatasetAnnotation, updateDatasetAnnotation, deleteDatasetAnnotation, getDatasetAnnotation, listDatasetAnnotations, projectAnnotations } = require('./datasetAnnotations');
+const { generateSyntheticBars } = require('./synthetic');
+const { buildContinuous, stitchContinuous, rebuildContinuous } = require('./continuousFutures');
+const { buildMerged, storeMerged, rebuildMerged } = require('./datasetMerge');
+const { planStatementImport } = require('./brokerStatements');
+const { initializeBarEditTable, editBar, deleteBar, revertBarEdit, listBarEdits, dependentDatasets } = require('./barEditor');
+const { instrumentIpc, getPerfMetrics, resetPerfMetrics } = require('./perfMetrics');
+const { initializeUsageTable, createUsageAnalytics } = require('./usageAnalytics');
+const { runDiagnostics, writeSupportBundle, findOrphanedChartStates } = require('./diagnostics');
+const { initializeChartTrashTable, moveChartStatesToTrash, listChartTrash, restoreChartStates, emptyChartTrash } = require('./chartStateTrash');
+const { getStorageReport, purgeCaches, deleteUnusedSounds, compactDatabase, freePageRatio } = require('./storage');
+const { KINDS: COMPUTE_CACHE_KINDS, codeVersion, createComputeCache } = require('./computeCache');
+const { createComputePool, defaultThreadCount } = require('./computePool');
+const { setFastAggregate } = require('./fastAggregate');
+const { INDICATORS } = require('./indicators');
+const { createIndicatorStreams } = require('./indicatorStreams');
+const { createCrosshairService } = require('./crosshairContext');
+const { initializeThumbnailTable, decodeThumbnail, saveThumbnail, getThumbnail, deleteThumbnail } = require('./chartThumbnails');
+const { collectDatasetSlices, encodeShare, decodeShare } = require('./chartShare');
+const { createExternalChangeMonitor } = require('./externalChanges');
+const { initializeTemplateTable, saveDrawingTemplate, listDrawingTemplates, getDrawingTemplate, deleteDrawingTemplate, applyDrawingTemplate } = require('./drawingTemplates');
+const { initializeThemeTable, listThemes, getTheme, saveTheme, deleteTheme, toChartConfig, exportThemeFile, readThemeFile } = require('./themes');
+const { ACTIONS, resolveKeybindings, updateKeybinding } = require('./keybindings');
+const { t, getLocaleStrings, setLanguage, getLanguage, resolveLanguage, listLanguages } = require('./i18n');
+const { createActionRegistry } = require('./actionRegistry');
+const { initializeJournalTable, diffDrawings, recordChartEdit, undoChartEdit, redoChartEdit, journalStatus, listChartJournal, clearChartJournal } = require('./editJournal');
+const { createSyncBus, drawingChanges } = require('./syncBus');
+const { createPresentationController } = require('./presentation');
+const { initializeSchedulerTables, createScheduler } = require('./scheduler');
+const { createBarRecorder, createTrayController } = require('./backgroundMode');
+const { createPowerGovernor } = require('./powerGovernor');
+const { normalizeIdleConfig, createIdleMonitor } = require('./idleMonitor');
+const { createBackgroundQueue } = require('./backgroundJobs');
+const { normalizeImportLocale, isDefaultLocale, probeFile } = require('./importLocale');
+const { APPEARANCE_MODES, windowColors, createAppearanceTracker } = require('./appearance');
+const { createOnboarding } = require('./onboarding');
+const { DEMO_SYMBOL, DEMO_PREFIX, DEMO_BOARD_NAME, DEMO_DATASETS, DEMO_WATCHLIST, buildDemoChartState, buildDemoNotes, buildDemoAlerts } = require('./demoContent');
+const { SETTINGS_SECTIONS, buildSettingsProfile, writeSettingsFile, readSettingsFile, planSettingsImport, applySettingsImport, publicPlan } = require('./settingsProfile');
+const { DEFAULT_DOWNLOAD_CONFIG, initializeDownloadTables, createDownloadManager } = require('./downloadManager');
+const { initializePaperTradingTables, createSimEngine } = require('./paperTrading');
+const { initializeSymbolMetaTable, getSymbolMeta, saveSymbolMeta, deleteSymbolMeta, listSymbolMeta, pointValue, positionPnl } = require('./positionSizing');
+const { collectTrades, convertTrades, getPortfolioStats } = require('./portfolioAnalytics');
+const { validateExpression } = require('./expressions');
+const { listCalendars, calendarForAsset, resolveCalendar, computeSessionStats, createSegmentClassifier, cleanSegments, computeExtendedHoursLevels } = require('./sessions');
+const { END_OF_DAY_STEPS, ROLLUP_SOURCE, runEndOfDay, formatEndOfDaySummary, rollDailyBars, rotateLogFiles } = require('./endOfDay');
+const { LEVEL_KEYS: SESSION_LEVEL_KEYS, cleanLevelKeys, computeSessionLevels, applySessionLevels } = require('./sessionLevels');
+const { checkTimeframe: checkSeasonalityTimeframe } = require('./seasonality');
+const { computeRelative } = require('./relativeStrength');
+const { initializeScannerTable, createScanner } = require('./scanner');
+const { createHeatmapService } = require('./heatmap');
+const { initializeFxTable, saveRate, deleteRate, listRates, createFxService, normalizeCurrency } = require('./fxRates');
+const { initializeCalendarTable, createEconomicCalendar } = require('./economicCalendar');
+const { buildICalendar } = require('./icalExport');
+const { createCrashReporter } = require('./crashReports');
+const { createShutdownPipeline, withTimeout } = require('./shutdown');
+const { initializeQuarantineTable, runIntegrityScan } = require('./integrityScan');
+const { SCHEME: ATTACHMENT_SCHEME, initializeAttachmentTable, saveAttachment, getAttachmentInfo, readAttachment, listAttachments, deleteAttachment, pruneAttachments } = require('./attachments');
+const { initializeNoteTemplateTable, listNoteTemplates, saveNoteTemplate, deleteNoteTemplate, createNoteFromTemplate } = require('./noteTemplates');
+const { SOURCES: EXPORT_SOURCES, initializeExportTemplateTable, listExportTemplates, getExportTemplate, saveExportTemplate, deleteExportTemplate, renderExport } = require('./exportTemplates');
+const { hasInkRecognizer, recognizeInk } = require('./inkRecognition');
+const { AUTO_ARCHIVE_DEFAULTS, initializeStickyNotesTable, listStickyNotes, searchStickyNotes, getStickyNote, bulkUpdateNotes, autoArchiveNotes, getNoteHistory, restoreNoteVersion, listBoards, createBoard, renameBoard, setBoardArchived } = require('./stickyNotes');
+const { renderPortfolioReportHtml } = require('./portfolioReport');
+const { normalizeExport, createLiveExportService } = require('./liveExport');
+const { composePrintHtml, attachmentIdsIn } = require('./printDocuments');
+const { KINDS: ACTIVITY_KINDS, initializeActivityTable, recordActivity, pruneActivity, getActivityTimeline } = require('./activityLog');
+const { priceOption, computeChainGreeks } = require('./options');
+const { registerBroker, getBroker, listBrokers } = require('./brokers');
+const { createAlpacaBroker } = require('./brokers/alpaca');
+const { createOrderManager } = require('./orderRouting');
+const { initializeWatchImportTable, normalizeRule, createWatchFolderService, listWatchImports } = require('./watchFolders');
+const { INDEX_MIN_BYTES, DEFAULT_STRIDE, initializeTimeIndexTable, fileSignature, getTimeIndex, saveTimeIndex, deleteTimeIndex, seekOffset } = require('./timeIndex');
+
+// --read-only or the runtime toggle (see readOnlyMode.js)
+let readOnly = startedReadOnly;
+
+// Usage counters stay local unless uploading is switched on (see usageAnalytics.js)
+const usageAnalytics = createUsageAnalytics({
+    getDb: () => (readOnly ? null : db),
+    loadConfig: () => readJsonSetting('usage.analytics'),
+    saveConfig: (config) => writeJsonSetting('usage.analytics', config),
+    environment: { appVersion: app.getVersion(), platform: process.platform },
+    onLog: (level, message, data) => logSystemEvent(message, data, level)
+});
+
+// Outermost layer: every { success: false } answer carries an error code (see appErrors.js)
+installErrorCodes(ipcMain);
+// Must run before any ipcMain.handle registration so every command is timed (and counted)
+instrumentIpc(ipcMain, { onCall: (channel) => { if (!channel.startsWith('usage:')) usageAnalytics.record(channel); } });
+installReadOnlyGuard(ipcMain, {
+    isEnabled: () => readOnly,
+    blocked: () => failure('READ_ONLY', t('errors.readOnly'), { readOnly: true })
+});
+// Payload size / nesting limits and per-window rate limits (see ipcGuards.js); logged at most once a minute per channel and code
+const loggedRejections = new Map();
+const ipcGuards = installIpcGuards(ipcMain, {
+    onReject: (channel, answer) => {
+        const key = `${channel}|${answer.code}`;
+        if (Date.now() - (loggedRejections.get(key) || 0) < 60000) return;
+        loggedRejections.set(key, Date.now());
+        logSystemEvent('IPC_REJECTED', { channel, code: answer.code, limit: answer.limit, bytes: answer.bytes }, 'WARN');
+    }
+});
+// Plugin panel windows reach plugin:invoke and nothing else (see pluginSandbox.js)
+const pluginWindows = new Map(); // webContents id -> { plugin, win }
+installPluginIpcGuard(ipcMain, {
+    isRestricted: senderId => pluginWindows.has(senderId),
+    onReject: (channel, senderId) => logSystemEvent('PLUGIN_IPC_REFUSED', { channel, plugin: pluginWindows.get(senderId)?.plugin.id }, 'WARN')
+});
+app.on('web-contents-created', (event, contents) => {
+    const id = contents.id;
+    contents.once('destroyed', () => ipcGuards.forgetSender(id));
+});
 
 // [TELEMETRY] Initialize Session Start Time
 const sessionStartTime = Date.now();
@@ -28,7 +206,7 @@ const logSystemEvent = (eventName, data = null, level = 'INFO') => {
         event: eventName, 
         timestamp: Date.now(),
         level,
-        data: safeIPC(data) 
+        data: safeIPC(scrubSecrets(data))
     };
     systemLogBuffer.unshift(entry);
     if (systemLogBuffer.length > MAX_SYSTEM_LOGS) systemLogBuffer.pop();
@@ -38,13 +216,43 @@ const logSystemEvent = (eventName, data = null, level = 'INFO') => {
             category: 'IPC BRIDGE',
             level,
             message: eventName,
-            data: safeIPC(data),
+            data: entry.data,
             timestamp: entry.timestamp // Pass timestamp to maintain accuracy across bridge
         });
     }
     console.log(`[SYS_EVENT] ${eventName}`);
 };
 
+// Helper: Send an event to every open window (main + popouts)
+const broadcast = (channel, payload) => {
+    const data = safeIPC(payload);
+    BrowserWindow.getAllWindows().forEach((win) => {
+        if (!win.isDestroyed() && !presentation.isDimmer(win) && !pluginWindows.has(win.webContents.id)) win.webContents.send(channel, data);
+    });
+};
+
+// Sequenced change events for keeping windows in sync (see syncBus.js)
+const syncBus = createSyncBus({ send: (envelope) => broadcast('sync:event', envelope) });
+
+// Publishes the drawing-level difference between two states of one chart
+const publishDrawingChanges = (sourceId, prevState, nextState, origin = null) => {
+    if (alertEngine) alertEngine.invalidateDrawings(sourceId);
+    if (db) {
+        try {
+            indexChartState(db, sourceId, nextState);
+        } catch (indexErr) {
+            // Retried from the stored state once the queue gets to it
+            logSystemEvent('ANNOTATION_INDEX_FAILED', { sourceId, error: indexErr.message }, 'WARN');
+            backgroundJobs.enqueue({ type: 'annotation-index', key: sourceId, priority: 'low', params: { sourceId } });
+        }
+    }
+    const changes = drawingChanges(diffDrawings(prevState, nextState));
+    const prevFolders = JSON.stringify((prevState && prevState.folders) || []);
+    const folders = nextState && JSON.stringify(nextState.folders || []) !== prevFolders ? nextState.folders || [] : undefined;
+    if (changes.length === 0 && folders === undefined) return null;
+    return syncBus.publish('drawings', { sourceId, changes, folders }, origin);
+};
+
 let mainWindow;
 let watcher = null;
 let db = null;
@@ -69,10 +277,13 @@ const setupDatabase = () => {
 
         // 2. SQLITE OPTIMIZATION (Phase 1)
         // Performance Pragmas per PRD
-        db.pragma('journal_mode = WAL');
+        // Switching journal mode rewrites the header, so a read-only session keeps the file's own
+        if (!readOnly) db.pragma('journal_mode = WAL');
         db.pragma('synchronous = NORMAL');
 
         initializeTables();
+        applyFastAggregate();
+        if (readOnly) db.pragma('query_only = ON');
     } catch (err) {
         console.error('Database initialization failed:', err);
         logSystemEvent('DB_INIT_FAILED', { error: err.message }, 'CRITICAL');
@@ -101,6 +312,40 @@ const initializeTables = () => {
             
             CREATE INDEX IF NOT EXISTS idx_market_data ON market_data (symbol, timeframe, timestamp);
         `);
+        initializeDatasetTables(db);
+        initializeSecretsTable(db);
+        initializeNewsTables(db);
+        initializeAlertTables(db);
+        initializeChartTrashTable(db);
+        initializeTimeIndexTable(db);
+        initializeThumbnailTable(db);
+        initializeTemplateTable(db);
+        initializeThemeTable(db);
+        initializeJournalTable(db);
+        initializeWatchImportTable(db);
+        initializeSchedulerTables(db);
+        initializeDownloadTables(db);
+        initializePaperTradingTables(db);
+        initializeSymbolMetaTable(db);
+        initializeFxTable(db);
+        initializeScannerTable(db);
+        initializeCalendarTable(db);
+        initializeAnnotationIndexTable(db);
+        initializeStickyNotesTable(db);
+        initializeNoteTemplateTable(db);
+        initializeAttachmentTable(db);
+        initializeUsageTable(db);
+        initializeTradePrintTable(db);
+        initializeQuarantineTable(db);
+        initializeDatasetAnnotationTable(db);
+        initializeDossierTable(db);
+        initializeSignalInboxTable(db);
+        initializeBarEditTable(db);
+        initializeSwitchUsageTable(db);
+        initializePluginStorageTable(db);
+        initializeActivityTable(db);
+        initializeExportTemplateTable(db);
+        if (!readOnly) pruneActivity(db);
         logSystemEvent('DB_TABLES_READY');
     } catch (e) {
         logSystemEvent('DB_SCHEMA_ERROR', { error: e.message }, 'CRITICAL');
@@ -126,16 +371,17 @@ const createWindow = () => {
   if (!resolvedPath) resolvedPath = path.join(__dirname, 'preload.js'); 
 
   logSystemEvent('WINDOW_CREATING');
+  const colors = currentWindowColors();
 
   mainWindow = new BrowserWindow({
     width: 1280,
     height: 800,
     minWidth: 800,
     minHeight: 600,
-    backgroundColor: '#0f172a',
+    backgroundColor: colors.background,
     title: 'Red Pill Charting',
     titleBarStyle: 'hidden',
-    titleBarOverlay: { color: '#0f172a', symbolColor: '#94a3b8', height: 30 },
+    titleBarOverlay: { color: colors.background, symbolColor: colors.symbol, height: 30 },
     webPreferences: {
       nodeIntegration: false,
       contextIsolation: true,
@@ -159,6 +405,31 @@ const createWindow = () => {
   }
   
   mainWindow.once('ready-to-show', () => logSystemEvent('WINDOW_READY'));
+
+  // Tray mode: hide instead of minimizing / closing; feeds, alerts and recording keep running
+  mainWindow.on('minimize', (event) => {
+    if (!getBackgroundConfig().minimizeToTray) return;
+    event.preventDefault();
+    hideToTray();
+  });
+  mainWindow.on('close', (event) => {
+    if (isQuitting) return;
+    event.preventDefault();
+    if (getBackgroundConfig().closeToTray) {
+      hideToTray();
+      return;
+    }
+    // Closing quits through the shutdown pipeline; on macOS the app stays, so only this window flushes
+    if (process.platform !== 'darwin') app.quit();
+    else flushRenderers([mainWindow]).finally(() => { if (mainWindow && !mainWindow.isDestroyed()) mainWindow.destroy(); });
+  });
+};
+
+const showMainWindow = () => {
+  if (!mainWindow || mainWindow.isDestroyed()) return;
+  if (mainWindow.isMinimized()) mainWindow.restore();
+  mainWindow.show();
+  mainWindow.focus();
 };
 
 const resolveAssetsPath = () => {
@@ -194,11 +465,43 @@ const runBootScan = () => {
     if (fs.existsSync(assetsPath)) scanDir(assetsPath);
     logSystemEvent('BOOT_SCAN_COMPLETE', { count: results.length });
     internalLibraryStorage = results;
+    quickSwitch.invalidate();
     return internalLibraryStorage;
 };
 
 // --- WORKER HANDLER ---
-const runIngestWorker = (filePath, symbol, timeframe) => {
+// ETA from average throughput so far; null until there is enough signal
+const withImportEta = (progress) => {
+    const { bytesProcessed, totalBytes, elapsedMs } = progress;
+    const percent = totalBytes ? Math.min(100, (bytesProcessed / totalBytes) * 100) : null;
+    const etaMs = bytesProcessed > 0 && elapsedMs > 1000 && totalBytes
+        ? Math.round((elapsedMs / bytesProcessed) * (totalBytes - bytesProcessed))
+        : null;
+    return { ...progress, percent, etaMs };
+};
+
+// fastParse.js char-code parsers in the CSV path; the setting is an escape hatch
+const fastParseEnabled = () => !db || readJsonSetting('import.fastParse') !== false;
+// Same for the fastAggregate.js range loops (session levels, heatmaps), here and in compute workers
+const fastAggregateEnabled = () => !db || readJsonSetting('compute.fastAggregate') !== false;
+const applyFastAggregate = () => setFastAggregate(fastAggregateEnabled());
+
+// How a generic CSV is read (importLocale.js): an explicit locale wins, otherwise
+// the probe's guess. A date order the probe couldn't settle keeps the historical
+// parse for dates; MetaTrader and HST files have fixed layouts and never get one.
+const resolveImportLocale = (filePath, format = 'csv', explicit = null) => {
+    if (explicit) return normalizeImportLocale(explicit);
+    if (format !== 'csv') return null;
+    try {
+        const hints = probeFile(filePath);
+        const locale = hints.ambiguous.includes('dateOrder') ? { ...hints.locale, dateOrder: 'ymd' } : hints.locale;
+        return isDefaultLocale(locale) ? null : locale;
+    } catch (e) {
+        return null;
+    }
+};
+
+const runIngestWorker = (filePath, symbol, timeframe, options = {}) => {
     return new Promise((resolve, reject) => {
         // Resolve worker path
         let workerPath = path.join(__dirname, 'ingestWorker.js');
@@ -208,10 +511,21 @@ const runIngestWorker = (filePath, symbol, timeframe) => {
         }
 
         const worker = new Worker(workerPath);
-        
+        const taskId = options.taskId || crypto.randomUUID();
+
         worker.on('message', (result) => {
-            if (result.success) resolve(result);
-            else reject(new Error(result.error));
+            if (result.type === 'progress') {
+                broadcast('import:progress', withImportEta({ ...result, symbol, timeframe, filePath }));
+                return;
+            }
+            if (result.success) {
+                broadcast('import:complete', { ...result, symbol, timeframe, filePath });
+                if (result.rowsRejected > 0) logSystemEvent('IMPORT_ROWS_REJECTED', { taskId, symbol, rejected: result.rowsRejected, sample: result.errors.slice(0, 3) }, 'WARN');
+                resolve(result);
+            } else {
+                broadcast('import:complete', { taskId, success: false, error: result.error, symbol, timeframe, filePath });
+                reject(new Error(result.error));
+            }
         });
         
         worker.on('error', reject);
@@ -225,11 +539,116 @@ const runIngestWorker = (filePath, symbol, timeframe) => {
             dbPath: dbPathGlobal,
             filePath,
             symbol,
-            timeframe
+            timeframe,
+            fastParse: fastParseEnabled(),
+            ...options,
+            locale: resolveImportLocale(filePath, options.format, options.locale),
+            taskId,
+            // A read-only session's worker can't write even if an ingest slips through
+            readOnly
+        });
+    });
+};
+
+// Direct file read (no ingest): column selection and time range are applied inside the parse loop
+const runWorkerTask = (task) => {
+    return new Promise((resolve, reject) => {
+        let workerPath = path.join(__dirname, 'ingestWorker.js');
+        if (!fs.existsSync(workerPath)) {
+             workerPath = path.join(app.getAppPath(), 'electron', 'ingestWorker.js');
+        }
+
+        const worker = new Worker(workerPath);
+        worker.on('message', (result) => {
+            worker.terminate();
+            if (result.success) resolve(result);
+            else reject(new Error(result.error));
         });
+        worker.on('error', reject);
+        worker.postMessage({ fastParse: fastParseEnabled(), ...task, locale: resolveImportLocale(task.filePath, task.format, task.locale) });
     });
 };
 
+const runReadWorker = (filePath, options = {}) => runWorkerTask({ mode: 'read', filePath, ...options });
+
+// --- TIME INDEX ---
+const indexBuildsInFlight = new Map(); // filePath -> Promise
+
+const buildTimeIndex = (filePath, format, options = {}) => {
+    if (indexBuildsInFlight.has(filePath)) return indexBuildsInFlight.get(filePath);
+    const signature = fileSignature(filePath);
+    const build = runWorkerTask({ mode: 'index', filePath, format, brokerOffset: options.brokerOffset || 0, stride: options.stride || DEFAULT_STRIDE, locale: options.locale || null })
+        .then((result) => {
+            // The file changed mid-build: the offsets can't be trusted
+            const after = fileSignature(filePath);
+            if (after.size !== signature.size || after.mtime !== signature.mtime) throw new Error('File changed while indexing');
+            saveTimeIndex(db, filePath, signature, format, result);
+            logSystemEvent('TIME_INDEX_BUILT', { file: path.basename(filePath), entries: result.entries.length, rows: result.rows, ascending: result.ascending });
+            return getTimeIndex(db, filePath);
+        })
+        .finally(() => indexBuildsInFlight.delete(filePath));
+    indexBuildsInFlight.set(filePath, build);
+    return build;
+};
+
+const isIndexableFormat = (format) => format === 'csv' || format === 'mt-csv';
+
+// Import locale hints from the head of a CSV (see importLocale.js); pass
+// `locale` (adjusted where `ambiguous` says so) to read-file to override them
+ipcMain.handle('market:probe-file', async (event, filePath) => {
+    try {
+        if (!filePath || !fs.existsSync(filePath)) return failure('NOT_FOUND', t('errors.fileNotFound'));
+        return { success: true, ...probeFile(filePath) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// options: { columns?: ('open'|'high'|'low'|'close'|'volume')[], from?: ms, to?: ms, limit?, format?, brokerOffset?, locale? }
+ipcMain.handle('market:read-file', async (event, filePath, options = {}) => {
+    try {
+        if (!filePath || !fs.existsSync(filePath)) return failure('NOT_FOUND', t('errors.fileNotFound'));
+        const format = options.format || (filePath.toLowerCase().endsWith('.hst') ? 'hst' : 'csv');
+        const indexable = !!db && isIndexableFormat(format) && fs.statSync(filePath).size >= INDEX_MIN_BYTES;
+        const index = indexable ? getTimeIndex(db, filePath) : null;
+        const startOffset = seekOffset(index && index.format === format ? index : null, options.from);
+
+        const result = await runReadWorker(filePath, { ...options, format, startOffset });
+        logSystemEvent('FILE_READ', { file: path.basename(filePath), rows: result.data.length, scanned: result.scanned, stoppedEarly: result.stoppedEarly, startOffset });
+
+        // First open of a big file: index in the background so the next range read can seek
+        if (indexable && !index) {
+            backgroundJobs.enqueue({ type: 'time-index', key: filePath, label: `Time index ${path.basename(filePath)}`, params: { filePath, format, brokerOffset: options.brokerOffset || 0, locale: options.locale || null } });
+        }
+        return { ...result, indexed: !!index };
+    } catch (err) {
+        logSystemEvent('FILE_READ_FAILED', { error: err.message }, 'ERROR');
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('market:build-time-index', async (event, filePath, options = {}) => {
+    try {
+        if (!filePath || !fs.existsSync(filePath)) return failure('NOT_FOUND', t('errors.fileNotFound'));
+        const format = options.format || 'csv';
+        if (!isIndexableFormat(format)) return failure('UNSUPPORTED', `Format ${format} is not indexable`);
+        const index = (!options.force && getTimeIndex(db, filePath)) || await buildTimeIndex(filePath, format, options);
+        const { entries, ...summary } = index;
+        return { success: true, index: { ...summary, entryCount: entries.length } };
+    } catch (err) {
+        logSystemEvent('TIME_INDEX_FAILED', { file: filePath ? path.basename(filePath) : null, error: err.message }, 'WARN');
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('market:delete-time-index', async (event, filePath) => {
+    try {
+        return { success: true, deleted: deleteTimeIndex(db, filePath) > 0 };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
 ipcMain.handle('debug:get-global-state', async () => {
     // Collect state from main process memory
     const mem = process.memoryUsage();
@@ -329,12 +748,17 @@ ipcMain.handle('file:get-details', async (event, filePath) => {
 });
 
 // --- OPTIMIZED MARKET DATA HANDLER (Sync Better-SQLite3) ---
-ipcMain.handle('market:get-data', async (event, symbol, timeframe, filePath, toTime = null, limit = 1000) => {
+// options: { segments?: ['pre' | 'regular' | 'post'] (e.g. ['regular'] for regular hours
+// only), calendar?, tagSegments? } — with either, intraday rows come back with a
+// parallel `segments` array (see SESSION SEGMENTS)
+ipcMain.handle('market:get-data', async (event, symbol, timeframe, filePath, toTime = null, limit = 1000, options = {}) => {
     try {
         if (!db) return { error: "Database not initialized" };
-        
+        const segmented = !!(options && (options.segments != null || options.tagSegments)) && TIMEFRAME_MS[timeframe] < TIMEFRAME_MS['1D'];
+
         // Define Reusable Query Function using synchronous API
         const fetchFromDb = () => {
+            if (segmented) return fetchSegmentedBars(symbol, timeframe, toTime, limit, options);
             // Mapping timestamp -> time for frontend compatibility
             let query = `
                 SELECT timestamp as time, open, high, low, close, volume 
@@ -372,6 +796,15 @@ ipcMain.handle('market:get-data', async (event, symbol, timeframe, filePath, toT
             const checkStmt = db.prepare('SELECT 1 FROM market_data WHERE symbol = ? AND timeframe = ? LIMIT 1');
             const hasData = checkStmt.get(symbol, timeframe);
 
+            // Read-only sessions never ingest: the window is parsed straight from the file
+            if (!hasData && readOnly) {
+                const format = filePath.toLowerCase().endsWith('.hst') ? 'hst' : 'csv';
+                const { data } = await runReadWorker(filePath, { format, to: toTime ? toTime - 1 : null });
+                data.sort((a, b) => a[0] - b[0]);
+                logSystemEvent('FILE_READ', { file: path.basename(filePath), rows: data.length, readOnly: true });
+                return { data: data.slice(-limit), format: 'array' };
+            }
+
             if (!hasData) {
                 logSystemEvent('WORKER_SPAWN', { symbol, filePath });
                 
@@ -380,6 +813,7 @@ ipcMain.handle('market:get-data', async (event, symbol, timeframe, filePath, toT
                     
                     // Re-fetch after worker completion
                     cachedRows = fetchFromDb();
+                    registerDataset(db, symbol, timeframe, 'csv', { filePath });
                     logSystemEvent('WORKER_COMPLETE', { symbol, rowsLoaded: cachedRows.length });
                 } catch (err) {
                     logSystemEvent('WORKER_ERROR', { error: err.message }, 'ERROR');
@@ -392,7 +826,7 @@ ipcMain.handle('market:get-data', async (event, symbol, timeframe, filePath, toT
         if (cachedRows && cachedRows.length > 0) {
             // Map to flat array [t, o, h, l, c, v]
             const flatData = cachedRows.map(r => [r.time, r.open, r.high, r.low, r.close, r.volume]);
-            return { data: flatData, format: 'array' };
+            return segmented ? { data: flatData, format: 'array', segments: cachedRows.map(r => r.segment) } : { data: flatData, format: 'array' };
         }
 
         return { data: [] };
@@ -403,118 +837,6524 @@ ipcMain.handle('market:get-data', async (event, symbol, timeframe, filePath, toT
     }
 });
 
-// --- TAIL-FIRST ACCESS ---
-ipcMain.handle('market:get-tail', async (event, filePath) => {
+// --- METATRADER HISTORY IMPORT ---
+// Accepts MT4 .hst files or MT4/MT5 CSV exports. Symbol/timeframe come from the
+// HST header when available; CSV exports need them passed in (or derived from the
+// file name, e.g. EURUSD60.csv / EURUSD_H1.csv).
+const MT_FILENAME_PERIODS = { M1: 1, M5: 5, M15: 15, M30: 30, H1: 60, H4: 240, D1: 1440, W1: 10080, MN1: 43200, MN: 43200 };
+
+const inferMetaTraderFileInfo = (filePath) => {
+    const base = path.basename(filePath, path.extname(filePath));
+    const named = /^([A-Za-z0-9.#]+?)[_\-\s]?(M1|M5|M15|M30|H1|H4|D1|W1|MN1|MN)$/i.exec(base);
+    if (named) return { symbol: named[1].toUpperCase(), timeframe: periodToTimeframe(MT_FILENAME_PERIODS[named[2].toUpperCase()]) };
+    const numeric = /^([A-Za-z.#]+?)(\d+)$/.exec(base);
+    if (numeric && periodToTimeframe(Number(numeric[2]))) return { symbol: numeric[1].toUpperCase(), timeframe: periodToTimeframe(Number(numeric[2])) };
+    return { symbol: base.toUpperCase(), timeframe: null };
+};
+
+ipcMain.handle('market:import-metatrader', async (event, filePath = null, options = {}) => {
     try {
-        const stats = fs.statSync(filePath);
-        const size = stats.size;
-        const chunkSize = 200 * 1024; // 200KB tail
-        const start = Math.max(0, size - chunkSize);
-        
-        return new Promise((resolve, reject) => {
-            fs.open(filePath, 'r', (err, fd) => {
-                if (err) return reject(err);
-                const buffer = Buffer.alloc(size - start);
-                fs.read(fd, buffer, 0, buffer.length, start, (err, bytesRead, b) => {
-                    fs.close(fd, () => {});
-                    if (err) return reject(err);
-                    const text = b.toString('utf8');
-                    resolve({ text }); 
-                });
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+
+        let target = filePath;
+        if (!target) {
+            const { canceled, filePaths } = await dialog.showOpenDialog(mainWindow, {
+                properties: ['openFile'],
+                filters: [{ name: 'MetaTrader History', extensions: ['hst', 'csv', 'txt'] }]
             });
-        });
-    } catch (e) {
-        return { error: e.message };
+            if (canceled || !filePaths.length) return { success: false, canceled: true };
+            target = filePaths[0];
+        }
+
+        const isHst = target.toLowerCase().endsWith('.hst');
+        let { symbol, timeframe } = inferMetaTraderFileInfo(target);
+
+        if (isHst) {
+            const fd = fs.openSync(target, 'r');
+            const headerBuf = Buffer.alloc(HST_HEADER_SIZE);
+            fs.readSync(fd, headerBuf, 0, HST_HEADER_SIZE, 0);
+            fs.closeSync(fd);
+            const header = readHstHeader(headerBuf);
+            if (header.symbol) symbol = header.symbol.toUpperCase();
+            timeframe = periodToTimeframe(header.period) || timeframe;
+        }
+
+        symbol = options.symbol || symbol;
+        timeframe = options.timeframe || timeframe;
+        if (!timeframe) return failure('INVALID_INPUT', 'Could not determine timeframe; pass one explicitly');
+
+        const brokerOffset = options.brokerOffset ?? 0;
+        logSystemEvent('METATRADER_IMPORT_START', { file: path.basename(target), symbol, timeframe, brokerOffset });
+        const result = await runIngestWorker(target, symbol, timeframe, { format: isHst ? 'hst' : 'mt-csv', brokerOffset, taskId: options.taskId });
+        registerDataset(db, symbol, timeframe, 'metatrader', { filePath: target, brokerOffset });
+        logSystemEvent('METATRADER_IMPORT_COMPLETE', { symbol, timeframe, count: result.count });
+
+        return { success: true, symbol, timeframe, count: result.count };
+    } catch (err) {
+        logSystemEvent('METATRADER_IMPORT_FAILED', { error: err.message }, 'ERROR');
+        return errorResult(err);
     }
 });
 
-ipcMain.handle('drawings:get-state', async (event, symbol) => {
+// --- WATCH FOLDERS ---
+// Rules live in the 'watchFolders' setting; see normalizeRule in watchFolders.js
+let watchFolderService = null;
+
+const summarizeWatchImports = ({ imported, failed }) => {
+    const rows = imported.reduce((sum, f) => sum + f.rows, 0);
+    const symbols = Array.from(new Set(imported.map(f => f.symbol)));
+    const parts = [];
+    if (imported.length) parts.push(`${imported.length} file(s), ${rows} bars (${symbols.slice(0, 5).join(', ')}${symbols.length > 5 ? ', …' : ''})`);
+    if (failed.length) parts.push(`${failed.length} failed`);
+    return parts.join('; ');
+};
+
+const startWatchFolders = () => {
+    if (!db) return [];
+    if (!watchFolderService) {
+        watchFolderService = createWatchFolderService({
+            db,
+            fallbackInfer: inferMetaTraderFileInfo,
+            importFile: async (filePath, { symbol, timeframe, format }) => {
+                const result = await runIngestWorker(filePath, symbol, timeframe, { format });
+                registerDataset(db, symbol, timeframe, 'watch-folder', { filePath });
+                return result;
+            },
+            onSummary: (summary) => {
+                const text = summarizeWatchImports(summary);
+                logSystemEvent('WATCH_IMPORT_SUMMARY', { imported: summary.imported.length, failed: summary.failed.length }, summary.failed.length ? 'WARN' : undefined);
+                broadcast('watch-folders:summary', { ...summary, text });
+                // Screen is being shared: keep the OS toast off it, the in-app summary still arrives
+                if (Notification.isSupported() && !presentation.isActive()) {
+                    new Notification({ title: 'Watch folder import', body: text }).show();
+                }
+            },
+            onError: (target, err) => logSystemEvent('WATCH_IMPORT_FAILED', { file: path.basename(target), error: err.message }, 'ERROR')
+        });
+    }
+    const status = watchFolderService.start(readJsonSetting('watchFolders') || []);
+    status.filter(s => s.error).forEach(s => logSystemEvent('WATCH_FOLDER_UNAVAILABLE', s, 'WARN'));
+    return status;
+};
+
+ipcMain.handle('watch-folders:get-config', async () => {
     try {
-        const stmt = db.prepare('SELECT data FROM drawings WHERE symbol = ?');
-        const row = stmt.get(symbol);
-        return row ? JSON.parse(row.data) : null;
-    } catch (e) {
-        return null;
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, rules: readJsonSetting('watchFolders') || [], ...(watchFolderService ? watchFolderService.status() : {}) };
+    } catch (err) {
+        return errorResult(err);
     }
 });
 
-ipcMain.handle('drawings:save-state', async (event, symbol, data) => {
+ipcMain.handle('watch-folders:set-config', async (event, rules = []) => {
     try {
-        const stmt = db.prepare('INSERT OR REPLACE INTO drawings (symbol, data) VALUES (?, ?)');
-        stmt.run(symbol, JSON.stringify(data));
-        return { success: true };
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const normalized = (rules || []).map(normalizeRule);
+        writeJsonSetting('watchFolders', normalized);
+        const folders = startWatchFolders();
+        logSystemEvent('WATCH_FOLDERS_CONFIGURED', { rules: normalized.length });
+        return { success: true, rules: normalized, folders };
     } catch (err) {
-        return { success: false, error: err.message };
+        return errorResult(err);
     }
 });
 
-ipcMain.handle('drawings:delete-all', async (event, sourceId) => {
+ipcMain.handle('watch-folders:rescan', async () => {
     try {
-        const stmt = db.prepare('DELETE FROM drawings WHERE symbol = ?');
-        stmt.run(sourceId);
+        if (!watchFolderService) return failure('UNAVAILABLE', 'Watch folders are not running');
+        watchFolderService.rescan();
         return { success: true };
     } catch (err) {
-        return { success: false, error: err.message };
+        return errorResult(err);
     }
 });
 
-ipcMain.handle('master-drawings:load', async () => {
+ipcMain.handle('watch-folders:get-history', async (event, limit = 100) => {
     try {
-        const stmt = db.prepare('SELECT symbol, data FROM drawings');
-        const rows = stmt.all();
-        const map = {};
-        rows.forEach(row => { map[row.symbol] = JSON.parse(row.data); });
-        return { success: true, data: map };
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, files: listWatchImports(db, limit) };
     } catch (err) {
-        return { success: false, error: err.message };
+        return errorResult(err);
     }
 });
 
-ipcMain.handle('trades:get-ledger', async (event, sourceId) => {
+ipcMain.handle('datasets:list', async () => {
     try {
-        const stmt = db.prepare('SELECT data FROM trades WHERE sourceId = ? ORDER BY timestamp DESC');
-        const rows = stmt.all(sourceId);
-        return rows.map(r => JSON.parse(r.data));
+        if (!db) return [];
+        return listDatasets(db);
     } catch (e) {
         return [];
     }
 });
 
-ipcMain.handle('trades:save', async (event, trade) => {
+// sort: { column: 'timestamp' | 'open' | 'high' | 'low' | 'close' | 'volume', direction: 'asc' | 'desc' }
+ipcMain.handle('datasets:get-rows-page', async (event, datasetId, offset, limit, sort) => {
     try {
-        const stmt = db.prepare('INSERT INTO trades (id, sourceId, data, timestamp) VALUES (?, ?, ?, ?)');
-        stmt.run(trade.id, trade.sourceId, JSON.stringify(trade), trade.timestamp);
-        logSystemEvent('TRADE_SAVED', { id: trade.id });
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotReady'));
+        return { success: true, ...getRowsPage(db, datasetId, offset, limit, sort) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// spec: see DEFAULT_SPEC in synthetic.js. Regenerating a synthetic dataset replaces it;
+// real data under the same symbol/timeframe is never overwritten.
+const generateSyntheticDataset = (spec) => {
+    const { spec: resolved, bars } = generateSyntheticBars(spec);
+    const existing = getDataset(db, datasetId(resolved.symbol, resolved.timeframe));
+    if (existing && existing.source !== 'synthetic') throw new Error(`${existing.id} already holds ${existing.source} data`);
+    db.prepare('DELETE FROM market_data WHERE symbol = ? AND timeframe = ?').run(resolved.symbol, resolved.timeframe);
+    insertBars(db, resolved.symbol, resolved.timeframe, bars);
+    const dataset = registerDataset(db, resolved.symbol, resolved.timeframe, 'synthetic', { spec: resolved });
+    logSystemEvent('SYNTHETIC_GENERATED', { id: dataset.id, bars: bars.length, model: resolved.model, seed: resolved.seed });
+    return dataset;
+};
+
+ipcMain.handle('datasets:generate-synthetic', async (event, spec = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotReady'));
+        return { success: true, dataset: generateSyntheticDataset(spec) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// spec: { contracts: [datasetId | { datasetId, expiry?, openInterest?: [[ts, oi]] }], symbol?,
+//   roll: { rule: 'date' | 'volume' | 'open_interest', daysBeforeExpiry, confirmDays },
+//   adjustment: 'none' | 'difference' | 'ratio' }. preview: build without storing.
+ipcMain.handle('datasets:build-continuous', async (event, spec = {}, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotReady'));
+        if (options && options.preview) {
+            const { symbol, timeframe, bars, rolls } = buildContinuous(db, spec);
+            return { success: true, symbol, timeframe, bars, rolls };
+        }
+        const result = stitchContinuous(db, spec);
+        logSystemEvent('CONTINUOUS_BUILT', { id: result.dataset.id, bars: result.bars, rolls: result.rolls.length });
+        return { success: true, ...result };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('datasets:rebuild-continuous', async (event, id) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotReady'));
+        const result = rebuildContinuous(db, id);
+        logSystemEvent('CONTINUOUS_BUILT', { id: result.dataset.id, bars: result.bars, rolls: result.rolls.length });
+        return { success: true, ...result };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// spec: { datasets: [id], priority?: [dataset id | source name], symbol?,
+//   tolerance?: { price?, volume? } (relative) }. preview: merge without storing.
+ipcMain.handle('datasets:merge', async (event, spec = {}, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotReady'));
+        if (options && options.preview) {
+            const { bars, ...report } = buildMerged(db, spec);
+            return { success: true, ...report, bars: bars.length };
+        }
+        const result = storeMerged(db, spec);
+        logSystemEvent('DATASETS_MERGED', { id: result.dataset.id, sources: result.spec.datasets, bars: result.bars, conflicts: result.conflictCount }, result.conflictCount ? 'WARN' : 'INFO');
+        return { success: true, ...result };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('datasets:rebuild-merged', async (event, id) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotReady'));
+        const result = rebuildMerged(db, id);
+        logSystemEvent('DATASETS_MERGED', { id: result.dataset.id, sources: result.spec.datasets, bars: result.bars, conflicts: result.conflictCount }, result.conflictCount ? 'WARN' : 'INFO');
+        return { success: true, ...result };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- BAR EDITOR ---
+// Edits to single bars (barEditor.js) ripple out to what was computed from
+// them: the registry stats, streaming indicators on the dataset, continuous
+// and merged series built from it and the rolled-up 1D bar of its session.
+// A derived series that fails to rebuild is reported, the edit stays.
+const recomputeAfterBarChange = (id, timestamp) => {
+    const dataset = getDataset(db, id);
+    if (dataset) registerDataset(db, dataset.symbol, dataset.timeframe, dataset.source);
+    const streams = indicatorStreams ? indicatorStreams.reseed(id) : 0;
+    const derived = dependentDatasets(db, id).map((dep) => {
+        try {
+            if (dep.source === 'continuous') rebuildContinuous(db, dep.id);
+            else if (dep.source === 'merged') rebuildMerged(db, dep.id);
+            else {
+                // Only the finest intraday series feeds the roll-up
+                const { symbol, timeframe } = parseDatasetId(id);
+                const rolled = rollDailyBars(db, symbol, { from: timestamp - 24 * 60 * 60 * 1000, calendar: calendarForAsset(getSymbolMeta(db, symbol).assetClass) });
+                if (rolled.source !== timeframe) return { id: dep.id, rebuilt: false, skipped: rolled.skipped || `rolled from ${rolled.source}` };
+                registerDataset(db, symbol, '1D', ROLLUP_SOURCE, { rolledFrom: rolled.source });
+            }
+            if (indicatorStreams) indicatorStreams.reseed(dep.id);
+            return { id: dep.id, rebuilt: true };
+        } catch (err) {
+            logSystemEvent('DERIVED_REBUILD_FAILED', { id: dep.id, from: id, error: err.message }, 'WARN');
+            return { id: dep.id, rebuilt: false, error: err.message };
+        }
+    });
+    broadcast('datasets:bars-changed', { datasetId: id, timestamps: [timestamp], derived: derived.filter(d => d.rebuilt).map(d => d.id) });
+    return { streams, derived };
+};
+
+// changes: { open?, high?, low?, close?, volume? }; options: { reason? }
+ipcMain.handle('bars:edit', async (event, id, timestamp, changes = {}, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotReady'));
+        const result = editBar(db, id, timestamp, changes, options || {});
+        logSystemEvent('BAR_EDITED', { id, timestamp: result.edit.timestamp, editId: result.edit.id, fields: Object.keys(changes || {}) });
+        return { success: true, ...result, recomputed: recomputeAfterBarChange(id, result.edit.timestamp) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('bars:delete', async (event, id, timestamp, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotReady'));
+        const result = deleteBar(db, id, timestamp, options || {});
+        logSystemEvent('BAR_DELETED', { id, timestamp: result.edit.timestamp, editId: result.edit.id });
+        return { success: true, ...result, recomputed: recomputeAfterBarChange(id, result.edit.timestamp) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('bars:revert', async (event, editId, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotReady'));
+        const result = revertBarEdit(db, editId, options || {});
+        logSystemEvent('BAR_EDIT_REVERTED', { id: result.edit.datasetId, timestamp: result.edit.timestamp, editId: result.reverted.id });
+        return { success: true, ...result, recomputed: recomputeAfterBarChange(result.edit.datasetId, result.edit.timestamp) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// options: { datasetId?, from?, to?, limit? }
+ipcMain.handle('bars:list-edits', async (event, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotReady'));
+        return { success: true, edits: listBarEdits(db, options || {}) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// Helpers: JSON values in the settings table (backend-owned config)
+const readJsonSetting = (key) => {
+    const row = db ? db.prepare('SELECT value FROM settings WHERE key = ?').get(key) : null;
+    return row ? JSON.parse(row.value) : null;
+};
+
+const writeJsonSetting = (key, value) => {
+    if (db) db.prepare('INSERT OR REPLACE INTO settings (key, value) VALUES (?, ?)').run(key, JSON.stringify(value));
+};
+
+// --- LIVE FEED FAN-OUT ---
+// Pausing only stops the fan-out to windows; recording, alerts and indicators keep running
+let liveFeedPaused = false;
+
+const setLiveFeedPaused = (paused) => {
+    liveFeedPaused = !!paused;
+    broadcast('live-feed:paused', { paused: liveFeedPaused });
+    logSystemEvent(liveFeedPaused ? 'LIVE_FEED_PAUSED' : 'LIVE_FEED_RESUMED');
+};
+
+// Intraday bars go out tagged with their session segment
+const withSegment = (event) => {
+    if (!db || !(TIMEFRAME_MS[event.timeframe] < TIMEFRAME_MS['1D'])) return event;
+    try {
+        return { ...event, segment: segmentClassifierFor(event.symbol).classify(event.bar.timestamp).segment };
+    } catch (e) {
+        return event;
+    }
+};
+
+liveFeed.on('bar', (event) => { if (!liveFeedPaused) broadcast('live-feed:bar', withSegment(event)); });
+liveFeed.on('quote', (event) => { if (!liveFeedPaused) broadcast('live-feed:quote', event); });
+liveFeed.on('status', (event) => {
+    logSystemEvent('PROVIDER_STATUS', event, event.status === 'degraded' ? 'WARN' : 'INFO');
+    broadcast('provider:status', event);
+});
+networkEvents.on('health', (event) => {
+    logSystemEvent('PROVIDER_HEALTH', event, event.status === 'healthy' ? 'INFO' : 'WARN');
+    broadcast('provider:health', event);
+});
+
+// --- SECRETS ---
+const SECRET_EXPIRY_WARN_MS = 14 * 86400000;
+const SECRET_EXPIRY_CHECK_MS = 6 * 3600000;
+const expiryNotified = new Set(); // keys already announced this session
+
+// options: { provider?, expiresAt? }
+ipcMain.handle('secrets:set', async (event, key, value, options = {}) => {
+    try {
+        setSecret(db, key, value, options);
+        expiryNotified.delete(key);
+        logSystemEvent('SECRET_STORED', { key });
         return { success: true };
     } catch (err) {
-        return { success: false, error: err.message };
+        return errorResult(err);
     }
 });
 
-ipcMain.handle('get-system-telemetry', async () => {
-    const mem = process.memoryUsage();
-    return {
-        processInfo: { version: process.versions.electron, uptime: Math.floor(process.uptime()), pid: process.pid },
-        resources: { memory: { rss: (mem.rss / 1024 / 1024).toFixed(2) + ' MB' } },
-        logBuffer: systemLogBuffer
-    };
+ipcMain.handle('secrets:set-meta', async (event, key, updates = {}) => {
+    try {
+        const secret = setSecretMeta(db, key, updates);
+        expiryNotified.delete(key);
+        return { success: true, secret };
+    } catch (err) {
+        return errorResult(err);
+    }
 });
 
-ipcMain.handle('logs:get-db-status', async () => {
+// Asks the provider to make an authenticated no-op call with its stored keys
+ipcMain.handle('secrets:test-credentials', async (event, providerId) => {
     try {
-        if (!db) return { connected: false, error: 'DB Null' };
-        db.prepare("SELECT 1").get();
-        return { connected: true };
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const provider = getProvider(providerId);
+        if (typeof provider.testCredentials !== 'function') return failure('UNSUPPORTED', `${provider.name} has no credential check`);
+        let result;
+        try {
+            result = await provider.testCredentials();
+        } catch (err) {
+            // Could not tell (network, outage): keep the previous verdict
+            logSystemEvent('CREDENTIAL_TEST_FAILED', { provider: providerId, error: err.message }, 'WARN');
+            return errorResult(err);
+        }
+        recordValidation(db, providerId, result.valid ? 'valid' : 'invalid', result.message);
+        logSystemEvent('CREDENTIAL_TEST', { provider: providerId, valid: result.valid }, result.valid ? 'INFO' : 'WARN');
+        return { success: true, provider: providerId, ...result };
     } catch (err) {
-        return { connected: false, error: err.message };
+        return errorResult(err);
     }
 });
 
-app.whenReady().then(() => {
-  logSystemEvent('APP_READY');
-  setupDatabase();
-  runBootScan();
-  createWindow();
+ipcMain.handle('secrets:get-expiring', async (event, withinDays = 14) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, secrets: expiringSecrets(db, withinDays * 86400000) };
+    } catch (err) {
+        return errorResult(err);
+    }
 });
 
-app.on('window-all-closed', () => { if (db) db.close(); if (process.platform !== 'darwin') app.quit(); });
+// Warns once per key (per session) when a key is within the warning window or expired
+const checkSecretExpiry = () => {
+    if (!db) return;
+    try {
+        const fresh = expiringSecrets(db, SECRET_EXPIRY_WARN_MS).filter(s => !expiryNotified.has(s.key));
+        if (fresh.length === 0) return;
+        fresh.forEach(s => expiryNotified.add(s.key));
+        const summary = fresh.map(({ key, provider, expiresAt, expired }) => ({ key, provider, expiresAt, expired }));
+        logSystemEvent('SECRETS_EXPIRING', { secrets: summary }, 'WARN');
+        broadcast('secrets:expiring', summary);
+        if (Notification.isSupported() && !presentation.isActive()) {
+            const names = Array.from(new Set(fresh.map(s => s.provider))).join(', ');
+            new Notification({ title: 'Red Pill Charting', body: `API keys expiring soon: ${names}` }).show();
+        }
+    } catch (err) {
+        logSystemEvent('SECRETS_EXPIRY_CHECK_FAILED', { error: err.message }, 'WARN');
+    }
+};
+
+const startSecretExpiryChecks = () => {
+    checkSecretExpiry();
+    setInterval(checkSecretExpiry, SECRET_EXPIRY_CHECK_MS);
+};
+
+ipcMain.handle('secrets:delete', async (event, key) => {
+    try {
+        return { success: deleteSecret(db, key) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('secrets:list', async () => {
+    try {
+        return listSecretKeys(db);
+    } catch (e) {
+        return [];
+    }
+});
+
+// --- PROXY ---
+// Setting 'proxy': { mode: 'none' | 'http' | 'socks5', host, port, username, bypass };
+// the password lives in the secrets store as 'proxy.password'. Open WebSockets
+// keep their route until they reconnect.
+const PROXY_PASSWORD_KEY = 'proxy.password';
+
+const proxySecrets = createSecretScope(() => db, 'proxy');
+const proxyPassword = () => proxySecrets(PROXY_PASSWORD_KEY);
+
+const applyProxyConfig = () => {
+    try {
+        const config = configureProxy(readJsonSetting('proxy') || {}, proxyPassword);
+        if (config.mode !== 'none') logSystemEvent('PROXY_ENABLED', { mode: config.mode, host: config.host, port: config.port });
+    } catch (err) {
+        logSystemEvent('PROXY_CONFIG_INVALID', { error: err.message }, 'ERROR');
+    }
+};
+
+ipcMain.handle('proxy:get-config', async () => {
+    try {
+        const config = normalizeProxyConfig(readJsonSetting('proxy') || {});
+        return { success: true, config, hasPassword: !!(db && listSecretKeys(db).some(s => s.key === PROXY_PASSWORD_KEY)) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// password: undefined keeps the stored one, '' / null removes it
+ipcMain.handle('proxy:set-config', async (event, updates = {}, password) => {
+    try {
+        const config = normalizeProxyConfig({ ...(readJsonSetting('proxy') || {}), ...updates });
+        if (password) setSecret(db, PROXY_PASSWORD_KEY, password);
+        else if (password !== undefined) deleteSecret(db, PROXY_PASSWORD_KEY);
+        writeJsonSetting('proxy', config);
+        configureProxy(config, proxyPassword);
+        logSystemEvent('PROXY_CONFIG_UPDATED', { mode: config.mode, host: config.host, port: config.port });
+        return { success: true, config };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// Checks a config before saving it (or the active one when omitted)
+ipcMain.handle('proxy:test', async (event, config = null, password, url) => {
+    const result = await testProxy({ config, password: password !== undefined ? password : proxyPassword(), url });
+    logSystemEvent('PROXY_TEST', result, result.success ? 'INFO' : 'WARN');
+    return result;
+});
+
+// --- TLS POLICY ---
+// Setting 'tlsPolicy': { strictTls, providers: { [id]: { strictTls?, pins: ['sha256/...'] } } }.
+// Applies to new connections; open sockets are kept until they reconnect.
+const applyTlsPolicy = () => {
+    try {
+        configureTlsPolicy(readJsonSetting('tlsPolicy') || {});
+    } catch (err) {
+        logSystemEvent('TLS_POLICY_INVALID', { error: err.message }, 'ERROR');
+    }
+};
+
+ipcMain.handle('tls:get-policy', async () => ({ success: true, policy: getTlsPolicy() }));
+
+ipcMain.handle('tls:set-policy', async (event, policy = {}) => {
+    try {
+        const next = configureTlsPolicy(policy);
+        writeJsonSetting('tlsPolicy', next);
+        logSystemEvent('TLS_POLICY_UPDATED', { strictTls: next.strictTls, pinned: Object.keys(next.providers).filter(id => next.providers[id].pins.length) });
+        return { success: true, policy: next };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// Returns the pins of an endpoint's current chain (through the proxy, if any) for the pinning UI
+ipcMain.handle('tls:inspect', async (event, url) => {
+    try {
+        return { success: true, ...(await inspectCertificates(url, agentFor(url.replace(/^wss:/, 'https:')))) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- MARKET DATA PROVIDERS ---
+const providerLog = (id) => ({ level, message }) => logSystemEvent(`${id.toUpperCase()}_MESSAGE`, { message }, level);
+
+const REPLAY_MAX_BARS = 200000;
+
+// Each provider can only read its own keys ('<provider>.<name>')
+const providerContext = (plugin) => ({
+    onLog: providerLog(plugin.id),
+    getSecret: createSecretScope(() => db, plugin.id),
+    onBackfill: (symbol, timeframe, bars) => persistProviderBars(plugin.id, symbol, timeframe, bars),
+    // Latest REPLAY_MAX_BARS only, so a huge dataset doesn't stall the main process
+    readBars: (symbol, timeframe) => (db ? db.prepare('SELECT timestamp, open, high, low, close, volume FROM market_data WHERE symbol = ? COLLATE NOCASE AND timeframe = ? ORDER BY timestamp DESC LIMIT ?')
+        .all(symbol, timeframe, REPLAY_MAX_BARS).reverse() : [])
+});
+
+// Test mode swaps every network provider for the mock one
+loadProviderPlugins(testMode ? [mockProviderPlugin] : BUILTIN_PLUGINS, providerContext);
+
+// The Alpaca broker shares the provider's keys
+const alpacaSecrets = createSecretScope(() => db, 'alpaca');
+
+// Writes provider bars into market_data and refreshes the dataset registry
+const persistProviderBars = (id, symbol, timeframe, bars, meta = null) => {
+    if (!db) return null;
+    insertBars(db, symbol, timeframe, bars);
+    return registerDataset(db, symbol, timeframe, id, meta);
+};
+
+const loadProviderConfig = (id) => readJsonSetting(`provider.${id}`) || {};
+const saveProviderConfig = (id, config) => writeJsonSetting(`provider.${id}`, config);
+
+ipcMain.handle('providers:list', async () => listProviders());
+
+// Declared settings with the stored values (defaults filled in) and which secrets are set
+ipcMain.handle('providers:get-settings', async (event, id) => {
+    try {
+        const { settings, secrets } = getProviderDescriptor(id);
+        const stored = db ? new Set(listSecretKeys(db).map(s => s.key)) : new Set();
+        return { success: true, schema: settings, values: resolveProviderSettings(id, loadProviderConfig(id)), secrets: secrets.map(key => ({ key, set: stored.has(key) })) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// values: { key: value }; null or '' restores the declared default
+ipcMain.handle('providers:set-settings', async (event, id, values = {}) => {
+    try {
+        const { settings } = getProviderDescriptor(id);
+        const stored = { ...loadProviderConfig(id) };
+        settings.forEach((field) => { if (field.key in values && (values[field.key] == null || values[field.key] === '')) delete stored[field.key]; });
+        saveProviderConfig(id, { ...stored, ...normalizeProviderSettings(id, values) });
+        return { success: true, values: resolveProviderSettings(id, loadProviderConfig(id)) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('providers:connect', async (event, id, config = {}) => {
+    try {
+        const merged = { ...loadProviderConfig(id), ...config };
+        const result = await getProvider(id).connect(merged);
+        if (!readOnly) saveProviderConfig(id, merged);
+        return { success: true, ...result };
+    } catch (err) {
+        logSystemEvent('PROVIDER_CONNECT_FAILED', { provider: id, error: err.message }, 'ERROR');
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('providers:disconnect', async (event, id) => {
+    try {
+        await getProvider(id).disconnect();
+        return { success: true };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('providers:get-status', async (event, id) => {
+    try {
+        return { success: true, ...getProvider(id).getStatus() };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('providers:fetch-history', async (event, id, symbol, timeframe, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const bars = await getProvider(id).fetchHistory(symbol, timeframe, options);
+        const dataset = persistProviderBars(id, symbol, timeframe, bars, options.contract ? { contract: options.contract } : null);
+        logSystemEvent('PROVIDER_HISTORY_LOADED', { provider: id, symbol, timeframe, bars: bars.length });
+        return { success: true, count: bars.length, dataset };
+    } catch (err) {
+        logSystemEvent('PROVIDER_HISTORY_FAILED', { provider: id, symbol, error: err.message }, 'ERROR');
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('providers:subscribe', async (event, id, symbol, timeframe, options = {}) => {
+    try {
+        await getProvider(id).subscribe(symbol, timeframe, options);
+        logSystemEvent('PROVIDER_SUBSCRIBED', { provider: id, symbol, timeframe });
+        return { success: true };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// Zero-config download: fetch from Yahoo Finance and register as a dataset
+ipcMain.handle('market:download-history', async (event, symbol, range = '1y', interval = '1D') => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const normalized = String(symbol || '').trim().toUpperCase();
+        if (!normalized) return failure('INVALID_INPUT', 'Symbol is required');
+        const bars = await getProvider('yahoo').fetchHistory(normalized, interval, { range });
+        if (bars.length === 0) return failure('NOT_FOUND', `No data returned for ${normalized}`);
+        const dataset = persistProviderBars('yahoo', normalized, interval, bars, { range });
+        logSystemEvent('YAHOO_DOWNLOAD_COMPLETE', { symbol: normalized, interval, range, bars: bars.length });
+        return { success: true, count: bars.length, dataset };
+    } catch (err) {
+        logSystemEvent('YAHOO_DOWNLOAD_FAILED', { symbol, error: err.message }, 'ERROR');
+        return errorResult(err);
+    }
+});
+
+// FRED observations are cached as datasets; revisits within maxAgeMs skip the network
+const FRED_CACHE_MAX_AGE = 12 * 60 * 60 * 1000;
+
+ipcMain.handle('fred:get-series', async (event, seriesId, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const symbol = fredSymbol(String(seriesId).replace(/^FRED:/i, ''));
+        const maxAge = options.maxAgeMs ?? FRED_CACHE_MAX_AGE;
+        let dataset = findDatasetsBySymbol(db, symbol).find(d => d.source === 'fred') || null;
+
+        const isFresh = dataset && dataset.meta.fetchedAt && (Date.now() - dataset.meta.fetchedAt) < maxAge;
+        // Read-only sessions show the cached series, however old, and never fetch one
+        if (readOnly && !dataset) return failure('READ_ONLY', t('errors.readOnly'), { readOnly: true });
+        if (!readOnly && (!isFresh || options.forceRefresh)) {
+            const fred = getProvider('fred');
+            const info = await fred.getSeriesInfo(symbol.slice(5));
+            const bars = await fred.fetchHistory(info.id, info.timeframe);
+            // FRED revises history, so replace the cached series wholesale
+            db.prepare('DELETE FROM market_data WHERE symbol = ? AND timeframe = ?').run(symbol, info.timeframe);
+            dataset = persistProviderBars('fred', symbol, info.timeframe, bars, { ...info, fetchedAt: Date.now() });
+            logSystemEvent('FRED_SERIES_FETCHED', { series: info.id, observations: bars.length });
+        }
+
+        const rows = db.prepare('SELECT timestamp, close FROM market_data WHERE symbol = ? AND timeframe = ? ORDER BY timestamp ASC').all(symbol, dataset.timeframe);
+        return { success: true, dataset, data: rows.map(r => [r.timestamp, r.close, r.close, r.close, r.close, 0]), format: 'array' };
+    } catch (err) {
+        logSystemEvent('FRED_SERIES_FAILED', { series: seriesId, error: err.message }, 'ERROR');
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('providers:search-symbols', async (event, id, query) => {
+    try {
+        const provider = getProvider(id);
+        if (!provider.searchSymbols) return failure('UNSUPPORTED', `${provider.name} does not support symbol search`);
+        return { success: true, results: await provider.searchSymbols(query) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('providers:unsubscribe', async (event, id, symbol, timeframe) => {
+    try {
+        const removed = getProvider(id).unsubscribe(symbol, timeframe);
+        return removed ? { success: true } : failure('NOT_FOUND', 'Not subscribed');
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// Retry / circuit-breaker state of provider REST endpoints
+ipcMain.handle('providers:get-health', async () => getNetworkHealth());
+
+ipcMain.handle('providers:reset-health', async (event, id = null) => {
+    try {
+        const reset = resetBreakers(id);
+        logSystemEvent('PROVIDER_BREAKERS_RESET', { provider: id, providers: reset });
+        return { success: true, ...getNetworkHealth() };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- ORDER BOOK ---
+// Books are maintained here from provider depth events; windows get throttled
+// snapshots on 'depth:snapshot' (skipped while the live feed is paused).
+const depthService = createDepthService({
+    onSnapshot: (snapshot) => { if (!liveFeedPaused) broadcast('depth:snapshot', snapshot); },
+    onGap: (provider, symbol) => {
+        logSystemEvent('DEPTH_RESYNC', { provider, symbol }, 'WARN');
+        Promise.resolve(getProvider(provider).resyncDepth(symbol)).catch(err => logSystemEvent('DEPTH_RESYNC_FAILED', { provider, symbol, error: err.message }, 'ERROR'));
+    }
+});
+
+liveFeed.on('depth', depthService.handleDepth);
+
+// options: { levels?, throttleMs? }
+ipcMain.handle('depth:subscribe', async (event, providerId, symbol, options = {}) => {
+    try {
+        const provider = getProvider(providerId);
+        if (typeof provider.subscribeDepth !== 'function') return failure('UNSUPPORTED', `${provider.name} does not provide order book data`);
+        const { created } = depthService.watch(providerId, symbol, options || {});
+        if (created) await provider.subscribeDepth(symbol);
+        return { success: true };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('depth:unsubscribe', async (event, providerId, symbol) => {
+    try {
+        if (depthService.unwatch(providerId, symbol)) getProvider(providerId).unsubscribeDepth(symbol);
+        return { success: true };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('depth:get-snapshot', async (event, providerId, symbol, levels = null) => {
+    try {
+        const snapshot = depthService.getSnapshot(providerId, symbol, levels);
+        return snapshot ? { success: true, snapshot } : failure('NOT_FOUND', 'Not subscribed');
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('depth:list', async () => depthService.list());
+
+// --- TIME & SALES ---
+// Trade prints are recorded only when switched on (all symbols, or the listed
+// ones); the aggressor side uses the latest quote, else the order book top.
+const latestQuotes = new Map(); // `${provider}:${symbol}` -> { bid, ask }
+liveFeed.on('quote', ({ provider, symbol, bid, ask }) => latestQuotes.set(`${provider}:${symbol}`, { bid, ask }));
+
+let orderFlowConfig = null; // cached; read on every print
+
+const getOrderFlowConfig = () => {
+    if (!orderFlowConfig && db) orderFlowConfig = { ...ORDER_FLOW_DEFAULTS, ...(readJsonSetting('orderflow.recording') || {}) };
+    return orderFlowConfig || ORDER_FLOW_DEFAULTS;
+};
+
+const tradeRecorder = createTradeRecorder({
+    getDb: () => (readOnly ? null : db),
+    getConfig: getOrderFlowConfig,
+    getQuote: (provider, symbol) => {
+        const quote = latestQuotes.get(`${provider}:${symbol}`);
+        if (quote) return quote;
+        const book = depthService.getSnapshot(provider, symbol, 1);
+        return book ? { bid: book.bestBid, ask: book.bestAsk } : null;
+    },
+    onError: (err) => logSystemEvent('TRADE_RECORDING_FAILED', { error: err.message }, 'ERROR')
+});
+liveFeed.on('trade', (event) => tradeRecorder.handleTrade(event));
+
+const orderFlowSymbol = (symbol) => String(symbol || '').trim().toUpperCase();
+
+ipcMain.handle('orderflow:get-config', async () => getOrderFlowConfig());
+
+ipcMain.handle('orderflow:get-status', async () => tradeRecorder.status());
+
+// updates: { enabled?, symbols?, retentionDays? }
+ipcMain.handle('orderflow:set-config', async (event, updates = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const next = { ...getOrderFlowConfig() };
+        if (updates.enabled !== undefined) next.enabled = !!updates.enabled;
+        if (updates.symbols !== undefined) next.symbols = (Array.isArray(updates.symbols) ? updates.symbols : []).map(orderFlowSymbol).filter(Boolean);
+        if (updates.retentionDays !== undefined) next.retentionDays = Math.max(1, Math.min(365, Math.round(Number(updates.retentionDays) || ORDER_FLOW_DEFAULTS.retentionDays)));
+        writeJsonSetting('orderflow.recording', next);
+        orderFlowConfig = next;
+        logSystemEvent('ORDER_FLOW_CONFIG', { enabled: next.enabled, symbols: next.symbols.length });
+        return { success: true, config: next };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// options: { from?, to?, limit? }
+ipcMain.handle('orderflow:get-prints', async (event, symbol, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        tradeRecorder.flush();
+        return { success: true, prints: readPrints(db, orderFlowSymbol(symbol), options || {}) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('orderflow:get-delta', async (event, symbol, timeframe, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        tradeRecorder.flush();
+        return { success: true, bars: computeDeltaBars(db, orderFlowSymbol(symbol), timeframe, options || {}) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// options: { from?, to?, tickSize? } (tick size defaults to the symbol metadata's)
+ipcMain.handle('orderflow:get-footprint', async (event, symbol, timeframe, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        tradeRecorder.flush();
+        const normalized = orderFlowSymbol(symbol);
+        const tickSize = Number(options?.tickSize) || getSymbolMeta(db, normalized).tickSize;
+        return { success: true, tickSize, bars: computeFootprint(db, normalized, timeframe, { ...(options || {}), tickSize }) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- BACKGROUND MODE ---
+// Closed live bars are recorded to the bar store from the main process, so a
+// hidden or closed chart doesn't lose data. Setting 'background' controls the
+// recorder and whether minimize / close hide the window to the tray.
+const BACKGROUND_DEFAULTS = { recordLiveBars: true, minimizeToTray: false, closeToTray: false };
+let isQuitting = false;
+
+const getBackgroundConfig = () => ({ ...BACKGROUND_DEFAULTS, ...(readJsonSetting('background') || {}) });
+
+const barRecorder = createBarRecorder({
+    persist: (provider, symbol, timeframe, bars) => persistProviderBars(provider, symbol, timeframe, bars),
+    onError: (stream, err) => logSystemEvent('LIVE_RECORDING_FAILED', { provider: stream.provider, symbol: stream.symbol, error: err.message }, 'ERROR')
+});
+liveFeed.on('bar', (event) => barRecorder.handleBar(event));
+
+const getBackgroundStatus = () => {
+    const windows = BrowserWindow.getAllWindows().filter(w => !w.isDestroyed() && !presentation.isDimmer(w));
+    const subscriptions = listProviders().reduce((n, p) => n + (Array.isArray(p.subscriptions) ? p.subscriptions.length : 0), 0);
+    return {
+        windows: { open: windows.length, visible: windows.filter(w => w.isVisible() && !w.isMinimized()).length },
+        tray: trayController.isActive(),
+        liveFeed: { paused: liveFeedPaused, subscriptions },
+        recording: barRecorder.status(),
+        alerts: { running: !!alertEngine, armed: alertEngine ? alertEngine.listAlerts().filter(a => a.enabled).length : 0 },
+        scheduler: { running: !!scheduler, jobs: scheduler ? scheduler.listJobs().filter(j => j.enabled).length : 0 },
+        watchFolders: watchFolderService ? watchFolderService.status().watching : 0
+    };
+};
+
+const trayController = createTrayController({
+    getStatus: getBackgroundStatus,
+    onShow: () => { showMainWindow(); if (!getBackgroundConfig().closeToTray && !getBackgroundConfig().minimizeToTray) trayController.destroy(); },
+    onTogglePause: () => setLiveFeedPaused(!liveFeedPaused),
+    onQuit: () => { isQuitting = true; app.quit(); }
+});
+setInterval(() => trayController.refresh(), 10000);
+
+const hideToTray = () => {
+    if (!mainWindow || mainWindow.isDestroyed()) return;
+    mainWindow.hide();
+    trayController.show();
+    logSystemEvent('HIDDEN_TO_TRAY', { recording: barRecorder.status().streams.length });
+};
+
+ipcMain.handle('background:get-status', async () => getBackgroundStatus());
+
+ipcMain.handle('background:get-config', async () => getBackgroundConfig());
+
+ipcMain.handle('background:set-config', async (event, updates = {}) => {
+    try {
+        const next = { ...getBackgroundConfig(), ...updates };
+        Object.keys(next).forEach((key) => { if (!(key in BACKGROUND_DEFAULTS)) delete next[key]; });
+        writeJsonSetting('background', next);
+        barRecorder.setEnabled(next.recordLiveBars);
+        if (!next.minimizeToTray && !next.closeToTray && mainWindow && mainWindow.isVisible()) trayController.destroy();
+        return { success: true, config: next };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- SIGNAL INBOX ---
+// Setting 'inbox.config': { enabled, port, notify: 'none' | 'desktop' | 'all' }; 'inbox.tokenHash'
+// holds the SHA-256 of the bearer token (the plaintext is only returned by inbox:rotate-token).
+// Stored signals reach the charts as 'inbox:signals' and can be listed per symbol and range.
+const INBOX_NOTIFY_MODES = ['none', 'desktop', 'all'];
+const MAX_SIGNAL_NOTIFICATIONS = 3; // per batch; the rest are summarized
+
+const getInboxConfig = () => ({ enabled: false, port: SIGNAL_INBOX_PORT, notify: 'desktop', ...(readJsonSetting('inbox.config') || {}) });
+
+const formatSignal = (signal) => [signal.side ? signal.side.toUpperCase() : 'SIGNAL', signal.symbol, signal.price != null ? `@ ${signal.price}` : null, signal.text]
+    .filter(Boolean).join(' ');
+
+const notifySignals = (signals) => {
+    const mode = getInboxConfig().notify;
+    const wanted = signals.filter(s => s.notify);
+    if (mode === 'none' || !wanted.length) return;
+    const lines = wanted.slice(0, MAX_SIGNAL_NOTIFICATIONS).map(formatSignal);
+    if (wanted.length > MAX_SIGNAL_NOTIFICATIONS) lines.push(`+${wanted.length - MAX_SIGNAL_NOTIFICATIONS} more`);
+    // Presenting: the screen is shared, so nothing pops up on it
+    if (!presentation.isActive() && Notification.isSupported()) new Notification({ title: 'Signal inbox', body: lines.join('\n') }).show();
+    if (mode === 'all') notifiers.notifyAll(lines.join('\n')).catch(() => {});
+};
+
+// Stores and forwards a validated batch (HTTP inbox and companion socket)
+const acceptSignals = (signals) => {
+    if (!db) throw Object.assign(new Error('Database not ready'), { status: 503 });
+    const stored = db.transaction(() => signals.map(signal => recordSignal(db, signal)))();
+    logSystemEvent('SIGNALS_RECEIVED', { count: stored.length, symbols: Array.from(new Set(stored.map(s => s.symbol))) });
+    broadcast('inbox:signals', stored.map(({ notify, ...signal }) => signal));
+    notifySignals(stored);
+    return stored;
+};
+
+const signalInbox = createSignalInbox({
+    getTokenHash: () => readJsonSetting('inbox.tokenHash'),
+    accept: acceptSignals,
+    onLog: (level, message, data) => logSystemEvent(message, data, level)
+});
+
+const startSignalInbox = async () => {
+    const config = getInboxConfig();
+    await signalInbox.stop();
+    if (!config.enabled || readOnly) return null;
+    try {
+        return await signalInbox.start(config.port);
+    } catch (err) {
+        logSystemEvent('SIGNAL_INBOX_START_FAILED', { port: config.port, error: err.message }, 'ERROR');
+        throw err;
+    }
+};
+
+const inboxStatus = () => {
+    const address = signalInbox.address();
+    return { ...getInboxConfig(), hasToken: !!readJsonSetting('inbox.tokenHash'), listening: !!address, url: address ? `http://${address.host}:${address.port}/signals` : null };
+};
+
+ipcMain.handle('inbox:get-status', async () => inboxStatus());
+
+ipcMain.handle('inbox:set-config', async (event, patch = {}) => {
+    try {
+        const next = { ...getInboxConfig(), ...patch };
+        next.enabled = !!next.enabled;
+        next.port = Math.round(Number(next.port));
+        if (!Number.isInteger(next.port) || next.port < 1024 || next.port > 65535) return failure('INVALID_INPUT', 'port must be between 1024 and 65535');
+        if (!INBOX_NOTIFY_MODES.includes(next.notify)) return failure('INVALID_INPUT', `Invalid notify mode: ${next.notify}`);
+        writeJsonSetting('inbox.config', next);
+        await startSignalInbox();
+        return { success: true, ...inboxStatus() };
+    } catch (err) {
+        return errorResult(err, inboxStatus());
+    }
+});
+
+// Returns the new token once; scripts using the old one are refused from now on
+ipcMain.handle('inbox:rotate-token', async () => {
+    try {
+        const token = generateToken();
+        writeJsonSetting('inbox.tokenHash', hashToken(token));
+        logSystemEvent('SIGNAL_INBOX_TOKEN_ROTATED');
+        // Companion connections authenticated with the old token are dropped
+        startCompanionServer().catch(() => {});
+        return { success: true, token, ...inboxStatus() };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// options: { symbol?, from?, to?, unreadOnly?, limit? }
+ipcMain.handle('inbox:list', async (event, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, signals: listSignals(db, options || {}) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('inbox:mark-read', async (event, ids = []) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, updated: markSignalsRead(db, Array.isArray(ids) ? ids : [ids]) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('inbox:delete', async (event, ids = []) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const deleted = deleteSignals(db, Array.isArray(ids) ? ids : [ids]);
+        broadcast('inbox:deleted', { ids });
+        return { success: true, deleted };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- COMPANION IPC ---
+// Setting 'companion.config': { enabled, path? } — a local socket for companion
+// processes (companionIpc.js), authenticated with the signal inbox token.
+const companionServer = createCompanionServer({
+    getTokenHash: () => readJsonSetting('inbox.tokenHash'),
+    pushBars: (provider, symbol, timeframe, bars, closed) => bars.forEach(bar => publishBar(provider, symbol, timeframe, bar, closed)),
+    acceptSignals: (signals) => acceptSignals(signals),
+    listAlerts: (symbol) => (alertEngine ? alertEngine.listAlerts(symbol) : []),
+    onLog: (level, message, data) => logSystemEvent(message, data, level)
+});
+
+const getCompanionConfig = () => ({ enabled: false, path: null, ...(readJsonSetting('companion.config') || {}) });
+
+const startCompanionServer = async () => {
+    const config = getCompanionConfig();
+    await companionServer.stop();
+    if (!config.enabled || readOnly) return null;
+    const target = config.path || defaultSocketPath(app.getPath('userData'));
+    try {
+        return await companionServer.start(target);
+    } catch (err) {
+        logSystemEvent('COMPANION_IPC_START_FAILED', { path: target, error: err.message }, 'ERROR');
+        throw err;
+    }
+};
+
+const companionStatus = () => ({ ...getCompanionConfig(), hasToken: !!readJsonSetting('inbox.tokenHash'), ...companionServer.status() });
+
+ipcMain.handle('companion:get-status', async () => companionStatus());
+
+ipcMain.handle('companion:set-config', async (event, patch = {}) => {
+    try {
+        const next = { ...getCompanionConfig(), ...patch };
+        next.enabled = !!next.enabled;
+        if (next.path != null && (typeof next.path !== 'string' || !next.path.trim())) return failure('INVALID_INPUT', 'path must be a socket path or null');
+        writeJsonSetting('companion.config', { enabled: next.enabled, path: next.path || null });
+        await startCompanionServer();
+        return { success: true, ...companionStatus() };
+    } catch (err) {
+        return errorResult(err, companionStatus());
+    }
+});
+
+// --- NEWS ---
+let newsService = null;
+
+const startNewsService = () => {
+    if (!db) return;
+    newsService = createNewsService({
+        db,
+        loadConfig: () => readJsonSetting('news.config'),
+        saveConfig: (config) => writeJsonSetting('news.config', config),
+        onItems: (items) => broadcast('news:new-items', items),
+        onLog: (level, message, data) => logSystemEvent(message, data, level),
+        getIntervalScale: backgroundPollScale
+    });
+    newsService.start();
+};
+
+ipcMain.handle('news:get', async (event, symbol = null, since = 0) => {
+    try {
+        if (!newsService) return failure('UNAVAILABLE', 'News service not initialized');
+        return { success: true, items: newsService.getNews(symbol, since) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('news:get-config', async () => newsService ? newsService.getConfig() : null);
+
+// Accepts any subset of { feeds, symbols, keywords, intervalMinutes, retentionDays }
+ipcMain.handle('news:configure', async (event, updates = {}) => {
+    try {
+        if (!newsService) return failure('UNAVAILABLE', 'News service not initialized');
+        const config = newsService.configure(updates);
+        logSystemEvent('NEWS_CONFIGURED', { feeds: config.feeds.length, symbols: config.symbols.length });
+        newsService.pollNow().catch(() => {});
+        return { success: true, config };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('news:refresh', async () => {
+    try {
+        if (!newsService) return failure('UNAVAILABLE', 'News service not initialized');
+        const items = await newsService.pollNow();
+        return { success: true, count: items.length };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- ALERTS ---
+let alertEngine = null;
+
+const buildAlertPayload = (alert, { price, level, values, timestamp, replay }) => ({
+    event: 'alert.triggered',
+    alertId: alert.id,
+    symbol: alert.symbol,
+    condition: alert.condition,
+    level: level ?? alert.price,
+    price,
+    message: alert.message,
+    ...(alert.options.expression ? { expression: alert.options.expression, values } : {}),
+    ...(alert.options.drawing ? { drawing: alert.options.drawing } : {}),
+    ...(replay ? { replay: true } : {}),
+    time: new Date(timestamp).toISOString(),
+    timestamp
+});
+
+// Global webhook from settings, overridable per alert via options.webhookUrl / options.webhook = false
+const dispatchAlertWebhook = async (alert, payload) => {
+    const global = readJsonSetting('alerts.webhook') || {};
+    if (alert.options.webhook === false) return;
+    const url = alert.options.webhookUrl || (global.enabled ? global.url : null);
+    if (!url) return;
+    const result = await postJson(url, payload, { headers: global.headers || {}, retries: global.retries });
+    if (result.success) {
+        logSystemEvent('ALERT_WEBHOOK_SENT', { alertId: alert.id, attempts: result.attempts });
+    } else {
+        logSystemEvent('ALERT_WEBHOOK_FAILED', { alertId: alert.id, error: result.error }, 'ERROR');
+    }
+};
+
+const notifiers = createNotifiers({
+    getSecret: createSecretScope(() => db, ['telegram', 'discord']),
+    loadConfig: () => readJsonSetting('notifiers')
+});
+
+const dispatchAlertNotifications = async (alert, payload) => {
+    if (alert.options.notify === false) return;
+    const results = await notifiers.notifyAll(formatAlertMessage(payload));
+    Object.entries(results).forEach(([channel, result]) => {
+        if (!result.success) logSystemEvent('ALERT_NOTIFY_FAILED', { alertId: alert.id, channel, error: result.error }, 'ERROR');
+    });
+};
+
+const handleAlertTriggered = (alert, hit) => {
+    const payload = buildAlertPayload(alert, hit);
+    // Replayed history only reaches the charts, not webhooks, chats or speakers
+    if (hit.replay) {
+        broadcast('alerts:triggered', payload);
+        return;
+    }
+    logSystemEvent('ALERT_TRIGGERED', payload);
+    broadcast('alerts:triggered', payload);
+    noteActivity('alert_fired', {
+        ref: alert.id,
+        symbol: alert.symbol,
+        title: alert.message || `${alert.symbol} ${alert.condition} ${payload.level ?? ''}`.trim(),
+        detail: { condition: alert.condition, level: payload.level, price: payload.price },
+        at: payload.timestamp || Date.now()
+    });
+    dispatchAlertWebhook(alert, payload).catch(() => {});
+    companionServer.publish('alerts', payload);
+    dispatchAlertNotifications(alert, payload).catch(() => {});
+    // Presenting: the screen is shared, so no sound (chat notifiers still go out)
+    if (alert.options.sound !== false && !presentation.isActive()) {
+        getAudioService().playSound(alert.options.sound || undefined).catch(() => {});
+    }
+};
+
+const startAlertEngine = () => {
+    if (!db) return;
+    alertEngine = createAlertEngine({
+        db,
+        onTrigger: handleAlertTriggered,
+        // Latest `limit` stored bars, up to toTime when given
+        loadBars: (symbol, timeframe, limit, toTime = null) => db.prepare(`SELECT timestamp, open, high, low, close, volume FROM market_data
+            WHERE symbol = ? COLLATE NOCASE AND timeframe = ?${toTime != null ? ' AND timestamp <= ?' : ''} ORDER BY timestamp DESC LIMIT ?`)
+            .all(...[symbol, timeframe, ...(toTime != null ? [toTime] : []), limit]).reverse(),
+        loadChartState: (sourceId) => readChartState(sourceId)
+    });
+    liveFeed.on('trade', (event) => alertEngine.evaluate(event.symbol, event.price, event.timestamp));
+    liveFeed.on('bar', (event) => {
+        alertEngine.evaluate(event.symbol, event.bar.close, Date.now());
+        alertEngine.evaluateBar(event);
+    });
+};
+
+ipcMain.handle('alerts:list', async (event, symbol = null) => {
+    try {
+        return alertEngine ? alertEngine.listAlerts(symbol) : [];
+    } catch (e) {
+        return [];
+    }
+});
+
+ipcMain.handle('alerts:save', async (event, alert) => {
+    try {
+        if (!alertEngine) return failure('UNAVAILABLE', 'Alert engine not initialized');
+        if (alert?.options?.webhookUrl) alert.options.webhookUrl = validateWebhookUrl(alert.options.webhookUrl);
+        return { success: true, alert: alertEngine.saveAlert(alert) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// Checks an alert (expression syntax, drawing reference) without saving it
+ipcMain.handle('alerts:validate', async (event, alert) => {
+    try {
+        if (!alertEngine) return failure('UNAVAILABLE', 'Alert engine not initialized');
+        return { success: true, valid: alertEngine.validateAlert(alert || {}) };
+    } catch (err) {
+        return { success: true, valid: false, error: err.message, position: err.position ?? null };
+    }
+});
+
+// Chart replay reached `timestamp` on symbol / timeframe; hits are also broadcast with replay: true
+ipcMain.handle('alerts:evaluate-replay', async (event, symbol, timeframe, timestamp) => {
+    try {
+        if (!alertEngine) return failure('UNAVAILABLE', 'Alert engine not initialized');
+        return { success: true, hits: alertEngine.evaluateReplay(symbol, timeframe, Number(timestamp)) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('alerts:delete', async (event, id) => {
+    try {
+        return { success: !!alertEngine && alertEngine.deleteAlert(id) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// Shareable alert files; ids = null exports every alert
+ipcMain.handle('alerts:export', async (event, filePath = null, ids = null) => {
+    try {
+        if (!alertEngine) return failure('UNAVAILABLE', 'Alert engine not initialized');
+        const document = alertEngine.exportAlerts(ids);
+        let target = filePath;
+        if (!target) {
+            const { canceled, filePath: chosen } = await dialog.showSaveDialog(mainWindow, {
+                defaultPath: `alerts-${new Date().toISOString().slice(0, 10)}.json`,
+                filters: [{ name: 'JSON', extensions: ['json'] }]
+            });
+            if (canceled || !chosen) return { success: false, canceled: true };
+            target = chosen;
+        }
+        fs.writeFileSync(target, JSON.stringify(document, null, 2));
+        logSystemEvent('ALERTS_EXPORTED', { count: document.alerts.length, file: path.basename(target) });
+        return { success: true, filePath: target, count: document.alerts.length };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// options: { symbolMap?, sourceMap?, disabled? } (see alerts.js importAlerts)
+ipcMain.handle('alerts:import', async (event, filePath = null, options = {}) => {
+    try {
+        if (!alertEngine) return failure('UNAVAILABLE', 'Alert engine not initialized');
+        let target = filePath;
+        if (!target) {
+            const { canceled, filePaths } = await dialog.showOpenDialog(mainWindow, {
+                properties: ['openFile'],
+                filters: [{ name: 'JSON', extensions: ['json'] }]
+            });
+            if (canceled || !filePaths.length) return { success: false, canceled: true };
+            target = filePaths[0];
+        }
+        const result = alertEngine.importAlerts(JSON.parse(fs.readFileSync(target, 'utf8')), options || {});
+        logSystemEvent('ALERTS_IMPORTED', { file: path.basename(target), imported: result.imported.length, duplicates: result.duplicates.length, rejected: result.rejected.length });
+        return { success: true, ...result };
+    } catch (err) {
+        logSystemEvent('ALERTS_IMPORT_FAILED', { error: err.message }, 'ERROR');
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('alerts:get-webhook-config', async () => readJsonSetting('alerts.webhook') || { enabled: false, url: '', headers: {}, retries: 3 });
+
+ipcMain.handle('alerts:set-webhook-config', async (event, config = {}) => {
+    try {
+        const next = { enabled: !!config.enabled, url: config.url ? validateWebhookUrl(config.url) : '', headers: config.headers || {}, retries: config.retries ?? 3 };
+        writeJsonSetting('alerts.webhook', next);
+        return { success: true, config: next };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// Notification channels: enable flags / chat id here, tokens via secrets:set
+ipcMain.handle('notifiers:get-config', async () => notifiers.getConfig());
+
+ipcMain.handle('notifiers:set-config', async (event, config = {}) => {
+    try {
+        const current = notifiers.getConfig();
+        const next = {};
+        CHANNELS.forEach((channel) => { next[channel] = { ...current[channel], ...(config[channel] || {}) }; });
+        writeJsonSetting('notifiers', next);
+        return { success: true, config: next };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('notifiers:send-test', async (event, channel) => {
+    try {
+        await notifiers.sendTest(channel);
+        logSystemEvent('NOTIFIER_TEST_SENT', { channel });
+        return { success: true };
+    } catch (err) {
+        logSystemEvent('NOTIFIER_TEST_FAILED', { channel, error: err.message }, 'WARN');
+        return errorResult(err);
+    }
+});
+
+// --- AUDIO ---
+let audioService = null;
+
+const soundsDirPath = () => path.join(app.getPath('userData'), 'sounds');
+
+// Created lazily: userData is only resolvable once the app is ready
+const getAudioService = () => {
+    if (!audioService) {
+        audioService = createAudioService({
+            soundsDir: soundsDirPath(),
+            onLog: (level, message, data) => logSystemEvent(message, data, level)
+        });
+    }
+    return audioService;
+};
+
+ipcMain.handle('audio:play', async (event, soundId) => {
+    try {
+        await getAudioService().playSound(soundId);
+        return { success: true };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('audio:list-sounds', async () => {
+    try {
+        return getAudioService().listSounds();
+    } catch (e) {
+        return [];
+    }
+});
+
+ipcMain.handle('audio:import-sound', async (event, filePath = null) => {
+    try {
+        let target = filePath;
+        if (!target) {
+            const { canceled, filePaths } = await dialog.showOpenDialog(mainWindow, {
+                properties: ['openFile'],
+                filters: [{ name: 'WAV Audio', extensions: ['wav'] }]
+            });
+            if (canceled || !filePaths.length) return { success: false, canceled: true };
+            target = filePaths[0];
+        }
+        const sound = getAudioService().importSound(target);
+        logSystemEvent('SOUND_IMPORTED', { id: sound.id });
+        return { success: true, sound };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('audio:delete-sound', async (event, soundId) => {
+    try {
+        return { success: getAudioService().deleteSound(soundId) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- COMPUTE ---
+// Indicator batches run on a worker pool; thread count lives in setting 'compute.config'
+let computePool = null;
+const MAX_BULK_JOBS = 5000;
+
+const loadComputeConfig = () => ({ threads: defaultThreadCount(), ...(readJsonSetting('compute.config') || {}) });
+
+const getComputePool = () => {
+    if (!computePool) {
+        let workerPath = path.join(__dirname, 'computeWorker.js');
+        if (!fs.existsSync(workerPath)) {
+            workerPath = path.join(app.getAppPath(), 'electron', 'computeWorker.js');
+        }
+        computePool = createComputePool({ workerPath, dbPath: dbPathGlobal, threads: loadComputeConfig().threads, batchOptions: () => ({ fastAggregate: fastAggregateEnabled() }) });
+    }
+    return computePool;
+};
+
+// Results persist across restarts in userData/compute-cache (see computeCache.js)
+const computeCacheDirPath = () => path.join(app.getPath('userData'), 'compute-cache');
+const computeCache = createComputeCache({
+    dir: computeCacheDirPath(),
+    getDb: () => db,
+    version: codeVersion(['computeWorker.js', 'indicators.js', 'expressions.js', 'seasonality.js'].map(f => path.join(__dirname, f)), app.getVersion()),
+    persist: () => !readOnly
+});
+
+const runCachedJobs = (jobs) => computeCache.runJobs(jobs, batch => getComputePool().run(batch));
+
+// jobs: [{ id?, datasetId, indicator: 'sma' | 'ema' | 'rsi' | 'atr' | 'stddev' | 'macd' | 'macd_signal' | 'macd_hist', params?, limit? }
+//        or { id?, datasetId, series: '<value expression>', limit? } for a custom series]
+ipcMain.handle('compute:indicators-bulk', async (event, jobs = []) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotReady'));
+        if (!Array.isArray(jobs) || jobs.length === 0) return { success: true, results: [] };
+        if (jobs.length > MAX_BULK_JOBS) return failure('INVALID_INPUT', `At most ${MAX_BULK_JOBS} jobs per call`);
+        const unknown = jobs.find(j => !j.series && !INDICATORS[j.indicator]);
+        if (unknown) return failure('NOT_FOUND', `Unknown indicator: ${unknown.indicator}`);
+        // Compile errors are reported once here instead of by every worker
+        const invalid = Array.from(new Set(jobs.filter(j => j.series).map(j => String(j.series))))
+            .map(source => ({ source, ...validateExpression(source, { condition: false }) }))
+            .find(v => !v.valid);
+        if (invalid) return failure('INVALID_INPUT', invalid.error, { position: invalid.position, expression: invalid.source });
+
+        const start = Date.now();
+        const normalized = jobs.map((j, i) => ({ ...j, id: j.id != null ? String(j.id) : String(i) }));
+        const results = await runCachedJobs(normalized);
+        const durationMs = Date.now() - start;
+        logSystemEvent('COMPUTE_BULK', { jobs: jobs.length, cached: results.filter(r => r.cached).length, failed: results.filter(r => !r.success).length, durationMs, threads: getComputePool().getSize() });
+        return { success: true, results, durationMs };
+    } catch (err) {
+        logSystemEvent('COMPUTE_BULK_FAILED', { error: err.message }, 'ERROR');
+        return errorResult(err);
+    }
+});
+
+// Streaming indicators: O(1) updates per live bar, pushed as 'compute:indicator-update'
+let indicatorStreams = null;
+
+const getIndicatorStreams = () => {
+    if (!indicatorStreams) {
+        indicatorStreams = createIndicatorStreams({ db, onUpdate: (update) => broadcast('compute:indicator-update', update) });
+    }
+    return indicatorStreams;
+};
+
+liveFeed.on('bar', (event) => { if (indicatorStreams) indicatorStreams.handleBar(event); });
+
+// indicator 'expression' with params { source } streams a custom series
+ipcMain.handle('compute:subscribe-indicator', async (event, datasetId, indicator, params = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotReady'));
+        if (!INDICATORS[indicator] && indicator !== 'expression') return failure('NOT_FOUND', `Unknown indicator: ${indicator}`);
+        return { success: true, ...getIndicatorStreams().subscribe(datasetId, indicator, params) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('compute:unsubscribe-indicator', async (event, subscriptionId) => {
+    try {
+        return { success: indicatorStreams ? indicatorStreams.unsubscribe(subscriptionId) : false };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- CROSSHAIR CONTEXT ---
+// Bar and indicator values under the cursor on every timeframe of a symbol;
+// subscriptions take cursor moves as 'crosshair:move' messages and push 'crosshair:context'
+const crosshairSenders = new Map(); // webContents id -> webContents
+const crosshair = createCrosshairService({
+    getDb: () => db,
+    onContext: (owner, context) => {
+        const contents = crosshairSenders.get(owner);
+        if (contents && !contents.isDestroyed()) contents.send('crosshair:context', context);
+    }
+});
+
+liveFeed.on('bar', (event) => crosshair.handleBar(event));
+
+// options: { timeframes?, indicators?: [{ id?, indicator, params? } | { id?, series }] or { [timeframe]: [...] } }
+ipcMain.handle('crosshair:get-context', async (event, symbol, timestamp = null, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotReady'));
+        return { success: true, context: crosshair.getContext(symbol, timestamp, options || {}) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// options: as above plus timestamp? (null follows the latest bar)
+ipcMain.handle('crosshair:subscribe', async (event, symbol, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotReady'));
+        const contents = event.sender;
+        if (!crosshairSenders.has(contents.id)) {
+            crosshairSenders.set(contents.id, contents);
+            const ownerId = contents.id;
+            contents.once('destroyed', () => { crosshair.dropOwner(ownerId); crosshairSenders.delete(ownerId); });
+        }
+        return { success: true, ...crosshair.subscribe(contents.id, symbol, options || {}) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.on('crosshair:move', (event, subscriptionId, timestamp) => { crosshair.move(subscriptionId, timestamp); });
+
+ipcMain.handle('crosshair:unsubscribe', async (event, subscriptionId) => {
+    try {
+        return { success: crosshair.unsubscribe(subscriptionId) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// Compile check for custom series ({ condition: false }); screens and alerts use scanner:validate-expression
+ipcMain.handle('compute:validate-series', async (event, source) => validateExpression(source, { condition: false }));
+
+ipcMain.handle('compute:get-config', async () => {
+    try {
+        return { ...loadComputeConfig(), maxThreads: os.cpus().length, pool: computePool ? computePool.stats() : null, streams: indicatorStreams ? indicatorStreams.list().length : 0 };
+    } catch (e) {
+        return { threads: defaultThreadCount(), maxThreads: os.cpus().length, pool: null };
+    }
+});
+
+ipcMain.handle('compute:set-config', async (event, config = {}) => {
+    try {
+        const threads = Math.floor(Number(config.threads));
+        if (!Number.isFinite(threads) || threads < 1 || threads > os.cpus().length) {
+            return failure('INVALID_INPUT', `threads must be between 1 and ${os.cpus().length}`);
+        }
+        writeJsonSetting('compute.config', { ...loadComputeConfig(), threads });
+        if (computePool) computePool.resize(threads);
+        return { success: true, config: loadComputeConfig() };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('compute:cache-stats', async () => {
+    try {
+        return { success: true, stats: computeCache.stats() };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// options: { datasetId?, kind?: 'indicator' | 'series' | 'scan' | 'seasonality', limit? }
+ipcMain.handle('compute:cache-list', async (event, options = {}) => {
+    try {
+        return { success: true, entries: computeCache.list(options || {}) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// options: { datasetId?, kind?, key?, stale? }; no options clears the whole cache
+ipcMain.handle('compute:cache-invalidate', async (event, options = {}) => {
+    try {
+        const opts = options || {};
+        if (opts.kind && !COMPUTE_CACHE_KINDS.includes(opts.kind)) return failure('INVALID_INPUT', `kind must be one of ${COMPUTE_CACHE_KINDS.join(', ')}`);
+        const removed = computeCache.invalidate(opts);
+        logSystemEvent('COMPUTE_CACHE_INVALIDATED', { ...opts, removed });
+        return { success: true, removed, stats: computeCache.stats() };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- GLOBAL SEARCH ---
+ipcMain.handle('search:global', async (event, query, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const results = globalSearch(db, query, { ...options, libraryFiles: internalLibraryStorage });
+        return { success: true, results };
+    } catch (err) {
+        logSystemEvent('GLOBAL_SEARCH_FAILED', { error: err.message }, 'ERROR');
+        return errorResult(err);
+    }
+});
+
+// --- QUICK SWITCHER ---
+// Ctrl+K: charts, symbols and library files ranked by match and frecency
+// (quickSwitch.js). Opening a chart counts by itself (drawings:get-state);
+// the renderer reports other opens with quick-switch:record.
+const quickSwitch = createQuickSwitch({ getDb: () => db, listFiles: () => internalLibraryStorage, persist: () => !readOnly });
+
+ipcMain.handle('quick-switch:get-candidates', async (event, prefix = '', options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, candidates: quickSwitch.getCandidates(prefix, options || {}) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('quick-switch:record', async (event, kind, ref) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, counted: quickSwitch.record(kind, ref) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('quick-switch:clear-history', async () => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, deleted: quickSwitch.clear() };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- ACTIVITY TIMELINE ---
+// Diary rows (activityLog.js) written where the events happen; a failed write
+// never fails the action that caused it. Nothing is recorded in read-only mode.
+const noteActivity = (kind, fields) => {
+    if (!db || readOnly) return;
+    try {
+        recordActivity(db, kind, fields);
+    } catch (err) {
+        logSystemEvent('ACTIVITY_RECORD_FAILED', { kind, error: err.message }, 'WARN');
+    }
+};
+
+// range: { from?, to? } or { day?: 'YYYY-MM-DD' } (today by default), plus { kinds?, symbol?, limit? }
+ipcMain.handle('activity:get-timeline', async (event, range = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const opts = range || {};
+        const unknown = (Array.isArray(opts.kinds) ? opts.kinds : []).find(k => !ACTIVITY_KINDS.includes(k));
+        if (unknown) return failure('INVALID_INPUT', `Unknown activity kind: ${unknown}`);
+        return { success: true, timeline: getActivityTimeline(db, opts) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// Ticker box: every searchable provider plus local datasets and symbol metadata
+const symbolSearch = createSymbolSearch({
+    getDb: () => db,
+    listSearchable: () => listProviders().filter(p => p.capabilities.search),
+    getProvider
+});
+
+// options: { providers?, assetClass?, limit?, local? }
+ipcMain.handle('symbols:search', async (event, query, options = {}) => {
+    try {
+        return { success: true, ...(await symbolSearch.search(query, options || {})) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- CLIPBOARD TICKERS ---
+// Setting 'clipboard.watch': { enabled, useProviders, pollMs }. A copied ticker that
+// the symbol store (or, with useProviders, a provider search) knows exactly is
+// announced as 'clipboard:symbol-copied' for an "open chart" prompt.
+const getClipboardConfig = () => normalizeClipboardConfig(readJsonSetting('clipboard.watch') || {});
+
+const compactTicker = (symbol) => String(symbol || '').toUpperCase().slice(String(symbol || '').lastIndexOf(':') + 1).replace(/[/\-]/g, '');
+
+const clipboardWatcher = createClipboardWatcher({
+    clipboard,
+    getConfig: () => readJsonSetting('clipboard.watch') || {},
+    shouldSkip: () => idleMonitor.state().locked,
+    resolve: async (candidate, config) => {
+        const { results } = await symbolSearch.search(candidate.symbol, { providers: config.useProviders ? null : [], local: true, limit: 10 });
+        const wanted = compactTicker(candidate.symbol);
+        return results.find(r => compactTicker(r.symbol) === wanted && (!candidate.exchange || String(r.exchange || '').toUpperCase() === candidate.exchange)) || null;
+    },
+    onSymbol: (match) => broadcast('clipboard:symbol-copied', match)
+});
+
+ipcMain.handle('clipboard:get-watch-config', async () => ({ ...getClipboardConfig(), running: clipboardWatcher.isRunning() }));
+
+ipcMain.handle('clipboard:set-watch-config', async (event, patch = {}) => {
+    try {
+        const next = normalizeClipboardConfig({ ...getClipboardConfig(), ...patch });
+        writeJsonSetting('clipboard.watch', next);
+        const running = clipboardWatcher.restart();
+        logSystemEvent('CLIPBOARD_WATCH_CONFIGURED', { enabled: next.enabled, useProviders: next.useProviders });
+        return { success: true, config: next, running };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// Text labels inside saved chart states; options: { sourceId?, limit? }
+ipcMain.handle('annotations:search', async (event, query, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, results: searchChartAnnotations(db, query, options) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('annotations:reindex', async () => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const charts = rebuildAnnotationIndex(db);
+        logSystemEvent('ANNOTATION_INDEX_REBUILT', { charts });
+        return { success: true, charts };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- DATASET ANNOTATIONS ---
+// Marks attached to (symbol, time) instead of a chart (see datasetAnnotations.js);
+// every change is broadcast so all charts of the symbol redraw them.
+const publishDatasetAnnotation = (action, annotation) => broadcast('dataset-annotations:changed', { action, symbol: annotation.symbol, annotation });
+
+// options: { from?, to?, kinds?, limit? }
+ipcMain.handle('dataset-annotations:list', async (event, symbol, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, annotations: listDatasetAnnotations(db, symbol, options || {}) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// The symbol's annotations snapped to the bars of one timeframe
+ipcMain.handle('dataset-annotations:project', async (event, symbol, timeframe, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, ...projectAnnotations(db, symbol, timeframe, options || {}) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('dataset-annotations:create', async (event, input) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const annotation = createDatasetAnnotation(db, input || {});
+        publishDatasetAnnotation('created', annotation);
+        return { success: true, annotation };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('dataset-annotations:update', async (event, id, patch) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const annotation = updateDatasetAnnotation(db, id, patch || {});
+        publishDatasetAnnotation('updated', annotation);
+        return { success: true, annotation };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('dataset-annotations:delete', async (event, id) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const annotation = getDatasetAnnotation(db, id);
+        if (!annotation) return failure('NOT_FOUND', `Annotation not found: ${id}`);
+        deleteDatasetAnnotation(db, id);
+        publishDatasetAnnotation('deleted', annotation);
+        return { success: true };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- TAIL-FIRST ACCESS ---
+ipcMain.handle('market:get-tail', async (event, filePath) => {
+    try {
+        const stats = fs.statSync(filePath);
+        const size = stats.size;
+        const chunkSize = 200 * 1024; // 200KB tail
+        const start = Math.max(0, size - chunkSize);
+        
+        return new Promise((resolve, reject) => {
+            fs.open(filePath, 'r', (err, fd) => {
+                if (err) return reject(err);
+                const buffer = Buffer.alloc(size - start);
+                fs.read(fd, buffer, 0, buffer.length, start, (err, bytesRead, b) => {
+                    fs.close(fd, () => {});
+                    if (err) return reject(err);
+                    const text = b.toString('utf8');
+                    resolve({ text }); 
+                });
+            });
+        });
+    } catch (e) {
+        return { error: e.message };
+    }
+});
+
+// options: { datasetId?, sessionLevels? } — with the dataset the chart shows, the
+// session level lines are brought up to date first when the 'charts.sessionLevels'
+// setting (or sessionLevels: true) asks for it
+ipcMain.handle('drawings:get-state', async (event, symbol, options = {}) => {
+    try {
+        if (options && options.datasetId && !readOnly && (options.sessionLevels ?? getSessionLevelConfig().enabled)) {
+            try {
+                syncSessionLevels(symbol, options.datasetId, {}, event.sender.id);
+            } catch (levelErr) {
+                logSystemEvent('SESSION_LEVELS_FAILED', { sourceId: symbol, datasetId: options.datasetId, error: levelErr.message }, 'WARN');
+            }
+        }
+        const stmt = db.prepare('SELECT data FROM drawings WHERE symbol = ?');
+        const row = stmt.get(symbol);
+        if (!readOnly) {
+            try {
+                quickSwitch.record('chart', symbol);
+                if (options && options.datasetId) quickSwitch.record('symbol', parseDatasetId(options.datasetId).symbol);
+            } catch (usageErr) {}
+        }
+        const isFile = /[\\/]/.test(String(symbol));
+        const chartDataset = (options && options.datasetId) || (!isFile && String(symbol).includes(':') ? String(symbol) : null);
+        noteActivity('chart_opened', {
+            ref: symbol,
+            symbol: chartDataset ? parseDatasetId(chartDataset).symbol : null,
+            title: isFile ? path.basename(String(symbol)) : String(symbol),
+            detail: options && options.datasetId ? { datasetId: options.datasetId } : null
+        });
+        return row ? JSON.parse(row.data) : null;
+    } catch (e) {
+        return null;
+    }
+});
+
+const readChartState = (sourceId) => {
+    const row = db.prepare('SELECT data FROM drawings WHERE symbol = ?').get(sourceId);
+    if (!row) return null;
+    try { return JSON.parse(row.data); } catch (e) { return null; }
+};
+
+// Validates, stores, journals and publishes a new state; shared by full saves and patches.
+// options.thumbnail: optional PNG (data URL or bytes) stored alongside the state
+// options.journal: false skips the undo journal (bulk restores, migrations)
+// Every save is validated (chartStateSchema.js); the normalized state is what gets stored
+const commitChartState = (symbol, input, previous, options, senderId) => {
+    const { state: data, report: validation } = validateChartState(input);
+    if (!validation.valid) {
+        logSystemEvent('CHART_STATE_REJECTED', { symbol, errors: validation.errors.length, first: validation.errors[0] }, 'WARN');
+        return failure('VALIDATION_FAILED', t('errors.chartStateInvalid', { count: validation.errors.length, path: validation.errors[0].path || 'state' }), { validation });
+    }
+    const stmt = db.prepare('INSERT OR REPLACE INTO drawings (symbol, data) VALUES (?, ?)');
+    stmt.run(symbol, JSON.stringify(data));
+    if (!previous) quickSwitch.invalidate();
+    if (previous && !(options && options.journal === false)) {
+        try { recordChartEdit(db, symbol, previous, data); } catch (journalErr) { logSystemEvent('JOURNAL_WRITE_FAILED', { symbol, error: journalErr.message }, 'WARN'); }
+    }
+    publishDrawingChanges(symbol, previous, data, senderId);
+    if (options && options.thumbnail) {
+        try {
+            saveThumbnail(db, symbol, options.thumbnail);
+        } catch (thumbErr) {
+            // The state itself is saved; a bad preview shouldn't fail the save
+            logSystemEvent('THUMBNAIL_REJECTED', { symbol, error: thumbErr.message }, 'WARN');
+            return { success: true, thumbnailError: thumbErr.message, validation };
+        }
+    }
+    return { success: true, validation };
+};
+
+ipcMain.handle('drawings:save-state', async (event, symbol, input, options = {}) => {
+    try {
+        return commitChartState(symbol, input, readChartState(symbol), options, event.sender.id);
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// ops: JSON Patch operations (chartStatePatch.js) applied to the stored state, so a
+// small edit doesn't resend the whole chart. With nothing stored yet the renderer
+// falls back to a full save (needsFullSave); a failed 'test' op reports a conflict,
+// and moving or deleting a locked drawing reports the drawings in `locked`.
+const patchChartState = (sourceId, buildOps, options, senderId) => {
+    const previous = readChartState(sourceId);
+    if (!previous) return failure('NOT_FOUND', 'No stored chart state to patch', { needsFullSave: true });
+    const ops = buildOps(previous);
+    let patched;
+    try {
+        patched = applyChartPatch(previous, ops);
+    } catch (patchErr) {
+        if (patchErr.code !== 'PATCH_FAILED') throw patchErr;
+        if (patchErr.locked) {
+            logSystemEvent('CHART_PATCH_LOCKED', { sourceId, drawings: patchErr.locked.length }, 'INFO');
+            return failure('LOCKED', patchErr.message, { locked: patchErr.locked });
+        }
+        const conflict = Array.isArray(ops) && ops[patchErr.index] && ops[patchErr.index].op === 'test';
+        return failure('CONFLICT', patchErr.message, { index: patchErr.index, conflict });
+    }
+    return { ...commitChartState(sourceId, patched, previous, options, senderId), ops: ops.length };
+};
+
+ipcMain.handle('drawings:patch-state', async (event, sourceId, ops, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return patchChartState(sourceId, () => ops, options, event.sender.id);
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('drawings:set-locked', async (event, sourceId, drawingIds, locked) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        if (!Array.isArray(drawingIds) || !drawingIds.length) return failure('INVALID_INPUT', 'drawingIds must be a non-empty array');
+        return patchChartState(sourceId, state => lockOps(state, drawingIds, locked), {}, event.sender.id);
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// group: { id, name?, locked? } (created if missing), or null to ungroup
+ipcMain.handle('drawings:set-group', async (event, sourceId, drawingIds, group) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        if (!Array.isArray(drawingIds)) return failure('INVALID_INPUT', 'drawingIds must be an array');
+        if (group && (group.id == null || group.id === '')) return failure('INVALID_INPUT', 'Group needs an id');
+        return patchChartState(sourceId, state => groupOps(state, drawingIds, group), {}, event.sender.id);
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// Locks a whole group (or unlocks it); its drawings follow the group's flag
+ipcMain.handle('drawings:set-group-locked', async (event, sourceId, groupId, locked) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return patchChartState(sourceId, (state) => {
+            const exists = Array.isArray(state.groups) && state.groups.some(g => g && String(g.id) === String(groupId));
+            if (!exists) throw new Error(`No group ${groupId}`);
+            return [{ op: 'add', path: pointer('groups', `@${groupId}`, 'locked'), value: Boolean(locked) }];
+        }, {}, event.sender.id);
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- UNDO / REDO JOURNAL ---
+ipcMain.handle('drawings:undo', async (event, sourceId) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const previous = readChartState(sourceId);
+        const result = undoChartEdit(db, sourceId);
+        if (result) publishDrawingChanges(sourceId, previous, result.state, event.sender.id);
+        return result ? { success: true, ...result, ...journalStatus(db, sourceId) } : failure('NOT_FOUND', 'Nothing to undo', { ...journalStatus(db, sourceId) });
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('drawings:redo', async (event, sourceId) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const previous = readChartState(sourceId);
+        const result = redoChartEdit(db, sourceId);
+        if (result) publishDrawingChanges(sourceId, previous, result.state, event.sender.id);
+        return result ? { success: true, ...result, ...journalStatus(db, sourceId) } : failure('NOT_FOUND', 'Nothing to redo', { ...journalStatus(db, sourceId) });
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// options: { limit?, full? } — full includes before/after snapshots, for backups and versioning
+ipcMain.handle('drawings:get-journal', async (event, sourceId, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, entries: listChartJournal(db, sourceId, options), ...journalStatus(db, sourceId) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- CROSS-WINDOW SYNC ---
+// Drawing and note changes are published by their handlers; renderer-owned
+// state (layout, selection) goes through sync:publish. Windows compare each
+// event's seq with the last one they saw and call sync:get-since on a gap.
+ipcMain.handle('sync:publish', async (event, topic, payload) => {
+    try {
+        if (typeof topic !== 'string' || !topic.trim()) return failure('INVALID_INPUT', 'Topic is required');
+        if (topic === 'drawings' || topic === 'notes') return failure('INVALID_INPUT', `The ${topic} topic is published by the backend`);
+        const envelope = syncBus.publish(topic.trim(), payload, event.sender.id);
+        return { success: true, seq: envelope.seq };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('sync:get-since', async (event, seq = 0, topics = null) => {
+    try {
+        return { success: true, windowId: event.sender.id, ...syncBus.since(Number(seq) || 0, topics) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// windowId is this window's webContents id, matching `origin` on its own events
+ipcMain.handle('sync:get-seq', async (event) => ({ seq: syncBus.currentSeq(), windowId: event.sender.id }));
+
+ipcMain.handle('drawings:get-thumbnail', async (event, sourceId) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotReady'));
+        const thumbnail = getThumbnail(db, sourceId);
+        return thumbnail ? { success: true, ...thumbnail } : failure('NOT_FOUND', 'No thumbnail');
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('drawings:delete-all', async (event, sourceId) => {
+    try {
+        const previous = readChartState(sourceId);
+        const stmt = db.prepare('DELETE FROM drawings WHERE symbol = ?');
+        stmt.run(sourceId);
+        deleteThumbnail(db, sourceId);
+        clearChartJournal(db, sourceId);
+        publishDrawingChanges(sourceId, previous, { drawings: [], folders: [] }, event.sender.id);
+        return { success: true };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('drawings:import-tradingview', async (event, filePath = null) => {
+    try {
+        let target = filePath;
+        if (!target) {
+            const { canceled, filePaths } = await dialog.showOpenDialog(mainWindow, {
+                properties: ['openFile'],
+                filters: [{ name: 'TradingView Export', extensions: ['json', 'txt'] }]
+            });
+            if (canceled || !filePaths.length) return { success: false, canceled: true };
+            target = filePaths[0];
+        }
+        const result = convertTradingViewLayout(fs.readFileSync(target, 'utf8'));
+        logSystemEvent('TRADINGVIEW_IMPORT', { file: path.basename(target), imported: result.drawings.length, skipped: result.skipped });
+        return { success: true, ...result };
+    } catch (err) {
+        logSystemEvent('TRADINGVIEW_IMPORT_FAILED', { error: err.message }, 'ERROR');
+        return errorResult(err);
+    }
+});
+
+// Exports brush (ink) drawings of a chart as SVG or PNG; drawingIds = null exports every stroke
+ipcMain.handle('drawings:export-ink', async (event, symbol, drawingIds = null, format = 'svg', filePath = null, options = {}) => {
+    try {
+        if (format !== 'svg' && format !== 'png') return failure('UNSUPPORTED', `Unsupported format: ${format}`);
+        const row = db.prepare('SELECT data FROM drawings WHERE symbol = ?').get(symbol);
+        if (!row) return failure('NOT_FOUND', `No chart state for ${symbol}`);
+        const { drawings = [] } = JSON.parse(row.data);
+        const selected = drawingIds ? drawings.filter(d => drawingIds.includes(d.id)) : drawings;
+
+        const { svg, width, height } = renderInkSvg(selected, options);
+
+        let target = filePath;
+        if (!target) {
+            const { canceled, filePath: chosen } = await dialog.showSaveDialog(mainWindow, {
+                defaultPath: `${symbol}-ink.${format}`,
+                filters: [{ name: format.toUpperCase(), extensions: [format] }]
+            });
+            if (canceled || !chosen) return { success: false, canceled: true };
+            target = chosen;
+        }
+
+        fs.writeFileSync(target, format === 'svg' ? svg : await rasterizeSvg(svg, width, height));
+        logSystemEvent('INK_EXPORTED', { symbol, format, strokes: selected.length, file: path.basename(target) });
+        return { success: true, filePath: target, width, height };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// Exports a sticky note's handwritten ink as SVG or PNG at options.scale
+ipcMain.handle('notes:export-ink', async (event, noteId, format = 'svg', filePath = null, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        if (format !== 'svg' && format !== 'png') return failure('UNSUPPORTED', `Unsupported format: ${format}`);
+        const note = getStickyNote(db, noteId);
+        if (!note) return failure('NOT_FOUND', `Note not found: ${noteId}`);
+        const { svg, width, height } = renderNoteInkSvg(note.inkData, options || {});
+
+        let target = filePath;
+        if (!target) {
+            const { canceled, filePath: chosen } = await dialog.showSaveDialog(mainWindow, {
+                defaultPath: `note-ink-${String(noteId).slice(0, 8)}.${format}`,
+                filters: [{ name: format.toUpperCase(), extensions: [format] }]
+            });
+            if (canceled || !chosen) return { success: false, canceled: true };
+            target = chosen;
+        }
+
+        fs.writeFileSync(target, format === 'svg' ? svg : await rasterizeSvg(svg, width, height));
+        logSystemEvent('INK_EXPORTED', { noteId, format, strokes: note.inkData.length, file: path.basename(target) });
+        return { success: true, filePath: target, width, height };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// Exports a chart's drawings as the open 'redpill' schema or a TradingView-style drawing export
+ipcMain.handle('drawings:export-state', async (event, sourceId, format = 'redpill', filePath = null) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const row = db.prepare('SELECT data FROM drawings WHERE symbol = ?').get(sourceId);
+        if (!row) return failure('NOT_FOUND', `No chart state for ${sourceId}`);
+        const { document, exported, skipped } = exportChartState(sourceId, JSON.parse(row.data), format);
+
+        let target = filePath;
+        if (!target) {
+            const { canceled, filePath: chosen } = await dialog.showSaveDialog(mainWindow, {
+                defaultPath: `${sourceId.replace(/[^A-Za-z0-9._-]+/g, '_')}-drawings${format === 'tradingview' ? '-tradingview' : ''}.json`,
+                filters: [{ name: 'JSON', extensions: ['json'] }]
+            });
+            if (canceled || !chosen) return { success: false, canceled: true };
+            target = chosen;
+        }
+
+        fs.writeFileSync(target, JSON.stringify(document, null, 2));
+        logSystemEvent('CHART_STATE_EXPORTED', { sourceId, format, exported, skipped, file: path.basename(target) });
+        return { success: true, filePath: target, exported, skipped };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- CHART SHARING ---
+// share:export-chart bundles a chart with its thumbnail and the bars around its
+// drawings into an encrypted .rpshare file (chartShare.js); share:open reads one
+// back for read-only viewing. options: { password?, filePath?, datasetIds?
+// (default: the datasets of the chart's symbol), padBars?, maxBars? }
+ipcMain.handle('share:export-chart', async (event, sourceId, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const state = readChartState(sourceId);
+        if (!state) return failure('NOT_FOUND', `No chart state for ${sourceId}`);
+        const datasetIds = Array.isArray(options.datasetIds) ? options.datasetIds : findDatasetsBySymbol(db, sourceId).map(d => d.id);
+        const datasets = collectDatasetSlices(db, datasetIds, state, { padBars: options.padBars, maxBars: options.maxBars });
+        const thumbnail = getThumbnail(db, sourceId);
+
+        let target = options.filePath;
+        if (!target) {
+            const { canceled, filePath: chosen } = await dialog.showSaveDialog(mainWindow, {
+                defaultPath: `${sourceId.replace(/[^A-Za-z0-9._-]+/g, '_')}.rpshare`,
+                filters: [{ name: 'Shared Chart', extensions: ['rpshare'] }]
+            });
+            if (canceled || !chosen) return { success: false, canceled: true };
+            target = chosen;
+        }
+
+        const bundle = encodeShare({
+            sourceId,
+            appVersion: app.getVersion(),
+            state,
+            thumbnail: thumbnail ? { dataUrl: thumbnail.dataUrl, width: thumbnail.width, height: thumbnail.height } : null,
+            datasets
+        }, options.password || null);
+        fs.writeFileSync(target, bundle);
+        const bars = datasets.reduce((sum, d) => sum + d.bars.length, 0);
+        logSystemEvent('CHART_SHARED', { sourceId, datasets: datasets.length, bars, protected: !!options.password, file: path.basename(target) });
+        return { success: true, filePath: target, bytes: bundle.length, datasets: datasets.map(d => ({ id: d.id, bars: d.bars.length })), protected: !!options.password };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// A protected share opened without (or with the wrong) password fails with
+// PERMISSION_DENIED and details.passwordRequired, for the renderer to ask again
+ipcMain.handle('share:open', async (event, filePath = null, password = null) => {
+    try {
+        let target = filePath;
+        if (!target) {
+            const { canceled, filePaths } = await dialog.showOpenDialog(mainWindow, {
+                properties: ['openFile'],
+                filters: [{ name: 'Shared Chart', extensions: ['rpshare'] }]
+            });
+            if (canceled || !filePaths.length) return { success: false, canceled: true };
+            target = filePaths[0];
+        }
+        const { protected: isProtected, payload } = decodeShare(fs.readFileSync(target), password);
+        const { state, report: validation } = validateChartState(payload.state);
+        if (!validation.valid) return failure('VALIDATION_FAILED', t('errors.chartStateInvalid', { count: validation.errors.length, path: validation.errors[0].path || 'state' }), { validation });
+        let thumbnail = null;
+        if (payload.thumbnail && payload.thumbnail.dataUrl) {
+            try { decodeThumbnail(payload.thumbnail.dataUrl); thumbnail = payload.thumbnail; } catch (thumbErr) { thumbnail = null; }
+        }
+        const datasets = (Array.isArray(payload.datasets) ? payload.datasets : [])
+            .filter(d => d && typeof d.id === 'string' && TIMEFRAME_MS[d.timeframe] && Array.isArray(d.bars))
+            .map(d => ({ id: d.id, symbol: String(d.symbol), timeframe: d.timeframe, bars: d.bars.filter(b => Array.isArray(b) && b.length >= 5 && b.slice(0, 5).every(Number.isFinite)).map(b => ({ timestamp: b[0], open: b[1], high: b[2], low: b[3], close: b[4], volume: Number.isFinite(b[5]) ? b[5] : 0 })) }));
+        logSystemEvent('SHARED_CHART_OPENED', { sourceId: payload.sourceId, datasets: datasets.length, protected: isProtected, file: path.basename(target) });
+        return {
+            success: true,
+            share: { filePath: target, sourceId: payload.sourceId, createdAt: payload.createdAt, appVersion: payload.appVersion || null, protected: isProtected, readOnly: true, state, thumbnail, datasets }
+        };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('master-drawings:load', async () => {
+    try {
+        const stmt = db.prepare('SELECT symbol, data FROM drawings');
+        const rows = stmt.all();
+        const map = {};
+        rows.forEach(row => { map[row.symbol] = JSON.parse(row.data); });
+        return { success: true, data: map };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('trades:get-ledger', async (event, sourceId) => {
+    try {
+        const stmt = db.prepare('SELECT data FROM trades WHERE sourceId = ? ORDER BY timestamp DESC');
+        const rows = stmt.all(sourceId);
+        return rows.map(r => JSON.parse(r.data));
+    } catch (e) {
+        return [];
+    }
+});
+
+const prepareJournalTrade = async (trade) => {
+    // Closed trades without a P&L get one with the symbol's point value, like paper trading
+    if (trade.exitPrice != null && trade.pnl == null && trade.symbol) {
+        trade = { ...trade, pnl: positionPnl(getSymbolMeta(db, trade.symbol), trade.side === 'sell' ? -trade.qty : trade.qty, trade.price, trade.exitPrice) };
+    }
+    // P&L in a foreign currency is also kept in the base currency at the rate of the trade's day
+    const currency = trade.currency || (trade.symbol ? getSymbolMeta(db, trade.symbol).currency : null);
+    const baseCurrency = fxService.baseCurrency();
+    if (trade.pnl != null && currency && currency !== baseCurrency) {
+        try {
+            const fx = await fxService.getRate(currency, baseCurrency, trade.exitTimestamp || trade.timestamp);
+            trade = { ...trade, currency, baseCurrency, fxRate: fx.rate, pnlBase: trade.pnl * fx.rate };
+        } catch (e) {
+            logSystemEvent('TRADE_FX_MISSING', { id: trade.id, currency, error: e.message }, 'WARN');
+        }
+    }
+    return trade;
+};
+
+ipcMain.handle('trades:save', async (event, trade) => {
+    try {
+        trade = await prepareJournalTrade(trade);
+        const stmt = db.prepare('INSERT INTO trades (id, sourceId, data, timestamp) VALUES (?, ?, ?, ?)');
+        stmt.run(trade.id, trade.sourceId, JSON.stringify(trade), trade.timestamp);
+        logSystemEvent('TRADE_SAVED', { id: trade.id });
+        noteActivity('trade_logged', {
+            ref: trade.id,
+            symbol: trade.symbol || null,
+            title: [trade.side, trade.qty, trade.symbol, trade.price != null ? `@ ${trade.price}` : null].filter(v => v != null && v !== '').join(' '),
+            detail: { side: trade.side || null, qty: trade.qty ?? null, price: trade.price ?? null, pnl: trade.pnl ?? null }
+        });
+        return { success: true };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// Broker statement exports (brokerStatements.js) -> journal trades.
+// options: { broker?: 'auto' | 'ibkr' | 'generic', timezone?, sourceId?, dryRun? }
+ipcMain.handle('trades:import-statement', async (event, filePath, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotReady'));
+        const text = await fs.promises.readFile(filePath, 'utf8');
+        const existing = db.prepare('SELECT data FROM trades').all().map(r => JSON.parse(r.data));
+        const plan = planStatementImport(text, existing, {
+            broker: options.broker || 'auto',
+            timezone: options.timezone || null,
+            sourceId: options.sourceId || null,
+            multiplierFor: symbol => pointValue(getSymbolMeta(db, symbol))
+        });
+        const summary = { broker: plan.broker, fills: plan.fills, duplicateFills: plan.duplicateFills, likelyDuplicates: plan.likelyDuplicates, skipped: plan.skipped };
+        if (options.dryRun) return { success: true, ...summary, trades: plan.trades, updated: plan.updated };
+        const added = await Promise.all(plan.trades.map(prepareJournalTrade));
+        const updated = await Promise.all(plan.updated.map(prepareJournalTrade));
+        const insert = db.prepare('INSERT OR IGNORE INTO trades (id, sourceId, data, timestamp) VALUES (?, ?, ?, ?)');
+        const replace = db.prepare('INSERT OR REPLACE INTO trades (id, sourceId, data, timestamp) VALUES (?, ?, ?, ?)');
+        db.transaction(() => {
+            added.forEach(trade => insert.run(trade.id, trade.sourceId, JSON.stringify(trade), trade.timestamp));
+            updated.forEach(trade => replace.run(trade.id, trade.sourceId, JSON.stringify(trade), trade.timestamp));
+        })();
+        logSystemEvent('STATEMENT_IMPORTED', { file: path.basename(filePath), broker: plan.broker, fills: plan.fills, added: added.length, updated: updated.length, duplicateFills: plan.duplicateFills, likelyDuplicates: plan.likelyDuplicates.length });
+        if (added.length || updated.length) {
+            noteActivity('trades_imported', {
+                ref: path.basename(filePath),
+                title: `${added.length} trades added, ${updated.length} updated from ${path.basename(filePath)}`,
+                detail: { broker: plan.broker, fills: plan.fills, added: added.length, updated: updated.length }
+            });
+        }
+        return { success: true, ...summary, added: added.length, updated: updated.length, trades: added };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- PAPER TRADING ---
+// Simulated orders fill against live ticks or prices pushed by chart playback
+// ('replay'); fills are broadcast so charts can annotate them.
+let simEngine = null;
+
+const startSimEngine = () => {
+    if (!db) return;
+    simEngine = createSimEngine({
+        db,
+        getPointValue: (symbol) => pointValue(getSymbolMeta(db, symbol)),
+        onOrder: (order) => broadcast('sim:order', order),
+        onFill: (event) => {
+            logSystemEvent('SIM_FILL', { symbol: event.fill.symbol, side: event.fill.side, qty: event.fill.qty, price: event.fill.price, source: event.fill.source });
+            broadcast('sim:fill', event);
+        }
+    });
+    liveFeed.on('trade', (event) => simEngine.updatePrice(event.symbol, event.price, event.timestamp, 'live'));
+    liveFeed.on('bar', (event) => simEngine.updatePrice(event.symbol, event.bar.close, Date.now(), 'live'));
+};
+
+const simUnavailable = () => (failure('UNAVAILABLE', t('errors.databaseNotInitialized')));
+
+ipcMain.handle('sim:place-order', async (event, order) => {
+    try {
+        if (!simEngine) return simUnavailable();
+        return { success: true, order: simEngine.placeOrder(order) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('sim:cancel-order', async (event, id) => {
+    try {
+        if (!simEngine) return simUnavailable();
+        return { success: true, order: simEngine.cancelOrder(id) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// Replay playback pushes its current price here; only 'replay' orders react
+ipcMain.handle('sim:push-price', async (event, symbol, price, timestamp) => {
+    try {
+        if (!simEngine) return simUnavailable();
+        return { success: true, fills: simEngine.updatePrice(symbol, Number(price), Number(timestamp), 'replay') };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('sim:list-orders', async (event, options = {}) => {
+    try {
+        if (!simEngine) return simUnavailable();
+        return { success: true, orders: simEngine.listOrders(options) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('sim:list-fills', async (event, options = {}) => {
+    try {
+        if (!simEngine) return simUnavailable();
+        return { success: true, fills: simEngine.listFills(options) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('sim:get-account', async (event, id) => {
+    try {
+        if (!simEngine) return simUnavailable();
+        return { success: true, account: simEngine.getAccountSummary(id || undefined) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('sim:list-accounts', async () => {
+    try {
+        if (!simEngine) return simUnavailable();
+        return { success: true, accounts: simEngine.listAccounts() };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('sim:create-account', async (event, options = {}) => {
+    try {
+        if (!simEngine) return simUnavailable();
+        return { success: true, account: simEngine.createAccount(options) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// settings: { slippageBps, latencyMs, commissionPerUnit, commissionPct, minCommission }
+ipcMain.handle('sim:update-settings', async (event, id, settings = {}) => {
+    try {
+        if (!simEngine) return simUnavailable();
+        return { success: true, account: simEngine.updateSettings(id, settings) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('sim:reset-account', async (event, id, startingCash = null) => {
+    try {
+        if (!simEngine) return simUnavailable();
+        const account = simEngine.resetAccount(id || undefined, startingCash);
+        logSystemEvent('SIM_ACCOUNT_RESET', { id: account.id, cash: account.cash });
+        return { success: true, account };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- POSITION SIZING ---
+// Symbol metadata and the sizing calculator; the same point values drive the
+// journal's P&L and paper trading (see positionSizing.js).
+ipcMain.handle('symbols:get-meta', async (event, symbol) => {
+    try {
+        return { success: true, meta: getSymbolMeta(db, symbol) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('symbols:list-meta', async () => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, items: listSymbolMeta(db) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// meta: { assetClass, tickSize, tickValue, multiplier, currency, qtyStep, minQty } (any subset)
+ipcMain.handle('symbols:set-meta', async (event, symbol, meta = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const saved = saveSymbolMeta(db, symbol, meta);
+        segmentClassifiers.clear();
+        logSystemEvent('SYMBOL_META_SAVED', { symbol: saved.symbol });
+        return { success: true, meta: saved };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('symbols:delete-meta', async (event, symbol) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const deleted = deleteSymbolMeta(db, symbol);
+        segmentClassifiers.clear();
+        return { success: true, deleted, meta: getSymbolMeta(db, symbol) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- SYMBOL DOSSIERS ---
+// Per-symbol research (thesis, key levels, links, files; see symbolDossier.js).
+// Changes are broadcast so an open dossier in another window refreshes.
+ipcMain.handle('dossiers:get', async (event, symbol) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, dossier: getDossier(db, symbol) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('dossiers:list', async () => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, dossiers: listDossiers(db) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// options: { expectedUpdatedAt? } — the dossier's updatedAt when the editor opened it
+ipcMain.handle('dossiers:update', async (event, symbol, patch = {}, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const dossier = updateDossier(db, symbol, patch || {}, options || {});
+        broadcast('dossiers:changed', { symbol: dossier.symbol, dossier });
+        return { success: true, dossier };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('dossiers:delete', async (event, symbol) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const deleted = deleteDossier(db, symbol);
+        const dossier = getDossier(db, symbol);
+        if (deleted) broadcast('dossiers:changed', { symbol: dossier.symbol, dossier });
+        return { success: true, deleted };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+/**
+ * request: { account, riskPct, entry, stop, symbol, fxRate?, targets? }
+ * account: a paper account id, an equity amount, or { equity, currency }.
+ * Without fxRate, a foreign-currency symbol uses the current FX rate.
+ */
+ipcMain.handle('risk:calc-position-size', async (event, request = {}) => {
+    try {
+        let { account } = request;
+        let equity;
+        let accountCurrency = 'USD';
+        if (typeof account === 'string') {
+            if (!simEngine) return simUnavailable();
+            const summary = simEngine.getAccountSummary(account);
+            equity = summary.equity;
+            accountCurrency = summary.currency;
+        } else if (account && typeof account === 'object') {
+            equity = account.equity;
+            accountCurrency = account.currency || accountCurrency;
+        } else {
+            equity = account;
+        }
+        const meta = getSymbolMeta(db, request.symbol);
+        let { fxRate } = request;
+        if (fxRate == null && meta.currency && meta.currency !== String(accountCurrency).toUpperCase()) {
+            fxRate = (await fxService.getRate(meta.currency, accountCurrency)).rate;
+        }
+        const result = calcPositionSize({
+            equity,
+            accountCurrency,
+            riskPct: request.riskPct,
+            entry: request.entry,
+            stop: request.stop,
+            meta,
+            fxRate,
+            targets: request.targets
+        });
+        return { success: true, ...result };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- PORTFOLIO ANALYTICS ---
+// Trades are fetched first so missing FX rates for their days can be fetched
+// before the (synchronous) statistics convert them into the base currency.
+const tradesInBase = async (filter = {}) => {
+    const baseCurrency = filter.baseCurrency ? normalizeCurrency(filter.baseCurrency) : fxService.baseCurrency();
+    const trades = collectTrades(db, filter);
+    await fxService.prefetch(trades.map(trade => ({ from: trade.currency, to: baseCurrency, timestamp: trade.timestamp })));
+    return { baseCurrency, trades };
+};
+
+const portfolioStats = async (filter = {}) => {
+    const { baseCurrency } = await tradesInBase(filter);
+    return getPortfolioStats(db, filter, { baseCurrency, rateFor: fxService.rateFor });
+};
+
+// filter: { sources?: ['journal', 'sim'], accountId?, sourceId?, mode?, symbols?, tags?, from?, to?, startingEquity?, baseCurrency? }
+ipcMain.handle('portfolio:get-stats', async (event, filter = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, stats: await portfolioStats(filter || {}) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+/**
+ * input: { filter } (journal / paper trades, see portfolio:get-stats), { pnls: number[] } or { trades: [{ pnl }] }.
+ * params: { method: 'bootstrap' | 'shuffle', tradesPerRun?, startingEquity?, ruinDrawdownPct?, percentiles?, seed? }
+ */
+ipcMain.handle('portfolio:monte-carlo', async (event, input = {}, iterations = 5000, params = {}) => {
+    try {
+        let source = input || {};
+        if (!source.pnls && !source.trades) {
+            if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+            const { baseCurrency, trades } = await tradesInBase(source.filter || {});
+            source = { trades: convertTrades(trades, baseCurrency, fxService.rateFor).trades };
+        }
+        let workerPath = path.join(__dirname, 'monteCarlo.js');
+        if (!fs.existsSync(workerPath)) {
+             workerPath = path.join(app.getAppPath(), 'electron', 'monteCarlo.js');
+        }
+        const outcome = await new Promise((resolve, reject) => {
+            const worker = new Worker(workerPath, { workerData: { monteCarlo: { input: source, iterations, params: params || {} } } });
+            worker.once('message', (message) => { worker.terminate(); resolve(message); });
+            worker.once('error', reject);
+        });
+        if (!outcome.success) return outcome;
+        return { success: true, result: outcome.result };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// Prints the report page offscreen; options: { filePath?, title?, currency? }
+ipcMain.handle('portfolio:export-report', async (event, filter = {}, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        let target = options.filePath;
+        if (!target) {
+            const { canceled, filePath: chosen } = await dialog.showSaveDialog(mainWindow, {
+                defaultPath: `portfolio-report-${new Date().toISOString().slice(0, 10)}.pdf`,
+                filters: [{ name: 'PDF', extensions: ['pdf'] }]
+            });
+            if (canceled || !chosen) return { success: false, canceled: true };
+            target = chosen;
+        }
+        const stats = await portfolioStats(filter || {});
+        const html = renderPortfolioReportHtml(stats, { title: options.title, currency: options.currency || stats.baseCurrency });
+        const win = new BrowserWindow({ show: false, webPreferences: { offscreen: true, javascript: false } });
+        try {
+            await win.loadURL(`data:text/html;charset=utf-8,${encodeURIComponent(html)}`);
+            const pdf = await win.webContents.printToPDF({ printBackground: true, pageSize: 'A4' });
+            fs.writeFileSync(target, pdf);
+        } finally {
+            win.destroy();
+        }
+        logSystemEvent('PORTFOLIO_REPORT_EXPORTED', { filePath: target, trades: stats.trades });
+        return { success: true, filePath: target };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- LIVE EXPORT ---
+// Setting 'liveExports': the export definitions (liveExport.js). Series tables
+// read the bar store and end with the forming live bar, which the recorder only
+// stores once it closes.
+const latestLiveBars = new Map(); // 'symbol|timeframe' -> bar
+liveFeed.on('bar', (event) => latestLiveBars.set(`${String(event.symbol).toUpperCase()}|${event.timeframe}`, event.bar));
+
+const buildLiveExportTable = (source) => {
+    if (source.kind === 'positions') {
+        if (!simEngine) throw new Error('Paper trading is not initialized');
+        const positions = simEngine.listPositions(source.accountId || undefined).filter(p => p.qty !== 0);
+        return {
+            name: source.name,
+            columns: ['Symbol', 'Qty', 'Avg Price', 'Mark', 'Unrealized PnL', 'Realized PnL', 'Updated'],
+            rows: positions.map(p => [p.symbol, p.qty, p.avgPrice, p.markPrice, p.unrealizedPnl, p.realizedPnl, p.updatedAt ? new Date(p.updatedAt) : null])
+        };
+    }
+    const { symbol, timeframe } = parseDatasetId(source.datasetId);
+    const bars = db.prepare('SELECT timestamp, open, high, low, close, volume FROM market_data WHERE symbol = ? AND timeframe = ? ORDER BY timestamp DESC LIMIT ?')
+        .all(symbol, timeframe, source.bars).reverse();
+    const live = latestLiveBars.get(`${symbol.toUpperCase()}|${timeframe}`);
+    if (live && (!bars.length || live.timestamp > bars[bars.length - 1].timestamp)) bars.push(live);
+    else if (live && live.timestamp === bars[bars.length - 1].timestamp) bars[bars.length - 1] = live;
+    return {
+        name: source.name,
+        columns: ['Time', 'Open', 'High', 'Low', 'Close', 'Volume'],
+        rows: bars.slice(-source.bars).map(b => [new Date(b.timestamp), b.open, b.high, b.low, b.close, b.volume])
+    };
+};
+
+const liveExports = createLiveExportService({
+    buildTable: (source) => {
+        if (!db) throw new Error(t('errors.databaseNotInitialized'));
+        return buildLiveExportTable(source);
+    },
+    onLog: (level, message, data) => logSystemEvent(message, data, level)
+});
+
+const readLiveExports = () => readJsonSetting('liveExports') || [];
+
+const startLiveExports = () => liveExports.setExports(readLiveExports());
+
+ipcMain.handle('live-export:list', async () => ({ success: true, exports: liveExports.list() }));
+
+// def: { id?, name, filePath, format?, intervalSeconds?, enabled?, sources } — an id updates that export
+ipcMain.handle('live-export:save', async (event, def = {}) => {
+    try {
+        const saved = normalizeExport(def || {});
+        const all = readLiveExports();
+        const index = all.findIndex(e => e.id === saved.id);
+        if (index === -1 && def && def.id) return failure('NOT_FOUND', `Live export not found: ${def.id}`);
+        const next = index === -1 ? [...all, saved] : all.map(e => (e.id === saved.id ? saved : e));
+        liveExports.setExports(next);
+        writeJsonSetting('liveExports', next);
+        logSystemEvent('LIVE_EXPORT_SAVED', { id: saved.id, format: saved.format, sources: saved.sources.length, enabled: saved.enabled });
+        return { success: true, export: liveExports.list().find(e => e.id === saved.id) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('live-export:delete', async (event, id) => {
+    try {
+        const all = readLiveExports();
+        const next = all.filter(e => e.id !== id);
+        if (next.length === all.length) return failure('NOT_FOUND', `Live export not found: ${id}`);
+        liveExports.setExports(next);
+        writeJsonSetting('liveExports', next);
+        return { success: true };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// Writes the file now, even when nothing changed since the last write
+ipcMain.handle('live-export:run-now', async (event, id) => {
+    try {
+        return { success: true, export: await liveExports.runNow(id) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- OPTIONS ---
+// input: { type, spot, strike, expiry | timeYears, rate?, dividendYield?, volatility? | marketPrice, style?, model?, steps? }
+ipcMain.handle('options:price', async (event, input = {}) => {
+    try {
+        return { success: true, ...priceOption(input) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// options: { model?, steps?, valuationTime? }
+ipcMain.handle('options:chain-greeks', async (event, chain = {}, options = {}) => {
+    try {
+        return { success: true, chain: computeChainGreeks(chain, options || {}) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- FX RATES ---
+// Setting 'fx': { baseCurrency, autoFetch }. Rates are quote units per one base unit.
+const fxService = createFxService({
+    getDb: () => db,
+    loadConfig: () => readJsonSetting('fx'),
+    onLog: (type, data, level) => logSystemEvent(type, data, level)
+});
+
+ipcMain.handle('fx:get-config', async () => fxService.config());
+
+ipcMain.handle('fx:set-config', async (event, updates = {}) => {
+    try {
+        const next = { ...fxService.config(), ...updates };
+        next.baseCurrency = normalizeCurrency(next.baseCurrency);
+        next.autoFetch = !!next.autoFetch;
+        writeJsonSetting('fx', { baseCurrency: next.baseCurrency, autoFetch: next.autoFetch });
+        return { success: true, config: fxService.config() };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// filter: { base?, quote?, from?, to?, limit? }
+ipcMain.handle('fx:list-rates', async (event, filter = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, rates: listRates(db, filter || {}) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// day: 'YYYY-MM-DD' or a timestamp (default today)
+ipcMain.handle('fx:set-rate', async (event, base, quote, rate, day) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        saveRate(db, base, quote, rate, day, 'manual');
+        logSystemEvent('FX_RATE_SAVED', { base, quote, rate, day: day || null });
+        return { success: true };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('fx:delete-rate', async (event, base, quote, day) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, deleted: deleteRate(db, base, quote, day) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('fx:get-rate', async (event, from, to, timestamp) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, ...(await fxService.getRate(from, to, timestamp)) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('fx:convert', async (event, amount, from, to, timestamp) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, ...(await fxService.convert(amount, from, to, timestamp)) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- SESSION STATISTICS ---
+ipcMain.handle('sessions:list-calendars', async () => listCalendars());
+
+/**
+ * options: { calendar? (id or custom spec; default by the symbol's asset class),
+ * openingRangeMinutes?, from?, to? }
+ */
+ipcMain.handle('sessions:get-stats', async (event, id, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotReady'));
+        const { symbol, timeframe } = parseDatasetId(id);
+        if (!TIMEFRAME_MS[timeframe] || TIMEFRAME_MS[timeframe] >= TIMEFRAME_MS['1D']) return failure('UNSUPPORTED', 'Session statistics need intraday bars');
+        const params = [symbol, timeframe];
+        let range = '';
+        if (options.from != null) { range += ' AND timestamp >= ?'; params.push(Number(options.from)); }
+        if (options.to != null) { range += ' AND timestamp <= ?'; params.push(Number(options.to)); }
+        const bars = db.prepare(`SELECT timestamp, open, high, low, close, volume FROM market_data WHERE symbol = ? AND timeframe = ?${range} ORDER BY timestamp`).all(...params);
+        const calendar = options.calendar || calendarForAsset(getSymbolMeta(db, symbol).assetClass);
+        return { success: true, datasetId: id, ...computeSessionStats(bars, { calendar, openingRangeMinutes: options.openingRangeMinutes }) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- SESSION SEGMENTS ---
+// Pre-market / regular / after-hours tags from the symbol's calendar
+// (sessions.js), worked out when bars are built or read rather than stored, so
+// a calendar change applies to history too. Live bars carry `segment`;
+// market:get-data filters on it; sessions:get-extended-levels gives the
+// pre-market and after-hours ranges.
+const segmentClassifiers = new Map(); // 'symbol|calendar' -> classifier
+const MAX_SEGMENT_CLASSIFIERS = 200;
+
+const segmentClassifierFor = (symbol, calendarSpec = null) => {
+    const calendar = calendarSpec || calendarForAsset(getSymbolMeta(db, symbol).assetClass);
+    const key = `${symbol}|${typeof calendar === 'string' ? calendar : JSON.stringify(calendar)}`;
+    if (!segmentClassifiers.has(key)) {
+        if (segmentClassifiers.size >= MAX_SEGMENT_CLASSIFIERS) segmentClassifiers.clear();
+        segmentClassifiers.set(key, createSegmentClassifier(calendar));
+    }
+    return segmentClassifiers.get(key);
+};
+
+// Newest `limit` bars before toTime in the wanted segments, oldest first, each with its segment
+const fetchSegmentedBars = (symbol, timeframe, toTime, limit, options) => {
+    const wanted = cleanSegments(options.segments);
+    const { classify } = segmentClassifierFor(symbol, options.calendar);
+    const batch = Math.max(500, limit * 2);
+    const out = [];
+    let cursor = toTime;
+    for (;;) {
+        const rows = db.prepare(`SELECT timestamp as time, open, high, low, close, volume FROM market_data WHERE symbol = ? AND timeframe = ?${cursor ? ' AND timestamp < ?' : ''} ORDER BY timestamp DESC LIMIT ?`)
+            .all(...(cursor ? [symbol, timeframe, cursor, batch] : [symbol, timeframe, batch]));
+        for (const row of rows) {
+            const { segment } = classify(row.time);
+            if (wanted.includes(segment)) out.push({ ...row, segment });
+            if (out.length >= limit) break;
+        }
+        if (out.length >= limit || rows.length < batch) break;
+        cursor = rows[rows.length - 1].time;
+    }
+    return out.reverse();
+};
+
+/**
+ * options: { calendar?, from?, to? } — the last 10 days by default.
+ * Returns computeExtendedHoursLevels(): per-day pre / regular / post ranges and
+ * `latest` (previous after-hours plus today's pre-market).
+ */
+ipcMain.handle('sessions:get-extended-levels', async (event, id, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotReady'));
+        const { symbol, timeframe } = parseDatasetId(id);
+        if (!TIMEFRAME_MS[timeframe] || TIMEFRAME_MS[timeframe] >= TIMEFRAME_MS['1D']) return failure('UNSUPPORTED', 'Extended-hours levels need intraday bars');
+        const latest = db.prepare('SELECT MAX(timestamp) AS t FROM market_data WHERE symbol = ? AND timeframe = ?').get(symbol, timeframe).t;
+        const to = options.to != null ? Number(options.to) : latest;
+        const from = options.from != null ? Number(options.from) : (to || 0) - 10 * 86400000;
+        const bars = db.prepare('SELECT timestamp, open, high, low, close, volume FROM market_data WHERE symbol = ? AND timeframe = ? AND timestamp >= ? AND timestamp <= ? ORDER BY timestamp')
+            .all(symbol, timeframe, from, to || 0);
+        const calendar = options.calendar || calendarForAsset(getSymbolMeta(db, symbol).assetClass);
+        return { success: true, datasetId: id, ...computeExtendedHoursLevels(bars, { calendar }) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- SESSION LEVELS ---
+// Yesterday's high / low / close and today's open as lines in a chart state
+// (sessionLevels.js). Setting 'charts.sessionLevels': { enabled, levels, calendar }
+// — enabled refreshes them whenever a chart is opened with its dataset
+// (drawings:get-state), calendar null follows the symbol's asset class.
+const SESSION_LEVEL_DEFAULTS = { enabled: false, levels: SESSION_LEVEL_KEYS, calendar: null };
+
+const getSessionLevelConfig = () => ({ ...SESSION_LEVEL_DEFAULTS, ...(readJsonSetting('charts.sessionLevels') || {}) });
+
+// Computes the dataset's levels and commits them to the chart state if they moved.
+// Not journaled: undo shouldn't step through data-driven updates.
+const syncSessionLevels = (sourceId, datasetId, options = {}, senderId = null) => {
+    const config = { ...getSessionLevelConfig(), ...options };
+    const { symbol } = parseDatasetId(datasetId);
+    const calendar = config.calendar || calendarForAsset(getSymbolMeta(db, symbol).assetClass);
+    const computed = computeSessionLevels(db, datasetId, { calendar, levels: config.levels });
+    const previous = readChartState(sourceId);
+    const base = previous || { sourceId, timestamp: Date.now(), drawings: [], folders: [], config: {}, visibleRange: null };
+    const { state, changed, added, updated, removed } = applySessionLevels(base, computed.levels);
+    // Nothing to add to a chart that was never saved
+    if (!changed || (!previous && !computed.levels.length)) return { ...computed, changed: false, added: 0, updated: 0, removed: 0 };
+    const result = commitChartState(sourceId, state, previous, { journal: false }, senderId);
+    if (!result.success) return result;
+    return { ...computed, changed: true, added, updated, removed };
+};
+
+ipcMain.handle('session-levels:get-config', async () => getSessionLevelConfig());
+
+ipcMain.handle('session-levels:set-config', async (event, updates = {}) => {
+    try {
+        const next = { ...getSessionLevelConfig(), ...updates };
+        Object.keys(next).forEach((key) => { if (!(key in SESSION_LEVEL_DEFAULTS)) delete next[key]; });
+        next.enabled = !!next.enabled;
+        next.levels = cleanLevelKeys(next.levels);
+        if (next.calendar != null) resolveCalendar(next.calendar); // throws for an unknown one
+        writeJsonSetting('charts.sessionLevels', next);
+        return { success: true, config: next };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// Refreshes the level lines of one chart now; options override the setting's levels / calendar
+ipcMain.handle('session-levels:sync', async (event, sourceId, datasetId, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const result = syncSessionLevels(sourceId, datasetId, options || {}, event.sender.id);
+        return result.success === false ? result : { success: true, ...result };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- SEASONALITY ---
+// grouping: 'month' | 'week' | 'weekday' | 'hour'; options: { timezone?, from?, to?, percentiles? }
+ipcMain.handle('seasonality:compute', async (event, id, grouping, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const { symbol, timeframe } = parseDatasetId(id);
+        checkSeasonalityTimeframe(grouping, timeframe);
+        let workerPath = path.join(__dirname, 'seasonality.js');
+        if (!fs.existsSync(workerPath)) {
+             workerPath = path.join(app.getAppPath(), 'electron', 'seasonality.js');
+        }
+        const start = Date.now();
+        let failed = null;
+        const { result, cached } = await computeCache.memoize('seasonality', id, { grouping, options: options || {} }, async () => {
+            const outcome = await new Promise((resolve, reject) => {
+                const worker = new Worker(workerPath, { workerData: { seasonality: { dbPath: dbPathGlobal, symbol, timeframe, grouping, options: options || {} } } });
+                worker.once('message', (message) => { worker.terminate(); resolve(message); });
+                worker.once('error', reject);
+            });
+            if (!outcome.success) failed = outcome;
+            return outcome.success ? outcome.result : null;
+        });
+        if (failed) return failed;
+        logSystemEvent('SEASONALITY_COMPUTED', { datasetId: id, grouping, bars: result.bars, cached, durationMs: Date.now() - start });
+        return { success: true, datasetId: id, result, cached };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- RELATIVE STRENGTH ---
+// mode: 'ratio' | 'beta'; options: { window?, fill?: 'previous' | 'exact', maxStaleBars?, from?, to? }
+ipcMain.handle('relative:compute', async (event, id, benchmarkId, mode = 'ratio', options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const target = parseDatasetId(id);
+        const benchmark = parseDatasetId(benchmarkId);
+        if (target.timeframe !== benchmark.timeframe) return failure('INVALID_INPUT', 'The benchmark must have the same timeframe as the dataset');
+        if (!TIMEFRAME_MS[target.timeframe]) return failure('UNSUPPORTED', `Unsupported timeframe: ${target.timeframe}`);
+        const opts = options || {};
+        const from = opts.from != null ? Number(opts.from) : Number.MIN_SAFE_INTEGER;
+        const to = opts.to != null ? Number(opts.to) : Number.MAX_SAFE_INTEGER;
+        const load = ({ symbol, timeframe }, since) => db.prepare('SELECT timestamp, close FROM market_data WHERE symbol = ? AND timeframe = ? AND timestamp BETWEEN ? AND ? ORDER BY timestamp ASC')
+            .all(symbol, timeframe, since, to);
+        const bars = load(target, from);
+        if (!bars.length) return failure('NOT_FOUND', `No bars for ${id}`);
+        // Reach back far enough to carry the benchmark into the first point
+        const barMs = TIMEFRAME_MS[target.timeframe];
+        const benchmarkBars = load(benchmark, bars[0].timestamp - Math.max(0, Number(opts.maxStaleBars ?? 5)) * barMs);
+        if (!benchmarkBars.length) return failure('NOT_FOUND', `No bars for ${benchmarkId}`);
+        return { success: true, datasetId: id, benchmarkId, ...computeRelative(bars, benchmarkBars, barMs, mode, opts) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- ECONOMIC CALENDAR ---
+// Warnings are broadcast as 'calendar:event-warning' and sent to the notifiers
+let economicCalendar = null;
+
+const startEconomicCalendar = () => {
+    if (!db) return;
+    economicCalendar = createEconomicCalendar({
+        db,
+        loadConfig: () => readJsonSetting('calendar.config'),
+        saveConfig: (config) => writeJsonSetting('calendar.config', config),
+        onWarning: (calendarEvent, minutesUntil) => {
+            const payload = { event: 'calendar.warning', minutesUntil, ...calendarEvent };
+            logSystemEvent('CALENDAR_EVENT_WARNING', { id: calendarEvent.id, title: calendarEvent.title, currency: calendarEvent.currency, minutesUntil });
+            broadcast('calendar:event-warning', payload);
+            if (economicCalendar.getConfig().warnings.notify) {
+                const text = `${calendarEvent.impact.toUpperCase()} impact in ${minutesUntil} min: ${calendarEvent.currency ? `${calendarEvent.currency} ` : ''}${calendarEvent.title}`
+                    + `${calendarEvent.forecast ? ` (forecast ${calendarEvent.forecast}, previous ${calendarEvent.previous || '-'})` : ''}`;
+                notifiers.notifyAll(text).catch(() => {});
+            }
+        },
+        onLog: (level, message, data) => logSystemEvent(message, data, level),
+        getIntervalScale: backgroundPollScale
+    });
+    economicCalendar.start();
+};
+
+const getEconomicCalendar = () => {
+    if (!economicCalendar) throw new Error(t('errors.databaseNotInitialized'));
+    return economicCalendar;
+};
+
+// Currencies a chart symbol reacts to: both legs of a forex pair, else the quote currency
+const symbolCurrencies = (symbol) => {
+    const meta = getSymbolMeta(db, symbol);
+    if (meta.assetClass === 'forex') return [String(symbol).replace(/[^A-Za-z]/g, '').slice(0, 3).toUpperCase(), meta.currency];
+    return meta.currency ? [meta.currency] : [];
+};
+
+// filter: { from?, to?, currencies?, symbol?, impact?: minimum level | levels[], limit? }
+ipcMain.handle('calendar:get-events', async (event, filter = {}) => {
+    try {
+        const currencies = filter.currencies && filter.currencies.length ? filter.currencies : (filter.symbol ? symbolCurrencies(filter.symbol) : null);
+        return { success: true, events: getEconomicCalendar().getEvents({ ...filter, currencies }) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// options: { format?: 'csv' | 'ics', utcOffsetMinutes?, source? }
+ipcMain.handle('calendar:import-file', async (event, filePath = null, options = {}) => {
+    try {
+        let target = filePath;
+        if (!target) {
+            const { canceled, filePaths } = await dialog.showOpenDialog(mainWindow, {
+                properties: ['openFile'],
+                filters: [{ name: 'Calendar', extensions: ['csv', 'ics', 'txt'] }]
+            });
+            if (canceled || !filePaths.length) return { success: false, canceled: true };
+            target = filePaths[0];
+        }
+        const result = getEconomicCalendar().importFile(target, options);
+        broadcast('calendar:updated', { source: result.source });
+        return { success: true, ...result };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('calendar:refresh', async () => {
+    try {
+        const result = await getEconomicCalendar().refresh();
+        broadcast('calendar:updated', { source: result.source });
+        refreshICalSubscription();
+        return { success: true, ...result };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// filter: { source?, before? }
+ipcMain.handle('calendar:delete-events', async (event, filter = {}) => {
+    try {
+        const deleted = getEconomicCalendar().deleteEvents(filter);
+        logSystemEvent('CALENDAR_EVENTS_DELETED', { ...filter, deleted });
+        return { success: true, deleted };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('calendar:get-config', async () => (economicCalendar ? { ...economicCalendar.getConfig(), providers: economicCalendar.providers() } : null));
+
+// Accepts any subset of { provider, refreshHours, warnings: { enabled, minutesBefore, impact, currencies, notify } }
+ipcMain.handle('calendar:configure', async (event, updates = {}) => {
+    try {
+        const config = getEconomicCalendar().configure(updates);
+        logSystemEvent('CALENDAR_CONFIGURED', { provider: config.provider, warnings: config.warnings.enabled });
+        return { success: true, config };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- ICALENDAR EXPORT ---
+// kinds: 'jobs' (upcoming scheduled job runs) and / or 'events' (economic
+// events, filtered like calendar:get-events). A subscription is a .ics file
+// rewritten every ICAL_FEED_REFRESH_MS and after each calendar refresh, for
+// calendar apps that watch a local or synced file.
+const ICAL_KINDS = ['jobs', 'events'];
+const ICAL_FEED_REFRESH_MS = 15 * 60 * 1000;
+
+// options: { horizonDays = 30, currencies?, impact?, name? }
+const renderICalendar = (kinds, options = {}) => {
+    const unknown = kinds.filter(kind => !ICAL_KINDS.includes(kind));
+    if (!kinds.length || unknown.length) throw new Error(`iCalendar kinds must be any of ${ICAL_KINDS.join(', ')}`);
+    const from = Date.now();
+    const to = from + (Number(options.horizonDays) || 30) * 86400000;
+    return buildICalendar({
+        jobs: kinds.includes('jobs') && scheduler ? scheduler.listJobs() : [],
+        events: kinds.includes('events') ? getEconomicCalendar().getEvents({ from, to, currencies: options.currencies || null, impact: options.impact || null }) : []
+    }, { from, to, name: options.name || 'Red Pill' });
+};
+
+const refreshICalSubscription = () => {
+    const subscription = readJsonSetting('ical.subscription');
+    if (!subscription || !db) return;
+    try {
+        fs.writeFileSync(subscription.filePath, renderICalendar(subscription.kinds, subscription.options).ics);
+    } catch (err) {
+        logSystemEvent('ICAL_SUBSCRIPTION_FAILED', { filePath: subscription.filePath, error: err.message }, 'WARN');
+    }
+};
+
+const startICalSubscription = () => {
+    refreshICalSubscription();
+    setInterval(refreshICalSubscription, ICAL_FEED_REFRESH_MS);
+};
+
+// target: a file path, null for a save dialog, or { subscribe: true, filePath } to keep the file updated
+ipcMain.handle('ical:export', async (event, kinds = ICAL_KINDS, target = null, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const { ics, counts } = renderICalendar(kinds || ICAL_KINDS, options || {});
+        const subscribe = !!(target && typeof target === 'object' && target.subscribe);
+        let filePath = subscribe ? target.filePath : target;
+        if (!filePath) {
+            const { canceled, filePath: chosen } = await dialog.showSaveDialog(mainWindow, {
+                defaultPath: subscribe ? 'red-pill.ics' : `red-pill-${new Date().toISOString().slice(0, 10)}.ics`,
+                filters: [{ name: 'iCalendar', extensions: ['ics'] }]
+            });
+            if (canceled || !chosen) return { success: false, canceled: true };
+            filePath = chosen;
+        }
+        fs.writeFileSync(filePath, ics);
+        if (subscribe) writeJsonSetting('ical.subscription', { filePath, kinds, options: options || {} });
+        logSystemEvent('ICAL_EXPORTED', { kinds, counts, subscribed: subscribe, file: path.basename(filePath) });
+        return { success: true, filePath, counts, subscribed: subscribe };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('ical:get-subscription', async () => readJsonSetting('ical.subscription') || null);
+
+// Stops rewriting the file; the last version stays on disk
+ipcMain.handle('ical:unsubscribe', async () => {
+    try {
+        writeJsonSetting('ical.subscription', null);
+        return { success: true };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- STICKY NOTES ---
+const publishNoteChanges = (changes, origin = null) => {
+    changes.filter(c => c.op === 'add' && c.item).forEach(c => noteActivity('note_created', {
+        ref: c.id,
+        symbol: c.item.symbol || null,
+        title: String(c.item.text || '').split('\n')[0].slice(0, 80) || 'Untitled note',
+        detail: c.item.boardId ? { boardId: c.item.boardId } : null
+    }));
+    const envelope = changes.length ? syncBus.publish('notes', { changes }, origin) : null;
+    recognizeNoteInk(changes);
+    return envelope;
+};
+
+// Ink notes whose strokes have no recognized text yet go to the registered
+// recognizer (inkRecognition.js); without one this is a no-op
+const recognizeNoteInk = (changes) => {
+    if (!db || readOnly || !hasInkRecognizer()) return;
+    changes.filter(c => c.item && c.item.inkData && c.item.recognizedText == null).forEach(async ({ id, item }) => {
+        try {
+            const text = await recognizeInk(item.inkData);
+            const current = db && getStickyNote(db, id);
+            // Skip when there was nothing to read or the ink changed meanwhile
+            if (!text || !current || JSON.stringify(current.inkData) !== JSON.stringify(item.inkData)) return;
+            publishNoteChanges(bulkUpdateNotes(db, [{ op: 'update', id, changes: { recognizedText: text } }]).changes);
+        } catch (err) {
+            logSystemEvent('INK_RECOGNITION_FAILED', { id, error: err.message }, 'WARN');
+        }
+    });
+};
+
+// boardId null loads the default board
+ipcMain.handle('notes:load', async (event, boardId = null) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, boardId: boardId || null, notes: listStickyNotes(db, boardId) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// One transaction per batch; changes reach every window as one 'notes' sync event
+ipcMain.handle('notes:bulk-update', async (event, ops = []) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const { applied, changes } = bulkUpdateNotes(db, ops);
+        const envelope = publishNoteChanges(changes, event.sender.id);
+        return { success: true, applied, changes, seq: envelope ? envelope.seq : syncBus.currentSeq() };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// ids: one note id or a list
+ipcMain.handle('notes:archive', async (event, ids, archived = true) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const { changes } = bulkUpdateNotes(db, [{ op: archived ? 'archive' : 'unarchive', ids: Array.isArray(ids) ? ids : [ids] }]);
+        publishNoteChanges(changes, event.sender.id);
+        return { success: true, changes };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// Prior contents of a note, newest first; deleted notes keep theirs
+ipcMain.handle('notes:get-history', async (event, noteId) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, noteId, note: getStickyNote(db, noteId), versions: getNoteHistory(db, noteId) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// Restoring is itself versioned, so it can be undone from the history
+ipcMain.handle('notes:restore-version', async (event, noteId, version) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const { changes } = restoreNoteVersion(db, noteId, version);
+        const envelope = publishNoteChanges(changes, event.sender.id);
+        return { success: true, note: changes.length ? changes[0].item : getStickyNote(db, noteId), changes, seq: envelope ? envelope.seq : syncBus.currentSeq() };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// options: { includeArchived = true, archivedOnly?, boardId?, limit? }
+ipcMain.handle('notes:search', async (event, query, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, notes: searchStickyNotes(db, query, options) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// Setting 'notes.autoArchive': { enabled, minimizedDays, untouchedDays }
+const NOTE_AUTO_ARCHIVE_CHECK_MS = 24 * 60 * 60 * 1000;
+
+const getNoteAutoArchiveConfig = () => ({ ...AUTO_ARCHIVE_DEFAULTS, ...(readJsonSetting('notes.autoArchive') || {}) });
+
+const runNoteAutoArchive = () => {
+    if (!db) return 0;
+    try {
+        const { changes } = autoArchiveNotes(db, getNoteAutoArchiveConfig());
+        if (changes.length) {
+            logSystemEvent('NOTES_AUTO_ARCHIVED', { count: changes.length });
+            publishNoteChanges(changes);
+        }
+        return changes.length;
+    } catch (err) {
+        logSystemEvent('NOTES_AUTO_ARCHIVE_FAILED', { error: err.message }, 'WARN');
+        return 0;
+    }
+};
+
+let noteAutoArchiveTimer = null;
+
+const startNoteAutoArchive = () => {
+    runNoteAutoArchive();
+    if (noteAutoArchiveTimer) clearInterval(noteAutoArchiveTimer);
+    noteAutoArchiveTimer = setInterval(runNoteAutoArchive, NOTE_AUTO_ARCHIVE_CHECK_MS);
+};
+
+const stopNoteAutoArchive = () => {
+    if (noteAutoArchiveTimer) clearInterval(noteAutoArchiveTimer);
+    noteAutoArchiveTimer = null;
+};
+
+ipcMain.handle('notes:get-auto-archive', async () => getNoteAutoArchiveConfig());
+
+// Saves the rules and applies them right away
+ipcMain.handle('notes:set-auto-archive', async (event, updates = {}) => {
+    try {
+        const next = { ...getNoteAutoArchiveConfig(), ...updates };
+        ['minimizedDays', 'untouchedDays'].forEach((key) => {
+            if (next[key] != null && !(Number(next[key]) > 0)) throw new Error(`${key} must be a positive number of days or null`);
+        });
+        Object.keys(next).forEach((key) => { if (!(key in AUTO_ARCHIVE_DEFAULTS)) delete next[key]; });
+        writeJsonSetting('notes.autoArchive', next);
+        return { success: true, config: next, archived: runNoteAutoArchive() };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- EXTERNAL DATABASE CHANGES ---
+// Writes to redpill.db by another process (externalChanges.js) are checked and
+// republished: valid chart states and notes go out on the sync bus like the
+// app's own edits, so windows update the affected items instead of serving
+// stale ones, and 'database:external-change' lists what changed. A chart state
+// that fails validation isn't published; windows keep what they show.
+const applyExternalChanges = (diff) => {
+    const at = Date.now();
+    const drawings = (diff.drawings || []).map(({ key: sourceId, op, before, after }) => {
+        let previous = null;
+        try { previous = before ? JSON.parse(before) : null; } catch (e) { previous = null; }
+        if (op === 'delete') {
+            publishDrawingChanges(sourceId, previous, { drawings: [], folders: [] }, null);
+            return { sourceId, op, valid: true };
+        }
+        let parsed;
+        try { parsed = JSON.parse(after); } catch (e) { return { sourceId, op, valid: false, error: 'Chart state is not valid JSON' }; }
+        const { state, report } = validateChartState(parsed);
+        if (!report.valid) return { sourceId, op, valid: false, error: report.errors[0].message };
+        publishDrawingChanges(sourceId, previous, state, null);
+        return { sourceId, op, valid: true };
+    });
+    const noteChanges = [];
+    const notes = (diff.notes || []).map(({ key: id, op }) => {
+        if (op === 'delete') {
+            noteChanges.push({ op, id, item: null });
+            return { id, op, valid: true };
+        }
+        try {
+            noteChanges.push({ op, id, item: getStickyNote(db, id) });
+            return { id, op, valid: true };
+        } catch (err) {
+            return { id, op, valid: false, error: err.message };
+        }
+    });
+    publishNoteChanges(noteChanges);
+    const invalid = [...drawings, ...notes].filter(c => !c.valid).length;
+    logSystemEvent('EXTERNAL_DATABASE_CHANGE', { drawings: drawings.length, notes: notes.length, invalid }, invalid ? 'WARN' : 'INFO');
+    broadcast('database:external-change', { at, replaced: false, drawings, notes });
+};
+
+const externalChanges = createExternalChangeMonitor({
+    dbFile: path.join(app.getPath('userData'), 'redpill.db'),
+    getDb: () => db,
+    onChanges: applyExternalChanges,
+    onReplaced: () => broadcast('database:external-change', { at: Date.now(), replaced: true, restartRequired: true, drawings: [], notes: [] }),
+    onLog: (level, event, data) => logSystemEvent(event, data, level)
+});
+
+ipcMain.handle('database:get-external-changes', async () => ({ success: true, ...externalChanges.status() }));
+
+// Checks now instead of waiting for the watch or the poll
+ipcMain.handle('database:check-external', async () => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, ...externalChanges.check(), status: externalChanges.status() };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- ATTACHMENTS ---
+// Notes embed redpill-attachment://<id>; the scheme must be registered before app ready
+// (plugin panels' scheme too: this may only be called once)
+protocol.registerSchemesAsPrivileged([
+    { scheme: ATTACHMENT_SCHEME, privileges: { standard: true, secure: true, supportFetchAPI: true } },
+    { scheme: PLUGIN_SCHEME, privileges: { standard: true, secure: true } }
+]);
+
+const registerAttachmentProtocol = () => {
+    protocol.handle(ATTACHMENT_SCHEME, (req) => {
+        const id = new URL(req.url).hostname;
+        const attachment = db ? readAttachment(db, id) : null;
+        if (!attachment) return new Response('Attachment not found', { status: 404 });
+        // Content never changes for an id
+        return new Response(attachment.data, { headers: { 'Content-Type': attachment.mime, 'Cache-Control': 'max-age=31536000, immutable' } });
+    });
+};
+
+// Stores an Electron NativeImage; options: { format: 'png' | 'jpeg', quality?, maxWidth? }
+const saveNativeImage = (image, source, { format = 'png', quality = 90, maxWidth = null } = {}) => {
+    if (!['png', 'jpeg'].includes(format)) throw new Error(`Unsupported image format: ${format}`);
+    let img = image;
+    if (maxWidth && img.getSize().width > maxWidth) img = img.resize({ width: Math.round(maxWidth), quality: 'best' });
+    const { width, height } = img.getSize();
+    const data = format === 'jpeg' ? img.toJPEG(Math.max(1, Math.min(100, Number(quality) || 90))) : img.toPNG();
+    return saveAttachment(db, { data, mime: `image/${format}`, width, height, source });
+};
+
+ipcMain.handle('attachments:paste-image', async (event, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const image = clipboard.readImage();
+        if (image.isEmpty()) return failure('NOT_FOUND', 'The clipboard holds no image');
+        const attachment = saveNativeImage(image, 'clipboard', options);
+        if (!attachment.existing) logSystemEvent('ATTACHMENT_SAVED', { id: attachment.id, source: 'clipboard', bytes: attachment.bytes });
+        return { success: true, attachment };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// Info plus a data URL, for contexts that can't load the custom scheme (exports, print)
+ipcMain.handle('attachments:get', async (event, id) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const info = getAttachmentInfo(db, id);
+        if (!info) return failure('NOT_FOUND', `Attachment not found: ${id}`);
+        const { mime, data } = readAttachment(db, id);
+        return { success: true, attachment: { ...info, dataUrl: `data:${mime};base64,${data.toString('base64')}` } };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('attachments:list', async (event, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, attachments: listAttachments(db, options) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('attachments:delete', async (event, id) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: deleteAttachment(db, id) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// Removes attachments no note references (older than a day)
+ipcMain.handle('attachments:prune', async () => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const result = pruneAttachments(db);
+        logSystemEvent('ATTACHMENTS_PRUNED', result);
+        return { success: true, ...result };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- PLUGIN PANELS ---
+// Each panel opens in its own sandboxed window and partition (pluginSandbox.js).
+// Capabilities a plugin asks for do nothing until the user grants them;
+// grants are read on every call, so revoking one takes effect at once.
+const pluginsDirPath = () => path.join(app.getPath('userData'), 'plugins');
+const panelPlugins = new Map(); // id -> manifest, as last discovered
+const pluginSessions = new Set();
+
+const loadPanelPlugins = () => {
+    const { plugins, errors } = discoverPanelPlugins(pluginsDirPath());
+    panelPlugins.clear();
+    plugins.forEach(p => panelPlugins.set(p.id, p));
+    errors.forEach(e => logSystemEvent('PLUGIN_MANIFEST_INVALID', e, 'WARN'));
+    return { plugins, errors };
+};
+
+const getPluginGrants = () => readJsonSetting('plugins.grants') || {};
+
+// What the plugin asked for and the user allowed
+const grantedCapabilities = plugin => (getPluginGrants()[plugin.id] || []).filter(c => plugin.capabilities.includes(c));
+
+const pluginCommands = createPluginCommands({
+    getDb: () => db,
+    listDatasets,
+    getSymbolMeta,
+    notify: (plugin, message) => broadcast('plugins:notification', { pluginId: plugin.id, name: plugin.name, message, at: Date.now() })
+});
+
+const preparePluginSession = (plugin) => {
+    const partition = `plugin:${plugin.id}`;
+    const ses = session.fromPartition(partition);
+    if (pluginSessions.has(partition)) return partition;
+    // Served from the current manifest, so an updated plugin folder is picked up
+    ses.protocol.handle(PLUGIN_SCHEME, (req) => {
+        const manifest = panelPlugins.get(plugin.id);
+        const file = manifest ? readPanelFile(manifest, req.url) : { status: 404, headers: {} };
+        return new Response(file.body || 'Not found', { status: file.status, headers: file.headers });
+    });
+    const origin = `${PLUGIN_SCHEME}://${plugin.id}/`;
+    ses.webRequest.onBeforeRequest((details, callback) => callback({ cancel: !(details.url.startsWith(origin) || details.url.startsWith('data:') || details.url.startsWith('devtools:')) }));
+    ses.setPermissionRequestHandler((contents, permission, callback) => callback(false));
+    ses.setPermissionCheckHandler(() => false);
+    pluginSessions.add(partition);
+    return partition;
+};
+
+const openPluginPanel = (plugin) => {
+    const open = Array.from(pluginWindows.values()).find(entry => entry.plugin.id === plugin.id);
+    if (open && !open.win.isDestroyed()) {
+        open.win.focus();
+        return open.win;
+    }
+    const win = new BrowserWindow({
+        width: plugin.panel.width,
+        height: plugin.panel.height,
+        title: `${plugin.panel.title} — plugin`,
+        autoHideMenuBar: true,
+        webPreferences: {
+            sandbox: true,
+            contextIsolation: true,
+            nodeIntegration: false,
+            webSecurity: true,
+            webviewTag: false,
+            spellcheck: false,
+            navigateOnDragDrop: false,
+            devTools: !app.isPackaged,
+            partition: preparePluginSession(plugin),
+            preload: path.join(__dirname, 'pluginPreload.js')
+        }
+    });
+    const contentsId = win.webContents.id;
+    const origin = `${PLUGIN_SCHEME}://${plugin.id}/`;
+    pluginWindows.set(contentsId, { plugin, win });
+    win.webContents.setWindowOpenHandler(() => ({ action: 'deny' }));
+    win.webContents.on('will-navigate', (event, url) => { if (!url.startsWith(origin)) event.preventDefault(); });
+    win.webContents.on('will-attach-webview', event => event.preventDefault());
+    win.on('closed', () => pluginWindows.delete(contentsId));
+    win.loadURL(`${origin}${plugin.panel.entry}`);
+    logSystemEvent('PLUGIN_PANEL_OPENED', { id: plugin.id, granted: grantedCapabilities(plugin) });
+    return win;
+};
+
+const panelPluginInfo = plugin => ({
+    id: plugin.id,
+    name: plugin.name,
+    version: plugin.version,
+    title: plugin.panel.title,
+    requested: plugin.capabilities,
+    granted: grantedCapabilities(plugin),
+    open: Array.from(pluginWindows.values()).some(entry => entry.plugin.id === plugin.id)
+});
+
+ipcMain.handle('plugins:list-panels', async () => {
+    try {
+        const { plugins, errors } = loadPanelPlugins();
+        return { success: true, dir: pluginsDirPath(), capabilities: PLUGIN_CAPABILITIES, plugins: plugins.map(panelPluginInfo), errors };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// capabilities: the full set the user allows (a subset of what the plugin requests)
+ipcMain.handle('plugins:set-grants', async (event, pluginId, capabilities = []) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        if (!panelPlugins.size) loadPanelPlugins();
+        const plugin = panelPlugins.get(pluginId);
+        if (!plugin) return failure('NOT_FOUND', `Plugin not found: ${pluginId}`);
+        const list = Array.isArray(capabilities) ? Array.from(new Set(capabilities.map(String))) : [];
+        const unrequested = list.filter(c => !plugin.capabilities.includes(c));
+        if (unrequested.length) return failure('INVALID_INPUT', `${plugin.id} doesn't request ${unrequested.join(', ')}`);
+        writeJsonSetting('plugins.grants', { ...getPluginGrants(), [plugin.id]: list });
+        logSystemEvent('PLUGIN_GRANTS_CHANGED', { id: plugin.id, granted: list });
+        pluginWindows.forEach((entry) => {
+            if (entry.plugin.id === plugin.id && !entry.win.isDestroyed()) entry.win.webContents.send('plugin:event', { type: 'capabilities', granted: list });
+        });
+        return { success: true, plugin: panelPluginInfo(plugin) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('plugins:open-panel', async (event, pluginId) => {
+    try {
+        if (!panelPlugins.has(pluginId)) loadPanelPlugins();
+        const plugin = panelPlugins.get(pluginId);
+        if (!plugin) return failure('NOT_FOUND', `Plugin not found: ${pluginId}`);
+        openPluginPanel(plugin);
+        return { success: true, plugin: panelPluginInfo(plugin) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('plugins:close-panel', async (event, pluginId) => {
+    try {
+        let closed = 0;
+        pluginWindows.forEach((entry) => {
+            if (entry.plugin.id === pluginId && !entry.win.isDestroyed()) { entry.win.close(); closed++; }
+        });
+        return { success: true, closed };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// The panel's only way in; the plugin is whoever owns the sending window
+ipcMain.handle('plugin:invoke', async (event, command, ...args) => {
+    const sender = pluginWindows.get(event.sender.id);
+    if (!sender) return failure('PERMISSION_DENIED', 'plugin:invoke is only available to plugin panels');
+    if (readOnly && PLUGIN_WRITE_COMMANDS.includes(command)) return failure('READ_ONLY', t('errors.readOnly'), { readOnly: true });
+    const plugin = panelPlugins.get(sender.plugin.id) || sender.plugin;
+    return invokePluginCommand(pluginCommands, { plugin, granted: grantedCapabilities(plugin) }, command, args);
+});
+
+// --- SCREEN CAPTURE ---
+// options: { destination: 'attachment' | 'clipboard' | 'both', format?, quality?, maxWidth? }
+const deliverCapture = (image, options = {}) => {
+    if (image.isEmpty()) throw new Error('Nothing was captured');
+    const destination = options.destination || 'attachment';
+    if (!['attachment', 'clipboard', 'both'].includes(destination)) throw new Error(`Unknown capture destination: ${destination}`);
+    const { width, height } = image.getSize();
+    const result = { width, height, destination };
+    if (destination !== 'attachment') clipboard.writeImage(image);
+    if (destination !== 'clipboard') {
+        if (!db) throw new Error(t('errors.databaseNotInitialized'));
+        result.attachment = saveNativeImage(image, 'capture', options);
+    }
+    logSystemEvent('SCREEN_CAPTURED', { width, height, destination, attachmentId: result.attachment ? result.attachment.id : null });
+    return result;
+};
+
+const checkRect = (rect) => {
+    const r = rect || {};
+    if (![r.x, r.y, r.width, r.height].every(Number.isFinite) || r.width <= 0 || r.height <= 0) throw new Error('rect needs numeric x, y and a positive width and height');
+    return { x: Math.round(r.x), y: Math.round(r.y), width: Math.round(r.width), height: Math.round(r.height) };
+};
+
+// label: 'main', a BrowserWindow id, or empty for the calling window
+ipcMain.handle('capture:window', async (event, label = null, options = {}) => {
+    try {
+        const win = resolveWindow(event, label);
+        if (!win || win.isDestroyed()) return failure('NOT_FOUND', 'Window not found');
+        return { success: true, ...deliverCapture(await win.capturePage(), options) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// rect in screen coordinates (DIPs), grabbed from the display it lies on;
+// with options.window it is relative to that window's content instead
+ipcMain.handle('capture:region', async (event, rect, options = {}) => {
+    try {
+        const area = checkRect(rect);
+        if (options.window != null) {
+            const win = resolveWindow(event, options.window);
+            if (!win || win.isDestroyed()) return failure('NOT_FOUND', 'Window not found');
+            return { success: true, ...deliverCapture(await win.capturePage(area), options) };
+        }
+        const display = screen.getDisplayMatching(area);
+        const { scaleFactor, bounds } = display;
+        const sources = await desktopCapturer.getSources({
+            types: ['screen'],
+            thumbnailSize: { width: Math.round(bounds.width * scaleFactor), height: Math.round(bounds.height * scaleFactor) }
+        });
+        const source = sources.find(s => s.display_id === String(display.id)) || (sources.length === 1 ? sources[0] : null);
+        // An empty thumbnail usually means screen recording permission is missing (macOS)
+        if (!source || source.thumbnail.isEmpty()) return failure('PERMISSION_DENIED', 'Could not capture the screen (check screen recording permission)');
+        const left = Math.max(bounds.x, area.x);
+        const top = Math.max(bounds.y, area.y);
+        const right = Math.min(bounds.x + bounds.width, area.x + area.width);
+        const bottom = Math.min(bounds.y + bounds.height, area.y + area.height);
+        if (right <= left || bottom <= top) return failure('INVALID_INPUT', 'The region is outside every display');
+        const image = source.thumbnail.crop({
+            x: Math.round((left - bounds.x) * scaleFactor),
+            y: Math.round((top - bounds.y) * scaleFactor),
+            width: Math.round((right - left) * scaleFactor),
+            height: Math.round((bottom - top) * scaleFactor)
+        });
+        return { success: true, displayId: display.id, ...deliverCapture(image, options) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- NOTE TEMPLATES ---
+ipcMain.handle('notes:list-templates', async () => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, templates: listNoteTemplates(db) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// template: { id?, name, text, color?, tags?, layout? }
+ipcMain.handle('notes:save-template', async (event, template = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, template: saveNoteTemplate(db, template) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('notes:delete-template', async (event, id) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: deleteNoteTemplate(db, id) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// vars: placeholder values ({ symbol, ... }); options: { boardId?, calendar? } — the
+// session calendar defaults to the symbol's asset class
+ipcMain.handle('notes:create-from-template', async (event, templateId, vars = {}, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const calendar = options.calendar || (vars.symbol ? calendarForAsset(getSymbolMeta(db, vars.symbol).assetClass) : null);
+        const { note, missing, changes } = createNoteFromTemplate(db, templateId, vars, { boardId: options.boardId || null, calendar });
+        publishNoteChanges(changes, event.sender.id);
+        return { success: true, note, missing };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// Board list changes go out as a 'notes' sync event carrying every board
+const boardHandler = (apply) => async (event, ...args) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const board = apply(...args);
+        syncBus.publish('notes', { changes: [], boards: listBoards(db, { includeArchived: true }) }, event.sender.id);
+        return { success: true, board };
+    } catch (err) {
+        return errorResult(err);
+    }
+};
+
+ipcMain.handle('notes:list-boards', async (event, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, boards: listBoards(db, options) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('notes:create-board', boardHandler(name => createBoard(db, name)));
+ipcMain.handle('notes:rename-board', boardHandler((id, name) => renameBoard(db, id, name)));
+ipcMain.handle('notes:archive-board', boardHandler((id, archived = true) => setBoardArchived(db, id, archived)));
+
+// --- PRINTING ---
+// print:document renders a chart snapshot, sticky notes, the portfolio report,
+// the activity diary or a mix of them ('document') into one page in a hidden window, then hands it
+// to the OS print pipeline. Without options.silent the system print dialog
+// picks the printer and page setup; options.pdfPath prints to a PDF instead.
+const attachmentDataUrl = (id) => {
+    const attachment = readAttachment(db, id);
+    return attachment ? `data:${attachment.mime};base64,${attachment.data.toString('base64')}` : null;
+};
+
+// Turns one { kind, id, options } request into a part for composePrintHtml
+const buildPrintPart = async (event, kind, id, options = {}) => {
+    if (kind === 'chart') {
+        if (!id) throw new Error('Chart source id is required');
+        let imageSrc = options.attachmentId ? attachmentDataUrl(options.attachmentId) : null;
+        if (!imageSrc && options.snapshot !== false) {
+            const win = resolveWindow(event, options.window);
+            if (win && !win.isDestroyed()) imageSrc = (await win.capturePage()).toDataURL();
+        }
+        const annotations = options.annotations === false ? [] : listChartAnnotations(db, id);
+        return { kind, title: options.title || id, imageSrc, annotations };
+    }
+    if (kind === 'notes') {
+        const notes = Array.isArray(id) && id.length
+            ? id.map(noteId => getStickyNote(db, noteId)).filter(Boolean)
+            : listStickyNotes(db, options.boardId || null);
+        const images = {};
+        attachmentIdsIn(notes).forEach((attachmentId) => {
+            const src = attachmentDataUrl(attachmentId);
+            if (src) images[attachmentId] = src;
+        });
+        return { kind, title: options.title, notes, images };
+    }
+    if (kind === 'report') {
+        const stats = await portfolioStats(options.filter || {});
+        return { kind, stats, title: options.title, currency: options.currency || stats.baseCurrency };
+    }
+    if (kind === 'diary') {
+        return { kind, title: options.title, timeline: getActivityTimeline(db, options.range || {}) };
+    }
+    throw new Error(`Unknown print kind: ${kind}`);
+};
+
+// Margin presets as accepted by webContents.print
+const PRINT_MARGINS = ['default', 'none', 'printableArea'];
+
+/**
+ * kind: 'chart' (id = chart source id; options.attachmentId or a capture of
+ * options.window), 'notes' (id = note ids, or options.boardId), 'report'
+ * (options.filter), 'diary' (options.range, as for activity:get-timeline),
+ * or 'document' (id = [{ kind, id, options }]).
+ * Print options: { silent?, deviceName?, pageSize?, landscape?, margins?, copies?, pdfPath?, title? }
+ */
+ipcMain.handle('print:document', async (event, kind, id = null, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        options = options || {};
+        const parts = kind === 'document'
+            ? await Promise.all((Array.isArray(id) ? id : []).map(part => buildPrintPart(event, part.kind, part.id, part.options || {})))
+            : [await buildPrintPart(event, kind, id, options)];
+        const html = composePrintHtml(parts, { title: options.title });
+        const pageSize = options.pageSize || 'A4';
+        const landscape = !!options.landscape;
+        const win = new BrowserWindow({ show: false, webPreferences: { javascript: false } });
+        try {
+            await win.loadURL(`data:text/html;charset=utf-8,${encodeURIComponent(html)}`);
+            if (options.pdfPath) {
+                fs.writeFileSync(options.pdfPath, await win.webContents.printToPDF({ printBackground: true, pageSize, landscape }));
+                logSystemEvent('PRINT_DOCUMENT', { kind, pdfPath: options.pdfPath, parts: parts.length });
+                return { success: true, pdfPath: options.pdfPath };
+            }
+            const printOptions = {
+                silent: !!options.silent,
+                printBackground: true,
+                pageSize,
+                landscape,
+                copies: Math.max(1, Math.min(99, Number(options.copies) || 1)),
+                margins: { marginType: PRINT_MARGINS.includes(options.margins) ? options.margins : 'default' }
+            };
+            if (options.deviceName) printOptions.deviceName = options.deviceName;
+            const { printed, reason } = await new Promise(resolve => win.webContents.print(printOptions, (ok, failureReason) => resolve({ printed: ok, reason: failureReason })));
+            if (!printed) {
+                if (reason === 'cancelled') return { success: false, canceled: true };
+                logSystemEvent('PRINT_FAILED', { kind, deviceName: options.deviceName || null, reason }, 'WARN');
+                return failure('INTERNAL', reason || 'Printing failed');
+            }
+            logSystemEvent('PRINT_DOCUMENT', { kind, deviceName: options.deviceName || null, parts: parts.length });
+            return { success: true };
+        } finally {
+            win.destroy();
+        }
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('print:list-printers', async () => {
+    try {
+        if (!mainWindow || mainWindow.isDestroyed()) return failure('NOT_FOUND', 'Window not found');
+        const printers = await mainWindow.webContents.getPrintersAsync();
+        return { success: true, printers: printers.map(p => ({ name: p.name, displayName: p.displayName, description: p.description })) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- EXPORT TEMPLATES ---
+// User-defined output formats (exportTemplates.js). Each source turns a query
+// into { items, ...fields } for the template:
+//   notes:    { boardId?, search?, includeArchived?, tags?, symbol?, limit? }
+//   journal:  { from?, to?, symbols?, tags?, sourceId?, status?: 'open' | 'closed' }
+//   scan:     { screenId } or { expression, universe } (runs the screen)
+//   activity: as for activity:get-timeline
+const EXPORT_PREVIEW_CHARS = 100000;
+
+const EXPORT_SOURCE_LOADERS = {
+    notes: async ({ boardId = null, search = null, includeArchived = false, tags = null, symbol = null, limit = 1000 }) => {
+        const notes = search
+            ? searchStickyNotes(db, search, { includeArchived, boardId: boardId || undefined, limit })
+            : listStickyNotes(db, boardId);
+        const tagSet = Array.isArray(tags) && tags.length ? new Set(tags) : null;
+        const items = notes
+            .filter(note => !tagSet || (note.tags || []).some(tag => tagSet.has(tag)))
+            .filter(note => !symbol || note.symbol === symbol);
+        return { boardId, search, items };
+    },
+    journal: async ({ from = null, to = null, symbols = null, tags = null, sourceId = null, status = null }) => {
+        const symbolSet = Array.isArray(symbols) && symbols.length ? new Set(symbols) : null;
+        const tagSet = Array.isArray(tags) && tags.length ? new Set(tags) : null;
+        const items = db.prepare('SELECT data FROM trades ORDER BY timestamp').all()
+            .map(row => JSON.parse(row.data))
+            .filter(trade => from == null || trade.timestamp >= from)
+            .filter(trade => to == null || trade.timestamp <= to)
+            .filter(trade => !symbolSet || symbolSet.has(trade.symbol))
+            .filter(trade => !tagSet || (trade.tags || []).some(tag => tagSet.has(tag)))
+            .filter(trade => !sourceId || trade.sourceId === sourceId)
+            .filter(trade => !status || (status === 'closed') === (trade.exitPrice != null));
+        return { from, to, items, netPnl: items.reduce((n, trade) => n + (Number(trade.pnl) || 0), 0) };
+    },
+    scan: async (query) => {
+        const result = await getScanner().run(query.screenId || { expression: query.expression, universe: query.universe || {} });
+        return { ...result, items: result.matches };
+    },
+    activity: async (query) => {
+        const timeline = getActivityTimeline(db, query);
+        return { ...timeline, items: timeline.events };
+    }
+};
+
+const loadExportSource = (source, query) => EXPORT_SOURCE_LOADERS[source](query || {});
+
+// A saved template id, or a draft { name, source, format, extension?, body }
+const resolveExportTemplate = (template) => {
+    if (template && typeof template === 'object') return template;
+    const saved = getExportTemplate(db, template);
+    if (!saved) throw new AppError('NOT_FOUND', `Export template not found: ${template}`);
+    return saved;
+};
+
+// options: { source? }
+ipcMain.handle('exports:list-templates', async (event, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, templates: listExportTemplates(db, options || {}), sources: EXPORT_SOURCES };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('exports:save-template', async (event, template = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, template: saveExportTemplate(db, template || {}) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('exports:delete-template', async (event, id) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, deleted: deleteExportTemplate(db, id) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// Renders without writing; the text is cut at EXPORT_PREVIEW_CHARS
+ipcMain.handle('exports:preview', async (event, template, query = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const { text, missing, count } = await renderExport(resolveExportTemplate(template), query || {}, { loadSource: loadExportSource });
+        return { success: true, text: text.slice(0, EXPORT_PREVIEW_CHARS), truncated: text.length > EXPORT_PREVIEW_CHARS, missing, count };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// filePath null opens a save dialog named after the template
+ipcMain.handle('exports:render', async (event, template, query = {}, filePath = null) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const resolved = resolveExportTemplate(template);
+        const { text, missing, count } = await renderExport(resolved, query || {}, { loadSource: loadExportSource });
+        let target = filePath;
+        if (!target) {
+            const slug = String(resolved.name || 'export').toLowerCase().replace(/[^a-z0-9]+/g, '-').replace(/^-|-$/g, '') || 'export';
+            const extension = resolved.extension || 'txt';
+            const { canceled, filePath: chosen } = await dialog.showSaveDialog(mainWindow, {
+                defaultPath: `${slug}-${new Date().toISOString().slice(0, 10)}.${extension}`,
+                filters: [{ name: resolved.name || 'Export', extensions: [extension] }]
+            });
+            if (canceled || !chosen) return { success: false, canceled: true };
+            target = chosen;
+        }
+        fs.writeFileSync(target, text);
+        logSystemEvent('TEMPLATE_EXPORTED', { template: resolved.id || null, source: resolved.source, count, file: path.basename(target) });
+        return { success: true, filePath: target, count, missing };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- WATCHLIST FILES ---
+// The renderer owns the watchlist: export gets its items, import returns the
+// symbols to add (those not in `existing`) and leaves adding them to the caller.
+ipcMain.handle('watchlist:export', async (event, items = [], filePath = null, options = {}) => {
+    try {
+        const document = buildWatchlistDocument(items, options || {});
+        let target = filePath;
+        if (!target) {
+            const { canceled, filePath: chosen } = await dialog.showSaveDialog(mainWindow, {
+                defaultPath: `${(document.name || 'watchlist').replace(/[^A-Za-z0-9._-]+/g, '_')}.json`,
+                filters: [{ name: 'JSON', extensions: ['json'] }]
+            });
+            if (canceled || !chosen) return { success: false, canceled: true };
+            target = chosen;
+        }
+        fs.writeFileSync(target, JSON.stringify(document, null, 2));
+        logSystemEvent('WATCHLIST_EXPORTED', { count: document.symbols.length, file: path.basename(target) });
+        return { success: true, filePath: target, count: document.symbols.length };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('watchlist:import', async (event, filePath = null, existing = []) => {
+    try {
+        let target = filePath;
+        if (!target) {
+            const { canceled, filePaths } = await dialog.showOpenDialog(mainWindow, {
+                properties: ['openFile'],
+                filters: [{ name: 'Watchlist', extensions: ['json', 'txt'] }]
+            });
+            if (canceled || !filePaths.length) return { success: false, canceled: true };
+            target = filePaths[0];
+        }
+        const result = parseWatchlistFile(fs.readFileSync(target, 'utf8'), existing || []);
+        logSystemEvent('WATCHLIST_IMPORTED', { file: path.basename(target), symbols: result.symbols.length, duplicates: result.duplicates.length, invalid: result.invalid.length });
+        return { success: true, ...result };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- SCANNER ---
+// Screens run on the compute pool; every run is broadcast as 'scanner:result'
+let scanner = null;
+
+const getScanner = () => {
+    if (!db) throw new Error(t('errors.databaseNotInitialized'));
+    if (!scanner) {
+        scanner = createScanner({
+            db,
+            runJobs: runCachedJobs,
+            onResult: (result) => {
+                logSystemEvent('SCAN_COMPLETED', { screenId: result.screenId, scanned: result.scanned, matched: result.matches.length, durationMs: result.durationMs });
+                broadcast('scanner:result', result);
+            }
+        });
+    }
+    return scanner;
+};
+
+ipcMain.handle('scanner:validate-expression', async (event, expression) => validateExpression(expression));
+
+ipcMain.handle('scanner:list-screens', async () => {
+    try {
+        return { success: true, screens: getScanner().listScreens() };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// screen: { id?, name, expression, universe: { timeframe?, symbols?, datasets? } }
+ipcMain.handle('scanner:save-screen', async (event, screen) => {
+    try {
+        return { success: true, screen: getScanner().saveScreen(screen) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('scanner:delete-screen', async (event, id) => {
+    try {
+        return { success: true, deleted: getScanner().deleteScreen(id) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// target: a saved screen id or an unsaved { expression, universe }
+ipcMain.handle('scanner:run', async (event, target) => {
+    try {
+        return { success: true, result: await getScanner().run(target) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- HEATMAP ---
+// Watchlist heatmaps run on the compute pool; live ones are pushed as 'heatmap:update'
+const heatmaps = createHeatmapService({
+    runJobs: (jobs) => getComputePool().run(jobs),
+    onUpdate: (update) => { if (!liveFeedPaused) broadcast('heatmap:update', update); }
+});
+
+liveFeed.on('bar', (event) => heatmaps.handleBar(event));
+
+ipcMain.handle('heatmap:validate-script', async (event, script) => validateExpression(script, { condition: false }));
+
+// metric: 'change' | 'relativeVolume' | { script }; range: { timeframe, periods?, baseline? }; options: { live? }
+ipcMain.handle('heatmap:compute', async (event, symbols, metric, range = {}, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const heatmap = await heatmaps.compute(symbols, metric, range || {}, options || {});
+        logSystemEvent('HEATMAP_COMPUTED', { metric: heatmap.metric, timeframe: heatmap.timeframe, symbols: heatmap.rows.length, failed: Object.keys(heatmap.errors).length, live: !!heatmap.id, durationMs: heatmap.durationMs });
+        return { success: true, heatmap };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('heatmap:get', async (event, id) => {
+    const heatmap = heatmaps.get(id);
+    return heatmap ? { success: true, heatmap } : failure('NOT_FOUND', `Heatmap not found: ${id}`);
+});
+
+ipcMain.handle('heatmap:stop', async (event, id) => ({ success: heatmaps.stop(id) }));
+
+ipcMain.handle('heatmap:list', async () => heatmaps.list());
+
+// --- BROKER ORDER ROUTING ---
+// Live orders. The confirmation gate lives here, in the main process: every
+// placement and modification waits for the user to approve a native dialog,
+// whatever the renderer sends. Switching a broker from paper to live asks too.
+const brokerLog = (id) => ({ level, message }) => logSystemEvent(`${id.toUpperCase()}_BROKER_MESSAGE`, { message }, level);
+
+registerBroker(createAlpacaBroker({
+    onLog: brokerLog('alpaca'),
+    getCredentials: () => ({ keyId: alpacaSecrets('alpaca.keyId'), secretKey: alpacaSecrets('alpaca.secretKey') })
+}));
+
+const loadBrokerConfig = (id) => readJsonSetting(`broker.${id}`) || {};
+
+// Returns the dialog's { response } for the window that asked (or the main window)
+const askUser = (sender, options) => {
+    const win = (sender && BrowserWindow.fromWebContents(sender)) || mainWindow;
+    const opts = { type: 'warning', defaultId: 0, cancelId: 0, noLink: true, ...options };
+    return win && !win.isDestroyed() ? dialog.showMessageBox(win, opts) : dialog.showMessageBox(opts);
+};
+
+const confirmOrder = async ({ action, broker, summary, context }) => {
+    const { response } = await askUser(context, {
+        title: t('orders.confirm.title'),
+        message: t(action === 'modify' ? 'orders.confirm.modify' : 'orders.confirm.place', { broker: broker.name, mode: t(`orders.mode.${broker.mode}`) }),
+        detail: summary,
+        buttons: [t('orders.confirm.cancel'), t(action === 'modify' ? 'orders.confirm.submitModify' : 'orders.confirm.submit')]
+    });
+    return response === 1;
+};
+
+const orderManager = createOrderManager({
+    getBroker,
+    confirm: confirmOrder,
+    onLog: ({ level, message }) => logSystemEvent('ORDER_ROUTING', { message }, level),
+    onUpdate: (update) => {
+        if (update.event === 'fill' || update.event === 'rejected') {
+            logSystemEvent('ORDER_UPDATE', { broker: update.broker, event: update.event, symbol: update.order.symbol, side: update.order.side, qty: update.order.qty, filledQty: update.order.filledQty, price: update.order.avgFillPrice });
+        }
+        broadcast('orders:update', update);
+    }
+});
+
+const applyBrokerConfigs = () => {
+    ['alpaca'].forEach((id) => {
+        try { getBroker(id).configure(loadBrokerConfig(id)); } catch (e) {}
+    });
+};
+
+const orderError = (err) => errorResult(err, { declined: err.code === 'ORDER_NOT_CONFIRMED' });
+
+ipcMain.handle('brokers:list', async () => listBrokers());
+
+// config: { paper?: boolean } — going live needs the user's confirmation
+ipcMain.handle('brokers:configure', async (event, id, config = {}) => {
+    try {
+        const broker = getBroker(id);
+        if (config.paper === false && broker.getStatus().mode !== 'live') {
+            const { response } = await askUser(event.sender, {
+                title: t('orders.confirm.title'),
+                message: t('orders.confirm.goLive', { broker: broker.name }),
+                detail: t('orders.confirm.goLiveDetail'),
+                buttons: [t('orders.confirm.cancel'), t('orders.confirm.enableLive')]
+            });
+            if (response !== 1) return failure('CANCELED', 'Live trading was not confirmed', { declined: true });
+        }
+        const next = broker.configure(config);
+        writeJsonSetting(`broker.${id}`, next);
+        logSystemEvent('BROKER_CONFIGURED', { broker: id, mode: broker.getStatus().mode });
+        return { success: true, config: next, status: broker.getStatus() };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('brokers:test-credentials', async (event, id) => {
+    try {
+        return { success: true, ...(await getBroker(id).testCredentials()) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('orders:place', async (event, brokerId, order) => {
+    try {
+        return { success: true, order: await orderManager.place(brokerId, order, event.sender) };
+    } catch (err) {
+        return orderError(err);
+    }
+});
+
+ipcMain.handle('orders:modify', async (event, brokerId, id, changes) => {
+    try {
+        return { success: true, order: await orderManager.modify(brokerId, id, changes, event.sender) };
+    } catch (err) {
+        return orderError(err);
+    }
+});
+
+ipcMain.handle('orders:cancel', async (event, brokerId, id) => {
+    try {
+        return { success: true, order: await orderManager.cancel(brokerId, id) };
+    } catch (err) {
+        return orderError(err);
+    }
+});
+
+// options: { status: 'open' | 'closed' | 'all', limit }
+ipcMain.handle('orders:list', async (event, brokerId, options = {}) => {
+    try {
+        return { success: true, orders: await orderManager.list(brokerId, options) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// Status streaming into 'orders:update'
+ipcMain.handle('orders:start-updates', async (event, brokerId) => {
+    try {
+        orderManager.startUpdates(brokerId);
+        return { success: true };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('orders:stop-updates', async (event, brokerId) => {
+    try {
+        orderManager.stopUpdates(brokerId);
+        return { success: true };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('get-system-telemetry', async () => {
+    const mem = process.memoryUsage();
+    return {
+        processInfo: { version: process.versions.electron, uptime: Math.floor(process.uptime()), pid: process.pid },
+        resources: { memory: { rss: (mem.rss / 1024 / 1024).toFixed(2) + ' MB' } },
+        logBuffer: systemLogBuffer
+    };
+});
+
+ipcMain.handle('debug:get-perf-metrics', async (event, options = {}) => getPerfMetrics(options));
+
+ipcMain.handle('debug:reset-perf-metrics', async () => {
+    resetPerfMetrics();
+    return { success: true };
+});
+
+// What is held in memory right now: the process, each compute worker's heap and
+// the main-process caches with their sizes; the compute cache lives on disk and is
+// reported with its byte total
+ipcMain.handle('perf:memory-report', async () => {
+    try {
+        const mem = process.memoryUsage();
+        return {
+            success: true,
+            at: Date.now(),
+            process: { rss: mem.rss, heapUsed: mem.heapUsed, heapTotal: mem.heapTotal, external: mem.external, arrayBuffers: mem.arrayBuffers },
+            computeWorkers: computePool ? await computePool.memory() : [],
+            caches: {
+                crosshair: crosshair.stats(),
+                indicatorStreams: indicatorStreams ? indicatorStreams.size() : 0,
+                symbolSearch: symbolSearch.stats(),
+                segmentClassifiers: segmentClassifiers.size,
+                liveBars: latestLiveBars.size,
+                quotes: latestQuotes.size,
+                logBuffer: systemLogBuffer.length
+            },
+            computeCache: computeCache.stats()
+        };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// Hidden: compares JSON / SQLite / binary persistence on this machine's userData volume
+ipcMain.handle('debug:benchmark-storage', async (event, options = {}) => {
+    try {
+        let workerPath = path.join(__dirname, 'storageBenchmark.js');
+        if (!fs.existsSync(workerPath)) {
+             workerPath = path.join(app.getAppPath(), 'electron', 'storageBenchmark.js');
+        }
+        const result = await new Promise((resolve, reject) => {
+            const worker = new Worker(workerPath, { workerData: { storageBenchmark: { baseDir: app.getPath('userData'), options } } });
+            worker.once('message', (message) => { worker.terminate(); resolve(message); });
+            worker.once('error', reject);
+        });
+        if (result.success) {
+            logSystemEvent('STORAGE_BENCHMARK', { fastestSave: result.report.fastestSave, fastestLoad: result.report.fastestLoad, results: result.report.results.map(r => ({ id: r.id, saveP50: r.save.p50Ms, loadP50: r.load.p50Ms })) });
+        }
+        return result;
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// Hidden: builtin vs fastParse.js parsing of generated Date,Time,O,H,L,C,V rows,
+// plus slice vs fastAggregate.js range aggregation over generated bars
+ipcMain.handle('debug:benchmark-parse', async (event, options = {}) => {
+    try {
+        let workerPath = path.join(__dirname, 'fastParse.js');
+        if (!fs.existsSync(workerPath)) {
+             workerPath = path.join(app.getAppPath(), 'electron', 'fastParse.js');
+        }
+        const result = await new Promise((resolve, reject) => {
+            const worker = new Worker(workerPath, { workerData: { parseBenchmark: { options } } });
+            worker.once('message', (message) => { worker.terminate(); resolve(message); });
+            worker.once('error', reject);
+        });
+        if (result.success) {
+            const { rows, speedup, identical, aggregation } = result.report;
+            logSystemEvent('PARSE_BENCHMARK', { rows, speedup, identical, aggregationSpeedup: aggregation.speedup, aggregationIdentical: aggregation.identical });
+        }
+        return result;
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- DIAGNOSTICS ---
+const collectDiagnostics = () => runDiagnostics({
+    db,
+    dbPath: dbPathGlobal,
+    userDataPath: app.getPath('userData'),
+    assetsPath: resolveAssetsPath(),
+    libraryFiles: internalLibraryStorage,
+    providers: listProviders()
+});
+
+ipcMain.handle('diagnostics:run', async () => {
+    try {
+        const report = collectDiagnostics();
+        logSystemEvent('DIAGNOSTICS_RUN', { status: report.status }, report.status === 'fail' ? 'ERROR' : 'INFO');
+        return { success: true, report };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('diagnostics:create-support-bundle', async (event, filePath = null) => {
+    try {
+        let target = filePath;
+        if (!target) {
+            const stamp = new Date().toISOString().replace(/[:.]/g, '-');
+            const { canceled, filePath: chosen } = await dialog.showSaveDialog(mainWindow, {
+                defaultPath: `redpill-support-${stamp}.zip`,
+                filters: [{ name: 'Zip Archive', extensions: ['zip'] }]
+            });
+            if (canceled || !chosen) return { success: false, canceled: true };
+            target = chosen;
+        }
+        const settings = db ? Object.fromEntries(db.prepare('SELECT key, value FROM settings').all().map((r) => {
+            try { return [r.key, JSON.parse(r.value)]; } catch (e) { return [r.key, '[unparseable]']; }
+        })) : {};
+        const bytes = writeSupportBundle(target, {
+            diagnostics: collectDiagnostics(),
+            logs: systemLogBuffer,
+            perfMetrics: getPerfMetrics({ recent: 200 }),
+            settings
+        });
+        logSystemEvent('SUPPORT_BUNDLE_CREATED', { file: path.basename(target), bytes });
+        return { success: true, filePath: target, bytes };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- INTEGRITY SCAN ---
+// Runs at startup right after the database opens; the summary is logged, kept
+// for integrity:get-report and sent to the window as 'integrity:scan-complete'
+// when anything was found.
+let lastIntegrityReport = null;
+
+const runStartupIntegrityScan = () => {
+    if (!db) return null;
+    try {
+        lastIntegrityReport = runIntegrityScan({
+            db,
+            backupsDir: path.join(app.getPath('userData'), 'backups'),
+            openBackup: (file) => new Database(file, { readonly: true, fileMustExist: true })
+        });
+        // Repaired chart states need their annotations re-indexed
+        new Set(lastIntegrityReport.repaired.concat(lastIntegrityReport.removed).filter(r => r.table === 'drawings').map(r => r.key)).forEach((sourceId) => {
+            const row = db.prepare('SELECT data FROM drawings WHERE symbol = ?').get(sourceId);
+            indexChartState(db, sourceId, row ? JSON.parse(row.data) : null);
+        });
+        const { status, ms, checked, repaired, reset, removed, sqlite } = lastIntegrityReport;
+        logSystemEvent('INTEGRITY_SCAN', { status, ms, checked, sqlite, repaired: repaired.length, reset: reset.length, removed: removed.length },
+            status === 'fail' ? 'ERROR' : status === 'ok' ? 'INFO' : 'WARN');
+    } catch (err) {
+        lastIntegrityReport = { status: 'fail', checkedAt: Date.now(), problems: [err.message], repaired: [], reset: [], removed: [] };
+        logSystemEvent('INTEGRITY_SCAN_FAILED', { error: err.message }, 'ERROR');
+    }
+    return lastIntegrityReport;
+};
+
+const announceIntegrityReport = () => {
+    if (lastIntegrityReport && lastIntegrityReport.status !== 'ok') broadcast('integrity:scan-complete', lastIntegrityReport);
+};
+
+ipcMain.handle('integrity:get-report', async () => lastIntegrityReport);
+
+ipcMain.handle('integrity:run', async () => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, report: runStartupIntegrityScan() };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// Original bytes of every record the scan replaced or removed
+ipcMain.handle('integrity:list-quarantine', async () => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const rows = db.prepare('SELECT * FROM integrity_quarantine ORDER BY id DESC LIMIT 500').all();
+        return { success: true, entries: rows.map(r => ({ id: r.id, table: r.source_table, key: r.record_key, data: r.data, problem: r.problem, quarantinedAt: r.quarantined_at })) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- CRASH REPORTS ---
+// Reports are written at crash time and offered on the next start; only
+// reports the user agrees to send leave the machine.
+const crashReports = createCrashReporter({
+    reportsDir: path.join(app.getPath('userData'), 'CrashReports'),
+    crashDumpsDir: app.getPath('crashDumps'),
+    getLogs: () => systemLogBuffer,
+    environment: { appVersion: app.getVersion(), platform: process.platform, arch: process.arch, osRelease: os.release(), electron: process.versions.electron },
+    loadConfig: () => readJsonSetting('crash.reporting'),
+    saveConfig: (config) => writeJsonSetting('crash.reporting', config)
+});
+
+// Monitor only: Electron's own handling of the exception is unchanged
+process.on('uncaughtExceptionMonitor', (err) => { crashReports.recordCrash('main-exception', err); });
+
+process.on('unhandledRejection', (reason) => {
+    crashReports.recordCrash('unhandled-rejection', reason instanceof Error ? reason : new Error(String(reason)));
+    logSystemEvent('UNHANDLED_REJECTION', { message: reason instanceof Error ? reason.message : String(reason) }, 'ERROR');
+});
+
+app.on('render-process-gone', (event, webContents, details) => {
+    if (details.reason === 'clean-exit') return;
+    crashReports.recordCrash('renderer-gone', null, { reason: details.reason, exitCode: details.exitCode });
+    logSystemEvent('RENDERER_GONE', { reason: details.reason, exitCode: details.exitCode }, 'ERROR');
+});
+
+app.on('child-process-gone', (event, details) => {
+    if (details.reason === 'clean-exit') return;
+    crashReports.recordCrash('child-gone', null, { type: details.type, name: details.name || null, reason: details.reason, exitCode: details.exitCode });
+    logSystemEvent('CHILD_PROCESS_GONE', { type: details.type, reason: details.reason }, 'ERROR');
+});
+
+// Asks once per start about pending reports; without an endpoint they just stay listed
+const promptPendingCrashReports = async () => {
+    try {
+        crashReports.collectMinidumps();
+        const pending = crashReports.pendingReports();
+        const config = crashReports.getConfig();
+        if (!pending.length || !config.endpoint || !config.promptOnStart || !mainWindow || mainWindow.isDestroyed()) return;
+        const { response, checkboxChecked } = await dialog.showMessageBox(mainWindow, {
+            type: 'question',
+            title: t('crash.prompt.title'),
+            message: t('crash.prompt.message', { count: pending.length }),
+            detail: t('crash.prompt.detail'),
+            buttons: [t('crash.prompt.dontSend'), t('crash.prompt.send')],
+            defaultId: 1,
+            cancelId: 0,
+            noLink: true,
+            checkboxLabel: t('crash.prompt.dontAsk')
+        });
+        if (checkboxChecked) crashReports.configure({ promptOnStart: false });
+        if (response !== 1) {
+            pending.forEach(report => crashReports.deleteReport(report.id));
+            logSystemEvent('CRASH_REPORTS_DECLINED', { count: pending.length });
+            return;
+        }
+        let sent = 0;
+        for (const report of pending) {
+            try {
+                await crashReports.submitReport(report.id);
+                sent++;
+            } catch (err) {
+                logSystemEvent('CRASH_REPORT_SEND_FAILED', { id: report.id, error: err.message }, 'WARN');
+            }
+        }
+        logSystemEvent('CRASH_REPORTS_SENT', { sent, pending: pending.length });
+    } catch (err) {
+        logSystemEvent('CRASH_REPORT_PROMPT_FAILED', { error: err.message }, 'WARN');
+    }
+};
+
+ipcMain.handle('crash:list-reports', async () => {
+    try {
+        crashReports.collectMinidumps();
+        return { success: true, reports: crashReports.listReports() };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// The exact payload submit would send
+ipcMain.handle('crash:preview-report', async (event, id) => {
+    try {
+        return { success: true, report: crashReports.previewReport(id) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('crash:submit-report', async (event, id) => {
+    try {
+        await crashReports.submitReport(id);
+        logSystemEvent('CRASH_REPORTS_SENT', { sent: 1, pending: 1 });
+        return { success: true };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('crash:delete-report', async (event, id) => {
+    try {
+        return { success: crashReports.deleteReport(id) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('crash:get-config', async () => crashReports.getConfig());
+
+// updates: { endpoint?, promptOnStart? }
+ipcMain.handle('crash:configure', async (event, updates = {}) => {
+    try {
+        return { success: true, config: crashReports.configure(updates) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- USAGE ANALYTICS ---
+ipcMain.handle('usage:record', async (event, feature) => ({ success: usageAnalytics.record(feature) }));
+
+ipcMain.handle('usage:get-summary', async (event, options = {}) => {
+    try {
+        return { success: true, ...usageAnalytics.getSummary(options || {}) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// The exact payload the next upload would send
+ipcMain.handle('usage:preview', async () => {
+    try {
+        return { success: true, payload: usageAnalytics.preview() };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('usage:get-config', async () => usageAnalytics.getConfig());
+
+// updates: { enabled?, endpoint? }
+ipcMain.handle('usage:configure', async (event, updates = {}) => {
+    try {
+        const config = usageAnalytics.configure(updates || {});
+        logSystemEvent('USAGE_ANALYTICS_CONFIGURED', { enabled: config.enabled });
+        return { success: true, config };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('usage:upload-now', async () => {
+    try {
+        return { success: true, ...(await usageAnalytics.upload()) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('usage:reset-install-id', async () => {
+    try {
+        return { success: true, config: usageAnalytics.resetInstallId() };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('usage:clear', async () => {
+    try {
+        return { success: true, deleted: usageAnalytics.clear() };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- READ-ONLY MODE ---
+// Services that write on their own schedule only start once the session is
+// writable, and stop when read-only is turned on at runtime: query_only only
+// covers the main connection, and watch-folder imports write through the
+// ingest worker's own. Turning read-only off starts them again.
+let backgroundWritersStarted = false;
+
+const startBackgroundWriters = () => {
+    if (backgroundWritersStarted || readOnly) return;
+    backgroundWritersStarted = true;
+    startWatchFolders();
+    startScheduler();
+    startDownloadManager();
+    startNoteAutoArchive();
+    barRecorder.setEnabled(getBackgroundConfig().recordLiveBars);
+    scheduleIdleCompaction();
+};
+
+const stopBackgroundWriters = () => {
+    if (!backgroundWritersStarted) return;
+    backgroundWritersStarted = false;
+    if (watchFolderService) watchFolderService.stop();
+    if (scheduler) scheduler.stop();
+    if (downloadManager) downloadManager.stop();
+    stopNoteAutoArchive();
+    barRecorder.setEnabled(false);
+    if (compactionTimer) clearInterval(compactionTimer);
+    compactionTimer = null;
+};
+
+const setReadOnly = (enabled) => {
+    if (!enabled && !workspaceLock.isHeld()) {
+        const result = workspaceLock.acquire();
+        if (!result.acquired) throw new Error(t('workspace.lock.inUse', { host: result.holder.hostname, time: new Date(result.holder.startedAt).toLocaleString() }));
+    }
+    readOnly = !!enabled;
+    if (db) db.pragma(`query_only = ${readOnly ? 'ON' : 'OFF'}`);
+    if (readOnly) {
+        usageAnalytics.stop();
+        stopBackgroundWriters();
+    } else {
+        usageAnalytics.start();
+        startBackgroundWriters();
+    }
+    logSystemEvent('READ_ONLY_MODE', { enabled: readOnly }, readOnly ? 'WARN' : 'INFO');
+    broadcast('app:read-only-changed', { readOnly });
+};
+
+ipcMain.handle('app:get-read-only', async () => ({ readOnly }));
+
+ipcMain.handle('app:set-read-only', async (event, enabled) => {
+    try {
+        setReadOnly(enabled);
+        return { success: true, readOnly };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- WORKSPACE LOCK ---
+// Taken right after the profile is chosen and released when the database
+// closes. Read-only sessions don't need it; an instance whose lock was taken
+// over falls back to read-only.
+const workspaceLock = createWorkspaceLock({
+    dir: app.getPath('userData'),
+    onLost: (holder) => {
+        logSystemEvent('WORKSPACE_LOCK_LOST', { hostname: holder.hostname, pid: holder.pid }, 'ERROR');
+        setReadOnly(true);
+        broadcast('workspace:lock-lost', { hostname: holder.hostname, since: holder.startedAt });
+    }
+});
+
+// false when the user chose to quit rather than share the workspace
+const acquireWorkspaceLock = () => {
+    if (readOnly) return true;
+    const result = workspaceLock.acquire();
+    if (result.acquired) {
+        if (result.replaced) logSystemEvent('WORKSPACE_LOCK_REPLACED', { hostname: result.replaced.hostname, pid: result.replaced.pid, stale: result.replaced.stale }, 'WARN');
+        return true;
+    }
+    const { holder } = result;
+    const response = dialog.showMessageBoxSync({
+        type: 'warning',
+        title: t('workspace.lock.title'),
+        message: t('workspace.lock.inUse', { host: holder.hostname, time: new Date(holder.startedAt).toLocaleString() }),
+        detail: t('workspace.lock.detail'),
+        buttons: [t('workspace.lock.quit'), t('workspace.lock.readOnly'), t('workspace.lock.takeOver')],
+        defaultId: 1,
+        cancelId: 0,
+        noLink: true
+    });
+    if (response === 2) {
+        workspaceLock.acquire({ force: true });
+        logSystemEvent('WORKSPACE_LOCK_TAKEN_OVER', { hostname: holder.hostname, pid: holder.pid }, 'WARN');
+        return true;
+    }
+    if (response === 1) {
+        readOnly = true;
+        return true;
+    }
+    app.exit(0);
+    return false;
+};
+
+ipcMain.handle('workspace:get-lock', async () => ({ held: workspaceLock.isHeld(), holder: workspaceLock.holder() }));
+
+// Explicit takeover from a read-only session, then writable
+ipcMain.handle('workspace:take-over', async () => {
+    try {
+        const holder = workspaceLock.holder();
+        workspaceLock.acquire({ force: true });
+        logSystemEvent('WORKSPACE_LOCK_TAKEN_OVER', { hostname: holder ? holder.hostname : null, pid: holder ? holder.pid : null }, 'WARN');
+        setReadOnly(false);
+        return { success: true };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- PROFILES ---
+// The active profile is fixed for the life of the process; switching relaunches
+// with --profile and quits through the normal shutdown pipeline.
+const relaunchWithProfile = (id) => {
+    app.relaunch({ args: stripProfileArg(process.argv.slice(1)).concat(`--profile=${id}`) });
+};
+
+// Startup picker when several profiles exist and no --profile was given;
+// false when the app is relaunching into another profile
+const pickStartupProfile = () => {
+    const all = profiles.list();
+    if (requestedProfile || !profiles.getConfig().pickAtStartup || all.length < 2) return true;
+    const response = dialog.showMessageBoxSync({
+        type: 'question',
+        title: t('profiles.picker.title'),
+        message: t('profiles.picker.message'),
+        buttons: all.map(p => p.name),
+        defaultId: Math.max(0, all.findIndex(p => p.id === activeProfile.id)),
+        cancelId: Math.max(0, all.findIndex(p => p.id === activeProfile.id)),
+        noLink: true
+    });
+    const chosen = all[response];
+    if (!chosen || chosen.id === activeProfile.id) return true;
+    if (!readOnly) profiles.setLastUsed(chosen.id);
+    relaunchWithProfile(chosen.id);
+    app.exit(0);
+    return false;
+};
+
+ipcMain.handle('profiles:list', async () => {
+    try {
+        return { success: true, profiles: profiles.list(), activeId: activeProfile.id, pickAtStartup: profiles.getConfig().pickAtStartup };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('profiles:create', async (event, name) => {
+    try {
+        const profile = profiles.create(name);
+        logSystemEvent('PROFILE_CREATED', { id: profile.id });
+        return { success: true, profile };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('profiles:rename', async (event, id, name) => {
+    try {
+        return { success: true, profile: profiles.rename(id, name) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('profiles:delete', async (event, id) => {
+    try {
+        if (id === activeProfile.id) throw new Error('Switch to another profile before deleting this one');
+        profiles.remove(id);
+        logSystemEvent('PROFILE_DELETED', { id });
+        return { success: true };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('profiles:switch', async (event, id) => {
+    try {
+        const profile = profiles.get(id);
+        if (!profile) throw new Error(`Profile not found: ${id}`);
+        if (profile.id === activeProfile.id) return { success: true, relaunching: false };
+        // A read-only session relaunches into the profile without recording the switch
+        if (!readOnly) profiles.setLastUsed(profile.id);
+        logSystemEvent('PROFILE_SWITCH', { from: activeProfile.id, to: profile.id });
+        relaunchWithProfile(profile.id);
+        app.quit();
+        return { success: true, relaunching: true };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('profiles:configure', async (event, updates = {}) => {
+    try {
+        if (updates.pickAtStartup !== undefined) profiles.setPickAtStartup(updates.pickAtStartup);
+        return { success: true, pickAtStartup: profiles.getConfig().pickAtStartup };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- STORAGE ---
+ipcMain.handle('storage:get-report', async () => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, report: getStorageReport({ db, dbPath: dbPathGlobal, soundsDir: soundsDirPath(), computeCacheDir: computeCacheDirPath() }) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// targets: any of 'news' | 'fred' | 'stale_csv' | 'csv_cache'
+ipcMain.handle('storage:purge-caches', async (event, targets = []) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const deleted = purgeCaches(db, targets);
+        logSystemEvent('STORAGE_PURGED', deleted);
+        return { success: true, deleted };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('storage:delete-unused-sounds', async () => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const removed = deleteUnusedSounds(db, soundsDirPath());
+        logSystemEvent('UNUSED_SOUNDS_DELETED', { count: removed.length });
+        return { success: true, removed };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- ORPHANED CHART STATES ---
+// knownNames: file names / symbols from the renderer's recent-files registry
+ipcMain.handle('drawings:find-orphaned', async (event, knownNames = []) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, orphans: findOrphanedChartStates(db, internalLibraryStorage, knownNames) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// Guarded: each symbol is re-checked and only moved if it is still orphaned
+ipcMain.handle('drawings:trash-orphaned', async (event, symbols = [], knownNames = []) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        if (!Array.isArray(symbols) || symbols.length === 0) return failure('INVALID_INPUT', 'No chart states selected');
+        const stillOrphaned = new Set(findOrphanedChartStates(db, internalLibraryStorage, knownNames).map(o => o.symbol));
+        const eligible = symbols.filter(s => stillOrphaned.has(s));
+        const moved = moveChartStatesToTrash(db, eligible, 'orphaned');
+        moved.forEach(symbol => indexChartState(db, symbol, null));
+        logSystemEvent('ORPHANED_CHART_STATES_TRASHED', { requested: symbols.length, moved: moved.length });
+        return { success: true, moved, rejected: symbols.filter(s => !eligible.includes(s)) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('drawings:list-trash', async () => {
+    try {
+        return db ? listChartTrash(db) : [];
+    } catch (e) {
+        return [];
+    }
+});
+
+ipcMain.handle('drawings:restore-from-trash', async (event, symbols = []) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const result = restoreChartStates(db, symbols);
+        result.restored.forEach(symbol => indexChartState(db, symbol, readChartState(symbol)));
+        return { success: true, ...result };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('drawings:empty-trash', async (event, olderThanDays = 30) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, deleted: emptyChartTrash(db, olderThanDays) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- DRAWING TEMPLATES ---
+ipcMain.handle('templates:save', async (event, name, objects, description = null) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const template = saveDrawingTemplate(db, name, objects, description);
+        logSystemEvent('TEMPLATE_SAVED', template);
+        return { success: true, template };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('templates:list', async () => {
+    try {
+        return db ? listDrawingTemplates(db) : [];
+    } catch (e) {
+        return [];
+    }
+});
+
+ipcMain.handle('templates:get', async (event, name) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const template = getDrawingTemplate(db, name);
+        return template ? { success: true, template } : failure('NOT_FOUND', `Template not found: ${name}`);
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('templates:delete', async (event, name) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: deleteDrawingTemplate(db, name) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// options: { anchor?: { time?, price? } } — where the template's earliest point should land
+ipcMain.handle('templates:apply', async (event, sourceId, name, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const previous = readChartState(sourceId);
+        const result = applyDrawingTemplate(db, sourceId, name, options);
+        recordChartEdit(db, sourceId, previous || { drawings: [] }, result.state);
+        publishDrawingChanges(sourceId, previous, result.state, event.sender.id);
+        logSystemEvent('TEMPLATE_APPLIED', { sourceId, name, added: result.added });
+        return { success: true, ...result };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- THEMES ---
+const DEFAULT_THEME = 'Red Pill Dark';
+
+let sessionTheme = null;
+const activeThemeName = () => sessionTheme || readJsonSetting('themes.active') || DEFAULT_THEME;
+
+ipcMain.handle('themes:list', async () => {
+    try {
+        return db ? listThemes(db, activeThemeName()) : [];
+    } catch (e) {
+        return [];
+    }
+});
+
+ipcMain.handle('themes:get-active', async () => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const theme = getTheme(db, activeThemeName()) || getTheme(db, DEFAULT_THEME);
+        return { success: true, theme, chartConfig: toChartConfig(theme) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('themes:save', async (event, theme) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const saved = saveTheme(db, theme);
+        // Editing the active theme re-applies it everywhere
+        if (saved.name === activeThemeName()) broadcast('themes:applied', { theme: saved, chartConfig: toChartConfig(saved) });
+        return { success: true, theme: saved };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('themes:delete', async (event, name) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const deleted = deleteTheme(db, name);
+        if (deleted && name === activeThemeName()) {
+            writeJsonSetting('themes.active', DEFAULT_THEME);
+            const fallback = getTheme(db, DEFAULT_THEME);
+            broadcast('themes:applied', { theme: fallback, chartConfig: toChartConfig(fallback) });
+        }
+        return { success: deleted };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// persist: false switches for this session only (read-only workspaces)
+const applyThemeByName = (name, { persist = true } = {}) => {
+    const theme = getTheme(db, name);
+    if (!theme) throw new Error(`Theme not found: ${name}`);
+    if (persist) writeJsonSetting('themes.active', theme.name);
+    sessionTheme = persist ? null : theme.name;
+    const payload = { theme, chartConfig: toChartConfig(theme) };
+    broadcast('themes:applied', payload);
+    applyAppearanceToWindows();
+    logSystemEvent('THEME_APPLIED', { name: theme.name });
+    return payload;
+};
+
+ipcMain.handle('themes:apply', async (event, name) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const payload = applyThemeByName(name);
+        // Picking a palette while following the OS makes it the one for the current scheme
+        const config = getAppearanceConfig();
+        if (config.followScheme) writeJsonSetting('appearance', { ...config, [`${appearance.state().scheme}Theme`]: payload.theme.name });
+        return { success: true, ...payload };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('themes:export', async (event, name, filePath = null) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const theme = getTheme(db, name);
+        if (!theme) return failure('NOT_FOUND', `Theme not found: ${name}`);
+        let target = filePath;
+        if (!target) {
+            const { canceled, filePath: chosen } = await dialog.showSaveDialog(mainWindow, {
+                defaultPath: `${theme.name.replace(/[^\w\- ]+/g, '_')}.theme.json`,
+                filters: [{ name: 'Red Pill Theme', extensions: ['json'] }]
+            });
+            if (canceled || !chosen) return { success: false, canceled: true };
+            target = chosen;
+        }
+        exportThemeFile(theme, target);
+        return { success: true, filePath: target };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('themes:import', async (event, filePath = null) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        let target = filePath;
+        if (!target) {
+            const { canceled, filePaths } = await dialog.showOpenDialog(mainWindow, {
+                properties: ['openFile'],
+                filters: [{ name: 'Red Pill Theme', extensions: ['json'] }]
+            });
+            if (canceled || !filePaths.length) return { success: false, canceled: true };
+            target = filePaths[0];
+        }
+        const theme = saveTheme(db, readThemeFile(target));
+        logSystemEvent('THEME_IMPORTED', { name: theme.name });
+        return { success: true, theme };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- APPEARANCE ---
+// Setting 'appearance': { mode: 'system' | 'dark' | 'light', followScheme, darkTheme, lightTheme }.
+// With followScheme the active palette switches when the effective scheme does
+// (OS change in 'system' mode, or a new mode). Window background and titlebar
+// overlay follow the active palette, for existing windows and at creation.
+const DEFAULT_APPEARANCE = { mode: 'system', followScheme: true, darkTheme: DEFAULT_THEME, lightTheme: 'Daylight' };
+
+const getAppearanceConfig = () => ({ ...DEFAULT_APPEARANCE, ...(readJsonSetting('appearance') || {}) });
+
+const currentWindowColors = () => {
+    const scheme = appearance.state().scheme;
+    let theme = null;
+    try { theme = db ? getTheme(db, activeThemeName()) : null; } catch (e) { theme = null; }
+    return windowColors(theme, scheme);
+};
+
+const applyWindowAppearance = (win, colors = currentWindowColors()) => {
+    if (!win || win.isDestroyed() || presentation.isDimmer(win) || win.webContents.isOffscreen()) return;
+    win.setBackgroundColor(colors.background);
+    // Only windows created with titleBarOverlay support updating it (Windows / Linux)
+    if (process.platform !== 'darwin' && typeof win.setTitleBarOverlay === 'function') {
+        try { win.setTitleBarOverlay({ color: colors.background, symbolColor: colors.symbol }); } catch (e) { /* window without an overlay */ }
+    }
+};
+
+const applyAppearanceToWindows = () => {
+    const colors = currentWindowColors();
+    BrowserWindow.getAllWindows().forEach(win => applyWindowAppearance(win, colors));
+};
+
+const appearance = createAppearanceTracker({
+    nativeTheme,
+    systemPreferences,
+    getMode: () => getAppearanceConfig().mode,
+    onChange: (state, previous) => {
+        if (previous) logSystemEvent('APPEARANCE_CHANGED', state);
+        const config = getAppearanceConfig();
+        const wanted = state.scheme === 'dark' ? config.darkTheme : config.lightTheme;
+        if (db && config.followScheme && wanted && wanted !== activeThemeName() && (!previous || previous.scheme !== state.scheme)) {
+            try { applyThemeByName(wanted, { persist: !readOnly }); } catch (err) { logSystemEvent('APPEARANCE_THEME_MISSING', { name: wanted, error: err.message }, 'WARN'); }
+        }
+        applyAppearanceToWindows();
+        broadcast('appearance:changed', { ...state, config });
+    }
+});
+
+// Windows opened later (detached charts, dialogs) get the current colors once they exist
+app.on('browser-window-created', (event, win) => setImmediate(() => applyWindowAppearance(win)));
+
+ipcMain.handle('appearance:get-state', async () => ({ ...appearance.state(), config: getAppearanceConfig(), colors: currentWindowColors() }));
+
+ipcMain.handle('appearance:set-config', async (event, patch = {}) => {
+    try {
+        const next = { ...getAppearanceConfig(), ...patch };
+        if (!APPEARANCE_MODES.includes(next.mode)) return failure('INVALID_INPUT', `Invalid appearance mode: ${next.mode}`);
+        next.followScheme = next.followScheme !== false;
+        for (const key of ['darkTheme', 'lightTheme']) {
+            if (db && next[key] && !getTheme(db, next[key])) return failure('NOT_FOUND', `Theme not found: ${next[key]}`);
+        }
+        writeJsonSetting('appearance', next);
+        const state = appearance.refresh();
+        return { success: true, ...state, config: next };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- LAYOUTS ---
+// Renderer layouts are opaque JSON kept as settings 'layout.<name>', so settings profiles carry them
+const layoutKey = (name) => {
+    const trimmed = String(name || '').trim();
+    if (!trimmed || trimmed.length > 100) throw new Error('Layout name must be 1 to 100 characters');
+    return `layout.${trimmed}`;
+};
+
+ipcMain.handle('layouts:save', async (event, name, data) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        writeJsonSetting(layoutKey(name), data);
+        return { success: true };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('layouts:load', async (event, name) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const data = readJsonSetting(layoutKey(name));
+        return data == null ? failure('NOT_FOUND', `Layout not found: ${name}`) : { success: true, data };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('layouts:list', async () => {
+    try {
+        return db ? db.prepare("SELECT key FROM settings WHERE key LIKE 'layout.%' ORDER BY key COLLATE NOCASE").all().map(r => r.key.slice('layout.'.length)) : [];
+    } catch (e) {
+        return [];
+    }
+});
+
+// --- SETTINGS PROFILE ---
+// options: { sections?: subset of SETTINGS_SECTIONS }. Export writes a settings file;
+// import with dryRun returns the plan (per-section added / changed / unchanged keys)
+// without writing. Settings that are read when used take effect at once; the
+// services that cache theirs are re-applied below.
+const pickSettingsFile = async (mode, filePath) => {
+    if (filePath) return filePath;
+    if (mode === 'save') {
+        const { canceled, filePath: chosen } = await dialog.showSaveDialog(mainWindow, {
+            defaultPath: `redpill-settings-${new Date().toISOString().slice(0, 10)}.json`,
+            filters: [{ name: 'Red Pill Settings', extensions: ['json'] }]
+        });
+        return canceled ? null : chosen;
+    }
+    const { canceled, filePaths } = await dialog.showOpenDialog(mainWindow, { properties: ['openFile'], filters: [{ name: 'Red Pill Settings', extensions: ['json'] }] });
+    return canceled || !filePaths.length ? null : filePaths[0];
+};
+
+const settingsSections = (options) => {
+    const sections = options && Array.isArray(options.sections) ? options.sections : SETTINGS_SECTIONS;
+    const unknown = sections.filter(s => !SETTINGS_SECTIONS.includes(s));
+    if (unknown.length) throw new Error(`Unknown settings sections: ${unknown.join(', ')}`);
+    return sections;
+};
+
+ipcMain.handle('settings:export', async (event, filePath = null, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const sections = settingsSections(options);
+        const target = await pickSettingsFile('save', filePath);
+        if (!target) return { success: false, canceled: true };
+        const profile = buildSettingsProfile(db, { sections, appVersion: app.getVersion() });
+        const bytes = writeSettingsFile(target, profile);
+        logSystemEvent('SETTINGS_EXPORTED', { file: path.basename(target), sections, stripped: profile.stripped.length });
+        return { success: true, filePath: target, bytes, stripped: profile.stripped };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// Services that keep their settings in memory pick up imported ones here
+const reapplyImportedSettings = (keys) => {
+    const touched = (prefix) => keys.some(k => k === prefix || k.startsWith(`${prefix}.`));
+    if (touched('keybindings')) {
+        unavailableShortcuts = registerGlobalShortcuts();
+        broadcast('keybindings:changed', keybindingSnapshot(unavailableShortcuts));
+    }
+    if (touched('proxy')) applyProxyConfig();
+    if (touched('tlsPolicy')) applyTlsPolicy();
+    if (touched('i18n')) initializeLanguage();
+    if (touched('appearance')) appearance.refresh();
+    if (touched('themes')) {
+        const theme = getTheme(db, activeThemeName());
+        if (theme) broadcast('themes:applied', { theme, chartConfig: toChartConfig(theme) });
+        applyAppearanceToWindows();
+    }
+    if (touched('clipboard')) clipboardWatcher.restart();
+    if (touched('inbox')) startSignalInbox().catch(() => {});
+    if (touched('idle')) idleMonitor.check();
+    if (touched('power')) powerGovernor.refresh();
+    if (touched('compute')) applyFastAggregate();
+};
+
+// options: { sections?, dryRun? }
+ipcMain.handle('settings:import', async (event, filePath = null, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const sections = settingsSections(options);
+        const source = await pickSettingsFile('open', filePath);
+        if (!source) return { success: false, canceled: true };
+        const plan = planSettingsImport(db, readSettingsFile(source), { sections });
+        if (options.dryRun) return { success: true, dryRun: true, filePath: source, plan: publicPlan(plan) };
+        const applied = applySettingsImport(db, plan);
+        reapplyImportedSettings([...applied.keys, ...(applied.themes.length ? ['themes'] : [])]);
+        logSystemEvent('SETTINGS_IMPORTED', { file: path.basename(source), keys: applied.keys.length, themes: applied.themes.length });
+        broadcast('settings:imported', { keys: applied.keys, themes: applied.themes });
+        return { success: true, filePath: source, plan: publicPlan(plan), applied };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- DEMO CONTENT ---
+// demo:install writes the demo workspace (demoContent.js): datasets, the DEMO
+// chart, a notes board and alerts. The watchlist belongs to the renderer, so it
+// comes back in the result and in 'demo:installed' for the renderer to merge.
+const installDemoContent = () => {
+    if (!db) throw new Error(t('errors.databaseNotInitialized'));
+    const datasets = DEMO_DATASETS.map(spec => generateSyntheticDataset(spec));
+    const bars = db.prepare('SELECT timestamp, open, high, low, close FROM market_data WHERE symbol = ? AND timeframe = ? ORDER BY timestamp DESC LIMIT 300')
+        .all(DEMO_SYMBOL, DEMO_DATASETS[0].timeframe).reverse();
+    const { state, levels } = buildDemoChartState(bars);
+    const saved = commitChartState(DEMO_SYMBOL, state, readChartState(DEMO_SYMBOL), { journal: false }, null);
+    if (!saved.success) throw new Error(saved.error);
+
+    let board = listBoards(db, { includeArchived: true }).find(b => b.name.toLowerCase() === DEMO_BOARD_NAME.toLowerCase());
+    if (!board) board = createBoard(db, DEMO_BOARD_NAME);
+    else if (board.archived) board = setBoardArchived(db, board.id, false);
+    syncBus.publish('notes', { changes: [], boards: listBoards(db, { includeArchived: true }) }, null);
+    const creates = buildDemoNotes({ boardId: board.id, levels });
+    const deletes = creates.filter(op => getStickyNote(db, op.note.id)).map(op => ({ op: 'delete', id: op.note.id }));
+    const { changes } = bulkUpdateNotes(db, [...deletes, ...creates]);
+    publishNoteChanges(changes);
+
+    const alerts = alertEngine ? buildDemoAlerts({ levels }).map(alert => alertEngine.saveAlert(alert)) : [];
+    const watchlist = buildWatchlistDocument(DEMO_WATCHLIST, { name: 'Demo' });
+    const result = {
+        symbol: DEMO_SYMBOL,
+        datasets: datasets.map(d => d.id),
+        drawings: state.drawings.length,
+        boardId: board.id,
+        notes: creates.map(op => op.note.id),
+        alerts: alerts.map(a => a.id),
+        watchlist: watchlist.symbols
+    };
+    logSystemEvent('DEMO_INSTALLED', { datasets: result.datasets, drawings: result.drawings, notes: result.notes.length, alerts: result.alerts.length });
+    broadcast('demo:installed', result);
+    return result;
+};
+
+ipcMain.handle('demo:install', async () => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, prefix: DEMO_PREFIX, ...installDemoContent() };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- ONBOARDING ---
+// State in setting 'onboarding' (onboarding.js); the wizard asks for the state,
+// completes the current step and follows 'onboarding:changed'.
+const SAMPLE_DATASETS = [
+    { symbol: 'SAMPLE', timeframe: '1h', bars: 5000, model: 'gbm', seed: 42 },
+    { symbol: 'SAMPLE', timeframe: '1D', bars: 1500, model: 'gbm', volatility: 0.015, seed: 42 }
+];
+const DATA_FILE_EXTENSIONS = ['.csv', '.hst'];
+const SHARE_FILE_EXTENSION = '.rpshare'; // opened read-only with share:open
+const DATA_FILE_PROG_ID = 'RedPillCharting.DataFile';
+
+const defaultFolders = () => [resolveAssetsPath(), path.join(app.getPath('userData'), 'backups'), path.join(app.getPath('userData'), 'reports')];
+
+// Windows: offered under "Open with" (OpenWithProgids) without taking over the default app.
+// Elsewhere associations come from the installer / desktop entry.
+const runReg = (args) => new Promise((resolve, reject) => {
+    execFile('reg', args, { windowsHide: true }, err => (err ? reject(err) : resolve()));
+});
+
+const registerFileAssociations = async () => {
+    if (process.platform !== 'win32') return { registered: false, reason: `Not supported on ${process.platform}; use the installer's associations` };
+    const command = [process.execPath, ...(app.isPackaged ? [] : [app.getAppPath()])].map(p => `"${p}"`).join(' ');
+    const classes = 'HKCU\\Software\\Classes';
+    await runReg(['add', `${classes}\\${DATA_FILE_PROG_ID}`, '/ve', '/d', 'Market data file', '/f']);
+    await runReg(['add', `${classes}\\${DATA_FILE_PROG_ID}\\shell\\open\\command`, '/ve', '/d', `${command} "%1"`, '/f']);
+    for (const ext of DATA_FILE_EXTENSIONS) {
+        await runReg(['add', `${classes}\\${ext}\\OpenWithProgids`, '/v', DATA_FILE_PROG_ID, '/t', 'REG_NONE', '/f']);
+    }
+    logSystemEvent('FILE_ASSOCIATIONS_REGISTERED', { extensions: DATA_FILE_EXTENSIONS });
+    return { registered: true, extensions: DATA_FILE_EXTENSIONS };
+};
+
+const onboarding = createOnboarding({
+    loadState: () => readJsonSetting('onboarding'),
+    saveState: (state) => writeJsonSetting('onboarding', state),
+    detectWorkspace: () => {
+        const datasets = db ? db.prepare('SELECT COUNT(*) AS n FROM datasets').get().n : 0;
+        const charts = db ? db.prepare('SELECT COUNT(*) AS n FROM drawings').get().n : 0;
+        return { empty: datasets === 0 && charts === 0 && internalLibraryStorage.length === 0, datasets, charts, libraryFiles: internalLibraryStorage.length };
+    },
+    actions: {
+        folders: async () => {
+            const created = defaultFolders().filter(dir => !fs.existsSync(dir));
+            created.forEach(dir => fs.mkdirSync(dir, { recursive: true }));
+            return { folders: defaultFolders(), created };
+        },
+        // options.specs: synthetic.js specs to use instead of the sample pair;
+        // options.demo: install the full demo workspace instead
+        'sample-data': async ({ specs = SAMPLE_DATASETS, demo = false } = {}) => {
+            if (demo) return installDemoContent();
+            return { datasets: specs.slice(0, 10).map(spec => generateSyntheticDataset(spec).id) };
+        },
+        'file-associations': () => registerFileAssociations()
+    }
+});
+
+ipcMain.handle('onboarding:get-state', async () => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, ...onboarding.getState() };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// options: { skip?, ...step options (sample-data: { specs?, demo? }) }
+ipcMain.handle('onboarding:complete-step', async (event, stepId, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const state = await onboarding.completeStep(stepId, options || {});
+        logSystemEvent('ONBOARDING_STEP_COMPLETED', { step: stepId, skipped: !!(options && options.skip) });
+        broadcast('onboarding:changed', state);
+        return { success: true, ...state };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('onboarding:reset', async () => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const state = onboarding.reset();
+        broadcast('onboarding:changed', state);
+        return { success: true, ...state };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- OPENED FILES ---
+// Data files handed to the app (command line, "Open with", macOS open-file) reach the
+// renderer as 'app:open-file' once it has loaded. A launch carrying files while this
+// profile is already running passes them to the running instance and exits.
+const openableFiles = (argv) => argv.filter(arg => !arg.startsWith('-') && [...DATA_FILE_EXTENSIONS, SHARE_FILE_EXTENSION].includes(path.extname(arg).toLowerCase()) && fs.existsSync(arg))
+    .map(arg => path.resolve(arg));
+
+const pendingOpenFiles = openableFiles(process.argv.slice(1));
+let rendererLoaded = false;
+
+const openFileInApp = (filePath) => {
+    if (!rendererLoaded || !mainWindow || mainWindow.isDestroyed()) {
+        pendingOpenFiles.push(filePath);
+        return;
+    }
+    logSystemEvent('FILE_OPEN_REQUESTED', { file: path.basename(filePath) });
+    mainWindow.webContents.send('app:open-file', { path: filePath });
+};
+
+const flushOpenFiles = () => {
+    rendererLoaded = true;
+    pendingOpenFiles.splice(0).forEach(openFileInApp);
+};
+
+if (!app.requestSingleInstanceLock() && pendingOpenFiles.length) app.exit(0);
+
+app.on('second-instance', (event, argv) => {
+    const files = openableFiles(argv.slice(1));
+    if (!files.length) return;
+    showMainWindow();
+    files.forEach(openFileInApp);
+});
+
+app.on('open-file', (event, filePath) => {
+    event.preventDefault();
+    openFileInApp(filePath);
+});
+
+// --- PLAYBACK EXPORT ---
+// range: { from?, to? } timestamps; options: { format: 'gif' | 'mp4', filePath?, width?, height?,
+// fps?, windowBars?, barsPerFrame?, maxFrames?, ffmpegPath? }. Colors follow the active theme.
+ipcMain.handle('playback:export', async (event, id, range = {}, options = {}) => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        const { symbol, timeframe } = parseDatasetId(id);
+        const format = options.format || 'gif';
+        if (format !== 'gif' && format !== 'mp4') return failure('UNSUPPORTED', `Unsupported format: ${format}`);
+
+        let target = options.filePath;
+        if (!target) {
+            const { canceled, filePath: chosen } = await dialog.showSaveDialog(mainWindow, {
+                defaultPath: `${symbol}-${timeframe}-playback.${format}`,
+                filters: [{ name: format.toUpperCase(), extensions: [format] }]
+            });
+            if (canceled || !chosen) return { success: false, canceled: true };
+            target = chosen;
+        }
+
+        const theme = getTheme(db, activeThemeName()) || getTheme(db, DEFAULT_THEME);
+        const chart = (theme && theme.chart) || {};
+        const colors = { background: chart.backgroundColor, grid: chart.gridColor, up: chart.upColor, down: chart.downColor, accent: chart.lineColor };
+
+        let workerPath = path.join(__dirname, 'playbackExport.js');
+        if (!fs.existsSync(workerPath)) {
+             workerPath = path.join(app.getAppPath(), 'electron', 'playbackExport.js');
+        }
+        const result = await new Promise((resolve, reject) => {
+            const worker = new Worker(workerPath, {
+                workerData: { playbackExport: { dbPath: dbPathGlobal, symbol, timeframe, range: range || {}, options: { colors, ...options, format, filePath: target } } }
+            });
+            worker.on('message', (message) => {
+                if (message.type === 'progress') {
+                    broadcast('playback:export-progress', { datasetId: id, filePath: target, frame: message.frame, frames: message.frames });
+                    return;
+                }
+                worker.terminate();
+                resolve(message);
+            });
+            worker.once('error', reject);
+        });
+        const { type, ...outcome } = result;
+        if (outcome.success) logSystemEvent('PLAYBACK_EXPORTED', { datasetId: id, format, frames: outcome.frames, bytes: outcome.bytes, elapsedMs: outcome.elapsedMs });
+        else logSystemEvent('PLAYBACK_EXPORT_FAILED', { datasetId: id, format, error: outcome.error }, 'ERROR');
+        return outcome;
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- KEYBINDINGS ---
+// Handlers for actions that must work while the app is in the background
+const GLOBAL_ACTION_HANDLERS = {
+    'note.quick': () => {
+        showMainWindow();
+        broadcast('keybindings:triggered', { action: 'note.quick' });
+    },
+    'feeds.togglePause': () => setLiveFeedPaused(!liveFeedPaused)
+};
+
+const loadKeybindingOverrides = () => readJsonSetting('keybindings') || {};
+
+// Re-registers every global shortcut; returns the ones the OS refused (taken by another app)
+const registerGlobalShortcuts = () => {
+    globalShortcut.unregisterAll();
+    const failed = [];
+    resolveKeybindings(loadKeybindingOverrides()).filter(b => b.global && b.accelerator).forEach((binding) => {
+        const handler = GLOBAL_ACTION_HANDLERS[binding.action];
+        let ok = false;
+        try { ok = handler ? globalShortcut.register(binding.accelerator, handler) : false; } catch (e) { ok = false; }
+        if (!ok) failed.push(binding.action);
+    });
+    if (failed.length) logSystemEvent('GLOBAL_SHORTCUT_UNAVAILABLE', { actions: failed }, 'WARN');
+    return failed;
+};
+
+const keybindingSnapshot = (unavailable = []) => ({
+    bindings: resolveKeybindings(loadKeybindingOverrides()).map(b => ({ ...b, registered: b.global ? !unavailable.includes(b.action) : null })),
+    feedsPaused: liveFeedPaused
+});
+
+let unavailableShortcuts = [];
+
+ipcMain.handle('keybindings:get', async () => keybindingSnapshot(unavailableShortcuts));
+
+// accelerator: Electron accelerator string, or null to unbind
+ipcMain.handle('keybindings:set', async (event, action, accelerator) => {
+    try {
+        const result = updateKeybinding(loadKeybindingOverrides(), action, accelerator);
+        if (result.conflict) return failure('CONFLICT', `Already used by "${result.conflict.title}"`, { conflict: result.conflict });
+        writeJsonSetting('keybindings', result.overrides);
+        if (ACTIONS[action].global) unavailableShortcuts = registerGlobalShortcuts();
+        const snapshot = keybindingSnapshot(unavailableShortcuts);
+        broadcast('keybindings:changed', snapshot);
+        return { success: true, ...snapshot };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('keybindings:reset', async (event, action = null) => {
+    try {
+        const overrides = loadKeybindingOverrides();
+        if (action) delete overrides[action];
+        writeJsonSetting('keybindings', action ? overrides : {});
+        unavailableShortcuts = registerGlobalShortcuts();
+        const snapshot = keybindingSnapshot(unavailableShortcuts);
+        broadcast('keybindings:changed', snapshot);
+        return { success: true, ...snapshot };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('live-feed:set-paused', async (event, paused) => {
+    setLiveFeedPaused(paused);
+    return { success: true, paused: liveFeedPaused };
+});
+
+// --- LOCALIZATION ---
+// Persisted choice wins; otherwise follow the OS locale when we ship it
+const initializeLanguage = () => {
+    const stored = readJsonSetting('i18n.language');
+    setLanguage(resolveLanguage(stored) || resolveLanguage(app.getLocale()) || 'en');
+};
+
+ipcMain.handle('i18n:get-locale-strings', async (event, lang = null) => {
+    try {
+        return { success: true, language: lang ? resolveLanguage(lang) : getLanguage(), strings: getLocaleStrings(lang || getLanguage()) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('i18n:list-languages', async () => ({ current: getLanguage(), languages: listLanguages() }));
+
+ipcMain.handle('i18n:set-language', async (event, lang) => {
+    try {
+        const language = setLanguage(lang);
+        writeJsonSetting('i18n.language', language);
+        broadcast('i18n:language-changed', { language, strings: getLocaleStrings(language) });
+        logSystemEvent('LANGUAGE_CHANGED', { language });
+        return { success: true, language };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- DATABASE COMPACTION ---
+const COMPACTION_DEFAULTS = { idleEnabled: false, idleMinutes: 10, minFreeRatio: 0.2, minIntervalHours: 24 };
+const COMPACTION_CHECK_MS = 5 * 60 * 1000;
+let compactionTimer = null;
+
+const getCompactionConfig = () => ({ ...COMPACTION_DEFAULTS, ...(readJsonSetting('maintenance.compaction') || {}) });
+
+const runCompaction = (trigger) => {
+    const result = compactDatabase(db, dbPathGlobal);
+    writeJsonSetting('maintenance.lastCompaction', { ...result, trigger });
+    logSystemEvent('DB_COMPACTED', { ...result, trigger });
+    return result;
+};
+
+// Idle-time compaction: only when the user has been away long enough and there is enough to reclaim
+const scheduleIdleCompaction = () => {
+    if (compactionTimer) clearInterval(compactionTimer);
+    compactionTimer = null;
+    const config = getCompactionConfig();
+    if (!config.idleEnabled) return;
+    compactionTimer = setInterval(() => {
+        try {
+            if (!db || !powerGovernor.policy().idleCompaction || powerMonitor.getSystemIdleTime() < config.idleMinutes * 60) return;
+            const last = readJsonSetting('maintenance.lastCompaction');
+            if (last && Date.now() - last.compactedAt < config.minIntervalHours * 3600000) return;
+            if (freePageRatio(db) < config.minFreeRatio) return;
+            runCompaction('idle');
+        } catch (err) {
+            logSystemEvent('DB_COMPACTION_FAILED', { error: err.message }, 'ERROR');
+        }
+    }, COMPACTION_CHECK_MS);
+};
+
+ipcMain.handle('storage:compact-database', async () => {
+    try {
+        if (!db) return failure('UNAVAILABLE', t('errors.databaseNotInitialized'));
+        return { success: true, ...runCompaction('manual') };
+    } catch (err) {
+        logSystemEvent('DB_COMPACTION_FAILED', { error: err.message }, 'ERROR');
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('storage:get-compaction-config', async () => ({ ...getCompactionConfig(), lastCompaction: readJsonSetting('maintenance.lastCompaction') }));
+
+ipcMain.handle('storage:set-compaction-config', async (event, updates = {}) => {
+    try {
+        const { lastCompaction, ...rest } = updates;
+        const next = { ...getCompactionConfig(), ...rest };
+        writeJsonSetting('maintenance.compaction', next);
+        scheduleIdleCompaction();
+        return { success: true, config: next };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- SCHEDULER ---
+// Job types for scheduled_jobs; params are validated when the job runs
+const BACKUP_KEEP_DEFAULT = 7;
+let scheduler = null;
+
+const runBackup = async ({ dir = null, keep = BACKUP_KEEP_DEFAULT } = {}) => {
+    const target = dir || path.join(app.getPath('userData'), 'backups');
+    fs.mkdirSync(target, { recursive: true });
+    const stamp = new Date().toISOString().replace(/[:T]/g, '-').slice(0, 16);
+    const file = path.join(target, `redpill-${stamp}.db`);
+    await db.backup(file);
+    // Keep the newest `keep` backups made by this job
+    const old = fs.readdirSync(target).filter(f => /^redpill-.*\.db$/.test(f)).sort().reverse().slice(Math.max(1, keep));
+    old.forEach(f => fs.rmSync(path.join(target, f), { force: true }));
+    logSystemEvent('BACKUP_CREATED', { file: path.basename(file), pruned: old.length });
+    return { file, pruned: old.length };
+};
+
+const runReport = async ({ days = 7, dir = null } = {}) => {
+    const to = Date.now();
+    const from = to - days * 86400000;
+    const target = dir || path.join(app.getPath('userData'), 'reports');
+    fs.mkdirSync(target, { recursive: true });
+    const report = {
+        generatedAt: to,
+        period: { from, to },
+        datasets: listDatasets(db),
+        storage: getStorageReport({ db, dbPath: dbPathGlobal, soundsDir: soundsDirPath(), computeCacheDir: computeCacheDirPath() }),
+        diagnostics: collectDiagnostics().status,
+        jobRuns: scheduler.listRuns(null, 500).filter(r => r.startedAt >= from)
+    };
+    const file = path.join(target, `report-${new Date(to).toISOString().slice(0, 10)}.json`);
+    fs.writeFileSync(file, JSON.stringify(report, null, 2));
+    return { file, datasets: report.datasets.length };
+};
+
+// --- END OF DAY ---
+// The 'end-of-day' job (endOfDay.js). Params: { steps?, rollDays = 3, logKeepDays = 14,
+// purgeTargets = ['stale_csv'], backup?: { dir?, keep? }, reportDir?, reportPdf = true,
+// notify?: 'none' | 'desktop' | 'all' }. The last result is kept in 'maintenance.lastEndOfDay'.
+const END_OF_DAY_NOTIFY_MODES = ['none', 'desktop', 'all'];
+
+const END_OF_DAY_HANDLERS = {
+    'finalize-bars': async () => {
+        const before = barRecorder.status().buffered;
+        barRecorder.flush();
+        const streams = barRecorder.status().streams;
+        streams.forEach(stream => registerDataset(db, stream.symbol, stream.timeframe, stream.provider));
+        return `${before} buffered bars written, ${streams.length} recorded series`;
+    },
+    'roll-daily': async ({ rollDays = 3 }) => {
+        const now = Date.now();
+        const from = now - Math.max(1, Number(rollDays) || 3) * 86400000;
+        const symbols = Array.from(new Set(listDatasets(db)
+            .filter(d => TIMEFRAME_MS[d.timeframe] < TIMEFRAME_MS['1D'] && d.lastTimestamp >= from)
+            .map(d => d.symbol)));
+        const results = symbols.map(symbol => rollDailyBars(db, symbol, { from, now, calendar: calendarForAsset(getSymbolMeta(db, symbol).assetClass) }));
+        const rolled = results.filter(r => r.bars);
+        rolled.forEach(r => registerDataset(db, r.symbol, '1D', ROLLUP_SOURCE, { rolledFrom: r.source }));
+        const skipped = results.filter(r => r.skipped).length;
+        return `${rolled.reduce((n, r) => n + r.bars, 0)} daily bars for ${rolled.length} symbols${skipped ? `, ${skipped} skipped` : ''}`;
+    },
+    'rotate-logs': async ({ logKeepDays = 14 }) => {
+        const since = readJsonSetting('maintenance.logsRotatedAt') || 0;
+        const entries = systemLogBuffer.filter(e => e.timestamp > since);
+        const result = rotateLogFiles(path.join(app.getPath('userData'), 'logs'), entries, { keepDays: Number(logKeepDays) || 14 });
+        if (entries.length) writeJsonSetting('maintenance.logsRotatedAt', entries.reduce((latest, e) => Math.max(latest, e.timestamp), since));
+        return `${result.written} log entries, ${result.deleted} old files deleted`;
+    },
+    'prune-caches': async ({ purgeTargets = ['stale_csv'] }) => {
+        const deleted = purgeCaches(db, purgeTargets);
+        return Object.entries(deleted).map(([target, n]) => `${target}: ${n}`).join(', ') || 'nothing to purge';
+    },
+    backup: async ({ backup = {} }) => {
+        const result = await runBackup(backup || {});
+        return `${path.basename(result.file)}${result.pruned ? `, ${result.pruned} old backups removed` : ''}`;
+    },
+    'journal-report': async ({ reportDir = null, reportPdf = true }) => {
+        const midnight = new Date();
+        midnight.setHours(0, 0, 0, 0);
+        const stats = await portfolioStats({ from: midnight.getTime(), to: Date.now() });
+        const day = `${midnight.getFullYear()}-${String(midnight.getMonth() + 1).padStart(2, '0')}-${String(midnight.getDate()).padStart(2, '0')}`;
+        const timeline = getActivityTimeline(db, { day });
+        const html = composePrintHtml([
+            { kind: 'report', stats, title: `Trading journal ${day}`, currency: stats.baseCurrency },
+            { kind: 'diary', title: `Activity ${day}`, timeline }
+        ], { title: `Trading journal ${day}` });
+        const target = reportDir || path.join(app.getPath('userData'), 'reports');
+        fs.mkdirSync(target, { recursive: true });
+        const file = path.join(target, `journal-${day}.html`);
+        fs.writeFileSync(file, html);
+        const files = [path.basename(file)];
+        if (reportPdf !== false) {
+            const pdfFile = path.join(target, `journal-${day}.pdf`);
+            const win = new BrowserWindow({ show: false, webPreferences: { javascript: false } });
+            try {
+                await win.loadURL(`data:text/html;charset=utf-8,${encodeURIComponent(html)}`);
+                fs.writeFileSync(pdfFile, await win.webContents.printToPDF({ printBackground: true, pageSize: 'A4' }));
+                files.push(path.basename(pdfFile));
+            } finally {
+                win.destroy();
+            }
+        }
+        return `${stats.trades} trades, ${timeline.events.length} activity events, ${files.join(' + ')}`;
+    }
+};
+
+const runEndOfDayRoutine = async (params = {}) => {
+    if (!db) throw new AppError('UNAVAILABLE', t('errors.databaseNotInitialized'));
+    const notify = params.notify || 'desktop';
+    if (!END_OF_DAY_NOTIFY_MODES.includes(notify)) throw new AppError('INVALID_INPUT', `notify must be one of ${END_OF_DAY_NOTIFY_MODES.join(', ')}`);
+    const result = await runEndOfDay(END_OF_DAY_HANDLERS, params);
+    writeJsonSetting('maintenance.lastEndOfDay', result);
+    logSystemEvent('END_OF_DAY_FINISHED', { status: result.status, failed: result.steps.filter(s => s.status === 'failed').map(s => s.step) }, result.status === 'success' ? 'INFO' : 'WARN');
+    const summary = formatEndOfDaySummary(result);
+    if (notify !== 'none' && !presentation.isActive() && Notification.isSupported()) new Notification({ title: 'End of day', body: summary }).show();
+    if (notify === 'all') notifiers.notifyAll(summary).catch(() => {});
+    broadcast('maintenance:end-of-day-finished', result);
+    // A routine where every step failed is a failed job run
+    if (result.status === 'failed') throw new Error(summary);
+    return summary;
+};
+
+ipcMain.handle('maintenance:run-end-of-day', async (event, params = {}) => {
+    try {
+        await runEndOfDayRoutine(params || {});
+        return { success: true, result: readJsonSetting('maintenance.lastEndOfDay') };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('maintenance:get-end-of-day', async () => ({ steps: END_OF_DAY_STEPS, last: readJsonSetting('maintenance.lastEndOfDay') }));
+
+const SCHEDULED_JOB_TYPES = {
+    // { provider, symbol, timeframe, options? }
+    'provider.refresh': async ({ provider, symbol, timeframe, options = {} }) => {
+        const bars = await getProvider(provider).fetchHistory(symbol, timeframe, options);
+        persistProviderBars(provider, symbol, timeframe, bars);
+        return `${bars.length} bars for ${symbol} ${timeframe}`;
+    },
+    // { dir?, keep? }
+    backup: (params) => runBackup(params),
+    // { days?, dir? }
+    report: (params) => runReport(params),
+    // { id, args? } — any command palette action
+    action: async ({ id, args = {} }) => {
+        const { result } = await actionRegistry.execute(id, args);
+        return result === undefined ? null : result;
+    },
+    // { screenId }
+    'scanner.run': async ({ screenId }) => {
+        const result = await getScanner().run(screenId);
+        return `${result.matches.length} of ${result.scanned} matched${result.matches.length ? `: ${result.matches.map(m => m.symbol).join(', ')}` : ''}`;
+    },
+    // see END OF DAY
+    'end-of-day': (params) => runEndOfDayRoutine(params),
+    // {} — fetch the configured economic calendar feed
+    'calendar.refresh': async () => {
+        const result = await getEconomicCalendar().refresh();
+        broadcast('calendar:updated', { source: result.source });
+        refreshICalSubscription();
+        return `${result.imported} events from ${result.source}`;
+    }
+};
+
+const startScheduler = () => {
+    if (!db) return;
+    scheduler = createScheduler({
+        db,
+        handlers: SCHEDULED_JOB_TYPES,
+        // Backups wait for mains power and scans / refreshes for the user; they stay due and run after
+        shouldDefer: (job) => {
+            if (job.type === 'backup' && powerGovernor.policy().deferBackups) return 'power-saver';
+            if (AWAY_DEFERRED_JOBS.includes(job.type) && idleMonitor.shouldPause()) return 'user-away';
+            return null;
+        },
+        onRunFinished: (run) => {
+            if (run.status === 'failed') logSystemEvent('SCHEDULED_JOB_FAILED', run, 'ERROR');
+            broadcast('scheduler:run-finished', run);
+        }
+    });
+    scheduler.start();
+};
+
+const schedulerUnavailable = () => (failure('UNAVAILABLE', t('errors.databaseNotInitialized')));
+
+ipcMain.handle('scheduler:list-jobs', async () => {
+    try {
+        if (!scheduler) return schedulerUnavailable();
+        return { success: true, jobs: scheduler.listJobs(), types: scheduler.jobTypes() };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// job: { name, type, schedule (cron, local time), params?, enabled?, catchUp?: 'once' | 'skip' }
+ipcMain.handle('scheduler:create-job', async (event, job) => {
+    try {
+        if (!scheduler) return schedulerUnavailable();
+        const created = scheduler.createJob(job);
+        logSystemEvent('SCHEDULED_JOB_CREATED', { id: created.id, type: created.type, schedule: created.schedule });
+        return { success: true, job: created };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('scheduler:update-job', async (event, id, updates = {}) => {
+    try {
+        if (!scheduler) return schedulerUnavailable();
+        return { success: true, job: scheduler.updateJob(id, updates) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('scheduler:delete-job', async (event, id) => {
+    try {
+        if (!scheduler) return schedulerUnavailable();
+        return { success: scheduler.deleteJob(id) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('scheduler:run-now', async (event, id) => {
+    try {
+        if (!scheduler) return schedulerUnavailable();
+        return { success: true, run: await scheduler.runNow(id) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('scheduler:get-runs', async (event, jobId = null, limit = 50) => {
+    try {
+        if (!scheduler) return schedulerUnavailable();
+        return { success: true, runs: scheduler.listRuns(jobId, limit) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- HISTORICAL DOWNLOADS ---
+// Setting 'downloads': { concurrency, bandwidthKbps (0 = unlimited), chunkBars }
+let downloadManager = null;
+
+const getDownloadConfig = () => ({ ...DEFAULT_DOWNLOAD_CONFIG, ...(readJsonSetting('downloads') || {}) });
+
+const startDownloadManager = () => {
+    if (!db) return;
+    downloadManager = createDownloadManager({
+        db,
+        getProvider,
+        persist: (provider, symbol, timeframe, bars) => persistProviderBars(provider, symbol, timeframe, bars),
+        getConfig: getDownloadConfig,
+        onProgress: (job) => {
+            if (job.status === 'failed') logSystemEvent('DOWNLOAD_FAILED', { id: job.id, symbol: job.symbol, error: job.error }, 'WARN');
+            if (job.status === 'done') logSystemEvent('DOWNLOAD_COMPLETED', { id: job.id, symbol: job.symbol, bars: job.bars });
+            broadcast('downloads:progress', job);
+        }
+    });
+    downloadManager.start();
+};
+
+const downloadsUnavailable = () => (failure('UNAVAILABLE', t('errors.databaseNotInitialized')));
+
+ipcMain.handle('downloads:list', async () => {
+    try {
+        if (!downloadManager) return downloadsUnavailable();
+        return { success: true, jobs: downloadManager.list(), config: getDownloadConfig() };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// specs: one or many { provider, symbol, timeframe, start, end?, options? } (ms timestamps)
+ipcMain.handle('downloads:enqueue', async (event, specs) => {
+    try {
+        if (!downloadManager) return downloadsUnavailable();
+        const jobs = (Array.isArray(specs) ? specs : [specs]).map(spec => downloadManager.enqueue(spec));
+        logSystemEvent('DOWNLOADS_QUEUED', { count: jobs.length, symbols: jobs.map(j => j.symbol) });
+        return { success: true, jobs };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('downloads:pause', async (event, id) => {
+    try {
+        if (!downloadManager) return downloadsUnavailable();
+        return { success: true, job: downloadManager.pause(id) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('downloads:resume', async (event, id) => {
+    try {
+        if (!downloadManager) return downloadsUnavailable();
+        return { success: true, job: downloadManager.resume(id) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('downloads:cancel', async (event, id) => {
+    try {
+        if (!downloadManager) return downloadsUnavailable();
+        return { success: true, job: downloadManager.cancel(id) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('downloads:remove', async (event, id) => {
+    try {
+        if (!downloadManager) return downloadsUnavailable();
+        return { success: downloadManager.remove(id) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('downloads:clear-finished', async () => {
+    try {
+        if (!downloadManager) return downloadsUnavailable();
+        return { success: true, removed: downloadManager.clearFinished() };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('downloads:set-config', async (event, updates = {}) => {
+    try {
+        const next = { ...getDownloadConfig(), ...updates };
+        next.concurrency = Math.max(1, Math.min(4, Math.floor(Number(next.concurrency) || 1)));
+        next.bandwidthKbps = Math.max(0, Number(next.bandwidthKbps) || 0);
+        next.chunkBars = Math.max(100, Math.min(50000, Math.floor(Number(next.chunkBars) || DEFAULT_DOWNLOAD_CONFIG.chunkBars)));
+        writeJsonSetting('downloads', next);
+        // A higher concurrency can start queued jobs straight away
+        if (downloadManager) downloadManager.pump();
+        return { success: true, config: next };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- POWER ---
+// Setting 'power.override': 'auto' (follow battery / thermal signals), 'normal' or 'saver'
+const POWER_OVERRIDES = ['auto', 'normal', 'saver'];
+
+const powerGovernor = createPowerGovernor({
+    powerMonitor,
+    getOverride: () => readJsonSetting('power.override') || 'auto',
+    onChange: (state) => {
+        logSystemEvent('POWER_MODE_CHANGED', state);
+        if (newsService) newsService.reschedule();
+        broadcast('power:state-changed', { ...state, policy: powerGovernor.policy() });
+    }
+});
+
+ipcMain.handle('power:get-state', async () => ({ ...powerGovernor.state(), policy: powerGovernor.policy() }));
+
+ipcMain.handle('power:set-override', async (event, override = 'auto') => {
+    try {
+        if (!POWER_OVERRIDES.includes(override)) return failure('INVALID_INPUT', `Invalid power override: ${override}`);
+        writeJsonSetting('power.override', override);
+        const state = powerGovernor.refresh();
+        return { success: true, ...state, policy: powerGovernor.policy() };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+// --- IDLE / AUTO-LOCK ---
+// Setting 'idle': { awayMinutes, autoLockMinutes (0 = off), lockOnScreenLock, pauseBackgroundWork }.
+// While away: no background time-index builds, scheduled scans and provider refreshes
+// wait, and news / calendar polling slows down. The lock itself is drawn by the
+// renderer (idle:locked) and lifted through idle:unlock.
+const AWAY_POLL_SCALE = 6;
+const AWAY_DEFERRED_JOBS = ['scanner.run', 'provider.refresh', 'report'];
+const RETURN_REFRESH_AFTER_MS = 15 * 60 * 1000; // away longer than this: refetch the calendar on return
+
+const backgroundPollScale = () => powerGovernor.policy().pollScale * (idleMonitor.shouldPause() ? AWAY_POLL_SCALE : 1);
+
+const getIdleConfig = () => normalizeIdleConfig(readJsonSetting('idle') || {});
+
+const idleMonitor = createIdleMonitor({
+    powerMonitor,
+    getConfig: () => readJsonSetting('idle') || {},
+    onAway: (away) => {
+        logSystemEvent('USER_AWAY', away);
+        broadcast('idle:away', away);
+    },
+    onReturn: (info) => {
+        logSystemEvent('USER_RETURNED', info);
+        // Polling picks its normal pace again right away instead of after a slow interval
+        if (newsService) newsService.reschedule();
+        if (economicCalendar && info.awayMs > RETURN_REFRESH_AFTER_MS) economicCalendar.refresh().catch(() => {});
+        broadcast('idle:returned', { ...info, state: idleMonitor.state() });
+    },
+    onLock: (lock) => {
+        logSystemEvent('WORKSPACE_AUTO_LOCKED', lock);
+        broadcast('idle:locked', lock);
+    }
+});
+
+ipcMain.handle('idle:get-state', async () => ({ ...idleMonitor.state(), config: getIdleConfig() }));
+
+ipcMain.handle('idle:set-config', async (event, patch = {}) => {
+    try {
+        const next = normalizeIdleConfig({ ...getIdleConfig(), ...patch });
+        writeJsonSetting('idle', next);
+        idleMonitor.check();
+        return { success: true, config: next, ...idleMonitor.state() };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('idle:lock', async () => {
+    const locked = idleMonitor.lock();
+    return { success: true, alreadyLocked: !locked, ...idleMonitor.state() };
+});
+
+ipcMain.handle('idle:unlock', async () => {
+    const unlocked = idleMonitor.unlock();
+    if (unlocked) {
+        logSystemEvent('WORKSPACE_UNLOCKED');
+        broadcast('idle:unlocked', idleMonitor.state());
+    }
+    return { success: true, ...idleMonitor.state() };
+});
+
+// --- BACKGROUND JOBS ---
+// Opportunistic index work (see backgroundJobs.js). Jobs wait while the user is
+// typing or clicking (input within the last ACTIVE_INPUT_SECONDS), while away
+// (idle.pauseBackgroundWork) and in power-saver mode. Thumbnails are drawn by
+// the renderer, so their job only reports the charts that have none
+// (thumbnails:missing); the renderer captures them when it's idle.
+const ACTIVE_INPUT_SECONDS = 2;
+
+const BACKGROUND_JOB_TYPES = {
+    // { filePath, format, brokerOffset, locale? }
+    'time-index': async ({ filePath, format, brokerOffset, locale }) => {
+        if (!db) throw new Error(t('errors.databaseNotInitialized'));
+        if (getTimeIndex(db, filePath)) return 'already indexed';
+        if (!fs.existsSync(filePath)) return 'file is gone';
+        const index = await buildTimeIndex(filePath, format, { brokerOffset, locale });
+        return `${index.entries.length} entries`;
+    },
+    // { sourceId? } — one chart, or the whole index when omitted
+    'annotation-index': async ({ sourceId = null }) => {
+        if (!db) throw new Error(t('errors.databaseNotInitialized'));
+        if (!sourceId) return `${rebuildAnnotationIndex(db)} charts`;
+        indexChartState(db, sourceId, readChartState(sourceId));
+        return sourceId;
+    },
+    'thumbnail-sweep': async () => {
+        if (!db) throw new Error(t('errors.databaseNotInitialized'));
+        const symbols = db.prepare('SELECT d.symbol FROM drawings d LEFT JOIN chart_thumbnails t ON t.symbol = d.symbol WHERE t.symbol IS NULL ORDER BY d.symbol')
+            .all().map(r => r.symbol);
+        if (symbols.length) broadcast('thumbnails:missing', { symbols });
+        return `${symbols.length} charts without a thumbnail`;
+    }
+};
+
+const backgroundJobs = createBackgroundQueue({
+    handlers: BACKGROUND_JOB_TYPES,
+    // Index builds run in a worker each; one at a time keeps a core free for the UI
+    concurrency: { total: 2, perType: { 'time-index': 1 } },
+    shouldPause: () => {
+        if (!powerGovernor.policy().backgroundIndexing) return 'power-saver';
+        if (idleMonitor.shouldPause()) return 'user-away';
+        if (powerMonitor.getSystemIdleTime() < ACTIVE_INPUT_SECONDS) return 'user-active';
+        return null;
+    },
+    onJobFinished: (job) => {
+        if (job.status === 'failed') logSystemEvent('BACKGROUND_JOB_FAILED', { type: job.type, key: job.key, error: job.message }, 'WARN');
+    }
+});
+
+ipcMain.handle('background:get-jobs', async () => ({ success: true, ...backgroundJobs.list() }));
+
+ipcMain.handle('background:cancel-job', async (event, id) => ({ success: true, canceled: backgroundJobs.cancel(id) }));
+
+// --- PRESENTATION MODE ---
+const presentation = createPresentationController({
+    onChange: (status) => {
+        logSystemEvent(status.active ? 'PRESENTATION_STARTED' : 'PRESENTATION_ENDED', status);
+        broadcast('presentation:changed', status);
+    }
+});
+
+// label: 'main', a BrowserWindow id, or empty for the calling window
+const resolveWindow = (event, label) => {
+    if (label === 'main') return mainWindow;
+    if (label != null && label !== '') return BrowserWindow.fromId(Number(label));
+    return event ? BrowserWindow.fromWebContents(event.sender) : mainWindow;
+};
+
+// options: { kiosk?, dimOtherDisplays?, dimOpacity? }
+ipcMain.handle('presentation:enter', async (event, label = null, options = {}) => {
+    try {
+        const win = resolveWindow(event, label);
+        if (!win || win.isDestroyed()) return failure('NOT_FOUND', 'Window not found');
+        return { success: true, ...presentation.enter(win, options || {}) };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('presentation:exit', async () => {
+    try {
+        return { success: true, ...presentation.exit() };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('presentation:get-status', async () => presentation.status());
+
+// --- ACTION REGISTRY ---
+// Everything the command palette can do. Renderer-owned actions have no `run`
+// and are forwarded as 'actions:invoke'; shortcut hints come from the keybinding map.
+const actionRegistry = createActionRegistry({
+    getShortcut: (id) => {
+        const binding = resolveKeybindings(loadKeybindingOverrides()).find(b => b.action === id);
+        return binding ? binding.accelerator : null;
+    },
+    forwardToRenderer: (invocation) => broadcast('actions:invoke', invocation)
+});
+const hasDb = () => !!db;
+
+// Renderer-owned; titles shared with the keybinding map
+[
+    ['search.open', { category: 'Navigation' }],
+    ['chart.undo', { category: 'Chart' }],
+    ['chart.redo', { category: 'Chart' }],
+    ['tool.cancel', { category: 'Chart' }],
+    ['drawings.lockAll', { category: 'Drawings' }],
+    ['drawings.hideAll', { category: 'Drawings' }],
+    ['devtools.toggle', { category: 'Developer' }],
+    ['note.quick', { category: 'Notes' }]
+].forEach(([id, def]) => actionRegistry.register(id, { title: ACTIONS[id].title, ...def }));
+actionRegistry.register('chart.open', { title: 'Open chart', category: 'Navigation', args: { filePath: 'string' } });
+
+actionRegistry.register('indicator.run', {
+    title: 'Run indicator',
+    category: 'Analysis',
+    args: { datasetId: 'string', indicator: 'string', period: 'number?' },
+    when: hasDb,
+    run: async ({ datasetId: id, indicator, period }) => {
+        if (!INDICATORS[indicator]) throw new Error(`Unknown indicator: ${indicator}`);
+        const [result] = await runCachedJobs([{ id: '0', datasetId: id, indicator, params: period ? { period } : {} }]);
+        if (!result.success) throw new Error(result.error);
+        return result;
+    }
+});
+
+actionRegistry.register('alert.create', {
+    title: 'Create price alert',
+    category: 'Alerts',
+    args: { symbol: 'string', condition: 'string', price: 'number', message: 'string?' },
+    when: () => !!alertEngine,
+    run: ({ symbol, condition, price, message }) => alertEngine.saveAlert({ symbol, condition, price, message })
+});
+
+actionRegistry.register('theme.apply', {
+    title: 'Apply theme',
+    category: 'Appearance',
+    args: { name: 'string' },
+    when: hasDb,
+    run: ({ name }) => applyThemeByName(name)
+});
+
+actionRegistry.register('template.apply', {
+    title: 'Apply drawing template',
+    category: 'Drawings',
+    args: { sourceId: 'string', name: 'string' },
+    when: hasDb,
+    run: ({ sourceId, name, anchor }) => applyDrawingTemplate(db, sourceId, name, { anchor })
+});
+
+actionRegistry.register('feeds.togglePause', {
+    title: 'Pause / resume live feeds',
+    category: 'Live Data',
+    run: () => { setLiveFeedPaused(!liveFeedPaused); return { paused: liveFeedPaused }; }
+});
+
+actionRegistry.register('presentation.toggle', {
+    title: 'Toggle presentation mode',
+    category: 'View',
+    args: { dimOtherDisplays: 'boolean?' },
+    run: ({ dimOtherDisplays }) => (presentation.isActive()
+        ? presentation.exit()
+        : presentation.enter(mainWindow, { dimOtherDisplays: !!dimOtherDisplays }))
+});
+
+actionRegistry.register('storage.compact', {
+    title: 'Compact database',
+    category: 'Maintenance',
+    when: hasDb,
+    run: () => runCompaction('manual')
+});
+
+actionRegistry.register('diagnostics.run', {
+    title: 'Run diagnostics',
+    category: 'Maintenance',
+    run: () => collectDiagnostics()
+});
+
+ipcMain.handle('actions:list', async () => actionRegistry.list());
+
+ipcMain.handle('actions:execute', async (event, id, args = {}) => {
+    try {
+        const outcome = await actionRegistry.execute(id, args || {});
+        logSystemEvent('ACTION_EXECUTED', { id, forwarded: outcome.forwarded });
+        return { success: true, ...outcome };
+    } catch (err) {
+        return errorResult(err);
+    }
+});
+
+ipcMain.handle('logs:get-db-status', async () => {
+    try {
+        if (!db) return { connected: false, error: 'DB Null' };
+        db.prepare("SELECT 1").get();
+        return { connected: true };
+    } catch (err) {
+        return { connected: false, error: err.message };
+    }
+});
+
+// --- TEST MODE ---
+// Introspection for end-to-end tests (--test-mode, see testMode.js); none of
+// these channels exist in a normal run. Outbound requests from main (proxy.js)
+// and from the renderer's session are refused; loopback stays reachable.
+if (testMode) {
+    setNetworkGuard(isExternalUrl);
+    logSystemEvent('TEST_MODE', { dataDir: testMode.dataDir, keepData: testMode.keepData, clock: new Date().toISOString() }, 'WARN');
+
+    const testHandler = (run) => async (event, ...args) => {
+        try {
+            return { success: true, ...(await run(...args)) };
+        } catch (err) {
+            return errorResult(err);
+        }
+    };
+
+    ipcMain.handle('test:get-info', testHandler(() => ({
+        dataDir: testMode.dataDir,
+        keepData: testMode.keepData,
+        now: Date.now(),
+        profile: activeProfile.id,
+        readOnly,
+        providers: listProviders().map(p => p.id)
+    })));
+
+    ipcMain.handle('test:list-channels', testHandler(() => ({ channels: listTestChannels() })));
+
+    ipcMain.handle('test:set-clock', testHandler(value => ({ now: testClock.set(value) })));
+
+    ipcMain.handle('test:advance-clock', testHandler(ms => ({ now: testClock.advance(ms) })));
+
+    // options: { event?, level?, limit? } — newest first
+    ipcMain.handle('test:get-log', testHandler(({ event: name = null, level = null, limit = 200 } = {}) => ({
+        entries: systemLogBuffer.filter(e => (!name || e.event === name) && (!level || e.level === level)).slice(0, Math.max(1, Number(limit) || 200))
+    })));
+
+    // Read-only SQL against the test database, for assertions
+    ipcMain.handle('test:query', testHandler((sql, params = []) => {
+        if (!db) throw new Error(t('errors.databaseNotInitialized'));
+        const stmt = db.prepare(String(sql));
+        if (!stmt.reader) throw new Error('test:query only runs statements that return rows');
+        return { rows: stmt.all(...(Array.isArray(params) ? params : [params])) };
+    }));
+
+    // Live bar from the mock provider: symbol / timeframe must be subscribed
+    ipcMain.handle('test:push-bar', testHandler((symbol, timeframe, bar = {}, isClosed = false) => ({
+        bar: getProvider('mock').push(symbol, timeframe, bar, isClosed)
+    })));
+
+    app.on('quit', () => {
+        if (testMode.keepData) return;
+        try { fs.rmSync(testMode.dataDir, { recursive: true, force: true }); } catch (e) { /* best effort */ }
+    });
+}
+
+// The renderer's own requests (fetch, WebSocket, images) in test mode
+const blockExternalRequests = () => {
+    session.defaultSession.webRequest.onBeforeRequest((details, callback) => {
+        const blocked = isExternalUrl(details.url);
+        if (blocked) logSystemEvent('TEST_MODE_REQUEST_BLOCKED', { url: details.url }, 'WARN');
+        callback({ cancel: blocked });
+    });
+};
+
+app.whenReady().then(() => {
+  if (!pickStartupProfile()) return;
+  if (!acquireWorkspaceLock()) return;
+  if (testMode) blockExternalRequests();
+  logSystemEvent('APP_READY', { profile: activeProfile.id, readOnly, testMode: !!testMode });
+  setupDatabase();
+  if (!readOnly) runStartupIntegrityScan();
+  registerAttachmentProtocol();
+  applyProxyConfig();
+  applyTlsPolicy();
+  powerGovernor.start();
+  appearance.start();
+  idleMonitor.start();
+  clipboardWatcher.restart();
+  startSignalInbox().catch(() => {});
+  startCompanionServer().catch(() => {});
+  initializeLanguage();
+  startNewsService();
+  startEconomicCalendar();
+  startAlertEngine();
+  startSimEngine();
+  startLiveExports();
+  if (db) externalChanges.start();
+  applyBrokerConfigs();
+  startBackgroundWriters();
+  startSecretExpiryChecks();
+  startICalSubscription();
+  if (!readOnly) usageAnalytics.start();
+  unavailableShortcuts = registerGlobalShortcuts();
+  runBootScan();
+  if (db) backgroundJobs.enqueue({ type: 'thumbnail-sweep', priority: 'low' });
+  createWindow();
+  mainWindow.webContents.once('did-finish-load', () => {
+    announceIntegrityReport();
+    promptPendingCrashReports();
+    flushOpenFiles();
+  });
+});
+
+// --- SHUTDOWN ---
+// Renderers with debounced saves register through preload (onShutdownFlush);
+// each registered window is asked to save now and acknowledges when done.
+const flushRegistrations = new Map(); // webContents id -> listener count
+const flushWaiters = new Map(); // token -> resolve
+const RENDERER_FLUSH_TIMEOUT_MS = 3000;
+
+ipcMain.on('app:shutdown-flush-register', (event, delta = 1) => {
+    const id = event.sender.id;
+    const count = (flushRegistrations.get(id) || 0) + (delta < 0 ? -1 : 1);
+    if (count > 0) flushRegistrations.set(id, count);
+    else flushRegistrations.delete(id);
+    if (delta > 0) event.sender.once('destroyed', () => flushRegistrations.delete(id));
+});
+
+ipcMain.on('app:shutdown-flushed', (event, token) => {
+    const resolve = flushWaiters.get(token);
+    if (resolve) resolve();
+});
+
+// Resolves once every registered window among `windows` saved (or the timeout passed)
+const flushRenderers = (windows = BrowserWindow.getAllWindows()) => Promise.all(windows
+    .filter(win => win && !win.isDestroyed() && flushRegistrations.has(win.webContents.id))
+    .map((win) => {
+        const token = crypto.randomUUID();
+        const acked = new Promise((resolve) => {
+            flushWaiters.set(token, resolve);
+            win.webContents.send('app:shutdown-flush', token);
+        });
+        return withTimeout(acked, RENDERER_FLUSH_TIMEOUT_MS, `flush window ${win.id}`)
+            .catch(err => logSystemEvent('SHUTDOWN_FLUSH_TIMEOUT', { windowId: win.id, error: err.message }, 'WARN'))
+            .finally(() => flushWaiters.delete(token));
+    }));
+
+// Stopped one by one so a service that throws doesn't keep the rest (and the
+// final analytics flush) from running
+const SHUTDOWN_SERVICES = [
+    ['scheduler', () => { if (scheduler) scheduler.stop(); }],
+    ['backgroundJobs', () => backgroundJobs.stop()],
+    ['idleMonitor', () => idleMonitor.stop()],
+    ['clipboardWatcher', () => clipboardWatcher.stop()],
+    ['signalInbox', () => signalInbox.stop()],
+    ['companionServer', () => companionServer.stop()],
+    ['liveExports', () => liveExports.stop()],
+    ['externalChanges', () => externalChanges.stop()],
+    ['heatmaps', () => heatmaps.stopAll()],
+    ['downloadManager', () => { if (downloadManager) downloadManager.stop(); }],
+    ['watchFolders', () => { if (watchFolderService) watchFolderService.stop(); }],
+    ['economicCalendar', () => { if (economicCalendar) economicCalendar.stop(); }],
+    ['alpacaUpdates', () => getBroker('alpaca').stopUpdates()],
+    ['usageAnalytics', () => usageAnalytics.stop()],
+    ['usageAnalyticsFlush', () => usageAnalytics.flush()]
+];
+
+const stopServices = () => SHUTDOWN_SERVICES.forEach(([service, stop]) => {
+    try {
+        stop();
+    } catch (err) {
+        logSystemEvent('SHUTDOWN_STEP_FAILED', { step: 'services', service, error: err.message }, 'WARN');
+    }
+});
+
+const shutdownPipeline = createShutdownPipeline({
+    steps: [
+        { name: 'renderers', run: () => flushRenderers(), timeoutMs: RENDERER_FLUSH_TIMEOUT_MS + 500 },
+        { name: 'services', run: stopServices },
+        // Feeds go first so no bar arrives after the recorder's final flush
+        {
+            name: 'feeds',
+            run: () => Promise.all(listProviders().map(p => Promise.resolve(getProvider(p.id).disconnect()).catch(() => {})))
+        },
+        { name: 'recordings', run: () => { barRecorder.stop(); tradeRecorder.stop(); } },
+        {
+            name: 'database',
+            run: () => {
+                if (computePool) computePool.destroy();
+                if (!db) return;
+                db.pragma('wal_checkpoint(TRUNCATE)');
+                db.close();
+                db = null;
+                workspaceLock.release();
+            }
+        }
+    ],
+    onProgress: (progress) => {
+        if (progress.status === 'failed') logSystemEvent('SHUTDOWN_STEP_FAILED', progress, 'WARN');
+        broadcast('app:shutdown-progress', progress);
+    }
+});
+
+const forceQuit = (reason) => {
+    logSystemEvent('FORCE_QUIT', { reason }, 'WARN');
+    workspaceLock.release();
+    app.exit(0);
+};
+
+app.on('before-quit', (event) => {
+    isQuitting = true;
+    if (shutdownPipeline.state() === 'done') return;
+    event.preventDefault();
+    // Asking to quit again while the pipeline runs means "now"
+    if (shutdownPipeline.state() === 'running') {
+        forceQuit('quit requested during shutdown');
+        return;
+    }
+    logSystemEvent('SHUTDOWN_STARTED');
+    shutdownPipeline.run().then((result) => {
+        logSystemEvent('SHUTDOWN_FINISHED', { ms: result.ms, timedOut: result.timedOut });
+        app.quit();
+    });
+});
+
+ipcMain.handle('app:force-quit', async () => {
+    forceQuit('requested by renderer');
+    return { success: true };
+});
+
+app.on('will-quit', () => { presentation.exit(); trayController.destroy(); globalShortcut.unregisterAll(); });
+
+// Teardown happens in the shutdown pipeline (before-quit)
+app.on('window-all-closed', () => { if (process.platform !== 'darwin') app.quit(); });
@@ -0,0 +1,143 @@
+
+// --- METATRADER HISTORY FORMATS ---
+// HST (MT4 history center) binary files and the CSV exports produced by the
+// MT4/MT5 history centers. All MetaTrader timestamps are broker server time,
+// so every bar is shifted back to UTC with the configured broker offset.
+
+const HST_HEADER_SIZE = 148;
+const HST_V400_RECORD = 44;
+const HST_V401_RECORD = 60;
+
+// MetaTrader period (minutes) -> app Timeframe values
+const PERIOD_TIMEFRAMES = {
+    1: '1mn', 3: '3m', 5: '5m', 15: '15m', 30: '30m',
+    60: '1h', 120: '2h', 240: '4h', 720: '12h',
+    1440: '1D', 10080: '1W', 43200: '1mo'
+};
+
+const periodToTimeframe = (minutes) => PERIOD_TIMEFRAMES[minutes] || null;
+
+// Second Sunday of March .. first Sunday of November (US DST window)
+const isUsDst = (utcMs) => {
+    const d = new Date(utcMs);
+    const year = d.getUTCFullYear();
+    const nthSunday = (month, n) => {
+        const first = new Date(Date.UTC(year, month, 1));
+        const offset = (7 - first.getUTCDay()) % 7;
+        return Date.UTC(year, month, 1 + offset + (n - 1) * 7);
+    };
+    return utcMs >= nthSunday(2, 2) && utcMs < nthSunday(10, 1);
+};
+
+/**
+ * Converts a broker-time timestamp (ms) to UTC.
+ * `offset` is a fixed number of hours, or 'ny-close' for the common
+ * UTC+2 / UTC+3 (during US DST) server clock so daily bars close at 17:00 New York.
+ */
+const brokerTimeToUtc = (ms, offset = 0) => {
+    if (offset === 'ny-close') {
+        const hours = isUsDst(ms - 2 * 3600000) ? 3 : 2;
+        return ms - hours * 3600000;
+    }
+    const hours = Number(offset) || 0;
+    return ms - hours * 3600000;
+};
+
+const readHstHeader = (buffer) => {
+    if (!buffer || buffer.length < HST_HEADER_SIZE) throw new Error('File too small to be an HST history file');
+    const version = buffer.readInt32LE(0);
+    if (version !== 400 && version !== 401) throw new Error(`Unsupported HST version ${version}`);
+    const readString = (start, len) => buffer.toString('latin1', start, start + len).replace(/\0.*$/, '').trim();
+    return {
+        version,
+        copyright: readString(4, 64),
+        symbol: readString(68, 12),
+        period: buffer.readInt32LE(80),
+        digits: buffer.readInt32LE(84),
+        recordSize: version === 400 ? HST_V400_RECORD : HST_V401_RECORD
+    };
+};
+
+/**
+ * Iterates HST records, yielding normalized { timestamp, open, high, low, close, volume } rows.
+ * A trailing partial record (file still being written by the terminal) is ignored.
+ */
+function* parseHstRecords(buffer, header, brokerOffset = 0) {
+    const { version, recordSize } = header;
+    for (let pos = HST_HEADER_SIZE; pos + recordSize <= buffer.length; pos += recordSize) {
+        let time, open, high, low, close, volume;
+        if (version === 400) {
+            time = buffer.readInt32LE(pos) * 1000;
+            open = buffer.readDoubleLE(pos + 4);
+            low = buffer.readDoubleLE(pos + 12);
+            high = buffer.readDoubleLE(pos + 20);
+            close = buffer.readDoubleLE(pos + 28);
+            volume = buffer.readDoubleLE(pos + 36);
+        } else {
+            time = Number(buffer.readBigInt64LE(pos)) * 1000;
+            open = buffer.readDoubleLE(pos + 8);
+            high = buffer.readDoubleLE(pos + 16);
+            low = buffer.readDoubleLE(pos + 24);
+            close = buffer.readDoubleLE(pos + 32);
+            const tickVolume = Number(buffer.readBigInt64LE(pos + 40));
+            const realVolume = Number(buffer.readBigInt64LE(pos + 52));
+            volume = realVolume > 0 ? realVolume : tickVolume;
+        }
+        if (!(time > 0) || !isFinite(open) || !isFinite(close)) continue;
+        yield { timestamp: brokerTimeToUtc(time, brokerOffset), open, high, low, close, volume: isFinite(volume) ? volume : 0 };
+    }
+}
+
+/**
+ * Parses one line of a MetaTrader CSV export. Handles both layouts:
+ *   MT4: 2024.01.02,00:00,1.1040,1.1045,1.1038,1.1041,120
+ *   MT5: 2024.01.02<TAB>00:00:00<TAB>open<TAB>high<TAB>low<TAB>close<TAB>tickvol<TAB>vol<TAB>spread
+ * Header lines (<DATE>...) and blanks return null.
+ */
+const parseMetaTraderCsvLine = (line, brokerOffset = 0) => {
+    if (!line) return null;
+    const trimmed = line.trim();
+    if (!trimmed || !/^\d/.test(trimmed)) return null;
+
+    const parts = trimmed.split(trimmed.includes('\t') ? '\t' : (trimmed.includes(';') ? ';' : ','));
+    if (parts.length < 6) return null;
+
+    const dateMatch = /^(\d{4})[.\-\/](\d{2})[.\-\/](\d{2})$/.exec(parts[0].trim());
+    if (!dateMatch) return null;
+
+    let timePart = '00:00:00';
+    let idx = 1;
+    if (parts[1].includes(':')) {
+        timePart = parts[1].trim();
+        if (timePart.length === 5) timePart += ':00';
+        idx = 2;
+    }
+    if (parts.length < idx + 4) return null;
+
+    const open = parseFloat(parts[idx]);
+    const high = parseFloat(parts[idx + 1]);
+    const low = parseFloat(parts[idx + 2]);
+    const close = parseFloat(parts[idx + 3]);
+    if (isNaN(open) || isNaN(close)) return null;
+
+    // MT5 exports tick volume then real volume; prefer real volume when present
+    const tickVolume = parseFloat(parts[idx + 4]);
+    const realVolume = parseFloat(parts[idx + 5]);
+    const volume = realVolume > 0 ? realVolume : (isNaN(tickVolume) ? 0 : tickVolume);
+
+    const brokerMs = Date.parse(`${dateMatch[1]}-${dateMatch[2]}-${dateMatch[3]}T${timePart}Z`);
+    if (isNaN(brokerMs)) return null;
+
+    return { timestamp: brokerTimeToUtc(brokerMs, brokerOffset), open, high, low, close, volume };
+};
+
+module.exports = {
+    HST_HEADER_SIZE,
+    HST_V400_RECORD,
+    HST_V401_RECORD,
+    periodToTimeframe,
+    brokerTimeToUtc,
+    readHstHeader,
+    parseHstRecords,
+    parseMetaTraderCsvLine
+};
@@ -0,0 +1,85 @@
+
+const test = require('node:test');
+const assert = require('node:assert');
+const { HST_HEADER_SIZE, HST_V400_RECORD, HST_V401_RECORD, readHstHeader, parseHstRecords, brokerTimeToUtc } = require('./metaTrader');
+
+const header = (version, symbol = 'EURUSD', period = 60, digits = 5) => {
+    const buf = Buffer.alloc(HST_HEADER_SIZE);
+    buf.writeInt32LE(version, 0);
+    buf.write('(C)opyright 2003, MetaQuotes Software Corp.', 4, 'latin1');
+    buf.write(symbol, 68, 'latin1');
+    buf.writeInt32LE(period, 80);
+    buf.writeInt32LE(digits, 84);
+    return buf;
+};
+
+// v400: time int32, open, low, high, close, volume doubles
+const v400Record = ({ time, open, high, low, close, volume }) => {
+    const buf = Buffer.alloc(HST_V400_RECORD);
+    buf.writeInt32LE(time, 0);
+    buf.writeDoubleLE(open, 4);
+    buf.writeDoubleLE(low, 12);
+    buf.writeDoubleLE(high, 20);
+    buf.writeDoubleLE(close, 28);
+    buf.writeDoubleLE(volume, 36);
+    return buf;
+};
+
+// v401: time int64, open, high, low, close doubles, tick volume int64, spread int32, real volume int64
+const v401Record = ({ time, open, high, low, close, tickVolume, spread = 0, realVolume = 0 }) => {
+    const buf = Buffer.alloc(HST_V401_RECORD);
+    buf.writeBigInt64LE(BigInt(time), 0);
+    buf.writeDoubleLE(open, 8);
+    buf.writeDoubleLE(high, 16);
+    buf.writeDoubleLE(low, 24);
+    buf.writeDoubleLE(close, 32);
+    buf.writeBigInt64LE(BigInt(tickVolume), 40);
+    buf.writeInt32LE(spread, 48);
+    buf.writeBigInt64LE(BigInt(realVolume), 52);
+    return buf;
+};
+
+const T0 = Date.UTC(2024, 0, 2, 0, 0) / 1000;
+
+test('reads the header of both versions', () => {
+    const v400 = readHstHeader(header(400, 'GBPUSD', 1440, 4));
+    assert.match(v400.copyright, /MetaQuotes/);
+    assert.deepStrictEqual({ ...v400, copyright: null }, { version: 400, copyright: null, symbol: 'GBPUSD', period: 1440, digits: 4, recordSize: HST_V400_RECORD });
+    assert.strictEqual(readHstHeader(header(401)).recordSize, HST_V401_RECORD);
+    assert.throws(() => readHstHeader(header(500)), /Unsupported HST version 500/);
+    assert.throws(() => readHstHeader(Buffer.alloc(10)), /too small/);
+});
+
+test('decodes v400 records, with low stored before high', () => {
+    const buf = Buffer.concat([header(400), v400Record({ time: T0, open: 1.1, high: 1.2, low: 1.05, close: 1.15, volume: 321 })]);
+    const bars = Array.from(parseHstRecords(buf, readHstHeader(buf)));
+    assert.deepStrictEqual(bars, [{ timestamp: T0 * 1000, open: 1.1, high: 1.2, low: 1.05, close: 1.15, volume: 321 }]);
+});
+
+test('decodes v401 records and prefers real volume over tick volume', () => {
+    const buf = Buffer.concat([
+        header(401),
+        v401Record({ time: T0, open: 1.1, high: 1.2, low: 1.05, close: 1.15, tickVolume: 50, realVolume: 0 }),
+        v401Record({ time: T0 + 3600, open: 1.15, high: 1.25, low: 1.1, close: 1.2, tickVolume: 50, spread: 12, realVolume: 7000 })
+    ]);
+    const bars = Array.from(parseHstRecords(buf, readHstHeader(buf)));
+    assert.deepStrictEqual(bars, [
+        { timestamp: T0 * 1000, open: 1.1, high: 1.2, low: 1.05, close: 1.15, volume: 50 },
+        { timestamp: (T0 + 3600) * 1000, open: 1.15, high: 1.25, low: 1.1, close: 1.2, volume: 7000 }
+    ]);
+});
+
+test('skips empty records, ignores a trailing partial one and applies the broker offset', () => {
+    const complete = v401Record({ time: T0, open: 1, high: 2, low: 0.5, close: 1.5, tickVolume: 1 });
+    const buf = Buffer.concat([header(401), v401Record({ time: 0, open: 1, high: 1, low: 1, close: 1, tickVolume: 1 }), complete, complete.subarray(0, 20)]);
+    const bars = Array.from(parseHstRecords(buf, readHstHeader(buf), 2));
+    assert.strictEqual(bars.length, 1);
+    assert.strictEqual(bars[0].timestamp, T0 * 1000 - 2 * 3600000);
+});
+
+test('ny-close offset is UTC+3 during US daylight saving and UTC+2 outside it', () => {
+    const summer = Date.UTC(2024, 6, 1, 0, 0);
+    const winter = Date.UTC(2024, 0, 15, 0, 0);
+    assert.strictEqual(brokerTimeToUtc(summer, 'ny-close'), summer - 3 * 3600000);
+    assert.strictEqual(brokerTimeToUtc(winter, 'ny-close'), winter - 2 * 3600000);
+});
@@ -0,0 +1,149 @@
+
+const { isMainThread, parentPort, workerData } = require('worker_threads');
+
+// --- MONTE CARLO TRADE SIMULATION ---
+// Resamples the historical trade P&L distribution to show how much of a
+// track record is luck: each run draws a sequence of trades and replays it
+// from the starting equity. 'bootstrap' draws with replacement (and may run
+// longer than the history); 'shuffle' reorders the real trades, so only the
+// path changes, not the final P&L. Runs in a worker: tens of thousands of
+// runs of a long trade list take seconds. Seeded, so a result can be
+// reproduced.
+
+const DEFAULTS = {
+    method: 'bootstrap',
+    tradesPerRun: null,   // default: number of historical trades
+    startingEquity: 10000,
+    ruinDrawdownPct: 50,  // a run is "ruined" once its drawdown reaches this
+    percentiles: [5, 25, 50, 75, 95],
+    bandPoints: 100,      // equity steps sampled for the confidence bands
+    seed: null
+};
+const MAX_ITERATIONS = 100000;
+const MAX_TRADES_PER_RUN = 10000;
+
+// mulberry32: small, fast and good enough for resampling
+const createRandom = (seed) => {
+    let a = seed >>> 0;
+    return () => {
+        a = (a + 0x6D2B79F5) >>> 0;
+        let t = a;
+        t = Math.imul(t ^ (t >>> 15), t | 1);
+        t ^= t + Math.imul(t ^ (t >>> 7), t | 61);
+        return ((t ^ (t >>> 14)) >>> 0) / 4294967296;
+    };
+};
+
+// Accepts P&L numbers, trade records ({ pnl }) or portfolio stats ({ equityCurve })
+const pnlsFrom = (input) => {
+    if (!input) return [];
+    if (Array.isArray(input)) return input.map(item => (typeof item === 'number' ? item : Number(item && item.pnl))).filter(Number.isFinite);
+    if (Array.isArray(input.pnls)) return pnlsFrom(input.pnls);
+    if (Array.isArray(input.trades)) return pnlsFrom(input.trades.filter(trade => trade.closing !== false));
+    if (Array.isArray(input.equityCurve)) return pnlsFrom(input.equityCurve);
+    return [];
+};
+
+// Linear interpolation between closest ranks of a sorted array
+const percentileOf = (sorted, p) => {
+    if (!sorted.length) return null;
+    const rank = (p / 100) * (sorted.length - 1);
+    const lo = Math.floor(rank);
+    const hi = Math.ceil(rank);
+    return sorted[lo] + (sorted[hi] - sorted[lo]) * (rank - lo);
+};
+
+const summarizeDistribution = (values, percentiles) => {
+    const sorted = Float64Array.from(values).sort();
+    const out = { mean: values.length ? values.reduce((sum, v) => sum + v, 0) / values.length : null };
+    percentiles.forEach((p) => { out[`p${p}`] = percentileOf(sorted, p); });
+    return out;
+};
+
+/**
+ * input: see pnlsFrom. params: see DEFAULTS.
+ * Returns { iterations, tradesPerRun, method, seed, finalEquity, returnPct,
+ * maxDrawdown, maxDrawdownPct, probabilityOfProfit, probabilityOfRuin, bands }.
+ */
+const runMonteCarlo = (input, iterations = 5000, params = {}) => {
+    const opts = { ...DEFAULTS, ...params };
+    const pnls = pnlsFrom(input);
+    if (pnls.length < 2) throw new Error('At least two closed trades are needed for a Monte Carlo simulation');
+    const runs = Math.floor(Number(iterations));
+    if (!Number.isFinite(runs) || runs < 1 || runs > MAX_ITERATIONS) throw new Error(`iterations must be between 1 and ${MAX_ITERATIONS}`);
+    if (opts.method !== 'bootstrap' && opts.method !== 'shuffle') throw new Error(`Unknown method: ${opts.method}`);
+    const steps = opts.method === 'shuffle' ? pnls.length : Math.floor(Number(opts.tradesPerRun || pnls.length));
+    if (!(steps >= 1 && steps <= MAX_TRADES_PER_RUN)) throw new Error(`tradesPerRun must be between 1 and ${MAX_TRADES_PER_RUN}`);
+    const start = Number(opts.startingEquity);
+    if (!(start > 0)) throw new Error('startingEquity must be positive');
+
+    const seed = opts.seed != null ? Number(opts.seed) >>> 0 : (Date.now() ^ (Math.random() * 0x100000000)) >>> 0;
+    const random = createRandom(seed);
+    const bandSteps = Array.from(new Set(Array.from({ length: Math.min(opts.bandPoints, steps) + 1 }, (_, i) => Math.round((i / Math.min(opts.bandPoints, steps)) * steps))));
+    const bandIndex = new Map(bandSteps.map((step, i) => [step, i]));
+    const bandValues = bandSteps.map(() => new Float64Array(runs));
+
+    const finals = new Float64Array(runs);
+    const drawdowns = new Float64Array(runs);
+    const drawdownPcts = new Float64Array(runs);
+    let ruined = 0;
+    let profitable = 0;
+    const deck = Float64Array.from(pnls);
+
+    for (let run = 0; run < runs; run++) {
+        let equity = start;
+        let peak = start;
+        let worst = 0;
+        let worstPct = 0;
+        if (opts.method === 'shuffle') {
+            for (let i = deck.length - 1; i > 0; i--) {
+                const j = Math.floor(random() * (i + 1));
+                const tmp = deck[i]; deck[i] = deck[j]; deck[j] = tmp;
+            }
+        }
+        bandValues[0][run] = start;
+        for (let step = 1; step <= steps; step++) {
+            equity += opts.method === 'shuffle' ? deck[step - 1] : pnls[Math.floor(random() * pnls.length)];
+            if (equity > peak) peak = equity;
+            const dd = peak - equity;
+            if (dd > worst) worst = dd;
+            const ddPct = peak > 0 ? (dd / peak) * 100 : 100;
+            if (ddPct > worstPct) worstPct = ddPct;
+            const slot = bandIndex.get(step);
+            if (slot !== undefined) bandValues[slot][run] = equity;
+        }
+        finals[run] = equity;
+        drawdowns[run] = worst;
+        drawdownPcts[run] = worstPct;
+        if (worstPct >= opts.ruinDrawdownPct) ruined += 1;
+        if (equity > start) profitable += 1;
+    }
+
+    return {
+        iterations: runs,
+        tradesPerRun: steps,
+        historicalTrades: pnls.length,
+        method: opts.method,
+        seed,
+        startingEquity: start,
+        finalEquity: summarizeDistribution(finals, opts.percentiles),
+        returnPct: summarizeDistribution(Array.from(finals, v => ((v - start) / start) * 100), opts.percentiles),
+        maxDrawdown: summarizeDistribution(drawdowns, opts.percentiles),
+        maxDrawdownPct: summarizeDistribution(drawdownPcts, opts.percentiles),
+        probabilityOfProfit: profitable / runs,
+        probabilityOfRuin: ruined / runs,
+        ruinDrawdownPct: opts.ruinDrawdownPct,
+        bands: bandSteps.map((step, i) => ({ step, ...summarizeDistribution(bandValues[i], opts.percentiles) }))
+    };
+};
+
+if (!isMainThread && workerData && workerData.monteCarlo) {
+    try {
+        const { input, iterations, params } = workerData.monteCarlo;
+        parentPort.postMessage({ success: true, result: runMonteCarlo(input, iterations, params) });
+    } catch (err) {
+        parentPort.postMessage({ success: false, error: err.message });
+    }
+}
+
+module.exports = { runMonteCarlo };
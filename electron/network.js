@@ -0,0 +1,191 @@
+
+const { EventEmitter } = require('events');
+const { proxyFetch } = require('./proxy');
+
+// --- RESILIENT HTTP LAYER ---
+// Every provider REST call goes through request() instead of bare fetch so
+// resilience is the same everywhere: transient failures (network errors, 429,
+// 5xx) are retried with exponential backoff and full jitter, honouring
+// Retry-After; each endpoint (provider + host + leading path) has a circuit
+// breaker that fails fast after repeated failures and lets a single trial
+// request through once it cools down. Provider health is derived from the
+// breakers and emitted on change so the UI can say "Polygon degraded: retrying".
+//
+// Events (networkEvents):
+//   'health' { provider, status: 'healthy' | 'degraded' | 'down', message, endpoint, at }
+
+const RETRIES = 3;
+const BASE_DELAY_MS = 500;
+const MAX_DELAY_MS = 15000;
+const REQUEST_TIMEOUT_MS = 20000;
+const FAILURE_THRESHOLD = 5;       // consecutive failures that open a breaker
+const OPEN_MS = 30000;             // first cool-down; doubles each time a trial fails
+const MAX_OPEN_MS = 5 * 60000;
+
+const TLS_POLICY_ERRORS = ['CERT_PIN_MISMATCH', 'TLS_POLICY_PLAINTEXT'];
+
+const networkEvents = new EventEmitter();
+networkEvents.setMaxListeners(50);
+
+const breakers = new Map(); // endpoint key -> breaker
+const health = new Map();   // provider -> { provider, status, message, endpoint, at }
+
+const sleep = (ms) => new Promise(resolve => setTimeout(resolve, ms));
+
+// 'polygon api.polygon.io/v2/aggs' — query strings and ids further down the path don't split an endpoint
+const endpointKey = (provider, url) => {
+    try {
+        const parsed = new URL(url);
+        const segments = parsed.pathname.split('/').filter(Boolean).slice(0, 2);
+        return `${provider} ${parsed.host}/${segments.join('/')}`;
+    } catch (e) {
+        return `${provider} ${url}`;
+    }
+};
+
+const getBreaker = (provider, key) => {
+    if (!breakers.has(key)) breakers.set(key, { key, provider, state: 'closed', failures: 0, openMs: OPEN_MS, openedAt: null, trial: false, lastError: null });
+    return breakers.get(key);
+};
+
+const setHealth = (provider, status, message = null, endpoint = null) => {
+    const prev = health.get(provider);
+    if (prev && prev.status === status && prev.message === message) return;
+    const entry = { provider, status, message, endpoint, at: Date.now() };
+    health.set(provider, entry);
+    networkEvents.emit('health', entry);
+};
+
+// Healthy once no breaker of the provider is open or failing
+const settleHealth = (provider) => {
+    const mine = Array.from(breakers.values()).filter(b => b.provider === provider);
+    const open = mine.find(b => b.state !== 'closed');
+    if (open) return setHealth(provider, 'down', `circuit open: ${open.lastError || 'repeated failures'}`, open.key);
+    const failing = mine.find(b => b.failures > 0);
+    if (failing) return setHealth(provider, 'degraded', failing.lastError, failing.key);
+    setHealth(provider, 'healthy');
+};
+
+const recordSuccess = (breaker) => {
+    breaker.state = 'closed';
+    breaker.failures = 0;
+    breaker.openMs = OPEN_MS;
+    breaker.trial = false;
+    breaker.lastError = null;
+    settleHealth(breaker.provider);
+};
+
+const recordFailure = (breaker, message) => {
+    breaker.failures += 1;
+    breaker.lastError = message;
+    if (breaker.state === 'half-open') {
+        breaker.openMs = Math.min(MAX_OPEN_MS, breaker.openMs * 2);
+        breaker.state = 'open';
+        breaker.openedAt = Date.now();
+    } else if (breaker.failures >= FAILURE_THRESHOLD) {
+        breaker.state = 'open';
+        breaker.openedAt = Date.now();
+    }
+    breaker.trial = false;
+    settleHealth(breaker.provider);
+};
+
+// Throws while the breaker is open; moves it to half-open (one trial request) after the cool-down
+const admit = (breaker) => {
+    if (breaker.state === 'closed') return;
+    const remaining = breaker.openedAt + breaker.openMs - Date.now();
+    if (breaker.state === 'open' && remaining > 0) {
+        const err = new Error(`${breaker.provider} unavailable (circuit open, retry in ${Math.ceil(remaining / 1000)}s): ${breaker.lastError || 'repeated failures'}`);
+        err.code = 'CIRCUIT_OPEN';
+        throw err;
+    }
+    if (breaker.trial) {
+        const err = new Error(`${breaker.provider} unavailable (circuit half-open, trial request in flight)`);
+        err.code = 'CIRCUIT_OPEN';
+        throw err;
+    }
+    breaker.state = 'half-open';
+    breaker.trial = true;
+};
+
+const isRetryableStatus = (status) => status === 429 || status >= 500;
+
+const retryAfterMs = (response) => {
+    const header = response && response.headers && response.headers.get('retry-after');
+    if (!header) return null;
+    const seconds = Number(header);
+    if (Number.isFinite(seconds)) return seconds * 1000;
+    const date = Date.parse(header);
+    return Number.isFinite(date) ? Math.max(0, date - Date.now()) : null;
+};
+
+// Full jitter: uniformly random in [0, min(cap, base * 2^attempt)]
+const backoffDelay = (attempt) => Math.random() * Math.min(MAX_DELAY_MS, BASE_DELAY_MS * 2 ** attempt);
+
+/**
+ * fetch() (through the configured proxy) with retries and a per-endpoint circuit breaker.
+ * Returns the Response like fetch does (non-retryable 4xx included, so callers
+ * keep their own error messages); throws on network failure once retries are
+ * used up, or straight away while the endpoint's circuit is open.
+ */
+const request = async (provider, url, init = {}, { retries = RETRIES, timeoutMs = REQUEST_TIMEOUT_MS } = {}) => {
+    const breaker = getBreaker(provider, endpointKey(provider, url));
+    admit(breaker);
+    let lastError = null;
+    let lastResponse = null;
+    for (let attempt = 0; attempt <= retries; attempt++) {
+        try {
+            const response = await proxyFetch(url, { ...init, signal: init.signal || AbortSignal.timeout(timeoutMs) }, provider);
+            if (!isRetryableStatus(response.status)) {
+                // 4xx are the caller's problem, not the endpoint's
+                recordSuccess(breaker);
+                return response;
+            }
+            lastResponse = response;
+            lastError = `HTTP ${response.status} ${response.statusText}`;
+        } catch (err) {
+            if (init.signal && init.signal.aborted) throw err; // cancelled by the caller
+            if (TLS_POLICY_ERRORS.includes(err.code) || err.code === 'NETWORK_DISABLED') {
+                // Not transient: a pin mismatch, refused plaintext endpoint or disabled network stays that way
+                setHealth(provider, 'down', err.message, breaker.key);
+                throw err;
+            }
+            lastResponse = null;
+            lastError = err.name === 'TimeoutError' ? `timed out after ${timeoutMs}ms` : err.message;
+        }
+        if (attempt === retries || breaker.state === 'half-open') break;
+        if (lastResponse && lastResponse.body) lastResponse.body.cancel().catch(() => {});
+        const delay = Math.min(MAX_DELAY_MS, retryAfterMs(lastResponse) ?? backoffDelay(attempt));
+        setHealth(provider, 'degraded', `retrying (${attempt + 1}/${retries}): ${lastError}`, breaker.key);
+        await sleep(delay);
+    }
+    recordFailure(breaker, lastError);
+    if (lastResponse) return lastResponse;
+    throw new Error(`${provider} request failed: ${lastError}`);
+};
+
+const getHealth = () => ({
+    providers: Array.from(health.values()),
+    endpoints: Array.from(breakers.values()).map(b => ({
+        key: b.key,
+        provider: b.provider,
+        state: b.state,
+        failures: b.failures,
+        lastError: b.lastError,
+        reopensAt: b.state === 'open' ? b.openedAt + b.openMs : null
+    }))
+});
+
+// Closes the breakers of one provider (or all), e.g. after the user fixed their connection
+const resetBreakers = (provider = null) => {
+    const touched = new Set();
+    breakers.forEach((b) => {
+        if (provider && b.provider !== provider) return;
+        breakers.delete(b.key);
+        touched.add(b.provider);
+    });
+    touched.forEach(settleHealth);
+    return touched.size;
+};
+
+module.exports = { networkEvents, request, getHealth, resetBreakers };
@@ -0,0 +1,196 @@
+
+const crypto = require('crypto');
+const { request } = require('./network');
+
+// --- NEWS AGGREGATION ---
+// Polls configured RSS / Atom feeds in the background, keeps only items that
+// mention a watched symbol (or one of its keywords) and stores them in
+// `news_items` for the news panel. Feeds are parsed with a small tolerant
+// extractor rather than a full XML parser; feed markup is shallow and we only
+// need a handful of fields.
+
+const DEFAULT_CONFIG = {
+    feeds: [],            // [{ url, name? }]
+    symbols: [],          // watched symbols, usually mirrored from the watchlist
+    keywords: {},         // { SYMBOL: ['Apple', 'iPhone'] }
+    intervalMinutes: 15,
+    retentionDays: 14
+};
+
+const MAX_ITEMS_PER_QUERY = 200;
+
+const initializeNewsTables = (db) => {
+    db.exec(`
+        CREATE TABLE IF NOT EXISTS news_items (
+            id TEXT PRIMARY KEY,
+            feed TEXT,
+            title TEXT,
+            link TEXT,
+            summary TEXT,
+            published INTEGER,
+            symbols TEXT,
+            fetched_at INTEGER
+        );
+        CREATE INDEX IF NOT EXISTS idx_news_published ON news_items (published);
+    `);
+};
+
+const decodeEntities = (text) => text
+    .replace(/<!\[CDATA\[([\s\S]*?)\]\]>/g, '$1')
+    .replace(/&lt;/g, '<').replace(/&gt;/g, '>').replace(/&quot;/g, '"').replace(/&#39;|&apos;/g, "'")
+    .replace(/&#(\d+);/g, (m, n) => String.fromCharCode(Number(n)))
+    .replace(/&#x([0-9a-f]+);/gi, (m, n) => String.fromCharCode(parseInt(n, 16)))
+    .replace(/&amp;/g, '&');
+
+const stripTags = (html) => html.replace(/<[^>]*>/g, ' ').replace(/\s+/g, ' ').trim();
+
+const tagContent = (block, names) => {
+    for (const name of names) {
+        const match = new RegExp(`<${name}(?:\\s[^>]*)?>([\\s\\S]*?)</${name}>`, 'i').exec(block);
+        if (match) return decodeEntities(match[1]).trim();
+    }
+    return '';
+};
+
+const atomLink = (block) => {
+    const alt = /<link[^>]*rel=["']alternate["'][^>]*href=["']([^"']+)["']/i.exec(block)
+        || /<link[^>]*href=["']([^"']+)["'][^>]*rel=["']alternate["']/i.exec(block)
+        || /<link[^>]*href=["']([^"']+)["']/i.exec(block);
+    return alt ? decodeEntities(alt[1]) : '';
+};
+
+/**
+ * Extracts { guid, title, link, summary, published } from RSS 2.0 <item> or Atom <entry> blocks.
+ */
+const parseFeed = (xml) => {
+    const items = [];
+    const blocks = xml.match(/<item[\s>][\s\S]*?<\/item>/gi) || xml.match(/<entry[\s>][\s\S]*?<\/entry>/gi) || [];
+    blocks.forEach((block) => {
+        const title = stripTags(tagContent(block, ['title']));
+        const link = tagContent(block, ['link']) || atomLink(block);
+        const summary = stripTags(tagContent(block, ['description', 'summary', 'content'])).slice(0, 1000);
+        const dateText = tagContent(block, ['pubDate', 'published', 'updated', 'dc:date']);
+        const published = Date.parse(dateText);
+        const guid = tagContent(block, ['guid', 'id']) || link || title;
+        if (title) items.push({ guid, title, link, summary, published: isNaN(published) ? Date.now() : published });
+    });
+    return items;
+};
+
+// BTCUSDT should also match plain "BTC" / "Bitcoin"-style keyword lists
+const symbolTerms = (symbol, keywords = []) => {
+    const terms = new Set([symbol]);
+    const base = symbol.replace(/(USDT|USDC|BUSD|USD|EUR|BTC)$/, '');
+    if (base && base !== symbol && base.length >= 2) terms.add(base);
+    keywords.forEach(k => k && terms.add(k));
+    return Array.from(terms);
+};
+
+const escapeRegex = (s) => s.replace(/[.*+?^${}()|[\]\\]/g, '\\$&');
+
+const matchSymbols = (item, config) => {
+    const text = `${item.title} ${item.summary}`;
+    return config.symbols.filter((symbol) => symbolTerms(symbol, config.keywords[symbol]).some((term) => {
+        // Tickers are matched case-sensitively (or as $TICKER); keywords are not
+        const isTicker = term === term.toUpperCase();
+        const re = new RegExp(`(^|[^A-Za-z0-9])\\$?${escapeRegex(term)}(?![A-Za-z0-9])`, isTicker ? '' : 'i');
+        return re.test(text);
+    }));
+};
+
+const mapNewsRow = (row) => ({
+    id: row.id,
+    feed: row.feed,
+    title: row.title,
+    link: row.link,
+    summary: row.summary,
+    published: row.published,
+    symbols: row.symbols ? row.symbols.split(',').filter(Boolean) : []
+});
+
+// getIntervalScale: multiplier on the poll interval (power saving stretches it)
+const createNewsService = ({ db, loadConfig, saveConfig, onItems, onLog, getIntervalScale = () => 1 }) => {
+    let config = { ...DEFAULT_CONFIG, ...(loadConfig() || {}) };
+    let timer = null;
+    let polling = false;
+
+    const log = (level, message, data) => onLog && onLog(level, message, data);
+
+    const insertItem = db.prepare(`
+        INSERT OR IGNORE INTO news_items (id, feed, title, link, summary, published, symbols, fetched_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+    `);
+
+    const pollFeed = async (feed) => {
+        const response = await request('news', feed.url, { headers: { 'User-Agent': 'RedPillCharting/NewsReader', Accept: 'application/rss+xml, application/atom+xml, text/xml' } });
+        if (!response.ok) throw new Error(`${response.status} ${response.statusText}`);
+        const items = parseFeed(await response.text());
+        const fresh = [];
+        items.forEach((item) => {
+            const symbols = matchSymbols(item, config);
+            if (symbols.length === 0) return;
+            const id = crypto.createHash('sha1').update(`${feed.url}|${item.guid}`).digest('hex');
+            const info = insertItem.run(id, feed.name || feed.url, item.title, item.link, item.summary, item.published, `,${symbols.join(',')},`, Date.now());
+            if (info.changes > 0) fresh.push({ id, feed: feed.name || feed.url, title: item.title, link: item.link, summary: item.summary, published: item.published, symbols });
+        });
+        return fresh;
+    };
+
+    const pollNow = async () => {
+        if (polling || config.feeds.length === 0 || config.symbols.length === 0) return [];
+        polling = true;
+        const fresh = [];
+        try {
+            for (const feed of config.feeds) {
+                try {
+                    fresh.push(...await pollFeed(feed));
+                } catch (err) {
+                    log('WARN', 'NEWS_FEED_FAILED', { feed: feed.url, error: err.message });
+                }
+            }
+            const cutoff = Date.now() - config.retentionDays * 86400000;
+            db.prepare('DELETE FROM news_items WHERE published < ?').run(cutoff);
+            if (fresh.length > 0) {
+                fresh.sort((a, b) => b.published - a.published);
+                onItems(fresh);
+            }
+        } finally {
+            polling = false;
+        }
+        return fresh;
+    };
+
+    const schedule = () => {
+        if (timer) clearInterval(timer);
+        timer = null;
+        if (config.feeds.length === 0) return;
+        timer = setInterval(() => { pollNow().catch(() => {}); }, Math.max(1, config.intervalMinutes) * 60000 * Math.max(1, getIntervalScale()));
+    };
+
+    return {
+        start: () => {
+            schedule();
+            pollNow().catch(() => {});
+        },
+        stop: () => { if (timer) clearInterval(timer); timer = null; },
+        reschedule: schedule,
+        getConfig: () => config,
+        configure: (updates) => {
+            config = { ...config, ...updates };
+            config.symbols = Array.from(new Set((config.symbols || []).map(s => String(s).toUpperCase())));
+            saveConfig(config);
+            schedule();
+            return config;
+        },
+        pollNow,
+        getNews: (symbol = null, since = 0, limit = MAX_ITEMS_PER_QUERY) => {
+            const capped = Math.min(limit, MAX_ITEMS_PER_QUERY);
+            const rows = symbol
+                ? db.prepare('SELECT * FROM news_items WHERE symbols LIKE ? AND published >= ? ORDER BY published DESC LIMIT ?').all(`%,${String(symbol).toUpperCase()},%`, since || 0, capped)
+                : db.prepare('SELECT * FROM news_items WHERE published >= ? ORDER BY published DESC LIMIT ?').all(since || 0, capped);
+            return rows.map(mapNewsRow);
+        }
+    };
+};
+
+module.exports = { initializeNewsTables, createNewsService, parseFeed };
@@ -0,0 +1,146 @@
+
+const crypto = require('crypto');
+const { bulkUpdateNotes } = require('./stickyNotes');
+const { sessionWindows } = require('./sessions');
+
+// --- NOTE TEMPLATES ---
+// Reusable sticky-note bodies (pre-trade checklist, daily plan) with
+// {{placeholders}}. Creating a note from a template fills built-in values
+// (date, time, weekday, symbol, session state and day) and caller variables,
+// which win over built-ins; {{name|fallback}} supplies a default and unknown
+// placeholders are left in place and reported. Two starter templates are
+// seeded when the table is first created and can be edited like any other.
+
+const MAX_TEMPLATE_TEXT = 20000;
+const PLACEHOLDER_RE = /\{\{\s*([A-Za-z_][A-Za-z0-9_]*)\s*(?:\|([^}]*))?\}\}/g;
+
+const STARTER_TEMPLATES = [
+    {
+        name: 'Pre-trade checklist',
+        color: '#3B82F6',
+        tags: ['checklist'],
+        text: '{{symbol}} · {{date}} {{time}} ({{session}})\n[ ] Higher-timeframe trend\n[ ] Key level / setup: \n[ ] Stop: \n[ ] Target: \n[ ] Size within risk budget\n[ ] News / calendar checked'
+    },
+    {
+        name: 'Daily plan',
+        color: '#10B981',
+        tags: ['plan'],
+        text: 'Plan for {{weekday}} {{date}}\nBias: \nLevels to watch: \nEvents: \nMax loss today: {{max_loss|}}\nRules: '
+    }
+];
+
+const initializeNoteTemplateTable = (db) => {
+    const exists = db.prepare("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'note_templates'").get();
+    db.exec(`
+        CREATE TABLE IF NOT EXISTS note_templates (
+            id TEXT PRIMARY KEY,
+            name TEXT,
+            text TEXT,
+            color TEXT,
+            tags TEXT,
+            layout TEXT,
+            created_at INTEGER,
+            updated_at INTEGER
+        );
+    `);
+    if (!exists) STARTER_TEMPLATES.forEach(template => saveNoteTemplate(db, template));
+};
+
+const mapTemplateRow = (row) => (row ? {
+    id: row.id,
+    name: row.name,
+    text: row.text,
+    color: row.color,
+    tags: row.tags ? JSON.parse(row.tags) : [],
+    layout: row.layout ? JSON.parse(row.layout) : {},
+    placeholders: placeholdersOf(row.text),
+    createdAt: row.created_at,
+    updatedAt: row.updated_at
+} : null);
+
+const placeholdersOf = (text) => Array.from(new Set(Array.from(String(text || '').matchAll(PLACEHOLDER_RE), m => m[1].toLowerCase())));
+
+const listNoteTemplates = (db) => db.prepare('SELECT * FROM note_templates ORDER BY name COLLATE NOCASE').all().map(mapTemplateRow);
+
+const getNoteTemplate = (db, id) => mapTemplateRow(db.prepare('SELECT * FROM note_templates WHERE id = ?').get(id));
+
+// template: { id?, name, text, color?, tags?, layout? }; saving an existing id updates it
+const saveNoteTemplate = (db, template = {}) => {
+    const existing = template.id ? getNoteTemplate(db, template.id) : null;
+    const merged = { ...(existing || {}), ...template };
+    const name = String(merged.name || '').trim();
+    if (!name) throw new Error('Template name is required');
+    if (name.length > 100) throw new Error('Template name is too long');
+    const text = String(merged.text == null ? '' : merged.text);
+    if (text.length > MAX_TEMPLATE_TEXT) throw new Error(`Template text is longer than ${MAX_TEMPLATE_TEXT} characters`);
+    if (merged.color && !/^#([0-9a-f]{3}|[0-9a-f]{6}|[0-9a-f]{8})$/i.test(merged.color)) throw new Error(`Invalid note color: ${merged.color}`);
+    const id = existing ? existing.id : crypto.randomUUID();
+    if (db.prepare('SELECT 1 FROM note_templates WHERE name = ? COLLATE NOCASE AND id != ?').get(name, id)) throw new Error(`A note template named "${name}" already exists`);
+    const now = Date.now();
+    db.prepare('INSERT OR REPLACE INTO note_templates (id, name, text, color, tags, layout, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)')
+        .run(id, name, text, merged.color || null, JSON.stringify(merged.tags || []), JSON.stringify(merged.layout || {}), existing ? existing.createdAt : now, now);
+    return getNoteTemplate(db, id);
+};
+
+const deleteNoteTemplate = (db, id) => db.prepare('DELETE FROM note_templates WHERE id = ?').run(id).changes > 0;
+
+const pad = (n) => String(n).padStart(2, '0');
+
+// RTH / ETH / Overnight / Closed at `now` for a session calendar (see sessions.js)
+const sessionState = (calendar, now) => {
+    const { days } = sessionWindows(calendar, now, now);
+    const inside = (w) => w && now >= w.start && now < w.end;
+    const day = days.find(d => inside(d.rth) || inside(d.eth) || inside(d.overnight));
+    if (!day) return { session: 'Closed', sessionDay: null };
+    return { session: inside(day.rth) ? 'RTH' : inside(day.overnight) ? 'Overnight' : 'ETH', sessionDay: day.day };
+};
+
+// Built-in values in local time; session ones need a calendar
+const builtinVariables = ({ now = Date.now(), symbol = null, calendar = null } = {}) => {
+    const d = new Date(now);
+    const vars = {
+        date: `${d.getFullYear()}-${pad(d.getMonth() + 1)}-${pad(d.getDate())}`,
+        time: `${pad(d.getHours())}:${pad(d.getMinutes())}`,
+        weekday: d.toLocaleDateString('en-US', { weekday: 'long' }),
+        symbol
+    };
+    if (calendar) {
+        const { session, sessionDay } = sessionState(calendar, now);
+        vars.session = session;
+        vars.session_day = sessionDay;
+    }
+    return vars;
+};
+
+// Returns { text, missing } where missing lists placeholders without a value or fallback
+const renderTemplate = (text, vars = {}) => {
+    const values = {};
+    Object.entries(vars).forEach(([key, value]) => { if (value != null) values[key.toLowerCase()] = String(value); });
+    const missing = new Set();
+    const rendered = String(text || '').replace(PLACEHOLDER_RE, (match, name, fallback) => {
+        const key = name.toLowerCase();
+        if (key in values) return values[key];
+        if (fallback !== undefined) return fallback.trim();
+        missing.add(key);
+        return match;
+    });
+    return { text: rendered, missing: Array.from(missing) };
+};
+
+/**
+ * Creates a sticky note from a template. vars override built-ins; options:
+ * { boardId?, calendar?, now? } (calendar enables {{session}} / {{session_day}}).
+ * Returns { note, missing, changes } — changes as from bulkUpdateNotes.
+ */
+const createNoteFromTemplate = (db, templateId, vars = {}, { boardId = null, calendar = null, now = Date.now() } = {}) => {
+    const template = getNoteTemplate(db, templateId);
+    if (!template) throw new Error(`Note template not found: ${templateId}`);
+    const { text, missing } = renderTemplate(template.text, { ...builtinVariables({ now, symbol: vars.symbol || null, calendar }), ...vars });
+    const id = crypto.randomUUID();
+    const note = { id, text, tags: template.tags, layout: template.layout, symbol: vars.symbol || null, boardId };
+    if (template.color) note.color = template.color;
+    const { changes } = bulkUpdateNotes(db, [{ op: 'create', note }]);
+    return { note: changes[0].item, missing, changes };
+};
+
+module.exports = { initializeNoteTemplateTable, listNoteTemplates, getNoteTemplate, saveNoteTemplate, deleteNoteTemplate, renderTemplate, createNoteFromTemplate };
@@ -0,0 +1,80 @@
+
+const { postJson } = require('./webhooks');
+const { t } = require('./i18n');
+
+// --- ALERT NOTIFIERS ---
+// Built-in chat channels for alert messages. Credentials come from the secrets
+// store (telegram.botToken, discord.webhookUrl); the non-secret parts (enable
+// flags, Telegram chat id) live in the `notifiers` setting.
+
+const CHANNELS = ['telegram', 'discord'];
+
+const DEFAULT_CONFIG = {
+    telegram: { enabled: false, chatId: '' },
+    discord: { enabled: false }
+};
+
+const formatAlertMessage = (payload) => {
+    const direction = t(`alerts.condition.${payload.condition}`);
+    const target = payload.expression ? `"${payload.expression}"` : payload.level;
+    const text = `🔔 ${payload.symbol} ${direction} ${target} (last ${payload.price})`;
+    return payload.message ? `${text}\n${payload.message}` : text;
+};
+
+const createNotifiers = ({ getSecret, loadConfig }) => {
+    const config = () => {
+        const stored = loadConfig() || {};
+        return {
+            telegram: { ...DEFAULT_CONFIG.telegram, ...(stored.telegram || {}) },
+            discord: { ...DEFAULT_CONFIG.discord, ...(stored.discord || {}) }
+        };
+    };
+
+    const senders = {
+        telegram: async (text) => {
+            const token = getSecret('telegram.botToken');
+            const { chatId } = config().telegram;
+            if (!token) throw new Error('Telegram bot token is not configured');
+            if (!chatId) throw new Error('Telegram chat id is not configured');
+            const result = await postJson(`https://api.telegram.org/bot${token}/sendMessage`, { chat_id: chatId, text, disable_web_page_preview: true }, { retries: 2 });
+            // Never echo the token (it is part of the URL) back in errors
+            if (!result.success) throw new Error(`Telegram: ${result.error.replace(token, '***')}`);
+        },
+        discord: async (text) => {
+            const url = getSecret('discord.webhookUrl');
+            if (!url) throw new Error('Discord webhook URL is not configured');
+            const result = await postJson(url, { content: text, username: 'Red Pill Alerts' }, { retries: 2 });
+            if (!result.success) throw new Error(`Discord: ${result.error}`);
+        }
+    };
+
+    const send = async (channel, text) => {
+        if (!senders[channel]) throw new Error(`Unknown notification channel: ${channel}`);
+        await senders[channel](text);
+    };
+
+    return {
+        getConfig: config,
+
+        /**
+         * Sends to every enabled channel. Resolves to { [channel]: { success, error? } }.
+         */
+        notifyAll: async (text) => {
+            const cfg = config();
+            const results = {};
+            await Promise.all(CHANNELS.filter(c => cfg[c].enabled).map(async (channel) => {
+                try {
+                    await send(channel, text);
+                    results[channel] = { success: true };
+                } catch (err) {
+                    results[channel] = { success: false, error: err.message };
+                }
+            }));
+            return results;
+        },
+
+        sendTest: (channel) => send(channel, '✅ Red Pill test notification — this channel is working.')
+    };
+};
+
+module.exports = { CHANNELS, createNotifiers, formatAlertMessage };
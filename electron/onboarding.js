@@ -0,0 +1,97 @@
+
+// --- FIRST-RUN ONBOARDING ---
+// The wizard's steps live here so the renderer and the backend agree on
+// where the user is. Steps run in order; each is completed (running its
+// backend action, if any) or, when optional, skipped:
+//
+//   welcome            acknowledge
+//   folders            create the default data, backup and report folders
+//   sample-data        optional: generate synthetic datasets to play with
+//   file-associations  optional: offer the app for opening CSV / HST files
+//   finish
+//
+// A workspace that already has data on first contact (an upgrade) is
+// marked finished without showing the wizard. Progress is stored after every
+// step, so quitting halfway resumes at the same step.
+
+const ONBOARDING_VERSION = 1;
+
+const STEPS = [
+    { id: 'welcome', optional: false },
+    { id: 'folders', optional: false },
+    { id: 'sample-data', optional: true },
+    { id: 'file-associations', optional: true },
+    { id: 'finish', optional: false }
+];
+
+/**
+ * loadState() / saveState(state) persist progress; detectWorkspace() ->
+ * { empty, datasets, charts }; actions: { [stepId]: async (options) => result }.
+ */
+const createOnboarding = ({ loadState, saveState, detectWorkspace, actions = {} }) => {
+    const stored = () => {
+        const state = loadState();
+        return state && state.version === ONBOARDING_VERSION ? state : { version: ONBOARDING_VERSION, steps: {}, finishedAt: null, finishReason: null };
+    };
+
+    const snapshot = (state) => {
+        const workspace = detectWorkspace();
+        const current = state.finishedAt ? null : STEPS.find(s => !state.steps[s.id]);
+        return {
+            active: !state.finishedAt,
+            currentStep: current ? current.id : null,
+            finishedAt: state.finishedAt,
+            finishReason: state.finishReason,
+            workspace,
+            steps: STEPS.map(s => ({
+                id: s.id,
+                optional: s.optional,
+                hasAction: !!actions[s.id],
+                status: state.steps[s.id] ? (state.steps[s.id].skipped ? 'skipped' : 'done') : 'pending',
+                completedAt: state.steps[s.id] ? state.steps[s.id].at : null,
+                result: state.steps[s.id] ? state.steps[s.id].result : null
+            }))
+        };
+    };
+
+    // First contact with a workspace that isn't empty: nothing to onboard
+    const getState = () => {
+        const state = stored();
+        if (!state.finishedAt && !state.restarted && !Object.keys(state.steps).length && !detectWorkspace().empty) {
+            state.finishedAt = Date.now();
+            state.finishReason = 'existing-workspace';
+            saveState(state);
+        }
+        return snapshot(state);
+    };
+
+    /**
+     * Completes the current step. options.skip skips an optional one; the rest
+     * of options goes to the step's action. Completing any other step throws.
+     */
+    const completeStep = async (stepId, { skip = false, ...options } = {}) => {
+        const state = stored();
+        if (state.finishedAt) throw new Error('Onboarding is already finished');
+        const current = STEPS.find(s => !state.steps[s.id]);
+        if (!current || current.id !== stepId) throw new Error(`Step ${stepId} is not the current step${current ? ` (${current.id} is)` : ''}`);
+        if (skip && !current.optional) throw new Error(`Step ${stepId} can't be skipped`);
+        const result = !skip && actions[stepId] ? await actions[stepId](options) : null;
+        state.steps[stepId] = { at: Date.now(), skipped: !!skip, result: result == null ? null : result };
+        if (stepId === STEPS[STEPS.length - 1].id) {
+            state.finishedAt = Date.now();
+            state.finishReason = 'completed';
+        }
+        saveState(state);
+        return snapshot(state);
+    };
+
+    // Runs the wizard again, also on a workspace that has data
+    const reset = () => {
+        saveState({ version: ONBOARDING_VERSION, steps: {}, finishedAt: null, finishReason: null, restarted: true });
+        return snapshot(stored());
+    };
+
+    return { getState, completeStep, reset };
+};
+
+module.exports = { ONBOARDING_STEPS: STEPS.map(s => s.id), createOnboarding };
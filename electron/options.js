@@ -0,0 +1,266 @@
+
+// --- OPTIONS PRICING ---
+// Option chains, pricing and greeks for chart overlays. European contracts
+// use Black-Scholes-Merton with a continuous dividend yield; American ones a
+// Cox-Ross-Rubinstein binomial tree with early exercise. Implied volatility
+// is solved with Newton's method on vega, falling back to bisection where
+// vega is too flat (deep in / out of the money, close to expiry).
+//
+// Units: time in years (365-day), rates and yields continuous and annual
+// (0.05 = 5%), volatility annual (0.2 = 20%). Greeks are quoted the way
+// traders read them: vega and rho per 1 point (0.01), theta per calendar day.
+//
+// Chain: { underlying, spot, rate?, dividendYield?, valuationTime?,
+//   expirations: [{ expiry (ms), style?, contracts: [{ strike, type: 'call' | 'put',
+//   bid?, ask?, last?, iv?, volume?, openInterest? }] }] }
+
+const YEAR_MS = 365 * 86400000;
+const MODELS = ['black-scholes', 'binomial'];
+const IV_MIN = 1e-4;
+const IV_MAX = 5;
+const IV_TOLERANCE = 1e-8;
+const DEFAULT_STEPS = 200;
+
+const normPdf = (x) => Math.exp(-0.5 * x * x) / Math.sqrt(2 * Math.PI);
+
+// West (2005), "Better approximations to cumulative normal functions": double precision
+const normCdf = (x) => {
+    const z = Math.abs(x);
+    let c;
+    if (z > 37) {
+        c = 0;
+    } else {
+        const e = Math.exp(-z * z / 2);
+        if (z < 7.07106781186547) {
+            let n = 3.52624965998911e-02 * z + 0.700383064443688;
+            n = n * z + 6.37396220353165;
+            n = n * z + 33.912866078383;
+            n = n * z + 112.079291497871;
+            n = n * z + 221.213596169931;
+            n = n * z + 220.206867912376;
+            let d = 8.83883476483184e-02 * z + 1.75566716318264;
+            d = d * z + 16.064177579207;
+            d = d * z + 86.7807322029461;
+            d = d * z + 296.564248779674;
+            d = d * z + 637.333633378831;
+            d = d * z + 793.826512519948;
+            d = d * z + 440.413735824752;
+            c = e * n / d;
+        } else {
+            const b = z + 1 / (z + 2 / (z + 3 / (z + 4 / (z + 0.65))));
+            c = e / (b * 2.506628274631);
+        }
+    }
+    return x > 0 ? 1 - c : c;
+};
+
+const intrinsicValue = (type, spot, strike) => Math.max(0, type === 'call' ? spot - strike : strike - spot);
+
+const blackScholes = ({ type, spot, strike, timeYears: T, rate: r = 0, dividendYield: q = 0, volatility: sigma }) => {
+    if (T <= 0 || sigma <= 0) {
+        const price = intrinsicValue(type, spot, strike);
+        const itm = price > 0;
+        return { price, greeks: { delta: itm ? (type === 'call' ? 1 : -1) : 0, gamma: 0, vega: 0, theta: 0, rho: 0 } };
+    }
+    const sqrtT = Math.sqrt(T);
+    const d1 = (Math.log(spot / strike) + (r - q + sigma * sigma / 2) * T) / (sigma * sqrtT);
+    const d2 = d1 - sigma * sqrtT;
+    const dfR = Math.exp(-r * T);
+    const dfQ = Math.exp(-q * T);
+    const pdf = normPdf(d1);
+    const gamma = dfQ * pdf / (spot * sigma * sqrtT);
+    const vega = spot * dfQ * pdf * sqrtT / 100;
+    if (type === 'call') {
+        const price = spot * dfQ * normCdf(d1) - strike * dfR * normCdf(d2);
+        const theta = (-spot * dfQ * pdf * sigma / (2 * sqrtT) - r * strike * dfR * normCdf(d2) + q * spot * dfQ * normCdf(d1)) / 365;
+        return { price, greeks: { delta: dfQ * normCdf(d1), gamma, vega, theta, rho: strike * T * dfR * normCdf(d2) / 100 } };
+    }
+    const price = strike * dfR * normCdf(-d2) - spot * dfQ * normCdf(-d1);
+    const theta = (-spot * dfQ * pdf * sigma / (2 * sqrtT) + r * strike * dfR * normCdf(-d2) - q * spot * dfQ * normCdf(-d1)) / 365;
+    return { price, greeks: { delta: -dfQ * normCdf(-d1), gamma, vega, theta, rho: -strike * T * dfR * normCdf(-d2) / 100 } };
+};
+
+// CRR tree; delta, gamma and theta come from the first nodes, vega and rho by bumping
+const binomialPrice = ({ type, spot, strike, timeYears: T, rate: r = 0, dividendYield: q = 0, volatility: sigma, style = 'american', steps = DEFAULT_STEPS }) => {
+    const n = Math.max(2, Math.floor(steps));
+    const dt = T / n;
+    // Below |r - q|·√dt the up-probability leaves (0, 1); such volatilities price like that floor
+    const u = Math.exp(Math.max(sigma, Math.abs(r - q) * Math.sqrt(dt) * 1.001, 1e-6) * Math.sqrt(dt));
+    const d = 1 / u;
+    const growth = Math.exp((r - q) * dt);
+    const p = (growth - d) / (u - d);
+    const disc = Math.exp(-r * dt);
+    const american = style === 'american';
+    const values = new Float64Array(n + 1);
+    for (let i = 0; i <= n; i++) values[i] = intrinsicValue(type, spot * u ** (n - i) * d ** i, strike);
+    const early = [];
+    for (let step = n - 1; step >= 0; step--) {
+        for (let i = 0; i <= step; i++) {
+            let value = disc * (p * values[i] + (1 - p) * values[i + 1]);
+            if (american) value = Math.max(value, intrinsicValue(type, spot * u ** (step - i) * d ** i, strike));
+            values[i] = value;
+        }
+        if (step <= 2) early[step] = Array.from(values.subarray(0, step + 1));
+    }
+    const [[price], [v1u, v1d], [v2uu, v2ud, v2dd]] = [early[0], early[1], early[2]];
+    const s1u = spot * u;
+    const s1d = spot * d;
+    const delta = (v1u - v1d) / (s1u - s1d);
+    const gamma = ((v2uu - v2ud) / (spot * u * u - spot) - (v2ud - v2dd) / (spot - spot * d * d)) / ((spot * u * u - spot * d * d) / 2);
+    const theta = (v2ud - price) / (2 * dt) / 365;
+    return { price, delta, gamma, theta };
+};
+
+const binomial = (input) => {
+    if (input.timeYears <= 0 || input.volatility <= 0) return blackScholes(input);
+    const base = binomialPrice(input);
+    const bump = (changes) => binomialPrice({ ...input, ...changes }).price;
+    const vega = (bump({ volatility: input.volatility + 0.005 }) - bump({ volatility: Math.max(IV_MIN, input.volatility - 0.005) })) / ((input.volatility + 0.005 - Math.max(IV_MIN, input.volatility - 0.005)) * 100);
+    const rho = (bump({ rate: (input.rate || 0) + 0.0005 }) - bump({ rate: (input.rate || 0) - 0.0005 })) / 0.1;
+    return { price: base.price, greeks: { delta: base.delta, gamma: base.gamma, vega, theta: base.theta, rho } };
+};
+
+const modelFor = (input) => (input.model === 'binomial' || (input.model == null && input.style === 'american') ? 'binomial' : 'black-scholes');
+
+const evaluate = (input) => (modelFor(input) === 'binomial' ? binomial(input) : blackScholes(input));
+
+/**
+ * Implied volatility for a market price, or null when the price is outside
+ * the no-arbitrage bounds (below intrinsic value / above the underlying).
+ */
+const impliedVolatility = (input, marketPrice) => {
+    const target = Number(marketPrice);
+    if (!(target > 0) || input.timeYears <= 0) return null;
+    const priceAt = (sigma) => evaluate({ ...input, volatility: sigma }).price;
+    const lowest = priceAt(IV_MIN);
+    const highest = priceAt(IV_MAX);
+    if (target < lowest - IV_TOLERANCE || target > highest + IV_TOLERANCE) return null;
+    // Newton from a Brenner-Subrahmanyam style start; bisection keeps the bracket
+    let lo = IV_MIN;
+    let hi = IV_MAX;
+    let sigma = Math.min(IV_MAX, Math.max(0.05, Math.sqrt(2 * Math.PI / input.timeYears) * target / input.spot));
+    for (let i = 0; i < 100; i++) {
+        const { price, greeks } = evaluate({ ...input, volatility: sigma });
+        const diff = price - target;
+        if (Math.abs(diff) < IV_TOLERANCE) return sigma;
+        if (diff > 0) hi = sigma; else lo = sigma;
+        const vega = greeks.vega * 100;
+        let next = vega > 1e-8 ? sigma - diff / vega : NaN;
+        if (!(next > lo && next < hi)) next = (lo + hi) / 2;
+        if (Math.abs(next - sigma) < 1e-10) return next;
+        sigma = next;
+    }
+    return sigma;
+};
+
+const timeToExpiry = (input) => {
+    if (input.timeYears != null) return Math.max(0, Number(input.timeYears));
+    if (input.expiry == null) throw new Error('expiry or timeYears is required');
+    const valuation = input.valuationTime != null ? Number(input.valuationTime) : Date.now();
+    return Math.max(0, (Number(input.expiry) - valuation) / YEAR_MS);
+};
+
+const normalizeInput = (input = {}) => {
+    const type = String(input.type || '').toLowerCase();
+    if (type !== 'call' && type !== 'put') throw new Error(`Invalid option type: ${input.type}`);
+    const spot = Number(input.spot);
+    const strike = Number(input.strike);
+    if (!(spot > 0)) throw new Error('spot must be positive');
+    if (!(strike > 0)) throw new Error('strike must be positive');
+    if (input.model != null && !MODELS.includes(input.model)) throw new Error(`Unknown model: ${input.model}`);
+    const style = input.style || 'european';
+    if (style !== 'european' && style !== 'american') throw new Error(`Invalid style: ${input.style}`);
+    return {
+        type,
+        spot,
+        strike,
+        timeYears: timeToExpiry(input),
+        rate: Number(input.rate || 0),
+        dividendYield: Number(input.dividendYield || 0),
+        volatility: input.volatility != null ? Number(input.volatility) : null,
+        style,
+        model: input.model,
+        steps: input.steps || DEFAULT_STEPS
+    };
+};
+
+/**
+ * input: { type, spot, strike, expiry (ms) | timeYears, valuationTime?, rate?,
+ * dividendYield?, volatility? | marketPrice, style?, model?, steps? }
+ * Without a volatility, it is implied from marketPrice first.
+ */
+const priceOption = (raw) => {
+    const input = normalizeInput(raw);
+    let { volatility } = input;
+    if (volatility == null) {
+        if (raw.marketPrice == null) throw new Error('volatility or marketPrice is required');
+        volatility = impliedVolatility(input, raw.marketPrice);
+        if (volatility == null) throw new Error(`Market price ${raw.marketPrice} is outside the no-arbitrage bounds`);
+    }
+    if (!(volatility >= 0)) throw new Error('volatility must not be negative');
+    const { price, greeks } = evaluate({ ...input, volatility });
+    const intrinsic = intrinsicValue(input.type, input.spot, input.strike);
+    return {
+        model: modelFor(input),
+        style: input.style,
+        timeYears: input.timeYears,
+        volatility,
+        price,
+        intrinsic,
+        timeValue: price - intrinsic,
+        greeks
+    };
+};
+
+// Market price used to imply a contract's volatility: mid when quoted both sides
+const marketPriceOf = (contract) => {
+    const bid = Number(contract.bid);
+    const ask = Number(contract.ask);
+    if (bid > 0 && ask > 0 && ask >= bid) return (bid + ask) / 2;
+    if (Number(contract.last) > 0) return Number(contract.last);
+    return null;
+};
+
+/**
+ * Fills iv, theoretical price and greeks for every contract of a chain, plus
+ * per-expiry ATM volatility. A contract keeps its own iv when given one;
+ * options: { model?, steps?, valuationTime? } override the chain's.
+ */
+const computeChainGreeks = (chain = {}, options = {}) => {
+    const spot = Number(chain.spot);
+    if (!(spot > 0)) throw new Error('Chain spot must be positive');
+    const valuationTime = options.valuationTime ?? chain.valuationTime ?? Date.now();
+    const expirations = (chain.expirations || []).map((expiration) => {
+        const base = {
+            spot,
+            expiry: expiration.expiry,
+            valuationTime,
+            rate: chain.rate || 0,
+            dividendYield: chain.dividendYield || 0,
+            style: expiration.style || chain.style || 'european',
+            model: options.model,
+            steps: options.steps
+        };
+        const contracts = (expiration.contracts || []).map((contract) => {
+            const input = normalizeInput({ ...base, type: contract.type, strike: contract.strike });
+            const marketPrice = marketPriceOf(contract);
+            const iv = contract.iv != null ? Number(contract.iv) : marketPrice != null ? impliedVolatility(input, marketPrice) : null;
+            if (iv == null) return { ...contract, marketPrice, iv: null, price: null, greeks: null };
+            const { price, greeks } = evaluate({ ...input, volatility: iv });
+            return { ...contract, marketPrice, iv, price, greeks };
+        });
+        const priced = contracts.filter(c => c.iv != null);
+        const atmStrike = priced.length ? priced.reduce((best, c) => (Math.abs(c.strike - spot) < Math.abs(best - spot) ? c.strike : best), priced[0].strike) : null;
+        const atm = priced.filter(c => c.strike === atmStrike);
+        return {
+            ...expiration,
+            timeYears: timeToExpiry(base),
+            atmStrike,
+            atmIv: atm.length ? atm.reduce((sum, c) => sum + c.iv, 0) / atm.length : null,
+            contracts
+        };
+    });
+    return { ...chain, valuationTime, expirations };
+};
+
+module.exports = { normCdf, blackScholes, impliedVolatility, priceOption, computeChainGreeks };
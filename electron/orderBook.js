@@ -0,0 +1,182 @@
+
+// --- ORDER BOOK (LEVEL 2) ---
+// Per-symbol books kept in the main process from provider depth events, so
+// windows only ever receive throttled top-of-book snapshots instead of every
+// incremental update. Providers publish through liveFeed.publishDepth:
+//
+//   { type: 'snapshot', bids, asks, sequence }
+//   { type: 'update', bids, asks, firstSequence?, sequence? }
+//
+// bids / asks are [price, size] pairs; size 0 removes the level. Updates that
+// arrive before the first snapshot are buffered. With sequence numbers,
+// updates the snapshot already covers are dropped and a gap marks the book
+// out of sync until the provider sends a fresh snapshot (onGap asks for one).
+
+const DEFAULT_LEVELS = 25;
+const DEFAULT_THROTTLE_MS = 250;
+const MIN_THROTTLE_MS = 50;
+const MAX_BUFFERED_UPDATES = 2000;
+
+const toLevel = ([price, size]) => [Number(price), Number(size)];
+
+const createOrderBook = () => {
+    const bids = new Map(); // price -> size
+    const asks = new Map();
+    let sequence = null;
+    let synced = false;
+    let buffered = [];
+    let updatedAt = null;
+
+    const applyLevels = (side, levels) => levels.forEach((level) => {
+        const [price, size] = toLevel(level);
+        if (!Number.isFinite(price)) return;
+        if (!size) side.delete(price);
+        else side.set(price, size);
+    });
+
+    // 'applied' | 'buffered' | 'stale' (already in the snapshot) | 'gap'
+    const applyUpdate = (update) => {
+        if (!synced) {
+            buffered.push(update);
+            if (buffered.length > MAX_BUFFERED_UPDATES) buffered.shift();
+            return 'buffered';
+        }
+        if (sequence != null && update.sequence != null) {
+            if (update.sequence <= sequence) return 'stale';
+            if (update.firstSequence != null && update.firstSequence > sequence + 1) {
+                synced = false;
+                buffered = [];
+                return 'gap';
+            }
+        }
+        applyLevels(bids, update.bids || []);
+        applyLevels(asks, update.asks || []);
+        if (update.sequence != null) sequence = update.sequence;
+        updatedAt = update.timestamp || Date.now();
+        return 'applied';
+    };
+
+    const applySnapshot = (snapshot) => {
+        bids.clear();
+        asks.clear();
+        applyLevels(bids, snapshot.bids || []);
+        applyLevels(asks, snapshot.asks || []);
+        sequence = snapshot.sequence != null ? snapshot.sequence : null;
+        synced = true;
+        updatedAt = snapshot.timestamp || Date.now();
+        const pending = buffered;
+        buffered = [];
+        // A gap while replaying means the snapshot is older than the buffer start
+        for (const update of pending) if (applyUpdate(update) === 'gap') return 'gap';
+        return 'applied';
+    };
+
+    const apply = (event) => (event.type === 'snapshot' ? applySnapshot(event) : applyUpdate(event));
+
+    const sorted = (side, descending, levels) => Array.from(side.entries())
+        .sort((a, b) => (descending ? b[0] - a[0] : a[0] - b[0]))
+        .slice(0, levels);
+
+    /**
+     * Top `levels` per side with derived metrics: spread (absolute and in bps
+     * of mid), mid, and imbalance of the shown size ((bid - ask) / total, -1..1).
+     */
+    const snapshot = (levels = DEFAULT_LEVELS) => {
+        const topBids = sorted(bids, true, levels);
+        const topAsks = sorted(asks, false, levels);
+        const bestBid = topBids.length ? topBids[0][0] : null;
+        const bestAsk = topAsks.length ? topAsks[0][0] : null;
+        const mid = bestBid != null && bestAsk != null ? (bestBid + bestAsk) / 2 : null;
+        const spread = mid != null ? bestAsk - bestBid : null;
+        const bidSize = topBids.reduce((sum, [, size]) => sum + size, 0);
+        const askSize = topAsks.reduce((sum, [, size]) => sum + size, 0);
+        return {
+            bids: topBids,
+            asks: topAsks,
+            bestBid,
+            bestAsk,
+            mid,
+            spread,
+            spreadBps: spread != null && mid ? (spread / mid) * 10000 : null,
+            bidSize,
+            askSize,
+            imbalance: bidSize + askSize > 0 ? (bidSize - askSize) / (bidSize + askSize) : null,
+            levelCount: { bids: bids.size, asks: asks.size },
+            synced,
+            sequence,
+            updatedAt
+        };
+    };
+
+    return { apply, snapshot, isSynced: () => synced };
+};
+
+/**
+ * onSnapshot(snapshot) gets a throttled snapshot per watched book; onGap
+ * (provider, symbol) should request a fresh snapshot from the provider.
+ */
+const createDepthService = ({ onSnapshot = () => {}, onGap = () => {} }) => {
+    const books = new Map(); // `${provider}:${symbol}` -> { book, provider, symbol, levels, throttleMs, timer, lastSentAt, watchers }
+
+    const bookKey = (provider, symbol) => `${provider}:${String(symbol).toUpperCase()}`;
+
+    const emit = (entry) => {
+        entry.timer = null;
+        entry.lastSentAt = Date.now();
+        onSnapshot({ provider: entry.provider, symbol: entry.symbol, ...entry.book.snapshot(entry.levels) });
+    };
+
+    const schedule = (entry) => {
+        if (entry.timer) return;
+        const wait = Math.max(0, entry.lastSentAt + entry.throttleMs - Date.now());
+        entry.timer = setTimeout(() => emit(entry), wait);
+    };
+
+    // Liveness of the feed is the provider's job; this only keeps the books
+    const handleDepth = (event) => {
+        const entry = books.get(bookKey(event.provider, event.symbol));
+        if (!entry) return;
+        const result = entry.book.apply(event);
+        if (result === 'gap') onGap(entry.provider, entry.symbol);
+        if (result !== 'stale' && result !== 'buffered') schedule(entry);
+    };
+
+    // options: { levels?, throttleMs? }; several watchers share one book (largest levels, fastest throttle)
+    const watch = (provider, symbol, { levels = DEFAULT_LEVELS, throttleMs = DEFAULT_THROTTLE_MS } = {}) => {
+        const key = bookKey(provider, symbol);
+        let entry = books.get(key);
+        const created = !entry;
+        if (!entry) {
+            entry = { book: createOrderBook(), provider, symbol: String(symbol).toUpperCase(), levels, throttleMs: Math.max(MIN_THROTTLE_MS, throttleMs), timer: null, lastSentAt: 0, watchers: 0 };
+            books.set(key, entry);
+        } else {
+            entry.levels = Math.max(entry.levels, levels);
+            entry.throttleMs = Math.max(MIN_THROTTLE_MS, Math.min(entry.throttleMs, throttleMs));
+        }
+        entry.watchers++;
+        return { created, watchers: entry.watchers };
+    };
+
+    // true when the last watcher left and the book was dropped
+    const unwatch = (provider, symbol) => {
+        const key = bookKey(provider, symbol);
+        const entry = books.get(key);
+        if (!entry) return false;
+        entry.watchers--;
+        if (entry.watchers > 0) return false;
+        if (entry.timer) clearTimeout(entry.timer);
+        books.delete(key);
+        return true;
+    };
+
+    const getSnapshot = (provider, symbol, levels = null) => {
+        const entry = books.get(bookKey(provider, symbol));
+        return entry ? { provider: entry.provider, symbol: entry.symbol, ...entry.book.snapshot(levels || entry.levels) } : null;
+    };
+
+    const list = () => Array.from(books.values()).map(e => ({ provider: e.provider, symbol: e.symbol, levels: e.levels, throttleMs: e.throttleMs, watchers: e.watchers, synced: e.book.isSynced() }));
+
+    return { handleDepth, watch, unwatch, getSnapshot, list };
+};
+
+module.exports = { createOrderBook, createDepthService };
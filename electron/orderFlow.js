@@ -0,0 +1,198 @@
+
+const { TIMEFRAME_MS } = require('./datasets');
+
+// --- TIME & SALES / ORDER FLOW ---
+// Trade prints from the live feed are recorded per symbol into `trade_prints`
+// with the aggressor side inferred at arrival: at or above the ask is a buy,
+// at or below the bid a sell, and inside the spread (or with no quote) the
+// tick rule decides (uptick buy, downtick sell, unchanged keeps the last
+// side). Delta bars, cumulative delta and footprint matrices are computed
+// from the stored prints on request, so any timeframe or tick size works
+// after the fact.
+
+const FLUSH_INTERVAL_MS = 1000;
+const MAX_BUFFERED = 50000;
+const PRUNE_INTERVAL_MS = 3600 * 1000;
+const MAX_PRINTS_READ = 2000000;
+const MAX_FOOTPRINT_LEVELS = 2000;
+const ORDER_FLOW_DEFAULTS = { enabled: false, symbols: [], retentionDays: 7 };
+
+const BUY = 1;
+const SELL = -1;
+
+const initializeTradePrintTable = (db) => {
+    db.exec(`
+        CREATE TABLE IF NOT EXISTS trade_prints (
+            provider TEXT,
+            symbol TEXT,
+            timestamp INTEGER,
+            price REAL,
+            size REAL,
+            side INTEGER
+        );
+        CREATE INDEX IF NOT EXISTS idx_trade_prints_symbol_time ON trade_prints(symbol, timestamp);
+    `);
+};
+
+/**
+ * Aggressor side from the prevailing quote, falling back to the tick rule.
+ * state: { lastPrice, lastSide } per symbol, updated in place.
+ */
+const inferAggressor = (price, quote, state) => {
+    let side = 0;
+    if (quote && quote.ask != null && price >= quote.ask) side = BUY;
+    else if (quote && quote.bid != null && price <= quote.bid) side = SELL;
+    else if (state.lastPrice != null && price > state.lastPrice) side = BUY;
+    else if (state.lastPrice != null && price < state.lastPrice) side = SELL;
+    else side = state.lastSide || 0;
+    state.lastPrice = price;
+    state.lastSide = side;
+    return side;
+};
+
+/**
+ * getDb() -> database or null; getConfig() -> { enabled, symbols, retentionDays };
+ * getQuote(provider, symbol) -> { bid, ask } | null (latest quote or top of book).
+ */
+const createTradeRecorder = ({ getDb, getConfig = () => ORDER_FLOW_DEFAULTS, getQuote = () => null, onError = () => {} }) => {
+    const buffer = [];
+    const tickState = new Map(); // `${provider}:${symbol}` -> { lastPrice, lastSide }
+    const counts = new Map(); // symbol -> prints recorded this session
+    let dropped = 0;
+    let lastFlushAt = null;
+    let lastPruneAt = 0;
+
+    const handleTrade = ({ provider, symbol, price, size, timestamp }) => {
+        const config = { ...ORDER_FLOW_DEFAULTS, ...(getConfig() || {}) };
+        if (!config.enabled || price == null || !Number.isFinite(Number(price))) return;
+        if (config.symbols.length && !config.symbols.includes(symbol)) return;
+        const key = `${provider}:${symbol}`;
+        if (!tickState.has(key)) tickState.set(key, { lastPrice: null, lastSide: 0 });
+        const side = inferAggressor(Number(price), getQuote(provider, symbol), tickState.get(key));
+        if (buffer.length >= MAX_BUFFERED) {
+            buffer.shift();
+            dropped++;
+        }
+        buffer.push([provider, symbol, timestamp || Date.now(), Number(price), Number(size) || 0, side]);
+    };
+
+    const prune = (db, retentionDays) => {
+        if (Date.now() - lastPruneAt < PRUNE_INTERVAL_MS) return;
+        lastPruneAt = Date.now();
+        db.prepare('DELETE FROM trade_prints WHERE timestamp < ?').run(Date.now() - retentionDays * 86400000);
+    };
+
+    const flush = () => {
+        const db = getDb();
+        if (!db || !buffer.length) return 0;
+        const rows = buffer.splice(0, buffer.length);
+        try {
+            const insert = db.prepare('INSERT INTO trade_prints (provider, symbol, timestamp, price, size, side) VALUES (?, ?, ?, ?, ?, ?)');
+            db.transaction(() => rows.forEach(row => insert.run(...row)))();
+            rows.forEach(row => counts.set(row[1], (counts.get(row[1]) || 0) + 1));
+            prune(db, { ...ORDER_FLOW_DEFAULTS, ...(getConfig() || {}) }.retentionDays);
+        } catch (err) {
+            onError(err);
+        }
+        lastFlushAt = Date.now();
+        return rows.length;
+    };
+
+    const timer = setInterval(flush, FLUSH_INTERVAL_MS);
+
+    const status = () => ({
+        symbols: Object.fromEntries(counts),
+        buffered: buffer.length,
+        dropped,
+        lastFlushAt
+    });
+
+    const stop = () => { clearInterval(timer); flush(); };
+
+    return { handleTrade, flush, status, stop };
+};
+
+// Newest `limit` prints in [from, to], oldest first
+const readPrints = (db, symbol, { from = 0, to = Date.now(), limit = 1000 } = {}) => db.prepare(`
+    SELECT * FROM (
+        SELECT provider, timestamp, price, size, side FROM trade_prints
+        WHERE symbol = ? AND timestamp BETWEEN ? AND ? ORDER BY timestamp DESC LIMIT ?
+    ) ORDER BY timestamp ASC
+`).all(symbol, from, to, Math.min(limit, MAX_PRINTS_READ));
+
+const bucketMs = (timeframe) => {
+    const ms = TIMEFRAME_MS[timeframe];
+    if (!ms) throw new Error(`Unsupported timeframe: ${timeframe}`);
+    return ms;
+};
+
+const scanPrints = (db, symbol, from, to, onPrint) => {
+    const stmt = db.prepare('SELECT timestamp, price, size, side FROM trade_prints WHERE symbol = ? AND timestamp BETWEEN ? AND ? ORDER BY timestamp ASC LIMIT ?');
+    for (const print of stmt.iterate(symbol, from, to, MAX_PRINTS_READ)) onPrint(print);
+};
+
+/**
+ * Per bar: { timestamp, volume, buyVolume, sellVolume, delta, cumulativeDelta,
+ * trades, maxDelta, minDelta } (max/min = intrabar extremes of the running delta).
+ */
+const computeDeltaBars = (db, symbol, timeframe, { from = 0, to = Date.now() } = {}) => {
+    const size = bucketMs(timeframe);
+    const bars = [];
+    let current = null;
+    let cumulative = 0;
+    scanPrints(db, symbol, from, to, (p) => {
+        const bucket = Math.floor(p.timestamp / size) * size;
+        if (!current || current.timestamp !== bucket) {
+            current = { timestamp: bucket, volume: 0, buyVolume: 0, sellVolume: 0, delta: 0, cumulativeDelta: cumulative, trades: 0, maxDelta: 0, minDelta: 0 };
+            bars.push(current);
+        }
+        current.volume += p.size;
+        current.trades++;
+        if (p.side === BUY) current.buyVolume += p.size;
+        else if (p.side === SELL) current.sellVolume += p.size;
+        current.delta = current.buyVolume - current.sellVolume;
+        current.maxDelta = Math.max(current.maxDelta, current.delta);
+        current.minDelta = Math.min(current.minDelta, current.delta);
+        cumulative += p.side * p.size;
+        current.cumulativeDelta = cumulative;
+    });
+    return bars;
+};
+
+/**
+ * Per bar: { timestamp, open, high, low, close, volume, delta, poc, levels }
+ * with levels [price, sellVolume, buyVolume] from high to low, prices
+ * rounded to tickSize (the point of control is the level with most volume).
+ */
+const computeFootprint = (db, symbol, timeframe, { from = 0, to = Date.now(), tickSize } = {}) => {
+    if (!(tickSize > 0)) throw new Error('tickSize must be positive');
+    const size = bucketMs(timeframe);
+    const decimals = Math.max(0, Math.min(10, Math.ceil(-Math.log10(tickSize)) + 1));
+    const bars = [];
+    let current = null;
+    scanPrints(db, symbol, from, to, (p) => {
+        const bucket = Math.floor(p.timestamp / size) * size;
+        if (!current || current.timestamp !== bucket) {
+            current = { timestamp: bucket, open: p.price, high: p.price, low: p.price, close: p.price, volume: 0, delta: 0, cells: new Map() };
+            bars.push(current);
+        }
+        current.high = Math.max(current.high, p.price);
+        current.low = Math.min(current.low, p.price);
+        current.close = p.price;
+        current.volume += p.size;
+        current.delta += p.side * p.size;
+        const level = Number((Math.round(p.price / tickSize) * tickSize).toFixed(decimals));
+        if (!current.cells.has(level) && current.cells.size >= MAX_FOOTPRINT_LEVELS) return;
+        const cell = current.cells.get(level) || [0, 0];
+        if (p.side === SELL) cell[0] += p.size;
+        else if (p.side === BUY) cell[1] += p.size;
+        current.cells.set(level, cell);
+    });
+    return bars.map(({ cells, ...bar }) => {
+        const levels = Array.from(cells.entries()).sort((a, b) => b[0] - a[0]).map(([price, [sell, buy]]) => [price, sell, buy]);
+        const poc = levels.reduce((best, l) => (!best || l[1] + l[2] > best[1] + best[2] ? l : best), null);
+        return { ...bar, poc: poc ? poc[0] : null, levels };
+    });
+};
+
+module.exports = { ORDER_FLOW_DEFAULTS, initializeTradePrintTable, inferAggressor, createTradeRecorder, readPrints, computeDeltaBars, computeFootprint };
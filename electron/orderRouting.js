@@ -0,0 +1,160 @@
+
+const crypto = require('crypto');
+
+// --- ORDER ROUTING ---
+// Live order management on top of the broker registry (brokers/). Orders are
+// validated here, then every placement and modification must pass `confirm`,
+// which main.js implements as a native dialog: the renderer can ask for an
+// order but has no way to approve one, so a script, shortcut or stray click in
+// the UI never reaches a broker on its own. Cancels are not gated: they only
+// ever reduce exposure. Broker status updates are kept in a per-broker order
+// book and forwarded through onUpdate.
+
+const SIDES = ['buy', 'sell'];
+const TYPES = ['market', 'limit', 'stop', 'stop_limit'];
+const TIME_IN_FORCE = ['day', 'gtc', 'ioc', 'fok'];
+const MAX_TRACKED = 500;
+
+const positive = (value, name) => {
+    const n = Number(value);
+    if (!Number.isFinite(n) || n <= 0) throw new Error(`${name} must be a positive number`);
+    return n;
+};
+
+/**
+ * input: { symbol, side, type, qty, limitPrice?, stopPrice?, timeInForce?, extendedHours? }
+ */
+const normalizeOrder = (input = {}) => {
+    const symbol = String(input.symbol || '').trim().toUpperCase();
+    if (!symbol) throw new Error('symbol is required');
+    const side = String(input.side || '').toLowerCase();
+    if (!SIDES.includes(side)) throw new Error(`Invalid side: ${input.side}`);
+    const type = String(input.type || 'market').toLowerCase();
+    if (!TYPES.includes(type)) throw new Error(`Invalid order type: ${input.type}`);
+    const timeInForce = String(input.timeInForce || 'day').toLowerCase();
+    if (!TIME_IN_FORCE.includes(timeInForce)) throw new Error(`Invalid time in force: ${input.timeInForce}`);
+    const order = { symbol, side, type, qty: positive(input.qty, 'qty'), timeInForce, limitPrice: null, stopPrice: null, extendedHours: !!input.extendedHours };
+    if (type === 'limit' || type === 'stop_limit') order.limitPrice = positive(input.limitPrice, 'limitPrice');
+    if (type === 'stop' || type === 'stop_limit') order.stopPrice = positive(input.stopPrice, 'stopPrice');
+    return order;
+};
+
+const normalizeChanges = (input = {}) => {
+    const changes = {};
+    if (input.qty != null) changes.qty = positive(input.qty, 'qty');
+    if (input.limitPrice != null) changes.limitPrice = positive(input.limitPrice, 'limitPrice');
+    if (input.stopPrice != null) changes.stopPrice = positive(input.stopPrice, 'stopPrice');
+    if (input.timeInForce != null) {
+        changes.timeInForce = String(input.timeInForce).toLowerCase();
+        if (!TIME_IN_FORCE.includes(changes.timeInForce)) throw new Error(`Invalid time in force: ${input.timeInForce}`);
+    }
+    if (Object.keys(changes).length === 0) throw new Error('Nothing to modify');
+    return changes;
+};
+
+const priceText = (order) => {
+    if (order.type === 'stop_limit') return `stop ${order.stopPrice} limit ${order.limitPrice}`;
+    if (order.type === 'limit') return `@ ${order.limitPrice}`;
+    if (order.type === 'stop') return `@ ${order.stopPrice}`;
+    return null;
+};
+
+// "BUY 10 AAPL LIMIT @ 190 DAY" — what the confirmation dialog shows
+const describeOrder = (order) => [
+    order.side.toUpperCase(),
+    order.qty,
+    order.symbol,
+    order.type.replace('_', ' ').toUpperCase(),
+    priceText(order),
+    order.timeInForce.toUpperCase()
+].filter(part => part !== null).join(' ');
+
+const notConfirmed = () => {
+    const err = new Error('Order was not confirmed');
+    err.code = 'ORDER_NOT_CONFIRMED';
+    return err;
+};
+
+/**
+ * getBroker(id) -> broker (see brokers/index.js)
+ * confirm({ action: 'place' | 'modify', broker, order, summary, context }) -> Promise<boolean>
+ * onUpdate({ broker, event, order }) for placements, changes and stream updates.
+ */
+const createOrderManager = ({ getBroker, confirm, onUpdate = () => {}, onLog = () => {} }) => {
+    if (typeof confirm !== 'function') throw new Error('Order routing requires a confirmation gate');
+    const books = new Map(); // broker id -> Map(order id -> order)
+
+    const track = (brokerId, event, order) => {
+        if (!books.has(brokerId)) books.set(brokerId, new Map());
+        const book = books.get(brokerId);
+        book.delete(order.id);
+        book.set(order.id, { ...order, broker: brokerId });
+        if (book.size > MAX_TRACKED) book.delete(book.keys().next().value);
+        const tracked = book.get(order.id);
+        onUpdate({ broker: brokerId, event, order: tracked });
+        return tracked;
+    };
+
+    const gate = async (action, broker, order, summary, context) => {
+        const status = broker.getStatus();
+        const approved = await confirm({ action, broker: { id: broker.id, name: broker.name, mode: status.mode }, order, summary, context });
+        if (approved !== true) {
+            onLog({ level: 'INFO', message: `${broker.name} ${action} declined: ${summary}` });
+            throw notConfirmed();
+        }
+    };
+
+    /**
+     * context is passed through to `confirm` (e.g. the requesting window).
+     */
+    const place = async (brokerId, input, context = null) => {
+        const broker = getBroker(brokerId);
+        const order = { ...normalizeOrder(input), clientOrderId: crypto.randomUUID() };
+        const summary = describeOrder(order);
+        await gate('place', broker, order, summary, context);
+        onLog({ level: 'INFO', message: `${broker.name} (${broker.getStatus().mode}) place: ${summary}` });
+        return track(brokerId, 'submitted', await broker.placeOrder(order));
+    };
+
+    const modify = async (brokerId, id, input, context = null) => {
+        const broker = getBroker(brokerId);
+        const changes = normalizeChanges(input);
+        const current = books.has(brokerId) ? books.get(brokerId).get(id) : null;
+        const preview = current ? { ...current, ...changes } : null;
+        const summary = preview
+            ? describeOrder(preview)
+            : `order ${id}: ${Object.entries(changes).map(([k, v]) => `${k} ${v}`).join(', ')}`;
+        await gate('modify', broker, { id, ...changes }, summary, context);
+        onLog({ level: 'INFO', message: `${broker.name} (${broker.getStatus().mode}) modify ${id}: ${summary}` });
+        const replacement = await broker.modifyOrder(id, changes);
+        if (current && replacement.id !== id) track(brokerId, 'replaced', { ...current, status: 'replaced', replacedBy: replacement.id });
+        return track(brokerId, 'modified', replacement);
+    };
+
+    const cancel = async (brokerId, id) => {
+        const broker = getBroker(brokerId);
+        await broker.cancelOrder(id);
+        onLog({ level: 'INFO', message: `${broker.name} (${broker.getStatus().mode}) cancel requested: ${id}` });
+        const current = books.has(brokerId) ? books.get(brokerId).get(id) : null;
+        return current ? track(brokerId, 'pending_cancel', { ...current, status: 'pending_cancel', updatedAt: Date.now() }) : null;
+    };
+
+    // Refreshes the book from the broker, so orders placed elsewhere show up too
+    const list = async (brokerId, options = {}) => {
+        const orders = await getBroker(brokerId).listOrders(options);
+        const book = new Map();
+        orders.slice().reverse().forEach(order => book.set(order.id, { ...order, broker: brokerId }));
+        books.set(brokerId, book);
+        return orders.map(order => book.get(order.id));
+    };
+
+    const startUpdates = (brokerId) => getBroker(brokerId).startUpdates(({ event, order }) => track(brokerId, event, order));
+
+    const stopUpdates = (brokerId) => getBroker(brokerId).stopUpdates();
+
+    const tracked = (brokerId) => Array.from((books.get(brokerId) || new Map()).values()).reverse();
+
+    return { place, modify, cancel, list, tracked, startUpdates, stopUpdates };
+};
+
+module.exports = { normalizeOrder, describeOrder, createOrderManager };
@@ -0,0 +1,416 @@
+
+const crypto = require('crypto');
+
+// --- PAPER TRADING SIMULATOR ---
+// Simulated execution against live ticks (liveFeed) or replayed prices pushed
+// by the chart's playback. Orders carry the price source they trade against,
+// so a replay session never fills on live quotes and vice versa. Time is the
+// tick's own timestamp: latency delays a fill by that much market time, which
+// keeps replay at 10x speed consistent with real time.
+//
+// Fill rules: market orders fill at the first eligible tick with adverse
+// slippage; limits fill at the limit (or better, if the tick gapped through);
+// stops trigger on touch and then fill like market orders. Positions are
+// signed (negative = short), average-price based; P&L is realized on any fill
+// that reduces a position. Cash, P&L and equity scale by the symbol's point
+// value (positionSizing.js), so a futures contract is worth its multiplier.
+// Everything is persisted, so working orders and account state survive restarts.
+
+const DEFAULT_ACCOUNT_ID = 'default';
+const DEFAULT_SETTINGS = {
+    slippageBps: 2,        // adverse slippage on market / stop fills
+    latencyMs: 250,        // market-time delay before an order can fill
+    commissionPerUnit: 0,
+    commissionPct: 0,      // of notional
+    minCommission: 0
+};
+const ORDER_TYPES = ['market', 'limit', 'stop'];
+const SOURCES = ['live', 'replay'];
+
+const initializePaperTradingTables = (db) => {
+    db.exec(`
+        CREATE TABLE IF NOT EXISTS sim_accounts (
+            id TEXT PRIMARY KEY,
+            name TEXT,
+            currency TEXT,
+            starting_cash REAL,
+            cash REAL,
+            settings TEXT,
+            created_at INTEGER
+        );
+        CREATE TABLE IF NOT EXISTS sim_orders (
+            id TEXT PRIMARY KEY,
+            account_id TEXT,
+            symbol TEXT,
+            side TEXT,
+            type TEXT,
+            qty REAL,
+            limit_price REAL,
+            stop_price REAL,
+            source TEXT,
+            status TEXT,
+            tag TEXT,
+            created_at INTEGER,
+            active_at INTEGER,
+            filled_at INTEGER,
+            fill_price REAL,
+            reason TEXT
+        );
+        CREATE INDEX IF NOT EXISTS idx_sim_orders_working ON sim_orders (status, symbol);
+        CREATE TABLE IF NOT EXISTS sim_positions (
+            account_id TEXT,
+            symbol TEXT,
+            qty REAL,
+            avg_price REAL,
+            realized_pnl REAL,
+            updated_at INTEGER,
+            PRIMARY KEY (account_id, symbol)
+        );
+        CREATE TABLE IF NOT EXISTS sim_fills (
+            id TEXT PRIMARY KEY,
+            order_id TEXT,
+            account_id TEXT,
+            symbol TEXT,
+            side TEXT,
+            qty REAL,
+            price REAL,
+            commission REAL,
+            realized_pnl REAL,
+            source TEXT,
+            tag TEXT,
+            timestamp INTEGER
+        );
+        CREATE INDEX IF NOT EXISTS idx_sim_fills_account ON sim_fills (account_id, timestamp);
+    `);
+};
+
+const mapAccountRow = (row) => ({
+    id: row.id,
+    name: row.name,
+    currency: row.currency,
+    startingCash: row.starting_cash,
+    cash: row.cash,
+    settings: { ...DEFAULT_SETTINGS, ...JSON.parse(row.settings || '{}') },
+    createdAt: row.created_at
+});
+
+const mapOrderRow = (row) => ({
+    id: row.id,
+    accountId: row.account_id,
+    symbol: row.symbol,
+    side: row.side,
+    type: row.type,
+    qty: row.qty,
+    limitPrice: row.limit_price,
+    stopPrice: row.stop_price,
+    source: row.source,
+    status: row.status,
+    tag: row.tag,
+    createdAt: row.created_at,
+    activeAt: row.active_at,
+    filledAt: row.filled_at,
+    fillPrice: row.fill_price,
+    reason: row.reason
+});
+
+const mapFillRow = (row) => ({
+    id: row.id,
+    orderId: row.order_id,
+    accountId: row.account_id,
+    symbol: row.symbol,
+    side: row.side,
+    qty: row.qty,
+    price: row.price,
+    commission: row.commission,
+    realizedPnl: row.realized_pnl,
+    source: row.source,
+    tag: row.tag,
+    timestamp: row.timestamp
+});
+
+/**
+ * Applies a fill to a signed position. Returns the new { qty, avgPrice } and
+ * the P&L realized by the part of the fill that closed existing exposure.
+ */
+const applyFill = (position, side, qty, price, pointValue = 1) => {
+    const signed = side === 'buy' ? qty : -qty;
+    const current = position ? position.qty : 0;
+    const avg = position ? position.avgPrice : 0;
+    if (current === 0 || Math.sign(current) === Math.sign(signed)) {
+        const nextQty = current + signed;
+        return { qty: nextQty, avgPrice: (avg * Math.abs(current) + price * qty) / Math.abs(nextQty), realized: 0 };
+    }
+    const closing = Math.min(Math.abs(current), qty);
+    const realized = (price - avg) * closing * Math.sign(current) * pointValue;
+    const nextQty = current + signed;
+    // Flipped through zero: the remainder opens at the fill price
+    const avgPrice = nextQty === 0 ? 0 : Math.sign(nextQty) === Math.sign(current) ? avg : price;
+    return { qty: nextQty, avgPrice, realized };
+};
+
+/**
+ * onFill({ fill, order, position, account }) and onOrder(order) report changes.
+ * getPointValue(symbol) -> money per unit of price movement (1 for stocks).
+ */
+const createSimEngine = ({ db, onFill = () => {}, onOrder = () => {}, getPointValue = () => 1 }) => {
+    const lastPrices = new Map(); // `${source}|${symbol}` -> { price, timestamp }
+
+    const getAccount = (id = DEFAULT_ACCOUNT_ID) => {
+        const row = db.prepare('SELECT * FROM sim_accounts WHERE id = ?').get(id);
+        if (!row) throw new Error(`Simulated account not found: ${id}`);
+        return mapAccountRow(row);
+    };
+
+    const ensureDefaultAccount = () => {
+        db.prepare('INSERT OR IGNORE INTO sim_accounts (id, name, currency, starting_cash, cash, settings, created_at) VALUES (?, ?, ?, ?, ?, ?, ?)')
+            .run(DEFAULT_ACCOUNT_ID, 'Paper account', 'USD', 100000, 100000, '{}', Date.now());
+    };
+
+    const createAccount = ({ name = 'Paper account', currency = 'USD', startingCash = 100000, settings = {} } = {}) => {
+        const id = crypto.randomUUID();
+        db.prepare('INSERT INTO sim_accounts (id, name, currency, starting_cash, cash, settings, created_at) VALUES (?, ?, ?, ?, ?, ?, ?)')
+            .run(id, name, currency, startingCash, startingCash, JSON.stringify(settings), Date.now());
+        return getAccount(id);
+    };
+
+    const updateSettings = (id, updates = {}) => {
+        const account = getAccount(id);
+        const next = { ...account.settings };
+        Object.keys(DEFAULT_SETTINGS).forEach((key) => {
+            if (updates[key] === undefined) return;
+            const value = Number(updates[key]);
+            if (!Number.isFinite(value) || value < 0) throw new Error(`Invalid ${key}: ${updates[key]}`);
+            next[key] = value;
+        });
+        db.prepare('UPDATE sim_accounts SET settings = ? WHERE id = ?').run(JSON.stringify(next), id);
+        return getAccount(id);
+    };
+
+    // Cancels working orders and clears positions and fills
+    const resetAccount = (id = DEFAULT_ACCOUNT_ID, startingCash = null) => {
+        const account = getAccount(id);
+        const cash = startingCash != null ? Number(startingCash) : account.startingCash;
+        db.transaction(() => {
+            db.prepare('DELETE FROM sim_orders WHERE account_id = ?').run(id);
+            db.prepare('DELETE FROM sim_positions WHERE account_id = ?').run(id);
+            db.prepare('DELETE FROM sim_fills WHERE account_id = ?').run(id);
+            db.prepare('UPDATE sim_accounts SET starting_cash = ?, cash = ? WHERE id = ?').run(cash, cash, id);
+        })();
+        return getAccount(id);
+    };
+
+    const getPosition = (accountId, symbol) => {
+        const row = db.prepare('SELECT * FROM sim_positions WHERE account_id = ? AND symbol = ?').get(accountId, symbol);
+        return row ? { qty: row.qty, avgPrice: row.avg_price, realizedPnl: row.realized_pnl } : null;
+    };
+
+    const markPrice = (symbol) => {
+        const live = lastPrices.get(`live|${symbol}`);
+        const replay = lastPrices.get(`replay|${symbol}`);
+        const latest = [live, replay].filter(Boolean).sort((a, b) => b.seenAt - a.seenAt)[0];
+        return latest ? latest.price : null;
+    };
+
+    const listPositions = (accountId = DEFAULT_ACCOUNT_ID) => db.prepare('SELECT * FROM sim_positions WHERE account_id = ? ORDER BY symbol').all(accountId).map((row) => {
+        const mark = markPrice(row.symbol);
+        return {
+            symbol: row.symbol,
+            qty: row.qty,
+            avgPrice: row.avg_price,
+            realizedPnl: row.realized_pnl,
+            markPrice: mark,
+            unrealizedPnl: mark != null && row.qty !== 0 ? (mark - row.avg_price) * row.qty * getPointValue(row.symbol) : 0,
+            updatedAt: row.updated_at
+        };
+    });
+
+    const getAccountSummary = (id = DEFAULT_ACCOUNT_ID) => {
+        const account = getAccount(id);
+        const positions = listPositions(id);
+        const marketValue = positions.reduce((sum, p) => sum + (p.markPrice != null ? p.markPrice : p.avgPrice) * p.qty * getPointValue(p.symbol), 0);
+        return {
+            ...account,
+            positions,
+            realizedPnl: positions.reduce((sum, p) => sum + p.realizedPnl, 0),
+            unrealizedPnl: positions.reduce((sum, p) => sum + p.unrealizedPnl, 0),
+            equity: account.cash + marketValue
+        };
+    };
+
+    const commissionFor = (settings, qty, price, pointValue) => Math.max(
+        settings.minCommission,
+        settings.commissionPerUnit * qty + settings.commissionPct / 100 * qty * price * pointValue
+    );
+
+    const fillOrder = (order, rawPrice, timestamp) => {
+        const account = getAccount(order.accountId);
+        const { settings } = account;
+        const slip = order.type === 'limit' ? 0 : rawPrice * settings.slippageBps / 10000;
+        const price = order.side === 'buy' ? rawPrice + slip : rawPrice - slip;
+        const pointValue = getPointValue(order.symbol);
+        const commission = commissionFor(settings, order.qty, price, pointValue);
+        const position = getPosition(order.accountId, order.symbol);
+        const next = applyFill(position, order.side, order.qty, price, pointValue);
+        const cashDelta = (order.side === 'buy' ? -1 : 1) * order.qty * price * pointValue - commission;
+        const fill = {
+            id: crypto.randomUUID(),
+            orderId: order.id,
+            accountId: order.accountId,
+            symbol: order.symbol,
+            side: order.side,
+            qty: order.qty,
+            price,
+            commission,
+            realizedPnl: next.realized - commission,
+            source: order.source,
+            tag: order.tag,
+            timestamp
+        };
+        db.transaction(() => {
+            db.prepare("UPDATE sim_orders SET status = 'filled', filled_at = ?, fill_price = ? WHERE id = ?").run(timestamp, price, order.id);
+            db.prepare('INSERT OR REPLACE INTO sim_positions (account_id, symbol, qty, avg_price, realized_pnl, updated_at) VALUES (?, ?, ?, ?, ?, ?)')
+                .run(order.accountId, order.symbol, next.qty, next.avgPrice, (position ? position.realizedPnl : 0) + fill.realizedPnl, timestamp);
+            db.prepare('UPDATE sim_accounts SET cash = cash + ? WHERE id = ?').run(cashDelta, order.accountId);
+            db.prepare('INSERT INTO sim_fills (id, order_id, account_id, symbol, side, qty, price, commission, realized_pnl, source, tag, timestamp) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)')
+                .run(fill.id, fill.orderId, fill.accountId, fill.symbol, fill.side, fill.qty, fill.price, fill.commission, fill.realizedPnl, fill.source, fill.tag, fill.timestamp);
+        })();
+        const filled = { ...order, status: 'filled', filledAt: timestamp, fillPrice: price };
+        onOrder(filled);
+        onFill({ fill, order: filled, position: listPositions(order.accountId).find(p => p.symbol === order.symbol), account: getAccountSummary(order.accountId) });
+        return fill;
+    };
+
+    // Price at which a working order fills on this tick, or null
+    const triggerPrice = (order, price) => {
+        if (order.type === 'market') return price;
+        if (order.type === 'limit') {
+            if (order.side === 'buy' && price <= order.limitPrice) return Math.min(price, order.limitPrice);
+            if (order.side === 'sell' && price >= order.limitPrice) return Math.max(price, order.limitPrice);
+            return null;
+        }
+        // stop: triggers on touch, fills at the tick (slippage applied in fillOrder)
+        if (order.side === 'buy' && price >= order.stopPrice) return price;
+        if (order.side === 'sell' && price <= order.stopPrice) return price;
+        return null;
+    };
+
+    const workingOrders = (symbol, source) => db.prepare("SELECT * FROM sim_orders WHERE status = 'working' AND symbol = ? AND source = ? ORDER BY created_at")
+        .all(symbol, source).map(mapOrderRow);
+
+    /**
+     * Feeds a price. source: 'live' (liveFeed) or 'replay' (chart playback).
+     * Returns the fills it caused.
+     */
+    const updatePrice = (symbol, price, timestamp = Date.now(), source = 'live') => {
+        if (!Number.isFinite(price) || price <= 0) return [];
+        lastPrices.set(`${source}|${symbol}`, { price, timestamp, seenAt: Date.now() });
+        const fills = [];
+        workingOrders(symbol, source).forEach((order) => {
+            // Orders placed "before" this tick in market time, e.g. after a replay seek backwards, wait
+            if (timestamp < order.activeAt) return;
+            const at = triggerPrice(order, price);
+            if (at != null) fills.push(fillOrder(order, at, timestamp));
+        });
+        return fills;
+    };
+
+    /**
+     * order: { accountId?, symbol, side: 'buy' | 'sell', type: 'market' | 'limit' | 'stop',
+     *          qty, limitPrice?, stopPrice?, source?: 'live' | 'replay', tag? }
+     */
+    const placeOrder = (input = {}) => {
+        const accountId = input.accountId || DEFAULT_ACCOUNT_ID;
+        const account = getAccount(accountId);
+        const symbol = String(input.symbol || '').trim();
+        const side = input.side;
+        const type = input.type || 'market';
+        const source = input.source || 'live';
+        const qty = Number(input.qty);
+        if (!symbol) throw new Error('symbol is required');
+        if (side !== 'buy' && side !== 'sell') throw new Error(`Invalid side: ${side}`);
+        if (!ORDER_TYPES.includes(type)) throw new Error(`Invalid order type: ${type}`);
+        if (!SOURCES.includes(source)) throw new Error(`Invalid price source: ${source}`);
+        if (!Number.isFinite(qty) || qty <= 0) throw new Error('qty must be a positive number');
+        const limitPrice = type === 'limit' ? Number(input.limitPrice) : null;
+        const stopPrice = type === 'stop' ? Number(input.stopPrice) : null;
+        if (type === 'limit' && !(limitPrice > 0)) throw new Error('limitPrice is required for limit orders');
+        if (type === 'stop' && !(stopPrice > 0)) throw new Error('stopPrice is required for stop orders');
+
+        const last = lastPrices.get(`${source}|${symbol}`);
+        const marketNow = last ? last.timestamp : Date.now();
+        const order = {
+            id: crypto.randomUUID(),
+            accountId,
+            symbol,
+            side,
+            type,
+            qty,
+            limitPrice,
+            stopPrice,
+            source,
+            status: 'working',
+            tag: input.tag || null,
+            createdAt: Date.now(),
+            activeAt: marketNow + account.settings.latencyMs,
+            filledAt: null,
+            fillPrice: null,
+            reason: null
+        };
+        db.prepare(`INSERT INTO sim_orders (id, account_id, symbol, side, type, qty, limit_price, stop_price, source, status, tag, created_at, active_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)`)
+            .run(order.id, accountId, symbol, side, type, qty, limitPrice, stopPrice, source, 'working', order.tag, order.createdAt, order.activeAt);
+        onOrder(order);
+        // With no latency the current price is already eligible
+        if (last && account.settings.latencyMs === 0) {
+            const at = triggerPrice(order, last.price);
+            if (at != null) fillOrder(order, at, last.timestamp);
+        }
+        return getOrder(order.id);
+    };
+
+    const getOrder = (id) => {
+        const row = db.prepare('SELECT * FROM sim_orders WHERE id = ?').get(id);
+        return row ? mapOrderRow(row) : null;
+    };
+
+    const cancelOrder = (id, reason = 'cancelled by user') => {
+        const order = getOrder(id);
+        if (!order) throw new Error(`Order not found: ${id}`);
+        if (order.status !== 'working') return order;
+        db.prepare("UPDATE sim_orders SET status = 'cancelled', reason = ? WHERE id = ?").run(reason, id);
+        const cancelled = getOrder(id);
+        onOrder(cancelled);
+        return cancelled;
+    };
+
+    const listOrders = ({ accountId = DEFAULT_ACCOUNT_ID, status = null, limit = 200 } = {}) => (status
+        ? db.prepare('SELECT * FROM sim_orders WHERE account_id = ? AND status = ? ORDER BY created_at DESC LIMIT ?').all(accountId, status, limit)
+        : db.prepare('SELECT * FROM sim_orders WHERE account_id = ? ORDER BY created_at DESC LIMIT ?').all(accountId, limit)
+    ).map(mapOrderRow);
+
+    const listFills = ({ accountId = DEFAULT_ACCOUNT_ID, symbol = null, since = 0, limit = 500 } = {}) => (symbol
+        ? db.prepare('SELECT * FROM sim_fills WHERE account_id = ? AND symbol = ? AND timestamp >= ? ORDER BY timestamp DESC LIMIT ?').all(accountId, symbol, since, limit)
+        : db.prepare('SELECT * FROM sim_fills WHERE account_id = ? AND timestamp >= ? ORDER BY timestamp DESC LIMIT ?').all(accountId, since, limit)
+    ).map(mapFillRow);
+
+    const listAccounts = () => db.prepare('SELECT * FROM sim_accounts ORDER BY created_at').all().map(mapAccountRow);
+
+    ensureDefaultAccount();
+
+    return {
+        updatePrice,
+        placeOrder,
+        cancelOrder,
+        getOrder,
+        listOrders,
+        listFills,
+        listPositions,
+        listAccounts,
+        createAccount,
+        updateSettings,
+        resetAccount,
+        getAccountSummary
+    };
+};
+
+module.exports = { DEFAULT_SIM_SETTINGS: DEFAULT_SETTINGS, initializePaperTradingTables, createSimEngine, applyFill };
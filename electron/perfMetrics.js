@@ -0,0 +1,154 @@
+
+// --- IPC PERFORMANCE METRICS ---
+// Wraps ipcMain.handle so every command records duration, payload sizes and
+// failures. Recent calls go into a fixed-size ring buffer; per-channel totals
+// are kept separately so long sessions don't lose the big picture.
+
+const RING_SIZE = 1000;
+const SIZE_WALK_LIMIT = 20000; // nodes visited before the size estimate stops walking and extrapolates
+const SIZE_SAMPLE = 64; // arrays / objects with more entries than twice this are sampled
+
+const ring = new Array(RING_SIZE);
+let ringIndex = 0;
+let ringCount = 0;
+const totals = new Map(); // channel -> { calls, errors, totalMs, maxMs, argBytes, resultBytes }
+
+// One pick per 1/SIZE_SAMPLE stride, jittered inside the stride (fixed seed,
+// so estimates are repeatable) so regularly repeating lists don't alias
+const samplePicks = (length) => {
+    const step = length / SIZE_SAMPLE;
+    const picks = [];
+    let seed = 0x9e3779b9;
+    for (let i = 0; i < SIZE_SAMPLE; i++) {
+        seed = (Math.imul(seed, 1664525) + 1013904223) >>> 0;
+        picks.push(Math.min(length - 1, Math.floor((i + seed / 0x100000000) * step)));
+    }
+    return picks;
+};
+
+/**
+ * Rough serialized size in bytes without building the JSON string, so giant
+ * chart-state saves are measured without doubling their cost. Large arrays
+ * and objects are sampled: SIZE_SAMPLE spread-out entries are walked and
+ * weighted by length / SIZE_SAMPLE, so a 100k-drawing state costs a few
+ * thousand visits and is estimated from entries spread across the whole list.
+ * Should the walk still pass SIZE_WALK_LIMIT, what is left is extrapolated
+ * from the average size of the entries seen so far.
+ */
+const estimateSize = (value) => {
+    let bytes = 0;
+    let visited = 0;
+    let visitedWeight = 0;
+    const stack = [[value, 1]];
+    while (stack.length) {
+        const [v, weight] = stack.pop();
+        if (++visited > SIZE_WALK_LIMIT) {
+            const pending = stack.reduce((n, entry) => n + entry[1], weight);
+            return Math.round(bytes + (bytes / visitedWeight) * pending);
+        }
+        visitedWeight += weight;
+        if (v == null) bytes += 4 * weight;
+        else if (typeof v === 'string') bytes += (v.length + 2) * weight;
+        else if (typeof v === 'number') bytes += 8 * weight;
+        else if (typeof v === 'boolean') bytes += 5 * weight;
+        else if (Buffer.isBuffer(v) || ArrayBuffer.isView(v)) bytes += v.byteLength * weight;
+        else if (Array.isArray(v)) {
+            bytes += (2 + v.length) * weight;
+            if (v.length > SIZE_SAMPLE * 2) {
+                const share = weight * (v.length / SIZE_SAMPLE);
+                samplePicks(v.length).forEach(i => stack.push([v[i], share]));
+            } else {
+                for (let i = 0; i < v.length; i++) stack.push([v[i], weight]);
+            }
+        } else if (typeof v === 'object') {
+            const keys = Object.keys(v);
+            const picks = keys.length > SIZE_SAMPLE * 2 ? samplePicks(keys.length).map(i => keys[i]) : keys;
+            const share = weight * (keys.length / picks.length);
+            picks.forEach((key) => { bytes += (key.length + 4) * share; stack.push([v[key], share]); });
+        }
+    }
+    return Math.round(bytes);
+};
+
+const isErrorResult = (result) => !!result && typeof result === 'object' && (result.success === false || (typeof result.error === 'string' && !result.canceled));
+
+const record = (channel, durationMs, argBytes, resultBytes, ok, error) => {
+    ring[ringIndex] = { channel, durationMs, argBytes, resultBytes, ok, error: error || null, at: Date.now() };
+    ringIndex = (ringIndex + 1) % RING_SIZE;
+    ringCount = Math.min(ringCount + 1, RING_SIZE);
+
+    const t = totals.get(channel) || { calls: 0, errors: 0, totalMs: 0, maxMs: 0, argBytes: 0, resultBytes: 0 };
+    t.calls++;
+    if (!ok) t.errors++;
+    t.totalMs += durationMs;
+    t.maxMs = Math.max(t.maxMs, durationMs);
+    t.argBytes += argBytes;
+    t.resultBytes += resultBytes;
+    totals.set(channel, t);
+};
+
+// onCall(channel) runs on every invocation (usage counters)
+const instrumentIpc = (ipcMain, { onCall = null } = {}) => {
+    const originalHandle = ipcMain.handle.bind(ipcMain);
+    ipcMain.handle = (channel, handler) => originalHandle(channel, async (event, ...args) => {
+        if (onCall) onCall(channel);
+        const start = process.hrtime.bigint();
+        const argBytes = estimateSize(args);
+        try {
+            const result = await handler(event, ...args);
+            const durationMs = Number(process.hrtime.bigint() - start) / 1e6;
+            record(channel, durationMs, argBytes, estimateSize(result), !isErrorResult(result), isErrorResult(result) ? result.error : null);
+            return result;
+        } catch (err) {
+            record(channel, Number(process.hrtime.bigint() - start) / 1e6, argBytes, 0, false, err.message);
+            throw err;
+        }
+    });
+};
+
+const recentCalls = () => {
+    const out = [];
+    for (let i = 0; i < ringCount; i++) out.push(ring[(ringIndex - 1 - i + RING_SIZE) % RING_SIZE]);
+    return out; // newest first
+};
+
+const percentile = (sorted, p) => sorted.length ? sorted[Math.min(sorted.length - 1, Math.floor(sorted.length * p))] : 0;
+
+const round = (n) => Math.round(n * 100) / 100;
+
+/**
+ * Per-channel summary (lifetime totals + p50/p95 over the ring) and the most recent calls.
+ */
+const getPerfMetrics = ({ recent = 100 } = {}) => {
+    const calls = recentCalls();
+    const durations = new Map();
+    calls.forEach((c) => {
+        if (!durations.has(c.channel)) durations.set(c.channel, []);
+        durations.get(c.channel).push(c.durationMs);
+    });
+
+    const channels = Array.from(totals.entries()).map(([channel, t]) => {
+        const sorted = (durations.get(channel) || []).slice().sort((a, b) => a - b);
+        return {
+            channel,
+            calls: t.calls,
+            errors: t.errors,
+            avgMs: round(t.totalMs / t.calls),
+            p50Ms: round(percentile(sorted, 0.5)),
+            p95Ms: round(percentile(sorted, 0.95)),
+            maxMs: round(t.maxMs),
+            avgArgBytes: Math.round(t.argBytes / t.calls),
+            avgResultBytes: Math.round(t.resultBytes / t.calls)
+        };
+    }).sort((a, b) => b.p95Ms - a.p95Ms);
+
+    return { since: calls.length ? calls[calls.length - 1].at : null, channels, recent: calls.slice(0, recent).map(c => ({ ...c, durationMs: round(c.durationMs) })) };
+};
+
+const resetPerfMetrics = () => {
+    ringIndex = 0;
+    ringCount = 0;
+    totals.clear();
+};
+
+module.exports = { instrumentIpc, getPerfMetrics, resetPerfMetrics, estimateSize };
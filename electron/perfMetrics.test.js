@@ -0,0 +1,45 @@
+
+const test = require('node:test');
+const assert = require('node:assert');
+const { estimateSize } = require('./perfMetrics');
+const { checkCall } = require('./ipcGuards');
+
+// Mixed chart state: two-point trend lines, long brush strokes, some notes
+const chartState = (count) => ({
+    drawings: Array.from({ length: count }, (_, i) => ({
+        id: `drawing-${i}`,
+        type: i % 3 ? 'trend_line' : 'brush',
+        points: Array.from({ length: i % 3 ? 2 : (i % 50) + 5 }, (_, k) => ({ time: 1700000000000 + k * 60000, price: 100.25 + k })),
+        properties: { color: '#3B82F6', lineWidth: 2, text: i % 7 ? '' : 'note '.repeat(i % 40) }
+    }))
+});
+
+const ratio = (value) => estimateSize(value) / JSON.stringify(value).length;
+
+test('small payloads are measured within a few percent', () => {
+    const r = ratio([chartState(20)]);
+    assert.ok(r > 0.9 && r < 1.1, `ratio ${r}`);
+});
+
+test('a 100k-drawing payload is estimated within a bounded factor', () => {
+    const r = ratio(['BTCUSD', chartState(100000)]);
+    assert.ok(r > 0.67 && r < 1.5, `ratio ${r}`);
+});
+
+test('a 100k-element number array is estimated within a bounded factor', () => {
+    const bars = Array.from({ length: 100000 }, (_, i) => 20000 + i * 0.37);
+    const r = ratio([bars]);
+    assert.ok(r > 0.5 && r < 2, `ratio ${r}`);
+});
+
+test('an object with 100k keys is estimated within a bounded factor', () => {
+    const settings = {};
+    for (let i = 0; i < 100000; i++) settings[`key_${i}`] = `value_${i}`;
+    const r = ratio([settings]);
+    assert.ok(r > 0.67 && r < 1.5, `ratio ${r}`);
+});
+
+test('oversized saves trip the payload guard', () => {
+    const result = checkCall(new Map(), 1, 'drawings:save-state', ['BTCUSD', chartState(400000)]);
+    assert.ok(result && result.code === 'PAYLOAD_TOO_LARGE', JSON.stringify(result));
+});
@@ -0,0 +1,313 @@
+
+const fs = require('fs');
+const { spawn } = require('child_process');
+const { isMainThread, parentPort, workerData } = require('worker_threads');
+
+// --- PLAYBACK EXPORT ---
+// Renders a bar-by-bar replay of a dataset into an animated GIF (encoded here)
+// or an MP4 (raw frames piped to ffmpeg, which must be on PATH or configured).
+// Runs in a worker: long ranges are thousands of frames. Frames are drawn with
+// a small fixed palette straight into an index buffer — candles, grid and a
+// progress strip, no text — so every frame is cheap and GIF-friendly.
+
+const DEFAULTS = {
+    format: 'gif',
+    width: 800,
+    height: 450,
+    fps: 10,
+    windowBars: 120, // bars visible at once; the view scrolls once it fills
+    barsPerFrame: 1,
+    maxFrames: 3000,
+    loop: true
+};
+
+const MAX_DIMENSION = 1920;
+const PROGRESS_EVERY = 25; // frames between progress messages
+
+// Palette slots
+const BG = 0, GRID = 1, UP = 2, DOWN = 3, ACCENT = 4;
+
+const parseColor = (value, fallback) => {
+    const s = String(value || '').trim();
+    let m = s.match(/^#([0-9a-f]{3,8})$/i);
+    if (m) {
+        const hex = m[1].length < 6 ? m[1].slice(0, 3).split('').map(c => c + c).join('') : m[1].slice(0, 6);
+        return [0, 2, 4].map(i => parseInt(hex.slice(i, i + 2), 16));
+    }
+    m = s.match(/^rgba?\(\s*([\d.]+)\s*,\s*([\d.]+)\s*,\s*([\d.]+)/i);
+    if (m) return [m[1], m[2], m[3]].map(v => Math.min(255, Math.round(Number(v))));
+    return fallback;
+};
+
+const buildPalette = (colors = {}) => [
+    parseColor(colors.background, [15, 23, 42]),
+    parseColor(colors.grid, [51, 65, 85]),
+    parseColor(colors.up, [16, 185, 129]),
+    parseColor(colors.down, [239, 68, 68]),
+    parseColor(colors.accent, [59, 130, 246]),
+    [0, 0, 0], [0, 0, 0], [0, 0, 0] // padding to a power of two for the GIF color table
+];
+
+const resolveOptions = (options = {}) => {
+    const opts = { ...DEFAULTS, ...options };
+    if (opts.format !== 'gif' && opts.format !== 'mp4') throw new Error(`Unsupported format: ${opts.format}`);
+    opts.width = Math.max(64, Math.min(MAX_DIMENSION, Math.round(opts.width)));
+    opts.height = Math.max(64, Math.min(MAX_DIMENSION, Math.round(opts.height)));
+    // yuv420p needs even dimensions
+    if (opts.format === 'mp4') { opts.width -= opts.width % 2; opts.height -= opts.height % 2; }
+    opts.fps = Math.max(1, Math.min(60, Number(opts.fps) || DEFAULTS.fps));
+    opts.windowBars = Math.max(10, Math.round(opts.windowBars));
+    opts.barsPerFrame = Math.max(1, Math.round(opts.barsPerFrame));
+    return opts;
+};
+
+// --- Rendering ---
+
+const fillRect = (pixels, width, height, x0, y0, x1, y1, color) => {
+    const xa = Math.max(0, Math.min(x0, x1)), xb = Math.min(width - 1, Math.max(x0, x1));
+    const ya = Math.max(0, Math.min(y0, y1)), yb = Math.min(height - 1, Math.max(y0, y1));
+    for (let y = ya; y <= yb; y++) pixels.fill(color, y * width + xa, y * width + xb + 1);
+};
+
+/**
+ * Draws bars[start..end) into `pixels` (one palette index per pixel),
+ * with a progress strip at `progress` (0..1) along the bottom edge.
+ */
+const renderFrame = (pixels, bars, start, end, { width, height, windowBars }, progress) => {
+    pixels.fill(BG);
+    const chartH = height - 4;
+    const visible = bars.slice(start, end);
+    if (visible.length > 0) {
+        let lo = Infinity, hi = -Infinity;
+        visible.forEach((b) => { if (b.low < lo) lo = b.low; if (b.high > hi) hi = b.high; });
+        const pad = (hi - lo) * 0.05 || Math.abs(hi) * 0.01 || 1;
+        lo -= pad; hi += pad;
+        const toY = (price) => Math.round((1 - (price - lo) / (hi - lo)) * (chartH - 1));
+
+        for (let g = 1; g < 5; g++) fillRect(pixels, width, height, 0, Math.round(chartH * g / 5), width - 1, Math.round(chartH * g / 5), GRID);
+
+        const slot = width / windowBars;
+        const body = Math.max(1, Math.floor(slot * 0.7));
+        visible.forEach((b, i) => {
+            const color = b.close >= b.open ? UP : DOWN;
+            const center = Math.floor(i * slot + slot / 2);
+            fillRect(pixels, width, height, center, toY(b.high), center, toY(b.low), color);
+            const left = center - Math.floor(body / 2);
+            fillRect(pixels, width, height, left, toY(b.open), left + body - 1, toY(b.close), color);
+        });
+    }
+    fillRect(pixels, width, height, 0, height - 3, Math.round((width - 1) * progress), height - 1, ACCENT);
+};
+
+// --- GIF encoding ---
+
+const lzwEncode = (indices, minCodeSize) => {
+    const clearCode = 1 << minCodeSize;
+    const eoiCode = clearCode + 1;
+    const out = [];
+    let codeSize = minCodeSize + 1;
+    let nextCode = eoiCode + 1;
+    let table = new Map();
+    let bitBuf = 0, bitCount = 0;
+
+    const emit = (code) => {
+        bitBuf |= code << bitCount;
+        bitCount += codeSize;
+        while (bitCount >= 8) {
+            out.push(bitBuf & 0xff);
+            bitBuf >>>= 8;
+            bitCount -= 8;
+        }
+    };
+
+    emit(clearCode);
+    let current = indices[0];
+    for (let i = 1; i < indices.length; i++) {
+        const k = indices[i];
+        const key = current * 256 + k;
+        const found = table.get(key);
+        if (found !== undefined) {
+            current = found;
+            continue;
+        }
+        emit(current);
+        if (nextCode === 4096) {
+            emit(clearCode);
+            codeSize = minCodeSize + 1;
+            nextCode = eoiCode + 1;
+            table = new Map();
+        } else {
+            if (nextCode >= (1 << codeSize)) codeSize++;
+            table.set(key, nextCode++);
+        }
+        current = k;
+    }
+    emit(current);
+    emit(eoiCode);
+    if (bitCount > 0) out.push(bitBuf & 0xff);
+
+    // Data sub-blocks of at most 255 bytes, zero-terminated
+    const blocks = Buffer.alloc(out.length + Math.ceil(out.length / 255) + 1);
+    let pos = 0;
+    for (let i = 0; i < out.length; i += 255) {
+        const n = Math.min(255, out.length - i);
+        blocks[pos++] = n;
+        for (let j = 0; j < n; j++) blocks[pos++] = out[i + j];
+    }
+    blocks[pos++] = 0;
+    return blocks.subarray(0, pos);
+};
+
+const u16 = (v) => [v & 0xff, (v >> 8) & 0xff];
+
+const createGifWriter = (filePath, { width, height, fps, loop }, palette) => {
+    const fd = fs.openSync(filePath, 'w');
+    const delay = Math.max(2, Math.round(100 / fps)); // hundredths; browsers clamp < 2
+    const header = [
+        ...Buffer.from('GIF89a'), ...u16(width), ...u16(height),
+        0xf2, 0, 0, // global color table, 8 entries
+        ...palette.flat()
+    ];
+    if (loop) header.push(0x21, 0xff, 0x0b, ...Buffer.from('NETSCAPE2.0'), 0x03, 0x01, 0, 0, 0);
+    fs.writeSync(fd, Buffer.from(header));
+
+    return {
+        writeFrame: (pixels) => {
+            fs.writeSync(fd, Buffer.from([
+                0x21, 0xf9, 0x04, 0x00, ...u16(delay), 0, 0, // graphic control
+                0x2c, 0, 0, 0, 0, ...u16(width), ...u16(height), 0, // image descriptor
+                3 // LZW minimum code size
+            ]));
+            fs.writeSync(fd, lzwEncode(pixels, 3));
+        },
+        finish: async () => {
+            fs.writeSync(fd, Buffer.from([0x3b]));
+            fs.closeSync(fd);
+        },
+        abort: () => { try { fs.closeSync(fd); } catch (e) {} }
+    };
+};
+
+// --- MP4 via ffmpeg ---
+
+const createMp4Writer = (filePath, { width, height, fps, ffmpegPath }, palette) => {
+    const ffmpeg = spawn(ffmpegPath || 'ffmpeg', [
+        '-y', '-loglevel', 'error',
+        '-f', 'rawvideo', '-pix_fmt', 'rgb24', '-s', `${width}x${height}`, '-r', String(fps), '-i', '-',
+        '-c:v', 'libx264', '-pix_fmt', 'yuv420p', '-movflags', '+faststart', filePath
+    ], { stdio: ['pipe', 'ignore', 'pipe'] });
+
+    let stderr = '';
+    let failure = null;
+    ffmpeg.stderr.on('data', (d) => { stderr += d; });
+    const exited = new Promise((resolve) => {
+        ffmpeg.on('error', (err) => {
+            failure = err.code === 'ENOENT' ? new Error('ffmpeg not found; install it or set options.ffmpegPath') : err;
+            resolve();
+        });
+        ffmpeg.on('close', (code) => {
+            if (code !== 0 && !failure) failure = new Error(`ffmpeg exited with code ${code}: ${stderr.trim().slice(-300)}`);
+            resolve();
+        });
+    });
+    ffmpeg.stdin.on('error', () => {}); // surfaced through 'close' instead
+
+    const rgb = Buffer.alloc(width * height * 3);
+
+    return {
+        writeFrame: async (pixels) => {
+            if (failure) throw failure;
+            for (let i = 0, o = 0; i < pixels.length; i++, o += 3) {
+                const c = palette[pixels[i]];
+                rgb[o] = c[0]; rgb[o + 1] = c[1]; rgb[o + 2] = c[2];
+            }
+            if (!ffmpeg.stdin.write(Buffer.from(rgb))) {
+                // ffmpeg dying mid-export never drains; the exit settles the wait instead
+                await Promise.race([new Promise(resolve => ffmpeg.stdin.once('drain', resolve)), exited]);
+                if (failure) throw failure;
+            }
+        },
+        finish: async () => {
+            ffmpeg.stdin.end();
+            await exited;
+            if (failure) throw failure;
+        },
+        abort: () => ffmpeg.kill()
+    };
+};
+
+/**
+ * bars: history context followed by the bars to play back, ascending.
+ * `firstPlayed` is the index of the first played bar.
+ */
+const exportPlayback = async (bars, firstPlayed, options, onProgress = () => {}) => {
+    const opts = resolveOptions(options);
+    if (!opts.filePath) throw new Error('filePath is required');
+    const played = bars.length - firstPlayed;
+    if (played <= 0) throw new Error('No bars in the selected range');
+
+    const frames = Math.min(opts.maxFrames, Math.ceil(played / opts.barsPerFrame));
+    // Past maxFrames, reveal more bars per frame instead of truncating the range
+    const step = Math.max(opts.barsPerFrame, Math.ceil(played / frames));
+
+    const palette = buildPalette(opts.colors);
+    const writer = opts.format === 'gif' ? createGifWriter(opts.filePath, opts, palette) : createMp4Writer(opts.filePath, opts, palette);
+    const pixels = new Uint8Array(opts.width * opts.height);
+    const started = Date.now();
+    try {
+        for (let f = 0; f < frames; f++) {
+            const end = Math.min(bars.length, firstPlayed + (f + 1) * step);
+            renderFrame(pixels, bars, Math.max(0, end - opts.windowBars), end, opts, (f + 1) / frames);
+            await writer.writeFrame(pixels);
+            if ((f + 1) % PROGRESS_EVERY === 0 || f === frames - 1) onProgress({ frame: f + 1, frames });
+        }
+        await writer.finish();
+    } catch (err) {
+        writer.abort();
+        try { fs.rmSync(opts.filePath, { force: true }); } catch (e) {}
+        throw err;
+    }
+    return {
+        filePath: opts.filePath,
+        format: opts.format,
+        frames,
+        barsPerFrame: step,
+        width: opts.width,
+        height: opts.height,
+        durationSec: frames / opts.fps,
+        bytes: fs.statSync(opts.filePath).size,
+        elapsedMs: Date.now() - started
+    };
+};
+
+// Loads `windowBars` of context before range.from plus everything in the range
+const loadPlaybackBars = (db, symbol, timeframe, range, windowBars) => {
+    const from = range && range.from != null ? range.from : -Infinity;
+    const to = range && range.to != null ? range.to : Infinity;
+    const history = Number.isFinite(from)
+        ? db.prepare('SELECT timestamp, open, high, low, close FROM market_data WHERE symbol = ? AND timeframe = ? AND timestamp < ? ORDER BY timestamp DESC LIMIT ?')
+            .all(symbol, timeframe, from, windowBars).reverse()
+        : [];
+    const played = db.prepare('SELECT timestamp, open, high, low, close FROM market_data WHERE symbol = ? AND timeframe = ? AND timestamp >= ? AND timestamp <= ? ORDER BY timestamp ASC')
+        .all(symbol, timeframe, Number.isFinite(from) ? from : Number.MIN_SAFE_INTEGER, Number.isFinite(to) ? to : Number.MAX_SAFE_INTEGER);
+    return { bars: history.concat(played), firstPlayed: history.length };
+};
+
+if (!isMainThread && workerData && workerData.playbackExport) {
+    const { dbPath, symbol, timeframe, range, options } = workerData.playbackExport;
+    (async () => {
+        const Database = require('better-sqlite3');
+        const db = new Database(dbPath, { readonly: true, fileMustExist: true });
+        try {
+            const { bars, firstPlayed } = loadPlaybackBars(db, symbol, timeframe, range, resolveOptions(options).windowBars);
+            db.close();
+            const result = await exportPlayback(bars, firstPlayed, options, (progress) => parentPort.postMessage({ type: 'progress', ...progress }));
+            parentPort.postMessage({ type: 'done', success: true, ...result });
+        } catch (err) {
+            if (db.open) db.close();
+            parentPort.postMessage({ type: 'done', success: false, error: err.message });
+        }
+    })();
+}
+
+module.exports = { exportPlayback, renderFrame, lzwEncode };
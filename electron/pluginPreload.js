@@ -0,0 +1,14 @@
+
+// Preload of plugin panel windows (see pluginSandbox.js). Sandboxed: the page
+// gets `redpill.invoke` and `redpill.onEvent`, nothing from Node or Electron.
+const { contextBridge, ipcRenderer } = require('electron');
+
+contextBridge.exposeInMainWorld('redpill', {
+    invoke: (command, ...args) => ipcRenderer.invoke('plugin:invoke', command, ...args),
+    onEvent: (callback) => {
+        const channel = 'plugin:event';
+        const subscription = (event, ...args) => callback(...args);
+        ipcRenderer.on(channel, subscription);
+        return () => ipcRenderer.removeListener(channel, subscription);
+    }
+});
@@ -0,0 +1,271 @@
+
+const fs = require('fs');
+const path = require('path');
+const { AppError, failure } = require('./appErrors');
+
+// --- PLUGIN PANEL SANDBOX ---
+// Plugins may contribute a panel: a page shipped in their folder under
+// <userData>/plugins/<dir>/ next to a plugin.json manifest. A panel never runs
+// in the app's renderer. main.js opens it in its own window — sandboxed,
+// context-isolated, in a partition of its own (no shared storage or cookies),
+// served from redpill-plugin://<id>/ with PANEL_CSP, network requests
+// cancelled — whose preload (pluginPreload.js) exposes one function,
+// redpill.invoke(command, ...args), on the single 'plugin:invoke' channel.
+//
+// The broker answers those calls. The plugin is identified by the window that
+// sent the call, never by an argument, and a command runs only when the user
+// granted the capability it belongs to:
+//
+//   market:read    datasets.list(), bars.get(datasetId, { from?, to?, limit? })
+//   symbols:read   symbols.getMeta(symbol)
+//   storage        storage.get / set / delete / keys — a namespace per plugin
+//                  in `plugin_storage`, MAX_STORAGE_KEYS keys of MAX_VALUE_BYTES
+//   notify         ui.notify(message) — shown by the app, attributed to the plugin
+//
+// Nothing else is reachable: no journal, drawings, notes, settings or
+// secrets. installPluginIpcGuard keeps plugin windows off every other IPC
+// channel even if a page found a way to ipcRenderer.
+
+const PLUGIN_SCHEME = 'redpill-plugin';
+const MANIFEST_FILE = 'plugin.json';
+const ID_RE = /^[a-z0-9][a-z0-9._-]{1,63}$/;
+const MAX_STORAGE_KEYS = 1000;
+const MAX_VALUE_BYTES = 64 * 1024;
+const MAX_BARS = 5000;
+const MAX_NOTIFY_LENGTH = 300;
+const PANEL_CSP = [
+    "default-src 'none'",
+    `script-src ${PLUGIN_SCHEME}:`,
+    `style-src ${PLUGIN_SCHEME}: 'unsafe-inline'`,
+    `img-src ${PLUGIN_SCHEME}: data:`,
+    `font-src ${PLUGIN_SCHEME}:`,
+    "connect-src 'none'",
+    "frame-src 'none'",
+    "object-src 'none'",
+    "base-uri 'none'",
+    "form-action 'none'"
+].join('; ');
+
+const CAPABILITIES = {
+    'market:read': ['datasets.list', 'bars.get'],
+    'symbols:read': ['symbols.getMeta'],
+    storage: ['storage.get', 'storage.set', 'storage.delete', 'storage.keys'],
+    notify: ['ui.notify']
+};
+const WRITE_COMMANDS = ['storage.set', 'storage.delete']; // refused in read-only mode
+
+const CONTENT_TYPES = {
+    '.html': 'text/html; charset=utf-8',
+    '.js': 'text/javascript; charset=utf-8',
+    '.mjs': 'text/javascript; charset=utf-8',
+    '.css': 'text/css; charset=utf-8',
+    '.json': 'application/json',
+    '.svg': 'image/svg+xml',
+    '.png': 'image/png',
+    '.jpg': 'image/jpeg',
+    '.gif': 'image/gif',
+    '.woff2': 'font/woff2',
+    '.woff': 'font/woff'
+};
+
+const initializePluginStorageTable = (db) => {
+    db.exec(`
+        CREATE TABLE IF NOT EXISTS plugin_storage (
+            plugin_id TEXT,
+            key TEXT,
+            value TEXT,
+            updated_at INTEGER,
+            PRIMARY KEY (plugin_id, key)
+        );
+    `);
+};
+
+// A path inside `dir`, or null when `relative` leaves it
+const resolveInside = (dir, relative) => {
+    const target = path.resolve(dir, String(relative || '').replace(/^[/\\]+/, ''));
+    return target === dir || target.startsWith(`${dir}${path.sep}`) ? target : null;
+};
+
+/**
+ * plugin.json: { id, name, version?, panel: { entry, title?, width?, height? },
+ * capabilities: [] } -> normalized manifest with `dir`. Unknown capabilities
+ * are refused rather than ignored, so a typo doesn't silently do less.
+ */
+const normalizePanelManifest = (manifest = {}, dir) => {
+    const id = String(manifest.id || '').toLowerCase();
+    if (!ID_RE.test(id)) throw new Error('Plugin id must be 2-64 lowercase letters, digits, dots, dashes or underscores');
+    const name = String(manifest.name || id).slice(0, 80);
+    const panel = manifest.panel || {};
+    const entry = String(panel.entry || 'index.html');
+    const entryPath = resolveInside(dir, entry);
+    if (!entryPath || path.extname(entryPath).toLowerCase() !== '.html') throw new Error(`${id}: panel.entry must be an .html file inside the plugin folder`);
+    const capabilities = Array.isArray(manifest.capabilities) ? Array.from(new Set(manifest.capabilities.map(String))) : [];
+    const unknown = capabilities.filter(c => !CAPABILITIES[c]);
+    if (unknown.length) throw new Error(`${id}: unknown capabilities ${unknown.join(', ')}`);
+    const size = (value, fallback) => Math.max(200, Math.min(3000, Math.round(Number(value)) || fallback));
+    return {
+        id,
+        name,
+        version: manifest.version ? String(manifest.version) : null,
+        dir,
+        panel: { entry: path.relative(dir, entryPath).split(path.sep).join('/'), title: String(panel.title || name).slice(0, 80), width: size(panel.width, 420), height: size(panel.height, 600) },
+        capabilities
+    };
+};
+
+// Every plugin folder with a valid manifest; broken ones are listed in `errors`
+const discoverPanelPlugins = (pluginsDir) => {
+    const plugins = [];
+    const errors = [];
+    let entries = [];
+    try { entries = fs.readdirSync(pluginsDir, { withFileTypes: true }); } catch (e) { return { plugins, errors }; }
+    entries.filter(e => e.isDirectory()).forEach((e) => {
+        const dir = path.join(pluginsDir, e.name);
+        const file = path.join(dir, MANIFEST_FILE);
+        if (!fs.existsSync(file)) return;
+        try {
+            const manifest = normalizePanelManifest(JSON.parse(fs.readFileSync(file, 'utf8')), dir);
+            if (plugins.some(p => p.id === manifest.id)) throw new Error(`${manifest.id}: another plugin folder uses this id`);
+            plugins.push(manifest);
+        } catch (err) {
+            errors.push({ dir: e.name, error: err.message });
+        }
+    });
+    return { plugins: plugins.sort((a, b) => a.name.localeCompare(b.name)), errors };
+};
+
+/**
+ * The file for a redpill-plugin://<id>/<path> request of plugin `manifest`:
+ * { status, body?, headers }. Other plugins' hosts and paths leaving the
+ * folder are 404s.
+ */
+const readPanelFile = (manifest, url) => {
+    const parsed = new URL(url);
+    if (parsed.protocol !== `${PLUGIN_SCHEME}:` || parsed.hostname !== manifest.id) return { status: 404, headers: {} };
+    const requested = decodeURIComponent(parsed.pathname).replace(/^\/+/, '');
+    const target = resolveInside(manifest.dir, requested || manifest.panel.entry);
+    if (!target || !fs.existsSync(target) || !fs.statSync(target).isFile()) return { status: 404, headers: {} };
+    return {
+        status: 200,
+        body: fs.readFileSync(target),
+        headers: {
+            'Content-Type': CONTENT_TYPES[path.extname(target).toLowerCase()] || 'application/octet-stream',
+            'Content-Security-Policy': PANEL_CSP,
+            'X-Content-Type-Options': 'nosniff'
+        }
+    };
+};
+
+const storageValue = (value) => {
+    const text = JSON.stringify(value === undefined ? null : value);
+    if (Buffer.byteLength(text, 'utf8') > MAX_VALUE_BYTES) throw new AppError('PAYLOAD_TOO_LARGE', `Plugin storage values are limited to ${MAX_VALUE_BYTES} bytes`);
+    return text;
+};
+
+const storageKey = (key) => {
+    const text = String(key == null ? '' : key);
+    if (!text || text.length > 200) throw new AppError('INVALID_INPUT', 'Storage keys must be 1-200 characters');
+    return text;
+};
+
+/**
+ * The command handlers behind the capabilities. deps: { getDb, listDatasets,
+ * getSymbolMeta, notify(plugin, message) }. Handlers get ({ plugin }, ...args).
+ */
+const createPluginCommands = ({ getDb, listDatasets, getSymbolMeta, notify }) => {
+    const db = () => {
+        const handle = getDb();
+        if (!handle) throw new AppError('UNAVAILABLE', 'The database is not ready');
+        return handle;
+    };
+    return {
+        'datasets.list': () => listDatasets(db()).map(d => ({ id: d.id, symbol: d.symbol, timeframe: d.timeframe, rowCount: d.rowCount, firstTimestamp: d.firstTimestamp, lastTimestamp: d.lastTimestamp })),
+        'bars.get': (ctx, id, { from = null, to = null, limit = 1000 } = {}) => {
+            const [symbol, timeframe] = String(id || '').split(':');
+            if (!symbol || !timeframe) throw new AppError('INVALID_INPUT', 'datasetId must be SYMBOL:TIMEFRAME');
+            const rows = db().prepare('SELECT timestamp, open, high, low, close, volume FROM market_data WHERE symbol = ? AND timeframe = ? AND timestamp >= ? AND timestamp <= ? ORDER BY timestamp DESC LIMIT ?')
+                .all(symbol, timeframe, from == null ? -8.64e15 : Number(from), to == null ? 8.64e15 : Number(to), Math.max(1, Math.min(MAX_BARS, Number(limit) || 1000)));
+            return rows.reverse();
+        },
+        'symbols.getMeta': (ctx, symbol) => {
+            const meta = getSymbolMeta(db(), String(symbol || ''));
+            return { symbol: meta.symbol, assetClass: meta.assetClass, currency: meta.currency, tickSize: meta.tickSize, tickValue: meta.tickValue, multiplier: meta.multiplier };
+        },
+        'storage.get': (ctx, key) => {
+            const row = db().prepare('SELECT value FROM plugin_storage WHERE plugin_id = ? AND key = ?').get(ctx.plugin.id, storageKey(key));
+            return row ? JSON.parse(row.value) : null;
+        },
+        'storage.set': (ctx, key, value) => {
+            const k = storageKey(key);
+            const text = storageValue(value);
+            const exists = db().prepare('SELECT 1 FROM plugin_storage WHERE plugin_id = ? AND key = ?').get(ctx.plugin.id, k);
+            if (!exists && db().prepare('SELECT COUNT(*) AS n FROM plugin_storage WHERE plugin_id = ?').get(ctx.plugin.id).n >= MAX_STORAGE_KEYS) {
+                throw new AppError('PAYLOAD_TOO_LARGE', `Plugins can store at most ${MAX_STORAGE_KEYS} keys`);
+            }
+            db().prepare('INSERT OR REPLACE INTO plugin_storage (plugin_id, key, value, updated_at) VALUES (?, ?, ?, ?)').run(ctx.plugin.id, k, text, Date.now());
+            return true;
+        },
+        'storage.delete': (ctx, key) => db().prepare('DELETE FROM plugin_storage WHERE plugin_id = ? AND key = ?').run(ctx.plugin.id, storageKey(key)).changes > 0,
+        'storage.keys': ctx => db().prepare('SELECT key FROM plugin_storage WHERE plugin_id = ? ORDER BY key').all(ctx.plugin.id).map(r => r.key),
+        'ui.notify': (ctx, message) => {
+            notify(ctx.plugin, String(message == null ? '' : message).slice(0, MAX_NOTIFY_LENGTH));
+            return true;
+        }
+    };
+};
+
+/**
+ * Runs `command` for the plugin window that sent it. sender: { plugin,
+ * granted: [capability] } from main.js's window registry. Returns the
+ * handler's answer as { success, result } or a failure.
+ */
+const invokePluginCommand = async (commands, sender, command, args) => {
+    const name = String(command || '');
+    const capability = Object.keys(CAPABILITIES).find(c => CAPABILITIES[c].includes(name));
+    if (!capability || !commands[name]) return failure('NOT_FOUND', `Unknown plugin command: ${name}`);
+    if (!sender.granted.includes(capability)) return failure('PERMISSION_DENIED', `${sender.plugin.id} has not been granted ${capability}`, { capability });
+    try {
+        return { success: true, result: await commands[name]({ plugin: sender.plugin }, ...args) };
+    } catch (err) {
+        return failure(err.code || 'INTERNAL', err.message);
+    }
+};
+
+/**
+ * Wraps ipcMain.handle / ipcMain.on like the other guards: calls from a
+ * window isRestricted(senderId) reports are refused on every channel but
+ * `allowed`. Must be installed before any handler is registered.
+ */
+const installPluginIpcGuard = (ipcMain, { isRestricted, allowed = ['plugin:invoke'], onReject = () => {} }) => {
+    const allow = new Set(allowed);
+    const originalHandle = ipcMain.handle.bind(ipcMain);
+    const originalOn = ipcMain.on.bind(ipcMain);
+    ipcMain.handle = (channel, handler) => originalHandle(channel, allow.has(channel) ? handler : async (event, ...args) => {
+        if (event && event.sender && isRestricted(event.sender.id)) {
+            onReject(channel, event.sender.id);
+            return failure('PERMISSION_DENIED', `${channel} is not available to plugin panels`);
+        }
+        return handler(event, ...args);
+    });
+    ipcMain.on = (channel, listener) => originalOn(channel, allow.has(channel) ? listener : (event, ...args) => {
+        if (event && event.sender && isRestricted(event.sender.id)) {
+            onReject(channel, event.sender.id);
+            return;
+        }
+        listener(event, ...args);
+    });
+};
+
+module.exports = {
+    PLUGIN_SCHEME,
+    PANEL_CSP,
+    CAPABILITIES,
+    WRITE_COMMANDS,
+    initializePluginStorageTable,
+    normalizePanelManifest,
+    discoverPanelPlugins,
+    readPanelFile,
+    createPluginCommands,
+    invokePluginCommand,
+    installPluginIpcGuard
+};
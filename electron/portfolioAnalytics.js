@@ -0,0 +1,239 @@
+
+const { rMultiple, getSymbolMeta } = require('./positionSizing');
+
+// --- PORTFOLIO ANALYTICS ---
+// Performance statistics over closed trades from the journal (trades table)
+// and paper trading (sim_fills). A journal trade counts once it has a P&L; a
+// simulated trade is a fill that closed exposure. Opening fills don't count as
+// trades but their commissions still cost equity, so the curve matches the
+// simulated account. Sharpe / Sortino use daily (UTC) returns, annualized with
+// 252 trading days; without a starting equity they fall back to daily P&L.
+// Each trade carries its P&L currency (journal: the trade's or the symbol's,
+// paper: the account's); with a base currency and a rate lookup, P&L is
+// converted at the rate of the trade's day before anything is summed.
+
+const TRADING_DAYS = 252;
+const DAY_MS = 86400000;
+
+const tagsOf = (trade) => {
+    if (Array.isArray(trade.tags)) return trade.tags.map(String).filter(Boolean);
+    return trade.tag ? [String(trade.tag)] : [];
+};
+
+const journalTrades = (db, filter) => db.prepare('SELECT data FROM trades ORDER BY timestamp').all()
+    .map(row => JSON.parse(row.data))
+    .filter(trade => trade.pnl != null && trade.status !== 'cancelled')
+    .filter(trade => !filter.sourceId || trade.sourceId === filter.sourceId)
+    .filter(trade => !filter.mode || (trade.mode || 'live') === filter.mode)
+    .map(trade => ({
+        id: trade.id,
+        source: 'journal',
+        symbol: trade.symbol,
+        side: trade.side,
+        qty: trade.qty,
+        pnl: Number(trade.pnl),
+        currency: trade.currency || getSymbolMeta(db, trade.symbol).currency || null,
+        r: trade.rMultiple != null ? Number(trade.rMultiple)
+            : trade.stopPrice != null && trade.exitPrice != null ? rMultiple(trade.price, trade.stopPrice, trade.exitPrice) : null,
+        timestamp: trade.exitTimestamp || trade.timestamp,
+        closing: true,
+        tags: tagsOf(trade)
+    }));
+
+const simTrades = (db, filter) => {
+    const params = [];
+    let sql = 'SELECT f.*, a.currency AS account_currency FROM sim_fills f LEFT JOIN sim_accounts a ON a.id = f.account_id';
+    if (filter.accountId) {
+        sql += ' WHERE f.account_id = ?';
+        params.push(filter.accountId);
+    }
+    return db.prepare(`${sql} ORDER BY f.timestamp`).all(...params).map(row => ({
+        id: row.id,
+        source: 'sim',
+        symbol: row.symbol,
+        side: row.side,
+        qty: row.qty,
+        pnl: row.realized_pnl,
+        currency: row.account_currency || null,
+        r: null,
+        timestamp: row.timestamp,
+        // Opening fills realize nothing but their commission
+        closing: Math.abs(row.realized_pnl + row.commission) > 1e-9,
+        tags: row.tag ? [row.tag] : []
+    }));
+};
+
+/**
+ * filter: { sources?: ['journal', 'sim'], accountId?, sourceId?, mode?, symbols?, tags?, from?, to? }
+ * Returns trade records, oldest first.
+ */
+const collectTrades = (db, filter = {}) => {
+    const sources = filter.sources && filter.sources.length ? filter.sources : ['journal', 'sim'];
+    const symbols = filter.symbols && filter.symbols.length ? new Set(filter.symbols) : null;
+    const tags = filter.tags && filter.tags.length ? new Set(filter.tags) : null;
+    return [
+        ...(sources.includes('journal') ? journalTrades(db, filter) : []),
+        ...(sources.includes('sim') ? simTrades(db, filter) : [])
+    ]
+        .filter(trade => Number.isFinite(trade.pnl))
+        .filter(trade => filter.from == null || trade.timestamp >= filter.from)
+        .filter(trade => filter.to == null || trade.timestamp <= filter.to)
+        .filter(trade => !symbols || symbols.has(trade.symbol))
+        .filter(trade => !tags || trade.tags.some(tag => tags.has(tag)))
+        .sort((a, b) => a.timestamp - b.timestamp);
+};
+
+/**
+ * Converts trade P&L into baseCurrency with rateFor(from, to, timestamp) ->
+ * rate | null. Trades without a known rate are left out and listed in
+ * `unconverted`; trades without a currency are taken as already in base.
+ */
+const convertTrades = (trades, baseCurrency, rateFor) => {
+    if (!baseCurrency || !rateFor) return { trades, unconverted: [] };
+    const unconverted = [];
+    const converted = trades.flatMap((trade) => {
+        if (!trade.currency || trade.currency === baseCurrency) return [trade];
+        const rate = rateFor(trade.currency, baseCurrency, trade.timestamp);
+        if (rate == null) {
+            unconverted.push({ id: trade.id, source: trade.source, symbol: trade.symbol, currency: trade.currency, timestamp: trade.timestamp });
+            return [];
+        }
+        return [{ ...trade, pnl: trade.pnl * rate, pnlLocal: trade.pnl, fxRate: rate, currency: baseCurrency }];
+    });
+    return { trades: converted, unconverted };
+};
+
+const mean = (values) => (values.length ? values.reduce((sum, v) => sum + v, 0) / values.length : 0);
+
+const stdev = (values) => {
+    if (values.length < 2) return 0;
+    const m = mean(values);
+    return Math.sqrt(values.reduce((sum, v) => sum + (v - m) ** 2, 0) / (values.length - 1));
+};
+
+// Win rate, expectancy and friends over closing trades
+const summarize = (trades) => {
+    const closed = trades.filter(trade => trade.closing);
+    const wins = closed.filter(trade => trade.pnl > 0);
+    const losses = closed.filter(trade => trade.pnl < 0);
+    const grossProfit = wins.reduce((sum, trade) => sum + trade.pnl, 0);
+    const grossLoss = -losses.reduce((sum, trade) => sum + trade.pnl, 0);
+    const rs = closed.map(trade => trade.r).filter(r => r != null && Number.isFinite(r));
+    const winRate = closed.length ? wins.length / closed.length : 0;
+    const avgWin = wins.length ? grossProfit / wins.length : 0;
+    const avgLoss = losses.length ? grossLoss / losses.length : 0;
+    return {
+        trades: closed.length,
+        wins: wins.length,
+        losses: losses.length,
+        winRate,
+        netPnl: trades.reduce((sum, trade) => sum + trade.pnl, 0),
+        grossProfit,
+        grossLoss,
+        profitFactor: grossLoss > 0 ? grossProfit / grossLoss : null,
+        avgWin,
+        avgLoss,
+        largestWin: wins.length ? Math.max(...wins.map(trade => trade.pnl)) : 0,
+        largestLoss: losses.length ? Math.min(...losses.map(trade => trade.pnl)) : 0,
+        expectancy: winRate * avgWin - (1 - winRate) * avgLoss,
+        avgR: rs.length ? mean(rs) : null
+    };
+};
+
+const drawdownOf = (curve, startingEquity) => {
+    let peak = { equity: startingEquity, timestamp: curve.length ? curve[0].timestamp : null };
+    let worst = { amount: 0, pct: 0, peakAt: null, troughAt: null, recoveredAt: null, peakEquity: startingEquity };
+    curve.forEach((point) => {
+        if (point.equity >= peak.equity) {
+            peak = point;
+            return;
+        }
+        const amount = peak.equity - point.equity;
+        if (amount > worst.amount) {
+            worst = { amount, pct: peak.equity > 0 ? amount / peak.equity : null, peakAt: peak.timestamp, troughAt: point.timestamp, recoveredAt: null, peakEquity: peak.equity };
+        }
+    });
+    const recovery = worst.troughAt != null && curve.find(point => point.timestamp > worst.troughAt && point.equity >= worst.peakEquity);
+    const { peakEquity, ...result } = worst;
+    return { ...result, recoveredAt: recovery ? recovery.timestamp : null };
+};
+
+const dailySeries = (curve, startingEquity) => {
+    const days = new Map(); // UTC day -> closing equity
+    curve.forEach(point => days.set(Math.floor(point.timestamp / DAY_MS), point.equity));
+    let previous = startingEquity;
+    return Array.from(days.entries()).sort((a, b) => a[0] - b[0]).map(([day, equity]) => {
+        const entry = { day: day * DAY_MS, pnl: equity - previous, ret: previous > 0 ? (equity - previous) / previous : null };
+        previous = equity;
+        return entry;
+    });
+};
+
+const ratios = (daily) => {
+    const useReturns = daily.length > 0 && daily.every(d => d.ret != null);
+    const values = daily.map(d => (useReturns ? d.ret : d.pnl));
+    const m = mean(values);
+    const sd = stdev(values);
+    const downside = Math.sqrt(values.reduce((sum, v) => sum + Math.min(0, v) ** 2, 0) / Math.max(1, values.length));
+    const scale = Math.sqrt(TRADING_DAYS);
+    return {
+        basis: useReturns ? 'returns' : 'pnl',
+        sharpe: sd > 0 ? (m / sd) * scale : null,
+        sortino: downside > 0 ? (m / downside) * scale : null
+    };
+};
+
+const breakdown = (trades, keyOf) => {
+    const groups = new Map();
+    trades.forEach(trade => keyOf(trade).forEach((key) => {
+        if (!groups.has(key)) groups.set(key, []);
+        groups.get(key).push(trade);
+    }));
+    return Array.from(groups.entries())
+        .map(([key, group]) => ({ key, ...summarize(group) }))
+        .sort((a, b) => b.netPnl - a.netPnl);
+};
+
+/**
+ * startingEquity: equity before the first trade (0 = P&L curve only).
+ */
+const computePortfolioStats = (trades, { startingEquity = 0 } = {}) => {
+    let equity = startingEquity;
+    const equityCurve = trades.map((trade) => {
+        equity += trade.pnl;
+        return { timestamp: trade.timestamp, equity, pnl: trade.pnl };
+    });
+    const daily = dailySeries(equityCurve, startingEquity);
+    return {
+        startingEquity,
+        endingEquity: equity,
+        returnPct: startingEquity > 0 ? (equity - startingEquity) / startingEquity : null,
+        from: trades.length ? trades[0].timestamp : null,
+        to: trades.length ? trades[trades.length - 1].timestamp : null,
+        ...summarize(trades),
+        maxDrawdown: drawdownOf(equityCurve, startingEquity),
+        ...ratios(daily),
+        equityCurve,
+        daily,
+        byTag: breakdown(trades, trade => (trade.tags.length ? trade.tags : ['(untagged)'])),
+        bySymbol: breakdown(trades, trade => [trade.symbol]),
+        bySource: breakdown(trades, trade => [trade.source])
+    };
+};
+
+/**
+ * filter: see collectTrades, plus startingEquity. With a single paper account
+ * and no startingEquity, the account's starting cash is used.
+ * options: { baseCurrency?, rateFor? } (see convertTrades).
+ */
+const getPortfolioStats = (db, filter = {}, { baseCurrency = null, rateFor = null } = {}) => {
+    let startingEquity = filter.startingEquity != null ? Number(filter.startingEquity) : 0;
+    if (filter.startingEquity == null && filter.accountId && filter.sources && filter.sources.length === 1 && filter.sources[0] === 'sim') {
+        const account = db.prepare('SELECT starting_cash FROM sim_accounts WHERE id = ?').get(filter.accountId);
+        if (account) startingEquity = account.starting_cash;
+    }
+    const { trades, unconverted } = convertTrades(collectTrades(db, filter), baseCurrency, rateFor);
+    return { filter, baseCurrency, unconverted, ...computePortfolioStats(trades, { startingEquity }) };
+};
+
+module.exports = { collectTrades, convertTrades, computePortfolioStats, getPortfolioStats };
@@ -0,0 +1,88 @@
+
+// --- PORTFOLIO REPORT ---
+// Turns portfolio statistics (portfolioAnalytics.js) into a self-contained
+// HTML page: headline numbers, an SVG equity curve with the max drawdown
+// shaded, and per-tag / per-symbol tables. main.js prints it to PDF with an
+// offscreen window, so the report needs no renderer code and no network.
+
+const CURVE_WIDTH = 720;
+const CURVE_HEIGHT = 220;
+
+const escapeHtml = (value) => String(value).replace(/[&<>"']/g, c => ({ '&': '&amp;', '<': '&lt;', '>': '&gt;', '"': '&quot;', "'": '&#39;' }[c]));
+
+const money = (value) => (value == null ? '—' : value.toLocaleString('en-US', { minimumFractionDigits: 2, maximumFractionDigits: 2 }));
+const pct = (value) => (value == null ? '—' : `${(value * 100).toFixed(1)}%`);
+const ratio = (value) => (value == null ? '—' : value.toFixed(2));
+const date = (ts) => (ts == null ? '—' : new Date(ts).toISOString().slice(0, 10));
+
+const curveSvg = (stats) => {
+    const points = [{ timestamp: stats.from, equity: stats.startingEquity }, ...stats.equityCurve];
+    if (stats.equityCurve.length === 0) return '<p class="muted">No closed trades in this range.</p>';
+    const t0 = points[0].timestamp;
+    const t1 = Math.max(t0 + 1, points[points.length - 1].timestamp);
+    const values = points.map(p => p.equity);
+    const lo = Math.min(...values);
+    const hi = Math.max(lo + 1e-9, ...values);
+    const x = (ts) => ((ts - t0) / (t1 - t0)) * CURVE_WIDTH;
+    const y = (v) => CURVE_HEIGHT - ((v - lo) / (hi - lo)) * CURVE_HEIGHT;
+    const path = points.map((p, i) => `${i ? 'L' : 'M'}${x(p.timestamp).toFixed(1)},${y(p.equity).toFixed(1)}`).join(' ');
+    const dd = stats.maxDrawdown;
+    const shade = dd.peakAt != null && dd.troughAt != null
+        ? `<rect x="${x(dd.peakAt).toFixed(1)}" y="0" width="${Math.max(1, x(dd.troughAt) - x(dd.peakAt)).toFixed(1)}" height="${CURVE_HEIGHT}" fill="#e5484d" opacity="0.12"/>`
+        : '';
+    return `<svg viewBox="0 0 ${CURVE_WIDTH} ${CURVE_HEIGHT}" width="100%" height="${CURVE_HEIGHT}">${shade}`
+        + `<path d="${path}" fill="none" stroke="#2962ff" stroke-width="1.5"/></svg>`;
+};
+
+const breakdownTable = (title, rows) => {
+    if (!rows.length) return '';
+    const body = rows.map(row => `<tr><td>${escapeHtml(row.key)}</td><td>${row.trades}</td><td>${pct(row.winRate)}</td>`
+        + `<td>${money(row.netPnl)}</td><td>${money(row.expectancy)}</td><td>${ratio(row.profitFactor)}</td></tr>`).join('');
+    return `<h2>${escapeHtml(title)}</h2><table><thead><tr><th></th><th>Trades</th><th>Win rate</th><th>Net P&amp;L</th>`
+        + `<th>Expectancy</th><th>Profit factor</th></tr></thead><tbody>${body}</tbody></table>`;
+};
+
+const REPORT_STYLE = `
+body { font: 12px -apple-system, Segoe UI, Helvetica, Arial, sans-serif; color: #131722; margin: 32px; }
+h1 { font-size: 20px; margin: 0 0 4px; } h2 { font-size: 14px; margin: 24px 0 8px; }
+.muted { color: #787b86; } .grid { display: grid; grid-template-columns: repeat(5, 1fr); gap: 8px; margin: 16px 0; }
+.cell { border: 1px solid #e0e3eb; border-radius: 4px; padding: 8px; } .label { color: #787b86; font-size: 10px; } .value { font-size: 14px; font-weight: 600; }
+table { width: 100%; border-collapse: collapse; } th, td { text-align: right; padding: 4px 6px; border-bottom: 1px solid #e0e3eb; }
+th:first-child, td:first-child { text-align: left; } tr { page-break-inside: avoid; }
+`;
+
+// The report's body markup, for pages that combine it with other content (printDocuments.js)
+const renderPortfolioReportBody = (stats, { title = 'Portfolio report', currency = '', generatedAt = Date.now() } = {}) => {
+    const unit = currency ? ` ${escapeHtml(currency)}` : '';
+    const cells = [
+        ['Net P&L', `${money(stats.netPnl)}${unit}`],
+        ['Return', pct(stats.returnPct)],
+        ['Trades', stats.trades],
+        ['Win rate', pct(stats.winRate)],
+        ['Expectancy', `${money(stats.expectancy)}${unit}`],
+        ['Profit factor', ratio(stats.profitFactor)],
+        ['Max drawdown', `${money(stats.maxDrawdown.amount)}${unit} (${pct(stats.maxDrawdown.pct)})`],
+        ['Sharpe', ratio(stats.sharpe)],
+        ['Sortino', ratio(stats.sortino)],
+        ['Average R', ratio(stats.avgR)]
+    ].map(([label, value]) => `<div class="cell"><div class="label">${label}</div><div class="value">${value}</div></div>`).join('');
+
+    return `<h1>${escapeHtml(title)}</h1>
+<div class="muted">${date(stats.from)} – ${date(stats.to)} · generated ${new Date(generatedAt).toISOString().replace('T', ' ').slice(0, 16)} UTC</div>
+<div class="grid">${cells}</div>
+<h2>Equity curve</h2>${curveSvg(stats)}
+${breakdownTable('By tag', stats.byTag)}
+${breakdownTable('By symbol', stats.bySymbol)}`;
+};
+
+/**
+ * options: { title?, currency?, generatedAt? }
+ */
+const renderPortfolioReportHtml = (stats, options = {}) => {
+    const title = options.title || 'Portfolio report';
+    return `<!DOCTYPE html><html><head><meta charset="utf-8"><title>${escapeHtml(title)}</title><style>${REPORT_STYLE}</style></head><body>
+${renderPortfolioReportBody(stats, options)}
+</body></html>`;
+};
+
+module.exports = { REPORT_STYLE, escapeHtml, renderPortfolioReportBody, renderPortfolioReportHtml };
@@ -0,0 +1,229 @@
+
+// --- POSITION SIZING & RISK ---
+// One place for contract math, so the journal, paper trading and the sizing
+// calculator agree on what a point is worth. Symbol metadata (tick size, tick
+// value, multiplier, currency, quantity step) is inferred from the symbol:
+// known futures roots, six-letter FX pairs, crypto pairs, and stocks
+// otherwise. Entries saved in symbol_meta override the inference.
+//
+// Money per unit of price movement ("point value") is tickValue / tickSize
+// when both are known, otherwise the multiplier. Amounts are in the symbol's
+// currency until converted with an FX rate (account currency per one unit of
+// symbol currency).
+
+const FUTURES = {
+    ES: { tickSize: 0.25, tickValue: 12.5, multiplier: 50 },
+    MES: { tickSize: 0.25, tickValue: 1.25, multiplier: 5 },
+    NQ: { tickSize: 0.25, tickValue: 5, multiplier: 20 },
+    MNQ: { tickSize: 0.25, tickValue: 0.5, multiplier: 2 },
+    YM: { tickSize: 1, tickValue: 5, multiplier: 5 },
+    MYM: { tickSize: 1, tickValue: 0.5, multiplier: 0.5 },
+    RTY: { tickSize: 0.1, tickValue: 5, multiplier: 50 },
+    M2K: { tickSize: 0.1, tickValue: 0.5, multiplier: 5 },
+    CL: { tickSize: 0.01, tickValue: 10, multiplier: 1000 },
+    MCL: { tickSize: 0.01, tickValue: 1, multiplier: 100 },
+    NG: { tickSize: 0.001, tickValue: 10, multiplier: 10000 },
+    GC: { tickSize: 0.1, tickValue: 10, multiplier: 100 },
+    MGC: { tickSize: 0.1, tickValue: 1, multiplier: 10 },
+    SI: { tickSize: 0.005, tickValue: 25, multiplier: 5000 },
+    HG: { tickSize: 0.0005, tickValue: 12.5, multiplier: 25000 },
+    ZB: { tickSize: 1 / 32, tickValue: 31.25, multiplier: 1000 },
+    ZN: { tickSize: 1 / 64, tickValue: 15.625, multiplier: 1000 },
+    ZF: { tickSize: 1 / 128, tickValue: 7.8125, multiplier: 1000 },
+    ZC: { tickSize: 0.25, tickValue: 12.5, multiplier: 50 },
+    ZS: { tickSize: 0.25, tickValue: 12.5, multiplier: 50 },
+    ZW: { tickSize: 0.25, tickValue: 12.5, multiplier: 50 },
+    '6E': { tickSize: 0.00005, tickValue: 6.25, multiplier: 125000 },
+    '6J': { tickSize: 0.0000005, tickValue: 6.25, multiplier: 12500000 },
+    '6B': { tickSize: 0.0001, tickValue: 6.25, multiplier: 62500 },
+    FDAX: { tickSize: 0.5, tickValue: 12.5, multiplier: 25, currency: 'EUR' },
+    FESX: { tickSize: 1, tickValue: 10, multiplier: 10, currency: 'EUR' }
+};
+
+const CURRENCIES = ['USD', 'EUR', 'GBP', 'JPY', 'CHF', 'CAD', 'AUD', 'NZD', 'SEK', 'NOK', 'DKK', 'SGD', 'HKD', 'CNH', 'MXN', 'ZAR', 'TRY', 'PLN'];
+const CRYPTO_QUOTES = ['USDT', 'USDC', 'USD', 'BTC', 'ETH', 'EUR'];
+const META_FIELDS = ['assetClass', 'tickSize', 'tickValue', 'multiplier', 'currency', 'qtyStep', 'minQty'];
+
+const initializeSymbolMetaTable = (db) => {
+    db.exec(`
+        CREATE TABLE IF NOT EXISTS symbol_meta (
+            symbol TEXT PRIMARY KEY,
+            data TEXT,
+            updated_at INTEGER
+        );
+    `);
+};
+
+// "ESZ4", "ES:FUT:CME:USD:202412", "/ES", "ES1!" -> "ES"
+const futuresRoot = (symbol) => {
+    const head = symbol.replace(/^\//, '').split(':')[0];
+    if (FUTURES[head]) return head;
+    const stripped = head.replace(/\d+!$/, '').replace(/[FGHJKMNQUVXZ]\d{1,2}$/, '');
+    return FUTURES[stripped] ? stripped : null;
+};
+
+/**
+ * Best-effort metadata from the symbol alone.
+ */
+const inferSymbolMeta = (rawSymbol) => {
+    const symbol = String(rawSymbol || '').trim().toUpperCase();
+    const root = futuresRoot(symbol);
+    if (root) return { symbol, assetClass: 'future', currency: 'USD', qtyStep: 1, minQty: 1, ...FUTURES[root] };
+
+    const pair = symbol.replace(/[/_\-.]/g, '');
+    if (/^[A-Z]{6}$/.test(pair) && CURRENCIES.includes(pair.slice(0, 3)) && CURRENCIES.includes(pair.slice(3))) {
+        const quote = pair.slice(3);
+        const tickSize = quote === 'JPY' ? 0.001 : 0.00001;
+        return { symbol, assetClass: 'forex', tickSize, tickValue: tickSize, multiplier: 1, currency: quote, qtyStep: 1000, minQty: 1000 };
+    }
+
+    const cryptoQuote = symbol.includes('/')
+        ? symbol.split('/')[1]
+        : CRYPTO_QUOTES.find(q => symbol.endsWith(q) && symbol.length > q.length + 2);
+    if (cryptoQuote) {
+        const currency = cryptoQuote.startsWith('USD') ? 'USD' : cryptoQuote;
+        return { symbol, assetClass: 'crypto', tickSize: 0.01, tickValue: 0.01, multiplier: 1, currency, qtyStep: 0.0001, minQty: 0.0001 };
+    }
+
+    return { symbol, assetClass: 'stock', tickSize: 0.01, tickValue: 0.01, multiplier: 1, currency: 'USD', qtyStep: 1, minQty: 1 };
+};
+
+const normalizeMeta = (input = {}) => {
+    const meta = {};
+    META_FIELDS.forEach((key) => {
+        if (input[key] === undefined || input[key] === null || input[key] === '') return;
+        if (key === 'assetClass' || key === 'currency') {
+            meta[key] = String(input[key]).trim().toUpperCase();
+            if (key === 'assetClass') meta[key] = meta[key].toLowerCase();
+            return;
+        }
+        const value = Number(input[key]);
+        if (!Number.isFinite(value) || value <= 0) throw new Error(`Invalid ${key}: ${input[key]}`);
+        meta[key] = value;
+    });
+    return meta;
+};
+
+const getSymbolMeta = (db, rawSymbol) => {
+    const inferred = inferSymbolMeta(rawSymbol);
+    const row = db ? db.prepare('SELECT data FROM symbol_meta WHERE symbol = ?').get(inferred.symbol) : null;
+    return row ? { ...inferred, ...JSON.parse(row.data), custom: true } : { ...inferred, custom: false };
+};
+
+// Stores overrides only; fields left out keep following the inference
+const saveSymbolMeta = (db, rawSymbol, input) => {
+    const symbol = String(rawSymbol || '').trim().toUpperCase();
+    if (!symbol) throw new Error('symbol is required');
+    db.prepare('INSERT OR REPLACE INTO symbol_meta (symbol, data, updated_at) VALUES (?, ?, ?)')
+        .run(symbol, JSON.stringify(normalizeMeta(input)), Date.now());
+    return getSymbolMeta(db, symbol);
+};
+
+const deleteSymbolMeta = (db, rawSymbol) => db.prepare('DELETE FROM symbol_meta WHERE symbol = ?').run(String(rawSymbol || '').trim().toUpperCase()).changes > 0;
+
+const listSymbolMeta = (db) => db.prepare('SELECT symbol FROM symbol_meta ORDER BY symbol').all().map(row => getSymbolMeta(db, row.symbol));
+
+const pointValue = (meta) => (meta && meta.tickSize && meta.tickValue ? meta.tickValue / meta.tickSize : (meta && meta.multiplier) || 1);
+
+// P&L of a signed quantity (negative = short) from entry to exit, in the symbol's currency
+const positionPnl = (meta, qty, entry, exit) => (exit - entry) * qty * pointValue(meta);
+
+// Signed R: +2 means the trade made twice what it risked
+const rMultiple = (entry, stop, exit) => {
+    const risk = entry - stop;
+    return risk === 0 ? null : (exit - entry) / risk;
+};
+
+const roundDown = (value, step) => {
+    if (!step) return value;
+    // Guard against 0.3 / 0.1 = 2.9999999999999996
+    const units = Math.floor(value / step + 1e-9);
+    return Number((units * step).toFixed(10));
+};
+
+/**
+ * equity: account equity in accountCurrency; riskPct: percent of equity to risk.
+ * fxRate: accountCurrency per one unit of meta.currency (required when they differ).
+ * targets: optional exit prices to express in R.
+ * Returns { side, size, riskBudget, riskAmount, riskPerUnit, riskPctActual,
+ * stopDistance, stopTicks, notional, currency, rLevels, targets, meta,
+ * warnings }; money in accountCurrency.
+ */
+const calcPositionSize = ({ equity, accountCurrency = 'USD', riskPct, entry, stop, meta, fxRate = null, targets = [] }) => {
+    const numbers = { equity, riskPct, entry, stop };
+    Object.entries(numbers).forEach(([key, value]) => {
+        if (!Number.isFinite(Number(value))) throw new Error(`${key} must be a number`);
+    });
+    equity = Number(equity);
+    riskPct = Number(riskPct);
+    entry = Number(entry);
+    stop = Number(stop);
+    if (equity <= 0) throw new Error('Account equity must be positive');
+    if (riskPct <= 0 || riskPct > 100) throw new Error('riskPct must be between 0 and 100');
+    if (entry === stop) throw new Error('Stop must differ from entry');
+
+    const currency = String(accountCurrency).toUpperCase();
+    const warnings = [];
+    let rate = 1;
+    if (meta.currency && meta.currency !== currency) {
+        rate = Number(fxRate);
+        if (!Number.isFinite(rate) || rate <= 0) {
+            throw new Error(`${meta.symbol} is priced in ${meta.currency}: an FX rate (${currency} per ${meta.currency}) is required`);
+        }
+    }
+
+    const side = entry > stop ? 'buy' : 'sell';
+    const stopDistance = Math.abs(entry - stop);
+    const stopTicks = meta.tickSize ? stopDistance / meta.tickSize : null;
+    if (stopTicks !== null && Math.abs(stopTicks - Math.round(stopTicks)) > 1e-6) {
+        warnings.push(`Stop distance is not a whole number of ticks (${meta.tickSize})`);
+    }
+    const riskPerUnit = stopDistance * pointValue(meta) * rate;
+    const riskBudget = equity * riskPct / 100;
+    let size = roundDown(riskBudget / riskPerUnit, meta.qtyStep);
+    if (meta.minQty && size < meta.minQty) {
+        warnings.push(`Risk budget is below the minimum size: one ${meta.minQty} lot risks ${(riskPerUnit * meta.minQty).toFixed(2)} ${currency}`);
+        size = 0;
+    }
+    const riskAmount = size * riskPerUnit;
+    const direction = side === 'buy' ? 1 : -1;
+    const signedSize = size * direction;
+    const level = (r) => {
+        const price = entry + direction * stopDistance * r;
+        return { r, price, pnl: positionPnl(meta, signedSize, entry, price) * rate };
+    };
+
+    return {
+        side,
+        size,
+        riskBudget,
+        riskAmount,
+        riskPerUnit,
+        riskPctActual: (riskAmount / equity) * 100,
+        stopDistance,
+        stopTicks,
+        notional: size * entry * pointValue(meta) * rate,
+        currency,
+        rLevels: [level(-1), level(1), level(2), level(3)],
+        targets: (targets || []).map(Number).filter(Number.isFinite).map(price => ({
+            price,
+            r: rMultiple(entry, stop, price),
+            pnl: positionPnl(meta, signedSize, entry, price) * rate
+        })),
+        meta,
+        warnings
+    };
+};
+
+module.exports = {
+    initializeSymbolMetaTable,
+    inferSymbolMeta,
+    getSymbolMeta,
+    saveSymbolMeta,
+    deleteSymbolMeta,
+    listSymbolMeta,
+    pointValue,
+    positionPnl,
+    rMultiple,
+    calcPositionSize
+};
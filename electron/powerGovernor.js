@@ -0,0 +1,80 @@
+
+// --- POWER-AWARE THROTTLING ---
+// Tracks battery / thermal / CPU speed-limit signals from Electron's
+// powerMonitor and turns them into one mode: 'normal' or 'saver'. Subsystems
+// read policy() instead of the raw signals, so what "saver" means lives here:
+// slower polling, no background index/cache building, no idle compaction and
+// backups held back until power returns. The user can pin a mode (override).
+
+const SAVER_POLL_SCALE = 4;
+const LOW_SPEED_LIMIT = 50; // percent; below this the OS is throttling the CPU
+
+const createPowerGovernor = ({ powerMonitor, getOverride = () => 'auto', onChange = () => {} }) => {
+    let signals = { onBattery: false, thermalState: 'unknown', speedLimit: 100 };
+    let current = null;
+
+    const readSignals = () => ({
+        ...signals,
+        onBattery: typeof powerMonitor.isOnBatteryPower === 'function' ? powerMonitor.isOnBatteryPower() : !!powerMonitor.onBatteryPower,
+        thermalState: typeof powerMonitor.getCurrentThermalState === 'function' ? powerMonitor.getCurrentThermalState() : signals.thermalState
+    });
+
+    const evaluate = () => {
+        const override = getOverride() || 'auto';
+        let mode = 'normal';
+        let reason = null;
+        if (override === 'normal' || override === 'saver') {
+            mode = override;
+            reason = 'override';
+        } else if (signals.onBattery) {
+            mode = 'saver';
+            reason = 'battery';
+        } else if (signals.thermalState === 'serious' || signals.thermalState === 'critical') {
+            mode = 'saver';
+            reason = 'thermal';
+        } else if (signals.speedLimit < LOW_SPEED_LIMIT) {
+            mode = 'saver';
+            reason = 'cpu-throttled';
+        }
+        return { mode, reason, override, ...signals };
+    };
+
+    const refresh = () => {
+        signals = readSignals();
+        const next = evaluate();
+        const changed = !current || current.mode !== next.mode || current.reason !== next.reason;
+        current = next;
+        if (changed) onChange(current);
+        return current;
+    };
+
+    // Must run after app 'ready' (powerMonitor is unavailable before)
+    const start = () => {
+        ['on-battery', 'on-ac'].forEach(name => powerMonitor.on(name, refresh));
+        powerMonitor.on('thermal-state-change', (event) => {
+            signals.thermalState = (event && event.state) || signals.thermalState;
+            refresh();
+        });
+        powerMonitor.on('speed-limit-change', (event) => {
+            signals.speedLimit = event && Number.isFinite(event.limit) ? event.limit : 100;
+            refresh();
+        });
+        return refresh();
+    };
+
+    const state = () => current || evaluate();
+
+    const policy = () => {
+        const saver = state().mode === 'saver';
+        return {
+            pollScale: saver ? SAVER_POLL_SCALE : 1,
+            backgroundIndexing: !saver,
+            idleCompaction: !saver,
+            deferBackups: saver
+        };
+    };
+
+    return { start, refresh, state, policy };
+};
+
+module.exports = { createPowerGovernor };
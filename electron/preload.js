@@ -39,30 +39,698 @@ try {
         getInternalFolders: () => ipcRenderer.invoke('get-internal-library'),
         
         // --- Data Ingestion ---
-        getMarketData: (symbol, timeframe, filePath, toTime, limit) => ipcRenderer.invoke('market:get-data', symbol, timeframe, filePath, toTime, limit),
+        getMarketData: (symbol, timeframe, filePath, toTime, limit, options) => ipcRenderer.invoke('market:get-data', symbol, timeframe, filePath, toTime, limit, options),
+        importMetaTraderHistory: (filePath, options) => ipcRenderer.invoke('market:import-metatrader', filePath, options),
+        probeMarketFile: (filePath) => ipcRenderer.invoke('market:probe-file', filePath),
+        readMarketFile: (filePath, options) => ipcRenderer.invoke('market:read-file', filePath, options),
+        buildTimeIndex: (filePath, options) => ipcRenderer.invoke('market:build-time-index', filePath, options),
+        deleteTimeIndex: (filePath) => ipcRenderer.invoke('market:delete-time-index', filePath),
+        listDatasets: () => ipcRenderer.invoke('datasets:list'),
+        getRowsPage: (datasetId, offset, limit, sort) => ipcRenderer.invoke('datasets:get-rows-page', datasetId, offset, limit, sort),
+        generateSyntheticSeries: (spec) => ipcRenderer.invoke('datasets:generate-synthetic', spec),
+        buildContinuousFutures: (spec, options) => ipcRenderer.invoke('datasets:build-continuous', spec, options),
+        rebuildContinuousFutures: (id) => ipcRenderer.invoke('datasets:rebuild-continuous', id),
+        mergeDatasets: (spec, options) => ipcRenderer.invoke('datasets:merge', spec, options),
+        rebuildMergedDataset: (id) => ipcRenderer.invoke('datasets:rebuild-merged', id),
+        editBar: (datasetId, timestamp, changes, options) => ipcRenderer.invoke('bars:edit', datasetId, timestamp, changes, options),
+        deleteBar: (datasetId, timestamp, options) => ipcRenderer.invoke('bars:delete', datasetId, timestamp, options),
+        revertBarEdit: (editId, options) => ipcRenderer.invoke('bars:revert', editId, options),
+        listBarEdits: (options) => ipcRenderer.invoke('bars:list-edits', options),
+        onBarsChanged: (callback) => {
+            const channel = 'datasets:bars-changed';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+
+        // --- Watch Folders ---
+        getWatchFolders: () => ipcRenderer.invoke('watch-folders:get-config'),
+        setWatchFolders: (rules) => ipcRenderer.invoke('watch-folders:set-config', rules),
+        rescanWatchFolders: () => ipcRenderer.invoke('watch-folders:rescan'),
+        getWatchImportHistory: (limit) => ipcRenderer.invoke('watch-folders:get-history', limit),
+
+        // --- Market Data Providers ---
+        listProviders: () => ipcRenderer.invoke('providers:list'),
+        getProviderSettings: (id) => ipcRenderer.invoke('providers:get-settings', id),
+        setProviderSettings: (id, values) => ipcRenderer.invoke('providers:set-settings', id, values),
+        connectProvider: (id, config) => ipcRenderer.invoke('providers:connect', id, config),
+        disconnectProvider: (id) => ipcRenderer.invoke('providers:disconnect', id),
+        getProviderStatus: (id) => ipcRenderer.invoke('providers:get-status', id),
+        fetchProviderHistory: (id, symbol, timeframe, options) => ipcRenderer.invoke('providers:fetch-history', id, symbol, timeframe, options),
+        subscribeProvider: (id, symbol, timeframe, options) => ipcRenderer.invoke('providers:subscribe', id, symbol, timeframe, options),
+        unsubscribeProvider: (id, symbol, timeframe) => ipcRenderer.invoke('providers:unsubscribe', id, symbol, timeframe),
+        searchProviderSymbols: (id, query) => ipcRenderer.invoke('providers:search-symbols', id, query),
+        searchSymbols: (query, options) => ipcRenderer.invoke('symbols:search', query, options),
+        getProviderHealth: () => ipcRenderer.invoke('providers:get-health'),
+        resetProviderHealth: (id) => ipcRenderer.invoke('providers:reset-health', id),
+        subscribeDepth: (providerId, symbol, options) => ipcRenderer.invoke('depth:subscribe', providerId, symbol, options),
+        unsubscribeDepth: (providerId, symbol) => ipcRenderer.invoke('depth:unsubscribe', providerId, symbol),
+        getDepthSnapshot: (providerId, symbol, levels) => ipcRenderer.invoke('depth:get-snapshot', providerId, symbol, levels),
+        listDepthBooks: () => ipcRenderer.invoke('depth:list'),
+        getOrderFlowConfig: () => ipcRenderer.invoke('orderflow:get-config'),
+        setOrderFlowConfig: (updates) => ipcRenderer.invoke('orderflow:set-config', updates),
+        getOrderFlowStatus: () => ipcRenderer.invoke('orderflow:get-status'),
+        getTradePrints: (symbol, options) => ipcRenderer.invoke('orderflow:get-prints', symbol, options),
+        getDeltaBars: (symbol, timeframe, options) => ipcRenderer.invoke('orderflow:get-delta', symbol, timeframe, options),
+        getFootprint: (symbol, timeframe, options) => ipcRenderer.invoke('orderflow:get-footprint', symbol, timeframe, options),
+        downloadHistory: (symbol, range, interval) => ipcRenderer.invoke('market:download-history', symbol, range, interval),
+        getFredSeries: (seriesId, options) => ipcRenderer.invoke('fred:get-series', seriesId, options),
+
+        // --- News ---
+        getNews: (symbol, since) => ipcRenderer.invoke('news:get', symbol, since),
+        getNewsConfig: () => ipcRenderer.invoke('news:get-config'),
+        configureNews: (updates) => ipcRenderer.invoke('news:configure', updates),
+        refreshNews: () => ipcRenderer.invoke('news:refresh'),
+
+        // --- Alerts ---
+        listAlerts: (symbol) => ipcRenderer.invoke('alerts:list', symbol),
+        saveAlert: (alert) => ipcRenderer.invoke('alerts:save', alert),
+        validateAlert: (alert) => ipcRenderer.invoke('alerts:validate', alert),
+        evaluateReplayAlerts: (symbol, timeframe, timestamp) => ipcRenderer.invoke('alerts:evaluate-replay', symbol, timeframe, timestamp),
+        deleteAlert: (id) => ipcRenderer.invoke('alerts:delete', id),
+        exportAlerts: (filePath, ids) => ipcRenderer.invoke('alerts:export', filePath, ids),
+        importAlerts: (filePath, options) => ipcRenderer.invoke('alerts:import', filePath, options),
+        exportWatchlist: (items, filePath, options) => ipcRenderer.invoke('watchlist:export', items, filePath, options),
+        importWatchlist: (filePath, existing) => ipcRenderer.invoke('watchlist:import', filePath, existing),
+        getAlertWebhookConfig: () => ipcRenderer.invoke('alerts:get-webhook-config'),
+        setAlertWebhookConfig: (config) => ipcRenderer.invoke('alerts:set-webhook-config', config),
+        getNotifierConfig: () => ipcRenderer.invoke('notifiers:get-config'),
+        setNotifierConfig: (config) => ipcRenderer.invoke('notifiers:set-config', config),
+        sendTestNotification: (channel) => ipcRenderer.invoke('notifiers:send-test', channel),
+
+        // --- Audio ---
+        playSound: (soundId) => ipcRenderer.invoke('audio:play', soundId),
+        listSounds: () => ipcRenderer.invoke('audio:list-sounds'),
+        importSound: (filePath) => ipcRenderer.invoke('audio:import-sound', filePath),
+        deleteSound: (soundId) => ipcRenderer.invoke('audio:delete-sound', soundId),
+
+        // --- Compute ---
+        computeIndicatorsBulk: (jobs) => ipcRenderer.invoke('compute:indicators-bulk', jobs),
+        validateSeries: (source) => ipcRenderer.invoke('compute:validate-series', source),
+        getComputeConfig: () => ipcRenderer.invoke('compute:get-config'),
+        setComputeConfig: (config) => ipcRenderer.invoke('compute:set-config', config),
+        getComputeCacheStats: () => ipcRenderer.invoke('compute:cache-stats'),
+        listComputeCache: (options) => ipcRenderer.invoke('compute:cache-list', options),
+        invalidateComputeCache: (options) => ipcRenderer.invoke('compute:cache-invalidate', options),
+        subscribeIndicator: (datasetId, indicator, params) => ipcRenderer.invoke('compute:subscribe-indicator', datasetId, indicator, params),
+        unsubscribeIndicator: (subscriptionId) => ipcRenderer.invoke('compute:unsubscribe-indicator', subscriptionId),
+        onIndicatorUpdate: (callback) => {
+            const channel = 'compute:indicator-update';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+        getCrosshairContext: (symbol, timestamp, options) => ipcRenderer.invoke('crosshair:get-context', symbol, timestamp, options),
+        subscribeCrosshair: (symbol, options) => ipcRenderer.invoke('crosshair:subscribe', symbol, options),
+        moveCrosshair: (subscriptionId, timestamp) => ipcRenderer.send('crosshair:move', subscriptionId, timestamp),
+        unsubscribeCrosshair: (subscriptionId) => ipcRenderer.invoke('crosshair:unsubscribe', subscriptionId),
+        onCrosshairContext: (callback) => {
+            const channel = 'crosshair:context';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+
+        // --- Search ---
+        globalSearch: (query, options) => ipcRenderer.invoke('search:global', query, options),
+        getQuickSwitchCandidates: (prefix, options) => ipcRenderer.invoke('quick-switch:get-candidates', prefix, options),
+        recordQuickSwitchOpen: (kind, ref) => ipcRenderer.invoke('quick-switch:record', kind, ref),
+        clearQuickSwitchHistory: () => ipcRenderer.invoke('quick-switch:clear-history'),
+        getActivityTimeline: (range) => ipcRenderer.invoke('activity:get-timeline', range),
+
+        // --- Plugin panels (sandboxed windows, see pluginSandbox.js) ---
+        listPluginPanels: () => ipcRenderer.invoke('plugins:list-panels'),
+        setPluginGrants: (pluginId, capabilities) => ipcRenderer.invoke('plugins:set-grants', pluginId, capabilities),
+        openPluginPanel: (pluginId) => ipcRenderer.invoke('plugins:open-panel', pluginId),
+        closePluginPanel: (pluginId) => ipcRenderer.invoke('plugins:close-panel', pluginId),
+        onPluginNotification: (callback) => {
+            const channel = 'plugins:notification';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+        searchChartAnnotations: (query, options) => ipcRenderer.invoke('annotations:search', query, options),
+        rebuildAnnotationIndex: () => ipcRenderer.invoke('annotations:reindex'),
+        // Dataset annotations: attached to (symbol, time), shown on every chart of the symbol
+        listDatasetAnnotations: (symbol, options) => ipcRenderer.invoke('dataset-annotations:list', symbol, options),
+        projectDatasetAnnotations: (symbol, timeframe, options) => ipcRenderer.invoke('dataset-annotations:project', symbol, timeframe, options),
+        createDatasetAnnotation: (input) => ipcRenderer.invoke('dataset-annotations:create', input),
+        updateDatasetAnnotation: (id, patch) => ipcRenderer.invoke('dataset-annotations:update', id, patch),
+        deleteDatasetAnnotation: (id) => ipcRenderer.invoke('dataset-annotations:delete', id),
+        onDatasetAnnotationsChanged: (callback) => {
+            const channel = 'dataset-annotations:changed';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+
+        // --- Secrets (write-only from the renderer) ---
+        setSecret: (key, value, options) => ipcRenderer.invoke('secrets:set', key, value, options),
+        deleteSecret: (key) => ipcRenderer.invoke('secrets:delete', key),
+        listSecrets: () => ipcRenderer.invoke('secrets:list'),
+        setSecretMeta: (key, updates) => ipcRenderer.invoke('secrets:set-meta', key, updates),
+        testCredentials: (provider) => ipcRenderer.invoke('secrets:test-credentials', provider),
+        getExpiringSecrets: (withinDays) => ipcRenderer.invoke('secrets:get-expiring', withinDays),
+        onSecretsExpiring: (callback) => {
+            const channel = 'secrets:expiring';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+
+        // --- Proxy ---
+        getProxyConfig: () => ipcRenderer.invoke('proxy:get-config'),
+        setProxyConfig: (config, password) => ipcRenderer.invoke('proxy:set-config', config, password),
+        testProxy: (config, password, url) => ipcRenderer.invoke('proxy:test', config, password, url),
+
+        // --- TLS Policy ---
+        getTlsPolicy: () => ipcRenderer.invoke('tls:get-policy'),
+        setTlsPolicy: (policy) => ipcRenderer.invoke('tls:set-policy', policy),
+        inspectCertificates: (url) => ipcRenderer.invoke('tls:inspect', url),
 
         // --- Persistence ---
         loadMasterDrawings: () => ipcRenderer.invoke('master-drawings:load'),
-        getDrawingsState: (symbol) => ipcRenderer.invoke('drawings:get-state', symbol),
-        saveDrawingState: (symbol, data) => ipcRenderer.invoke('drawings:save-state', symbol, data),
+        getDrawingsState: (symbol, options) => ipcRenderer.invoke('drawings:get-state', symbol, options),
+        saveDrawingState: (symbol, data, options) => ipcRenderer.invoke('drawings:save-state', symbol, data, options),
+        patchDrawingState: (sourceId, ops, options) => ipcRenderer.invoke('drawings:patch-state', sourceId, ops, options),
+        setDrawingsLocked: (sourceId, drawingIds, locked) => ipcRenderer.invoke('drawings:set-locked', sourceId, drawingIds, locked),
+        setDrawingGroup: (sourceId, drawingIds, group) => ipcRenderer.invoke('drawings:set-group', sourceId, drawingIds, group),
+        setDrawingGroupLocked: (sourceId, groupId, locked) => ipcRenderer.invoke('drawings:set-group-locked', sourceId, groupId, locked),
+        getChartThumbnail: (sourceId) => ipcRenderer.invoke('drawings:get-thumbnail', sourceId),
+        undoChartEdit: (sourceId) => ipcRenderer.invoke('drawings:undo', sourceId),
+        redoChartEdit: (sourceId) => ipcRenderer.invoke('drawings:redo', sourceId),
+        getChartJournal: (sourceId, options) => ipcRenderer.invoke('drawings:get-journal', sourceId, options),
         deleteAllDrawings: (sourceId) => ipcRenderer.invoke('drawings:delete-all', sourceId),
 
+        // --- Cross-Window Sync ---
+        publishSyncEvent: (topic, payload) => ipcRenderer.invoke('sync:publish', topic, payload),
+        getSyncEventsSince: (seq, topics) => ipcRenderer.invoke('sync:get-since', seq, topics),
+        getSyncSeq: () => ipcRenderer.invoke('sync:get-seq'),
+
+        // --- Sticky Notes ---
+        loadStickyNotes: (boardId) => ipcRenderer.invoke('notes:load', boardId),
+        bulkUpdateNotes: (ops) => ipcRenderer.invoke('notes:bulk-update', ops),
+        archiveNotes: (ids, archived) => ipcRenderer.invoke('notes:archive', ids, archived),
+        searchStickyNotes: (query, options) => ipcRenderer.invoke('notes:search', query, options),
+        getNoteHistory: (noteId) => ipcRenderer.invoke('notes:get-history', noteId),
+        restoreNoteVersion: (noteId, version) => ipcRenderer.invoke('notes:restore-version', noteId, version),
+        getNoteAutoArchive: () => ipcRenderer.invoke('notes:get-auto-archive'),
+        setNoteAutoArchive: (updates) => ipcRenderer.invoke('notes:set-auto-archive', updates),
+        listNoteTemplates: () => ipcRenderer.invoke('notes:list-templates'),
+        saveNoteTemplate: (template) => ipcRenderer.invoke('notes:save-template', template),
+        deleteNoteTemplate: (id) => ipcRenderer.invoke('notes:delete-template', id),
+        createNoteFromTemplate: (templateId, vars, options) => ipcRenderer.invoke('notes:create-from-template', templateId, vars, options),
+
+        // --- Attachments ---
+        pasteImageAttachment: (options) => ipcRenderer.invoke('attachments:paste-image', options),
+        getAttachment: (id) => ipcRenderer.invoke('attachments:get', id),
+        listAttachments: (options) => ipcRenderer.invoke('attachments:list', options),
+        deleteAttachment: (id) => ipcRenderer.invoke('attachments:delete', id),
+        pruneAttachments: () => ipcRenderer.invoke('attachments:prune'),
+        captureWindow: (label, options) => ipcRenderer.invoke('capture:window', label, options),
+        captureRegion: (rect, options) => ipcRenderer.invoke('capture:region', rect, options),
+        listNoteBoards: (options) => ipcRenderer.invoke('notes:list-boards', options),
+        createNoteBoard: (name) => ipcRenderer.invoke('notes:create-board', name),
+        renameNoteBoard: (id, name) => ipcRenderer.invoke('notes:rename-board', id, name),
+        archiveNoteBoard: (id, archived) => ipcRenderer.invoke('notes:archive-board', id, archived),
+
+        importTradingViewDrawings: (filePath) => ipcRenderer.invoke('drawings:import-tradingview', filePath),
+        exportInk: (symbol, drawingIds, format, filePath, options) => ipcRenderer.invoke('drawings:export-ink', symbol, drawingIds, format, filePath, options),
+        exportNoteInk: (noteId, format, filePath, options) => ipcRenderer.invoke('notes:export-ink', noteId, format, filePath, options),
+        exportChartState: (sourceId, format, filePath) => ipcRenderer.invoke('drawings:export-state', sourceId, format, filePath),
+        shareChart: (sourceId, options) => ipcRenderer.invoke('share:export-chart', sourceId, options),
+        openSharedChart: (filePath, password) => ipcRenderer.invoke('share:open', filePath, password),
+        findOrphanedChartStates: (knownNames) => ipcRenderer.invoke('drawings:find-orphaned', knownNames),
+        trashOrphanedChartStates: (symbols, knownNames) => ipcRenderer.invoke('drawings:trash-orphaned', symbols, knownNames),
+        listChartTrash: () => ipcRenderer.invoke('drawings:list-trash'),
+        restoreChartStates: (symbols) => ipcRenderer.invoke('drawings:restore-from-trash', symbols),
+        emptyChartTrash: (olderThanDays) => ipcRenderer.invoke('drawings:empty-trash', olderThanDays),
+
+        // --- Drawing Templates ---
+        saveDrawingTemplate: (name, objects, description) => ipcRenderer.invoke('templates:save', name, objects, description),
+        listDrawingTemplates: () => ipcRenderer.invoke('templates:list'),
+        getDrawingTemplate: (name) => ipcRenderer.invoke('templates:get', name),
+        deleteDrawingTemplate: (name) => ipcRenderer.invoke('templates:delete', name),
+        applyDrawingTemplate: (sourceId, name, options) => ipcRenderer.invoke('templates:apply', sourceId, name, options),
+
+        // --- Keybindings ---
+        getKeybindings: () => ipcRenderer.invoke('keybindings:get'),
+        setKeybinding: (action, accelerator) => ipcRenderer.invoke('keybindings:set', action, accelerator),
+        resetKeybindings: (action) => ipcRenderer.invoke('keybindings:reset', action),
+        setLiveFeedPaused: (paused) => ipcRenderer.invoke('live-feed:set-paused', paused),
+
+        // --- Background Mode ---
+        getBackgroundStatus: () => ipcRenderer.invoke('background:get-status'),
+        getBackgroundConfig: () => ipcRenderer.invoke('background:get-config'),
+        setBackgroundConfig: (updates) => ipcRenderer.invoke('background:set-config', updates),
+
+        // --- Power ---
+        getPowerState: () => ipcRenderer.invoke('power:get-state'),
+        setPowerOverride: (override) => ipcRenderer.invoke('power:set-override', override),
+        onPowerStateChanged: (callback) => {
+            const channel = 'power:state-changed';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+
+        // --- Signal inbox ---
+        getInboxStatus: () => ipcRenderer.invoke('inbox:get-status'),
+        setInboxConfig: (patch) => ipcRenderer.invoke('inbox:set-config', patch),
+        rotateInboxToken: () => ipcRenderer.invoke('inbox:rotate-token'),
+        listSignals: (options) => ipcRenderer.invoke('inbox:list', options),
+        markSignalsRead: (ids) => ipcRenderer.invoke('inbox:mark-read', ids),
+        deleteSignals: (ids) => ipcRenderer.invoke('inbox:delete', ids),
+        getCompanionStatus: () => ipcRenderer.invoke('companion:get-status'),
+        setCompanionConfig: (patch) => ipcRenderer.invoke('companion:set-config', patch),
+        onSignalsReceived: (callback) => {
+            const channel = 'inbox:signals';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+
+        // --- Clipboard tickers ---
+        getClipboardWatchConfig: () => ipcRenderer.invoke('clipboard:get-watch-config'),
+        setClipboardWatchConfig: (patch) => ipcRenderer.invoke('clipboard:set-watch-config', patch),
+        onSymbolCopied: (callback) => {
+            const channel = 'clipboard:symbol-copied';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+
+        // --- Idle / auto-lock ---
+        getIdleState: () => ipcRenderer.invoke('idle:get-state'),
+        setIdleConfig: (patch) => ipcRenderer.invoke('idle:set-config', patch),
+        lockWorkspace: () => ipcRenderer.invoke('idle:lock'),
+        unlockWorkspace: () => ipcRenderer.invoke('idle:unlock'),
+        onUserAway: (callback) => {
+            const channel = 'idle:away';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+        onUserReturned: (callback) => {
+            const channel = 'idle:returned';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+        onWorkspaceLocked: (callback) => {
+            const channel = 'idle:locked';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+        onWorkspaceUnlocked: (callback) => {
+            const channel = 'idle:unlocked';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+
+        // --- Background jobs ---
+        getBackgroundJobs: () => ipcRenderer.invoke('background:get-jobs'),
+        cancelBackgroundJob: (id) => ipcRenderer.invoke('background:cancel-job', id),
+        onThumbnailsMissing: (callback) => {
+            const channel = 'thumbnails:missing';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+
+        // --- Appearance ---
+        getAppearance: () => ipcRenderer.invoke('appearance:get-state'),
+        setAppearanceConfig: (patch) => ipcRenderer.invoke('appearance:set-config', patch),
+        onAppearanceChanged: (callback) => {
+            const channel = 'appearance:changed';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+
+        // --- Scheduler ---
+        listScheduledJobs: () => ipcRenderer.invoke('scheduler:list-jobs'),
+        createScheduledJob: (job) => ipcRenderer.invoke('scheduler:create-job', job),
+        updateScheduledJob: (id, updates) => ipcRenderer.invoke('scheduler:update-job', id, updates),
+        deleteScheduledJob: (id) => ipcRenderer.invoke('scheduler:delete-job', id),
+        runScheduledJobNow: (id) => ipcRenderer.invoke('scheduler:run-now', id),
+        getScheduledJobRuns: (jobId, limit) => ipcRenderer.invoke('scheduler:get-runs', jobId, limit),
+        runEndOfDay: (params) => ipcRenderer.invoke('maintenance:run-end-of-day', params),
+        getEndOfDay: () => ipcRenderer.invoke('maintenance:get-end-of-day'),
+
+        // --- Historical Downloads ---
+        listDownloads: () => ipcRenderer.invoke('downloads:list'),
+        enqueueDownloads: (specs) => ipcRenderer.invoke('downloads:enqueue', specs),
+        pauseDownload: (id) => ipcRenderer.invoke('downloads:pause', id),
+        resumeDownload: (id) => ipcRenderer.invoke('downloads:resume', id),
+        cancelDownload: (id) => ipcRenderer.invoke('downloads:cancel', id),
+        removeDownload: (id) => ipcRenderer.invoke('downloads:remove', id),
+        clearFinishedDownloads: () => ipcRenderer.invoke('downloads:clear-finished'),
+        setDownloadConfig: (updates) => ipcRenderer.invoke('downloads:set-config', updates),
+        onDownloadProgress: (callback) => {
+            const channel = 'downloads:progress';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+
+        // --- Presentation Mode ---
+        enterPresentationMode: (label, options) => ipcRenderer.invoke('presentation:enter', label, options),
+        exitPresentationMode: () => ipcRenderer.invoke('presentation:exit'),
+        getPresentationStatus: () => ipcRenderer.invoke('presentation:get-status'),
+
+        // --- Command Palette Actions ---
+        listActions: () => ipcRenderer.invoke('actions:list'),
+        executeAction: (id, args) => ipcRenderer.invoke('actions:execute', id, args),
+
+        // --- Localization ---
+        getLocaleStrings: (lang) => ipcRenderer.invoke('i18n:get-locale-strings', lang),
+        listLanguages: () => ipcRenderer.invoke('i18n:list-languages'),
+        setLanguage: (lang) => ipcRenderer.invoke('i18n:set-language', lang),
+
+        // --- Themes ---
+        listThemes: () => ipcRenderer.invoke('themes:list'),
+        getActiveTheme: () => ipcRenderer.invoke('themes:get-active'),
+        saveTheme: (theme) => ipcRenderer.invoke('themes:save', theme),
+        deleteTheme: (name) => ipcRenderer.invoke('themes:delete', name),
+        applyTheme: (name) => ipcRenderer.invoke('themes:apply', name),
+        exportTheme: (name, filePath) => ipcRenderer.invoke('themes:export', name, filePath),
+        importTheme: (filePath) => ipcRenderer.invoke('themes:import', filePath),
+        onThemeApplied: (callback) => {
+            const channel = 'themes:applied';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+
+        // --- Playback Export ---
+        exportPlayback: (datasetId, range, options) => ipcRenderer.invoke('playback:export', datasetId, range, options),
+        onPlaybackExportProgress: (callback) => {
+            const channel = 'playback:export-progress';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+
         // --- Layouts ---
         saveLayout: (name, data) => ipcRenderer.invoke('layouts:save', name, data),
         loadLayout: (name) => ipcRenderer.invoke('layouts:load', name),
         listLayouts: () => ipcRenderer.invoke('layouts:list'),
 
+        // --- Onboarding ---
+        getOnboardingState: () => ipcRenderer.invoke('onboarding:get-state'),
+        completeOnboardingStep: (stepId, options) => ipcRenderer.invoke('onboarding:complete-step', stepId, options),
+        resetOnboarding: () => ipcRenderer.invoke('onboarding:reset'),
+        onOnboardingChanged: (callback) => {
+            const channel = 'onboarding:changed';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+        getExternalChangeStatus: () => ipcRenderer.invoke('database:get-external-changes'),
+        checkExternalChanges: () => ipcRenderer.invoke('database:check-external'),
+        onExternalChange: (callback) => {
+            const channel = 'database:external-change';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+        onOpenFile: (callback) => {
+            const channel = 'app:open-file';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+        installDemoContent: () => ipcRenderer.invoke('demo:install'),
+        onDemoInstalled: (callback) => {
+            const channel = 'demo:installed';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+
+        // --- Settings profile ---
+        exportSettings: (filePath, options) => ipcRenderer.invoke('settings:export', filePath, options),
+        importSettings: (filePath, options) => ipcRenderer.invoke('settings:import', filePath, options),
+
         // --- Trades ---
         getTradesBySource: (sourceId) => ipcRenderer.invoke('trades:get-ledger', sourceId),
         saveTrade: (trade) => ipcRenderer.invoke('trades:save', trade),
+        importBrokerStatement: (filePath, options) => ipcRenderer.invoke('trades:import-statement', filePath, options),
+
+        // --- Paper Trading ---
+        placeSimOrder: (order) => ipcRenderer.invoke('sim:place-order', order),
+        cancelSimOrder: (id) => ipcRenderer.invoke('sim:cancel-order', id),
+        pushSimPrice: (symbol, price, timestamp) => ipcRenderer.invoke('sim:push-price', symbol, price, timestamp),
+        listSimOrders: (options) => ipcRenderer.invoke('sim:list-orders', options),
+        listSimFills: (options) => ipcRenderer.invoke('sim:list-fills', options),
+        getSimAccount: (id) => ipcRenderer.invoke('sim:get-account', id),
+        listSimAccounts: () => ipcRenderer.invoke('sim:list-accounts'),
+        createSimAccount: (options) => ipcRenderer.invoke('sim:create-account', options),
+        updateSimSettings: (id, settings) => ipcRenderer.invoke('sim:update-settings', id, settings),
+        resetSimAccount: (id, startingCash) => ipcRenderer.invoke('sim:reset-account', id, startingCash),
+        onSimFill: (callback) => {
+            const channel = 'sim:fill';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+        onSimOrder: (callback) => {
+            const channel = 'sim:order';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+
+        // --- Position Sizing ---
+        getSymbolMeta: (symbol) => ipcRenderer.invoke('symbols:get-meta', symbol),
+        listSymbolMeta: () => ipcRenderer.invoke('symbols:list-meta'),
+        setSymbolMeta: (symbol, meta) => ipcRenderer.invoke('symbols:set-meta', symbol, meta),
+        deleteSymbolMeta: (symbol) => ipcRenderer.invoke('symbols:delete-meta', symbol),
+        getDossier: (symbol) => ipcRenderer.invoke('dossiers:get', symbol),
+        listDossiers: () => ipcRenderer.invoke('dossiers:list'),
+        updateDossier: (symbol, patch, options) => ipcRenderer.invoke('dossiers:update', symbol, patch, options),
+        deleteDossier: (symbol) => ipcRenderer.invoke('dossiers:delete', symbol),
+        onDossierChanged: (callback) => {
+            const channel = 'dossiers:changed';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+        calcPositionSize: (request) => ipcRenderer.invoke('risk:calc-position-size', request),
+
+        // --- Portfolio Analytics ---
+        getPortfolioStats: (filter) => ipcRenderer.invoke('portfolio:get-stats', filter),
+        runMonteCarlo: (input, iterations, params) => ipcRenderer.invoke('portfolio:monte-carlo', input, iterations, params),
+        exportPortfolioReport: (filter, options) => ipcRenderer.invoke('portfolio:export-report', filter, options),
+        listLiveExports: () => ipcRenderer.invoke('live-export:list'),
+        saveLiveExport: (def) => ipcRenderer.invoke('live-export:save', def),
+        deleteLiveExport: (id) => ipcRenderer.invoke('live-export:delete', id),
+        runLiveExportNow: (id) => ipcRenderer.invoke('live-export:run-now', id),
+
+        // --- Printing ---
+        printDocument: (kind, id, options) => ipcRenderer.invoke('print:document', kind, id, options),
+        listPrinters: () => ipcRenderer.invoke('print:list-printers'),
+
+        // --- Export templates ---
+        listExportTemplates: (options) => ipcRenderer.invoke('exports:list-templates', options),
+        saveExportTemplate: (template) => ipcRenderer.invoke('exports:save-template', template),
+        deleteExportTemplate: (id) => ipcRenderer.invoke('exports:delete-template', id),
+        previewExport: (template, query) => ipcRenderer.invoke('exports:preview', template, query),
+        renderExport: (template, query, filePath) => ipcRenderer.invoke('exports:render', template, query, filePath),
+
+        // --- Options ---
+        priceOption: (input) => ipcRenderer.invoke('options:price', input),
+        computeChainGreeks: (chain, options) => ipcRenderer.invoke('options:chain-greeks', chain, options),
+
+        // --- FX Rates ---
+        getFxConfig: () => ipcRenderer.invoke('fx:get-config'),
+        setFxConfig: (updates) => ipcRenderer.invoke('fx:set-config', updates),
+        listFxRates: (filter) => ipcRenderer.invoke('fx:list-rates', filter),
+        setFxRate: (base, quote, rate, day) => ipcRenderer.invoke('fx:set-rate', base, quote, rate, day),
+        deleteFxRate: (base, quote, day) => ipcRenderer.invoke('fx:delete-rate', base, quote, day),
+        getFxRate: (from, to, timestamp) => ipcRenderer.invoke('fx:get-rate', from, to, timestamp),
+        convertCurrency: (amount, from, to, timestamp) => ipcRenderer.invoke('fx:convert', amount, from, to, timestamp),
+
+        // --- Session Statistics ---
+        listSessionCalendars: () => ipcRenderer.invoke('sessions:list-calendars'),
+        getSessionStats: (datasetId, options) => ipcRenderer.invoke('sessions:get-stats', datasetId, options),
+        getExtendedHoursLevels: (datasetId, options) => ipcRenderer.invoke('sessions:get-extended-levels', datasetId, options),
+        getSessionLevelConfig: () => ipcRenderer.invoke('session-levels:get-config'),
+        setSessionLevelConfig: (updates) => ipcRenderer.invoke('session-levels:set-config', updates),
+        syncSessionLevels: (sourceId, datasetId, options) => ipcRenderer.invoke('session-levels:sync', sourceId, datasetId, options),
+        computeSeasonality: (datasetId, grouping, options) => ipcRenderer.invoke('seasonality:compute', datasetId, grouping, options),
+        computeRelative: (datasetId, benchmarkId, mode, options) => ipcRenderer.invoke('relative:compute', datasetId, benchmarkId, mode, options),
+
+        // --- Economic Calendar ---
+        getEconomicEvents: (filter) => ipcRenderer.invoke('calendar:get-events', filter),
+        importEconomicCalendar: (filePath, options) => ipcRenderer.invoke('calendar:import-file', filePath, options),
+        refreshEconomicCalendar: () => ipcRenderer.invoke('calendar:refresh'),
+        deleteEconomicEvents: (filter) => ipcRenderer.invoke('calendar:delete-events', filter),
+        getEconomicCalendarConfig: () => ipcRenderer.invoke('calendar:get-config'),
+        configureEconomicCalendar: (updates) => ipcRenderer.invoke('calendar:configure', updates),
+        exportICalendar: (kinds, target, options) => ipcRenderer.invoke('ical:export', kinds, target, options),
+        getICalSubscription: () => ipcRenderer.invoke('ical:get-subscription'),
+        unsubscribeICalendar: () => ipcRenderer.invoke('ical:unsubscribe'),
+        onEconomicEventWarning: (callback) => {
+            const subscription = (_event, value) => callback(value);
+            ipcRenderer.on('calendar:event-warning', subscription);
+            return () => ipcRenderer.removeListener('calendar:event-warning', subscription);
+        },
+        onEconomicCalendarUpdated: (callback) => {
+            const subscription = (_event, value) => callback(value);
+            ipcRenderer.on('calendar:updated', subscription);
+            return () => ipcRenderer.removeListener('calendar:updated', subscription);
+        },
+
+        // --- Scanner ---
+        validateExpression: (expression) => ipcRenderer.invoke('scanner:validate-expression', expression),
+        listScreens: () => ipcRenderer.invoke('scanner:list-screens'),
+        saveScreen: (screen) => ipcRenderer.invoke('scanner:save-screen', screen),
+        deleteScreen: (id) => ipcRenderer.invoke('scanner:delete-screen', id),
+        runScreen: (target) => ipcRenderer.invoke('scanner:run', target),
+        onScanResult: (callback) => {
+            const subscription = (_event, value) => callback(value);
+            ipcRenderer.on('scanner:result', subscription);
+            return () => ipcRenderer.removeListener('scanner:result', subscription);
+        },
+
+        // --- Heatmap ---
+        validateHeatmapScript: (script) => ipcRenderer.invoke('heatmap:validate-script', script),
+        computeHeatmap: (symbols, metric, range, options) => ipcRenderer.invoke('heatmap:compute', symbols, metric, range, options),
+        getHeatmap: (id) => ipcRenderer.invoke('heatmap:get', id),
+        stopHeatmap: (id) => ipcRenderer.invoke('heatmap:stop', id),
+        listHeatmaps: () => ipcRenderer.invoke('heatmap:list'),
+        onHeatmapUpdate: (callback) => {
+            const subscription = (_event, value) => callback(value);
+            ipcRenderer.on('heatmap:update', subscription);
+            return () => ipcRenderer.removeListener('heatmap:update', subscription);
+        },
+
+        // --- Broker Orders ---
+        listBrokers: () => ipcRenderer.invoke('brokers:list'),
+        configureBroker: (id, config) => ipcRenderer.invoke('brokers:configure', id, config),
+        testBrokerCredentials: (id) => ipcRenderer.invoke('brokers:test-credentials', id),
+        placeOrder: (brokerId, order) => ipcRenderer.invoke('orders:place', brokerId, order),
+        modifyOrder: (brokerId, id, changes) => ipcRenderer.invoke('orders:modify', brokerId, id, changes),
+        cancelOrder: (brokerId, id) => ipcRenderer.invoke('orders:cancel', brokerId, id),
+        listOrders: (brokerId, options) => ipcRenderer.invoke('orders:list', brokerId, options),
+        startOrderUpdates: (brokerId) => ipcRenderer.invoke('orders:start-updates', brokerId),
+        stopOrderUpdates: (brokerId) => ipcRenderer.invoke('orders:stop-updates', brokerId),
+        onOrderUpdate: (callback) => {
+            const channel = 'orders:update';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
 
         // --- Logs & Diagnostics ---
         getDbStatus: () => ipcRenderer.invoke('logs:get-db-status'),
         sendLog: (category, message, data) => ipcRenderer.send('log:send', category, message, data),
         getSystemTelemetry: () => ipcRenderer.invoke('get-system-telemetry'),
+
+        // --- Shutdown ---
+        // callback (may be async) saves anything pending; quitting waits for it (up to a few seconds)
+        onShutdownFlush: (callback) => {
+            const channel = 'app:shutdown-flush';
+            const subscription = async (event, token) => {
+                try { await callback(); } finally { ipcRenderer.send('app:shutdown-flushed', token); }
+            };
+            ipcRenderer.on(channel, subscription);
+            ipcRenderer.send('app:shutdown-flush-register', 1);
+            return () => {
+                ipcRenderer.removeListener(channel, subscription);
+                ipcRenderer.send('app:shutdown-flush-register', -1);
+            };
+        },
+        onShutdownProgress: (callback) => {
+            const channel = 'app:shutdown-progress';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+        forceQuit: () => ipcRenderer.invoke('app:force-quit'),
         
         // --- NEW: Global State Explorer ---
         getGlobalState: () => ipcRenderer.invoke('debug:get-global-state'),
+        getPerfMetrics: (options) => ipcRenderer.invoke('debug:get-perf-metrics', options),
+        resetPerfMetrics: () => ipcRenderer.invoke('debug:reset-perf-metrics'),
+        getMemoryReport: () => ipcRenderer.invoke('perf:memory-report'),
+        benchmarkStorage: (options) => ipcRenderer.invoke('debug:benchmark-storage', options),
+        benchmarkParse: (options) => ipcRenderer.invoke('debug:benchmark-parse', options),
+        // Only answered when the app runs with --test-mode
+        testHarness: {
+            getInfo: () => ipcRenderer.invoke('test:get-info'),
+            listChannels: () => ipcRenderer.invoke('test:list-channels'),
+            setClock: (value) => ipcRenderer.invoke('test:set-clock', value),
+            advanceClock: (ms) => ipcRenderer.invoke('test:advance-clock', ms),
+            getLog: (options) => ipcRenderer.invoke('test:get-log', options),
+            query: (sql, params) => ipcRenderer.invoke('test:query', sql, params),
+            pushBar: (symbol, timeframe, bar, isClosed) => ipcRenderer.invoke('test:push-bar', symbol, timeframe, bar, isClosed),
+        },
+        runDiagnostics: () => ipcRenderer.invoke('diagnostics:run'),
+        createSupportBundle: (filePath) => ipcRenderer.invoke('diagnostics:create-support-bundle', filePath),
+        getIntegrityReport: () => ipcRenderer.invoke('integrity:get-report'),
+        runIntegrityScan: () => ipcRenderer.invoke('integrity:run'),
+        listIntegrityQuarantine: () => ipcRenderer.invoke('integrity:list-quarantine'),
+        onIntegrityScanComplete: (callback) => {
+            const channel = 'integrity:scan-complete';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+        listCrashReports: () => ipcRenderer.invoke('crash:list-reports'),
+        previewCrashReport: (id) => ipcRenderer.invoke('crash:preview-report', id),
+        submitCrashReport: (id) => ipcRenderer.invoke('crash:submit-report', id),
+        deleteCrashReport: (id) => ipcRenderer.invoke('crash:delete-report', id),
+        getCrashReportConfig: () => ipcRenderer.invoke('crash:get-config'),
+        configureCrashReports: (updates) => ipcRenderer.invoke('crash:configure', updates),
+
+        // --- Usage Analytics ---
+        recordUsage: (feature) => ipcRenderer.invoke('usage:record', feature),
+        getUsageSummary: (options) => ipcRenderer.invoke('usage:get-summary', options),
+        previewUsageUpload: () => ipcRenderer.invoke('usage:preview'),
+        getUsageConfig: () => ipcRenderer.invoke('usage:get-config'),
+        configureUsage: (updates) => ipcRenderer.invoke('usage:configure', updates),
+        uploadUsageNow: () => ipcRenderer.invoke('usage:upload-now'),
+        resetUsageInstallId: () => ipcRenderer.invoke('usage:reset-install-id'),
+        clearUsage: () => ipcRenderer.invoke('usage:clear'),
+
+        // --- Read-Only Mode ---
+        getReadOnly: () => ipcRenderer.invoke('app:get-read-only'),
+        setReadOnly: (enabled) => ipcRenderer.invoke('app:set-read-only', enabled),
+        onReadOnlyChanged: (callback) => {
+            const channel = 'app:read-only-changed';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+
+        getWorkspaceLock: () => ipcRenderer.invoke('workspace:get-lock'),
+        takeOverWorkspace: () => ipcRenderer.invoke('workspace:take-over'),
+        onWorkspaceLockLost: (callback) => {
+            const channel = 'workspace:lock-lost';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+
+        // --- Profiles ---
+        listProfiles: () => ipcRenderer.invoke('profiles:list'),
+        createProfile: (name) => ipcRenderer.invoke('profiles:create', name),
+        renameProfile: (id, name) => ipcRenderer.invoke('profiles:rename', id, name),
+        deleteProfile: (id) => ipcRenderer.invoke('profiles:delete', id),
+        switchProfile: (id) => ipcRenderer.invoke('profiles:switch', id),
+        configureProfiles: (updates) => ipcRenderer.invoke('profiles:configure', updates),
+
+        getStorageReport: () => ipcRenderer.invoke('storage:get-report'),
+        purgeCaches: (targets) => ipcRenderer.invoke('storage:purge-caches', targets),
+        deleteUnusedSounds: () => ipcRenderer.invoke('storage:delete-unused-sounds'),
+        compactDatabase: () => ipcRenderer.invoke('storage:compact-database'),
+        getCompactionConfig: () => ipcRenderer.invoke('storage:get-compaction-config'),
+        setCompactionConfig: (config) => ipcRenderer.invoke('storage:set-compaction-config', config),
 
         // --- Clipboard ---
         copyToClipboard: (text) => clipboard.writeText(text),
@@ -74,6 +742,120 @@ try {
             ipcRenderer.on(channel, subscription);
             return () => ipcRenderer.removeListener(channel, subscription);
         },
+        onLiveBar: (callback) => {
+            const channel = 'live-feed:bar';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+        onLiveQuote: (callback) => {
+            const channel = 'live-feed:quote';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+        onLiveFeedPaused: (callback) => {
+            const channel = 'live-feed:paused';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+        onPresentationChanged: (callback) => {
+            const channel = 'presentation:changed';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+        onScheduledJobRun: (callback) => {
+            const channel = 'scheduler:run-finished';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+        onEndOfDayFinished: (callback) => {
+            const channel = 'maintenance:end-of-day-finished';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+        onWatchImportSummary: (callback) => {
+            const channel = 'watch-folders:summary';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+        onSyncEvent: (callback) => {
+            const channel = 'sync:event';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+        onActionInvoked: (callback) => {
+            const channel = 'actions:invoke';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+        onLanguageChanged: (callback) => {
+            const channel = 'i18n:language-changed';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+        onKeybindingsChanged: (callback) => {
+            const channel = 'keybindings:changed';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+        onKeybindingTriggered: (callback) => {
+            const channel = 'keybindings:triggered';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+        onProviderStatus: (callback) => {
+            const channel = 'provider:status';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+        onProviderHealth: (callback) => {
+            const channel = 'provider:health';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+        onDepthSnapshot: (callback) => {
+            const channel = 'depth:snapshot';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+        onAlertTriggered: (callback) => {
+            const channel = 'alerts:triggered';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+        onImportProgress: (callback) => {
+            const channel = 'import:progress';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+        onImportComplete: (callback) => {
+            const channel = 'import:complete';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
+        onNewsItems: (callback) => {
+            const channel = 'news:new-items';
+            const subscription = (event, ...args) => callback(...args);
+            ipcRenderer.on(channel, subscription);
+            return () => ipcRenderer.removeListener(channel, subscription);
+        },
     });
 
     console.log('PRELOAD_SUCCESS');
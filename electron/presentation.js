@@ -0,0 +1,109 @@
+
+const { BrowserWindow, screen, powerSaveBlocker } = require('electron');
+
+// --- PRESENTATION MODE ---
+// One window at a time goes fullscreen (or kiosk) for screen-shared chart
+// reviews: menu bar hidden, display sleep / screen lock blocked, and optional
+// translucent black overlays over every other monitor. The window's previous
+// state is kept so exit puts it back exactly. Notification suppression is the
+// caller's job (main.js checks isActive() before sounding alerts).
+
+const DEFAULT_DIM_OPACITY = 0.85;
+
+const createPresentationController = ({ onChange = () => {} } = {}) => {
+    let session = null;
+
+    const createDimmer = (display, opacity) => {
+        const dimmer = new BrowserWindow({
+            ...display.bounds,
+            frame: false,
+            transparent: false,
+            backgroundColor: '#000000',
+            focusable: false,
+            skipTaskbar: true,
+            resizable: false,
+            movable: false,
+            hasShadow: false,
+            show: false,
+            webPreferences: { sandbox: true }
+        });
+        dimmer.setAlwaysOnTop(true, 'screen-saver');
+        dimmer.setIgnoreMouseEvents(true);
+        dimmer.setOpacity(opacity);
+        dimmer.loadURL('about:blank');
+        dimmer.showInactive();
+        return dimmer;
+    };
+
+    const status = () => (session
+        ? { active: true, windowId: session.windowId, kiosk: session.kiosk, dimmedDisplays: session.dimmers.length, startedAt: session.startedAt }
+        : { active: false });
+
+    /**
+     * options: { kiosk?: boolean, dimOtherDisplays?: boolean, dimOpacity?: 0..1 }
+     */
+    const enter = (win, options = {}) => {
+        if (!win || win.isDestroyed()) throw new Error('Window not found');
+        if (session && session.windowId !== win.id) throw new Error('Another window is already presenting');
+        if (session) exit();
+
+        const kiosk = !!options.kiosk;
+        const previous = {
+            fullScreen: win.isFullScreen(),
+            kiosk: win.isKiosk(),
+            menuBarVisible: win.isMenuBarVisible(),
+            bounds: win.getBounds()
+        };
+
+        win.setMenuBarVisibility(false);
+        if (kiosk) win.setKiosk(true);
+        else win.setFullScreen(true);
+
+        const blockerId = powerSaveBlocker.start('prevent-display-sleep');
+
+        const dimmers = [];
+        if (options.dimOtherDisplays) {
+            const opacity = Math.min(1, Math.max(0.1, Number(options.dimOpacity) || DEFAULT_DIM_OPACITY));
+            const presenting = screen.getDisplayMatching(previous.bounds);
+            screen.getAllDisplays()
+                .filter(d => d.id !== presenting.id)
+                .forEach(d => dimmers.push(createDimmer(d, opacity)));
+        }
+
+        // Closing the presenting window ends the session
+        const onClosed = () => exit();
+        win.once('closed', onClosed);
+
+        session = { win, windowId: win.id, kiosk, previous, blockerId, dimmers, onClosed, startedAt: Date.now() };
+        onChange(status());
+        return status();
+    };
+
+    const exit = () => {
+        if (!session) return status();
+        const { win, previous, blockerId, dimmers, onClosed } = session;
+        session = null;
+
+        if (powerSaveBlocker.isStarted(blockerId)) powerSaveBlocker.stop(blockerId);
+        dimmers.forEach((d) => { if (!d.isDestroyed()) d.destroy(); });
+
+        if (!win.isDestroyed()) {
+            win.removeListener('closed', onClosed);
+            if (win.isKiosk() && !previous.kiosk) win.setKiosk(false);
+            if (win.isFullScreen() !== previous.fullScreen) win.setFullScreen(previous.fullScreen);
+            win.setMenuBarVisibility(previous.menuBarVisible);
+            if (!previous.fullScreen && !previous.kiosk) win.setBounds(previous.bounds);
+        }
+        onChange(status());
+        return status();
+    };
+
+    const isActive = () => !!session;
+
+    // Overlay windows must not receive app broadcasts or be treated as app windows
+    const isDimmer = (win) => !!session && session.dimmers.includes(win);
+
+    return { enter, exit, status, isActive, isDimmer };
+};
+
+module.exports = { createPresentationController };
@@ -0,0 +1,85 @@
+
+const { REPORT_STYLE, escapeHtml, renderPortfolioReportBody } = require('./portfolioReport');
+
+// --- PRINT DOCUMENTS ---
+// Composes printable pages from backend data: a chart snapshot with its text
+// annotations, a set of sticky notes, the portfolio report, the activity
+// diary, or several of these in one document (each part starts on a new
+// page). The output is one self-contained HTML page; main.js loads it in a
+// hidden window and hands it to the OS print pipeline (or prints it to PDF).
+// Images must be data URLs.
+
+const PRINT_STYLE = `
+${REPORT_STYLE}
+.part + .part { page-break-before: always; }
+.snapshot { width: 100%; border: 1px solid #e0e3eb; }
+.annotations td { text-align: left; vertical-align: top; }
+.note { border: 1px solid #e0e3eb; border-left-width: 6px; border-radius: 4px; padding: 8px 10px; margin: 0 0 10px; page-break-inside: avoid; }
+.note .text { white-space: pre-wrap; font-size: 12px; }
+.note img { max-width: 100%; display: block; margin: 6px 0; }
+.note .meta { color: #787b86; font-size: 10px; margin-top: 6px; }
+`;
+
+const isoMinute = (ts) => (ts == null ? '—' : new Date(ts).toISOString().replace('T', ' ').slice(0, 16));
+
+/**
+ * chart: { title, imageSrc?, annotations?: [{ text, type, time, price }] }
+ */
+const renderChartPart = ({ title, imageSrc = null, annotations = [] }) => {
+    const rows = annotations.map(a => `<tr><td>${isoMinute(a.time)}</td><td>${a.price == null ? '—' : a.price}</td>`
+        + `<td>${escapeHtml(a.type || '')}</td><td>${escapeHtml(a.text).replace(/\n/g, '<br>')}</td></tr>`).join('');
+    return `<h1>${escapeHtml(title)}</h1>`
+        + (imageSrc ? `<img class="snapshot" src="${escapeHtml(imageSrc)}">` : '<p class="muted">No snapshot.</p>')
+        + (rows ? `<h2>Annotations</h2><table class="annotations"><thead><tr><th>Time (UTC)</th><th>Price</th><th>Type</th><th>Text</th></tr></thead><tbody>${rows}</tbody></table>` : '');
+};
+
+/**
+ * notes: sticky notes; images: attachment id -> data URL for embedded
+ * redpill-attachment:// references (unknown ones print as their reference).
+ */
+const renderNotesPart = ({ title = 'Notes', notes = [], images = {} }) => {
+    const body = notes.map((note) => {
+        const text = escapeHtml(note.text || '').replace(/redpill-attachment:\/\/([0-9a-f-]{36})/gi, (ref, id) => (images[id.toLowerCase()] ? `<img src="${images[id.toLowerCase()]}">` : ref));
+        const meta = [note.symbol, (note.tags || []).map(tag => `#${tag}`).join(' '), `updated ${isoMinute(note.updatedAt)} UTC`].filter(Boolean).map(escapeHtml).join(' · ');
+        return `<div class="note" style="border-left-color: ${escapeHtml(note.color || '#e0e3eb')}"><div class="text">${text}</div><div class="meta">${meta}</div></div>`;
+    }).join('');
+    return `<h1>${escapeHtml(title)}</h1>${body || '<p class="muted">No notes.</p>'}`;
+};
+
+const renderReportPart = ({ stats, title, currency }) => renderPortfolioReportBody(stats, { title, currency });
+
+const DIARY_LABELS = { chart_opened: 'Chart', note_created: 'Note', alert_fired: 'Alert', trade_logged: 'Trade', trades_imported: 'Import' };
+
+/**
+ * timeline: activityLog.getActivityTimeline's result
+ */
+const renderDiaryPart = ({ title = 'Activity diary', timeline }) => {
+    const { events = [], counts = {} } = timeline || {};
+    const summary = Object.entries(DIARY_LABELS).filter(([kind]) => counts[kind]).map(([kind, label]) => `${label}: ${counts[kind]}`).join(' · ');
+    const rows = events.map(e => `<tr><td>${isoMinute(e.at)}</td><td>${DIARY_LABELS[e.kind] || escapeHtml(e.kind)}</td>`
+        + `<td>${escapeHtml(e.symbol || '')}</td><td>${escapeHtml(e.title || '')}</td></tr>`).join('');
+    return `<h1>${escapeHtml(title)}</h1>`
+        + (summary ? `<div class="muted">${summary}${timeline.truncated ? ' · truncated' : ''}</div>` : '')
+        + (rows ? `<table class="annotations"><thead><tr><th>Time (UTC)</th><th>Event</th><th>Symbol</th><th>What</th></tr></thead><tbody>${rows}</tbody></table>` : '<p class="muted">No activity recorded.</p>');
+};
+
+const PART_RENDERERS = { chart: renderChartPart, notes: renderNotesPart, report: renderReportPart, diary: renderDiaryPart };
+
+/**
+ * parts: [{ kind: 'chart' | 'notes' | 'report' | 'diary', ...data for its renderer }]
+ */
+const composePrintHtml = (parts, { title = 'Red Pill' } = {}) => {
+    if (!parts.length) throw new Error('Nothing to print');
+    const body = parts.map((part) => {
+        const render = PART_RENDERERS[part.kind];
+        if (!render) throw new Error(`Unknown print part: ${part.kind}`);
+        return `<section class="part">${render(part)}</section>`;
+    }).join('\n');
+    return `<!DOCTYPE html><html><head><meta charset="utf-8"><title>${escapeHtml(title)}</title><style>${PRINT_STYLE}</style></head><body>
+${body}
+</body></html>`;
+};
+
+const attachmentIdsIn = (notes) => Array.from(new Set(notes.flatMap(note => Array.from(String(note.text || '').matchAll(/redpill-attachment:\/\/([0-9a-f-]{36})/gi), m => m[1].toLowerCase()))));
+
+module.exports = { composePrintHtml, attachmentIdsIn };
@@ -0,0 +1,131 @@
+
+const fs = require('fs');
+const path = require('path');
+
+// --- PROFILES ---
+// Each profile is a separate workspace with its own userData directory, and so
+// its own database, settings, layouts, backups and renderer storage. The
+// 'default' profile is the original userData directory so existing installs
+// keep their data; the others live under <base>/profiles/<id>. The registry
+// (profiles.json in the base directory) holds the names, the last used profile
+// and whether to ask at startup. Chromium fixes its data directory once the
+// app is ready, so the profile is picked before that and switching relaunches
+// with --profile.
+
+const REGISTRY_FILE = 'profiles.json';
+const DEFAULT_PROFILE = { id: 'default', name: 'Default', createdAt: null };
+const MAX_NAME_LENGTH = 64;
+
+// `--profile=<id|name>` or `--profile <id|name>`
+const parseProfileArg = (argv = []) => {
+    for (let i = 0; i < argv.length; i++) {
+        if (argv[i].startsWith('--profile=')) return argv[i].slice('--profile='.length) || null;
+        if (argv[i] === '--profile') return argv[i + 1] || null;
+    }
+    return null;
+};
+
+// argv without any --profile flag, to relaunch with a different one
+const stripProfileArg = (argv = []) => argv.filter((arg, i) =>
+    !arg.startsWith('--profile=') && arg !== '--profile' && argv[i - 1] !== '--profile');
+
+const slugify = (name) => String(name).toLowerCase().normalize('NFKD').replace(/[^a-z0-9]+/g, '-').replace(/^-+|-+$/g, '').slice(0, 40) || 'profile';
+
+const createProfileStore = (baseDir) => {
+    const registryPath = path.join(baseDir, REGISTRY_FILE);
+
+    const load = () => {
+        try {
+            const parsed = JSON.parse(fs.readFileSync(registryPath, 'utf8'));
+            return { profiles: Array.isArray(parsed.profiles) ? parsed.profiles : [], lastUsed: parsed.lastUsed || null, pickAtStartup: !!parsed.pickAtStartup };
+        } catch (e) {
+            return { profiles: [], lastUsed: null, pickAtStartup: false };
+        }
+    };
+
+    const save = (registry) => {
+        fs.mkdirSync(baseDir, { recursive: true });
+        fs.writeFileSync(registryPath, JSON.stringify(registry, null, 2));
+    };
+
+    const list = () => [DEFAULT_PROFILE, ...load().profiles.filter(p => p.id !== DEFAULT_PROFILE.id)];
+
+    const get = (id) => list().find(p => p.id === id) || null;
+
+    // By id first, then case-insensitive name
+    const resolve = (ref) => {
+        if (!ref) return null;
+        const wanted = String(ref).toLowerCase();
+        return get(String(ref)) || list().find(p => p.name.toLowerCase() === wanted) || null;
+    };
+
+    const dirFor = (id) => (id === DEFAULT_PROFILE.id ? baseDir : path.join(baseDir, 'profiles', id));
+
+    const validName = (name) => {
+        const trimmed = String(name || '').trim();
+        if (!trimmed) throw new Error('Profile name is required');
+        if (trimmed.length > MAX_NAME_LENGTH) throw new Error(`Profile name is longer than ${MAX_NAME_LENGTH} characters`);
+        if (list().some(p => p.name.toLowerCase() === trimmed.toLowerCase())) throw new Error(`A profile named "${trimmed}" already exists`);
+        return trimmed;
+    };
+
+    const create = (name) => {
+        const trimmed = validName(name);
+        const registry = load();
+        let id = slugify(trimmed);
+        for (let n = 2; get(id); n++) id = `${slugify(trimmed)}-${n}`;
+        const profile = { id, name: trimmed, createdAt: Date.now() };
+        fs.mkdirSync(dirFor(id), { recursive: true });
+        registry.profiles.push(profile);
+        save(registry);
+        return profile;
+    };
+
+    const rename = (id, name) => {
+        if (id === DEFAULT_PROFILE.id) throw new Error('The default profile cannot be renamed');
+        const registry = load();
+        const profile = registry.profiles.find(p => p.id === id);
+        if (!profile) throw new Error(`Profile not found: ${id}`);
+        if (profile.name.toLowerCase() !== String(name || '').trim().toLowerCase()) profile.name = validName(name);
+        save(registry);
+        return profile;
+    };
+
+    // Deletes the profile's whole directory; callers refuse the active profile
+    const remove = (id) => {
+        if (id === DEFAULT_PROFILE.id) throw new Error('The default profile cannot be deleted');
+        const registry = load();
+        if (!registry.profiles.some(p => p.id === id)) throw new Error(`Profile not found: ${id}`);
+        registry.profiles = registry.profiles.filter(p => p.id !== id);
+        if (registry.lastUsed === id) registry.lastUsed = null;
+        save(registry);
+        fs.rmSync(dirFor(id), { recursive: true, force: true });
+        return true;
+    };
+
+    const getConfig = () => {
+        const { lastUsed, pickAtStartup } = load();
+        return { lastUsed, pickAtStartup };
+    };
+
+    const update = (changes) => {
+        const registry = { ...load(), ...changes };
+        save(registry);
+        return getConfig();
+    };
+
+    return {
+        list,
+        get,
+        resolve,
+        dirFor,
+        create,
+        rename,
+        remove,
+        getConfig,
+        setLastUsed: (id) => update({ lastUsed: id }),
+        setPickAtStartup: (enabled) => update({ pickAtStartup: !!enabled })
+    };
+};
+
+module.exports = { DEFAULT_PROFILE, parseProfileArg, stripProfileArg, createProfileStore };
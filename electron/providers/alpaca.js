@@ -0,0 +1,228 @@
+
+const WebSocket = require('ws');
+const { publishBar, publishQuote, publishTrade, publishStatus, createBarAggregator } = require('../liveFeed');
+const { request } = require('../network');
+const { wsOptions } = require('../proxy');
+
+// --- ALPACA MARKETS PROVIDER ---
+// REST (data.alpaca.markets) for historical bars, WebSocket stream for live
+// trades / quotes. Stocks use the v2 endpoints; symbols containing "/"
+// (e.g. BTC/USD) are routed to the v1beta3 crypto endpoints.
+
+const DATA_URL = 'https://data.alpaca.markets';
+const STREAM_URL = 'wss://stream.data.alpaca.markets';
+
+const TIMEFRAMES = {
+    '1mn': '1Min', '3m': '3Min', '5m': '5Min', '15m': '15Min', '30m': '30Min',
+    '1h': '1Hour', '2h': '2Hour', '4h': '4Hour', '12h': '12Hour',
+    '1D': '1Day', '1W': '1Week', '1mo': '1Month'
+};
+
+const isCrypto = (symbol) => symbol.includes('/');
+
+const mapBar = (b) => ({ timestamp: Date.parse(b.t), open: b.o, high: b.h, low: b.l, close: b.c, volume: b.v || 0 });
+
+/**
+ * `getCredentials()` returns { keyId, secretKey } from the secrets store at call
+ * time so rotated keys take effect without restarting the provider.
+ */
+const createAlpacaProvider = ({ getCredentials, onLog } = {}) => {
+    const state = { status: 'disconnected', feed: 'iex', message: null };
+    const sockets = { stocks: null, crypto: null };
+    const subscriptions = new Map(); // `${symbol}:${timeframe}` -> aggregate
+    let shouldReconnect = false;
+
+    const log = (level, message) => onLog && onLog({ level, message });
+
+    const setStatus = (status, message = null) => {
+        state.status = status;
+        state.message = message;
+        publishStatus('alpaca', status, message);
+    };
+
+    const credentials = () => {
+        const creds = getCredentials ? getCredentials() : null;
+        if (!creds || !creds.keyId || !creds.secretKey) throw new Error('Alpaca API keys are not configured');
+        return creds;
+    };
+
+    const authHeaders = () => {
+        const { keyId, secretKey } = credentials();
+        return { 'APCA-API-KEY-ID': keyId, 'APCA-API-SECRET-KEY': secretKey };
+    };
+
+    const symbolsFor = (kind) => Array.from(new Set(
+        Array.from(subscriptions.keys())
+            .map(key => key.slice(0, key.lastIndexOf(':')))
+            .filter(symbol => (kind === 'crypto') === isCrypto(symbol))
+    ));
+
+    const sendSubscription = (kind, action, symbols) => {
+        const socket = sockets[kind];
+        if (!socket || socket.readyState !== WebSocket.OPEN || symbols.length === 0) return;
+        socket.send(JSON.stringify({ action, trades: symbols, quotes: symbols }));
+    };
+
+    const onTrade = (msg) => {
+        const timestamp = Date.parse(msg.t);
+        publishTrade('alpaca', msg.S, { price: msg.p, size: msg.s, timestamp });
+        subscriptions.forEach((aggregate, key) => {
+            if (key.slice(0, key.lastIndexOf(':')) === msg.S) {
+                aggregate({ timestamp, open: msg.p, high: msg.p, low: msg.p, close: msg.p, volume: msg.s });
+            }
+        });
+    };
+
+    const openSocket = (kind) => {
+        const url = kind === 'crypto' ? `${STREAM_URL}/v1beta3/crypto/us` : `${STREAM_URL}/v2/${state.feed}`;
+        const socket = new WebSocket(url, wsOptions(url, 'alpaca'));
+        sockets[kind] = socket;
+
+        socket.on('message', (raw) => {
+            let messages;
+            try { messages = JSON.parse(raw.toString()); } catch (e) { return; }
+            for (const msg of Array.isArray(messages) ? messages : [messages]) {
+                switch (msg.T) {
+                    case 'success':
+                        if (msg.msg === 'connected') {
+                            const { keyId, secretKey } = credentials();
+                            socket.send(JSON.stringify({ action: 'auth', key: keyId, secret: secretKey }));
+                        } else if (msg.msg === 'authenticated') {
+                            setStatus('connected');
+                            sendSubscription(kind, 'subscribe', symbolsFor(kind));
+                        }
+                        break;
+                    case 'error':
+                        log('ERROR', `Alpaca ${kind} stream error ${msg.code}: ${msg.msg}`);
+                        if (msg.code === 402 || msg.code === 401) setStatus('degraded', msg.msg);
+                        break;
+                    case 't':
+                        onTrade(msg);
+                        break;
+                    case 'q':
+                        publishQuote('alpaca', msg.S, { bid: msg.bp, ask: msg.ap, bidSize: msg.bs, askSize: msg.as, timestamp: Date.parse(msg.t) });
+                        break;
+                    default:
+                        break;
+                }
+            }
+        });
+
+        socket.on('error', (err) => log('ERROR', `Alpaca ${kind} stream: ${err.message}`));
+        socket.on('close', () => {
+            if (sockets[kind] === socket) sockets[kind] = null;
+            if (shouldReconnect) {
+                setStatus('degraded', 'Stream closed, reconnecting');
+                setTimeout(() => { if (shouldReconnect && !sockets[kind]) openSocket(kind); }, 3000);
+            }
+        });
+    };
+
+    const ensureSocket = (kind) => {
+        if (!sockets[kind]) openSocket(kind);
+    };
+
+    const fetchPage = async (url) => {
+        const response = await request('alpaca', url, { headers: authHeaders() });
+        if (!response.ok) {
+            const body = await response.text().catch(() => '');
+            throw new Error(`Alpaca API ${response.status}: ${body || response.statusText}`);
+        }
+        return response.json();
+    };
+
+    return {
+        id: 'alpaca',
+        name: 'Alpaca Markets',
+
+        connect: async ({ feed } = {}) => {
+            credentials(); // fail fast if keys are missing
+            if (feed) state.feed = feed;
+            shouldReconnect = true;
+            setStatus('connecting');
+            ensureSocket('stocks');
+            return { feed: state.feed };
+        },
+
+        disconnect: () => {
+            shouldReconnect = false;
+            Object.keys(sockets).forEach((kind) => {
+                if (sockets[kind]) sockets[kind].close();
+                sockets[kind] = null;
+            });
+            subscriptions.clear();
+            setStatus('disconnected');
+        },
+
+        getStatus: () => ({ status: state.status, message: state.message, feed: state.feed, subscriptions: Array.from(subscriptions.keys()) }),
+
+        testCredentials: async () => {
+            const response = await request('alpaca', `${DATA_URL}/v2/stocks/bars/latest?symbols=AAPL&feed=iex`, { headers: authHeaders() });
+            if (response.status === 401 || response.status === 403) return { valid: false, message: `Alpaca rejected the API keys (${response.status})` };
+            if (!response.ok) throw new Error(`Alpaca API ${response.status}: ${response.statusText}`);
+            return { valid: true, message: null };
+        },
+
+        fetchHistory: async (symbol, timeframe, { start, end, limit = 10000, adjustment = 'raw' } = {}) => {
+            const tf = TIMEFRAMES[timeframe];
+            if (!tf) throw new Error(`Timeframe ${timeframe} is not supported by Alpaca`);
+            const startIso = new Date(start || Date.now() - 30 * 86400000).toISOString();
+            const endIso = new Date(end || Date.now()).toISOString();
+
+            const bars = [];
+            let pageToken = null;
+            do {
+                const params = new URLSearchParams({ timeframe: tf, start: startIso, end: endIso, limit: String(Math.min(limit, 10000)) });
+                if (pageToken) params.set('page_token', pageToken);
+                let page;
+                if (isCrypto(symbol)) {
+                    params.set('symbols', symbol);
+                    page = await fetchPage(`${DATA_URL}/v1beta3/crypto/us/bars?${params}`);
+                    bars.push(...((page.bars && page.bars[symbol]) || []).map(mapBar));
+                } else {
+                    params.set('adjustment', adjustment);
+                    params.set('feed', state.feed);
+                    page = await fetchPage(`${DATA_URL}/v2/stocks/${encodeURIComponent(symbol)}/bars?${params}`);
+                    bars.push(...(page.bars || []).map(mapBar));
+                }
+                pageToken = page.next_page_token;
+            } while (pageToken && bars.length < limit);
+
+            return bars;
+        },
+
+        subscribe: (symbol, timeframe) => {
+            const key = `${symbol}:${timeframe}`;
+            if (subscriptions.has(key)) return;
+            const kind = isCrypto(symbol) ? 'crypto' : 'stocks';
+            const isNewSymbol = !symbolsFor(kind).includes(symbol);
+            subscriptions.set(key, createBarAggregator(timeframe, (bar, isClosed) => publishBar('alpaca', symbol, timeframe, bar, isClosed)));
+            shouldReconnect = true;
+            ensureSocket(kind);
+            if (isNewSymbol) sendSubscription(kind, 'subscribe', [symbol]);
+        },
+
+        unsubscribe: (symbol, timeframe) => {
+            const key = `${symbol}:${timeframe}`;
+            if (!subscriptions.delete(key)) return false;
+            const kind = isCrypto(symbol) ? 'crypto' : 'stocks';
+            if (!symbolsFor(kind).includes(symbol)) sendSubscription(kind, 'unsubscribe', [symbol]);
+            return true;
+        }
+    };
+};
+
+// Registry entry (see providers/index.js)
+const plugin = {
+    id: 'alpaca',
+    name: 'Alpaca Markets',
+    capabilities: { history: true, live: true, search: false, credentials: true },
+    settings: [{ key: 'feed', label: 'Data feed', type: 'select', options: ['iex', 'sip'], default: 'iex' }],
+    secrets: ['alpaca.keyId', 'alpaca.secretKey'],
+    create: ({ onLog, getSecret }) => createAlpacaProvider({
+        onLog,
+        getCredentials: () => ({ keyId: getSecret('alpaca.keyId'), secretKey: getSecret('alpaca.secretKey') })
+    })
+};
+
+module.exports = { createAlpacaProvider, plugin };
@@ -0,0 +1,266 @@
+
+const WebSocket = require('ws');
+const { publishBar, publishTrade, publishDepth, publishStatus } = require('../liveFeed');
+const { request } = require('../network');
+const { wsOptions } = require('../proxy');
+
+// --- BINANCE-COMPATIBLE CRYPTO PROVIDER ---
+// Works against any exchange exposing the Binance spot API shape
+// (/api/v3/klines, /api/v3/exchangeInfo, <symbol>@kline_<interval> streams).
+// Kline streams already carry the forming bar, so no local aggregation is
+// needed. The socket reconnects automatically (Binance drops connections every
+// 24h) and backfills missed bars over REST.
+
+const DEFAULT_REST_URL = 'https://api.binance.com';
+const DEFAULT_WS_URL = 'wss://stream.binance.com:9443/ws';
+const DEPTH_SNAPSHOT_LIMIT = 1000;
+const MAX_KLINES = 1000;
+
+const INTERVALS = {
+    '1mn': '1m', '3m': '3m', '5m': '5m', '15m': '15m', '30m': '30m',
+    '1h': '1h', '2h': '2h', '4h': '4h', '12h': '12h',
+    '1D': '1d', '1W': '1w', '1mo': '1M'
+};
+const TIMEFRAME_BY_INTERVAL = Object.fromEntries(Object.entries(INTERVALS).map(([tf, i]) => [i, tf]));
+
+const mapKline = (k) => ({
+    timestamp: k[0],
+    open: parseFloat(k[1]),
+    high: parseFloat(k[2]),
+    low: parseFloat(k[3]),
+    close: parseFloat(k[4]),
+    volume: parseFloat(k[5])
+});
+
+const createBinanceProvider = ({ id = 'binance', name = 'Binance', onLog, onBackfill } = {}) => {
+    const state = { status: 'disconnected', message: null, restUrl: DEFAULT_REST_URL, wsUrl: DEFAULT_WS_URL };
+    const subscriptions = new Map(); // `${symbol}:${timeframe}` -> { symbol, timeframe, lastClosedTs }
+    const depthSymbols = new Set();
+    const depthFetches = new Set(); // symbols with a snapshot request in flight
+    let socket = null;
+    let shouldReconnect = false;
+    let disconnectedAt = null;
+    let requestId = 1;
+    let symbolCache = null;
+
+    const log = (level, message) => onLog && onLog({ level, message });
+
+    const setStatus = (status, message = null) => {
+        state.status = status;
+        state.message = message;
+        publishStatus(id, status, message);
+    };
+
+    const streamsFor = (symbol, timeframe) => [`${symbol.toLowerCase()}@kline_${INTERVALS[timeframe]}`];
+
+    const depthStream = (symbol) => `${symbol.toLowerCase()}@depth@100ms`;
+
+    const allStreams = () => {
+        const streams = new Set();
+        subscriptions.forEach(({ symbol, timeframe }) => streamsFor(symbol, timeframe).forEach(s => streams.add(s)));
+        subscriptions.forEach(({ symbol }) => streams.add(`${symbol.toLowerCase()}@trade`));
+        depthSymbols.forEach(symbol => streams.add(depthStream(symbol)));
+        return Array.from(streams);
+    };
+
+    const send = (method, params) => {
+        if (socket && socket.readyState === WebSocket.OPEN && params.length) {
+            socket.send(JSON.stringify({ method, params, id: requestId++ }));
+        }
+    };
+
+    const fetchKlines = async (symbol, timeframe, start, end, limit = 5000) => {
+        const interval = INTERVALS[timeframe];
+        if (!interval) throw new Error(`Timeframe ${timeframe} is not supported by ${name}`);
+        const bars = [];
+        let cursor = start;
+        while (bars.length < limit) {
+            const params = new URLSearchParams({ symbol: symbol.toUpperCase(), interval, limit: String(MAX_KLINES) });
+            if (cursor) params.set('startTime', String(Math.floor(cursor)));
+            if (end) params.set('endTime', String(Math.floor(end)));
+            const response = await request(id, `${state.restUrl}/api/v3/klines?${params}`);
+            if (!response.ok) throw new Error(`${name} API ${response.status}: ${response.statusText}`);
+            const page = await response.json();
+            if (!Array.isArray(page) || page.length === 0) break;
+            bars.push(...page.map(mapKline));
+            if (page.length < MAX_KLINES || !start) break;
+            cursor = page[page.length - 1][0] + 1;
+        }
+        return bars.slice(0, limit);
+    };
+
+    // REST snapshot; stream diffs it already covers are dropped by the order book
+    const fetchDepthSnapshot = async (symbol) => {
+        if (depthFetches.has(symbol)) return;
+        depthFetches.add(symbol);
+        try {
+            const response = await request(id, `${state.restUrl}/api/v3/depth?${new URLSearchParams({ symbol, limit: String(DEPTH_SNAPSHOT_LIMIT) })}`);
+            if (!response.ok) throw new Error(`${name} API ${response.status}: ${response.statusText}`);
+            const book = await response.json();
+            if (depthSymbols.has(symbol)) publishDepth(id, symbol, { type: 'snapshot', bids: book.bids, asks: book.asks, sequence: book.lastUpdateId, timestamp: Date.now() });
+        } catch (err) {
+            log('ERROR', `Depth snapshot failed for ${symbol}: ${err.message}`);
+        } finally {
+            depthFetches.delete(symbol);
+        }
+    };
+
+    const backfill = async () => {
+        const since = disconnectedAt;
+        disconnectedAt = null;
+        if (!since) return;
+        for (const sub of subscriptions.values()) {
+            try {
+                const bars = (await fetchKlines(sub.symbol, sub.timeframe, sub.lastClosedTs || since, Date.now()))
+                    .filter(b => b.timestamp > (sub.lastClosedTs || 0));
+                // The last REST bar is still forming; the stream will deliver it
+                const closed = bars.slice(0, -1);
+                if (closed.length === 0) continue;
+                closed.forEach(b => publishBar(id, sub.symbol, sub.timeframe, b, true));
+                sub.lastClosedTs = closed[closed.length - 1].timestamp;
+                if (onBackfill) onBackfill(sub.symbol, sub.timeframe, closed);
+            } catch (err) {
+                log('ERROR', `Backfill failed for ${sub.symbol}: ${err.message}`);
+            }
+        }
+    };
+
+    const onMessage = (msg) => {
+        if (msg.e === 'kline') {
+            const k = msg.k;
+            const timeframe = TIMEFRAME_BY_INTERVAL[k.i];
+            const sub = subscriptions.get(`${msg.s}:${timeframe}`);
+            if (!sub) return;
+            const bar = { timestamp: k.t, open: parseFloat(k.o), high: parseFloat(k.h), low: parseFloat(k.l), close: parseFloat(k.c), volume: parseFloat(k.v) };
+            if (k.x) sub.lastClosedTs = k.t;
+            publishBar(id, msg.s, timeframe, bar, !!k.x);
+        } else if (msg.e === 'depthUpdate') {
+            if (depthSymbols.has(msg.s)) publishDepth(id, msg.s, { type: 'update', bids: msg.b, asks: msg.a, firstSequence: msg.U, sequence: msg.u, timestamp: msg.E });
+        } else if (msg.e === 'trade') {
+            publishTrade(id, msg.s, { price: parseFloat(msg.p), size: parseFloat(msg.q), timestamp: msg.T });
+        } else if (msg.error) {
+            log('ERROR', `${name} stream error: ${msg.error.msg || JSON.stringify(msg.error)}`);
+        }
+    };
+
+    const openSocket = () => {
+        const ws = new WebSocket(state.wsUrl, wsOptions(state.wsUrl, id));
+        socket = ws;
+        setStatus('connecting');
+
+        ws.on('open', () => {
+            setStatus('connected');
+            send('SUBSCRIBE', allStreams());
+            backfill();
+        });
+        ws.on('message', (raw) => {
+            try { onMessage(JSON.parse(raw.toString())); } catch (e) {}
+        });
+        ws.on('error', (err) => log('ERROR', `${name} stream: ${err.message}`));
+        ws.on('close', () => {
+            if (socket === ws) socket = null;
+            if (!shouldReconnect) return;
+            if (!disconnectedAt) disconnectedAt = Date.now();
+            setStatus('degraded', 'Stream closed, reconnecting');
+            setTimeout(() => { if (shouldReconnect && !socket) openSocket(); }, 3000);
+        });
+    };
+
+    return {
+        id,
+        name,
+
+        connect: async ({ restUrl, wsUrl } = {}) => {
+            if (restUrl) state.restUrl = restUrl.replace(/\/$/, '');
+            if (wsUrl) state.wsUrl = wsUrl;
+            symbolCache = null;
+            shouldReconnect = true;
+            if (!socket) openSocket();
+            return { restUrl: state.restUrl, wsUrl: state.wsUrl };
+        },
+
+        disconnect: () => {
+            shouldReconnect = false;
+            if (socket) socket.close();
+            socket = null;
+            subscriptions.clear();
+            depthSymbols.clear();
+            setStatus('disconnected');
+        },
+
+        getStatus: () => ({ status: state.status, message: state.message, restUrl: state.restUrl, subscriptions: Array.from(subscriptions.keys()), depth: Array.from(depthSymbols) }),
+
+        fetchHistory: (symbol, timeframe, { start, end, limit = 5000 } = {}) => fetchKlines(symbol, timeframe, start, end, limit),
+
+        subscribe: (symbol, timeframe) => {
+            const upper = symbol.toUpperCase();
+            const key = `${upper}:${timeframe}`;
+            if (subscriptions.has(key)) return;
+            if (!INTERVALS[timeframe]) throw new Error(`Timeframe ${timeframe} is not supported by ${name}`);
+            const hadSymbol = Array.from(subscriptions.values()).some(s => s.symbol === upper);
+            subscriptions.set(key, { symbol: upper, timeframe, lastClosedTs: null });
+            shouldReconnect = true;
+            if (!socket) openSocket();
+            else send('SUBSCRIBE', [...streamsFor(upper, timeframe), ...(hadSymbol ? [] : [`${upper.toLowerCase()}@trade`])]);
+        },
+
+        unsubscribe: (symbol, timeframe) => {
+            const upper = symbol.toUpperCase();
+            if (!subscriptions.delete(`${upper}:${timeframe}`)) return false;
+            const stillUsed = Array.from(subscriptions.values()).some(s => s.symbol === upper);
+            send('UNSUBSCRIBE', [...streamsFor(upper, timeframe), ...(stillUsed ? [] : [`${upper.toLowerCase()}@trade`])]);
+            return true;
+        },
+
+        subscribeDepth: (symbol) => {
+            const upper = symbol.toUpperCase();
+            if (depthSymbols.has(upper)) return;
+            depthSymbols.add(upper);
+            shouldReconnect = true;
+            if (!socket) openSocket();
+            else send('SUBSCRIBE', [depthStream(upper)]);
+            fetchDepthSnapshot(upper);
+        },
+
+        unsubscribeDepth: (symbol) => {
+            const upper = symbol.toUpperCase();
+            if (!depthSymbols.delete(upper)) return false;
+            send('UNSUBSCRIBE', [depthStream(upper)]);
+            return true;
+        },
+
+        resyncDepth: (symbol) => fetchDepthSnapshot(symbol.toUpperCase()),
+
+        searchSymbols: async (query = '') => {
+            if (!symbolCache) {
+                const response = await request(id, `${state.restUrl}/api/v3/exchangeInfo`);
+                if (!response.ok) throw new Error(`${name} API ${response.status}: ${response.statusText}`);
+                const info = await response.json();
+                symbolCache = (info.symbols || [])
+                    .filter(s => s.status === 'TRADING')
+                    .map(s => ({ symbol: s.symbol, baseAsset: s.baseAsset, quoteAsset: s.quoteAsset, assetClass: 'crypto', exchange: name }));
+            }
+            const q = String(query).toUpperCase().replace(/[^A-Z0-9]/g, '');
+            if (!q) return symbolCache.slice(0, 50);
+            return symbolCache
+                .filter(s => s.symbol.includes(q) || s.baseAsset === q)
+                .sort((a, b) => (a.symbol.startsWith(q) ? 0 : 1) - (b.symbol.startsWith(q) ? 0 : 1) || a.symbol.length - b.symbol.length)
+                .slice(0, 50);
+        }
+    };
+};
+
+// Registry entry (see providers/index.js)
+const plugin = {
+    id: 'binance',
+    name: 'Binance',
+    capabilities: { history: true, live: true, search: true, credentials: false, depth: true },
+    settings: [
+        { key: 'restUrl', label: 'REST endpoint', type: 'url', default: DEFAULT_REST_URL },
+        { key: 'wsUrl', label: 'WebSocket endpoint', type: 'url', default: DEFAULT_WS_URL }
+    ],
+    secrets: [],
+    create: ({ onLog, onBackfill }) => createBinanceProvider({ onLog, onBackfill })
+};
+
+module.exports = { createBinanceProvider, plugin };
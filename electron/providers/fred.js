@@ -0,0 +1,106 @@
+
+const { request: networkRequest } = require('../network');
+
+// --- FRED (FEDERAL RESERVE ECONOMIC DATA) PROVIDER ---
+// Macro series (rates, CPI, payrolls...) for overlays. Observations are single
+// values, stored as flat bars (open = high = low = close = value) under the
+// symbol "FRED:<SERIES_ID>" so they flow through the normal dataset path.
+
+const API_URL = 'https://api.stlouisfed.org/fred';
+
+// FRED frequency_short -> app timeframe label
+const FREQUENCY_TIMEFRAMES = { D: '1D', W: '1W', BW: '1W', M: '1mo', Q: '3mo', SA: '6mo', A: '12M' };
+
+const fredSymbol = (seriesId) => `FRED:${String(seriesId).toUpperCase()}`;
+
+const createFredProvider = ({ getApiKey } = {}) => {
+    const apiKey = () => {
+        const key = getApiKey ? getApiKey() : null;
+        if (!key) throw new Error('FRED API key is not configured');
+        return key;
+    };
+
+    const request = async (endpoint, params) => {
+        const query = new URLSearchParams({ ...params, api_key: apiKey(), file_type: 'json' });
+        const response = await networkRequest('fred', `${API_URL}/${endpoint}?${query}`);
+        const body = await response.json().catch(() => null);
+        if (!response.ok) throw new Error(`FRED API ${response.status}: ${body?.error_message || response.statusText}`);
+        return body;
+    };
+
+    const getSeriesInfo = async (seriesId) => {
+        const body = await request('series', { series_id: seriesId });
+        const s = body.seriess?.[0];
+        if (!s) throw new Error(`FRED series ${seriesId} not found`);
+        return {
+            id: s.id,
+            title: s.title,
+            units: s.units,
+            frequency: s.frequency_short,
+            timeframe: FREQUENCY_TIMEFRAMES[s.frequency_short] || '1D',
+            seasonalAdjustment: s.seasonal_adjustment_short,
+            lastUpdated: s.last_updated
+        };
+    };
+
+    const fetchHistory = async (seriesId, timeframe, { start, end } = {}) => {
+        const id = String(seriesId).replace(/^FRED:/i, '');
+        const params = { series_id: id };
+        if (start) params.observation_start = new Date(start).toISOString().slice(0, 10);
+        if (end) params.observation_end = new Date(end).toISOString().slice(0, 10);
+        const body = await request('series/observations', params);
+        const bars = [];
+        (body.observations || []).forEach((o) => {
+            const value = parseFloat(o.value); // missing observations are "."
+            if (isNaN(value)) return;
+            bars.push({ timestamp: Date.parse(`${o.date}T00:00:00Z`), open: value, high: value, low: value, close: value, volume: 0 });
+        });
+        return bars;
+    };
+
+    return {
+        id: 'fred',
+        name: 'FRED (St. Louis Fed)',
+        connect: async () => ({}),
+        disconnect: () => {},
+        getStatus: () => ({ status: 'connected', message: null, historyOnly: true }),
+        testCredentials: async () => {
+            try {
+                await request('series', { series_id: 'GDP' });
+                return { valid: true, message: null };
+            } catch (err) {
+                // FRED answers 400 "api_key ... not registered" for bad keys
+                if (/api_key/i.test(err.message)) return { valid: false, message: err.message };
+                throw err;
+            }
+        },
+        fetchHistory,
+        getSeriesInfo,
+        subscribe: () => { throw new Error('FRED series are not streamed'); },
+        unsubscribe: () => false,
+        searchSymbols: async (query) => {
+            const body = await request('series/search', { search_text: query, limit: 50, order_by: 'popularity', sort_order: 'desc' });
+            return (body.seriess || []).map(s => ({
+                symbol: fredSymbol(s.id),
+                name: s.title,
+                assetClass: 'economic',
+                exchange: 'FRED',
+                frequency: s.frequency_short,
+                units: s.units_short,
+                observationEnd: s.observation_end
+            }));
+        }
+    };
+};
+
+// Registry entry (see providers/index.js)
+const plugin = {
+    id: 'fred',
+    name: 'FRED (St. Louis Fed)',
+    capabilities: { history: true, live: false, search: true, credentials: true },
+    settings: [],
+    secrets: ['fred.apiKey'],
+    create: ({ getSecret }) => createFredProvider({ getApiKey: () => getSecret('fred.apiKey') })
+};
+
+module.exports = { createFredProvider, fredSymbol, plugin };
@@ -0,0 +1,378 @@
+
+const net = require('net');
+const { EventEmitter } = require('events');
+const { publishBar, publishStatus, createBarAggregator } = require('../liveFeed');
+
+// --- INTERACTIVE BROKERS (TWS / IB GATEWAY) PROVIDER ---
+// Minimal client for the TWS socket API: length-prefixed messages made of
+// NUL-terminated fields. We negotiate server versions 100..176, which keeps
+// historical data in the single-message layout (no HISTORICAL_DATA_END).
+
+const MIN_CLIENT_VER = 100;
+const MAX_CLIENT_VER = 176;
+
+// Server versions gating optional fields
+const MIN_SERVER_VER_TRADING_CLASS = 68;
+const MIN_SERVER_VER_LINKING = 70;
+const MIN_SERVER_VER_SYNT_REALTIME_BARS = 124;
+
+const OUT = { REQ_HISTORICAL_DATA: 20, CANCEL_HISTORICAL_DATA: 25, REQ_REAL_TIME_BARS: 50, CANCEL_REAL_TIME_BARS: 51, START_API: 71 };
+const IN = { ERROR: 4, NEXT_VALID_ID: 9, MANAGED_ACCTS: 15, HISTORICAL_DATA: 17, REAL_TIME_BARS: 50 };
+
+// Timeframe -> TWS bar size setting
+const BAR_SIZES = {
+    '1mn': '1 min', '3m': '3 mins', '5m': '5 mins', '15m': '15 mins', '30m': '30 mins',
+    '1h': '1 hour', '2h': '2 hours', '4h': '4 hours', '1D': '1 day', '1W': '1 week', '1mo': '1 month'
+};
+
+// Sensible default lookback per bar size (TWS caps bars per request)
+const DEFAULT_DURATIONS = {
+    '1mn': '2 D', '3m': '5 D', '5m': '10 D', '15m': '20 D', '30m': '1 M',
+    '1h': '2 M', '2h': '3 M', '4h': '6 M', '1D': '5 Y', '1W': '10 Y', '1mo': '20 Y'
+};
+
+// Informational codes TWS reports through the error channel
+const INFO_CODES = new Set([2104, 2106, 2107, 2108, 2119, 2158]);
+
+/**
+ * Accepts "AAPL", "EURUSD:CASH:IDEALPRO", "ES:FUT:CME:USD:202412" or a contract object.
+ */
+const normalizeContract = (input) => {
+    if (input && typeof input === 'object') {
+        return { conId: 0, secType: 'STK', exchange: 'SMART', currency: 'USD', primaryExchange: '', lastTradeDateOrContractMonth: '', localSymbol: '', tradingClass: '', ...input };
+    }
+    const [symbol, secType = 'STK', exchange, currency = 'USD', expiry = ''] = String(input).split(':');
+    if (secType === 'CASH' && symbol.length === 6) {
+        return { conId: 0, symbol: symbol.slice(0, 3), secType, exchange: exchange || 'IDEALPRO', currency: symbol.slice(3), primaryExchange: '', lastTradeDateOrContractMonth: '', localSymbol: '', tradingClass: '' };
+    }
+    return { conId: 0, symbol, secType, exchange: exchange || 'SMART', currency, primaryExchange: '', lastTradeDateOrContractMonth: expiry, localSymbol: '', tradingClass: '' };
+};
+
+const parseBarTime = (value) => {
+    // formatDate=2 returns epoch seconds for intraday bars; daily+ bars come back as yyyymmdd
+    if (/^\d{8}$/.test(value)) return Date.UTC(+value.slice(0, 4), +value.slice(4, 6) - 1, +value.slice(6, 8));
+    const n = Number(value);
+    return isFinite(n) ? n * 1000 : NaN;
+};
+
+class IbkrClient extends EventEmitter {
+    constructor() {
+        super();
+        this.socket = null;
+        this.serverVersion = 0;
+        this.status = 'disconnected';
+        this.buffer = Buffer.alloc(0);
+        this.handshakeDone = false;
+        this.nextReqId = 1000;
+        this.pending = new Map(); // reqId -> { resolve, reject, timer }
+        this.subscriptions = new Map(); // reqId -> { symbol, contract }
+        this.config = null;
+    }
+
+    setStatus(status, message = null) {
+        this.status = status;
+        this.emit('status', { status, message });
+    }
+
+    connect({ host = '127.0.0.1', port = 7497, clientId = 1, timeoutMs = 10000 } = {}) {
+        if (this.socket) this.disconnect();
+        this.config = { host, port, clientId };
+        this.setStatus('connecting');
+
+        return new Promise((resolve, reject) => {
+            const timer = setTimeout(() => {
+                this.disconnect('Connection timed out');
+                reject(new Error(`Timed out connecting to TWS at ${host}:${port}`));
+            }, timeoutMs);
+
+            this.once('ready', () => { clearTimeout(timer); resolve({ serverVersion: this.serverVersion }); });
+
+            this.socket = net.createConnection({ host, port }, () => {
+                const versions = Buffer.from(`v${MIN_CLIENT_VER}..${MAX_CLIENT_VER}`, 'ascii');
+                const len = Buffer.alloc(4);
+                len.writeUInt32BE(versions.length);
+                this.socket.write(Buffer.concat([Buffer.from('API\0', 'ascii'), len, versions]));
+            });
+
+            this.socket.on('data', (chunk) => this.onData(chunk));
+            this.socket.on('error', (err) => {
+                clearTimeout(timer);
+                this.emit('log', { level: 'ERROR', message: err.message });
+                if (this.status === 'connecting') reject(err);
+            });
+            this.socket.on('close', () => {
+                clearTimeout(timer);
+                this.failPending(new Error('Connection to TWS closed'));
+                this.socket = null;
+                this.handshakeDone = false;
+                this.buffer = Buffer.alloc(0);
+                if (this.status !== 'disconnected') this.setStatus('disconnected');
+            });
+        });
+    }
+
+    disconnect(reason = null) {
+        if (this.socket) {
+            this.socket.destroy();
+            this.socket = null;
+        }
+        this.handshakeDone = false;
+        this.subscriptions.clear();
+        this.failPending(new Error(reason || 'Disconnected'));
+        if (this.status !== 'disconnected') this.setStatus('disconnected', reason);
+    }
+
+    failPending(err) {
+        this.pending.forEach(({ reject, timer }) => { clearTimeout(timer); reject(err); });
+        this.pending.clear();
+    }
+
+    send(fields) {
+        if (!this.socket) throw new Error('Not connected to TWS');
+        const payload = Buffer.from(fields.map(f => (f === null || f === undefined ? '' : String(f)) + '\0').join(''), 'utf8');
+        const len = Buffer.alloc(4);
+        len.writeUInt32BE(payload.length);
+        this.socket.write(Buffer.concat([len, payload]));
+    }
+
+    onData(chunk) {
+        this.buffer = Buffer.concat([this.buffer, chunk]);
+        while (this.buffer.length >= 4) {
+            const size = this.buffer.readUInt32BE(0);
+            if (this.buffer.length < 4 + size) break;
+            const fields = this.buffer.toString('utf8', 4, 4 + size).split('\0');
+            fields.pop(); // trailing terminator
+            this.buffer = this.buffer.subarray(4 + size);
+            this.onMessage(fields);
+        }
+    }
+
+    onMessage(fields) {
+        if (!this.handshakeDone) {
+            this.serverVersion = Number(fields[0]);
+            this.handshakeDone = true;
+            this.send([OUT.START_API, 2, this.config.clientId, '']);
+            return;
+        }
+
+        const msgId = Number(fields[0]);
+        switch (msgId) {
+            case IN.NEXT_VALID_ID:
+                if (this.status !== 'connected') {
+                    this.setStatus('connected');
+                    this.emit('ready');
+                }
+                break;
+            case IN.MANAGED_ACCTS:
+                this.emit('accounts', (fields[2] || '').split(',').filter(Boolean));
+                break;
+            case IN.ERROR:
+                this.onError(fields);
+                break;
+            case IN.HISTORICAL_DATA:
+                this.onHistoricalData(fields);
+                break;
+            case IN.REAL_TIME_BARS:
+                this.onRealTimeBar(fields);
+                break;
+            default:
+                break;
+        }
+    }
+
+    onError(fields) {
+        // [4, version, reqId, code, message, ...]
+        const reqId = Number(fields[2]);
+        const code = Number(fields[3]);
+        const message = fields[4] || '';
+        if (INFO_CODES.has(code)) {
+            this.emit('log', { level: 'INFO', message: `${code} ${message}` });
+            return;
+        }
+        if (code === 1100) this.setStatus('degraded', message);
+        if (code === 1101 || code === 1102) this.setStatus('connected', message);
+
+        const pending = this.pending.get(reqId);
+        if (pending) {
+            clearTimeout(pending.timer);
+            this.pending.delete(reqId);
+            pending.reject(new Error(`TWS error ${code}: ${message}`));
+            return;
+        }
+        if (this.subscriptions.has(reqId)) {
+            this.emit('subscription-error', { reqId, code, message, ...this.subscriptions.get(reqId) });
+            return;
+        }
+        this.emit('log', { level: 'WARN', message: `${code} ${message}` });
+    }
+
+    onHistoricalData(fields) {
+        // [17, reqId, startDate, endDate, count, (date, o, h, l, c, vol, wap, barCount)*]
+        let i = 1;
+        if (this.serverVersion < MIN_SERVER_VER_SYNT_REALTIME_BARS) i++; // version field
+        const reqId = Number(fields[i++]);
+        i += 2; // start / end date strings
+        const count = Number(fields[i++]);
+        const bars = [];
+        for (let n = 0; n < count; n++) {
+            const time = parseBarTime(fields[i++]);
+            const open = Number(fields[i++]);
+            const high = Number(fields[i++]);
+            const low = Number(fields[i++]);
+            const close = Number(fields[i++]);
+            const volume = Number(fields[i++]);
+            i++; // wap
+            if (this.serverVersion < MIN_SERVER_VER_SYNT_REALTIME_BARS) i++; // hasGaps
+            i++; // barCount
+            if (isFinite(time)) bars.push({ timestamp: time, open, high, low, close, volume: volume > 0 ? volume : 0 });
+        }
+
+        const pending = this.pending.get(reqId);
+        if (pending) {
+            clearTimeout(pending.timer);
+            this.pending.delete(reqId);
+            pending.resolve(bars);
+        }
+    }
+
+    onRealTimeBar(fields) {
+        // [50, version, reqId, time, o, h, l, c, volume, wap, count]
+        const reqId = Number(fields[2]);
+        const sub = this.subscriptions.get(reqId);
+        if (!sub) return;
+        this.emit('realtime-bar', {
+            reqId,
+            symbol: sub.symbol,
+            bar: {
+                timestamp: Number(fields[3]) * 1000,
+                open: Number(fields[4]),
+                high: Number(fields[5]),
+                low: Number(fields[6]),
+                close: Number(fields[7]),
+                volume: Math.max(0, Number(fields[8]) || 0)
+            }
+        });
+    }
+
+    contractFields(contract) {
+        const f = [];
+        if (this.serverVersion >= MIN_SERVER_VER_TRADING_CLASS) f.push(contract.conId || 0);
+        f.push(contract.symbol, contract.secType, contract.lastTradeDateOrContractMonth || '', contract.strike || 0,
+            contract.right || '', contract.multiplier || '', contract.exchange, contract.primaryExchange || '',
+            contract.currency, contract.localSymbol || '');
+        if (this.serverVersion >= MIN_SERVER_VER_TRADING_CLASS) f.push(contract.tradingClass || '');
+        return f;
+    }
+
+    /**
+     * Requests historical bars; resolves with normalized bars (timestamps in ms, UTC).
+     */
+    requestHistoricalBars(contractInput, timeframe, { duration, endDateTime = '', whatToShow, useRTH = false, timeoutMs = 60000 } = {}) {
+        if (this.status !== 'connected' && this.status !== 'degraded') return Promise.reject(new Error('Not connected to TWS'));
+        const barSize = BAR_SIZES[timeframe];
+        if (!barSize) return Promise.reject(new Error(`Timeframe ${timeframe} is not supported by TWS`));
+
+        const contract = normalizeContract(contractInput);
+        const reqId = this.nextReqId++;
+        const show = whatToShow || (contract.secType === 'CASH' ? 'MIDPOINT' : 'TRADES');
+
+        const fields = [OUT.REQ_HISTORICAL_DATA];
+        if (this.serverVersion < MIN_SERVER_VER_SYNT_REALTIME_BARS) fields.push(6);
+        fields.push(reqId, ...this.contractFields(contract));
+        fields.push(0, endDateTime, barSize, duration || DEFAULT_DURATIONS[timeframe], useRTH ? 1 : 0, show, 2);
+        if (this.serverVersion >= MIN_SERVER_VER_SYNT_REALTIME_BARS) fields.push(0); // keepUpToDate
+        if (this.serverVersion >= MIN_SERVER_VER_LINKING) fields.push('');
+
+        return new Promise((resolve, reject) => {
+            const timer = setTimeout(() => {
+                this.pending.delete(reqId);
+                try { this.send([OUT.CANCEL_HISTORICAL_DATA, 1, reqId]); } catch (e) {}
+                reject(new Error('Historical data request timed out'));
+            }, timeoutMs);
+            this.pending.set(reqId, { resolve, reject, timer });
+            try { this.send(fields); } catch (e) { clearTimeout(timer); this.pending.delete(reqId); reject(e); }
+        });
+    }
+
+    /**
+     * Subscribes to 5-second real-time bars. Returns the request id used to cancel.
+     */
+    subscribeRealTimeBars(contractInput, symbol, { whatToShow, useRTH = false } = {}) {
+        if (this.status !== 'connected' && this.status !== 'degraded') throw new Error('Not connected to TWS');
+        const contract = normalizeContract(contractInput);
+        const reqId = this.nextReqId++;
+        const show = whatToShow || (contract.secType === 'CASH' ? 'MIDPOINT' : 'TRADES');
+
+        const fields = [OUT.REQ_REAL_TIME_BARS, 3, reqId, ...this.contractFields(contract), 5, show, useRTH ? 1 : 0];
+        if (this.serverVersion >= MIN_SERVER_VER_LINKING) fields.push('');
+        this.subscriptions.set(reqId, { symbol, contract });
+        this.send(fields);
+        return reqId;
+    }
+
+    unsubscribeRealTimeBars(reqId) {
+        if (!this.subscriptions.has(reqId)) return false;
+        this.subscriptions.delete(reqId);
+        if (this.socket) this.send([OUT.CANCEL_REAL_TIME_BARS, 1, reqId]);
+        return true;
+    }
+}
+
+/**
+ * Provider adapter over IbkrClient. `symbol` doubles as the contract spec
+ * ("AAPL", "EURUSD:CASH:IDEALPRO"); pass options.contract to override.
+ */
+const createIbkrProvider = ({ onLog } = {}) => {
+    const client = new IbkrClient();
+    const subscriptions = new Map(); // `${symbol}:${timeframe}` -> { reqId, aggregate }
+
+    client.on('status', ({ status, message }) => publishStatus('ibkr', status, message));
+    client.on('log', (entry) => onLog && onLog(entry));
+    client.on('subscription-error', ({ symbol, code, message }) => onLog && onLog({ level: 'ERROR', message: `${symbol}: ${code} ${message}` }));
+    client.on('realtime-bar', ({ reqId, bar }) => {
+        for (const sub of subscriptions.values()) {
+            if (sub.reqId === reqId) sub.aggregate(bar);
+        }
+    });
+
+    return {
+        id: 'ibkr',
+        name: 'Interactive Brokers (TWS / Gateway)',
+        client,
+        connect: (config) => client.connect(config),
+        disconnect: () => { subscriptions.clear(); client.disconnect(); },
+        getStatus: () => ({ status: client.status, serverVersion: client.serverVersion, subscriptions: Array.from(subscriptions.keys()) }),
+        fetchHistory: (symbol, timeframe, options = {}) => client.requestHistoricalBars(options.contract || symbol, timeframe, options),
+        subscribe: (symbol, timeframe, options = {}) => {
+            const key = `${symbol}:${timeframe}`;
+            if (subscriptions.has(key)) return;
+            const aggregate = createBarAggregator(timeframe, (bar, isClosed) => publishBar('ibkr', symbol, timeframe, bar, isClosed));
+            const reqId = client.subscribeRealTimeBars(options.contract || symbol, symbol, options);
+            subscriptions.set(key, { reqId, aggregate });
+        },
+        unsubscribe: (symbol, timeframe) => {
+            const key = `${symbol}:${timeframe}`;
+            const sub = subscriptions.get(key);
+            if (!sub) return false;
+            subscriptions.delete(key);
+            try { client.unsubscribeRealTimeBars(sub.reqId); } catch (e) {}
+            return true;
+        }
+    };
+};
+
+// Registry entry (see providers/index.js)
+const plugin = {
+    id: 'ibkr',
+    name: 'Interactive Brokers (TWS / Gateway)',
+    capabilities: { history: true, live: true, search: false, credentials: false },
+    settings: [
+        { key: 'host', label: 'Host', type: 'string', default: '127.0.0.1' },
+        { key: 'port', label: 'Port (7497 paper, 7496 live)', type: 'number', default: 7497 },
+        { key: 'clientId', label: 'Client id', type: 'number', default: 1 }
+    ],
+    secrets: [],
+    create: ({ onLog }) => createIbkrProvider({ onLog })
+};
+
+module.exports = { IbkrClient, createIbkrProvider, normalizeContract, BAR_SIZES, plugin };
@@ -0,0 +1,116 @@
+
+// --- MARKET DATA PROVIDER REGISTRY ---
+// Every data source implements the same shape so charts, alerts and recording
+// treat them identically:
+//
+//   id, name
+//   connect(config)                          -> Promise<{ ... }>
+//   disconnect()                             -> Promise<void> | void
+//   getStatus()                              -> { status, ... }
+//   fetchHistory(symbol, timeframe, options) -> Promise<bars[]>   (timestamps in ms, UTC)
+//   subscribe(symbol, timeframe, options)    -> Promise<void> | void  (publishes via liveFeed)
+//   unsubscribe(symbol, timeframe)           -> boolean
+//   searchSymbols(query)                     -> Promise<ProviderSymbol[]>  (optional)
+//   testCredentials()                        -> Promise<{ valid, message }>  (optional, for keyed providers)
+//   subscribeDepth(symbol) / unsubscribeDepth(symbol) / resyncDepth(symbol)  (optional, level 2 via liveFeed.publishDepth)
+//
+// Live data always flows through liveFeed.publishBar / publishQuote / publishTrade
+// (and publishDepth for order books).
+//
+// Each provider module also exports a `plugin` describing itself, so adding a
+// source means adding a module and listing it in BUILTIN_PLUGINS:
+//
+//   id, name
+//   capabilities  { history, live, search, credentials, depth }
+//   settings      [{ key, label, type: 'string' | 'url' | 'number' | 'boolean' | 'select', options?, default }]
+//                 (non-secret; stored per provider and passed to connect)
+//   secrets       ['<id>.<name>', ...]  keys it reads from the secrets store
+//   create(context) -> provider, context: { onLog, getSecret(key), onBackfill(symbol, timeframe, bars),
+//                 readBars(symbol, timeframe) (stored bars, oldest first) }
+
+const BUILTIN_PLUGINS = [
+    require('./ibkr').plugin,
+    require('./alpaca').plugin,
+    require('./polygon').plugin,
+    require('./binance').plugin,
+    require('./yahoo').plugin,
+    require('./fred').plugin,
+    require('./simulator').plugin
+];
+
+const providers = new Map();
+const descriptors = new Map(); // id -> { capabilities, settings, secrets }
+
+const registerProvider = (provider, descriptor = {}) => {
+    if (!provider || !provider.id) throw new Error('Provider must have an id');
+    providers.set(provider.id, provider);
+    descriptors.set(provider.id, {
+        capabilities: { history: true, live: true, search: typeof provider.searchSymbols === 'function', credentials: typeof provider.testCredentials === 'function', depth: typeof provider.subscribeDepth === 'function', ...(descriptor.capabilities || {}) },
+        settings: descriptor.settings || [],
+        secrets: descriptor.secrets || []
+    });
+    return provider;
+};
+
+// contextFor(plugin) builds the context handed to plugin.create
+const loadProviderPlugins = (plugins, contextFor) => plugins.map((plugin) => {
+    if (!plugin || !plugin.id || typeof plugin.create !== 'function') throw new Error('Provider plugin must have an id and create()');
+    if (providers.has(plugin.id)) throw new Error(`Provider already registered: ${plugin.id}`);
+    return registerProvider(plugin.create(contextFor(plugin)), plugin);
+});
+
+const getProvider = (id) => {
+    const provider = providers.get(id);
+    if (!provider) throw new Error(`Unknown provider: ${id}`);
+    return provider;
+};
+
+const getProviderDescriptor = (id) => {
+    getProvider(id);
+    return descriptors.get(id);
+};
+
+const listProviders = () => Array.from(providers.values()).map(p => ({ id: p.id, name: p.name, ...descriptors.get(p.id), ...p.getStatus() }));
+
+const coerceSetting = (field, value) => {
+    switch (field.type) {
+        case 'boolean':
+            return value === true || value === 'true';
+        case 'number': {
+            const n = Number(value);
+            if (!Number.isFinite(n)) throw new Error(`${field.label || field.key} must be a number`);
+            return n;
+        }
+        case 'select':
+            if (!field.options.includes(value)) throw new Error(`${field.label || field.key} must be one of ${field.options.join(', ')}`);
+            return value;
+        case 'url': {
+            const url = String(value).trim();
+            if (url && !/^(https?|wss?):\/\//i.test(url)) throw new Error(`${field.label || field.key} must be an http(s) or ws(s) URL`);
+            return url;
+        }
+        default:
+            return String(value);
+    }
+};
+
+// Known keys only, coerced to their declared type; null/'' restores the default
+const normalizeProviderSettings = (id, values = {}) => {
+    const { settings } = getProviderDescriptor(id);
+    const out = {};
+    settings.forEach((field) => {
+        if (!(field.key in values)) return;
+        const value = values[field.key];
+        if (value == null || value === '') return;
+        out[field.key] = coerceSetting(field, value);
+    });
+    return out;
+};
+
+// Declared defaults overlaid with the stored values
+const resolveProviderSettings = (id, stored = {}) => {
+    const { settings } = getProviderDescriptor(id);
+    return Object.fromEntries(settings.map(field => [field.key, stored[field.key] !== undefined ? stored[field.key] : field.default]));
+};
+
+module.exports = { BUILTIN_PLUGINS, registerProvider, loadProviderPlugins, getProvider, getProviderDescriptor, listProviders, normalizeProviderSettings, resolveProviderSettings };
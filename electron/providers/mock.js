@@ -0,0 +1,79 @@
+
+const { generateSyntheticBars } = require('../synthetic');
+const { TIMEFRAME_MS } = require('../datasets');
+const { publishBar, publishTrade } = require('../liveFeed');
+
+// --- MOCK PROVIDER ---
+// Stands in for every network provider in --test-mode (see testMode.js).
+// History is a synthetic series seeded from symbol + timeframe, so the same
+// request always returns the same bars (ending at the test clock's now). Live
+// data only moves when a test pushes it (test:push-bar), which is published
+// through liveFeed exactly like a real stream.
+
+const MAX_HISTORY_BARS = 5000;
+const DEFAULT_HISTORY_BARS = 500;
+
+// FNV-1a: a stable seed per series
+const seedFor = (text) => {
+    let hash = 0x811c9dc5;
+    for (let i = 0; i < text.length; i++) {
+        hash ^= text.charCodeAt(i);
+        hash = Math.imul(hash, 0x01000193) >>> 0;
+    }
+    return hash;
+};
+
+const createMockProvider = () => {
+    const subscriptions = new Set(); // 'SYMBOL|timeframe'
+    let connected = false;
+
+    const fetchHistory = async (symbol, timeframe, { start, end, limit } = {}) => {
+        const step = TIMEFRAME_MS[timeframe];
+        if (!step) throw new Error(`Unknown timeframe: ${timeframe}`);
+        const last = Math.floor((end != null ? Number(end) : Date.now()) / step) * step;
+        const wanted = start != null ? Math.floor((last - Number(start)) / step) + 1 : (Number(limit) || DEFAULT_HISTORY_BARS);
+        const count = Math.max(1, Math.min(MAX_HISTORY_BARS, wanted));
+        const { bars } = generateSyntheticBars({
+            symbol, timeframe, bars: count, model: 'gbm', startTime: last - (count - 1) * step,
+            seed: seedFor(`${String(symbol).toUpperCase()}|${timeframe}`)
+        });
+        return bars;
+    };
+
+    // Test hook: publishes a bar (and a trade at its close) for a subscribed series
+    const push = (symbol, timeframe, bar, isClosed = false) => {
+        if (!subscriptions.has(`${String(symbol).toUpperCase()}|${timeframe}`)) throw new Error(`Not subscribed: ${symbol} ${timeframe}`);
+        const full = { timestamp: Date.now(), volume: 0, ...bar };
+        publishBar('mock', symbol, timeframe, full, !!isClosed);
+        publishTrade('mock', symbol, { price: full.close, size: full.volume, timestamp: full.timestamp });
+        return full;
+    };
+
+    return {
+        id: 'mock',
+        name: 'Mock (test mode)',
+        connect: async () => { connected = true; return {}; },
+        disconnect: () => { connected = false; subscriptions.clear(); },
+        getStatus: () => ({ status: connected ? 'connected' : 'disconnected', message: null, subscriptions: subscriptions.size }),
+        fetchHistory,
+        subscribe: (symbol, timeframe) => { subscriptions.add(`${String(symbol).toUpperCase()}|${timeframe}`); },
+        unsubscribe: (symbol, timeframe) => subscriptions.delete(`${String(symbol).toUpperCase()}|${timeframe}`),
+        searchSymbols: async (query) => {
+            const symbol = String(query || '').trim().toUpperCase();
+            return symbol ? [{ symbol, name: `${symbol} (mock)`, assetClass: 'stock', exchange: 'MOCK' }] : [];
+        },
+        push
+    };
+};
+
+// Registry entry (see providers/index.js); only loaded in test mode
+const plugin = {
+    id: 'mock',
+    name: 'Mock (test mode)',
+    capabilities: { history: true, live: true, search: true, credentials: false },
+    settings: [],
+    secrets: [],
+    create: () => createMockProvider()
+};
+
+module.exports = { createMockProvider, plugin };
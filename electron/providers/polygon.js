@@ -0,0 +1,264 @@
+
+const WebSocket = require('ws');
+const { publishBar, publishQuote, publishTrade, publishStatus, createBarAggregator } = require('../liveFeed');
+const { TIMEFRAME_MS } = require('../datasets');
+const { request } = require('../network');
+const { wsOptions } = require('../proxy');
+
+// --- POLYGON.IO PROVIDER ---
+// Historical aggregates via /v2/aggs (following next_url pagination) and the
+// real-time WebSocket clusters. Minute aggregates (AM / CA / XA) drive live
+// bars; after a reconnect every symbol is resubscribed and the gap since its
+// last received bar is backfilled over REST.
+
+const REST_URL = 'https://api.polygon.io';
+
+const TIMESPANS = {
+    '1mn': [1, 'minute'], '3m': [3, 'minute'], '5m': [5, 'minute'], '15m': [15, 'minute'], '30m': [30, 'minute'],
+    '1h': [1, 'hour'], '2h': [2, 'hour'], '4h': [4, 'hour'], '12h': [12, 'hour'],
+    '1D': [1, 'day'], '1W': [1, 'week'], '1mo': [1, 'month']
+};
+
+// Polygon ticker prefixes: X: crypto, C: forex, O: options, none: stocks
+const clusterFor = (ticker) => {
+    if (ticker.startsWith('X:')) return 'crypto';
+    if (ticker.startsWith('C:')) return 'forex';
+    if (ticker.startsWith('O:')) return 'options';
+    return 'stocks';
+};
+
+// Streaming channel + symbol for a REST-style ticker (X:BTCUSD -> XA.BTC-USD)
+const streamChannels = (ticker) => {
+    const cluster = clusterFor(ticker);
+    if (cluster === 'crypto') {
+        const pair = ticker.slice(2);
+        const sym = `${pair.slice(0, -3)}-${pair.slice(-3)}`;
+        return { agg: `XA.${sym}`, trade: `XT.${sym}`, quote: `XQ.${sym}` };
+    }
+    if (cluster === 'forex') {
+        const pair = ticker.slice(2);
+        const sym = `${pair.slice(0, 3)}/${pair.slice(3)}`;
+        return { agg: `CA.${sym}`, trade: null, quote: `C.${sym}` };
+    }
+    return { agg: `AM.${ticker}`, trade: `T.${ticker}`, quote: `Q.${ticker}` };
+};
+
+// Maps a stream symbol back to the REST ticker we subscribed with
+const tickerFromStream = (ev, sym, pair) => {
+    if (ev === 'XA' || ev === 'XT' || ev === 'XQ') return `X:${String(pair || sym).replace(/[-/]/g, '')}`;
+    if (ev === 'CA' || ev === 'C') return `C:${String(pair || sym).replace(/[-/]/g, '')}`;
+    return sym;
+};
+
+const createPolygonProvider = ({ getApiKey, onLog, onBackfill } = {}) => {
+    const state = { status: 'disconnected', message: null, realtime: true };
+    const sockets = {}; // cluster -> WebSocket
+    const subscriptions = new Map(); // `${ticker}:${timeframe}` -> { ticker, timeframe, aggregate, lastBarTs }
+    const disconnectedAt = {}; // cluster -> ms
+    let shouldReconnect = false;
+
+    const log = (level, message) => onLog && onLog({ level, message });
+
+    const setStatus = (status, message = null) => {
+        state.status = status;
+        state.message = message;
+        publishStatus('polygon', status, message);
+    };
+
+    const apiKey = () => {
+        const key = getApiKey ? getApiKey() : null;
+        if (!key) throw new Error('Polygon API key is not configured');
+        return key;
+    };
+
+    const tickersFor = (cluster) => Array.from(new Set(
+        Array.from(subscriptions.values()).filter(s => clusterFor(s.ticker) === cluster).map(s => s.ticker)
+    ));
+
+    const channelParams = (tickers) => tickers.flatMap((t) => {
+        const c = streamChannels(t);
+        return [c.agg, c.trade, c.quote].filter(Boolean);
+    }).join(',');
+
+    const send = (cluster, payload) => {
+        const socket = sockets[cluster];
+        if (socket && socket.readyState === WebSocket.OPEN) socket.send(JSON.stringify(payload));
+    };
+
+    const fetchAggregates = async (ticker, timeframe, from, to, limit = 50000) => {
+        const span = TIMESPANS[timeframe];
+        if (!span) throw new Error(`Timeframe ${timeframe} is not supported by Polygon`);
+        const [multiplier, timespan] = span;
+
+        const bars = [];
+        let url = `${REST_URL}/v2/aggs/ticker/${encodeURIComponent(ticker)}/range/${multiplier}/${timespan}/${Math.floor(from)}/${Math.floor(to)}?adjusted=true&sort=asc&limit=50000`;
+        while (url && bars.length < limit) {
+            const sep = url.includes('?') ? '&' : '?';
+            const response = await request('polygon', `${url}${sep}apiKey=${encodeURIComponent(apiKey())}`);
+            if (!response.ok) {
+                const body = await response.text().catch(() => '');
+                throw new Error(`Polygon API ${response.status}: ${body || response.statusText}`);
+            }
+            const page = await response.json();
+            (page.results || []).forEach(r => bars.push({ timestamp: r.t, open: r.o, high: r.h, low: r.l, close: r.c, volume: r.v || 0 }));
+            url = page.next_url || null;
+        }
+        return bars;
+    };
+
+    const backfillCluster = async (cluster) => {
+        const since = disconnectedAt[cluster];
+        delete disconnectedAt[cluster];
+        if (!since) return;
+
+        for (const sub of subscriptions.values()) {
+            if (clusterFor(sub.ticker) !== cluster) continue;
+            const from = sub.lastBarTs || since;
+            try {
+                const bars = await fetchAggregates(sub.ticker, sub.timeframe, from, Date.now());
+                const gap = bars.filter(b => b.timestamp > (sub.lastBarTs || 0));
+                if (gap.length === 0) continue;
+                gap.forEach(b => publishBar('polygon', sub.ticker, sub.timeframe, b, true));
+                sub.lastBarTs = gap[gap.length - 1].timestamp;
+                if (onBackfill) onBackfill(sub.ticker, sub.timeframe, gap);
+                log('INFO', `Backfilled ${gap.length} ${sub.timeframe} bars for ${sub.ticker}`);
+            } catch (err) {
+                log('ERROR', `Backfill failed for ${sub.ticker}: ${err.message}`);
+            }
+        }
+    };
+
+    const onMessage = (cluster, msg) => {
+        switch (msg.ev) {
+            case 'status':
+                if (msg.status === 'connected') {
+                    send(cluster, { action: 'auth', params: apiKey() });
+                } else if (msg.status === 'auth_success') {
+                    setStatus('connected');
+                    const tickers = tickersFor(cluster);
+                    if (tickers.length) send(cluster, { action: 'subscribe', params: channelParams(tickers) });
+                    backfillCluster(cluster);
+                } else if (msg.status === 'auth_failed' || msg.status === 'error') {
+                    setStatus('degraded', msg.message);
+                    log('ERROR', `Polygon ${cluster}: ${msg.message}`);
+                }
+                break;
+            case 'AM':
+            case 'CA':
+            case 'XA': {
+                const ticker = tickerFromStream(msg.ev, msg.sym, msg.pair);
+                const bar = { timestamp: msg.s, open: msg.o, high: msg.h, low: msg.l, close: msg.c, volume: msg.v || 0 };
+                subscriptions.forEach((sub) => {
+                    if (sub.ticker !== ticker) return;
+                    sub.aggregate(bar);
+                });
+                break;
+            }
+            case 'T':
+            case 'XT':
+                publishTrade('polygon', tickerFromStream(msg.ev, msg.sym, msg.pair), { price: msg.p, size: msg.s, timestamp: msg.t });
+                break;
+            case 'Q':
+            case 'XQ':
+            case 'C':
+                publishQuote('polygon', tickerFromStream(msg.ev, msg.sym || msg.p, msg.pair), {
+                    bid: msg.bp ?? msg.b, ask: msg.ap ?? msg.a, bidSize: msg.bs, askSize: msg.as, timestamp: msg.t
+                });
+                break;
+            default:
+                break;
+        }
+    };
+
+    const openSocket = (cluster) => {
+        const host = state.realtime ? 'socket.polygon.io' : 'delayed.polygon.io';
+        const socket = new WebSocket(`wss://${host}/${cluster}`, wsOptions(`wss://${host}/${cluster}`, 'polygon'));
+        sockets[cluster] = socket;
+
+        socket.on('message', (raw) => {
+            let messages;
+            try { messages = JSON.parse(raw.toString()); } catch (e) { return; }
+            (Array.isArray(messages) ? messages : [messages]).forEach(m => onMessage(cluster, m));
+        });
+        socket.on('error', (err) => log('ERROR', `Polygon ${cluster} stream: ${err.message}`));
+        socket.on('close', () => {
+            if (sockets[cluster] === socket) delete sockets[cluster];
+            if (!shouldReconnect) return;
+            if (!disconnectedAt[cluster]) disconnectedAt[cluster] = Date.now();
+            setStatus('degraded', `${cluster} stream closed, reconnecting`);
+            setTimeout(() => { if (shouldReconnect && !sockets[cluster]) openSocket(cluster); }, 3000);
+        });
+    };
+
+    return {
+        id: 'polygon',
+        name: 'Polygon.io',
+
+        connect: async ({ realtime = true } = {}) => {
+            apiKey();
+            state.realtime = realtime;
+            shouldReconnect = true;
+            setStatus('connecting');
+            if (!sockets.stocks) openSocket('stocks');
+            return { realtime };
+        },
+
+        disconnect: () => {
+            shouldReconnect = false;
+            Object.keys(sockets).forEach((cluster) => { sockets[cluster].close(); delete sockets[cluster]; });
+            subscriptions.clear();
+            setStatus('disconnected');
+        },
+
+        getStatus: () => ({ status: state.status, message: state.message, realtime: state.realtime, subscriptions: Array.from(subscriptions.keys()) }),
+
+        testCredentials: async () => {
+            const response = await request('polygon', `${REST_URL}/v3/reference/tickers?limit=1&apiKey=${encodeURIComponent(apiKey())}`);
+            if (response.status === 401 || response.status === 403) return { valid: false, message: `Polygon rejected the API key (${response.status})` };
+            if (!response.ok) throw new Error(`Polygon API ${response.status}: ${response.statusText}`);
+            return { valid: true, message: null };
+        },
+
+        fetchHistory: (ticker, timeframe, { start, end, limit } = {}) => {
+            const tfMs = TIMEFRAME_MS[timeframe] || 60000;
+            const to = end || Date.now();
+            const from = start || to - tfMs * 5000;
+            return fetchAggregates(ticker, timeframe, from, to, limit);
+        },
+
+        subscribe: (ticker, timeframe) => {
+            const key = `${ticker}:${timeframe}`;
+            if (subscriptions.has(key)) return;
+            const cluster = clusterFor(ticker);
+            const isNewTicker = !tickersFor(cluster).includes(ticker);
+            const sub = { ticker, timeframe, lastBarTs: null, aggregate: null };
+            sub.aggregate = createBarAggregator(timeframe, (bar, isClosed) => {
+                if (isClosed) sub.lastBarTs = bar.timestamp;
+                publishBar('polygon', ticker, timeframe, bar, isClosed);
+            });
+            subscriptions.set(key, sub);
+            shouldReconnect = true;
+            if (!sockets[cluster]) openSocket(cluster);
+            else if (isNewTicker) send(cluster, { action: 'subscribe', params: channelParams([ticker]) });
+        },
+
+        unsubscribe: (ticker, timeframe) => {
+            if (!subscriptions.delete(`${ticker}:${timeframe}`)) return false;
+            const cluster = clusterFor(ticker);
+            if (!tickersFor(cluster).includes(ticker)) send(cluster, { action: 'unsubscribe', params: channelParams([ticker]) });
+            return true;
+        }
+    };
+};
+
+// Registry entry (see providers/index.js)
+const plugin = {
+    id: 'polygon',
+    name: 'Polygon.io',
+    capabilities: { history: true, live: true, search: false, credentials: true },
+    settings: [{ key: 'realtime', label: 'Real-time cluster (off = 15-minute delayed)', type: 'boolean', default: true }],
+    secrets: ['polygon.apiKey'],
+    create: ({ onLog, getSecret, onBackfill }) => createPolygonProvider({ onLog, onBackfill, getApiKey: () => getSecret('polygon.apiKey') })
+};
+
+module.exports = { createPolygonProvider, plugin };
@@ -0,0 +1,219 @@
+
+const { generateSyntheticBars } = require('../synthetic');
+const { TIMEFRAME_MS } = require('../datasets');
+const { publishBar, publishQuote, publishTrade, publishStatus } = require('../liveFeed');
+
+// --- SIMULATOR PROVIDER ---
+// Live data with no account: plays a stored dataset, or an endless synthetic
+// series, through liveFeed as if it were streaming, so alerts, recording and
+// the order simulator can be exercised offline.
+//
+// Each (symbol, timeframe) gets a tape. With source 'dataset' (or 'auto' and
+// stored bars for the series) the stored bars are replayed; otherwise a seeded
+// series is generated in chunks as playback goes. Timestamps are rebased so
+// the tape "now" is the current bar: fetchHistory returns what has already
+// played, subscribe continues from there. Every bar is played as ticksPerBar
+// steps (open, extremes, close) — a trade, a quote around it and a partial bar
+// each — and the last step closes the bar. `speed` scales time (60 = an hour
+// bar per minute), `jitter` randomizes each delay by up to that fraction.
+// A replayed dataset stops at its end with status 'ended'.
+
+const MIN_TICK_MS = 50;
+const SYNTHETIC_CHUNK = 1000;
+const DEFAULT_HISTORY_BARS = 500;
+
+// FNV-1a: a stable seed per series
+const seedFor = (text) => {
+    let hash = 0x811c9dc5;
+    for (let i = 0; i < text.length; i++) {
+        hash ^= text.charCodeAt(i);
+        hash = Math.imul(hash, 0x01000193) >>> 0;
+    }
+    return hash;
+};
+
+const tapeKey = (symbol, timeframe) => `${String(symbol).toUpperCase()}|${timeframe}`;
+
+// Prices a bar passes through: open, the nearer extreme, the other one, close
+const pathOf = (bar, steps) => {
+    const first = Math.abs(bar.high - bar.open) < Math.abs(bar.open - bar.low) ? bar.high : bar.low;
+    const second = first === bar.high ? bar.low : bar.high;
+    const anchors = [bar.open, first, second, bar.close];
+    if (steps <= 1) return [bar.close];
+    return Array.from({ length: steps }, (_, i) => {
+        const at = (i / (steps - 1)) * (anchors.length - 1);
+        const lo = Math.floor(at);
+        const hi = Math.min(anchors.length - 1, lo + 1);
+        return anchors[lo] + (anchors[hi] - anchors[lo]) * (at - lo);
+    });
+};
+
+/**
+ * readBars(symbol, timeframe) -> stored bars oldest first ([] when none).
+ */
+const createSimulatorProvider = ({ readBars = () => [], random = Math.random } = {}) => {
+    const settings = { source: 'auto', speed: 60, jitter: 0.2, ticksPerBar: 4, spreadBps: 2, historyBars: DEFAULT_HISTORY_BARS };
+    const tapes = new Map(); // key -> { symbol, timeframe, step, bars, cursor, source, ended, seed, chunks }
+    const playing = new Map(); // key -> { timer, tick, partial }
+    let status = 'disconnected';
+
+    const setStatus = (next, message = null) => {
+        status = next;
+        publishStatus('simulator', next, message);
+    };
+
+    const synthesize = (tape) => {
+        const last = tape.bars[tape.bars.length - 1];
+        const { bars } = generateSyntheticBars({
+            symbol: tape.symbol, timeframe: tape.timeframe, bars: SYNTHETIC_CHUNK, model: 'gbm',
+            startPrice: last ? last.close : 100, startTime: last ? last.timestamp + tape.step : tape.origin,
+            seed: (tape.seed + tape.chunks++) >>> 0
+        });
+        tape.bars.push(...bars);
+    };
+
+    // The tape for a series, built on first use with `historyBars` already played
+    const tapeFor = (symbol, timeframe) => {
+        const key = tapeKey(symbol, timeframe);
+        if (tapes.has(key)) return tapes.get(key);
+        const step = TIMEFRAME_MS[timeframe];
+        if (!step) throw new Error(`Unknown timeframe: ${timeframe}`);
+        const now = Math.floor(Date.now() / step) * step;
+        const stored = settings.source === 'synthetic' ? [] : readBars(String(symbol).toUpperCase(), timeframe);
+        if (settings.source === 'dataset' && stored.length < 2) throw new Error(`No stored bars to replay for ${symbol} ${timeframe}`);
+        let tape;
+        if (stored.length >= 2) {
+            // Replay from historyBars in (or the middle of a short dataset), rebased to now
+            const cursor = Math.min(settings.historyBars, Math.floor(stored.length / 2));
+            const shift = now - stored[cursor].timestamp;
+            tape = { source: 'dataset', bars: stored.map(b => ({ ...b, timestamp: b.timestamp + shift })), cursor };
+        } else {
+            tape = { source: 'synthetic', bars: [], cursor: settings.historyBars, origin: now - settings.historyBars * step };
+        }
+        Object.assign(tape, { symbol: String(symbol).toUpperCase(), timeframe, step, ended: false, seed: seedFor(key), chunks: 0 });
+        while (tape.source === 'synthetic' && tape.bars.length <= tape.cursor) synthesize(tape);
+        tapes.set(key, tape);
+        return tape;
+    };
+
+    // Time to the next step of a bar `step` ms long
+    const nextDelay = (step) => Math.max(MIN_TICK_MS, (step / settings.speed / settings.ticksPerBar) * (1 + settings.jitter * (random() * 2 - 1)));
+
+    const playTick = (key) => {
+        const play = playing.get(key);
+        const tape = tapes.get(key);
+        if (!play || !tape) return;
+        if (tape.cursor >= tape.bars.length) {
+            if (tape.source === 'synthetic') synthesize(tape);
+            else {
+                tape.ended = true;
+                clearTimeout(play.timer);
+                playing.delete(key);
+                publishStatus('simulator', 'ended', `${tape.symbol} ${tape.timeframe}: end of the dataset`);
+                return;
+            }
+        }
+        const bar = tape.bars[tape.cursor];
+        const prices = pathOf(bar, settings.ticksPerBar);
+        const price = prices[play.tick];
+        const closed = play.tick === prices.length - 1;
+        const timestamp = bar.timestamp + Math.floor((play.tick / prices.length) * tape.step);
+        play.partial = play.tick === 0
+            ? { timestamp: bar.timestamp, open: bar.open, high: Math.max(bar.open, price), low: Math.min(bar.open, price), close: price, volume: 0 }
+            : { ...play.partial, high: Math.max(play.partial.high, price), low: Math.min(play.partial.low, price), close: price };
+        const size = (bar.volume || 0) / prices.length;
+        play.partial.volume += size;
+        const half = price * settings.spreadBps / 20000;
+        publishTrade('simulator', tape.symbol, { price, size, timestamp });
+        publishQuote('simulator', tape.symbol, { bid: price - half, ask: price + half, bidSize: 1, askSize: 1, timestamp });
+        // The closing step publishes the stored bar itself, so OHLC match the tape exactly
+        publishBar('simulator', tape.symbol, tape.timeframe, closed ? { ...bar } : { ...play.partial }, closed);
+        if (closed) {
+            tape.cursor++;
+            play.tick = 0;
+        } else play.tick++;
+        play.timer = setTimeout(() => playTick(key), nextDelay(tape.step));
+    };
+
+    const subscribe = (symbol, timeframe) => {
+        if (status !== 'connected') throw new Error('Simulator is not connected');
+        const tape = tapeFor(symbol, timeframe);
+        const key = tapeKey(symbol, timeframe);
+        if (playing.has(key)) return;
+        if (tape.ended) throw new Error(`${tape.symbol} ${timeframe}: the dataset has been played to the end`);
+        const play = { timer: null, tick: 0, partial: null };
+        playing.set(key, play);
+        play.timer = setTimeout(() => playTick(key), nextDelay(tape.step));
+    };
+
+    const unsubscribe = (symbol, timeframe) => {
+        const key = tapeKey(symbol, timeframe);
+        const play = playing.get(key);
+        if (!play) return false;
+        clearTimeout(play.timer);
+        playing.delete(key);
+        return true;
+    };
+
+    return {
+        id: 'simulator',
+        name: 'Simulator',
+        connect: async (config = {}) => {
+            if (config.source !== undefined) settings.source = config.source;
+            ['speed', 'jitter', 'ticksPerBar', 'spreadBps', 'historyBars'].forEach((k) => { if (config[k] != null && config[k] !== '') settings[k] = Number(config[k]); });
+            if (!['auto', 'dataset', 'synthetic'].includes(settings.source)) throw new Error(`Unknown simulator source: ${settings.source}`);
+            if (!(settings.speed >= 0.1 && settings.speed <= 100000)) throw new Error('speed must be between 0.1 and 100000');
+            if (!(settings.jitter >= 0 && settings.jitter <= 1)) throw new Error('jitter must be between 0 and 1');
+            if (!(settings.spreadBps >= 0)) throw new Error('spreadBps must be zero or positive');
+            settings.ticksPerBar = Math.max(1, Math.min(20, Math.round(settings.ticksPerBar)));
+            settings.historyBars = Math.max(10, Math.min(50000, Math.round(settings.historyBars)));
+            setStatus('connected');
+            return { settings: { ...settings } };
+        },
+        disconnect: () => {
+            Array.from(playing.keys()).forEach((key) => { clearTimeout(playing.get(key).timer); });
+            playing.clear();
+            tapes.clear();
+            setStatus('disconnected');
+        },
+        getStatus: () => ({
+            status,
+            message: null,
+            settings: { ...settings },
+            subscriptions: Array.from(playing.keys()),
+            tapes: Array.from(tapes.values()).map(t => ({ symbol: t.symbol, timeframe: t.timeframe, source: t.source, played: t.cursor, length: t.source === 'dataset' ? t.bars.length : null, ended: t.ended }))
+        }),
+        // Bars already played; start / end (ms, rebased time) narrow it down
+        fetchHistory: async (symbol, timeframe, { start, end } = {}) => {
+            const tape = tapeFor(symbol, timeframe);
+            return tape.bars.slice(0, tape.cursor)
+                .filter(b => (start == null || b.timestamp >= Number(start)) && (end == null || b.timestamp <= Number(end)))
+                .map(b => ({ ...b }));
+        },
+        subscribe,
+        unsubscribe,
+        searchSymbols: async (query) => {
+            const symbol = String(query || '').trim().toUpperCase();
+            return symbol ? [{ symbol, name: `${symbol} (simulated)`, assetClass: 'stock', exchange: 'SIM' }] : [];
+        }
+    };
+};
+
+// Registry entry (see providers/index.js)
+const plugin = {
+    id: 'simulator',
+    name: 'Simulator',
+    capabilities: { history: true, live: true, search: true, credentials: false },
+    settings: [
+        { key: 'source', label: 'Source', type: 'select', options: ['auto', 'dataset', 'synthetic'], default: 'auto' },
+        { key: 'speed', label: 'Speed (x real time)', type: 'number', default: 60 },
+        { key: 'jitter', label: 'Timing jitter (0-1)', type: 'number', default: 0.2 },
+        { key: 'ticksPerBar', label: 'Updates per bar', type: 'number', default: 4 },
+        { key: 'spreadBps', label: 'Quote spread (bps)', type: 'number', default: 2 },
+        { key: 'historyBars', label: 'History bars', type: 'number', default: DEFAULT_HISTORY_BARS }
+    ],
+    secrets: [],
+    create: ({ readBars }) => createSimulatorProvider({ readBars })
+};
+
+module.exports = { createSimulatorProvider, plugin };
@@ -0,0 +1,104 @@
+
+const { request } = require('../network');
+
+// --- YAHOO FINANCE PROVIDER (HISTORY ONLY) ---
+// Zero-config historical downloads from the public v8 chart endpoint, plus
+// ticker lookup through the v1 search endpoint. No API key and no streaming;
+// intended for quick "just show me a chart" lookups.
+
+const CHART_URL = 'https://query1.finance.yahoo.com/v8/finance/chart';
+const SEARCH_URL = 'https://query1.finance.yahoo.com/v1/finance/search';
+const HEADERS = { 'User-Agent': 'Mozilla/5.0 (RedPillCharting)', Accept: 'application/json' };
+
+// Yahoo quoteType -> app asset class
+const ASSET_CLASSES = { EQUITY: 'stock', ETF: 'etf', MUTUALFUND: 'fund', INDEX: 'index', CURRENCY: 'forex', CRYPTOCURRENCY: 'crypto', FUTURE: 'futures', OPTION: 'option' };
+
+// App timeframe -> Yahoo interval
+const INTERVALS = {
+    '1mn': '1m', '5m': '5m', '15m': '15m', '30m': '30m', '1h': '60m',
+    '1D': '1d', '1W': '1wk', '1mo': '1mo'
+};
+
+const VALID_RANGES = ['1d', '5d', '1mo', '3mo', '6mo', '1y', '2y', '5y', '10y', 'ytd', 'max'];
+
+// Yahoo only serves recent intraday history; clamp so requests don't just fail
+const MAX_INTRADAY_RANGE = { '1mn': '5d', '5m': '1mo', '15m': '1mo', '30m': '1mo', '1h': '2y' };
+
+const clampRange = (timeframe, range) => {
+    const limit = MAX_INTRADAY_RANGE[timeframe];
+    if (!limit) return range;
+    const order = VALID_RANGES.filter(r => r !== 'ytd');
+    const idx = order.indexOf(range);
+    return idx === -1 || idx > order.indexOf(limit) ? limit : range;
+};
+
+const createYahooProvider = () => {
+    const fetchHistory = async (symbol, timeframe, { range = '1y', start, end } = {}) => {
+        const interval = INTERVALS[timeframe];
+        if (!interval) throw new Error(`Timeframe ${timeframe} is not supported by Yahoo Finance`);
+
+        const params = new URLSearchParams({ interval, includePrePost: 'false', events: 'div,splits' });
+        if (start) {
+            params.set('period1', String(Math.floor(start / 1000)));
+            params.set('period2', String(Math.floor((end || Date.now()) / 1000)));
+        } else {
+            if (!VALID_RANGES.includes(range)) throw new Error(`Invalid range "${range}" (expected one of ${VALID_RANGES.join(', ')})`);
+            params.set('range', clampRange(timeframe, range));
+        }
+
+        const response = await request('yahoo', `${CHART_URL}/${encodeURIComponent(symbol)}?${params}`, { headers: HEADERS });
+        const body = await response.json().catch(() => null);
+        const error = body?.chart?.error;
+        if (!response.ok || error) throw new Error(`Yahoo Finance: ${error?.description || `${response.status} ${response.statusText}`}`);
+
+        const result = body?.chart?.result?.[0];
+        if (!result || !result.timestamp) return [];
+
+        const quote = result.indicators?.quote?.[0] || {};
+        const bars = [];
+        result.timestamp.forEach((t, i) => {
+            const open = quote.open?.[i], high = quote.high?.[i], low = quote.low?.[i], close = quote.close?.[i];
+            // Yahoo pads halted/illiquid periods with nulls
+            if (open == null || high == null || low == null || close == null) return;
+            bars.push({ timestamp: t * 1000, open, high, low, close, volume: quote.volume?.[i] || 0 });
+        });
+        return bars;
+    };
+
+    return {
+        id: 'yahoo',
+        name: 'Yahoo Finance',
+        connect: async () => ({}),
+        disconnect: () => {},
+        getStatus: () => ({ status: 'connected', message: null, historyOnly: true }),
+        fetchHistory,
+        subscribe: () => { throw new Error('Yahoo Finance does not provide live streaming'); },
+        unsubscribe: () => false,
+        searchSymbols: async (query = '') => {
+            const q = String(query).trim();
+            if (!q) return [];
+            const params = new URLSearchParams({ q, quotesCount: '20', newsCount: '0', listsCount: '0' });
+            const response = await request('yahoo', `${SEARCH_URL}?${params}`, { headers: HEADERS });
+            if (!response.ok) throw new Error(`Yahoo Finance search: ${response.status} ${response.statusText}`);
+            const body = await response.json().catch(() => null);
+            return (body?.quotes || []).filter(r => r.symbol).map(r => ({
+                symbol: r.symbol,
+                name: r.longname || r.shortname || null,
+                assetClass: ASSET_CLASSES[r.quoteType] || String(r.quoteType || 'other').toLowerCase(),
+                exchange: r.exchDisp || r.exchange || 'Yahoo'
+            }));
+        }
+    };
+};
+
+// Registry entry (see providers/index.js)
+const plugin = {
+    id: 'yahoo',
+    name: 'Yahoo Finance',
+    capabilities: { history: true, live: false, search: true, credentials: false },
+    settings: [],
+    secrets: [],
+    create: () => createYahooProvider()
+};
+
+module.exports = { createYahooProvider, VALID_RANGES, plugin };
@@ -0,0 +1,260 @@
+
+const http = require('http');
+const https = require('https');
+const net = require('net');
+const tls = require('tls');
+const { Readable } = require('stream');
+const { assertAllowedUrl, tlsOptionsFor, tlsPolicyVersion } = require('./tlsPolicy');
+
+// --- OUTBOUND PROXY ---
+// Routes provider REST calls, WebSockets, news feeds and webhooks through an
+// HTTP (CONNECT) or SOCKS5 proxy. Every connection is tunnelled, plain http
+// included, so one agent type covers both fetch-style requests and `ws`
+// sockets; TLS is negotiated end to end through the tunnel. Node's global
+// fetch can't take an agent, so proxied requests use a small fetch over
+// http(s).request that returns a standard Response. The same agents carry the
+// provider's TLS policy (pinning / strict mode, see tlsPolicy.js), so pinned
+// providers use them even without a proxy. The password is not part of the
+// config: it comes from the secrets store each time a tunnel opens.
+
+const PROXY_MODES = ['none', 'http', 'socks5'];
+const DEFAULT_PROXY_CONFIG = { mode: 'none', host: '', port: 8080, username: '', bypass: ['localhost', '127.0.0.1', '::1'] };
+const CONNECT_TIMEOUT_MS = 15000;
+const TEST_URL = 'https://www.gstatic.com/generate_204';
+const NULL_BODY_STATUSES = [101, 204, 205, 304];
+
+const SOCKS_ERRORS = {
+    1: 'general failure', 2: 'not allowed by ruleset', 3: 'network unreachable', 4: 'host unreachable',
+    5: 'connection refused', 6: 'TTL expired', 7: 'command not supported', 8: 'address type not supported'
+};
+
+let state = { config: DEFAULT_PROXY_CONFIG, getPassword: () => null, agents: new Map(), tlsVersion: 0, refuse: null };
+
+// Known keys only, so a stray password never ends up in the plain settings table
+const normalizeProxyConfig = (config = {}) => {
+    const next = { ...DEFAULT_PROXY_CONFIG };
+    Object.keys(DEFAULT_PROXY_CONFIG).forEach((key) => { if (config[key] !== undefined) next[key] = config[key]; });
+    if (!PROXY_MODES.includes(next.mode)) throw new Error(`Invalid proxy mode: ${next.mode}`);
+    next.host = String(next.host || '').trim();
+    next.port = Number(next.port);
+    next.username = String(next.username || '');
+    next.bypass = (Array.isArray(next.bypass) ? next.bypass : String(next.bypass).split(','))
+        .map(rule => String(rule).trim().toLowerCase()).filter(Boolean);
+    if (next.mode !== 'none') {
+        if (!next.host) throw new Error('Proxy host is required');
+        if (!Number.isInteger(next.port) || next.port < 1 || next.port > 65535) throw new Error(`Invalid proxy port: ${config.port}`);
+    }
+    return next;
+};
+
+const connectViaHttp = (proxy, host, port) => new Promise((resolve, reject) => {
+    const headers = { Host: `${host}:${port}` };
+    if (proxy.username) headers['Proxy-Authorization'] = `Basic ${Buffer.from(`${proxy.username}:${proxy.password || ''}`).toString('base64')}`;
+    const req = http.request({ host: proxy.host, port: proxy.port, method: 'CONNECT', path: `${host}:${port}`, headers, agent: false, timeout: CONNECT_TIMEOUT_MS });
+    req.once('connect', (res, socket) => {
+        if (res.statusCode === 200) return resolve(socket);
+        socket.destroy();
+        reject(new Error(res.statusCode === 407
+            ? 'Proxy authentication failed (407): check the proxy username and password'
+            : `Proxy refused tunnel to ${host}:${port}: ${res.statusCode} ${res.statusMessage}`));
+    });
+    req.once('timeout', () => req.destroy(new Error('timed out')));
+    req.once('error', (err) => reject(new Error(`Proxy ${proxy.host}:${proxy.port} unreachable: ${err.message}`)));
+    req.end();
+});
+
+// RFC 1928 CONNECT with optional RFC 1929 username / password; the proxy resolves the host name
+const connectViaSocks = (proxy, host, port) => new Promise((resolve, reject) => {
+    const socket = net.connect(proxy.port, proxy.host);
+    let buffer = Buffer.alloc(0);
+    let stage = 'greeting';
+
+    const onError = (err) => fail(`Proxy ${proxy.host}:${proxy.port} unreachable: ${err.message}`);
+    const fail = (message) => {
+        socket.destroy();
+        reject(new Error(message));
+    };
+    const sendConnect = () => {
+        const name = Buffer.from(host);
+        socket.write(Buffer.concat([Buffer.from([5, 1, 0, 3, name.length]), name, Buffer.from([port >> 8, port & 255])]));
+        stage = 'connect';
+    };
+    const onData = (chunk) => {
+        buffer = Buffer.concat([buffer, chunk]);
+        if (stage === 'greeting') {
+            if (buffer.length < 2) return;
+            const method = buffer[1];
+            buffer = buffer.subarray(2);
+            if (method === 0) {
+                sendConnect();
+            } else if (method === 2 && proxy.username) {
+                const user = Buffer.from(proxy.username);
+                const pass = Buffer.from(proxy.password || '');
+                socket.write(Buffer.concat([Buffer.from([1, user.length]), user, Buffer.from([pass.length]), pass]));
+                stage = 'auth';
+            } else {
+                return fail(method === 2 ? 'SOCKS proxy requires a username and password' : 'SOCKS proxy offered no supported authentication method');
+            }
+        }
+        if (stage === 'auth') {
+            if (buffer.length < 2) return;
+            if (buffer[1] !== 0) return fail('SOCKS proxy authentication failed: check the proxy username and password');
+            buffer = buffer.subarray(2);
+            sendConnect();
+        }
+        if (stage === 'connect') {
+            if (buffer.length < 5) return;
+            if (buffer[1] !== 0) return fail(`SOCKS proxy could not reach ${host}:${port} (${SOCKS_ERRORS[buffer[1]] || `code ${buffer[1]}`})`);
+            const addressLength = buffer[3] === 1 ? 4 : buffer[3] === 4 ? 16 : 1 + buffer[4];
+            const total = 4 + addressLength + 2;
+            if (buffer.length < total) return;
+            socket.removeListener('data', onData);
+            socket.removeListener('error', onError);
+            socket.setTimeout(0);
+            if (buffer.length > total) socket.unshift(buffer.subarray(total));
+            resolve(socket);
+        }
+    };
+
+    socket.setTimeout(CONNECT_TIMEOUT_MS, () => fail(`Proxy ${proxy.host}:${proxy.port} timed out`));
+    socket.on('error', onError);
+    socket.on('data', onData);
+    socket.once('connect', () => socket.write(Buffer.from(proxy.username ? [5, 2, 0, 2] : [5, 1, 0])));
+});
+
+const openTunnel = (proxy, host, port) => (proxy.mode === 'socks5' ? connectViaSocks : connectViaHttp)(proxy, host, port);
+
+// An http(s).Agent whose sockets are tunnels; usable by http.request and by `ws`.
+// tlsOptions (pinning / strict mode) reach tls.connect through the agent's options.
+const createTunnelAgent = (config, getPassword, secure, tlsOptions = null) => {
+    const agent = secure ? new https.Agent(tlsOptions || {}) : new http.Agent();
+    agent.createConnection = (options, callback) => {
+        const proxy = { ...config, password: getPassword() };
+        const port = Number(options.port) || (secure ? 443 : 80);
+        openTunnel(proxy, options.host, port)
+            .then(socket => callback(null, secure ? tls.connect({ ...options, socket, servername: options.servername || options.host }) : socket))
+            .catch(callback);
+    };
+    return agent;
+};
+
+const isBypassed = (config, host) => {
+    const name = String(host).toLowerCase().replace(/^\[|\]$/g, '');
+    return config.bypass.some(rule => rule === '*'
+        || rule === name
+        || (rule.startsWith('*.') && name.endsWith(rule.slice(1)))
+        || (rule.startsWith('.') && name.endsWith(rule)));
+};
+
+const resetAgents = () => {
+    state.agents.forEach(agent => agent.destroy());
+    state.agents = new Map();
+    state.tlsVersion = tlsPolicyVersion();
+};
+
+// refuse(url) -> true blocks the request with code NETWORK_DISABLED (test mode); null lifts it
+const setNetworkGuard = (refuse) => {
+    state.refuse = refuse;
+};
+
+const assertNetworkAllowed = (url) => {
+    if (state.refuse && state.refuse(url)) {
+        throw Object.assign(new Error(`Network access is disabled (${new URL(url).host})`), { code: 'NETWORK_DISABLED' });
+    }
+};
+
+/**
+ * Agent for a provider's URL under the active proxy config and TLS policy, or
+ * undefined for a plain direct connection. Throws when the TLS policy forbids the URL.
+ */
+const agentFor = (url, provider = null) => {
+    assertNetworkAllowed(url);
+    assertAllowedUrl(provider, url);
+    const target = new URL(url);
+    const secure = target.protocol === 'https:' || target.protocol === 'wss:';
+    const tunnelled = state.config.mode !== 'none' && !isBypassed(state.config, target.hostname);
+    const tlsOptions = secure ? tlsOptionsFor(provider) : null;
+    if (!tunnelled && !tlsOptions) return undefined;
+    if (state.tlsVersion !== tlsPolicyVersion()) resetAgents();
+    const key = `${tunnelled ? 'tunnel' : 'direct'}|${secure ? 'tls' : 'plain'}|${tlsOptions ? provider : ''}`;
+    if (!state.agents.has(key)) {
+        state.agents.set(key, tunnelled ? createTunnelAgent(state.config, state.getPassword, secure, tlsOptions) : new https.Agent(tlsOptions));
+    }
+    return state.agents.get(key);
+};
+
+const configureProxy = (config, getPassword = state.getPassword) => {
+    const next = normalizeProxyConfig(config);
+    state.config = next;
+    state.getPassword = getPassword;
+    resetAgents();
+    return next;
+};
+
+const fetchViaAgent = (url, init, agent) => new Promise((resolve, reject) => {
+    const target = new URL(url);
+    const method = (init.method || 'GET').toUpperCase();
+    const headers = Object.fromEntries(new Headers(init.headers || {}).entries());
+    // No transparent decompression here, unlike fetch
+    if (!headers['accept-encoding']) headers['accept-encoding'] = 'identity';
+    const body = init.body == null ? null : Buffer.isBuffer(init.body) ? init.body : Buffer.from(String(init.body));
+    if (body) headers['content-length'] = String(body.length);
+    const lib = target.protocol === 'https:' ? https : http;
+    const req = lib.request(target, { method, headers, agent, signal: init.signal }, (res) => {
+        const responseHeaders = new Headers();
+        for (let i = 0; i < res.rawHeaders.length; i += 2) responseHeaders.append(res.rawHeaders[i], res.rawHeaders[i + 1]);
+        const empty = method === 'HEAD' || NULL_BODY_STATUSES.includes(res.statusCode);
+        if (empty) res.resume();
+        resolve(new Response(empty ? null : Readable.toWeb(res), { status: res.statusCode, statusText: res.statusMessage, headers: responseHeaders }));
+    });
+    req.once('error', reject);
+    if (body) req.write(body);
+    req.end();
+});
+
+/**
+ * Drop-in for fetch(url, init) that honours the proxy config and the
+ * provider's TLS policy. init: { method, headers, body (string / Buffer), signal }
+ */
+const proxyFetch = async (url, init = {}, provider = null) => {
+    const agent = agentFor(url, provider);
+    return agent ? fetchViaAgent(url, init, agent) : fetch(url, init);
+};
+
+// Options for `new WebSocket(url, options)`
+const wsOptions = (url, provider = null) => {
+    const agent = agentFor(url, provider);
+    return agent ? { agent } : {};
+};
+
+/**
+ * Connectivity check through a proxy config (the active one by default).
+ * Returns { success, status?, latencyMs, via, error? } and never throws.
+ */
+const testProxy = async ({ config = null, password, url = TEST_URL } = {}) => {
+    const started = Date.now();
+    let agent = null;
+    let via = null;
+    try {
+        assertNetworkAllowed(url);
+        const candidate = config ? normalizeProxyConfig(config) : state.config;
+        const getPassword = password !== undefined ? () => password : state.getPassword;
+        via = candidate.mode === 'none' ? 'direct' : `${candidate.mode}://${candidate.host}:${candidate.port}`;
+        const init = { headers: { 'User-Agent': 'RedPillCharting/ProxyTest' }, signal: AbortSignal.timeout(CONNECT_TIMEOUT_MS) };
+        let response;
+        if (candidate.mode === 'none') {
+            response = await fetch(url, init);
+        } else {
+            agent = createTunnelAgent(candidate, getPassword, new URL(url).protocol === 'https:');
+            response = await fetchViaAgent(url, init, agent);
+        }
+        if (response.body) await response.body.cancel().catch(() => {});
+        return { success: response.status < 500, status: response.status, latencyMs: Date.now() - started, via };
+    } catch (err) {
+        return { success: false, latencyMs: Date.now() - started, via, error: err.message };
+    } finally {
+        if (agent) agent.destroy();
+    }
+};
+
+module.exports = { PROXY_MODES, DEFAULT_PROXY_CONFIG, normalizeProxyConfig, configureProxy, agentFor, proxyFetch, wsOptions, testProxy, setNetworkGuard };
@@ -0,0 +1,161 @@
+
+const path = require('path');
+
+// --- QUICK SWITCHER ---
+// Backs the Ctrl+K switcher: candidates are every saved chart (the drawings
+// table's source ids), every symbol with data and every library file, ranked
+// by how well they match the typed prefix and by frecency — how often and how
+// recently they were opened. Each open adds 1 to a score that halves every
+// HALF_LIFE_DAYS, so a chart used daily last month falls behind one used
+// twice this week. Usage lives in `switch_usage` and is mirrored in memory,
+// and the candidate list is cached (until invalidated, at most
+// CANDIDATE_TTL_MS), so a keystroke costs a scan of an in-memory array, not
+// a query.
+
+const KINDS = ['chart', 'symbol', 'file'];
+const HALF_LIFE_DAYS = 7;
+const HALF_LIFE_MS = HALF_LIFE_DAYS * 24 * 60 * 60 * 1000;
+const REOPEN_WINDOW_MS = 60 * 1000; // a chart reloading its state isn't another open
+const CANDIDATE_TTL_MS = 30 * 1000;
+const DEFAULT_LIMIT = 20;
+const MAX_USAGE_ROWS = 5000;
+
+const initializeSwitchUsageTable = (db) => {
+    db.exec(`
+        CREATE TABLE IF NOT EXISTS switch_usage (
+            kind TEXT,
+            ref TEXT,
+            opens INTEGER,
+            score REAL,
+            last_used INTEGER,
+            PRIMARY KEY (kind, ref)
+        );
+    `);
+};
+
+const decayed = (usage, now) => (usage ? usage.score * Math.pow(0.5, (now - usage.lastUsed) / HALF_LIFE_MS) : 0);
+
+// Source ids are file paths or dataset ids ('SYMBOL:1h')
+const chartLabel = (sourceId) => {
+    const text = String(sourceId);
+    if (/[\\/]/.test(text)) return path.basename(text).replace(/\.[^.]+$/, '');
+    const [symbol, timeframe] = text.split(':');
+    return timeframe ? `${symbol} · ${timeframe}` : text;
+};
+
+// 4 = exact, 3 = prefix, 2 = word prefix, 1 = substring, 0.5 = letters in order
+const matchScore = (text, q) => {
+    if (!q) return 1;
+    const lower = String(text || '').toLowerCase();
+    if (lower === q) return 4;
+    if (lower.startsWith(q)) return 3;
+    if (new RegExp(`[\\s_.:·/\\\\-]${q.replace(/[.*+?^${}()|[\]\\]/g, '\\$&')}`).test(lower)) return 2;
+    if (lower.includes(q)) return 1;
+    let at = 0;
+    for (const ch of lower) { if (ch === q[at]) at++; if (at === q.length) return 0.5; }
+    return 0;
+};
+
+/**
+ * getDb() -> the open database; listFiles() -> library files [{ path, name, folder }];
+ * persist: false keeps usage in memory only (read-only mode).
+ */
+const createQuickSwitch = ({ getDb, listFiles = () => [], persist = () => true }) => {
+    let usage = null; // `${kind}\t${ref}` -> { opens, score, lastUsed }
+    let candidates = null;
+    let candidatesAt = 0;
+
+    const loadUsage = () => {
+        if (usage) return usage;
+        usage = new Map();
+        const db = getDb();
+        if (db) {
+            db.prepare('SELECT kind, ref, opens, score, last_used FROM switch_usage').all()
+                .forEach(r => usage.set(`${r.kind}\t${r.ref}`, { opens: r.opens, score: r.score, lastUsed: r.last_used }));
+        }
+        return usage;
+    };
+
+    const loadCandidates = () => {
+        if (candidates && Date.now() - candidatesAt < CANDIDATE_TTL_MS) return candidates;
+        const db = getDb();
+        const list = [];
+        const symbols = new Map();
+        if (db) {
+            db.prepare('SELECT symbol FROM drawings').all().forEach(r => list.push({ kind: 'chart', ref: r.symbol, label: chartLabel(r.symbol), detail: r.symbol }));
+            db.prepare('SELECT symbol, timeframe FROM datasets ORDER BY symbol').all().forEach((r) => {
+                if (!symbols.has(r.symbol)) symbols.set(r.symbol, []);
+                symbols.get(r.symbol).push(r.timeframe);
+            });
+        }
+        symbols.forEach((timeframes, symbol) => list.push({ kind: 'symbol', ref: symbol, label: symbol, detail: timeframes.join(', ') }));
+        listFiles().forEach(f => list.push({ kind: 'file', ref: f.path, label: f.name, detail: f.folder }));
+        candidates = list;
+        candidatesAt = Date.now();
+        return candidates;
+    };
+
+    // The candidate list is rebuilt on the next query
+    const invalidate = () => { candidates = null; };
+
+    /**
+     * Counts one open of `ref`. Returns false for a repeat within
+     * REOPEN_WINDOW_MS. The usage table is trimmed to its MAX_USAGE_ROWS
+     * most relevant rows.
+     */
+    const record = (kind, ref, now = Date.now()) => {
+        if (!KINDS.includes(kind)) throw new Error(`kind must be one of ${KINDS.join(', ')}`);
+        if (!ref) throw new Error('ref is required');
+        const key = `${kind}\t${ref}`;
+        const map = loadUsage();
+        const previous = map.get(key);
+        if (previous && now - previous.lastUsed < REOPEN_WINDOW_MS) return false;
+        const next = { opens: (previous ? previous.opens : 0) + 1, score: decayed(previous, now) + 1, lastUsed: now };
+        map.set(key, next);
+        const db = getDb();
+        if (db && persist()) {
+            db.prepare('INSERT OR REPLACE INTO switch_usage (kind, ref, opens, score, last_used) VALUES (?, ?, ?, ?, ?)').run(kind, String(ref), next.opens, next.score, next.lastUsed);
+            if (map.size > MAX_USAGE_ROWS) {
+                const drop = Array.from(map.entries()).sort((a, b) => decayed(a[1], now) - decayed(b[1], now)).slice(0, map.size - MAX_USAGE_ROWS);
+                const remove = db.prepare('DELETE FROM switch_usage WHERE kind = ? AND ref = ?');
+                db.transaction(() => drop.forEach(([k]) => { map.delete(k); remove.run(...k.split('\t')); }))();
+            }
+        }
+        return true;
+    };
+
+    /**
+     * Candidates for `prefix`, best first: [{ kind, ref, label, detail,
+     * opens, lastUsed, score }]. An empty prefix lists what was used most
+     * lately. options: { limit?, kinds? }.
+     */
+    const getCandidates = (prefix = '', { limit = DEFAULT_LIMIT, kinds = null } = {}) => {
+        const q = String(prefix || '').trim().toLowerCase();
+        const now = Date.now();
+        const map = loadUsage();
+        const results = [];
+        loadCandidates().forEach((c) => {
+            if (kinds && !kinds.includes(c.kind)) return;
+            const u = map.get(`${c.kind}\t${c.ref}`);
+            if (!q && !u) return;
+            const match = q ? Math.max(matchScore(c.label, q), matchScore(c.ref, q) * 0.9) : 1;
+            if (!match) return;
+            const frecency = decayed(u, now);
+            results.push({ ...c, opens: u ? u.opens : 0, lastUsed: u ? u.lastUsed : null, score: match * (1 + Math.log2(1 + frecency)) });
+        });
+        return results
+            .sort((a, b) => b.score - a.score || (b.lastUsed || 0) - (a.lastUsed || 0) || a.label.localeCompare(b.label))
+            .slice(0, Math.max(1, Math.min(200, Number(limit) || DEFAULT_LIMIT)));
+    };
+
+    const clear = () => {
+        const db = getDb();
+        const deleted = db ? db.prepare('DELETE FROM switch_usage').run().changes : 0;
+        usage = new Map();
+        return deleted;
+    };
+
+    return { record, getCandidates, invalidate, clear };
+};
+
+module.exports = { KINDS, initializeSwitchUsageTable, createQuickSwitch };
@@ -0,0 +1,57 @@
+
+// --- READ-ONLY MODE ---
+// For reviewing a backup or someone else's workspace without touching it:
+// started with --read-only or toggled at runtime. Every command that persists
+// something is answered with { success: false, readOnly: true } before its
+// handler runs, and the database connection gets PRAGMA query_only so a
+// background writer that slips through fails instead of modifying the file.
+
+const READ_ONLY_FLAG = '--read-only';
+
+// Commands that write to the workspace (database, settings or files under userData)
+const PERSISTENCE_CHANNELS = new Set([
+    'market:build-time-index', 'market:delete-time-index', 'market:import-metatrader', 'market:download-history',
+    'watch-folders:set-config', 'watch-folders:rescan',
+    'datasets:generate-synthetic', 'datasets:build-continuous', 'datasets:rebuild-continuous', 'datasets:merge', 'datasets:rebuild-merged',
+    'bars:edit', 'bars:delete', 'bars:revert',
+    'quick-switch:clear-history', 'plugins:set-grants',
+    'secrets:set', 'secrets:set-meta', 'secrets:delete',
+    'proxy:set-config', 'tls:set-policy', 'providers:fetch-history', 'providers:set-settings', 'background:set-config', 'news:configure',
+    'alerts:save', 'alerts:delete', 'alerts:import', 'alerts:set-webhook-config', 'notifiers:set-config',
+    'audio:import-sound', 'audio:delete-sound', 'compute:set-config', 'compute:cache-invalidate', 'annotations:reindex',
+    'session-levels:set-config', 'session-levels:sync', 'dataset-annotations:create', 'dataset-annotations:update', 'dataset-annotations:delete',
+    'drawings:save-state', 'drawings:patch-state', 'drawings:set-locked', 'drawings:set-group', 'drawings:set-group-locked', 'drawings:undo', 'drawings:redo', 'drawings:delete-all', 'drawings:import-tradingview',
+    'drawings:trash-orphaned', 'drawings:restore-from-trash', 'drawings:empty-trash',
+    'trades:save', 'trades:import-statement',
+    'sim:place-order', 'sim:cancel-order', 'sim:push-price', 'sim:create-account', 'sim:update-settings', 'sim:reset-account',
+    'symbols:set-meta', 'symbols:delete-meta', 'dossiers:update', 'dossiers:delete', 'orderflow:set-config',
+    'fx:set-config', 'fx:set-rate', 'fx:delete-rate',
+    'calendar:import-file', 'calendar:refresh', 'calendar:delete-events', 'calendar:configure', 'ical:unsubscribe',
+    'notes:bulk-update', 'notes:archive', 'notes:restore-version', 'notes:set-auto-archive', 'notes:save-template', 'notes:delete-template', 'exports:save-template', 'exports:delete-template',
+    'notes:create-from-template', 'notes:create-board', 'notes:rename-board', 'notes:archive-board',
+    'attachments:paste-image', 'attachments:delete', 'attachments:prune',
+    'scanner:save-screen', 'scanner:delete-screen', 'brokers:configure',
+    'integrity:run', 'crash:configure', 'usage:configure', 'usage:reset-install-id', 'usage:clear',
+    'storage:purge-caches', 'storage:delete-unused-sounds', 'storage:compact-database', 'storage:set-compaction-config',
+    'live-export:save', 'live-export:delete', 'templates:save', 'templates:delete', 'templates:apply',
+    'themes:save', 'themes:delete', 'themes:apply', 'themes:import', 'layouts:save', 'settings:import', 'onboarding:complete-step', 'onboarding:reset', 'demo:install',
+    'keybindings:set', 'keybindings:reset', 'i18n:set-language', 'power:set-override', 'appearance:set-config', 'idle:set-config', 'clipboard:set-watch-config', 'inbox:set-config', 'companion:set-config', 'inbox:rotate-token', 'inbox:mark-read', 'inbox:delete',
+    'scheduler:create-job', 'scheduler:update-job', 'scheduler:delete-job', 'scheduler:run-now', 'maintenance:run-end-of-day',
+    'downloads:enqueue', 'downloads:pause', 'downloads:resume', 'downloads:cancel', 'downloads:remove',
+    'downloads:clear-finished', 'downloads:set-config',
+    'profiles:create', 'profiles:rename', 'profiles:delete', 'profiles:configure',
+    'quick-switch:record', 'crash:submit-report', 'crash:delete-report', 'news:refresh'
+]);
+
+/**
+ * Wraps ipcMain.handle like instrumentIpc does. isEnabled() is checked per
+ * call; blocked(channel) builds the answer for a refused command.
+ */
+const installReadOnlyGuard = (ipcMain, { isEnabled, blocked }) => {
+    const originalHandle = ipcMain.handle.bind(ipcMain);
+    ipcMain.handle = (channel, handler) => originalHandle(channel, PERSISTENCE_CHANNELS.has(channel)
+        ? async (event, ...args) => (isEnabled() ? blocked(channel) : handler(event, ...args))
+        : handler);
+};
+
+module.exports = { READ_ONLY_FLAG, PERSISTENCE_CHANNELS, installReadOnlyGuard };
@@ -0,0 +1,98 @@
+
+// --- RELATIVE STRENGTH / ROLLING BETA ---
+// A dataset against a benchmark of the same timeframe. Points are taken at
+// the dataset's timestamps; the benchmark is matched exactly, or ('previous')
+// carried forward from its last bar for at most `maxStaleBars` bars, which
+// covers differing holidays and trading hours. Points without a benchmark
+// value are dropped, and a return is only taken between two points that
+// follow each other in the dataset, so a gap never shows up as one big move.
+//
+//   ratio  close / benchmark close, and both rebased to 100 at the first point
+//   beta   rolling beta, alpha (per bar, %) and correlation of bar returns
+//          over `window` returns
+
+const RELATIVE_MODES = ['ratio', 'beta'];
+const DEFAULTS = { window: 60, fill: 'previous', maxStaleBars: 5 };
+const MAX_WINDOW = 5000;
+
+const normalizeOptions = (options = {}) => {
+    const opts = { ...DEFAULTS, ...options };
+    opts.window = Math.round(Number(opts.window));
+    if (!Number.isInteger(opts.window) || opts.window < 2 || opts.window > MAX_WINDOW) throw new Error(`window must be a whole number from 2 to ${MAX_WINDOW}`);
+    if (!['previous', 'exact'].includes(opts.fill)) throw new Error(`fill must be 'previous' or 'exact'`);
+    opts.maxStaleBars = Math.max(0, Math.round(Number(opts.maxStaleBars) || 0));
+    return opts;
+};
+
+/**
+ * bars / benchmark: oldest first. Returns aligned points
+ * [{ timestamp, close, benchmark, index }] where index is the position in
+ * bars, plus how many dataset bars had no benchmark value.
+ */
+const alignSeries = (bars, benchmark, barMs, { fill = 'previous', maxStaleBars = 5 } = {}) => {
+    const points = [];
+    let j = 0;
+    let missing = 0;
+    bars.forEach((bar, index) => {
+        while (j < benchmark.length && benchmark[j].timestamp <= bar.timestamp) j++;
+        const match = benchmark[j - 1];
+        const usable = match && match.close && bar.close
+            && (match.timestamp === bar.timestamp || (fill === 'previous' && bar.timestamp - match.timestamp <= maxStaleBars * barMs));
+        if (usable) points.push({ timestamp: bar.timestamp, close: bar.close, benchmark: match.close, index });
+        else missing++;
+    });
+    return { points, missing };
+};
+
+const ratioRows = (points) => {
+    if (!points.length) return [];
+    const base = points[0];
+    return points.map(p => [p.timestamp, p.close / p.benchmark, ((p.close / base.close) / (p.benchmark / base.benchmark)) * 100]);
+};
+
+// Running sums over the last `window` return pairs
+const betaRows = (points, window) => {
+    const xs = [];
+    const ys = [];
+    let sx = 0; let sy = 0; let sxx = 0; let syy = 0; let sxy = 0;
+    const rows = [];
+    for (let i = 1; i < points.length; i++) {
+        const prev = points[i - 1];
+        const cur = points[i];
+        if (cur.index !== prev.index + 1) continue;
+        const x = (cur.benchmark / prev.benchmark - 1) * 100;
+        const y = (cur.close / prev.close - 1) * 100;
+        xs.push(x); ys.push(y);
+        sx += x; sy += y; sxx += x * x; syy += y * y; sxy += x * y;
+        if (xs.length > window) {
+            const ox = xs.shift();
+            const oy = ys.shift();
+            sx -= ox; sy -= oy; sxx -= ox * ox; syy -= oy * oy; sxy -= ox * oy;
+        }
+        if (xs.length < window) continue;
+        const n = window;
+        const varX = sxx - (sx * sx) / n;
+        const varY = syy - (sy * sy) / n;
+        const cov = sxy - (sx * sy) / n;
+        const beta = varX > 1e-12 ? cov / varX : null;
+        const alpha = beta != null ? sy / n - beta * (sx / n) : null;
+        const correlation = varX > 1e-12 && varY > 1e-12 ? cov / Math.sqrt(varX * varY) : null;
+        rows.push([cur.timestamp, beta, alpha, correlation]);
+    }
+    return rows;
+};
+
+/**
+ * mode: 'ratio' | 'beta'; options: { window?, fill?: 'previous' | 'exact', maxStaleBars? }.
+ * Returns { mode, columns, rows, aligned, missing } with one row per point.
+ */
+const computeRelative = (bars, benchmark, barMs, mode, options = {}) => {
+    if (!RELATIVE_MODES.includes(mode)) throw new Error(`Unknown relative mode: ${mode}`);
+    const opts = normalizeOptions(options);
+    const { points, missing } = alignSeries(bars, benchmark, barMs, opts);
+    const result = { mode, aligned: points.length, missing };
+    if (mode === 'ratio') return { ...result, columns: ['timestamp', 'ratio', 'relative'], rows: ratioRows(points) };
+    return { ...result, window: opts.window, columns: ['timestamp', 'beta', 'alpha', 'correlation'], rows: betaRows(points, opts.window) };
+};
+
+module.exports = { RELATIVE_MODES, alignSeries, computeRelative };
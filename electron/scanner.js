@@ -0,0 +1,124 @@
+
+const crypto = require('crypto');
+const { parseExpression } = require('./expressions');
+const { datasetId } = require('./datasets');
+
+// --- SCANNER ---
+// Screens are saved indicator expressions (expressions.js) with a universe:
+// every dataset of a timeframe, or an explicit list of symbols / dataset ids
+// (a watchlist). A run fans one screen job per dataset out to the compute
+// pool, so datasets are evaluated in parallel off the main thread, and
+// returns the datasets whose last bar matches with the values that triggered
+// it. Runs are on demand or through a 'scanner.run' scheduler job; the last
+// result summary is kept with the screen.
+
+const MAX_UNIVERSE = 5000;
+
+const initializeScannerTable = (db) => {
+    db.exec(`
+        CREATE TABLE IF NOT EXISTS scanner_screens (
+            id TEXT PRIMARY KEY,
+            name TEXT,
+            expression TEXT,
+            universe TEXT,
+            last_run TEXT,
+            created_at INTEGER,
+            updated_at INTEGER
+        );
+    `);
+};
+
+const mapScreenRow = (row) => (row ? {
+    id: row.id,
+    name: row.name,
+    expression: row.expression,
+    universe: row.universe ? JSON.parse(row.universe) : {},
+    lastRun: row.last_run ? JSON.parse(row.last_run) : null,
+    createdAt: row.created_at,
+    updatedAt: row.updated_at
+} : null);
+
+// universe: { timeframe?, symbols?: string[], datasets?: string[] }
+const normalizeUniverse = (universe = {}) => {
+    const out = {};
+    if (universe.timeframe) out.timeframe = String(universe.timeframe);
+    if (Array.isArray(universe.symbols) && universe.symbols.length) {
+        if (!out.timeframe) throw new Error('A symbol list needs a timeframe');
+        out.symbols = Array.from(new Set(universe.symbols.map(s => String(s).trim()).filter(Boolean)));
+    }
+    if (Array.isArray(universe.datasets) && universe.datasets.length) out.datasets = Array.from(new Set(universe.datasets.map(String)));
+    if (!out.timeframe && !out.datasets) throw new Error('A screen needs a timeframe or a list of datasets');
+    return out;
+};
+
+/**
+ * runJobs(jobs) -> results runs screen jobs on the compute pool.
+ * onResult(result) is called after every run (on demand and scheduled).
+ */
+const createScanner = ({ db, runJobs, onResult = () => {} }) => {
+    const getScreen = (id) => mapScreenRow(db.prepare('SELECT * FROM scanner_screens WHERE id = ?').get(id));
+
+    const listScreens = () => db.prepare('SELECT * FROM scanner_screens ORDER BY name COLLATE NOCASE').all().map(mapScreenRow);
+
+    // screen: { id?, name, expression, universe }
+    const saveScreen = (screen = {}) => {
+        const existing = screen.id ? getScreen(screen.id) : null;
+        const merged = { ...(existing || {}), ...screen };
+        const name = String(merged.name || '').trim();
+        if (!name) throw new Error('Screen name is required');
+        const { source } = parseExpression(merged.expression);
+        const universe = normalizeUniverse(merged.universe);
+        const now = Date.now();
+        const id = existing ? existing.id : crypto.randomUUID();
+        db.prepare(`INSERT OR REPLACE INTO scanner_screens (id, name, expression, universe, last_run, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)`).run(id, name, source, JSON.stringify(universe),
+            existing && existing.lastRun && existing.expression === source ? JSON.stringify(existing.lastRun) : null,
+            existing ? existing.createdAt : now, now);
+        return getScreen(id);
+    };
+
+    const deleteScreen = (id) => db.prepare('DELETE FROM scanner_screens WHERE id = ?').run(id).changes > 0;
+
+    const resolveUniverse = (universe) => {
+        const ids = new Set(universe.datasets || []);
+        if (universe.symbols) universe.symbols.forEach(symbol => ids.add(datasetId(symbol, universe.timeframe)));
+        else if (universe.timeframe && !universe.datasets) {
+            db.prepare('SELECT id FROM datasets WHERE timeframe = ?').all(universe.timeframe).forEach(row => ids.add(row.id));
+        }
+        if (ids.size > MAX_UNIVERSE) throw new Error(`At most ${MAX_UNIVERSE} datasets per scan`);
+        return Array.from(ids);
+    };
+
+    /**
+     * Runs a saved screen (id) or an unsaved one ({ expression, universe }).
+     * Returns { screenId, expression, ranAt, durationMs, scanned, matches: [{ datasetId, symbol, timestamp, values }], errors }.
+     */
+    const run = async (target) => {
+        const screen = typeof target === 'string' ? getScreen(target) : { id: null, ...target };
+        if (!screen) throw new Error(`Screen not found: ${target}`);
+        const expr = parseExpression(screen.expression);
+        const ids = resolveUniverse(normalizeUniverse(screen.universe));
+        const start = Date.now();
+        const results = ids.length ? await runJobs(ids.map(id => ({ id, datasetId: id, expression: expr.source, limit: expr.lookback }))) : [];
+        const matches = [];
+        const errors = [];
+        results.forEach((result) => {
+            if (!result.success) errors.push({ datasetId: result.id, error: result.error });
+            else if (result.matched) {
+                matches.push({ datasetId: result.id, symbol: result.id.slice(0, result.id.lastIndexOf(':')), timestamp: result.timestamp, values: result.values });
+            }
+        });
+        matches.sort((a, b) => a.symbol.localeCompare(b.symbol));
+        const outcome = { screenId: screen.id, name: screen.name || null, expression: expr.source, ranAt: start, durationMs: Date.now() - start, scanned: ids.length, cached: results.filter(r => r.cached).length, matches, errors };
+        if (screen.id) {
+            const summary = { ranAt: outcome.ranAt, scanned: outcome.scanned, matched: matches.length, symbols: matches.map(m => m.symbol), errors: errors.length };
+            db.prepare('UPDATE scanner_screens SET last_run = ? WHERE id = ?').run(JSON.stringify(summary), screen.id);
+        }
+        onResult(outcome);
+        return outcome;
+    };
+
+    return { getScreen, listScreens, saveScreen, deleteScreen, run };
+};
+
+module.exports = { initializeScannerTable, createScanner };
@@ -0,0 +1,269 @@
+
+const crypto = require('crypto');
+
+// --- SCHEDULER ---
+// Persisted cron-style jobs (provider refresh, backups, reports, palette
+// actions). Schedules are five-field cron expressions in local time
+// ("15 17 * * 1-5" = 17:15 on weekdays). Job types are handlers supplied by
+// main.js. A job whose run time passed while the app was closed is run once on
+// startup (catchUp 'once', the default) or just rescheduled (catchUp 'skip');
+// either way the outcome lands in `job_runs`, the execution log.
+
+const MAX_RUNS_PER_JOB = 100;
+const MAX_TIMER_MS = 60 * 1000; // re-check at least every minute (sleep, clock changes)
+
+const initializeSchedulerTables = (db) => {
+    db.exec(`
+        CREATE TABLE IF NOT EXISTS scheduled_jobs (
+            id TEXT PRIMARY KEY,
+            name TEXT,
+            type TEXT,
+            schedule TEXT,
+            params TEXT,
+            enabled INTEGER DEFAULT 1,
+            catch_up TEXT DEFAULT 'once',
+            last_run_at INTEGER,
+            next_run_at INTEGER,
+            created_at INTEGER
+        );
+        CREATE TABLE IF NOT EXISTS job_runs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            job_id TEXT,
+            trigger TEXT,
+            started_at INTEGER,
+            finished_at INTEGER,
+            status TEXT,
+            message TEXT
+        );
+        CREATE INDEX IF NOT EXISTS idx_job_runs ON job_runs (job_id, id);
+    `);
+};
+
+// --- Cron parsing ---
+
+const FIELDS = [
+    { name: 'minute', min: 0, max: 59 },
+    { name: 'hour', min: 0, max: 23 },
+    { name: 'day', min: 1, max: 31 },
+    { name: 'month', min: 1, max: 12 },
+    { name: 'weekday', min: 0, max: 7 } // 0 and 7 are Sunday
+];
+
+const ALIASES = {
+    '@hourly': '0 * * * *',
+    '@daily': '0 0 * * *',
+    '@weekly': '0 0 * * 0',
+    '@monthly': '0 0 1 * *'
+};
+
+const parseField = (text, { name, min, max }) => {
+    const values = new Set();
+    text.split(',').forEach((part) => {
+        const m = /^(\*|\d+)(?:-(\d+))?(?:\/(\d+))?$/.exec(part);
+        if (!m) throw new Error(`Invalid ${name} field: ${text}`);
+        const start = m[1] === '*' ? min : Number(m[1]);
+        const end = m[2] != null ? Number(m[2]) : (m[1] === '*' || m[3] ? max : start);
+        const step = m[3] != null ? Number(m[3]) : 1;
+        if (start < min || end > max || start > end || step < 1) throw new Error(`Out of range ${name} field: ${text}`);
+        for (let v = start; v <= end; v += step) values.add(name === 'weekday' && v === 7 ? 0 : v);
+    });
+    return { values, any: text === '*' };
+};
+
+const parseCron = (expression) => {
+    const source = ALIASES[String(expression).trim()] || String(expression).trim();
+    const parts = source.split(/\s+/);
+    if (parts.length !== 5) throw new Error(`Schedule needs 5 fields (minute hour day month weekday): ${expression}`);
+    const [minute, hour, day, month, weekday] = parts.map((p, i) => parseField(p, FIELDS[i]));
+    return { minute, hour, day, month, weekday };
+};
+
+// Standard cron: when both day-of-month and weekday are restricted, either may match
+const dayMatches = (cron, date) => {
+    const dom = cron.day.values.has(date.getDate());
+    const dow = cron.weekday.values.has(date.getDay());
+    if (cron.day.any && cron.weekday.any) return true;
+    if (cron.day.any) return dow;
+    if (cron.weekday.any) return dom;
+    return dom || dow;
+};
+
+/**
+ * First matching minute strictly after `after` (ms), in local time.
+ */
+const nextRunTime = (cron, after) => {
+    const d = new Date(after);
+    d.setSeconds(0, 0);
+    d.setMinutes(d.getMinutes() + 1);
+    const limit = after + 5 * 366 * 86400000;
+    while (d.getTime() < limit) {
+        if (!cron.month.values.has(d.getMonth() + 1)) { d.setMonth(d.getMonth() + 1, 1); d.setHours(0, 0, 0, 0); continue; }
+        if (!dayMatches(cron, d)) { d.setDate(d.getDate() + 1); d.setHours(0, 0, 0, 0); continue; }
+        if (!cron.hour.values.has(d.getHours())) { d.setHours(d.getHours() + 1, 0, 0, 0); continue; }
+        if (!cron.minute.values.has(d.getMinutes())) { d.setMinutes(d.getMinutes() + 1, 0, 0); continue; }
+        return d.getTime();
+    }
+    throw new Error('Schedule never fires');
+};
+
+// --- Scheduler ---
+
+const mapJobRow = (row) => ({
+    id: row.id,
+    name: row.name,
+    type: row.type,
+    schedule: row.schedule,
+    params: row.params ? JSON.parse(row.params) : {},
+    enabled: !!row.enabled,
+    catchUp: row.catch_up,
+    lastRunAt: row.last_run_at,
+    nextRunAt: row.next_run_at,
+    createdAt: row.created_at
+});
+
+/**
+ * handlers: { [type]: async (params, job) => summary string | object }
+ * onRunFinished(run) after every execution (including failures and skipped catch-ups).
+ * shouldDefer(job) -> reason string to postpone a due run until the next check.
+ */
+const createScheduler = ({ db, handlers, onRunFinished = () => {}, shouldDefer = () => null }) => {
+    let timer = null;
+    const running = new Set();
+
+    const getJob = (id) => {
+        const row = db.prepare('SELECT * FROM scheduled_jobs WHERE id = ?').get(id);
+        return row ? mapJobRow(row) : null;
+    };
+
+    const logRun = (jobId, trigger, startedAt, status, message) => {
+        const finishedAt = Date.now();
+        const { lastInsertRowid } = db.prepare('INSERT INTO job_runs (job_id, trigger, started_at, finished_at, status, message) VALUES (?, ?, ?, ?, ?, ?)')
+            .run(jobId, trigger, startedAt, finishedAt, status, message);
+        db.prepare(`DELETE FROM job_runs WHERE job_id = ? AND id NOT IN (
+            SELECT id FROM job_runs WHERE job_id = ? ORDER BY id DESC LIMIT ?)`).run(jobId, jobId, MAX_RUNS_PER_JOB);
+        const run = { id: Number(lastInsertRowid), jobId, trigger, startedAt, finishedAt, status, message };
+        onRunFinished(run);
+        return run;
+    };
+
+    const execute = async (job, trigger) => {
+        if (running.has(job.id)) return logRun(job.id, trigger, Date.now(), 'skipped', 'Previous run still in progress');
+        const handler = handlers[job.type];
+        const startedAt = Date.now();
+        running.add(job.id);
+        try {
+            if (!handler) throw new Error(`Unknown job type: ${job.type}`);
+            const outcome = await handler(job.params, job);
+            const message = outcome == null ? null : (typeof outcome === 'string' ? outcome : JSON.stringify(outcome));
+            return logRun(job.id, trigger, startedAt, 'success', message);
+        } catch (err) {
+            return logRun(job.id, trigger, startedAt, 'failed', err.message);
+        } finally {
+            running.delete(job.id);
+            db.prepare('UPDATE scheduled_jobs SET last_run_at = ? WHERE id = ?').run(startedAt, job.id);
+        }
+    };
+
+    const reschedule = (job, from = Date.now()) => {
+        const next = nextRunTime(parseCron(job.schedule), from);
+        db.prepare('UPDATE scheduled_jobs SET next_run_at = ? WHERE id = ?').run(next, job.id);
+        return next;
+    };
+
+    const tick = () => {
+        const now = Date.now();
+        let deferred = false;
+        db.prepare('SELECT * FROM scheduled_jobs WHERE enabled = 1 AND next_run_at <= ?').all(now).map(mapJobRow).forEach((job) => {
+            // A deferred job stays due and is retried on the next check
+            if (shouldDefer(job)) { deferred = true; return; }
+            reschedule(job, now);
+            execute(job, 'schedule');
+        });
+        arm(deferred);
+    };
+
+    const arm = (backOff = false) => {
+        clearTimeout(timer);
+        const row = db.prepare('SELECT MIN(next_run_at) as next FROM scheduled_jobs WHERE enabled = 1').get();
+        const due = row && row.next != null ? Math.max(0, Math.min(MAX_TIMER_MS, row.next - Date.now())) : MAX_TIMER_MS;
+        timer = setTimeout(tick, backOff ? MAX_TIMER_MS : due);
+    };
+
+    // Startup: settle jobs that came due while the app was closed, then start the timer
+    const start = () => {
+        const now = Date.now();
+        db.prepare('SELECT * FROM scheduled_jobs WHERE enabled = 1').all().map(mapJobRow).forEach((job) => {
+            try {
+                if (job.nextRunAt == null || job.nextRunAt > now) {
+                    if (job.nextRunAt == null) reschedule(job, now);
+                    return;
+                }
+                const missedAt = job.nextRunAt;
+                reschedule(job, now);
+                if (job.catchUp === 'skip') logRun(job.id, 'missed', now, 'missed', `Skipped run due ${new Date(missedAt).toISOString()}`);
+                else execute(job, 'catch-up');
+            } catch (err) {
+                logRun(job.id, 'missed', now, 'failed', err.message);
+            }
+        });
+        arm();
+    };
+
+    const stop = () => { clearTimeout(timer); timer = null; };
+
+    /**
+     * def: { name, type, schedule, params?, enabled?, catchUp?: 'once' | 'skip' }
+     */
+    const createJob = (def) => {
+        const name = String((def && def.name) || '').trim();
+        if (!name) throw new Error('Job name is required');
+        if (!handlers[def.type]) throw new Error(`Unknown job type: ${def.type}`);
+        const catchUp = def.catchUp || 'once';
+        if (catchUp !== 'once' && catchUp !== 'skip') throw new Error(`Invalid catch-up policy: ${catchUp}`);
+        const now = Date.now();
+        const next = nextRunTime(parseCron(def.schedule), now);
+        const id = crypto.randomUUID();
+        db.prepare('INSERT INTO scheduled_jobs (id, name, type, schedule, params, enabled, catch_up, next_run_at, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)')
+            .run(id, name, def.type, String(def.schedule).trim(), JSON.stringify(def.params || {}), def.enabled === false ? 0 : 1, catchUp, next, now);
+        if (timer) arm();
+        return getJob(id);
+    };
+
+    const updateJob = (id, updates = {}) => {
+        const job = getJob(id);
+        if (!job) throw new Error(`Job not found: ${id}`);
+        const next = { ...job, ...updates, id };
+        if (!handlers[next.type]) throw new Error(`Unknown job type: ${next.type}`);
+        if (next.catchUp !== 'once' && next.catchUp !== 'skip') throw new Error(`Invalid catch-up policy: ${next.catchUp}`);
+        const nextRunAt = nextRunTime(parseCron(next.schedule), Date.now());
+        db.prepare('UPDATE scheduled_jobs SET name = ?, type = ?, schedule = ?, params = ?, enabled = ?, catch_up = ?, next_run_at = ? WHERE id = ?')
+            .run(next.name, next.type, next.schedule, JSON.stringify(next.params || {}), next.enabled ? 1 : 0, next.catchUp, nextRunAt, id);
+        if (timer) arm();
+        return getJob(id);
+    };
+
+    const deleteJob = (id) => {
+        const { changes } = db.prepare('DELETE FROM scheduled_jobs WHERE id = ?').run(id);
+        db.prepare('DELETE FROM job_runs WHERE job_id = ?').run(id);
+        if (timer) arm();
+        return changes > 0;
+    };
+
+    const runNow = (id) => {
+        const job = getJob(id);
+        if (!job) throw new Error(`Job not found: ${id}`);
+        return execute(job, 'manual');
+    };
+
+    const listJobs = () => db.prepare('SELECT * FROM scheduled_jobs ORDER BY name').all().map(mapJobRow)
+        .map(job => ({ ...job, running: running.has(job.id) }));
+
+    const listRuns = (jobId = null, limit = 50) => (jobId
+        ? db.prepare('SELECT * FROM job_runs WHERE job_id = ? ORDER BY id DESC LIMIT ?').all(jobId, limit)
+        : db.prepare('SELECT * FROM job_runs ORDER BY id DESC LIMIT ?').all(limit))
+        .map(r => ({ id: r.id, jobId: r.job_id, trigger: r.trigger, startedAt: r.started_at, finishedAt: r.finished_at, status: r.status, message: r.message }));
+
+    return { start, stop, createJob, updateJob, deleteJob, runNow, listJobs, listRuns, jobTypes: () => Object.keys(handlers) };
+};
+
+module.exports = { initializeSchedulerTables, parseCron, nextRunTime, createScheduler };
@@ -0,0 +1,130 @@
+
+// --- GLOBAL SEARCH ---
+// One query fanned out over everything the backend knows by name: chart
+// annotations (text labels, folder names), symbols (dataset registry and the
+// Assets library), the trade journal, alerts, stored news, sticky notes
+// (archived ones included) and symbol dossiers. Results are
+// typed and carry ids plus a snippet so a command palette can jump to them.
+
+const SNIPPET_RADIUS = 40;
+const DEFAULT_LIMIT = 50;
+
+// 3 = exact, 2 = prefix, 1 = substring
+const scoreText = (text, q) => {
+    if (!text) return 0;
+    const lower = String(text).toLowerCase();
+    if (lower === q) return 3;
+    if (lower.startsWith(q) || lower.includes(` ${q}`)) return 2;
+    return lower.includes(q) ? 1 : 0;
+};
+
+const snippet = (text, q) => {
+    const value = String(text);
+    const idx = value.toLowerCase().indexOf(q);
+    if (idx === -1 || value.length <= SNIPPET_RADIUS * 2) return value;
+    const start = Math.max(0, idx - SNIPPET_RADIUS);
+    const end = Math.min(value.length, idx + q.length + SNIPPET_RADIUS);
+    return `${start > 0 ? '…' : ''}${value.slice(start, end)}${end < value.length ? '…' : ''}`;
+};
+
+const searchChartStates = (db, q, push) => {
+    db.prepare('SELECT symbol, data FROM drawings').all().forEach((row) => {
+        let state;
+        try { state = JSON.parse(row.data); } catch (e) { return; }
+        (state.drawings || []).forEach((d) => {
+            const text = d.properties?.text;
+            const score = scoreText(text, q);
+            if (score) push({ type: 'drawing', id: d.id, symbol: row.symbol, title: text.split('\n')[0].slice(0, 80), snippet: snippet(text, q), meta: { drawingType: d.type }, score });
+        });
+        (state.folders || []).forEach((f) => {
+            const score = scoreText(f.name, q);
+            if (score) push({ type: 'folder', id: f.id, symbol: row.symbol, title: f.name, snippet: f.name, score });
+        });
+    });
+};
+
+const searchSymbols = (db, q, libraryFiles, push) => {
+    const seen = new Set();
+    db.prepare('SELECT id, symbol, timeframe, source, row_count FROM datasets').all().forEach((d) => {
+        const score = scoreText(d.symbol, q);
+        if (!score) return;
+        seen.add(d.symbol);
+        push({ type: 'dataset', id: d.id, symbol: d.symbol, title: `${d.symbol} · ${d.timeframe}`, snippet: `${d.row_count} bars from ${d.source}`, score: score + 0.5 });
+    });
+    libraryFiles.forEach((f) => {
+        const score = Math.max(scoreText(f.name, q), scoreText(f.folder, q));
+        if (score) push({ type: 'file', id: f.path, symbol: f.folder !== '.' ? f.folder : null, title: f.name, snippet: f.folder, score });
+    });
+};
+
+const searchTrades = (db, q, push) => {
+    db.prepare('SELECT id, sourceId, data FROM trades').all().forEach((row) => {
+        let trade;
+        try { trade = JSON.parse(row.data); } catch (e) { return; }
+        const haystack = [trade.symbol, trade.side, trade.type, trade.status, trade.notes].filter(Boolean).join(' ');
+        const score = scoreText(haystack, q);
+        if (score) push({ type: 'trade', id: row.id, symbol: trade.symbol, title: `${trade.side} ${trade.qty} ${trade.symbol} @ ${trade.price}`, snippet: snippet(haystack, q), meta: { sourceId: row.sourceId, timestamp: trade.timestamp }, score });
+    });
+};
+
+const searchAlertsAndNews = (db, q, push) => {
+    const like = `%${q}%`;
+    db.prepare('SELECT id, symbol, condition, price, message FROM alerts WHERE lower(symbol) LIKE ? OR lower(message) LIKE ?').all(like, like).forEach((a) => {
+        push({ type: 'alert', id: a.id, symbol: a.symbol, title: `${a.symbol} ${a.condition.replace('_', ' ')} ${a.price}`, snippet: a.message || '', score: Math.max(scoreText(a.symbol, q), scoreText(a.message, q)) });
+    });
+    db.prepare('SELECT id, title, summary, symbols, published FROM news_items WHERE lower(title) LIKE ? OR lower(summary) LIKE ? ORDER BY published DESC LIMIT 100').all(like, like).forEach((n) => {
+        push({ type: 'news', id: n.id, symbol: n.symbols.split(',').filter(Boolean)[0] || null, title: n.title, snippet: snippet(n.summary || n.title, q), meta: { published: n.published }, score: scoreText(n.title, q) || 0.5 });
+    });
+};
+
+const searchNotes = (db, q, push) => {
+    const like = `%${q}%`;
+    db.prepare('SELECT id, text, recognized_text, tags, board_id, archived_at FROM sticky_notes WHERE lower(text) LIKE ? OR lower(recognized_text) LIKE ? OR lower(tags) LIKE ? ORDER BY updated_at DESC LIMIT 100').all(like, like, like).forEach((n) => {
+        const text = n.text || n.recognized_text || '';
+        const title = text.split('\n')[0].slice(0, 80) || '(untitled note)';
+        // Archived notes rank just below active ones
+        const score = Math.max(scoreText(n.text, q), scoreText(n.recognized_text, q), scoreText(n.tags, q), 1) - (n.archived_at ? 0.5 : 0);
+        // The snippet comes from whichever field holds the hit
+        const matched = [n.text, n.recognized_text, n.tags].find(field => field && field.toLowerCase().includes(q)) || text || n.tags;
+        push({ type: 'note', id: n.id, symbol: null, title, snippet: snippet(matched, q), meta: { boardId: n.board_id, archived: !!n.archived_at, tags: JSON.parse(n.tags || '[]') }, score });
+    });
+};
+
+const searchDossiers = (db, q, push) => {
+    db.prepare('SELECT symbol, data FROM symbol_dossiers').all().forEach((row) => {
+        let dossier;
+        try { dossier = JSON.parse(row.data); } catch (e) { return; }
+        const parts = [dossier.thesis, ...(dossier.tags || []), ...(dossier.keyLevels || []).map(l => l.label), ...(dossier.links || []).map(l => l.title), ...(dossier.files || []).map(f => f.name)].filter(Boolean);
+        const matched = parts.find(text => scoreText(text, q));
+        // The symbol itself ranks like a dataset hit; content matches like notes
+        const score = Math.max(scoreText(row.symbol, q) + 0.25, ...parts.map(text => scoreText(text, q)));
+        if (score <= 0.25) return;
+        push({ type: 'dossier', id: row.symbol, symbol: row.symbol, title: `${row.symbol} dossier`, snippet: matched ? snippet(matched, q) : (dossier.thesis || '').split('\n')[0].slice(0, 80), meta: { tags: dossier.tags || [] }, score });
+    });
+};
+
+/**
+ * Returns results sorted by score (then type order of the fan-out), capped at `limit`.
+ */
+const globalSearch = (db, query, { libraryFiles = [], limit = DEFAULT_LIMIT, types = null } = {}) => {
+    const q = String(query || '').trim().toLowerCase();
+    if (q.length === 0) return [];
+
+    const results = [];
+    const push = (r) => { if (!types || types.includes(r.type)) results.push(r); };
+
+    searchSymbols(db, q, libraryFiles, push);
+    searchChartStates(db, q, push);
+    searchTrades(db, q, push);
+    searchAlertsAndNews(db, q, push);
+    searchNotes(db, q, push);
+    searchDossiers(db, q, push);
+
+    return results
+        .map((r, order) => ({ r, order }))
+        .sort((a, b) => b.r.score - a.r.score || a.order - b.order)
+        .slice(0, limit)
+        .map(({ r }) => r);
+};
+
+module.exports = { globalSearch };
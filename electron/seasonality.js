@@ -0,0 +1,172 @@
+
+const { isMainThread, parentPort, workerData } = require('worker_threads');
+const { TIMEFRAME_MS } = require('./datasets');
+const { partsIn } = require('./sessions');
+
+// --- SEASONALITY ---
+// Returns of a dataset grouped by where they fall in a recurring cycle:
+//
+//   month    Jan..Dec        within each year
+//   week     ISO week 1..53  within each ISO year
+//   weekday  Mon..Sun        within each ISO week
+//   hour     00..23          within each day
+//
+// Each occurrence (e.g. March 2019) returns last close over the last close
+// of the occurrence before it, in the chosen timezone. Per bucket: mean,
+// median, dispersion and how often it was positive. The path compounds the
+// buckets through each cycle and summarizes the cycles bucket by bucket
+// (average and median path plus percentile bands); the latest cycle, if
+// unfinished, is returned separately so it can be drawn against the rest.
+// Runs in a worker: hourly bars over many years are a lot of rows.
+
+const GROUPINGS = {
+    month: { buckets: 12, finest: '1mo', label: (b) => ['Jan', 'Feb', 'Mar', 'Apr', 'May', 'Jun', 'Jul', 'Aug', 'Sep', 'Oct', 'Nov', 'Dec'][b - 1] },
+    week: { buckets: 53, finest: '1W', label: (b) => `W${b}` },
+    weekday: { buckets: 7, finest: '1D', label: (b) => ['Mon', 'Tue', 'Wed', 'Thu', 'Fri', 'Sat', 'Sun'][b - 1] },
+    hour: { buckets: 24, finest: '1h', label: (b) => String(b).padStart(2, '0') }
+};
+const DEFAULT_PERCENTILES = [10, 25, 75, 90];
+
+// ISO 8601 week and week-year of a calendar date
+const isoWeek = (year, month, day) => {
+    const date = new Date(Date.UTC(year, month - 1, day));
+    const weekday = date.getUTCDay() || 7;
+    date.setUTCDate(date.getUTCDate() + 4 - weekday);
+    const weekYear = date.getUTCFullYear();
+    return { weekYear, week: Math.ceil(((date - Date.UTC(weekYear, 0, 1)) / 86400000 + 1) / 7), weekday };
+};
+
+// { cycle, bucket } of a timestamp; cycle is a sortable key
+const locate = (grouping, ts, timezone) => {
+    const p = partsIn(ts, timezone);
+    switch (grouping) {
+        case 'month': return { cycle: p.year, bucket: p.month };
+        case 'week': { const w = isoWeek(p.year, p.month, p.day); return { cycle: w.weekYear, bucket: w.week }; }
+        case 'weekday': { const w = isoWeek(p.year, p.month, p.day); return { cycle: w.weekYear * 100 + w.week, bucket: w.weekday }; }
+        default: return { cycle: p.year * 10000 + p.month * 100 + p.day, bucket: p.hour };
+    }
+};
+
+// Linear interpolation between closest ranks of a sorted array
+const percentileOf = (sorted, p) => {
+    if (!sorted.length) return null;
+    const rank = (p / 100) * (sorted.length - 1);
+    const lo = Math.floor(rank);
+    const hi = Math.ceil(rank);
+    return sorted[lo] + (sorted[hi] - sorted[lo]) * (rank - lo);
+};
+
+const summarize = (values, percentiles) => {
+    const sorted = Float64Array.from(values).sort();
+    const n = values.length;
+    const mean = n ? values.reduce((sum, v) => sum + v, 0) / n : null;
+    const out = {
+        count: n,
+        mean,
+        median: percentileOf(sorted, 50),
+        stdDev: n > 1 ? Math.sqrt(values.reduce((sum, v) => sum + (v - mean) ** 2, 0) / (n - 1)) : null
+    };
+    percentiles.forEach((p) => { out[`p${p}`] = percentileOf(sorted, p); });
+    return out;
+};
+
+/**
+ * bars: oldest first. options: { timezone? (default 'UTC'), percentiles? }.
+ * Returns { grouping, timezone, cycles, buckets, path, current } with
+ * returns in percent.
+ */
+const computeSeasonality = (bars, grouping, { timezone = 'UTC', percentiles = DEFAULT_PERCENTILES } = {}) => {
+    const spec = GROUPINGS[grouping];
+    if (!spec) throw new Error(`Unknown seasonality grouping: ${grouping}`);
+    const bands = percentiles.map(Number).filter(p => p > 0 && p < 100 && p !== 50);
+
+    // Occurrences in time order: { cycle, bucket, close } with the last close of each
+    const occurrences = [];
+    bars.forEach((bar) => {
+        if (bar.close == null) return;
+        const { cycle, bucket } = locate(grouping, bar.timestamp, timezone);
+        const last = occurrences[occurrences.length - 1];
+        if (last && last.cycle === cycle && last.bucket === bucket) last.close = bar.close;
+        else occurrences.push({ cycle, bucket, close: bar.close });
+    });
+
+    const bucketReturns = Array.from({ length: spec.buckets + 1 }, () => []);
+    const cycles = new Map(); // cycle -> Map(bucket -> return)
+    for (let i = 1; i < occurrences.length; i++) {
+        const prev = occurrences[i - 1].close;
+        if (!prev) continue;
+        const { cycle, bucket, close } = occurrences[i];
+        const r = (close / prev - 1) * 100;
+        bucketReturns[bucket].push(r);
+        if (!cycles.has(cycle)) cycles.set(cycle, new Map());
+        cycles.get(cycle).set(bucket, r);
+    }
+
+    const present = [];
+    for (let b = 0; b <= spec.buckets; b++) if (bucketReturns[b].length) present.push(b);
+    const lastBucket = present.length ? present[present.length - 1] : null;
+
+    // Compounded path per cycle; a missing bucket carries the level forward
+    const pathOf = (returns) => {
+        let level = 1;
+        return present.map((b) => {
+            if (returns.has(b)) level *= 1 + returns.get(b) / 100;
+            return (level - 1) * 100;
+        });
+    };
+    const keys = Array.from(cycles.keys()).sort((a, b) => a - b);
+    const latest = keys.length ? cycles.get(keys[keys.length - 1]) : null;
+    const unfinished = latest && !latest.has(lastBucket);
+    const complete = unfinished ? keys.slice(0, -1) : keys;
+    const paths = complete.map(key => pathOf(cycles.get(key)));
+
+    let current = null;
+    if (unfinished) {
+        const full = pathOf(latest);
+        const reached = Math.max(...Array.from(latest.keys()));
+        current = present.filter(b => b <= reached).map(b => ({ bucket: b, label: spec.label(b), value: full[present.indexOf(b)] }));
+    }
+
+    return {
+        grouping,
+        timezone,
+        cycles: complete.length,
+        buckets: present.map(b => ({
+            bucket: b,
+            label: spec.label(b),
+            ...summarize(bucketReturns[b], bands),
+            positiveRatio: bucketReturns[b].filter(r => r > 0).length / bucketReturns[b].length
+        })),
+        path: present.map((b, i) => {
+            const { count, stdDev, ...stats } = summarize(paths.map(p => p[i]), bands);
+            return { bucket: b, label: spec.label(b), ...stats };
+        }),
+        current
+    };
+};
+
+// Bars finer than a bucket are needed to tell buckets apart
+const checkTimeframe = (grouping, timeframe) => {
+    const spec = GROUPINGS[grouping];
+    if (!spec) throw new Error(`Unknown seasonality grouping: ${grouping}`);
+    if (!TIMEFRAME_MS[timeframe]) throw new Error(`Unsupported timeframe: ${timeframe}`);
+    if (TIMEFRAME_MS[timeframe] > TIMEFRAME_MS[spec.finest]) throw new Error(`Grouping by ${grouping} needs ${spec.finest} bars or finer`);
+};
+
+if (!isMainThread && workerData && workerData.seasonality) {
+    const { dbPath, symbol, timeframe, grouping, options } = workerData.seasonality;
+    const Database = require('better-sqlite3');
+    const db = new Database(dbPath, { readonly: true, fileMustExist: true });
+    try {
+        const bars = db.prepare('SELECT timestamp, close FROM market_data WHERE symbol = ? AND timeframe = ? AND timestamp BETWEEN ? AND ? ORDER BY timestamp ASC')
+            .all(symbol, timeframe, options.from != null ? Number(options.from) : Number.MIN_SAFE_INTEGER, options.to != null ? Number(options.to) : Number.MAX_SAFE_INTEGER);
+        db.close();
+        const result = computeSeasonality(bars, grouping, options);
+        parentPort.postMessage({ success: true, result: { ...result, bars: bars.length, from: bars.length ? bars[0].timestamp : null, to: bars.length ? bars[bars.length - 1].timestamp : null } });
+    } catch (err) {
+        if (db.open) db.close();
+        parentPort.postMessage({ success: false, error: err.message });
+    }
+}
+
+module.exports = { SEASONALITY_GROUPINGS: Object.keys(GROUPINGS), computeSeasonality, checkTimeframe };
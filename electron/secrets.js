@@ -0,0 +1,164 @@
+
+const { safeStorage } = require('electron');
+
+// --- SECRETS STORE ---
+// API keys and tokens are encrypted with the OS keychain (DPAPI / Keychain /
+// libsecret via Electron safeStorage) and kept in the `secrets` table.
+// Plaintext values never cross the IPC bridge: the renderer can set, delete
+// and list key names, but only the main process can read a secret back.
+//
+// `secret_meta` tracks the owner of each key ('polygon.apiKey' -> polygon,
+// unless set explicitly), its expiry when known (entered by the user or read
+// from a JWT's exp claim) and the last credential check. Providers get a
+// scoped reader that only opens their own keys. Every value read or written
+// is remembered in memory so scrubSecrets() can mask it in logs and bundles.
+
+const MIN_SCRUB_LENGTH = 6;
+// ?apiKey=... / &token=... in URLs that end up in error messages
+const SENSITIVE_QUERY = /([?&](?:api[-_]?key|apikey|token|secret|access[-_]?token|password)=)[^&\s"']+/gi;
+
+const knownValues = new Set();
+
+const initializeSecretsTable = (db) => {
+    db.exec(`
+        CREATE TABLE IF NOT EXISTS secrets (key TEXT PRIMARY KEY, value BLOB, updated_at INTEGER);
+        CREATE TABLE IF NOT EXISTS secret_meta (
+            key TEXT PRIMARY KEY,
+            provider TEXT,
+            expires_at INTEGER,
+            validated_at INTEGER,
+            validation_status TEXT,
+            validation_message TEXT
+        );
+    `);
+};
+
+const remember = (value) => {
+    if (value && value.length >= MIN_SCRUB_LENGTH) knownValues.add(value);
+};
+
+const providerOfKey = (key) => String(key).split('.')[0];
+
+// JWT-shaped tokens carry their own expiry
+const detectExpiry = (value) => {
+    const parts = String(value).split('.');
+    if (parts.length !== 3) return null;
+    try {
+        const payload = JSON.parse(Buffer.from(parts[1], 'base64url').toString('utf8'));
+        return Number.isFinite(payload.exp) ? payload.exp * 1000 : null;
+    } catch (e) {
+        return null;
+    }
+};
+
+const assertEncryption = () => {
+    if (!safeStorage.isEncryptionAvailable()) {
+        throw new Error('OS secure storage is unavailable; refusing to store secrets in plaintext');
+    }
+};
+
+/**
+ * options: { provider?, expiresAt? (ms; null = never) } — a new value resets the last check.
+ */
+const setSecret = (db, key, value, { provider = null, expiresAt } = {}) => {
+    assertEncryption();
+    const text = String(value);
+    const encrypted = safeStorage.encryptString(text);
+    const expiry = expiresAt !== undefined ? expiresAt : detectExpiry(text);
+    // The value and its owner / expiry land together or not at all
+    db.transaction(() => {
+        db.prepare('INSERT OR REPLACE INTO secrets (key, value, updated_at) VALUES (?, ?, ?)').run(key, encrypted, Date.now());
+        db.prepare('INSERT OR REPLACE INTO secret_meta (key, provider, expires_at, validated_at, validation_status, validation_message) VALUES (?, ?, ?, NULL, NULL, NULL)')
+            .run(key, provider || providerOfKey(key), expiry);
+    })();
+    remember(text);
+};
+
+const getSecret = (db, key) => {
+    const row = db.prepare('SELECT value FROM secrets WHERE key = ?').get(key);
+    if (!row) return null;
+    assertEncryption();
+    const value = safeStorage.decryptString(row.value);
+    remember(value);
+    return value;
+};
+
+const deleteSecret = (db, key) => db.transaction(() => {
+    db.prepare('DELETE FROM secret_meta WHERE key = ?').run(key);
+    return db.prepare('DELETE FROM secrets WHERE key = ?').run(key).changes > 0;
+})();
+
+const listSecretKeys = (db) => db.prepare(`
+    SELECT s.key, s.updated_at as updatedAt, COALESCE(m.provider, substr(s.key, 1, instr(s.key || '.', '.') - 1)) as provider,
+           m.expires_at as expiresAt, m.validated_at as validatedAt, m.validation_status as validationStatus, m.validation_message as validationMessage
+    FROM secrets s LEFT JOIN secret_meta m ON m.key = s.key ORDER BY s.key
+`).all();
+
+// updates: { provider?, expiresAt? }
+const setSecretMeta = (db, key, updates = {}) => {
+    const current = listSecretKeys(db).find(s => s.key === key);
+    if (!current) throw new Error(`Secret not found: ${key}`);
+    db.prepare('INSERT OR REPLACE INTO secret_meta (key, provider, expires_at, validated_at, validation_status, validation_message) VALUES (?, ?, ?, ?, ?, ?)').run(
+        key,
+        updates.provider || current.provider,
+        updates.expiresAt !== undefined ? updates.expiresAt : current.expiresAt,
+        current.validatedAt, current.validationStatus, current.validationMessage
+    );
+    return listSecretKeys(db).find(s => s.key === key);
+};
+
+// Stores the outcome of a credential check on every key of the provider
+const recordValidation = (db, provider, status, message = null) => {
+    listSecretKeys(db).filter(s => s.provider === provider).forEach(({ key, expiresAt }) => {
+        db.prepare('INSERT OR REPLACE INTO secret_meta (key, provider, expires_at, validated_at, validation_status, validation_message) VALUES (?, ?, ?, ?, ?, ?)')
+            .run(key, provider, expiresAt, Date.now(), status, message);
+    });
+};
+
+const expiringSecrets = (db, withinMs, now = Date.now()) => listSecretKeys(db)
+    .filter(s => s.expiresAt != null && s.expiresAt - now <= withinMs)
+    .map(s => ({ ...s, expired: s.expiresAt <= now }));
+
+/**
+ * Reader limited to keys owned by `providers` (one id or a list); anything
+ * else throws. getDb is called per read, so the scope can exist before the DB.
+ */
+const createSecretScope = (getDb, providers) => {
+    const allowed = Array.isArray(providers) ? providers : [providers];
+    return (key) => {
+        if (!allowed.includes(providerOfKey(key))) throw new Error(`Secret "${key}" is outside the ${allowed.join('/')} scope`);
+        const db = getDb();
+        return db ? getSecret(db, key) : null;
+    };
+};
+
+// Deep-copies `value` with every known secret (and sensitive URL query values) masked
+const scrubSecrets = (value) => {
+    const scrub = (text) => {
+        let out = text.replace(SENSITIVE_QUERY, '$1[redacted]');
+        knownValues.forEach((secret) => { if (out.includes(secret)) out = out.split(secret).join('[secret]'); });
+        return out;
+    };
+    const walk = (v) => {
+        if (typeof v === 'string') return scrub(v);
+        if (Array.isArray(v)) return v.map(walk);
+        if (v instanceof Date) return v;
+        if (v instanceof Error) return { name: v.name, message: scrub(v.message) };
+        if (v && typeof v === 'object') return Object.fromEntries(Object.entries(v).map(([k, val]) => [k, walk(val)]));
+        return v;
+    };
+    return walk(value);
+};
+
+module.exports = {
+    initializeSecretsTable,
+    setSecret,
+    getSecret,
+    deleteSecret,
+    listSecretKeys,
+    setSecretMeta,
+    recordValidation,
+    expiringSecrets,
+    createSecretScope,
+    scrubSecrets
+};
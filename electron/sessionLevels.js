@@ -0,0 +1,127 @@
+
+const { TIMEFRAME_MS, parseDatasetId } = require('./datasets');
+const { computeSessionStats } = require('./sessions');
+
+// --- SESSION LEVELS ---
+// The lines every intraday trader draws by hand each morning: yesterday's
+// high, low and close and today's open. computeSessionLevels() reads them off
+// a dataset — the last two sessions of its calendar for intraday bars, the
+// last two bars for daily and slower ones — and applySessionLevels() keeps
+// one horizontal line per level in a chart state, in the "Session levels"
+// folder. The lines are recognized by properties.sessionLevel, so a user's
+// restyling survives; only their price and anchor follow the data, and a
+// level the data no longer has (or that was switched off) is removed.
+
+const LEVELS = {
+    pdh: { label: 'PDH', color: '#26a69a' },
+    pdl: { label: 'PDL', color: '#ef5350' },
+    pdc: { label: 'PDC', color: '#9e9e9e' },
+    open: { label: 'Open', color: '#2962ff' }
+};
+const LEVEL_KEYS = Object.keys(LEVELS);
+const FOLDER_ID = 'session-levels';
+const FOLDER_NAME = 'Session levels';
+const DAY_MS = 86400000;
+const LOOKBACK_DAYS = 10; // enough to span a long weekend plus a holiday
+
+const cleanLevelKeys = (keys) => {
+    if (keys == null) return LEVEL_KEYS;
+    if (!Array.isArray(keys)) throw new Error('levels must be an array');
+    const unknown = keys.filter(k => !LEVELS[k]);
+    if (unknown.length) throw new Error(`Unknown session level: ${unknown.join(', ')} (expected ${LEVEL_KEYS.join(', ')})`);
+    return LEVEL_KEYS.filter(k => keys.includes(k));
+};
+
+// The reference sessions: { today: { day, start, open }, previous: { day, start, high, low, close } | null }
+const referenceSessions = (bars, timeframe, calendar) => {
+    if (TIMEFRAME_MS[timeframe] >= DAY_MS) {
+        const day = bar => new Date(bar.timestamp).toISOString().slice(0, 10);
+        const last = bars[bars.length - 1];
+        const prev = bars.length > 1 ? bars[bars.length - 2] : null;
+        return {
+            today: { day: day(last), start: last.timestamp, open: last.open },
+            previous: prev ? { day: day(prev), start: prev.timestamp, high: prev.high, low: prev.low, close: prev.close } : null
+        };
+    }
+    const { days } = computeSessionStats(bars, { calendar });
+    if (!days.length) return { today: null, previous: null };
+    const last = days[days.length - 1];
+    // The last day may only have pre-market bars so far; yesterday is the last regular session before it
+    const prev = days.slice(0, -1).reverse().find(d => d.close != null) || null;
+    return {
+        today: { day: last.day, start: last.sessionStart, open: last.open },
+        previous: prev ? { day: prev.day, start: prev.sessionStart, high: prev.high, low: prev.low, close: prev.close } : null
+    };
+};
+
+/**
+ * Levels of the dataset `id` ('symbol:timeframe'). options: { calendar, levels? }.
+ * Returns { datasetId, today, previous, levels: [{ key, label, price, time }] }
+ * with only the levels the data has.
+ */
+const computeSessionLevels = (db, id, { calendar, levels: keys = null } = {}) => {
+    const { symbol, timeframe } = parseDatasetId(id);
+    if (!TIMEFRAME_MS[timeframe]) throw new Error(`Unknown timeframe: ${timeframe}`);
+    const wanted = cleanLevelKeys(keys);
+    const latest = db.prepare('SELECT MAX(timestamp) AS t FROM market_data WHERE symbol = ? AND timeframe = ?').get(symbol, timeframe).t;
+    if (latest == null) return { datasetId: id, today: null, previous: null, levels: [] };
+    const lookback = Math.max(LOOKBACK_DAYS * DAY_MS, 3 * TIMEFRAME_MS[timeframe]);
+    const bars = db.prepare('SELECT timestamp, open, high, low, close, volume FROM market_data WHERE symbol = ? AND timeframe = ? AND timestamp >= ? ORDER BY timestamp')
+        .all(symbol, timeframe, latest - lookback);
+    const { today, previous } = referenceSessions(bars, timeframe, calendar);
+    const values = {
+        pdh: previous && { price: previous.high, time: previous.start },
+        pdl: previous && { price: previous.low, time: previous.start },
+        pdc: previous && { price: previous.close, time: previous.start },
+        open: today && { price: today.open, time: today.start }
+    };
+    const levels = wanted
+        .filter(key => values[key] && Number.isFinite(values[key].price))
+        .map(key => ({ key, label: LEVELS[key].label, ...values[key] }));
+    return { datasetId: id, today, previous, levels };
+};
+
+const levelDrawing = (level) => ({
+    id: `session-level-${level.key}`,
+    type: 'horizontal_line',
+    points: [{ time: level.time, price: level.price }],
+    properties: { color: LEVELS[level.key].color, lineWidth: 1, lineStyle: 'dashed', text: level.label, locked: true, sessionLevel: level.key },
+    folderId: FOLDER_ID
+});
+
+/**
+ * Brings the session level lines of `state` in line with `levels` (from
+ * computeSessionLevels). Returns { state, changed, added, updated, removed };
+ * the input state is not modified.
+ */
+const applySessionLevels = (state, levels) => {
+    const byKey = new Map(levels.map(l => [l.key, l]));
+    const seen = new Set();
+    let added = 0, updated = 0, removed = 0;
+    const drawings = [];
+    (state.drawings || []).forEach((drawing) => {
+        const key = drawing && drawing.properties && drawing.properties.sessionLevel;
+        if (!key) { drawings.push(drawing); return; }
+        const level = byKey.get(key);
+        if (!level || seen.has(key)) { removed++; return; }
+        seen.add(key);
+        const point = drawing.points && drawing.points[0];
+        if (point && point.time === level.time && point.price === level.price) { drawings.push(drawing); return; }
+        updated++;
+        drawings.push({ ...drawing, points: [{ time: level.time, price: level.price }] });
+    });
+    levels.forEach((level) => {
+        if (seen.has(level.key)) return;
+        added++;
+        drawings.push(levelDrawing(level));
+    });
+    const changed = added + updated + removed > 0;
+    if (!changed) return { state, changed, added, updated, removed };
+    const next = { ...state, drawings, timestamp: Date.now() };
+    const folders = state.folders || [];
+    const hasLevels = drawings.some(d => d && d.folderId === FOLDER_ID);
+    if (hasLevels && !folders.some(f => f && f.id === FOLDER_ID)) next.folders = [...folders, { id: FOLDER_ID, name: FOLDER_NAME, isExpanded: false }];
+    return { state: next, changed, added, updated, removed };
+};
+
+module.exports = { LEVEL_KEYS, cleanLevelKeys, computeSessionLevels, applySessionLevels };
@@ -0,0 +1,295 @@
+
+// --- TRADING SESSIONS ---
+// Session calendars and the per-day statistics traders plot from them:
+// opening range, session high / low / VWAP and the overnight range. A
+// calendar has a timezone, a regular session (rth) and optionally a longer
+// electronic session (eth, e.g. CME Globex); a time window that closes at or
+// before it opens starts the previous evening. Days are named after the
+// local date the session closes on, so a Sunday-evening Globex open belongs
+// to Monday. Without an eth, "overnight" is what trades between the previous
+// regular close and this open (extended hours, if the data has them);
+// round-the-clock markets (overnight: false) have none.
+// Daylight saving follows the timezone through Intl.
+//
+// Segments tag each bar (by its open time) as 'pre', 'regular' or 'post'
+// relative to the regular session of its day. With an eth the day starts at
+// the eth open, otherwise at local midnight, so a US equity bar at 18:00 is
+// that day's after-hours and one at 07:00 the next day's pre-market; time
+// between sessions (weekends, holidays) belongs to the session before it
+// until midnight.
+
+const { rangeStats } = require('./fastAggregate');
+
+const DAY_MS = 86400000;
+const MINUTE_MS = 60000;
+const WEEKDAYS = [1, 2, 3, 4, 5];
+
+const CALENDARS = {
+    us_equity: { name: 'US equities', timezone: 'America/New_York', rth: { open: '09:30', close: '16:00' }, weekdays: WEEKDAYS },
+    cme_globex: { name: 'CME Globex', timezone: 'America/New_York', rth: { open: '09:30', close: '16:00' }, eth: { open: '18:00', close: '17:00' }, weekdays: WEEKDAYS },
+    eurex: { name: 'Eurex', timezone: 'Europe/Berlin', rth: { open: '09:00', close: '17:30' }, eth: { open: '01:10', close: '22:00' }, weekdays: WEEKDAYS },
+    lse: { name: 'London Stock Exchange', timezone: 'Europe/London', rth: { open: '08:00', close: '16:30' }, weekdays: WEEKDAYS },
+    forex: { name: 'Forex (New York close)', timezone: 'America/New_York', rth: { open: '17:00', close: '17:00' }, weekdays: WEEKDAYS, overnight: false },
+    crypto: { name: 'Crypto (UTC days)', timezone: 'UTC', rth: { open: '00:00', close: '24:00' }, weekdays: [0, 1, 2, 3, 4, 5, 6], overnight: false }
+};
+
+const ASSET_CALENDARS = { stock: 'us_equity', future: 'cme_globex', forex: 'forex', crypto: 'crypto' };
+
+const formatters = new Map();
+const partsIn = (ts, timezone) => {
+    if (!formatters.has(timezone)) {
+        formatters.set(timezone, new Intl.DateTimeFormat('en-US', {
+            timeZone: timezone, hourCycle: 'h23', year: 'numeric', month: '2-digit', day: '2-digit', hour: '2-digit', minute: '2-digit', second: '2-digit'
+        }));
+    }
+    const parts = {};
+    formatters.get(timezone).formatToParts(new Date(ts)).forEach((p) => { if (p.type !== 'literal') parts[p.type] = Number(p.value); });
+    return parts;
+};
+
+// Milliseconds the timezone is ahead of UTC at ts
+const offsetAt = (ts, timezone) => {
+    const p = partsIn(ts, timezone);
+    return Date.UTC(p.year, p.month - 1, p.day, p.hour, p.minute, p.second) - (ts - (ts % 1000));
+};
+
+// UTC timestamp of a local wall-clock time (dayUtc: midnight UTC of the local date)
+const localToUtc = (dayUtc, minutes, timezone) => {
+    const guess = dayUtc + minutes * MINUTE_MS;
+    const first = guess - offsetAt(guess, timezone);
+    const second = guess - offsetAt(first, timezone);
+    return second;
+};
+
+const parseClock = (text) => {
+    const m = /^(\d{1,2}):(\d{2})$/.exec(String(text || ''));
+    if (!m || Number(m[1]) > 24 || Number(m[2]) > 59) throw new Error(`Invalid session time: ${text}`);
+    return Number(m[1]) * 60 + Number(m[2]);
+};
+
+const isoDay = (dayUtc) => new Date(dayUtc).toISOString().slice(0, 10);
+
+/**
+ * spec: a calendar id or { timezone, rth: { open, close }, eth?, weekdays?, holidays?: ['YYYY-MM-DD'], overnight? }
+ */
+const resolveCalendar = (spec) => {
+    const base = typeof spec === 'string' ? CALENDARS[spec] : spec;
+    if (!base) throw new Error(`Unknown session calendar: ${spec}`);
+    const calendar = { ...base, id: typeof spec === 'string' ? spec : 'custom', holidays: new Set(base.holidays || []) };
+    try { partsIn(0, calendar.timezone); } catch (e) { throw new Error(`Unknown timezone: ${calendar.timezone}`); }
+    const window = (w) => (w ? { open: parseClock(w.open), close: parseClock(w.close) } : null);
+    calendar.rthMinutes = window(calendar.rth);
+    if (!calendar.rthMinutes) throw new Error('A session calendar needs rth hours');
+    calendar.ethMinutes = window(calendar.eth);
+    calendar.weekdays = calendar.weekdays || WEEKDAYS;
+    return calendar;
+};
+
+// Window of one local day; opening at or after the close means it opened the day before
+const windowOn = (dayUtc, { open, close }, timezone) => ({
+    start: localToUtc(open >= close ? dayUtc - DAY_MS : dayUtc, open, timezone),
+    end: localToUtc(dayUtc, close, timezone)
+});
+
+/**
+ * Session windows of every trading day overlapping [from, to].
+ * Returns [{ day, rth: { start, end }, eth, overnight }] (timestamps, end exclusive).
+ */
+const sessionWindows = (calendarSpec, from, to) => {
+    const calendar = resolveCalendar(calendarSpec);
+    const first = partsIn(from, calendar.timezone);
+    let dayUtc = Date.UTC(first.year, first.month - 1, first.day) - DAY_MS;
+    const days = [];
+    let previousClose = null;
+    while (dayUtc <= to + 2 * DAY_MS) {
+        const weekday = new Date(dayUtc).getUTCDay();
+        if (calendar.weekdays.includes(weekday) && !calendar.holidays.has(isoDay(dayUtc))) {
+            const rth = windowOn(dayUtc, calendar.rthMinutes, calendar.timezone);
+            const eth = calendar.ethMinutes ? windowOn(dayUtc, calendar.ethMinutes, calendar.timezone) : null;
+            let overnight = null;
+            if (eth && eth.start < rth.start) overnight = { start: eth.start, end: rth.start };
+            else if (!eth && calendar.overnight !== false && previousClose != null && previousClose < rth.start) overnight = { start: previousClose, end: rth.start };
+            previousClose = rth.end;
+            const span = eth ? { start: Math.min(eth.start, rth.start), end: Math.max(eth.end, rth.end) } : rth;
+            if (span.end > from && span.start <= to) days.push({ day: isoDay(dayUtc), rth, eth, overnight });
+        }
+        dayUtc += DAY_MS;
+    }
+    return { calendar, days };
+};
+
+// First index with bars[i].timestamp >= ts
+const lowerBound = (bars, ts) => {
+    let lo = 0;
+    let hi = bars.length;
+    while (lo < hi) {
+        const mid = (lo + hi) >> 1;
+        if (bars[mid].timestamp < ts) lo = mid + 1;
+        else hi = mid;
+    }
+    return lo;
+};
+
+const rangeOf = (bars, start, end) => {
+    const from = lowerBound(bars, start);
+    const stats = rangeStats(bars, from, lowerBound(bars, end));
+    if (!stats) return null;
+    const high = bars[stats.highIndex];
+    const low = bars[stats.lowIndex];
+    return {
+        open: bars[from].open,
+        high: high.high,
+        low: low.low,
+        close: bars[from + stats.count - 1].close,
+        highAt: high.timestamp,
+        lowAt: low.timestamp,
+        volume: stats.volume,
+        vwap: stats.volume > 0 ? stats.pv / stats.volume : null,
+        bars: stats.count
+    };
+};
+
+/**
+ * bars: intraday bars, oldest first. options: { calendar, openingRangeMinutes = 30 }.
+ * Returns { calendar: { id, name, timezone }, days: [{ day, sessionStart, sessionEnd,
+ * open, high, low, close, highAt, lowAt, volume, vwap, bars, openingRange, overnight, eth }] }.
+ */
+const computeSessionStats = (bars, { calendar: calendarSpec = 'us_equity', openingRangeMinutes = 30 } = {}) => {
+    const minutes = Number(openingRangeMinutes);
+    if (!(minutes > 0 && minutes <= 24 * 60)) throw new Error('openingRangeMinutes must be between 1 and 1440');
+    if (!bars.length) {
+        const calendar = resolveCalendar(calendarSpec);
+        return { calendar: { id: calendar.id, name: calendar.name || null, timezone: calendar.timezone }, days: [] };
+    }
+    const { calendar, days: windows } = sessionWindows(calendarSpec, bars[0].timestamp, bars[bars.length - 1].timestamp);
+    const days = [];
+    windows.forEach(({ day, rth, eth, overnight }) => {
+        const session = rangeOf(bars, rth.start, rth.end);
+        const ethRange = eth ? rangeOf(bars, eth.start, eth.end) : null;
+        if (!session && !ethRange) return;
+        const opening = rangeOf(bars, rth.start, Math.min(rth.end, rth.start + minutes * MINUTE_MS));
+        const night = overnight ? rangeOf(bars, overnight.start, overnight.end) : null;
+        days.push({
+            day,
+            sessionStart: rth.start,
+            sessionEnd: rth.end,
+            ...(session || { open: null, high: null, low: null, close: null, highAt: null, lowAt: null, volume: 0, vwap: null, bars: 0 }),
+            openingRange: opening ? { minutes, start: rth.start, end: rth.start + minutes * MINUTE_MS, high: opening.high, low: opening.low } : null,
+            overnight: night ? { start: overnight.start, end: overnight.end, high: night.high, low: night.low } : null,
+            eth: ethRange ? { start: eth.start, end: eth.end, high: ethRange.high, low: ethRange.low, vwap: ethRange.vwap, volume: ethRange.volume } : null
+        });
+    });
+    return { calendar: { id: calendar.id, name: calendar.name || null, timezone: calendar.timezone }, days };
+};
+
+const SEGMENTS = ['pre', 'regular', 'post'];
+const LOAD_MARGIN_MS = 7 * DAY_MS;
+
+/**
+ * Returns classify(ts) -> { day, segment } for a calendar, with the session
+ * windows loaded around whatever is asked for (call load(from, to) first when
+ * classifying a long series).
+ */
+const createSegmentClassifier = (calendarSpec) => {
+    const calendar = resolveCalendar(calendarSpec);
+    let spans = []; // [{ day, start, rthStart, rthEnd }], sorted; span i ends where i + 1 starts
+    let covered = { from: Infinity, to: -Infinity };
+
+    const load = (from, to) => {
+        const { days } = sessionWindows(calendarSpec, from - LOAD_MARGIN_MS, to + LOAD_MARGIN_MS);
+        spans = days.map((d, i) => {
+            const prev = days[i - 1];
+            let start = d.eth && d.eth.start < d.rth.start ? d.eth.start : localToUtc(Date.parse(`${d.day}T00:00:00Z`), 0, calendar.timezone);
+            start = Math.min(start, d.rth.start);
+            if (prev) start = Math.max(start, prev.rth.end);
+            return { day: d.day, start, rthStart: d.rth.start, rthEnd: d.rth.end };
+        });
+        // The first and last spans have no neighbour to bound them, so only the inside counts as loaded
+        covered = spans.length > 2 ? { from: spans[1].start, to: spans[spans.length - 1].start } : { from: Infinity, to: -Infinity };
+    };
+
+    const classify = (ts) => {
+        if (!(ts >= covered.from && ts < covered.to)) load(ts, ts);
+        let lo = 0;
+        let hi = spans.length - 1;
+        while (lo < hi) {
+            const mid = (lo + hi + 1) >> 1;
+            if (spans[mid].start <= ts) lo = mid;
+            else hi = mid - 1;
+        }
+        const span = spans[lo];
+        if (!span) return { day: null, segment: 'regular' };
+        return { day: span.day, segment: ts < span.rthStart ? 'pre' : ts < span.rthEnd ? 'regular' : 'post' };
+    };
+
+    return { classify, load, calendar };
+};
+
+const cleanSegments = (segments) => {
+    if (segments == null) return SEGMENTS;
+    const list = Array.isArray(segments) ? segments : [segments];
+    const unknown = list.filter(s => !SEGMENTS.includes(s));
+    if (unknown.length) throw new Error(`Unknown session segment: ${unknown.join(', ')} (expected ${SEGMENTS.join(', ')})`);
+    return list;
+};
+
+/**
+ * bars: oldest first. Returns the bars with `segment` and `day` added.
+ */
+const tagSegments = (bars, { calendar = 'us_equity' } = {}) => {
+    if (!bars.length) return [];
+    const classifier = createSegmentClassifier(calendar);
+    classifier.load(bars[0].timestamp, bars[bars.length - 1].timestamp);
+    return bars.map(bar => ({ ...bar, ...classifier.classify(bar.timestamp) }));
+};
+
+const segmentRange = (bars) => {
+    const stats = rangeStats(bars);
+    if (!stats) return null;
+    const high = bars[stats.highIndex];
+    const low = bars[stats.lowIndex];
+    return { start: bars[0].timestamp, end: bars[bars.length - 1].timestamp, high: high.high, low: low.low, highAt: high.timestamp, lowAt: low.timestamp, volume: stats.volume, bars: bars.length };
+};
+
+/**
+ * Pre-market, regular and after-hours ranges per day, from intraday bars (oldest
+ * first). `latest` is the extended-hours range traders mark before the open:
+ * the previous day's after-hours plus today's pre-market.
+ * Returns { calendar: { id, name, timezone }, days: [{ day, pre, regular, post }],
+ * latest: { day, pre, post, high, low } | null } (ranges: { start, end, high, low, highAt, lowAt, volume, bars } or null).
+ */
+const computeExtendedHoursLevels = (bars, { calendar: calendarSpec = 'us_equity' } = {}) => {
+    const classifier = createSegmentClassifier(calendarSpec);
+    const { calendar } = classifier;
+    const info = { id: calendar.id, name: calendar.name || null, timezone: calendar.timezone };
+    if (!bars.length) return { calendar: info, days: [], latest: null };
+    classifier.load(bars[0].timestamp, bars[bars.length - 1].timestamp);
+    const byDay = new Map();
+    bars.forEach((bar) => {
+        const { day, segment } = classifier.classify(bar.timestamp);
+        if (!byDay.has(day)) byDay.set(day, { pre: [], regular: [], post: [] });
+        byDay.get(day)[segment].push(bar);
+    });
+    const days = Array.from(byDay.entries()).map(([day, groups]) => ({
+        day, pre: segmentRange(groups.pre), regular: segmentRange(groups.regular), post: segmentRange(groups.post)
+    }));
+    const today = days[days.length - 1];
+    const previous = days.length > 1 ? days[days.length - 2] : null;
+    const pre = today.pre;
+    const post = previous ? previous.post : null;
+    const parts = [post, pre].filter(Boolean);
+    const latest = {
+        day: today.day,
+        pre,
+        post,
+        high: parts.length ? Math.max(...parts.map(p => p.high)) : null,
+        low: parts.length ? Math.min(...parts.map(p => p.low)) : null
+    };
+    return { calendar: info, days, latest };
+};
+
+const listCalendars = () => Object.entries(CALENDARS).map(([id, c]) => ({ id, ...c }));
+
+const calendarForAsset = (assetClass) => ASSET_CALENDARS[assetClass] || 'us_equity';
+
+module.exports = { CALENDARS, SEGMENTS, partsIn, localToUtc, listCalendars, calendarForAsset, resolveCalendar, sessionWindows, computeSessionStats, createSegmentClassifier, cleanSegments, tagSegments, computeExtendedHoursLevels };
@@ -0,0 +1,158 @@
+
+const fs = require('fs');
+const { listThemes, getTheme, saveTheme, normalizeTheme } = require('./themes');
+const { AppError } = require('./appErrors');
+
+// --- SETTINGS PROFILE ---
+// One file with everything needed to set up another machine the same way:
+//
+//   preferences  every settings key not listed below (proxy, news, alerts, ...)
+//   keybindings  the 'keybindings' overrides
+//   themes       user themes (built-ins ship with the app)
+//   providers    'provider.<id>' and 'broker.<id>' configs
+//   layouts      'layout.<name>' saved layouts
+//
+// Secrets never leave the secrets store, and fields that look like
+// credentials are stripped on export and listed in the file, so a profile can
+// be shared. Keys that only make sense on one install (token hashes, install
+// ids, maintenance timestamps) are left out. Import runs as a plan first: per
+// section which keys would be added, changed or stay, so a dry run shows the
+// diff and the real run applies the same plan.
+
+const SETTINGS_FILE_FORMAT = 'redpill-settings';
+const SETTINGS_FILE_VERSION = 1;
+const SETTINGS_SECTIONS = ['preferences', 'keybindings', 'themes', 'providers', 'layouts'];
+const MACHINE_KEYS = ['inbox.tokenHash', 'maintenance.lastCompaction', 'usage.analytics'];
+const CREDENTIAL_FIELD = /(password|passphrase|secret|token|api[-_]?key|private[-_]?key|credential)/i;
+
+const sectionOf = (key) => {
+    if (key === 'keybindings') return 'keybindings';
+    if (key.startsWith('provider.') || key.startsWith('broker.')) return 'providers';
+    if (key.startsWith('layout.')) return 'layouts';
+    return 'preferences';
+};
+
+// Copy of value without credential-looking fields; stripped paths are collected
+const stripCredentials = (value, path, stripped) => {
+    if (Array.isArray(value)) return value.map((v, i) => stripCredentials(v, `${path}[${i}]`, stripped));
+    if (!value || typeof value !== 'object') return value;
+    return Object.fromEntries(Object.entries(value).filter(([k, v]) => {
+        if (CREDENTIAL_FIELD.test(k) && v != null && v !== '' && typeof v !== 'boolean') {
+            stripped.push(`${path}.${k}`);
+            return false;
+        }
+        return true;
+    }).map(([k, v]) => [k, stripCredentials(v, `${path}.${k}`, stripped)]));
+};
+
+const readSettingsRows = (db) => db.prepare('SELECT key, value FROM settings').all().reduce((out, row) => {
+    try { out[row.key] = JSON.parse(row.value); } catch (e) { /* unreadable rows stay behind */ }
+    return out;
+}, {});
+
+/**
+ * Profile object for the current workspace. sections: subset of SETTINGS_SECTIONS.
+ */
+const buildSettingsProfile = (db, { sections = SETTINGS_SECTIONS, appVersion = null } = {}) => {
+    const wanted = new Set(sections);
+    const stripped = [];
+    const profile = { format: SETTINGS_FILE_FORMAT, version: SETTINGS_FILE_VERSION, exportedAt: new Date().toISOString(), appVersion, sections: {} };
+    SETTINGS_SECTIONS.filter(s => wanted.has(s) && s !== 'themes').forEach((s) => { profile.sections[s] = {}; });
+    Object.entries(readSettingsRows(db)).forEach(([key, value]) => {
+        if (MACHINE_KEYS.includes(key)) return;
+        const section = sectionOf(key);
+        if (!wanted.has(section)) return;
+        profile.sections[section][key] = stripCredentials(value, key, stripped);
+    });
+    if (wanted.has('themes')) {
+        profile.sections.themes = listThemes(db).filter(t => !t.builtin).map(({ builtin, active, updatedAt, ...theme }) => theme);
+    }
+    profile.stripped = stripped;
+    return profile;
+};
+
+const writeSettingsFile = (filePath, profile) => {
+    const text = JSON.stringify(profile, null, 2);
+    fs.writeFileSync(filePath, text);
+    return Buffer.byteLength(text);
+};
+
+const readSettingsFile = (filePath) => {
+    let text;
+    try {
+        text = fs.readFileSync(filePath, 'utf8');
+    } catch (err) {
+        // Keeps the system code (ENOENT, EACCES, ...) for the error classification
+        err.message = `Not a readable settings file: ${err.message}`;
+        throw err;
+    }
+    let parsed;
+    try {
+        parsed = JSON.parse(text);
+    } catch (err) {
+        throw new AppError('INVALID_JSON', `Not a readable settings file: ${err.message}`, { path: filePath });
+    }
+    if (!parsed || parsed.format !== SETTINGS_FILE_FORMAT) throw new AppError('INVALID_INPUT', 'Not a Red Pill settings file', { path: filePath });
+    if (!Number.isInteger(parsed.version) || parsed.version < 1) throw new AppError('CORRUPT_DATA', 'Settings file has no valid version', { path: filePath });
+    if (parsed.version > SETTINGS_FILE_VERSION) {
+        throw new AppError('UNSUPPORTED', `Settings file version ${parsed.version} is newer than this app supports (${SETTINGS_FILE_VERSION})`, { version: parsed.version, supported: SETTINGS_FILE_VERSION });
+    }
+    if (!parsed.sections || typeof parsed.sections !== 'object') throw new AppError('CORRUPT_DATA', 'Settings file has no sections', { path: filePath });
+    return parsed;
+};
+
+const sameJson = (a, b) => JSON.stringify(a) === JSON.stringify(b);
+
+const isPlainObject = (value) => value != null && typeof value === 'object' && !Array.isArray(value);
+
+/**
+ * What importing `profile` would do: { sections: { name: { added, changed,
+ * unchanged } }, invalid, stripped, changes } with key (or theme name) lists,
+ * plus the writes to apply. Only keys in the file are touched; nothing is removed.
+ */
+const planSettingsImport = (db, profile, { sections = SETTINGS_SECTIONS } = {}) => {
+    const current = readSettingsRows(db);
+    const plan = { version: profile.version, exportedAt: profile.exportedAt || null, appVersion: profile.appVersion || null, sections: {}, invalid: [], stripped: profile.stripped || [], writes: [], themes: [] };
+    sections.filter(s => SETTINGS_SECTIONS.includes(s) && profile.sections[s] != null).forEach((section) => {
+        const diff = { added: [], changed: [], unchanged: [] };
+        plan.sections[section] = diff;
+        if (section === 'themes') {
+            (Array.isArray(profile.sections.themes) ? profile.sections.themes : []).forEach((theme, i) => {
+                let normalized;
+                try { normalized = normalizeTheme(theme); } catch (err) { plan.invalid.push({ section, key: `themes[${i}]`, error: err.message }); return; }
+                const existing = getTheme(db, normalized.name);
+                if (existing && existing.builtin) { plan.invalid.push({ section, key: normalized.name, error: 'Has the name of a built-in theme' }); return; }
+                const { builtin, ...existingData } = existing || {};
+                const bucket = !existing ? diff.added : sameJson(normalizeTheme(existingData), normalized) ? diff.unchanged : diff.changed;
+                bucket.push(normalized.name);
+                if (bucket !== diff.unchanged) plan.themes.push(normalized);
+            });
+            return;
+        }
+        Object.entries(profile.sections[section] || {}).forEach(([key, value]) => {
+            if (MACHINE_KEYS.includes(key) || sectionOf(key) !== section) { plan.invalid.push({ section, key, error: 'Key does not belong to this section' }); return; }
+            // Objects merge over the local value, so stripped credentials stay as they are on this machine
+            const next = isPlainObject(current[key]) && isPlainObject(value) ? { ...current[key], ...value } : value;
+            const bucket = !(key in current) ? diff.added : sameJson(current[key], next) ? diff.unchanged : diff.changed;
+            bucket.push(key);
+            if (bucket !== diff.unchanged) plan.writes.push([key, next]);
+        });
+    });
+    plan.changes = plan.writes.length + plan.themes.length;
+    return plan;
+};
+
+// Applies a plan from planSettingsImport in one transaction; returns the keys written
+const applySettingsImport = (db, plan) => db.transaction(() => {
+    const stmt = db.prepare('INSERT OR REPLACE INTO settings (key, value) VALUES (?, ?)');
+    plan.writes.forEach(([key, value]) => stmt.run(key, JSON.stringify(value)));
+    plan.themes.forEach(theme => saveTheme(db, theme));
+    return { keys: plan.writes.map(([key]) => key), themes: plan.themes.map(t => t.name) };
+})();
+
+const publicPlan = ({ writes, themes, ...plan }) => plan;
+
+module.exports = {
+    SETTINGS_SECTIONS, buildSettingsProfile, writeSettingsFile, readSettingsFile,
+    planSettingsImport, applySettingsImport, publicPlan
+};
@@ -0,0 +1,60 @@
+
+// --- SHUTDOWN PIPELINE ---
+// Quitting runs the steps in order (flush renderers' pending saves, stop
+// services, disconnect feeds, finalize recordings, checkpoint and close the
+// database) before the app really exits. Each step has its own timeout and a
+// failing or slow step is logged and skipped, so a stuck feed can't hold the
+// app open; the whole run is capped at totalTimeoutMs. A second quit request
+// while it runs is the force-quit escape hatch.
+
+const DEFAULT_STEP_TIMEOUT_MS = 3000;
+const DEFAULT_TOTAL_TIMEOUT_MS = 10000;
+
+const withTimeout = (promise, ms, label) => new Promise((resolve, reject) => {
+    const timer = setTimeout(() => reject(new Error(`${label} timed out after ${ms}ms`)), ms);
+    Promise.resolve(promise).then(
+        (value) => { clearTimeout(timer); resolve(value); },
+        (err) => { clearTimeout(timer); reject(err); }
+    );
+});
+
+/**
+ * steps: [{ name, run: async () => any, timeoutMs? }]. onProgress({ step, status:
+ * 'running' | 'done' | 'failed', error?, ms }) per step. run() resolves with
+ * { steps, ms, timedOut } once every step finished or the total cap hit.
+ */
+const createShutdownPipeline = ({ steps = [], totalTimeoutMs = DEFAULT_TOTAL_TIMEOUT_MS, onProgress = () => {} }) => {
+    let state = 'idle'; // idle | running | done
+    let running = null;
+
+    const runSteps = async () => {
+        const results = [];
+        for (const step of steps) {
+            const started = Date.now();
+            onProgress({ step: step.name, status: 'running', ms: 0 });
+            try {
+                await withTimeout((async () => step.run())(), step.timeoutMs || DEFAULT_STEP_TIMEOUT_MS, step.name);
+                results.push({ step: step.name, status: 'done', ms: Date.now() - started });
+            } catch (err) {
+                results.push({ step: step.name, status: 'failed', error: err.message, ms: Date.now() - started });
+            }
+            onProgress(results[results.length - 1]);
+        }
+        return results;
+    };
+
+    const run = () => {
+        if (running) return running;
+        state = 'running';
+        const started = Date.now();
+        running = withTimeout(runSteps(), totalTimeoutMs, 'shutdown')
+            .then(results => ({ steps: results, ms: Date.now() - started, timedOut: false }))
+            .catch(() => ({ steps: [], ms: Date.now() - started, timedOut: true }))
+            .finally(() => { state = 'done'; });
+        return running;
+    };
+
+    return { run, state: () => state };
+};
+
+module.exports = { createShutdownPipeline, withTimeout };
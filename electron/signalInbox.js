@@ -0,0 +1,228 @@
+
+const crypto = require('crypto');
+const http = require('http');
+
+// --- SIGNAL INBOX ---
+// A local HTTP endpoint where scripts push signals and annotations:
+//
+//   POST http://127.0.0.1:<port>/signals
+//   Authorization: Bearer <token>
+//   { symbol, side?, price?, time?, text?, source?, notify? }   (application/json)
+//   signal: long ES 5021.25 breakout above ONH                  (text/plain)
+//
+// An array of objects, or several text lines, posts a batch. Signals are
+// stored in `signal_inbox` and forwarded to the charts of their symbol as
+// markers. The server only listens on loopback, and only a SHA-256 of the token
+// is kept, so the plaintext is shown once when it is generated.
+
+const DEFAULT_PORT = 47321;
+const MAX_BODY_BYTES = 64 * 1024;
+const MAX_BATCH = 100;
+const RATE_LIMIT_PER_MINUTE = 120;
+const SIDES = { long: 'long', buy: 'long', short: 'short', sell: 'short', flat: 'flat', exit: 'flat', close: 'flat' };
+
+const initializeSignalInboxTable = (db) => {
+    db.exec(`
+        CREATE TABLE IF NOT EXISTS signal_inbox (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            received_at INTEGER,
+            time INTEGER,
+            symbol TEXT,
+            side TEXT,
+            price REAL,
+            text TEXT,
+            source TEXT,
+            data TEXT,
+            read_at INTEGER
+        );
+        CREATE INDEX IF NOT EXISTS idx_signal_inbox_symbol_time ON signal_inbox(symbol, time);
+    `);
+};
+
+const hashToken = (token) => crypto.createHash('sha256').update(String(token)).digest('hex');
+
+const generateToken = () => crypto.randomBytes(24).toString('base64url');
+
+/**
+ * "signal: long ES 5021.25 breakout" -> { side, symbol, price, text }.
+ * The "signal:" prefix and the side are optional; the first token that isn't
+ * a side is the symbol and a number right after it is the price.
+ */
+const parseSignalText = (line) => {
+    const words = String(line || '').trim().replace(/^signal\s*:\s*/i, '').split(/\s+/).filter(Boolean);
+    if (!words.length) throw new Error('Empty signal');
+    const side = SIDES[words[0].toLowerCase()] || null;
+    if (side) words.shift();
+    const symbol = words.shift();
+    if (!symbol) throw new Error('Signal needs a symbol');
+    let price = null;
+    if (words[0] === '@') words.shift();
+    if (words.length && /^@?-?\d+(\.\d+)?$/.test(words[0])) price = Number(words.shift().replace('@', ''));
+    return { side, symbol, price, text: words.join(' ') || null };
+};
+
+// A stored row from a pushed object
+const normalizeSignal = (input, receivedAt = Date.now()) => {
+    if (!input || typeof input !== 'object' || Array.isArray(input)) throw new Error('Signal must be an object');
+    const symbol = String(input.symbol || '').trim();
+    if (!symbol || symbol.length > 64) throw new Error('Signal needs a symbol');
+    const side = input.side == null || input.side === '' ? null : SIDES[String(input.side).toLowerCase()];
+    if (side === undefined) throw new Error(`Unknown side: ${input.side}`);
+    const price = input.price == null || input.price === '' ? null : Number(input.price);
+    if (price !== null && !Number.isFinite(price)) throw new Error('price must be a number');
+    const rawTime = input.time == null ? receivedAt : (typeof input.time === 'number' ? input.time : Date.parse(input.time));
+    // Seconds since the epoch are common in scripts
+    const time = Number.isFinite(rawTime) ? (rawTime < 1e11 ? rawTime * 1000 : rawTime) : null;
+    if (time == null) throw new Error('time must be a timestamp or an ISO date');
+    const text = input.text == null ? null : String(input.text).slice(0, 2000);
+    const { symbol: _s, side: _d, price: _p, time: _t, text: _x, source, notify, ...extra } = input;
+    return {
+        receivedAt, time, symbol, side, price, text,
+        source: source == null ? null : String(source).slice(0, 100),
+        notify: notify !== false,
+        data: Object.keys(extra).length ? extra : null
+    };
+};
+
+const recordSignal = (db, signal) => {
+    const info = db.prepare('INSERT INTO signal_inbox (received_at, time, symbol, side, price, text, source, data) VALUES (?, ?, ?, ?, ?, ?, ?, ?)')
+        .run(signal.receivedAt, signal.time, signal.symbol, signal.side, signal.price, signal.text, signal.source, signal.data ? JSON.stringify(signal.data) : null);
+    return { id: Number(info.lastInsertRowid), ...signal, readAt: null };
+};
+
+const rowToSignal = (row) => ({
+    id: row.id, receivedAt: row.received_at, time: row.time, symbol: row.symbol, side: row.side, price: row.price,
+    text: row.text, source: row.source, data: row.data ? JSON.parse(row.data) : null, readAt: row.read_at
+});
+
+// options: { symbol?, from?, to?, unreadOnly?, limit? } — newest first
+const listSignals = (db, { symbol = null, from = null, to = null, unreadOnly = false, limit = 500 } = {}) => {
+    const where = [];
+    const params = [];
+    if (symbol) { where.push('symbol = ? COLLATE NOCASE'); params.push(symbol); }
+    if (from != null) { where.push('time >= ?'); params.push(Number(from)); }
+    if (to != null) { where.push('time <= ?'); params.push(Number(to)); }
+    if (unreadOnly) where.push('read_at IS NULL');
+    return db.prepare(`SELECT * FROM signal_inbox${where.length ? ` WHERE ${where.join(' AND ')}` : ''} ORDER BY time DESC, id DESC LIMIT ?`)
+        .all(...params, Math.min(5000, Math.max(1, Number(limit) || 500))).map(rowToSignal);
+};
+
+const markSignalsRead = (db, ids, at = Date.now()) => {
+    const stmt = db.prepare('UPDATE signal_inbox SET read_at = ? WHERE id = ? AND read_at IS NULL');
+    return db.transaction(() => ids.reduce((n, id) => n + stmt.run(at, Number(id)).changes, 0))();
+};
+
+const deleteSignals = (db, ids) => {
+    const stmt = db.prepare('DELETE FROM signal_inbox WHERE id = ?');
+    return db.transaction(() => ids.reduce((n, id) => n + stmt.run(Number(id)).changes, 0))();
+};
+
+const readBody = (req) => new Promise((resolve, reject) => {
+    const chunks = [];
+    let size = 0;
+    req.on('data', (chunk) => {
+        size += chunk.length;
+        if (size > MAX_BODY_BYTES) {
+            reject(Object.assign(new Error('Body too large'), { status: 413 }));
+            req.destroy();
+        } else chunks.push(chunk);
+    });
+    req.on('end', () => resolve(Buffer.concat(chunks).toString('utf8')));
+    req.on('error', reject);
+});
+
+// Pushed objects from a request body (JSON object / array, or text lines)
+const parseBody = (body, contentType) => {
+    if (/json/i.test(contentType || '') || /^\s*[[{]/.test(body)) {
+        let parsed;
+        try { parsed = JSON.parse(body); } catch (e) { throw Object.assign(new Error('Invalid JSON'), { status: 400 }); }
+        return Array.isArray(parsed) ? parsed : [parsed];
+    }
+    return body.split(/\r?\n/).map(l => l.trim()).filter(Boolean).map(parseSignalText);
+};
+
+/**
+ * getTokenHash() -> stored hash or null (no token: every request is refused).
+ * accept(signals) stores and forwards a validated batch and returns the rows.
+ */
+const createSignalInbox = ({ getTokenHash, accept, onLog = () => {} }) => {
+    let server = null;
+    let address = null;
+    let window = { start: 0, count: 0 };
+
+    const authorized = (req) => {
+        const expected = getTokenHash();
+        if (!expected) return false;
+        const header = req.headers.authorization || '';
+        const token = header.startsWith('Bearer ') ? header.slice(7).trim() : String(req.headers['x-redpill-token'] || '');
+        if (!token) return false;
+        const a = Buffer.from(hashToken(token), 'hex');
+        const b = Buffer.from(expected, 'hex');
+        return a.length === b.length && crypto.timingSafeEqual(a, b);
+    };
+
+    const rateLimited = () => {
+        const now = Date.now();
+        if (now - window.start > 60000) window = { start: now, count: 0 };
+        window.count++;
+        return window.count > RATE_LIMIT_PER_MINUTE;
+    };
+
+    const send = (res, status, payload) => {
+        res.writeHead(status, { 'Content-Type': 'application/json' });
+        res.end(JSON.stringify(payload));
+    };
+
+    const handle = async (req, res) => {
+        try {
+            const url = new URL(req.url, 'http://localhost');
+            if (req.method === 'GET' && url.pathname === '/health') return send(res, 200, { ok: true });
+            if (url.pathname !== '/signals') return send(res, 404, { error: 'Not found' });
+            if (req.method !== 'POST') return send(res, 405, { error: 'Use POST' });
+            // Browsers attach an Origin; a web page must not be able to post into the inbox
+            if (req.headers.origin) return send(res, 403, { error: 'Cross-origin requests are not accepted' });
+            if (!authorized(req)) return send(res, 401, { error: 'Missing or invalid token' });
+            if (rateLimited()) return send(res, 429, { error: 'Too many requests' });
+            const items = parseBody(await readBody(req), req.headers['content-type']);
+            if (!items.length) return send(res, 400, { error: 'No signals in the body' });
+            if (items.length > MAX_BATCH) return send(res, 413, { error: `At most ${MAX_BATCH} signals per request` });
+            const receivedAt = Date.now();
+            const signals = items.map((item, i) => {
+                try { return normalizeSignal(item, receivedAt); } catch (err) { throw Object.assign(new Error(`Signal ${i}: ${err.message}`), { status: 400 }); }
+            });
+            const stored = accept(signals);
+            return send(res, 201, { accepted: stored.length, ids: stored.map(s => s.id) });
+        } catch (err) {
+            if (!err.status) onLog('WARN', 'SIGNAL_INBOX_ERROR', { error: err.message });
+            return send(res, err.status || 500, { error: err.message });
+        }
+    };
+
+    const start = (port = DEFAULT_PORT) => new Promise((resolve, reject) => {
+        if (server) { resolve(address); return; }
+        const next = http.createServer((req, res) => { handle(req, res); });
+        next.once('error', reject);
+        next.listen(port, '127.0.0.1', () => {
+            server = next;
+            address = { host: '127.0.0.1', port: next.address().port };
+            onLog('INFO', 'SIGNAL_INBOX_LISTENING', address);
+            resolve(address);
+        });
+    });
+
+    const stop = () => new Promise((resolve) => {
+        if (!server) { resolve(); return; }
+        const closing = server;
+        server = null;
+        address = null;
+        closing.close(() => resolve());
+        if (typeof closing.closeAllConnections === 'function') closing.closeAllConnections();
+    });
+
+    return { start, stop, address: () => address };
+};
+
+module.exports = {
+    DEFAULT_PORT, initializeSignalInboxTable, hashToken, generateToken, parseSignalText, normalizeSignal,
+    recordSignal, listSignals, markSignalsRead, deleteSignals, createSignalInbox
+};
@@ -0,0 +1,436 @@
+
+const crypto = require('crypto');
+
+// --- STICKY NOTES ---
+// Notes live in `sticky_notes`, one row per note, and change through batches
+// of operations (create / update / delete / recolor / retag) instead of
+// re-saving the whole list. A batch is validated and applied in a single
+// transaction: if any operation fails, none are applied and the error names
+// the offending operation. Returns item-level changes for the sync bus.
+// Notes are grouped into boards (per strategy, per week...); a note without
+// a board sits on the default board, and loads are per board. Archiving a
+// board hides it from the board list but keeps its notes loadable.
+// Archived notes (archive / unarchive operations, or the auto-archive rules)
+// stay in the table but drop out of board loads; search still finds them.
+// Ink notes carry their strokes in inkData (note-local pixels) and, once a
+// recognizer has read them (inkRecognition.js), the text in recognizedText;
+// changing the ink clears a recognizedText that isn't set alongside it.
+// Every batch that changes a note's content (text, colour, tags, symbol,
+// board, ink) first saves its previous content to `note_versions`, deletes
+// included, so a note can be restored; layout moves and archiving don't
+// count. Only the newest version is stored whole: each older one is a diff
+// against the next newer (changed fields, and the text as the span that
+// differs), and at most MAX_VERSIONS_PER_NOTE are kept per note.
+
+const MAX_OPS_PER_BATCH = 1000;
+const MAX_TEXT_LENGTH = 20000;
+const MAX_TAGS = 32;
+const COLOR_RE = /^#([0-9a-f]{3}|[0-9a-f]{6}|[0-9a-f]{8})$/i;
+const DEFAULT_COLOR = '#F59E0B';
+const MAX_BOARD_NAME = 100;
+const DAY_MS = 86400000;
+const DEFAULT_SEARCH_LIMIT = 100;
+const MAX_INK_POINTS = 200000;
+const MAX_VERSIONS_PER_NOTE = 50;
+const VERSIONED_FIELDS = ['color', 'tags', 'symbol', 'boardId', 'inkData'];
+
+// untouchedDays: null disables that rule
+const AUTO_ARCHIVE_DEFAULTS = { enabled: true, minimizedDays: 90, untouchedDays: null };
+
+const initializeStickyNotesTable = (db) => {
+    db.exec(`
+        CREATE TABLE IF NOT EXISTS sticky_notes (
+            id TEXT PRIMARY KEY,
+            text TEXT,
+            color TEXT,
+            tags TEXT,
+            layout TEXT,
+            symbol TEXT,
+            created_at INTEGER,
+            updated_at INTEGER
+        );
+        CREATE TABLE IF NOT EXISTS note_boards (
+            id TEXT PRIMARY KEY,
+            name TEXT,
+            archived INTEGER DEFAULT 0,
+            created_at INTEGER,
+            updated_at INTEGER
+        );
+    `);
+    // Tables created before boards / archiving existed lack the columns
+    const columns = db.prepare('PRAGMA table_info(sticky_notes)').all().map(c => c.name);
+    if (!columns.includes('board_id')) db.exec('ALTER TABLE sticky_notes ADD COLUMN board_id TEXT');
+    if (!columns.includes('archived_at')) db.exec('ALTER TABLE sticky_notes ADD COLUMN archived_at INTEGER');
+    if (!columns.includes('ink_data')) db.exec('ALTER TABLE sticky_notes ADD COLUMN ink_data TEXT');
+    if (!columns.includes('recognized_text')) db.exec('ALTER TABLE sticky_notes ADD COLUMN recognized_text TEXT');
+    db.exec('CREATE INDEX IF NOT EXISTS idx_sticky_notes_board ON sticky_notes (board_id, archived_at)');
+    db.exec(`
+        CREATE TABLE IF NOT EXISTS note_versions (
+            note_id TEXT,
+            version INTEGER,
+            at INTEGER,
+            op TEXT,
+            text TEXT,
+            fields TEXT,
+            delta INTEGER DEFAULT 0,
+            PRIMARY KEY (note_id, version)
+        );
+    `);
+};
+
+const mapNoteRow = (row) => (row ? {
+    id: row.id,
+    text: row.text,
+    color: row.color,
+    tags: row.tags ? JSON.parse(row.tags) : [],
+    layout: row.layout ? JSON.parse(row.layout) : {},
+    symbol: row.symbol,
+    boardId: row.board_id || null,
+    archivedAt: row.archived_at || null,
+    inkData: row.ink_data ? JSON.parse(row.ink_data) : null,
+    recognizedText: row.recognized_text || null,
+    createdAt: row.created_at,
+    updatedAt: row.updated_at
+} : null);
+
+const checkColor = (color) => {
+    if (typeof color !== 'string' || !COLOR_RE.test(color)) throw new Error(`Invalid note color: ${color}`);
+    return color;
+};
+
+const checkTags = (tags) => {
+    if (!Array.isArray(tags)) throw new Error('Tags must be a list');
+    const clean = Array.from(new Set(tags.map(tag => String(tag).trim()).filter(Boolean)));
+    if (clean.length > MAX_TAGS) throw new Error(`A note can have at most ${MAX_TAGS} tags`);
+    return clean;
+};
+
+// [{ points: [{ x, y }], color?, width? }] -> cleaned strokes; null or [] clears the ink
+const checkInk = (ink) => {
+    if (ink == null) return null;
+    if (!Array.isArray(ink)) throw new Error('Note ink must be a list of strokes');
+    let total = 0;
+    const strokes = ink.map((stroke) => {
+        if (!stroke || !Array.isArray(stroke.points)) throw new Error('Each ink stroke needs a list of points');
+        total += stroke.points.length;
+        if (total > MAX_INK_POINTS) throw new Error(`Note ink has more than ${MAX_INK_POINTS} points`);
+        const points = stroke.points.map((p) => {
+            const x = Number(p && p.x);
+            const y = Number(p && p.y);
+            if (!Number.isFinite(x) || !Number.isFinite(y)) throw new Error('Ink points need numeric x and y');
+            return { x, y };
+        });
+        const out = { points };
+        if (stroke.color !== undefined) out.color = checkColor(stroke.color);
+        if (stroke.width !== undefined) {
+            out.width = Number(stroke.width);
+            if (!(out.width > 0)) throw new Error('Ink stroke width must be positive');
+        }
+        return out;
+    }).filter(stroke => stroke.points.length > 0);
+    return strokes.length ? strokes : null;
+};
+
+const mapBoardRow = (row) => (row ? {
+    id: row.id,
+    name: row.name,
+    archived: !!row.archived,
+    noteCount: row.note_count || 0,
+    createdAt: row.created_at,
+    updatedAt: row.updated_at
+} : null);
+
+const getBoard = (db, id) => mapBoardRow(db.prepare(`SELECT b.*, (SELECT COUNT(*) FROM sticky_notes n WHERE n.board_id = b.id AND n.archived_at IS NULL) AS note_count
+    FROM note_boards b WHERE b.id = ?`).get(id));
+
+// options: { includeArchived? }
+const listBoards = (db, { includeArchived = false } = {}) => db.prepare(`SELECT b.*, COUNT(n.id) AS note_count
+    FROM note_boards b LEFT JOIN sticky_notes n ON n.board_id = b.id AND n.archived_at IS NULL
+    ${includeArchived ? '' : 'WHERE b.archived = 0'} GROUP BY b.id ORDER BY b.name COLLATE NOCASE`).all().map(mapBoardRow);
+
+const cleanBoardName = (db, name, exceptId = null) => {
+    const value = String(name || '').trim();
+    if (!value) throw new Error('Board name is required');
+    if (value.length > MAX_BOARD_NAME) throw new Error('Board name is too long');
+    const clash = db.prepare('SELECT id FROM note_boards WHERE name = ? COLLATE NOCASE AND id IS NOT ?').get(value, exceptId);
+    if (clash) throw new Error(`A board named "${value}" already exists`);
+    return value;
+};
+
+const createBoard = (db, name) => {
+    const id = crypto.randomUUID();
+    const now = Date.now();
+    db.prepare('INSERT INTO note_boards (id, name, archived, created_at, updated_at) VALUES (?, ?, 0, ?, ?)').run(id, cleanBoardName(db, name), now, now);
+    return getBoard(db, id);
+};
+
+const requireBoard = (db, id) => {
+    const board = getBoard(db, id);
+    if (!board) throw new Error(`Board not found: ${id}`);
+    return board;
+};
+
+const renameBoard = (db, id, name) => {
+    requireBoard(db, id);
+    db.prepare('UPDATE note_boards SET name = ?, updated_at = ? WHERE id = ?').run(cleanBoardName(db, name, id), Date.now(), id);
+    return getBoard(db, id);
+};
+
+const setBoardArchived = (db, id, archived = true) => {
+    requireBoard(db, id);
+    db.prepare('UPDATE note_boards SET archived = ?, updated_at = ? WHERE id = ?').run(archived ? 1 : 0, Date.now(), id);
+    return getBoard(db, id);
+};
+
+// { text?, color?, tags?, layout?, symbol?, boardId?, inkData?, recognizedText? } -> validated subset
+const checkFields = (fields = {}, db = null) => {
+    const out = {};
+    if (fields.text !== undefined) {
+        out.text = String(fields.text == null ? '' : fields.text);
+        if (out.text.length > MAX_TEXT_LENGTH) throw new Error(`Note text is longer than ${MAX_TEXT_LENGTH} characters`);
+    }
+    if (fields.color !== undefined) out.color = checkColor(fields.color);
+    if (fields.tags !== undefined) out.tags = checkTags(fields.tags);
+    if (fields.layout !== undefined) {
+        if (!fields.layout || typeof fields.layout !== 'object' || Array.isArray(fields.layout)) throw new Error('Note layout must be an object');
+        out.layout = fields.layout;
+    }
+    if (fields.symbol !== undefined) out.symbol = fields.symbol ? String(fields.symbol) : null;
+    if (fields.boardId !== undefined) out.boardId = fields.boardId ? requireBoard(db, String(fields.boardId)).id : null;
+    if (fields.inkData !== undefined) {
+        out.inkData = checkInk(fields.inkData);
+        if (fields.recognizedText === undefined) out.recognizedText = null;
+    }
+    if (fields.recognizedText !== undefined) {
+        out.recognizedText = fields.recognizedText ? String(fields.recognizedText) : null;
+        if (out.recognizedText && out.recognizedText.length > MAX_TEXT_LENGTH) throw new Error(`Recognized text is longer than ${MAX_TEXT_LENGTH} characters`);
+    }
+    return out;
+};
+
+// Active notes of one board; null is the default board
+const listStickyNotes = (db, boardId = null) => {
+    if (boardId) requireBoard(db, boardId);
+    return db.prepare('SELECT * FROM sticky_notes WHERE board_id IS ? AND archived_at IS NULL ORDER BY created_at, id').all(boardId || null).map(mapNoteRow);
+};
+
+/**
+ * Every whitespace-separated term must appear in the text, the recognized ink text or a tag.
+ * options: { includeArchived = true, archivedOnly?, boardId?, limit? }. Newest first.
+ */
+const searchStickyNotes = (db, query, { includeArchived = true, archivedOnly = false, boardId, limit = DEFAULT_SEARCH_LIMIT } = {}) => {
+    const terms = String(query || '').trim().toLowerCase().split(/\s+/).filter(Boolean);
+    const where = terms.map(() => "(lower(text) LIKE ? ESCAPE '\\' OR lower(recognized_text) LIKE ? ESCAPE '\\' OR lower(tags) LIKE ? ESCAPE '\\')");
+    const params = [];
+    terms.forEach((term) => {
+        const like = `%${term.replace(/[\\%_]/g, m => `\\${m}`)}%`;
+        params.push(like, like, like);
+    });
+    if (archivedOnly) where.push('archived_at IS NOT NULL');
+    else if (!includeArchived) where.push('archived_at IS NULL');
+    if (boardId !== undefined) { where.push('board_id IS ?'); params.push(boardId || null); }
+    const sql = `SELECT * FROM sticky_notes${where.length ? ` WHERE ${where.join(' AND ')}` : ''} ORDER BY updated_at DESC LIMIT ?`;
+    return db.prepare(sql).all(...params, Math.min(1000, Number(limit) || DEFAULT_SEARCH_LIMIT)).map(mapNoteRow);
+};
+
+const getStickyNote = (db, id) => mapNoteRow(db.prepare('SELECT * FROM sticky_notes WHERE id = ?').get(id));
+
+// --- Version history ---
+
+const sameContent = (a, b) => a.text === b.text && VERSIONED_FIELDS.every(f => JSON.stringify(a[f]) === JSON.stringify(b[f]));
+
+// The span of `text` that differs from `base`: { p: common prefix, s: common suffix, m: middle }
+const textDelta = (base, text) => {
+    let p = 0;
+    while (p < base.length && p < text.length && base[p] === text[p]) p++;
+    let sfx = 0;
+    while (sfx < base.length - p && sfx < text.length - p && base[base.length - 1 - sfx] === text[text.length - 1 - sfx]) sfx++;
+    return { p, s: sfx, m: text.slice(p, text.length - sfx) };
+};
+
+const applyTextDelta = (base, { p, s, m }) => base.slice(0, p) + m + base.slice(base.length - s);
+
+const versionSnapshot = note => Object.fromEntries(VERSIONED_FIELDS.map(f => [f, note[f] === undefined ? null : note[f]]));
+
+// Saves `before` as the note's newest version, turning the previous newest into a diff against it
+const recordNoteVersion = (db, before, op, at) => {
+    const latest = db.prepare('SELECT * FROM note_versions WHERE note_id = ? ORDER BY version DESC LIMIT 1').get(before.id);
+    const text = before.text || '';
+    const fields = versionSnapshot(before);
+    if (latest && !latest.delta) {
+        const older = JSON.parse(latest.fields);
+        const changed = Object.fromEntries(VERSIONED_FIELDS.filter(f => JSON.stringify(older[f]) !== JSON.stringify(fields[f])).map(f => [f, older[f]]));
+        db.prepare('UPDATE note_versions SET text = ?, fields = ?, delta = 1 WHERE note_id = ? AND version = ?')
+            .run(JSON.stringify(textDelta(text, latest.text || '')), JSON.stringify(changed), before.id, latest.version);
+    }
+    const version = latest ? latest.version + 1 : 1;
+    db.prepare('INSERT INTO note_versions (note_id, version, at, op, text, fields, delta) VALUES (?, ?, ?, ?, ?, ?, 0)')
+        .run(before.id, version, at, op, text, JSON.stringify(fields));
+    db.prepare('DELETE FROM note_versions WHERE note_id = ? AND version <= ?').run(before.id, version - MAX_VERSIONS_PER_NOTE);
+};
+
+/**
+ * Prior contents of a note, newest first: [{ version, at, op ('update' |
+ * 'delete': what replaced it), text, color, tags, symbol, boardId, inkData }].
+ * Deleted notes keep their history.
+ */
+const getNoteHistory = (db, id) => {
+    const rows = db.prepare('SELECT * FROM note_versions WHERE note_id = ? ORDER BY version DESC').all(id);
+    const versions = [];
+    let newer = null;
+    rows.forEach((row) => {
+        const state = row.delta && newer
+            ? { ...newer, ...JSON.parse(row.fields), text: applyTextDelta(newer.text, JSON.parse(row.text)) }
+            : { ...JSON.parse(row.fields), text: row.text || '' };
+        versions.push({ version: row.version, at: row.at, op: row.op, ...state });
+        newer = state;
+    });
+    return versions;
+};
+
+/**
+ * Puts a saved version's content back (recreating the note if it was
+ * deleted) through bulkUpdateNotes, so the content it replaces becomes a
+ * version in turn. Returns the bulkUpdateNotes result.
+ */
+const restoreNoteVersion = (db, id, version) => {
+    const saved = getNoteHistory(db, id).find(v => v.version === Number(version));
+    if (!saved) throw new Error(`Note ${id} has no version ${version}`);
+    const fields = { text: saved.text, ...versionSnapshot(saved) };
+    if (fields.boardId && !getBoard(db, fields.boardId)) fields.boardId = null;
+    return getStickyNote(db, id)
+        ? bulkUpdateNotes(db, [{ op: 'update', id, changes: fields }])
+        : bulkUpdateNotes(db, [{ op: 'create', note: { ...fields, id } }]);
+};
+
+/**
+ * ops: [{ op: 'create', note }, { op: 'update', id, changes }, { op: 'delete', id },
+ * { op: 'recolor', ids, color }, { op: 'retag', ids, set? | add? / remove? },
+ * { op: 'archive', ids }, { op: 'unarchive', ids }].
+ * Returns { applied, changes: [{ op: 'add' | 'update' | 'delete', id, item }] }
+ * with one entry per note touched (its final state).
+ */
+const bulkUpdateNotes = (db, ops) => {
+    if (!Array.isArray(ops) || ops.length === 0) throw new Error('No note operations given');
+    if (ops.length > MAX_OPS_PER_BATCH) throw new Error(`At most ${MAX_OPS_PER_BATCH} operations per batch`);
+    const upsert = db.prepare(`INSERT OR REPLACE INTO sticky_notes (id, text, color, tags, layout, symbol, board_id, archived_at, ink_data, recognized_text, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)`);
+    const remove = db.prepare('DELETE FROM sticky_notes WHERE id = ?');
+    const now = Date.now();
+    // id -> { before (stored state or null), after (null once deleted) }
+    const touched = new Map();
+    const current = (id) => {
+        if (!touched.has(id)) {
+            const stored = getStickyNote(db, id);
+            touched.set(id, { before: stored, after: stored });
+        }
+        return touched.get(id).after;
+    };
+    const existing = (id) => {
+        const note = current(id);
+        if (!note) throw new Error(`Note not found: ${id}`);
+        return note;
+    };
+    // The stored content is saved once per batch, on the first op that changes it
+    const versioned = new Set();
+    const keepVersion = (id, next) => {
+        const { before } = touched.get(id);
+        if (!before || versioned.has(id) || (next && sameContent(before, next))) return;
+        versioned.add(id);
+        recordNoteVersion(db, before, next ? 'update' : 'delete', now);
+    };
+    const write = (note) => {
+        keepVersion(note.id, note);
+        touched.get(note.id).after = note;
+        upsert.run(note.id, note.text, note.color, JSON.stringify(note.tags), JSON.stringify(note.layout), note.symbol, note.boardId, note.archivedAt,
+            note.inkData ? JSON.stringify(note.inkData) : null, note.recognizedText, note.createdAt, note.updatedAt);
+    };
+    const idList = (op) => {
+        if (!Array.isArray(op.ids) || op.ids.length === 0) throw new Error(`${op.op} needs a list of note ids`);
+        return Array.from(new Set(op.ids.map(String)));
+    };
+
+    db.transaction(() => ops.forEach((op, index) => {
+        try {
+            switch (op && op.op) {
+                case 'create': {
+                    const fields = checkFields(op.note, db);
+                    const id = op.note && op.note.id ? String(op.note.id) : crypto.randomUUID();
+                    if (current(id)) throw new Error(`Note already exists: ${id}`);
+                    write({ id, text: '', color: DEFAULT_COLOR, tags: [], layout: {}, symbol: null, boardId: null, archivedAt: null, inkData: null, recognizedText: null, ...fields, createdAt: now, updatedAt: now });
+                    break;
+                }
+                case 'update': {
+                    const note = existing(op.id);
+                    write({ ...note, ...checkFields(op.changes, db), updatedAt: now });
+                    break;
+                }
+                case 'delete':
+                    existing(op.id);
+                    keepVersion(op.id, null);
+                    remove.run(op.id);
+                    touched.get(op.id).after = null;
+                    break;
+                case 'recolor': {
+                    const color = checkColor(op.color);
+                    idList(op).forEach(id => write({ ...existing(id), color, updatedAt: now }));
+                    break;
+                }
+                case 'retag': {
+                    const ids = idList(op);
+                    const set = op.set !== undefined ? checkTags(op.set) : null;
+                    const add = checkTags(op.add || []);
+                    const drop = new Set(checkTags(op.remove || []));
+                    if (!set && !add.length && !drop.size) throw new Error('retag needs set, add or remove');
+                    ids.forEach((id) => {
+                        const note = existing(id);
+                        const tags = checkTags([...(set || note.tags), ...add].filter(tag => !drop.has(tag)));
+                        write({ ...note, tags, updatedAt: now });
+                    });
+                    break;
+                }
+                case 'archive':
+                case 'unarchive':
+                    idList(op).forEach((id) => {
+                        const note = existing(id);
+                        if (!!note.archivedAt === (op.op === 'archive')) return;
+                        write({ ...note, archivedAt: op.op === 'archive' ? now : null, updatedAt: now });
+                    });
+                    break;
+                default:
+                    throw new Error(`Unknown note operation: ${op && op.op}`);
+            }
+        } catch (err) {
+            err.message = `Operation ${index + 1} (${(op && op.op) || '?'}): ${err.message}`;
+            throw err;
+        }
+    }))();
+
+    const changes = [];
+    touched.forEach(({ before, after }, id) => {
+        if (!before && !after) return; // created and deleted in the same batch
+        if (!after) changes.push({ op: 'delete', id, item: null });
+        else if (!before) changes.push({ op: 'add', id, item: after });
+        else if (after !== before) changes.push({ op: 'update', id, item: after });
+    });
+    return { applied: ops.length, changes };
+};
+
+/**
+ * Archives active notes matching the rules: minimized (layout.minimized) and
+ * untouched for minimizedDays, or untouched for untouchedDays whatever their state.
+ * Returns the bulkUpdateNotes result (no changes when nothing matched).
+ */
+const autoArchiveNotes = (db, rules = {}, now = Date.now()) => {
+    const { enabled, minimizedDays, untouchedDays } = { ...AUTO_ARCHIVE_DEFAULTS, ...rules };
+    if (!enabled) return { applied: 0, changes: [] };
+    const ids = db.prepare('SELECT id, layout, updated_at FROM sticky_notes WHERE archived_at IS NULL').all().filter((row) => {
+        const idle = now - row.updated_at;
+        if (untouchedDays > 0 && idle > untouchedDays * DAY_MS) return true;
+        if (!(minimizedDays > 0) || idle <= minimizedDays * DAY_MS) return false;
+        try { return !!JSON.parse(row.layout || '{}').minimized; } catch (e) { return false; }
+    }).map(row => row.id);
+    return ids.length ? bulkUpdateNotes(db, [{ op: 'archive', ids }]) : { applied: 0, changes: [] };
+};
+
+module.exports = { AUTO_ARCHIVE_DEFAULTS, initializeStickyNotesTable, listStickyNotes, searchStickyNotes, getStickyNote, bulkUpdateNotes, autoArchiveNotes, getNoteHistory, restoreNoteVersion, listBoards, createBoard, renameBoard, setBoardArchived };
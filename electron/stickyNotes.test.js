@@ -0,0 +1,67 @@
+
+const test = require('node:test');
+const assert = require('node:assert');
+const Database = require('better-sqlite3');
+const { initializeStickyNotesTable, bulkUpdateNotes, getStickyNote, getNoteHistory, restoreNoteVersion } = require('./stickyNotes');
+
+const openDb = () => {
+    const db = new Database(':memory:');
+    initializeStickyNotesTable(db);
+    return db;
+};
+
+const level = i => `Support at 1.08${i} held twice.\nWatch the London open for a retest of ${i}.`;
+
+test('each content change saves the previous content, newest first', () => {
+    const db = openDb();
+    bulkUpdateNotes(db, [{ op: 'create', note: { id: 'n1', text: level(0), tags: ['eurusd'] } }]);
+    bulkUpdateNotes(db, [{ op: 'update', id: 'n1', changes: { text: level(1) } }]);
+    bulkUpdateNotes(db, [{ op: 'update', id: 'n1', changes: { tags: ['eurusd', 'levels'] } }]);
+    bulkUpdateNotes(db, [{ op: 'update', id: 'n1', changes: { layout: { x: 40, y: 80 } } }]); // layout isn't content
+
+    const history = getNoteHistory(db, 'n1');
+    assert.deepStrictEqual(history.map(v => [v.op, v.text, v.tags]), [
+        ['update', level(1), ['eurusd']],
+        ['update', level(0), ['eurusd']]
+    ]);
+    assert.ok(history[0].version > history[1].version);
+});
+
+test('history is trimmed to the newest 50 versions and older diffs still resolve', () => {
+    const db = openDb();
+    bulkUpdateNotes(db, [{ op: 'create', note: { id: 'n1', text: level(0) } }]);
+    for (let i = 1; i <= 55; i++) bulkUpdateNotes(db, [{ op: 'update', id: 'n1', changes: { text: level(i) } }]);
+
+    const history = getNoteHistory(db, 'n1');
+    assert.strictEqual(history.length, 50);
+    assert.strictEqual(history[0].text, level(54));
+    assert.strictEqual(history[49].text, level(5));
+    history.forEach((v, k) => assert.strictEqual(v.text, level(54 - k)));
+});
+
+test('restoring a version brings its content back and keeps the replaced content', () => {
+    const db = openDb();
+    bulkUpdateNotes(db, [{ op: 'create', note: { id: 'n1', text: level(0), color: '#3B82F6' } }]);
+    bulkUpdateNotes(db, [{ op: 'update', id: 'n1', changes: { text: level(1), color: '#EF4444' } }]);
+    const oldest = getNoteHistory(db, 'n1')[0];
+
+    restoreNoteVersion(db, 'n1', oldest.version);
+    const note = getStickyNote(db, 'n1');
+    assert.strictEqual(note.text, level(0));
+    assert.strictEqual(note.color, '#3B82F6');
+    assert.deepStrictEqual(getNoteHistory(db, 'n1').map(v => v.text), [level(1), level(0)]);
+});
+
+test('a deleted note keeps its history and can be restored', () => {
+    const db = openDb();
+    bulkUpdateNotes(db, [{ op: 'create', note: { id: 'n1', text: level(3), tags: ['gold'] } }]);
+    bulkUpdateNotes(db, [{ op: 'delete', id: 'n1' }]);
+    assert.strictEqual(getStickyNote(db, 'n1'), null);
+
+    const [deleted] = getNoteHistory(db, 'n1');
+    assert.strictEqual(deleted.op, 'delete');
+    restoreNoteVersion(db, 'n1', deleted.version);
+    assert.strictEqual(getStickyNote(db, 'n1').text, level(3));
+    assert.deepStrictEqual(getStickyNote(db, 'n1').tags, ['gold']);
+    assert.throws(() => restoreNoteVersion(db, 'n1', 999), /has no version 999/);
+});
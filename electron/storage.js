@@ -0,0 +1,229 @@
+
+const fs = require('fs');
+const path = require('path');
+const { t } = require('./i18n');
+
+// --- STORAGE REPORT & CLEANUP ---
+// Breaks disk usage down by what the user would recognise (bar store, chart
+// drawings, journal, caches...) and offers targeted purges. Only data that can
+// be rebuilt or is unreachable is purgeable: CSV ingests re-import from the
+// source file on next open, FRED series re-download, and news re-polls.
+
+// SQLite table -> report category
+const TABLE_CATEGORIES = {
+    market_data: 'bar_store',
+    trade_prints: 'bar_store',
+    bar_edits: 'bar_store',
+    drawings: 'drawings',
+    drawings_trash: 'drawings',
+    chart_thumbnails: 'drawings',
+    drawing_templates: 'drawings',
+    chart_journal: 'drawings',
+    chart_annotations: 'drawings',
+    dataset_annotations: 'drawings',
+    trades: 'journal',
+    news_items: 'news_cache',
+    datasets: 'metadata',
+    settings: 'metadata',
+    secrets: 'metadata',
+    alerts: 'metadata',
+    file_time_index: 'metadata',
+    themes: 'metadata',
+    watch_imports: 'metadata',
+    scheduled_jobs: 'metadata',
+    job_runs: 'metadata',
+    download_jobs: 'metadata',
+    sim_accounts: 'journal',
+    sim_orders: 'journal',
+    sim_positions: 'journal',
+    sim_fills: 'journal',
+    activity_log: 'journal',
+    symbol_meta: 'metadata',
+    switch_usage: 'metadata',
+    plugin_storage: 'metadata',
+    symbol_dossiers: 'drawings',
+    fx_rates: 'metadata',
+    scanner_screens: 'metadata',
+    econ_events: 'metadata',
+    sticky_notes: 'drawings',
+    note_versions: 'drawings',
+    note_boards: 'drawings',
+    note_templates: 'drawings',
+    export_templates: 'metadata',
+    attachments: 'attachments',
+    usage_counters: 'metadata',
+    integrity_quarantine: 'metadata',
+    signal_inbox: 'journal'
+};
+
+const PURGE_TARGETS = ['news', 'fred', 'stale_csv', 'csv_cache'];
+
+const dirSize = (dir) => {
+    let total = 0;
+    let files = 0;
+    if (!fs.existsSync(dir)) return { bytes: 0, files: 0 };
+    const walk = (d) => {
+        fs.readdirSync(d, { withFileTypes: true }).forEach((entry) => {
+            const full = path.join(d, entry.name);
+            if (entry.isDirectory()) walk(full);
+            else { total += fs.statSync(full).size; files++; }
+        });
+    };
+    walk(dir);
+    return { bytes: total, files };
+};
+
+const fileSize = (file) => { try { return fs.statSync(file).size; } catch (e) { return 0; } };
+
+// Exact page usage via the dbstat virtual table, falling back to payload length sums
+const tableSizes = (db) => {
+    const owner = Object.fromEntries(db.prepare("SELECT name, tbl_name FROM sqlite_master WHERE type IN ('table', 'index')").all().map(r => [r.name, r.tbl_name]));
+    const sizes = {};
+    try {
+        db.prepare('SELECT name, SUM(pgsize) as bytes FROM dbstat GROUP BY name').all().forEach((r) => {
+            const table = owner[r.name] || r.name;
+            sizes[table] = (sizes[table] || 0) + r.bytes;
+        });
+        return { sizes, exact: true };
+    } catch (e) {
+        Object.keys(TABLE_CATEGORIES).forEach((table) => {
+            try {
+                const cols = db.prepare(`PRAGMA table_info(${table})`).all().map(c => `COALESCE(length(${c.name}), 8)`);
+                if (cols.length) sizes[table] = db.prepare(`SELECT COALESCE(SUM(${cols.join(' + ')}), 0) as bytes FROM ${table}`).get().bytes;
+            } catch (err) {}
+        });
+        return { sizes, exact: false };
+    }
+};
+
+const getStorageReport = ({ db, dbPath, soundsDir, computeCacheDir = null }) => {
+    const categories = {};
+    const add = (id, bytes, extra = {}) => {
+        const c = categories[id] || { id, label: t(`storage.category.${id}`), bytes: 0 };
+        c.bytes += bytes;
+        categories[id] = { ...c, ...extra };
+    };
+
+    const { sizes, exact } = tableSizes(db);
+    Object.entries(sizes).forEach(([table, bytes]) => add(TABLE_CATEGORIES[table] || 'other', bytes));
+
+    const rowCount = (sql, ...params) => db.prepare(sql).get(...params).n;
+    add('bar_store', 0, {
+        rows: rowCount('SELECT COUNT(*) as n FROM market_data'),
+        bySource: db.prepare('SELECT source, COUNT(*) as datasets, SUM(row_count) as rows FROM datasets GROUP BY source').all()
+    });
+    add('drawings', 0, { items: rowCount('SELECT COUNT(*) as n FROM drawings') });
+    add('journal', 0, { items: rowCount('SELECT COUNT(*) as n FROM trades') });
+    add('news_cache', 0, { items: rowCount('SELECT COUNT(*) as n FROM news_items') });
+
+    const pageSize = db.pragma('page_size', { simple: true });
+    add('free_pages', db.pragma('freelist_count', { simple: true }) * pageSize);
+    add('wal', fileSize(`${dbPath}-wal`));
+
+    const sounds = dirSize(soundsDir);
+    add('sounds', sounds.bytes, { items: sounds.files });
+
+    if (computeCacheDir) {
+        const computeCache = dirSize(computeCacheDir);
+        add('compute_cache', computeCache.bytes, { items: computeCache.files });
+    }
+
+    const list = Object.values(categories).filter(c => c.bytes > 0 || c.items || c.rows).sort((a, b) => b.bytes - a.bytes);
+    return {
+        generatedAt: Date.now(),
+        databaseFileBytes: fileSize(dbPath),
+        totalBytes: list.reduce((n, c) => n + c.bytes, 0),
+        exact,
+        categories: list
+    };
+};
+
+// CSV-ingested datasets, optionally only those whose source file is gone
+const csvDatasets = (db, onlyMissing) => db.prepare("SELECT id, symbol, timeframe, meta FROM datasets WHERE source = 'csv'").all()
+    .filter((d) => {
+        if (!onlyMissing) return true;
+        const filePath = d.meta ? JSON.parse(d.meta).filePath : null;
+        return !filePath || !fs.existsSync(filePath);
+    });
+
+/**
+ * Purges rebuildable data. `targets` is a subset of PURGE_TARGETS.
+ * Returns { [target]: rowsDeleted }.
+ */
+const purgeCaches = (db, targets = []) => {
+    const result = {};
+    const dropDatasets = (rows) => {
+        let deleted = 0;
+        const tx = db.transaction(() => {
+            rows.forEach((d) => {
+                deleted += db.prepare('DELETE FROM market_data WHERE symbol = ? AND timeframe = ?').run(d.symbol, d.timeframe).changes;
+                db.prepare('DELETE FROM datasets WHERE id = ?').run(d.id);
+            });
+        });
+        tx();
+        return deleted;
+    };
+
+    targets.forEach((target) => {
+        if (!PURGE_TARGETS.includes(target)) throw new Error(`Unknown purge target: ${target}`);
+    });
+    if (targets.includes('news')) result.news = db.prepare('DELETE FROM news_items').run().changes;
+    if (targets.includes('fred')) result.fred = dropDatasets(db.prepare("SELECT id, symbol, timeframe FROM datasets WHERE source = 'fred'").all());
+    if (targets.includes('csv_cache')) result.csv_cache = dropDatasets(csvDatasets(db, false));
+    else if (targets.includes('stale_csv')) result.stale_csv = dropDatasets(csvDatasets(db, true));
+    return result;
+};
+
+/**
+ * Deletes custom alert sounds that no alert references. Built-ins are regenerated on demand.
+ */
+const deleteUnusedSounds = (db, soundsDir) => {
+    if (!fs.existsSync(soundsDir)) return [];
+    const referenced = new Set(db.prepare('SELECT options FROM alerts').all()
+        .map(r => (r.options ? JSON.parse(r.options).sound : null))
+        .filter(Boolean));
+    const removed = [];
+    fs.readdirSync(soundsDir).filter(f => f.toLowerCase().endsWith('.wav')).forEach((file) => {
+        const id = path.basename(file, '.wav');
+        if (referenced.has(id)) return;
+        fs.unlinkSync(path.join(soundsDir, file));
+        removed.push(id);
+    });
+    return removed;
+};
+
+const databaseFootprint = (dbPath) => fileSize(dbPath) + fileSize(`${dbPath}-wal`) + fileSize(`${dbPath}-shm`);
+
+/**
+ * Checkpoints and truncates the WAL, VACUUMs free pages away and refreshes
+ * planner stats. Synchronous and blocks the main process for the duration,
+ * which is why the automatic path only runs while the user is idle.
+ */
+const compactDatabase = (db, dbPath) => {
+    const start = Date.now();
+    const before = databaseFootprint(dbPath);
+    const freePagesBefore = db.pragma('freelist_count', { simple: true });
+
+    db.pragma('wal_checkpoint(TRUNCATE)');
+    db.exec('VACUUM');
+    db.pragma('wal_checkpoint(TRUNCATE)');
+    db.pragma('optimize');
+
+    const after = databaseFootprint(dbPath);
+    return {
+        beforeBytes: before,
+        afterBytes: after,
+        reclaimedBytes: Math.max(0, before - after),
+        freePagesBefore,
+        durationMs: Date.now() - start,
+        compactedAt: Date.now()
+    };
+};
+
+// Fraction of the main file that VACUUM would give back
+const freePageRatio = (db) => {
+    const pages = db.pragma('page_count', { simple: true });
+    return pages ? db.pragma('freelist_count', { simple: true }) / pages : 0;
+};
+
+module.exports = { PURGE_TARGETS, getStorageReport, purgeCaches, deleteUnusedSounds, dirSize, compactDatabase, freePageRatio };
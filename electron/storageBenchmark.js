@@ -0,0 +1,141 @@
+
+const fs = require('fs');
+const os = require('os');
+const path = require('path');
+const { isMainThread, parentPort, workerData } = require('worker_threads');
+const Database = require('better-sqlite3');
+
+// --- STORAGE BENCHMARK ---
+// Measures save/load latency of the three persistence paths on the user's own
+// disk: JSON files (fsync'd), SQLite in WAL mode (same settings as redpill.db)
+// and raw Float64 binary. Everything happens in a scratch directory with a
+// scratch database, never the live one. Runs inside a worker (see bottom)
+// because a full run takes seconds.
+
+const DEFAULTS = { iterations: 20, drawings: 500, bars: 50000 };
+
+// Chart state shaped like what the renderer saves, plus a bar series for the binary path
+const buildPayload = ({ drawings, bars }) => {
+    const now = Date.now();
+    const state = {
+        drawings: Array.from({ length: drawings }, (_, i) => ({
+            id: `d${i}`,
+            type: i % 3 === 0 ? 'trendline' : i % 3 === 1 ? 'rectangle' : 'brush',
+            points: Array.from({ length: i % 3 === 2 ? 40 : 2 }, (_, p) => ({ time: now - (i * 60 + p) * 60000, price: 100 + Math.sin(i + p) * 5 })),
+            properties: { color: '#3b82f6', lineWidth: 2, text: i % 10 === 0 ? `Note ${i}` : undefined }
+        })),
+        folders: [],
+        config: { timeframe: '1h', chartType: 'candlestick' },
+        timestamp: now
+    };
+    const series = new Float64Array(bars * 6);
+    for (let i = 0; i < bars; i++) {
+        const o = 100 + Math.sin(i / 50) * 10;
+        series.set([now - (bars - i) * 60000, o, o + 1, o - 1, o + 0.5, 1000], i * 6);
+    }
+    return { state, series };
+};
+
+const stats = (samples, bytes) => {
+    const sorted = samples.slice().sort((a, b) => a - b);
+    const mean = samples.reduce((n, v) => n + v, 0) / samples.length;
+    const pick = (p) => sorted[Math.min(sorted.length - 1, Math.floor(sorted.length * p))];
+    const round = (n) => Math.round(n * 1000) / 1000;
+    return { meanMs: round(mean), p50Ms: round(pick(0.5)), p95Ms: round(pick(0.95)), maxMs: round(sorted[sorted.length - 1]), throughputMBps: round(bytes / 1024 / 1024 / (mean / 1000)) };
+};
+
+const time = (fn) => {
+    const start = process.hrtime.bigint();
+    fn();
+    return Number(process.hrtime.bigint() - start) / 1e6;
+};
+
+const writeDurable = (file, data) => {
+    const fd = fs.openSync(file, 'w');
+    try {
+        fs.writeSync(fd, data);
+        fs.fsyncSync(fd);
+    } finally {
+        fs.closeSync(fd);
+    }
+};
+
+const benchJson = (dir, state, iterations) => {
+    const file = path.join(dir, 'state.json');
+    const save = [], load = [];
+    let bytes = 0;
+    for (let i = 0; i < iterations; i++) {
+        save.push(time(() => { const json = JSON.stringify(state); bytes = Buffer.byteLength(json); writeDurable(file, json); }));
+        load.push(time(() => JSON.parse(fs.readFileSync(file, 'utf8'))));
+    }
+    return { id: 'json', label: 'JSON file', bytes, save: stats(save, bytes), load: stats(load, bytes) };
+};
+
+const benchSqlite = (dir, state, iterations) => {
+    const db = new Database(path.join(dir, 'bench.db'));
+    try {
+        db.pragma('journal_mode = WAL');
+        db.pragma('synchronous = NORMAL');
+        db.exec('CREATE TABLE drawings (symbol TEXT PRIMARY KEY, data TEXT)');
+        const put = db.prepare('INSERT OR REPLACE INTO drawings (symbol, data) VALUES (?, ?)');
+        const get = db.prepare('SELECT data FROM drawings WHERE symbol = ?');
+        const save = [], load = [];
+        let bytes = 0;
+        for (let i = 0; i < iterations; i++) {
+            save.push(time(() => { const json = JSON.stringify(state); bytes = Buffer.byteLength(json); put.run('BENCH', json); }));
+            load.push(time(() => JSON.parse(get.get('BENCH').data)));
+        }
+        return { id: 'sqlite', label: 'SQLite (WAL)', bytes, save: stats(save, bytes), load: stats(load, bytes) };
+    } finally {
+        db.close();
+    }
+};
+
+const benchBinary = (dir, series, iterations) => {
+    const file = path.join(dir, 'series.bin');
+    const buf = Buffer.from(series.buffer, series.byteOffset, series.byteLength);
+    const save = [], load = [];
+    for (let i = 0; i < iterations; i++) {
+        save.push(time(() => writeDurable(file, buf)));
+        load.push(time(() => { const b = fs.readFileSync(file); return new Float64Array(b.buffer, b.byteOffset, b.byteLength / 8); }));
+    }
+    return { id: 'binary', label: 'Binary (Float64)', bytes: buf.length, save: stats(save, buf.length), load: stats(load, buf.length) };
+};
+
+/**
+ * Returns { environment, options, results: [{ id, label, bytes, save, load }], fastestSave, fastestLoad }.
+ * `baseDir` should live on the same volume as userData so the numbers reflect the real disk.
+ */
+const runStorageBenchmark = (baseDir, options = {}) => {
+    const opts = { ...DEFAULTS, ...options };
+    const dir = fs.mkdtempSync(path.join(baseDir, 'bench-'));
+    try {
+        const { state, series } = buildPayload(opts);
+        const results = [
+            benchJson(dir, state, opts.iterations),
+            benchSqlite(dir, state, opts.iterations),
+            benchBinary(dir, series, opts.iterations)
+        ];
+        const fastest = (key) => results.slice().sort((a, b) => a[key].p50Ms - b[key].p50Ms)[0].id;
+        return {
+            generatedAt: Date.now(),
+            environment: { platform: process.platform, cpus: os.cpus().length, dir: baseDir },
+            options: opts,
+            results,
+            fastestSave: fastest('save'),
+            fastestLoad: fastest('load')
+        };
+    } finally {
+        fs.rmSync(dir, { recursive: true, force: true });
+    }
+};
+
+if (!isMainThread && workerData && workerData.storageBenchmark) {
+    try {
+        parentPort.postMessage({ success: true, report: runStorageBenchmark(workerData.storageBenchmark.baseDir, workerData.storageBenchmark.options) });
+    } catch (err) {
+        parentPort.postMessage({ success: false, error: err.message });
+    }
+}
+
+module.exports = { runStorageBenchmark };
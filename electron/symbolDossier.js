@@ -0,0 +1,119 @@
+
+const { AppError } = require('./appErrors');
+
+// --- SYMBOL DOSSIERS ---
+// One structured research file per symbol, next to symbol_meta in the symbol
+// store: the thesis (free text, may embed attachment refs like notes do), key
+// levels, links and attached files — images from the attachments table and
+// local files by path. Symbols are keyed like symbol_meta (trimmed, upper
+// case). A symbol without a dossier reads as an empty one; updates replace
+// each given field (arrays as a whole) and can carry the updatedAt they were
+// based on, so a stale editor gets CONFLICT instead of overwriting.
+
+const MAX_THESIS = 50000;
+const MAX_LEVELS = 100;
+const MAX_LINKS = 100;
+const MAX_FILES = 100;
+const LEVEL_KINDS = ['support', 'resistance', 'target', 'stop', 'entry', 'other'];
+const ATTACHMENT_ID = /^[0-9a-f-]{36}$/i;
+
+const initializeDossierTable = (db) => {
+    db.exec('CREATE TABLE IF NOT EXISTS symbol_dossiers (symbol TEXT PRIMARY KEY, data TEXT, created_at INTEGER, updated_at INTEGER);');
+};
+
+const cleanSymbol = (symbol) => {
+    const value = String(symbol || '').trim().toUpperCase();
+    if (!value) throw new AppError('INVALID_INPUT', 'symbol is required');
+    return value;
+};
+
+const emptyDossier = (symbol) => ({ symbol, thesis: '', keyLevels: [], links: [], attachments: [], files: [], tags: [], exists: false, createdAt: null, updatedAt: null });
+
+const cleanList = (value, max, name, mapItem) => {
+    if (!Array.isArray(value)) throw new AppError('INVALID_INPUT', `${name} must be an array`);
+    if (value.length > max) throw new AppError('INVALID_INPUT', `${name} is limited to ${max} entries`);
+    return value.map((item, i) => {
+        try {
+            return mapItem(item || {});
+        } catch (err) {
+            throw new AppError('INVALID_INPUT', `${name}[${i}]: ${err.message}`);
+        }
+    });
+};
+
+const FIELDS = {
+    thesis: (value) => {
+        const text = value == null ? '' : String(value);
+        if (text.length > MAX_THESIS) throw new AppError('INVALID_INPUT', `thesis is limited to ${MAX_THESIS} characters`);
+        return text;
+    },
+    keyLevels: value => cleanList(value, MAX_LEVELS, 'keyLevels', (l) => {
+        const price = Number(l.price);
+        if (!Number.isFinite(price)) throw new Error('price must be a number');
+        const kind = l.kind || 'other';
+        if (!LEVEL_KINDS.includes(kind)) throw new Error(`kind must be one of ${LEVEL_KINDS.join(', ')}`);
+        return { price, kind, label: l.label ? String(l.label).slice(0, 200) : null };
+    }).sort((a, b) => b.price - a.price),
+    links: value => cleanList(value, MAX_LINKS, 'links', (l) => {
+        let url;
+        try { url = new URL(String(l.url || '')); } catch (e) { throw new Error('url is not a valid URL'); }
+        if (!['http:', 'https:'].includes(url.protocol)) throw new Error('url must be http(s)');
+        return { url: url.toString(), title: l.title ? String(l.title).slice(0, 200) : null };
+    }),
+    // Images stored through attachments:paste-image
+    attachments: value => cleanList(value, MAX_FILES, 'attachments', (a) => {
+        if (!ATTACHMENT_ID.test(String(a.id || ''))) throw new Error('id must be an attachment id');
+        return { id: String(a.id).toLowerCase(), caption: a.caption ? String(a.caption).slice(0, 200) : null };
+    }),
+    // Local files (reports, spreadsheets) referenced by absolute path
+    files: value => cleanList(value, MAX_FILES, 'files', (f) => {
+        const filePath = String(f.path || '');
+        if (!filePath || !/^([a-zA-Z]:[\\/]|[\\/])/.test(filePath)) throw new Error('path must be absolute');
+        return { path: filePath, name: f.name ? String(f.name).slice(0, 200) : filePath.split(/[\\/]/).pop() };
+    }),
+    tags: value => cleanList(value, 50, 'tags', t => String(t).trim().slice(0, 50)).filter(Boolean)
+};
+
+const getDossier = (db, rawSymbol) => {
+    const symbol = cleanSymbol(rawSymbol);
+    const row = db.prepare('SELECT data, created_at, updated_at FROM symbol_dossiers WHERE symbol = ?').get(symbol);
+    if (!row) return emptyDossier(symbol);
+    return { ...emptyDossier(symbol), ...JSON.parse(row.data), symbol, exists: true, createdAt: row.created_at, updatedAt: row.updated_at };
+};
+
+/**
+ * patch: any of thesis, keyLevels, links, attachments, files, tags.
+ * options.expectedUpdatedAt: the updatedAt the edit started from (null for a new dossier).
+ */
+const updateDossier = (db, rawSymbol, patch = {}, { expectedUpdatedAt } = {}) => {
+    const current = getDossier(db, rawSymbol);
+    if (expectedUpdatedAt !== undefined && (expectedUpdatedAt ?? null) !== current.updatedAt) {
+        throw new AppError('CONFLICT', `The dossier for ${current.symbol} changed since it was opened`, { updatedAt: current.updatedAt });
+    }
+    const unknown = Object.keys(patch || {}).filter(k => !FIELDS[k]);
+    if (unknown.length) throw new AppError('INVALID_INPUT', `Unknown dossier field: ${unknown.join(', ')}`);
+    const next = {};
+    Object.keys(FIELDS).forEach((key) => { next[key] = key in patch ? FIELDS[key](patch[key]) : current[key]; });
+    const now = Math.max(Date.now(), (current.updatedAt || 0) + 1);
+    db.prepare('INSERT OR REPLACE INTO symbol_dossiers (symbol, data, created_at, updated_at) VALUES (?, ?, ?, ?)')
+        .run(current.symbol, JSON.stringify(next), current.createdAt || now, now);
+    return getDossier(db, current.symbol);
+};
+
+const deleteDossier = (db, rawSymbol) => db.prepare('DELETE FROM symbol_dossiers WHERE symbol = ?').run(cleanSymbol(rawSymbol)).changes > 0;
+
+// Summaries for a list view, most recently edited first
+const listDossiers = (db) => db.prepare('SELECT symbol, data, updated_at FROM symbol_dossiers ORDER BY updated_at DESC').all().map((row) => {
+    const d = JSON.parse(row.data);
+    return {
+        symbol: row.symbol,
+        summary: (d.thesis || '').split('\n')[0].slice(0, 160),
+        levels: (d.keyLevels || []).length,
+        links: (d.links || []).length,
+        files: (d.attachments || []).length + (d.files || []).length,
+        tags: d.tags || [],
+        updatedAt: row.updated_at
+    };
+});
+
+module.exports = { LEVEL_KINDS, initializeDossierTable, getDossier, updateDossier, deleteDossier, listDossiers };
@@ -0,0 +1,115 @@
+
+const { getSymbolMeta } = require('./positionSizing');
+const { withTimeout } = require('./shutdown');
+
+// --- SYMBOL SEARCH ---
+// "Type a ticker to open a chart": one query fanned out to every provider that
+// can search plus the local symbol store (datasets already on disk and saved
+// symbol metadata). Results are merged per symbol and exchange, keep every
+// provider that knows them, and rank exact > prefix > name matches, with
+// symbols that already have local data first among equals. Provider answers
+// are cached per query so typing doesn't hammer the endpoints.
+
+const CACHE_TTL_MS = 10 * 60 * 1000;
+const CACHE_MAX_ENTRIES = 300;
+const PROVIDER_TIMEOUT_MS = 4000;
+const DEFAULT_LIMIT = 30;
+
+const matchScore = (result, q) => {
+    const symbol = result.symbol.toUpperCase();
+    const bare = symbol.includes(':') ? symbol.slice(symbol.lastIndexOf(':') + 1) : symbol;
+    const name = String(result.name || '').toUpperCase();
+    if (symbol === q || bare === q) return 100;
+    if (symbol.startsWith(q) || bare.startsWith(q)) return 60 - Math.min(20, bare.length - q.length);
+    if (name.startsWith(q) || name.includes(` ${q}`)) return 30;
+    if (symbol.includes(q) || name.includes(q)) return 15;
+    return 0;
+};
+
+const searchLocal = (db, q) => {
+    const bySymbol = new Map();
+    db.prepare('SELECT id, symbol, timeframe, source FROM datasets WHERE upper(symbol) LIKE ?').all(`%${q}%`).forEach((d) => {
+        const entry = bySymbol.get(d.symbol) || { symbol: d.symbol, datasets: [] };
+        entry.datasets.push({ id: d.id, timeframe: d.timeframe, source: d.source });
+        bySymbol.set(d.symbol, entry);
+    });
+    db.prepare('SELECT symbol FROM symbol_meta WHERE upper(symbol) LIKE ?').all(`%${q}%`).forEach((row) => {
+        if (!bySymbol.has(row.symbol)) bySymbol.set(row.symbol, { symbol: row.symbol, datasets: [] });
+    });
+    return Array.from(bySymbol.values()).map(entry => ({
+        symbol: entry.symbol,
+        name: null,
+        exchange: null,
+        assetClass: getSymbolMeta(db, entry.symbol).assetClass || null,
+        datasets: entry.datasets
+    }));
+};
+
+/**
+ * getDb() -> database or null; listSearchable() -> [{ id, name }] of providers
+ * with search; getProvider(id) -> provider.
+ */
+const createSymbolSearch = ({ getDb, listSearchable, getProvider }) => {
+    const cache = new Map(); // `${providerId}\t${q}` -> { at, results }
+
+    const providerResults = async (id, q) => {
+        const key = `${id}\t${q}`;
+        const hit = cache.get(key);
+        if (hit && Date.now() - hit.at < CACHE_TTL_MS) return { results: hit.results, cached: true };
+        const results = await withTimeout(getProvider(id).searchSymbols(q), PROVIDER_TIMEOUT_MS, `${id} search`);
+        cache.delete(key);
+        cache.set(key, { at: Date.now(), results });
+        while (cache.size > CACHE_MAX_ENTRIES) cache.delete(cache.keys().next().value);
+        return { results, cached: false };
+    };
+
+    /**
+     * options: { providers?: ids, assetClass?, limit?, local? (default true) }.
+     * Returns { results, errors: { providerId: message } }; each result is
+     * { symbol, name, exchange, assetClass, providers, local: { datasets } | null, score }.
+     */
+    const search = async (query, { providers = null, assetClass = null, limit = DEFAULT_LIMIT, local = true } = {}) => {
+        const q = String(query || '').trim().toUpperCase();
+        if (!q) return { results: [], errors: {} };
+        const targets = listSearchable().filter(p => !providers || providers.includes(p.id));
+        const errors = {};
+        const merged = new Map(); // `${symbol}|${exchange}` -> result
+
+        const add = (result, providerId) => {
+            const key = `${result.symbol.toUpperCase()}|${String(result.exchange || '').toUpperCase()}`;
+            const existing = merged.get(key)
+                // Local symbols carry no exchange; fold them into the provider entry for the same ticker
+                || (result.exchange ? null : Array.from(merged.values()).find(r => r.symbol.toUpperCase() === result.symbol.toUpperCase()));
+            const target = existing || { symbol: result.symbol, name: null, exchange: result.exchange || null, assetClass: null, providers: [], local: null };
+            target.name = target.name || result.name || (result.baseAsset && result.quoteAsset ? `${result.baseAsset}/${result.quoteAsset}` : null);
+            target.assetClass = target.assetClass || result.assetClass || null;
+            if (providerId && !target.providers.includes(providerId)) target.providers.push(providerId);
+            if (result.datasets) target.local = { datasets: result.datasets };
+            if (!existing) merged.set(key, target);
+        };
+
+        const answers = await Promise.allSettled(targets.map(p => providerResults(p.id, q)));
+        answers.forEach((answer, i) => {
+            if (answer.status === 'fulfilled') answer.value.results.forEach(r => r && r.symbol && add(r, targets[i].id));
+            else errors[targets[i].id] = answer.reason.message;
+        });
+        const db = getDb();
+        if (local && db) searchLocal(db, q).forEach(r => add(r, null));
+
+        return {
+            results: Array.from(merged.values())
+                .filter(r => !assetClass || r.assetClass === assetClass)
+                .map(r => ({ ...r, score: matchScore(r, q) + (r.local ? 10 : 0) + Math.min(5, Math.max(0, r.providers.length - 1)) }))
+                .filter(r => r.score > 0)
+                .sort((a, b) => b.score - a.score || a.symbol.length - b.symbol.length || a.symbol.localeCompare(b.symbol))
+                .slice(0, limit),
+            errors
+        };
+    };
+
+    const clearCache = () => cache.clear();
+
+    return { search, clearCache, stats: () => ({ cachedQueries: cache.size, maxEntries: CACHE_MAX_ENTRIES }) };
+};
+
+module.exports = { createSymbolSearch };
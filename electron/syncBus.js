@@ -0,0 +1,43 @@
+
+// --- CROSS-WINDOW SYNC BUS ---
+// Republishes item-level change events (drawings, notes, ...) to every window
+// with a global sequence number. Every window receives every event, including
+// its own (tagged with `origin`, the sender's webContents id), so sequence
+// numbers stay gap-free per window: a jump means missed updates, recoverable
+// with since(seq) from the replay buffer or by reloading when that's too old.
+
+const REPLAY_SIZE = 500;
+
+const createSyncBus = ({ send }) => {
+    let seq = 0;
+    const buffer = []; // oldest first, at most REPLAY_SIZE envelopes
+
+    /**
+     * topic: 'drawings' | 'notes' | ... ; payload is topic-specific and item-level,
+     * e.g. { sourceId, changes: [{ op, id, item }] }.
+     */
+    const publish = (topic, payload, origin = null) => {
+        const envelope = { seq: ++seq, topic, payload, origin, at: Date.now() };
+        buffer.push(envelope);
+        if (buffer.length > REPLAY_SIZE) buffer.shift();
+        send(envelope);
+        return envelope;
+    };
+
+    /**
+     * Events after `sinceSeq`. `complete: false` means some were already evicted
+     * and the caller should reload the affected state instead of replaying.
+     */
+    const since = (sinceSeq, topics = null) => {
+        const oldest = buffer.length ? buffer[0].seq : seq + 1;
+        const events = buffer.filter(e => e.seq > sinceSeq && (!topics || topics.includes(e.topic)));
+        return { seq, complete: sinceSeq >= oldest - 1, events };
+    };
+
+    return { publish, since, currentSeq: () => seq };
+};
+
+// Drawing journal ops -> bus changes (full item for add/update, id only for delete)
+const drawingChanges = (ops) => ops.map(o => ({ op: o.op, id: o.drawingId, item: o.op === 'delete' ? null : o.after }));
+
+module.exports = { createSyncBus, drawingChanges };
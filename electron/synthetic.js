@@ -0,0 +1,122 @@
+
+const { TIMEFRAME_MS } = require('./datasets');
+
+// --- SYNTHETIC SERIES ---
+// Seeded random-walk / geometric Brownian motion bars for demos, UI testing and
+// replay practice. The same spec (including seed) always produces the same
+// series, so a generated dataset can be recreated from its registry meta.
+
+const MODELS = ['random-walk', 'gbm'];
+const MAX_BARS = 1000000;
+
+const DEFAULT_SPEC = {
+    symbol: 'SYNTH',
+    timeframe: '1h',
+    bars: 5000,
+    startPrice: 100,
+    model: 'gbm',
+    volatility: 0.01,   // per-bar: stdev of log returns (gbm) or of price change relative to startPrice (random-walk)
+    trend: 0,           // per-bar drift, same units as volatility
+    gapProbability: 0,  // chance of an opening gap at each session start
+    gapSize: 0.02,      // gap stdev as a fraction of price
+    session: null,      // { startHour, endHour, weekdays: [1..5] } in UTC; null = trade around the clock
+    startTime: null,    // ms; defaults to `bars` periods before now
+    seed: null
+};
+
+// mulberry32: small, fast and good enough for synthetic paths
+const createRng = (seed) => {
+    let a = seed >>> 0;
+    return () => {
+        a = (a + 0x6d2b79f5) >>> 0;
+        let t = a;
+        t = Math.imul(t ^ (t >>> 15), t | 1);
+        t ^= t + Math.imul(t ^ (t >>> 7), t | 61);
+        return ((t ^ (t >>> 14)) >>> 0) / 4294967296;
+    };
+};
+
+// Box-Muller standard normal
+const createNormal = (rng) => () => {
+    let u = 0;
+    while (u === 0) u = rng();
+    return Math.sqrt(-2 * Math.log(u)) * Math.cos(2 * Math.PI * rng());
+};
+
+const inSession = (ts, session) => {
+    if (!session) return true;
+    const d = new Date(ts);
+    const weekdays = session.weekdays || [1, 2, 3, 4, 5];
+    if (!weekdays.includes(d.getUTCDay())) return false;
+    const hour = d.getUTCHours() + d.getUTCMinutes() / 60;
+    const { startHour = 0, endHour = 24 } = session;
+    return startHour <= endHour ? hour >= startHour && hour < endHour : hour >= startHour || hour < endHour;
+};
+
+const normalizeSpec = (spec = {}) => {
+    const merged = { ...DEFAULT_SPEC, ...spec };
+    if (!TIMEFRAME_MS[merged.timeframe]) throw new Error(`Unsupported timeframe: ${merged.timeframe}`);
+    if (!MODELS.includes(merged.model)) throw new Error(`Unknown model: ${merged.model}`);
+    if (!Number.isInteger(merged.bars) || merged.bars < 1 || merged.bars > MAX_BARS) throw new Error(`bars must be between 1 and ${MAX_BARS}`);
+    if (!(merged.startPrice > 0)) throw new Error('startPrice must be positive');
+    if (!(merged.volatility >= 0)) throw new Error('volatility must be zero or positive');
+    merged.symbol = String(merged.symbol).trim().toUpperCase();
+    if (!merged.symbol) throw new Error('symbol is required');
+    if (merged.seed == null) merged.seed = Math.floor(Math.random() * 2 ** 31);
+    return merged;
+};
+
+/**
+ * Returns { spec, bars } with the fully resolved spec (seed filled in).
+ * Intra-bar high/low come from four sub-steps so wicks follow the same process.
+ */
+const generateSyntheticBars = (input) => {
+    const spec = normalizeSpec(input);
+    const rng = createRng(spec.seed);
+    const normal = createNormal(rng);
+    const step = TIMEFRAME_MS[spec.timeframe];
+    const subSteps = 4;
+    const subVol = spec.volatility / Math.sqrt(subSteps);
+    const subTrend = spec.trend / subSteps;
+
+    const move = (price) => (spec.model === 'gbm'
+        ? price * Math.exp(subTrend - (subVol * subVol) / 2 + subVol * normal())
+        : Math.max(price + spec.startPrice * (subTrend + subVol * normal()), spec.startPrice * 1e-4));
+
+    let ts = spec.startTime != null ? Math.floor(spec.startTime / step) * step : Math.floor(Date.now() / step) * step - spec.bars * step;
+    let price = spec.startPrice;
+    let prevInSession = false;
+    const bars = [];
+    let guard = 0;
+
+    while (bars.length < spec.bars) {
+        if (++guard > spec.bars * 50) throw new Error('Session window too narrow for the requested bar count');
+        const open = inSession(ts, spec.session);
+        if (!open) { prevInSession = false; ts += step; continue; }
+
+        // Session opens (and, without sessions, random bars) may gap
+        if ((spec.session ? !prevInSession : true) && spec.gapProbability > 0 && rng() < spec.gapProbability && bars.length > 0) {
+            price = Math.max(price * (1 + spec.gapSize * normal()), spec.startPrice * 1e-4);
+        }
+        prevInSession = true;
+
+        const o = price;
+        let high = o, low = o, c = o;
+        for (let i = 0; i < subSteps; i++) {
+            c = move(c);
+            high = Math.max(high, c);
+            low = Math.min(low, c);
+        }
+        const range = high - low;
+        high += range * 0.25 * rng();
+        low = Math.max(low - range * 0.25 * rng(), 0);
+        const volume = Math.round(1000 * (1 + Math.abs(normal())) * (1 + (range / o) / (spec.volatility || 1)));
+
+        bars.push({ timestamp: ts, open: o, high, low, close: c, volume });
+        price = c;
+        ts += step;
+    }
+    return { spec, bars };
+};
+
+module.exports = { MODELS, DEFAULT_SPEC, generateSyntheticBars };
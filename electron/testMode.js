@@ -0,0 +1,115 @@
+
+const fs = require('fs');
+const os = require('os');
+const path = require('path');
+
+// --- TEST MODE ---
+// --test-mode runs the app as an integration-test harness:
+//
+//   --test-mode                 userData (and logs / crash dumps) in a fresh temp
+//                               directory, removed on quit
+//   --test-data-dir=<path>      use (and keep) this directory instead
+//   --test-keep-data            keep the temp directory after quitting
+//   --test-clock=<ISO | ms>     frozen start time (default 2024-01-02T14:30:00Z)
+//
+// The clock is frozen: Date.now() and `new Date()` read an injectable time
+// source that only moves through set() / advance() (test:set-clock,
+// test:advance-clock); timers still fire on real time. Network providers are
+// replaced by the mock provider, outbound requests are refused (loopback is
+// allowed), and test:* introspection commands are registered.
+
+const TEST_MODE_FLAG = '--test-mode';
+const DEFAULT_TEST_CLOCK = Date.parse('2024-01-02T14:30:00Z');
+
+const argValue = (argv, name) => {
+    const prefix = `${name}=`;
+    const arg = argv.find(a => a.startsWith(prefix));
+    return arg ? arg.slice(prefix.length) : null;
+};
+
+const parseClockValue = (value) => {
+    const ms = /^-?\d+$/.test(String(value).trim()) ? Number(value) : Date.parse(value);
+    if (!Number.isFinite(ms)) throw new Error(`Invalid test clock: ${value}`);
+    return ms;
+};
+
+// null unless --test-mode is given
+const parseTestModeArgs = (argv) => {
+    if (!argv.includes(TEST_MODE_FLAG)) return null;
+    const clock = argValue(argv, '--test-clock');
+    const dataDir = argValue(argv, '--test-data-dir');
+    return {
+        startTime: clock ? parseClockValue(clock) : DEFAULT_TEST_CLOCK,
+        dataDir: dataDir ? path.resolve(dataDir) : null,
+        keepData: !!dataDir || argv.includes('--test-keep-data')
+    };
+};
+
+const createTestDataDir = (dir = null) => {
+    if (dir) {
+        fs.mkdirSync(dir, { recursive: true });
+        return dir;
+    }
+    return fs.mkdtempSync(path.join(os.tmpdir(), 'redpill-test-'));
+};
+
+// app.setPath targets inside the test directory
+const testDataPaths = (dir) => ({ userData: dir, logs: path.join(dir, 'logs'), crashDumps: path.join(dir, 'crash-dumps') });
+
+const createTestClock = (startTime = DEFAULT_TEST_CLOCK) => {
+    let current = startTime;
+    return {
+        now: () => current,
+        set: (value) => { current = parseClockValue(value); return current; },
+        advance: (ms) => {
+            const delta = Number(ms);
+            if (!Number.isFinite(delta) || delta < 0) throw new Error('advance needs a non-negative number of milliseconds');
+            current += delta;
+            return current;
+        }
+    };
+};
+
+// Replaces the global Date so Date.now() and argument-less `new Date()` read `clock`
+const installClock = (clock) => {
+    const RealDate = global.Date;
+    class TestDate extends RealDate {
+        constructor(...args) {
+            if (args.length) super(...args);
+            else super(clock.now());
+        }
+
+        static now() {
+            return clock.now();
+        }
+    }
+    global.Date = TestDate;
+    return () => { global.Date = RealDate; };
+};
+
+// Wraps ipcMain.handle to remember every registered channel (test:list-channels)
+const trackChannels = (ipcMain) => {
+    const channels = new Set();
+    const originalHandle = ipcMain.handle.bind(ipcMain);
+    ipcMain.handle = (channel, handler) => {
+        channels.add(channel);
+        return originalHandle(channel, handler);
+    };
+    return () => Array.from(channels).sort();
+};
+
+// Requests leaving the machine; the renderer's dev server and the signal inbox stay reachable
+const isExternalUrl = (url) => {
+    try {
+        const { protocol, hostname } = new URL(url);
+        if (!['http:', 'https:', 'ws:', 'wss:'].includes(protocol)) return false;
+        return !['localhost', '127.0.0.1', '[::1]', '::1'].includes(hostname);
+    } catch (e) {
+        return false;
+    }
+};
+
+module.exports = {
+    TEST_MODE_FLAG, DEFAULT_TEST_CLOCK, parseTestModeArgs, createTestDataDir, testDataPaths, createTestClock,
+    installClock, trackChannels, isExternalUrl
+};
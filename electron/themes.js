@@ -0,0 +1,130 @@
+
+const fs = require('fs');
+
+// --- THEMES & PALETTES ---
+// Named palettes covering chart colors (mapped onto ChartConfig overrides),
+// note/drawing swatches and UI theme tokens. Built-ins are read-only; user
+// themes live in the `themes` table. Theme files are plain JSON wrapped in a
+// small envelope so imports can be sanity-checked before they are stored.
+
+const THEME_FILE_FORMAT = 'redpill-theme';
+const THEME_FILE_VERSION = 1;
+const COLOR_PATTERN = /^(#[0-9a-f]{3,8}|rgba?\([\d\s.,%]+\)|hsla?\([\d\s.,%deg]+\)|transparent)$/i;
+
+const CHART_KEYS = ['upColor', 'downColor', 'wickUpColor', 'wickDownColor', 'borderUpColor', 'borderDownColor', 'backgroundColor', 'backgroundTopColor', 'backgroundBottomColor', 'gridColor', 'textColor', 'crosshairColor', 'lineColor', 'smaColor', 'volumeUpColor', 'volumeDownColor'];
+
+const BUILTIN_THEMES = [
+    {
+        name: 'Red Pill Dark',
+        base: 'dark',
+        chart: {
+            upColor: '#10B981', downColor: '#EF4444', wickUpColor: '#10B981', wickDownColor: '#EF4444',
+            borderUpColor: '#10B981', borderDownColor: '#EF4444', backgroundColor: '#0f172a',
+            gridColor: '#334155', textColor: '#94A3B8', crosshairColor: '#FFFFFF', lineColor: '#3B82F6', smaColor: '#F59E0B',
+            volumeUpColor: 'rgba(16, 185, 129, 0.3)', volumeDownColor: 'rgba(239, 68, 68, 0.3)'
+        },
+        notes: ['#F59E0B', '#3B82F6', '#10B981', '#EF4444', '#A855F7', '#F8FAFC'],
+        ui: { surface: '#1e293b', surfaceAlt: '#0f172a', border: '#334155', accent: '#3B82F6', text: '#e2e8f0', textMuted: '#94A3B8' }
+    },
+    {
+        name: 'Daylight',
+        base: 'light',
+        chart: {
+            upColor: '#059669', downColor: '#DC2626', wickUpColor: '#059669', wickDownColor: '#DC2626',
+            borderUpColor: '#059669', borderDownColor: '#DC2626', backgroundColor: '#ffffff',
+            gridColor: '#e2e8f0', textColor: '#475569', crosshairColor: '#0f172a', lineColor: '#2563EB', smaColor: '#D97706',
+            volumeUpColor: 'rgba(5, 150, 105, 0.3)', volumeDownColor: 'rgba(220, 38, 38, 0.3)'
+        },
+        notes: ['#D97706', '#2563EB', '#059669', '#DC2626', '#7C3AED', '#0f172a'],
+        ui: { surface: '#f8fafc', surfaceAlt: '#ffffff', border: '#cbd5e1', accent: '#2563EB', text: '#0f172a', textMuted: '#475569' }
+    }
+];
+
+const isBuiltin = (name) => BUILTIN_THEMES.some(t => t.name === name);
+
+const initializeThemeTable = (db) => {
+    db.exec('CREATE TABLE IF NOT EXISTS themes (name TEXT PRIMARY KEY, data TEXT, updated_at INTEGER);');
+};
+
+const checkColor = (value, where) => {
+    if (typeof value !== 'string' || !COLOR_PATTERN.test(value.trim())) throw new Error(`Invalid color at ${where}: ${value}`);
+    return value.trim();
+};
+
+/**
+ * Validates and normalizes a theme. Unknown chart keys are dropped; UI tokens
+ * are free-form names but their values must be colors.
+ */
+const normalizeTheme = (theme) => {
+    if (!theme || typeof theme !== 'object') throw new Error('Theme must be an object');
+    const name = String(theme.name || '').trim();
+    if (!name || name.length > 80) throw new Error('Theme name must be 1-80 characters');
+    const base = theme.base === 'light' ? 'light' : 'dark';
+
+    const chart = {};
+    Object.entries(theme.chart || {}).forEach(([key, value]) => {
+        if (CHART_KEYS.includes(key)) chart[key] = checkColor(value, `chart.${key}`);
+    });
+    const notes = (theme.notes || []).slice(0, 32).map((c, i) => checkColor(c, `notes[${i}]`));
+    const ui = {};
+    Object.entries(theme.ui || {}).forEach(([key, value]) => {
+        if (!/^[a-zA-Z][\w-]{0,40}$/.test(key)) throw new Error(`Invalid UI token name: ${key}`);
+        ui[key] = checkColor(value, `ui.${key}`);
+    });
+    return { name, base, chart, notes, ui };
+};
+
+const listThemes = (db, activeName = null) => {
+    const user = db.prepare('SELECT name, data, updated_at FROM themes ORDER BY name COLLATE NOCASE').all()
+        .map(r => ({ ...JSON.parse(r.data), builtin: false, updatedAt: r.updated_at }));
+    return [...BUILTIN_THEMES.map(t => ({ ...t, builtin: true, updatedAt: null })), ...user]
+        .map(t => ({ ...t, active: t.name === activeName }));
+};
+
+const getTheme = (db, name) => {
+    const builtin = BUILTIN_THEMES.find(t => t.name === name);
+    if (builtin) return { ...builtin, builtin: true };
+    const row = db.prepare('SELECT data FROM themes WHERE name = ?').get(name);
+    return row ? { ...JSON.parse(row.data), builtin: false } : null;
+};
+
+const saveTheme = (db, theme) => {
+    const normalized = normalizeTheme(theme);
+    if (isBuiltin(normalized.name)) throw new Error(`"${normalized.name}" is a built-in theme; save it under another name`);
+    db.prepare('INSERT OR REPLACE INTO themes (name, data, updated_at) VALUES (?, ?, ?)').run(normalized.name, JSON.stringify(normalized), Date.now());
+    return normalized;
+};
+
+const deleteTheme = (db, name) => {
+    if (isBuiltin(name)) throw new Error('Built-in themes cannot be deleted');
+    return db.prepare('DELETE FROM themes WHERE name = ?').run(name).changes > 0;
+};
+
+// Chart section as ChartConfig overrides, so the renderer can spread it straight into a tab config
+const toChartConfig = (theme) => {
+    const c = theme.chart || {};
+    const config = { theme: theme.base };
+    ['upColor', 'downColor', 'wickUpColor', 'wickDownColor', 'borderUpColor', 'borderDownColor', 'backgroundColor', 'backgroundTopColor', 'backgroundBottomColor']
+        .forEach((key) => { if (c[key]) config[key] = c[key]; });
+    if (c.backgroundTopColor && c.backgroundBottomColor) config.backgroundType = 'gradient';
+    return config;
+};
+
+const exportThemeFile = (theme, filePath) => {
+    const { builtin, active, updatedAt, ...data } = theme;
+    fs.writeFileSync(filePath, JSON.stringify({ format: THEME_FILE_FORMAT, version: THEME_FILE_VERSION, theme: data }, null, 2));
+};
+
+const readThemeFile = (filePath) => {
+    let parsed;
+    try {
+        parsed = JSON.parse(fs.readFileSync(filePath, 'utf8'));
+    } catch (err) {
+        throw new Error(`Not a readable theme file: ${err.message}`);
+    }
+    if (parsed.format !== THEME_FILE_FORMAT) throw new Error('Not a Red Pill theme file');
+    if (parsed.version > THEME_FILE_VERSION) throw new Error(`Theme file version ${parsed.version} is newer than this app supports`);
+    return normalizeTheme(parsed.theme);
+};
+
+module.exports = { BUILTIN_THEMES, initializeThemeTable, normalizeTheme, listThemes, getTheme, saveTheme, deleteTheme, toChartConfig, exportThemeFile, readThemeFile };
@@ -0,0 +1,79 @@
+
+const fs = require('fs');
+
+// --- PERSISTENT TIME INDEX ---
+// Sparse timestamp -> byte offset map for large CSV sources, built once by the
+// ingest worker and kept in SQLite keyed by file path. A range read looks up
+// the last entry at or before `from` and streams from there instead of the top
+// of the file. Entries are only trusted while the file's size and mtime match
+// what was recorded at build time.
+
+const INDEX_MIN_BYTES = 32 * 1024 * 1024;
+const DEFAULT_STRIDE = 1024 * 1024; // one entry per MB of file
+
+const initializeTimeIndexTable = (db) => {
+    db.exec('CREATE TABLE IF NOT EXISTS file_time_index (file_path TEXT PRIMARY KEY, size INTEGER, mtime INTEGER, format TEXT, ascending INTEGER, first_ts INTEGER, last_ts INTEGER, row_count INTEGER, entries TEXT, built_at INTEGER);');
+};
+
+const fileSignature = (filePath) => {
+    const stat = fs.statSync(filePath);
+    return { size: stat.size, mtime: Math.floor(stat.mtimeMs) };
+};
+
+const rowToIndex = (row) => ({
+    filePath: row.file_path,
+    size: row.size,
+    mtime: row.mtime,
+    format: row.format,
+    ascending: !!row.ascending,
+    firstTs: row.first_ts,
+    lastTs: row.last_ts,
+    rowCount: row.row_count,
+    entries: JSON.parse(row.entries),
+    builtAt: row.built_at
+});
+
+/**
+ * Returns the stored index for `filePath`, or null when missing or stale.
+ * Stale rows are dropped so the next open rebuilds them.
+ */
+const getTimeIndex = (db, filePath) => {
+    const row = db.prepare('SELECT * FROM file_time_index WHERE file_path = ?').get(filePath);
+    if (!row) return null;
+    let sig;
+    try { sig = fileSignature(filePath); } catch (e) { sig = null; }
+    if (!sig || sig.size !== row.size || sig.mtime !== row.mtime) {
+        db.prepare('DELETE FROM file_time_index WHERE file_path = ?').run(filePath);
+        return null;
+    }
+    return rowToIndex(row);
+};
+
+/**
+ * Stores a worker index result against the signature captured before the build started.
+ */
+const saveTimeIndex = (db, filePath, signature, format, result) => {
+    db.prepare(`INSERT OR REPLACE INTO file_time_index (file_path, size, mtime, format, ascending, first_ts, last_ts, row_count, entries, built_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)`)
+        .run(filePath, signature.size, signature.mtime, format, result.ascending ? 1 : 0, result.firstTs, result.lastTs, result.rows, JSON.stringify(result.entries), Date.now());
+};
+
+const deleteTimeIndex = (db, filePath) => db.prepare('DELETE FROM file_time_index WHERE file_path = ?').run(filePath).changes;
+
+/**
+ * Byte offset to start a read for `from`, or 0 when the index can't help
+ * (descending/unordered files, or `from` before the first entry).
+ */
+const seekOffset = (index, from) => {
+    if (!index || !index.ascending || from == null || index.entries.length === 0) return 0;
+    const entries = index.entries;
+    let lo = 0, hi = entries.length - 1, best = -1;
+    while (lo <= hi) {
+        const mid = (lo + hi) >> 1;
+        if (entries[mid][0] < from) { best = mid; lo = mid + 1; } else hi = mid - 1;
+    }
+    // strictly-before keeps rows sharing the `from` timestamp in range
+    return best === -1 ? 0 : entries[best][1];
+};
+
+module.exports = { INDEX_MIN_BYTES, DEFAULT_STRIDE, initializeTimeIndexTable, fileSignature, getTimeIndex, saveTimeIndex, deleteTimeIndex, seekOffset };
@@ -0,0 +1,146 @@
+
+const crypto = require('crypto');
+const https = require('https');
+const tls = require('tls');
+
+// --- TLS POLICY (PINNING / STRICT MODE) ---
+// Optional per-provider certificate pinning and a strict-TLS mode for broker
+// and exchange connections. Pins are HPKP-style SPKI hashes
+// ("sha256/<base64>") and may name any certificate in the chain, so pinning an
+// intermediate survives routine leaf renewals. Strict mode refuses plaintext
+// (http:// / ws://) endpoints, requires TLS 1.2+ with forward-secret AEAD
+// ciphers and can't be loosened by NODE_TLS_REJECT_UNAUTHORIZED. The transport
+// (proxy.js) asks tlsOptionsFor() for the options of each new connection.
+
+const STRICT_CIPHERS = [
+    'TLS_AES_256_GCM_SHA384', 'TLS_CHACHA20_POLY1305_SHA256', 'TLS_AES_128_GCM_SHA256',
+    'ECDHE-ECDSA-AES256-GCM-SHA384', 'ECDHE-RSA-AES256-GCM-SHA384',
+    'ECDHE-ECDSA-CHACHA20-POLY1305', 'ECDHE-RSA-CHACHA20-POLY1305',
+    'ECDHE-ECDSA-AES128-GCM-SHA256', 'ECDHE-RSA-AES128-GCM-SHA256'
+].join(':');
+
+const PIN_PATTERN = /^sha256\/[A-Za-z0-9+/]{43}=$/;
+
+const DEFAULT_TLS_POLICY = {
+    strictTls: false, // default for providers without their own setting
+    providers: {}     // { polygon: { strictTls?: boolean, pins?: ['sha256/...'] } }
+};
+
+let policy = DEFAULT_TLS_POLICY;
+let version = 0; // bumped on change so transports rebuild cached agents
+
+const spkiPin = (cert) => `sha256/${crypto.createHash('sha256').update(cert.pubkey).digest('base64')}`;
+
+// Leaf first; issuerCertificate points to itself at the root
+const chainOf = (cert) => {
+    const chain = [];
+    let current = cert;
+    while (current && current.pubkey && !chain.includes(current)) {
+        chain.push(current);
+        if (current.issuerCertificate === current) break;
+        current = current.issuerCertificate;
+    }
+    return chain;
+};
+
+const normalizeTlsPolicy = (input = {}) => {
+    const providers = {};
+    Object.entries(input.providers || {}).forEach(([id, entry]) => {
+        const pins = Array.from(new Set((entry.pins || []).map(p => String(p).trim()).filter(Boolean)));
+        const invalid = pins.find(p => !PIN_PATTERN.test(p));
+        if (invalid) throw new Error(`Invalid pin for ${id}: "${invalid}" (expected sha256/<base64 SPKI hash>)`);
+        providers[id] = { pins };
+        if (typeof entry.strictTls === 'boolean') providers[id].strictTls = entry.strictTls;
+    });
+    return { strictTls: !!input.strictTls, providers };
+};
+
+const configureTlsPolicy = (input) => {
+    policy = normalizeTlsPolicy(input);
+    version += 1;
+    return policy;
+};
+
+const providerRules = (provider) => {
+    const entry = (provider && policy.providers[provider]) || {};
+    return { strict: entry.strictTls !== undefined ? entry.strictTls : policy.strictTls, pins: entry.pins || [] };
+};
+
+const tlsError = (code, message) => {
+    const err = new Error(message);
+    err.code = code;
+    return err;
+};
+
+/**
+ * Throws TLS_POLICY_PLAINTEXT when strict mode forbids a plaintext URL.
+ */
+const assertAllowedUrl = (provider, url) => {
+    const { protocol, host } = new URL(url);
+    if ((protocol === 'http:' || protocol === 'ws:') && providerRules(provider).strict) {
+        throw tlsError('TLS_POLICY_PLAINTEXT', `Strict TLS: refusing unencrypted connection to ${host} for ${provider}`);
+    }
+};
+
+/**
+ * Extra tls.connect options for a provider's connections, or null when no
+ * policy applies (plain defaults).
+ */
+const tlsOptionsFor = (provider) => {
+    const { strict, pins } = providerRules(provider);
+    if (!strict && pins.length === 0) return null;
+    const options = {};
+    if (strict) {
+        options.minVersion = 'TLSv1.2';
+        options.ciphers = STRICT_CIPHERS;
+        options.rejectUnauthorized = true;
+    }
+    if (pins.length) {
+        options.checkServerIdentity = (host, cert) => {
+            const hostError = tls.checkServerIdentity(host, cert);
+            if (hostError) return hostError;
+            const presented = chainOf(cert).map(spkiPin);
+            if (presented.some(pin => pins.includes(pin))) return undefined;
+            return tlsError('CERT_PIN_MISMATCH', `Certificate pin mismatch for ${host} (${provider}): the server presented ${presented[0]}`
+                + `${presented.length > 1 ? ` (chain: ${presented.slice(1).join(', ')})` : ''}, which is not pinned. `
+                + 'The connection was refused to protect your credentials; if the provider rotated its certificates, update the pins.');
+        };
+    }
+    return options;
+};
+
+/**
+ * Connects to an https URL and reports the pins of its certificate chain, so
+ * the user can pin what they currently see. agent: optional (proxy tunnel).
+ */
+const inspectCertificates = (url, agent = undefined) => new Promise((resolve, reject) => {
+    const target = new URL(url.replace(/^wss:/, 'https:'));
+    if (target.protocol !== 'https:') return reject(new Error('Only https / wss endpoints have certificates to pin'));
+    const req = https.request(target, { method: 'HEAD', agent: agent || new https.Agent({ maxCachedSessions: 0 }), timeout: 15000 });
+    req.once('socket', (socket) => {
+        socket.once('secureConnect', () => {
+            const chain = chainOf(socket.getPeerCertificate(true)).map(cert => ({
+                subject: cert.subject && cert.subject.CN,
+                issuer: cert.issuer && cert.issuer.CN,
+                validTo: cert.valid_to,
+                pin: spkiPin(cert)
+            }));
+            resolve({ host: target.host, protocol: socket.getProtocol(), authorized: socket.authorized, chain });
+            req.destroy();
+        });
+    });
+    req.once('timeout', () => req.destroy(new Error('timed out')));
+    req.once('error', (err) => reject(err));
+    req.end();
+});
+
+module.exports = {
+    DEFAULT_TLS_POLICY,
+    normalizeTlsPolicy,
+    configureTlsPolicy,
+    getTlsPolicy: () => policy,
+    tlsPolicyVersion: () => version,
+    assertAllowedUrl,
+    tlsOptionsFor,
+    inspectCertificates
+};
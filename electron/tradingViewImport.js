@@ -0,0 +1,188 @@
+
+const crypto = require('crypto');
+
+// --- TRADINGVIEW LAYOUT IMPORTER ---
+// Converts TradingView chart layouts / drawing exports into Red Pill drawings.
+// TradingView nests line tools under charts[].panes[].sources[] (layout export)
+// or a flat sources[] / drawings[] array (drawing export), so we walk the whole
+// tree and pick up every object that looks like a line tool.
+
+const TYPE_MAP = {
+    LineToolTrendLine: 'trend_line',
+    LineToolRay: 'ray',
+    LineToolExtended: 'ray',
+    LineToolArrow: 'arrow_line',
+    LineToolHorzLine: 'horizontal_line',
+    LineToolHorzRay: 'horizontal_ray',
+    LineToolVertLine: 'vertical_line',
+    LineToolRectangle: 'rectangle',
+    LineToolRotatedRectangle: 'rotated_rectangle',
+    LineToolTriangle: 'triangle',
+    LineToolCircle: 'circle',
+    LineToolBrush: 'brush',
+    LineToolHighlighter: 'brush',
+    LineToolDateRange: 'date_range',
+    LineToolPriceRange: 'measure',
+    LineToolDateAndPriceRange: 'measure',
+    LineToolText: 'text',
+    LineToolTextAbsolute: 'text',
+    LineToolNote: 'text',
+    LineToolNoteAbsolute: 'text',
+    LineToolCallout: 'text',
+    LineToolBalloon: 'text',
+    LineToolComment: 'text',
+    LineToolPriceLabel: 'text',
+};
+
+const FIB_TYPES = ['LineToolFibRetracement', 'LineToolTrendBasedFibExtension'];
+const FIB_EXTENSION = 'LineToolTrendBasedFibExtension';
+const DEFAULT_FIB_LEVELS = [0, 0.236, 0.382, 0.5, 0.618, 0.786, 1];
+const DEFAULT_FIB_EXTENSION_LEVELS = [0, 0.382, 0.618, 1, 1.272, 1.618, 2.618];
+
+// TradingView linestyle: 0 = solid, 1 = dotted, 2 = dashed
+const mapLineStyle = (style) => {
+    if (style === 1) return 'dotted';
+    if (style === 2) return 'dashed';
+    return 'solid';
+};
+
+// TradingView stores either `time_t` (seconds) or `time` on each point.
+const mapPoint = (p) => {
+    if (!p || typeof p !== 'object') return null;
+    let time = p.time_t ?? p.time ?? p.timestamp;
+    const price = Number(p.price);
+    if (time === undefined || time === null || !isFinite(price)) return null;
+    time = Number(time);
+    if (!isFinite(time)) return null;
+    if (time < 10000000000) time *= 1000; // seconds -> ms
+    return { time, price };
+};
+
+const isLineTool = (node) =>
+    node && typeof node === 'object' && typeof node.type === 'string' && node.type.startsWith('LineTool');
+
+const collectLineTools = (node, out, depth = 0) => {
+    if (!node || typeof node !== 'object' || depth > 32) return out;
+    if (Array.isArray(node)) {
+        node.forEach(child => collectLineTools(child, out, depth + 1));
+        return out;
+    }
+    if (isLineTool(node)) {
+        out.push(node);
+        return out;
+    }
+    Object.keys(node).forEach((key) => {
+        let value = node[key];
+        // Layout exports embed the chart content as a JSON string
+        if (typeof value === 'string' && (key === 'content' || key === 'charts' || key === 'sources')) {
+            try { value = JSON.parse(value); } catch (e) { return; }
+        }
+        collectLineTools(value, out, depth + 1);
+    });
+    return out;
+};
+
+const baseProperties = (state = {}) => ({
+    color: state.linecolor || state.color || state.textColor || '#3B82F6',
+    lineWidth: Number(state.linewidth) || 2,
+    lineStyle: mapLineStyle(state.linestyle),
+    visible: state.visible !== false,
+    locked: !!state.frozen || !!state.locked,
+});
+
+// Retracements span two points; trend-based extensions project the p1 -> p2
+// move from a third point, so p3 + (p2 - p1) * coeff.
+const convertFib = (tool, points, folderId) => {
+    const state = tool.state || {};
+    const extension = tool.type === FIB_EXTENSION;
+    if (points.length < (extension ? 3 : 2)) return [];
+    const [p1, p2, p3] = points;
+
+    const levels = [];
+    Object.keys(state).forEach((key) => {
+        const lvl = state[key];
+        if (/^level\d+$/.test(key) && lvl && typeof lvl === 'object' && lvl.visible !== false && isFinite(Number(lvl.coeff))) {
+            levels.push({ coeff: Number(lvl.coeff), color: lvl.color });
+        }
+    });
+    if (levels.length === 0) (extension ? DEFAULT_FIB_EXTENSION_LEVELS : DEFAULT_FIB_LEVELS).forEach(coeff => levels.push({ coeff }));
+
+    const props = baseProperties(state);
+    const reverse = !!state.reverse;
+    const from = reverse ? p2.price : p1.price;
+    const to = reverse ? p1.price : p2.price;
+    const startTime = extension ? p3.time : Math.min(p1.time, p2.time);
+    const levelPrice = extension
+        ? coeff => p3.price + (p2.price - p1.price) * coeff
+        : coeff => to + (from - to) * coeff;
+
+    return levels.map(({ coeff, color }) => {
+        const price = levelPrice(coeff);
+        return {
+            id: crypto.randomUUID(),
+            type: 'horizontal_ray',
+            points: [{ time: startTime, price }],
+            properties: { ...props, color: color || props.color, text: `${coeff} (${price.toFixed(5).replace(/\.?0+$/, '')})` },
+            folderId,
+        };
+    });
+};
+
+/**
+ * Parses a TradingView export (object or JSON string) into Red Pill drawings.
+ * Fibonacci tools have no native equivalent, so each one becomes a folder of
+ * horizontal rays (one per visible level).
+ */
+const convertTradingViewLayout = (input) => {
+    const root = typeof input === 'string' ? JSON.parse(input) : input;
+    const tools = collectLineTools(root, []);
+
+    const drawings = [];
+    const folders = [];
+    const skipped = {};
+
+    tools.forEach((tool) => {
+        const state = tool.state || {};
+        const points = (tool.points || state.points || []).map(mapPoint).filter(Boolean);
+
+        if (FIB_TYPES.includes(tool.type)) {
+            const folder = { id: crypto.randomUUID(), name: state.title || (tool.type === FIB_EXTENSION ? 'Fib Extension' : 'Fib Retracement'), isExpanded: false };
+            const levels = convertFib(tool, points, folder.id);
+            if (levels.length > 0) {
+                folders.push(folder);
+                drawings.push(...levels);
+            } else {
+                skipped[tool.type] = (skipped[tool.type] || 0) + 1;
+            }
+            return;
+        }
+
+        let type = TYPE_MAP[tool.type];
+        if (type === 'trend_line' && (state.extendRight || state.extendLeft)) type = 'ray';
+        if (!type || points.length === 0) {
+            skipped[tool.type] = (skipped[tool.type] || 0) + 1;
+            return;
+        }
+
+        const properties = baseProperties(state);
+        if (type === 'text') {
+            properties.text = state.text || tool.text || '';
+            properties.color = state.color || state.textColor || properties.color;
+            properties.fontSize = Number(state.fontsize) || 14;
+            if (!properties.text) {
+                skipped[tool.type] = (skipped[tool.type] || 0) + 1;
+                return;
+            }
+        } else if (state.text) {
+            properties.text = state.text;
+        }
+        if (state.backgroundColor) properties.backgroundColor = state.backgroundColor;
+        if (state.fillBackground !== undefined) properties.filled = !!state.fillBackground;
+
+        drawings.push({ id: crypto.randomUUID(), type, points, properties, folderId: null });
+    });
+
+    return { drawings, folders, skipped, total: tools.length };
+};
+
+module.exports = { convertTradingViewLayout };
@@ -0,0 +1,52 @@
+
+const test = require('node:test');
+const assert = require('node:assert');
+const { convertTradingViewLayout } = require('./tradingViewImport');
+
+const point = (time_t, price) => ({ time_t, price });
+
+const extensionTool = (points, state = {}) => ({
+    type: 'LineToolTrendBasedFibExtension',
+    points,
+    state: {
+        level1: { coeff: 0, visible: true },
+        level2: { coeff: 0.618, visible: true },
+        level3: { coeff: 1, visible: true },
+        level4: { coeff: 1.618, visible: true },
+        ...state
+    }
+});
+
+test('trend-based fib extension projects the p1 -> p2 move from p3', () => {
+    const layout = { sources: [extensionTool([point(1700000000, 100), point(1700003600, 150), point(1700007200, 120)])] };
+    const { drawings, folders, skipped } = convertTradingViewLayout(layout);
+    assert.strictEqual(folders.length, 1);
+    assert.strictEqual(folders[0].name, 'Fib Extension');
+    assert.deepStrictEqual(skipped, {});
+    assert.deepStrictEqual(drawings.map(d => d.points[0].price), [120, 150.9, 170, 200.9]);
+    drawings.forEach((d) => {
+        assert.strictEqual(d.type, 'horizontal_ray');
+        assert.strictEqual(d.points[0].time, 1700007200000);
+        assert.strictEqual(d.folderId, folders[0].id);
+    });
+});
+
+test('fib extension with fewer than three points is skipped and reported', () => {
+    const layout = { sources: [extensionTool([point(1700000000, 100), point(1700003600, 150)])] };
+    const { drawings, folders, skipped } = convertTradingViewLayout(layout);
+    assert.strictEqual(drawings.length, 0);
+    assert.strictEqual(folders.length, 0);
+    assert.deepStrictEqual(skipped, { LineToolTrendBasedFibExtension: 1 });
+});
+
+test('fib retracement still spans its two points', () => {
+    const layout = {
+        sources: [{
+            type: 'LineToolFibRetracement',
+            points: [point(1700000000, 100), point(1700003600, 200)],
+            state: { level1: { coeff: 0, visible: true }, level2: { coeff: 0.5, visible: true }, level3: { coeff: 1, visible: true } }
+        }]
+    };
+    const { drawings } = convertTradingViewLayout(layout);
+    assert.deepStrictEqual(drawings.map(d => d.points[0].price), [200, 150, 100]);
+});
@@ -0,0 +1,154 @@
+
+const crypto = require('crypto');
+const { request } = require('./network');
+
+// --- USAGE ANALYTICS ---
+// Feature-usage counters per UTC day, kept in `usage_counters`. Features are
+// IPC command names plus renderer-reported ids (lowercase identifiers, never
+// free text); only counts are stored — no arguments, symbols or content.
+// Nothing is uploaded until the user enables it and sets an endpoint; then
+// finished days go out once as { installId, appVersion, platform, days }, the
+// same payload preview() shows. The install id is random and can be reset.
+
+const FEATURE_RE = /^[a-z0-9][a-z0-9:._-]{0,63}$/;
+const FLUSH_MS = 60 * 1000;
+const UPLOAD_CHECK_MS = 6 * 3600 * 1000;
+const RETAIN_DAYS = 90;
+const DEFAULT_CONFIG = { enabled: false, endpoint: '', installId: null, lastUploadAt: null };
+
+const initializeUsageTable = (db) => {
+    db.exec(`
+        CREATE TABLE IF NOT EXISTS usage_counters (
+            day TEXT,
+            feature TEXT,
+            count INTEGER,
+            uploaded INTEGER DEFAULT 0,
+            PRIMARY KEY (day, feature)
+        );
+    `);
+};
+
+const utcDay = (ts = Date.now()) => new Date(ts).toISOString().slice(0, 10);
+
+/**
+ * getDb() returns the open database (or null before startup); counts recorded
+ * earlier are buffered. environment: { appVersion, platform }.
+ */
+const createUsageAnalytics = ({ getDb, loadConfig = () => null, saveConfig = () => {}, environment = {}, onLog = () => {} }) => {
+    const pending = new Map(); // `${day}\t${feature}` -> count
+    let flushTimer = null;
+    let uploadTimer = null;
+
+    const getConfig = () => ({ ...DEFAULT_CONFIG, ...(loadConfig() || {}) });
+
+    const record = (feature) => {
+        const name = String(feature || '').toLowerCase();
+        if (!FEATURE_RE.test(name)) return false;
+        const key = `${utcDay()}\t${name}`;
+        pending.set(key, (pending.get(key) || 0) + 1);
+        return true;
+    };
+
+    const flush = () => {
+        const db = getDb();
+        if (!db || !pending.size) return 0;
+        const upsert = db.prepare(`INSERT INTO usage_counters (day, feature, count) VALUES (?, ?, ?)
+            ON CONFLICT (day, feature) DO UPDATE SET count = count + excluded.count`);
+        const entries = Array.from(pending.entries());
+        pending.clear();
+        db.transaction(() => entries.forEach(([key, count]) => upsert.run(...key.split('\t'), count)))();
+        db.prepare('DELETE FROM usage_counters WHERE day < ?').run(utcDay(Date.now() - RETAIN_DAYS * 86400000));
+        return entries.length;
+    };
+
+    // Local totals per feature over the last `days`, busiest first
+    const getSummary = ({ days = 30 } = {}) => {
+        flush();
+        const db = getDb();
+        if (!db) return { since: null, features: [] };
+        const since = utcDay(Date.now() - (days - 1) * 86400000);
+        const features = db.prepare('SELECT feature, SUM(count) AS count FROM usage_counters WHERE day >= ? GROUP BY feature ORDER BY count DESC, feature')
+            .all(since).map(row => ({ feature: row.feature, count: row.count }));
+        return { since, features };
+    };
+
+    // Exactly what upload() would send now (finished days not sent yet)
+    const preview = () => {
+        flush();
+        const db = getDb();
+        const rows = db ? db.prepare('SELECT day, feature, count FROM usage_counters WHERE uploaded = 0 AND day < ? ORDER BY day, feature').all(utcDay()) : [];
+        const days = [];
+        rows.forEach((row) => {
+            if (!days.length || days[days.length - 1].day !== row.day) days.push({ day: row.day, counters: {} });
+            days[days.length - 1].counters[row.feature] = row.count;
+        });
+        const config = getConfig();
+        return { installId: config.installId, appVersion: environment.appVersion || null, platform: environment.platform || null, days };
+    };
+
+    const upload = async () => {
+        const config = getConfig();
+        if (!config.enabled || !config.endpoint) return { uploaded: 0, skipped: 'disabled' };
+        const payload = preview();
+        if (!payload.days.length) return { uploaded: 0 };
+        const response = await request('usage-analytics', config.endpoint, {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify(payload)
+        }, { retries: 1 });
+        if (!response.ok) throw new Error(`Usage upload failed: HTTP ${response.status}`);
+        const db = getDb();
+        const mark = db.prepare('UPDATE usage_counters SET uploaded = 1 WHERE day = ?');
+        db.transaction(() => payload.days.forEach(d => mark.run(d.day)))();
+        saveConfig({ ...getConfig(), lastUploadAt: Date.now() });
+        return { uploaded: payload.days.length };
+    };
+
+    const tryUpload = () => upload()
+        .then((result) => { if (result.uploaded) onLog('INFO', 'USAGE_UPLOADED', result); })
+        .catch(err => onLog('WARN', 'USAGE_UPLOAD_FAILED', { error: err.message }));
+
+    // updates: { enabled?, endpoint? }; enabling the first time creates the install id
+    const configure = (updates = {}) => {
+        const next = { ...getConfig() };
+        if (updates.enabled !== undefined) next.enabled = !!updates.enabled;
+        if (updates.endpoint !== undefined) {
+            const endpoint = String(updates.endpoint || '').trim();
+            if (endpoint && !/^https:\/\//i.test(endpoint)) throw new Error('The usage endpoint must be an https:// URL');
+            next.endpoint = endpoint;
+        }
+        if (next.enabled && !next.installId) next.installId = crypto.randomUUID();
+        saveConfig(next);
+        return next;
+    };
+
+    const resetInstallId = () => {
+        const next = { ...getConfig(), installId: getConfig().enabled ? crypto.randomUUID() : null };
+        saveConfig(next);
+        return next;
+    };
+
+    const clear = () => {
+        pending.clear();
+        const db = getDb();
+        return db ? db.prepare('DELETE FROM usage_counters').run().changes : 0;
+    };
+
+    const start = () => {
+        stop();
+        flushTimer = setInterval(() => { try { flush(); } catch (e) { onLog('WARN', 'USAGE_FLUSH_FAILED', { error: e.message }); } }, FLUSH_MS);
+        uploadTimer = setInterval(tryUpload, UPLOAD_CHECK_MS);
+        tryUpload();
+    };
+
+    const stop = () => {
+        if (flushTimer) clearInterval(flushTimer);
+        if (uploadTimer) clearInterval(uploadTimer);
+        flushTimer = null;
+        uploadTimer = null;
+    };
+
+    return { record, flush, getSummary, preview, upload, configure, getConfig, resetInstallId, clear, start, stop };
+};
+
+module.exports = { initializeUsageTable, createUsageAnalytics };
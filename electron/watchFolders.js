@@ -0,0 +1,228 @@
+
+const fs = require('fs');
+const path = require('path');
+const { TIMEFRAME_MS } = require('./datasets');
+
+// --- WATCH FOLDERS ---
+// Auto-import for folders a data vendor drops files into. Each rule names a
+// folder, file-name patterns and how to derive symbol/timeframe. New or changed
+// files are imported once their size stops changing (vendors write in place),
+// one at a time, and appended to the bar store (existing bars are kept). What
+// was imported is remembered by size+mtime in `watch_imports`, so restarts only
+// pick up files that arrived or changed while the app was closed. Each burst of
+// imports ends with one summary instead of one notification per file.
+
+const SETTLE_MS = 2000; // a file must keep the same size this long before import
+const SUMMARY_QUIET_MS = 5000; // summary goes out after this long with nothing queued
+
+const initializeWatchImportTable = (db) => {
+    db.exec(`
+        CREATE TABLE IF NOT EXISTS watch_imports (
+            file_path TEXT PRIMARY KEY,
+            size INTEGER,
+            mtime INTEGER,
+            symbol TEXT,
+            timeframe TEXT,
+            rows INTEGER,
+            error TEXT,
+            imported_at INTEGER
+        );
+    `);
+};
+
+// "*.csv", "EUR*_1h.csv" -> case-insensitive regex over the file name
+const globToRegex = (glob) => new RegExp(`^${String(glob).split('').map((c) => {
+    if (c === '*') return '.*';
+    if (c === '?') return '.';
+    return c.replace(/[.+^${}()|[\]\\]/g, '\\$&');
+}).join('')}$`, 'i');
+
+/**
+ * rule: { path, patterns?: string[], recursive?, format?: 'csv' | 'mt-csv' | 'hst',
+ *   namePattern?: regex source with (?<symbol>) and optional (?<timeframe>) groups,
+ *   symbol?, timeframe? (fixed values that override the file name), enabled? }
+ */
+const normalizeRule = (rule) => {
+    if (!rule || typeof rule.path !== 'string' || !rule.path.trim()) throw new Error('Watch folder path is required');
+    const format = rule.format || 'csv';
+    if (!['csv', 'mt-csv', 'hst'].includes(format)) throw new Error(`Unsupported format: ${format}`);
+    if (rule.timeframe && !TIMEFRAME_MS[rule.timeframe]) throw new Error(`Unknown timeframe: ${rule.timeframe}`);
+    if (rule.namePattern) {
+        try { new RegExp(rule.namePattern); } catch (e) { throw new Error(`Invalid name pattern: ${e.message}`); }
+    }
+    const patterns = Array.isArray(rule.patterns) && rule.patterns.length ? rule.patterns.map(String) : [format === 'hst' ? '*.hst' : '*.csv'];
+    return {
+        path: path.resolve(rule.path.trim()),
+        patterns,
+        recursive: rule.recursive !== false,
+        format,
+        namePattern: rule.namePattern || null,
+        symbol: rule.symbol || null,
+        timeframe: rule.timeframe || null,
+        enabled: rule.enabled !== false
+    };
+};
+
+// Matches a timeframe token exactly as the app names them ("1h", "1D", "15m")
+const TIMEFRAME_TOKEN = new RegExp(`^(.+?)[_\\-\\s.](${Object.keys(TIMEFRAME_MS).map(k => k.replace(/[.*+?^${}()|[\]\\]/g, '\\$&')).join('|')})$`);
+
+/**
+ * Symbol/timeframe for a file: the rule's fixed values win, then its
+ * namePattern, then "<SYMBOL>_<tf>", then `fallbackInfer` (MetaTrader-style names).
+ */
+const inferFileInfo = (filePath, rule, fallbackInfer = null) => {
+    const base = path.basename(filePath, path.extname(filePath));
+    let symbol = null, timeframe = null;
+    if (rule.namePattern) {
+        const m = new RegExp(rule.namePattern).exec(base);
+        if (m && m.groups) {
+            symbol = m.groups.symbol || null;
+            timeframe = m.groups.timeframe || null;
+        }
+    }
+    if (!symbol) {
+        const m = TIMEFRAME_TOKEN.exec(base);
+        if (m) { symbol = m[1]; timeframe = timeframe || m[2]; }
+    }
+    if ((!symbol || !timeframe) && fallbackInfer) {
+        const guess = fallbackInfer(filePath);
+        symbol = symbol || guess.symbol;
+        timeframe = timeframe || guess.timeframe;
+    }
+    if (!TIMEFRAME_MS[timeframe]) timeframe = null;
+    return { symbol: String(rule.symbol || symbol || base).toUpperCase(), timeframe: rule.timeframe || timeframe };
+};
+
+/**
+ * importFile(filePath, { symbol, timeframe, format }) -> { count }
+ * onSummary({ imported: [...], failed: [...] }) after each burst.
+ */
+const createWatchFolderService = ({ db, importFile, onSummary = () => {}, onError = () => {}, fallbackInfer = null }) => {
+    let rules = [];
+    let watchers = [];
+    const pending = new Map(); // filePath -> { rule, timer }
+    const queue = [];
+    let running = false;
+    let burst = { imported: [], failed: [] };
+    let summaryTimer = null;
+
+    const matches = (rule, filePath) => rule.patterns.some(p => globToRegex(p).test(path.basename(filePath)));
+
+    const alreadyImported = (filePath, stat) => {
+        const row = db.prepare('SELECT size, mtime, error FROM watch_imports WHERE file_path = ?').get(filePath);
+        return !!row && !row.error && row.size === stat.size && row.mtime === Math.floor(stat.mtimeMs);
+    };
+
+    const scheduleSummary = () => {
+        clearTimeout(summaryTimer);
+        summaryTimer = setTimeout(() => {
+            if (queue.length || running || pending.size) return scheduleSummary();
+            if (burst.imported.length || burst.failed.length) onSummary(burst);
+            burst = { imported: [], failed: [] };
+        }, SUMMARY_QUIET_MS);
+    };
+
+    const drain = async () => {
+        if (running) return;
+        running = true;
+        while (queue.length) {
+            const { filePath, rule } = queue.shift();
+            let stat;
+            try { stat = fs.statSync(filePath); } catch (e) { continue; }
+            if (alreadyImported(filePath, stat)) continue;
+            const info = inferFileInfo(filePath, rule, fallbackInfer);
+            const record = (rows, error) => db.prepare('INSERT OR REPLACE INTO watch_imports (file_path, size, mtime, symbol, timeframe, rows, error, imported_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)')
+                .run(filePath, stat.size, Math.floor(stat.mtimeMs), info.symbol, info.timeframe, rows, error, Date.now());
+            try {
+                if (!info.timeframe) throw new Error('Could not determine timeframe; set one on the watch rule');
+                const format = filePath.toLowerCase().endsWith('.hst') ? 'hst' : rule.format;
+                const result = await importFile(filePath, { ...info, format });
+                record(result.count || 0, null);
+                burst.imported.push({ filePath, ...info, rows: result.count || 0 });
+            } catch (err) {
+                record(0, err.message);
+                burst.failed.push({ filePath, ...info, error: err.message });
+                onError(filePath, err);
+            }
+        }
+        running = false;
+        scheduleSummary();
+    };
+
+    // Waits until the file's size holds still for SETTLE_MS, then queues it
+    const consider = (rule, filePath) => {
+        if (!matches(rule, filePath)) return;
+        const existing = pending.get(filePath);
+        if (existing) clearTimeout(existing.timer);
+        const check = (lastSize) => {
+            let stat;
+            try { stat = fs.statSync(filePath); } catch (e) { pending.delete(filePath); return; }
+            if (!stat.isFile()) { pending.delete(filePath); return; }
+            if (stat.size !== lastSize) {
+                pending.set(filePath, { rule, timer: setTimeout(() => check(stat.size), SETTLE_MS) });
+                return;
+            }
+            pending.delete(filePath);
+            if (alreadyImported(filePath, stat)) return;
+            if (!queue.some(q => q.filePath === filePath)) queue.push({ filePath, rule });
+            drain();
+        };
+        pending.set(filePath, { rule, timer: setTimeout(() => check(-1), SETTLE_MS) });
+    };
+
+    const scanRule = (rule) => {
+        const walk = (dir) => {
+            let entries = [];
+            try { entries = fs.readdirSync(dir, { withFileTypes: true }); } catch (e) { return; }
+            entries.forEach((entry) => {
+                const full = path.join(dir, entry.name);
+                if (entry.isDirectory()) { if (rule.recursive) walk(full); }
+                else consider(rule, full);
+            });
+        };
+        walk(rule.path);
+    };
+
+    const stop = () => {
+        watchers.forEach(w => w.close());
+        watchers = [];
+        pending.forEach(p => clearTimeout(p.timer));
+        pending.clear();
+        queue.length = 0;
+    };
+
+    // Replaces the active rules; returns per-rule watch status
+    const start = (nextRules = []) => {
+        stop();
+        rules = nextRules.map(normalizeRule);
+        return rules.map((rule) => {
+            if (!rule.enabled) return { path: rule.path, watching: false };
+            if (!fs.existsSync(rule.path)) return { path: rule.path, watching: false, error: 'Folder not found' };
+            try {
+                const watcher = fs.watch(rule.path, { recursive: rule.recursive }, (eventType, filename) => {
+                    if (filename) consider(rule, path.join(rule.path, filename.toString()));
+                });
+                watcher.on('error', (err) => onError(rule.path, err));
+                watchers.push(watcher);
+                scanRule(rule); // catch up on files dropped while we weren't watching
+                return { path: rule.path, watching: true };
+            } catch (err) {
+                return { path: rule.path, watching: false, error: err.message };
+            }
+        });
+    };
+
+    const rescan = () => rules.filter(r => r.enabled && fs.existsSync(r.path)).forEach(scanRule);
+
+    const status = () => ({
+        rules,
+        watching: watchers.length,
+        pending: pending.size + queue.length + (running ? 1 : 0)
+    });
+
+    return { start, stop, rescan, status };
+};
+
+const listWatchImports = (db, limit = 100) => db.prepare('SELECT file_path as filePath, size, symbol, timeframe, rows, error, imported_at as importedAt FROM watch_imports ORDER BY imported_at DESC LIMIT ?').all(limit);
+
+module.exports = { initializeWatchImportTable, normalizeRule, inferFileInfo, createWatchFolderService, listWatchImports };
@@ -0,0 +1,60 @@
+
+// --- WATCHLIST FILES ---
+// The watchlist itself lives in the renderer; these are the shareable files.
+// Exports are "redpill.watchlist" JSON documents. Imports also accept plain
+// text lists (one symbol per line or comma-separated, as TradingView exports
+// them: "###Section" headers are skipped and "EXCHANGE:" prefixes dropped).
+
+const WATCHLIST_FILE_FORMAT = 'redpill.watchlist';
+const WATCHLIST_FILE_VERSION = 1;
+const MAX_SYMBOLS = 5000;
+
+const normalizeSymbol = (value) => {
+    const symbol = String(value || '').trim().toUpperCase();
+    const bare = symbol.includes(':') ? symbol.slice(symbol.lastIndexOf(':') + 1) : symbol;
+    return /^[A-Z0-9._\-/=^!]{1,40}$/.test(bare) ? bare : null;
+};
+
+// items: [{ symbol, addedAt? }] in list order
+const buildWatchlistDocument = (items, { name = 'Watchlist' } = {}) => ({
+    format: WATCHLIST_FILE_FORMAT,
+    version: WATCHLIST_FILE_VERSION,
+    exportedAt: new Date().toISOString(),
+    name,
+    symbols: (items || []).map(item => normalizeSymbol(typeof item === 'string' ? item : item.symbol)).filter(Boolean)
+});
+
+const parseSymbolList = (content) => {
+    const text = String(content).replace(/^\uFEFF/, '').trim();
+    if (text.startsWith('{')) {
+        const document = JSON.parse(text);
+        if (document.format !== WATCHLIST_FILE_FORMAT) throw new Error('Not a Red Pill watchlist file');
+        if (document.version > WATCHLIST_FILE_VERSION) throw new Error(`Unsupported watchlist file version: ${document.version}`);
+        return { name: document.name || null, entries: Array.isArray(document.symbols) ? document.symbols : [] };
+    }
+    const entries = text.split(/[\r\n,]+/).map(s => s.trim()).filter(s => s && !s.startsWith('###'));
+    return { name: null, entries };
+};
+
+/**
+ * Parses a watchlist file against the symbols already listed. Returns
+ * { name, symbols (new, in file order), duplicates, invalid }.
+ */
+const parseWatchlistFile = (content, existing = []) => {
+    const { name, entries } = parseSymbolList(content);
+    if (entries.length > MAX_SYMBOLS) throw new Error(`Watchlist files are limited to ${MAX_SYMBOLS} symbols`);
+    const seen = new Set(existing.map(normalizeSymbol).filter(Boolean));
+    const result = { name, symbols: [], duplicates: [], invalid: [] };
+    entries.forEach((entry) => {
+        const symbol = normalizeSymbol(entry);
+        if (!symbol) result.invalid.push(String(entry));
+        else if (seen.has(symbol)) result.duplicates.push(symbol);
+        else {
+            seen.add(symbol);
+            result.symbols.push(symbol);
+        }
+    });
+    return result;
+};
+
+module.exports = { buildWatchlistDocument, parseWatchlistFile };
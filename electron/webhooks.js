@@ -0,0 +1,46 @@
+
+const { proxyFetch } = require('./proxy');
+
+// --- OUTGOING WEBHOOKS ---
+// POSTs JSON to user-configured URLs with exponential backoff. 4xx responses
+// (other than 429) are treated as permanent and not retried.
+
+const DEFAULT_RETRIES = 3;
+const BASE_DELAY_MS = 1000;
+const REQUEST_TIMEOUT_MS = 10000;
+
+const sleep = (ms) => new Promise(resolve => setTimeout(resolve, ms));
+
+const postJson = async (url, payload, { headers = {}, retries = DEFAULT_RETRIES } = {}) => {
+    let lastError = null;
+    for (let attempt = 0; attempt <= retries; attempt++) {
+        if (attempt > 0) await sleep(BASE_DELAY_MS * 2 ** (attempt - 1));
+        try {
+            const response = await proxyFetch(url, {
+                method: 'POST',
+                headers: { 'Content-Type': 'application/json', 'User-Agent': 'RedPillCharting/Webhook', ...headers },
+                body: JSON.stringify(payload),
+                signal: AbortSignal.timeout(REQUEST_TIMEOUT_MS)
+            });
+            if (response.ok) return { success: true, status: response.status, attempts: attempt + 1 };
+            lastError = new Error(`HTTP ${response.status} ${response.statusText}`);
+            if (response.status >= 400 && response.status < 500 && response.status !== 429) break;
+        } catch (err) {
+            lastError = err;
+        }
+    }
+    return { success: false, error: lastError ? lastError.message : 'Unknown error' };
+};
+
+const validateWebhookUrl = (url) => {
+    let parsed;
+    try {
+        parsed = new URL(url);
+    } catch (e) {
+        throw new Error(`Invalid webhook URL: ${url}`);
+    }
+    if (parsed.protocol !== 'https:' && parsed.protocol !== 'http:') throw new Error('Webhook URL must be http(s)');
+    return parsed.toString();
+};
+
+module.exports = { postJson, validateWebhookUrl };
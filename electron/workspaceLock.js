@@ -0,0 +1,156 @@
+
+const crypto = require('crypto');
+const fs = require('fs');
+const os = require('os');
+const path = require('path');
+
+// --- WORKSPACE LOCK ---
+// One writer per workspace: a lock file in the profile directory names the
+// host, pid and start time of the instance that has it open, with a heartbeat
+// refreshed while it runs. A lock is stale when its pid is gone on this host,
+// or when the heartbeat stopped (another machine, e.g. through a synced
+// folder). A live lock is only broken by an explicit takeover; the instance
+// that lost it notices on its next heartbeat and stops writing.
+// The file is always written whole — to a temp file, then linked (first
+// take) or renamed into place — so a reader never sees half a lock. A file
+// that still doesn't parse counts as held until its mtime is older than the
+// stale limit, never as free.
+
+const LOCK_FILE = 'workspace.lock';
+const HEARTBEAT_MS = 30 * 1000;
+const STALE_AFTER_MS = 3 * 60 * 1000;
+const READ_RETRIES = 3;
+const READ_RETRY_MS = 50;
+
+const sleepSync = ms => Atomics.wait(new Int32Array(new SharedArrayBuffer(4)), 0, 0, ms);
+
+const processAlive = (pid) => {
+    try {
+        process.kill(pid, 0);
+        return true;
+    } catch (err) {
+        return err.code === 'EPERM';
+    }
+};
+
+/**
+ * dir: the workspace directory. onLost(holder) runs once if another instance
+ * takes the lock over while this one holds it.
+ */
+const createWorkspaceLock = ({ dir, onLost = () => {} }) => {
+    const lockPath = path.join(dir, LOCK_FILE);
+    const instanceId = crypto.randomUUID();
+    let held = false;
+    let startedAt = null;
+    let timer = null;
+
+    const tmpPath = `${lockPath}.${instanceId.slice(0, 8)}.tmp`;
+
+    // null when there is no lock file; { unreadable: true, heartbeatAt: mtime }
+    // when it exists but won't parse after a few tries
+    const read = () => {
+        for (let attempt = 0; ; attempt++) {
+            let text;
+            try {
+                text = fs.readFileSync(lockPath, 'utf8');
+            } catch (e) {
+                if (e.code === 'ENOENT') return null;
+                throw e;
+            }
+            try {
+                return JSON.parse(text);
+            } catch (e) {
+                if (attempt + 1 >= READ_RETRIES) break;
+                sleepSync(READ_RETRY_MS);
+            }
+        }
+        let mtime = Date.now();
+        try { mtime = fs.statSync(lockPath).mtimeMs; } catch (e) {}
+        return { unreadable: true, hostname: '?', pid: null, startedAt: mtime, heartbeatAt: mtime };
+    };
+
+    const isStale = (holder) => {
+        if (!holder) return true;
+        if (holder.unreadable) return Date.now() - holder.heartbeatAt > STALE_AFTER_MS;
+        if (!holder.instanceId) return true;
+        if (holder.hostname === os.hostname() && holder.pid && !processAlive(holder.pid)) return true;
+        return Date.now() - (holder.heartbeatAt || holder.startedAt || 0) > STALE_AFTER_MS;
+    };
+
+    // exclusive: fail with EEXIST instead of replacing an existing lock
+    const write = ({ exclusive = false } = {}) => {
+        const content = JSON.stringify({ instanceId, hostname: os.hostname(), pid: process.pid, startedAt, heartbeatAt: Date.now() }, null, 2);
+        fs.writeFileSync(tmpPath, content);
+        try {
+            if (exclusive) fs.linkSync(tmpPath, lockPath);
+            else fs.renameSync(tmpPath, lockPath);
+        } finally {
+            fs.rmSync(tmpPath, { force: true });
+        }
+    };
+
+    const heartbeat = () => {
+        if (!held) return;
+        let holder;
+        try { holder = read(); } catch (e) { return; }
+        // Unreadable: try again on the next beat rather than declare the lock lost
+        if (holder && holder.unreadable) return;
+        if (holder && holder.instanceId !== instanceId) {
+            held = false;
+            stopHeartbeat();
+            onLost(holder);
+            return;
+        }
+        try { write(); } catch (e) { /* retried on the next beat */ }
+    };
+
+    const startHeartbeat = () => {
+        stopHeartbeat();
+        timer = setInterval(heartbeat, HEARTBEAT_MS);
+        if (timer.unref) timer.unref();
+    };
+
+    const stopHeartbeat = () => {
+        if (timer) clearInterval(timer);
+        timer = null;
+    };
+
+    /**
+     * Returns { acquired: true, replaced? } or { acquired: false, holder }.
+     * Stale locks are replaced silently; force takes a live one over.
+     */
+    const acquire = ({ force = false } = {}) => {
+        fs.mkdirSync(dir, { recursive: true });
+        startedAt = Date.now();
+        try {
+            write({ exclusive: true });
+        } catch (err) {
+            if (err.code !== 'EEXIST') throw err;
+            const holder = read();
+            const stale = isStale(holder);
+            if (!stale && !force && holder.instanceId !== instanceId) return { acquired: false, holder };
+            write();
+            held = true;
+            startHeartbeat();
+            return { acquired: true, replaced: holder ? { ...holder, stale } : null };
+        }
+        held = true;
+        startHeartbeat();
+        return { acquired: true };
+    };
+
+    // Removes the file only while it is still ours
+    const release = () => {
+        stopHeartbeat();
+        if (!held) return false;
+        held = false;
+        const holder = read();
+        if (holder && (holder.unreadable || holder.instanceId !== instanceId)) return false;
+        fs.rmSync(lockPath, { force: true });
+        return true;
+    };
+
+    return { acquire, release, isHeld: () => held, holder: read };
+};
+
+module.exports = { LOCK_FILE, createWorkspaceLock };
@@ -0,0 +1,87 @@
+
+const zlib = require('zlib');
+
+// --- MINIMAL ZIP WRITER ---
+// Just enough of PKZIP to bundle a handful of generated files (deflate only,
+// no zip64, no encryption), so exports don't pull in an archive dependency.
+
+const CRC_TABLE = (() => {
+    const table = new Uint32Array(256);
+    for (let n = 0; n < 256; n++) {
+        let c = n;
+        for (let k = 0; k < 8; k++) c = c & 1 ? 0xedb88320 ^ (c >>> 1) : c >>> 1;
+        table[n] = c >>> 0;
+    }
+    return table;
+})();
+
+const crc32 = (buf) => {
+    let crc = 0xffffffff;
+    for (let i = 0; i < buf.length; i++) crc = CRC_TABLE[(crc ^ buf[i]) & 0xff] ^ (crc >>> 8);
+    return (crc ^ 0xffffffff) >>> 0;
+};
+
+const dosDateTime = (date) => ({
+    time: (date.getHours() << 11) | (date.getMinutes() << 5) | Math.floor(date.getSeconds() / 2),
+    date: ((date.getFullYear() - 1980) << 9) | ((date.getMonth() + 1) << 5) | date.getDate()
+});
+
+/**
+ * entries: [{ name, data: Buffer | string }] -> zip archive Buffer
+ */
+const createZip = (entries) => {
+    const locals = [];
+    const centrals = [];
+    let offset = 0;
+    const { time, date } = dosDateTime(new Date());
+
+    entries.forEach(({ name, data }) => {
+        const raw = Buffer.isBuffer(data) ? data : Buffer.from(String(data), 'utf8');
+        const compressed = zlib.deflateRawSync(raw);
+        const nameBuf = Buffer.from(name, 'utf8');
+        const crc = crc32(raw);
+
+        const local = Buffer.alloc(30);
+        local.writeUInt32LE(0x04034b50, 0);
+        local.writeUInt16LE(20, 4);            // version needed
+        local.writeUInt16LE(0x0800, 6);        // UTF-8 names
+        local.writeUInt16LE(8, 8);             // deflate
+        local.writeUInt16LE(time, 10);
+        local.writeUInt16LE(date, 12);
+        local.writeUInt32LE(crc, 14);
+        local.writeUInt32LE(compressed.length, 18);
+        local.writeUInt32LE(raw.length, 22);
+        local.writeUInt16LE(nameBuf.length, 26);
+        local.writeUInt16LE(0, 28);
+        locals.push(local, nameBuf, compressed);
+
+        const central = Buffer.alloc(46);
+        central.writeUInt32LE(0x02014b50, 0);
+        central.writeUInt16LE(20, 4);          // version made by
+        central.writeUInt16LE(20, 6);
+        central.writeUInt16LE(0x0800, 8);
+        central.writeUInt16LE(8, 10);
+        central.writeUInt16LE(time, 12);
+        central.writeUInt16LE(date, 14);
+        central.writeUInt32LE(crc, 16);
+        central.writeUInt32LE(compressed.length, 20);
+        central.writeUInt32LE(raw.length, 24);
+        central.writeUInt16LE(nameBuf.length, 28);
+        central.writeUInt32LE(offset, 42);
+        centrals.push(central, nameBuf);
+
+        offset += local.length + nameBuf.length + compressed.length;
+    });
+
+    const centralSize = centrals.reduce((sum, b) => sum + b.length, 0);
+    const end = Buffer.alloc(22);
+    end.writeUInt32LE(0x06054b50, 0);
+    end.writeUInt16LE(entries.length, 8);
+    end.writeUInt16LE(entries.length, 10);
+    end.writeUInt32LE(centralSize, 12);
+    end.writeUInt32LE(offset, 16);
+
+    return Buffer.concat([...locals, ...centrals, end]);
+};
+
+module.exports = { createZip, crc32 };
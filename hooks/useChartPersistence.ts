@@ -77,11 +77,16 @@ export const useSymbolPersistence = ({
     };
   }, [loadState]);
 
+  // The debounced save not yet written (run right away when the app quits) and the one in flight
+  const pendingSaveRef = useRef<(() => Promise<void>) | null>(null);
+  const savingRef = useRef<Promise<void> | null>(null);
+
   // Save state on change (debounced)
   useEffect(() => {
     if (!symbol || isHydrating) return;
 
-    const handler = setTimeout(async () => {
+    const save = async () => {
+      if (pendingSaveRef.current === save) pendingSaveRef.current = null;
       const stateToSave: ChartState = {
         sourceId: symbol,
         timestamp: Date.now(),
@@ -107,12 +112,24 @@ export const useSymbolPersistence = ({
         console.error("Failed to save chart state:", e);
         debugLog('Data', `Persistence: Error saving state for ${symbol}`, e.message);
       }
-    }, 1000); // 1s debounce
+    };
+    pendingSaveRef.current = save;
+    const handler = setTimeout(() => { savingRef.current = save(); }, 1000); // 1s debounce
 
     return () => {
       clearTimeout(handler);
+      if (pendingSaveRef.current === save) pendingSaveRef.current = null;
     };
   }, [symbol, drawings, folders, config, visibleRange, isHydrating, electron]);
 
+  useEffect(() => {
+    if (!electron || !electron.onShutdownFlush) return;
+    return electron.onShutdownFlush(async () => {
+      const pending = pendingSaveRef.current;
+      if (pending) await pending();
+      else if (savingRef.current) await savingRef.current;
+    });
+  }, [electron]);
+
   return { isHydrating, rehydrate: loadState };
 };
@@ -90,6 +90,7 @@ export interface Drawing {
   properties: DrawingProperties;
   creationTimeframe?: Timeframe;
   folderId?: string | null;
+  groupId?: string | null;
 }
 
 export interface Folder {
@@ -98,6 +99,540 @@ export interface Folder {
   isExpanded: boolean;
 }
 
+// Drawings of a locked group can't be moved or deleted through patches
+export interface DrawingGroup {
+  id: string;
+  name: string;
+  locked?: boolean;
+}
+
+export interface PaletteAction {
+  id: string;
+  title: string;
+  category: string;
+  description: string | null;
+  args: Record<string, string> | null; // name -> type, '?' suffix = optional
+  shortcut: string | null;
+  handledBy: 'backend' | 'renderer'; // renderer actions arrive via onActionInvoked
+}
+
+export interface Keybinding {
+  action: string;
+  title: string;
+  accelerator: string | null; // Electron accelerator syntax, null = unbound
+  global: boolean; // registered with the OS, works while the app is in the background
+  isDefault: boolean;
+  registered: boolean | null; // global only: false when another app holds the shortcut
+}
+
+export interface BackgroundConfig {
+  recordLiveBars: boolean; // closed live bars are written to the bar store by the backend
+  minimizeToTray: boolean;
+  closeToTray: boolean;
+}
+
+export interface BackgroundStatus {
+  windows: { open: number; visible: number };
+  tray: boolean;
+  liveFeed: { paused: boolean; subscriptions: number };
+  recording: {
+    enabled: boolean;
+    streams: { provider: string; symbol: string; timeframe: string; recorded: number; lastBarAt: number | null }[];
+    barsRecorded: number;
+    buffered: number;
+    lastFlushAt: number | null;
+  };
+  alerts: { running: boolean; armed: number };
+  scheduler: { running: boolean; jobs: number };
+  watchFolders: number;
+}
+
+export interface PowerState {
+  mode: 'normal' | 'saver';
+  reason: 'override' | 'battery' | 'thermal' | 'cpu-throttled' | null;
+  override: 'auto' | 'normal' | 'saver';
+  onBattery: boolean;
+  thermalState: 'unknown' | 'nominal' | 'fair' | 'serious' | 'critical';
+  speedLimit: number;
+  // What the backend is doing about it
+  policy: { pollScale: number; backgroundIndexing: boolean; idleCompaction: boolean; deferBackups: boolean };
+}
+
+export type OnboardingStepId = 'welcome' | 'folders' | 'sample-data' | 'file-associations' | 'finish';
+
+export interface OnboardingState {
+  active: boolean; // false once finished (or skipped for an existing workspace)
+  currentStep: OnboardingStepId | null;
+  finishedAt: number | null;
+  finishReason: 'completed' | 'existing-workspace' | null;
+  workspace: { empty: boolean; datasets: number; charts: number; libraryFiles: number };
+  steps: {
+    id: OnboardingStepId;
+    optional: boolean;
+    hasAction: boolean; // completing it runs backend work
+    status: 'pending' | 'done' | 'skipped';
+    completedAt: number | null;
+    result: any; // folders: { folders, created }; sample-data: { datasets } (or a DemoInstallResult); file-associations: { registered, extensions?, reason? }
+  }[];
+}
+
+export interface DemoInstallResult {
+  symbol: string;
+  datasets: string[];
+  drawings: number;
+  boardId: string;
+  notes: string[];
+  alerts: string[];
+  watchlist: string[]; // for the renderer's watchlist to merge
+}
+
+export type SettingsSection = 'preferences' | 'keybindings' | 'themes' | 'providers' | 'layouts';
+
+export interface SettingsImportPlan {
+  version: number;
+  exportedAt: string | null;
+  appVersion: string | null;
+  sections: Partial<Record<SettingsSection, { added: string[]; changed: string[]; unchanged: string[] }>>; // setting keys, or theme names
+  invalid: { section: SettingsSection; key: string; error: string }[];
+  stripped: string[]; // credential fields left out at export; local values are kept
+  changes: number;
+}
+
+export interface InboxSignal {
+  id: number;
+  receivedAt: number;
+  time: number; // ms; where the chart marker goes
+  symbol: string;
+  side: 'long' | 'short' | 'flat' | null;
+  price: number | null;
+  text: string | null;
+  source: string | null;
+  data: Record<string, any> | null; // extra fields of a JSON push
+  readAt: number | null;
+}
+
+export interface InboxStatus {
+  enabled: boolean;
+  port: number;
+  notify: 'none' | 'desktop' | 'all';
+  hasToken: boolean;
+  listening: boolean;
+  url: string | null; // POST endpoint while listening
+}
+
+// Local socket for companion processes, newline-delimited JSON (see electron/companionIpc.js);
+// clients authenticate with the signal inbox token
+export interface CompanionIpcStatus {
+  enabled: boolean;
+  path: string | null; // configured socket / pipe path, null for the default
+  hasToken: boolean;
+  listening: boolean;
+  clients: number;
+  authorized: number;
+}
+
+export interface ClipboardWatchConfig {
+  enabled: boolean; // opt-in
+  useProviders: boolean; // also validate against provider searches, not just local symbols
+  pollMs: number;
+}
+
+export interface IdleConfig {
+  awayMinutes: number; // no input for this long pauses background work
+  autoLockMinutes: number; // 0 = never
+  lockOnScreenLock: boolean;
+  pauseBackgroundWork: boolean;
+}
+
+export type IdleReason = 'idle' | 'screen-lock' | 'suspend' | 'manual';
+
+export interface IdleState {
+  idleSeconds: number; // OS idle time at the last check
+  away: boolean;
+  awaySince: number | null;
+  awayReason: IdleReason | null;
+  locked: boolean;
+  lockedSince: number | null;
+  lockReason: IdleReason | null;
+}
+
+export type BackgroundJobType = 'time-index' | 'annotation-index' | 'thumbnail-sweep';
+
+export interface BackgroundJob {
+  id: number;
+  type: BackgroundJobType;
+  key: string; // file path, chart id, ... ('' for one-off sweeps)
+  priority: 'high' | 'normal' | 'low';
+  label: string;
+  status: 'pending' | 'running' | 'done' | 'failed' | 'canceled';
+  queuedAt: number;
+  startedAt: number | null;
+  finishedAt: number | null;
+  message: string | null; // summary when done, error when failed
+}
+
+export interface BackgroundJobsState {
+  paused: boolean;
+  pauseReason: 'user-active' | 'user-away' | 'power-saver' | null;
+  concurrency: { total: number; perType: Partial<Record<BackgroundJobType, number>> };
+  running: BackgroundJob[];
+  pending: BackgroundJob[]; // in the order they will start
+  recent: BackgroundJob[];  // finished, newest first
+}
+
+export interface AppearanceConfig {
+  mode: 'system' | 'dark' | 'light';
+  followScheme: boolean; // switch palettes when the effective scheme changes
+  darkTheme: string;
+  lightTheme: string;
+}
+
+export interface AppearanceState {
+  mode: AppearanceConfig['mode'];
+  scheme: 'dark' | 'light'; // effective, after the mode override
+  accentColor: string | null; // '#rrggbb', Windows and macOS only
+  highContrast: boolean;
+  invertedColorScheme: boolean;
+  config: AppearanceConfig;
+}
+
+// provider.refresh: { provider, symbol, timeframe, options? }; backup: { dir?, keep? };
+// report: { days?, dir? }; action: { id, args? } (any command palette action); scanner.run: { screenId };
+// calendar.refresh: {}; end-of-day: EndOfDayParams
+export type ScheduledJobType = 'provider.refresh' | 'backup' | 'report' | 'action' | 'scanner.run' | 'calendar.refresh' | 'end-of-day';
+
+export type EndOfDayStep = 'finalize-bars' | 'roll-daily' | 'rotate-logs' | 'prune-caches' | 'backup' | 'journal-report';
+
+export interface EndOfDayParams {
+  steps?: EndOfDayStep[]; // default: all, always run in the order above
+  rollDays?: number; // sessions closed in the last N days are rolled into 1D bars (default 3)
+  logKeepDays?: number; // rotated log files kept (default 14)
+  purgeTargets?: string[]; // storage purge targets (default ['stale_csv'])
+  backup?: { dir?: string; keep?: number };
+  reportDir?: string;
+  reportPdf?: boolean; // journal-report also prints a PDF copy (default true)
+  notify?: 'none' | 'desktop' | 'all'; // summary notification (default desktop)
+}
+
+export interface EndOfDayResult {
+  startedAt: number;
+  finishedAt: number;
+  status: 'success' | 'partial' | 'failed';
+  steps: { step: EndOfDayStep; status: 'done' | 'failed'; message: string | null; ms: number }[];
+}
+
+export interface ScheduledJob {
+  id: string;
+  name: string;
+  type: ScheduledJobType;
+  schedule: string; // cron "minute hour day month weekday" in local time, or @daily / @weekly / ...
+  params: Record<string, any>;
+  enabled: boolean;
+  catchUp: 'once' | 'skip'; // what to do with a run missed while the app was closed
+  lastRunAt: number | null;
+  nextRunAt: number | null;
+  createdAt: number;
+  running?: boolean;
+}
+
+export interface ScheduledJobRun {
+  id: number;
+  jobId: string;
+  trigger: 'schedule' | 'catch-up' | 'missed' | 'manual';
+  startedAt: number;
+  finishedAt: number;
+  status: 'success' | 'failed' | 'skipped' | 'missed';
+  message: string | null;
+}
+
+export interface SecretInfo {
+  key: string;
+  updatedAt: number;
+  provider: string;
+  expiresAt: number | null; // entered by the user or read from a JWT's exp claim
+  validatedAt: number | null;
+  validationStatus: 'valid' | 'invalid' | null;
+  validationMessage: string | null;
+}
+
+export interface ProxyConfig {
+  mode: 'none' | 'http' | 'socks5'; // http = CONNECT tunnel
+  host: string;
+  port: number;
+  username: string;
+  bypass: string[]; // host names, '*.example.com' / '.example.com' suffixes, or '*'
+}
+
+export interface ProxyTestResult {
+  success: boolean;
+  status?: number;
+  latencyMs: number;
+  via: string | null; // 'direct' or 'socks5://host:port'
+  error?: string;
+}
+
+export interface TlsPolicy {
+  strictTls: boolean; // default for providers without their own setting
+  // pins: SPKI hashes "sha256/<base64>" of any certificate in the chain
+  providers: Record<string, { strictTls?: boolean; pins: string[] }>;
+}
+
+export interface DownloadConfig {
+  concurrency: number; // jobs downloading at once (1-4)
+  bandwidthKbps: number; // pacing cap across all jobs, 0 = unlimited
+  chunkBars: number; // bars requested per provider call
+}
+
+export interface DownloadJob {
+  id: string;
+  provider: string;
+  symbol: string;
+  timeframe: string;
+  start: number;
+  end: number;
+  cursor: number; // everything before this is stored; resumes from here
+  status: 'queued' | 'running' | 'paused' | 'done' | 'failed' | 'canceled';
+  percent: number;
+  bars: number;
+  bytes: number; // estimated payload size
+  error: string | null;
+  options: Record<string, any>;
+  createdAt: number;
+  updatedAt: number;
+}
+
+export interface WatchFolderRule {
+  path: string;
+  patterns?: string[]; // file-name globs, default ['*.csv']
+  recursive?: boolean;
+  format?: 'csv' | 'mt-csv' | 'hst';
+  namePattern?: string | null; // regex with (?<symbol>) / (?<timeframe>) groups, matched on the base name
+  symbol?: string | null; // fixed symbol / timeframe override what the file name says
+  timeframe?: string | null;
+  enabled?: boolean;
+}
+
+export interface WatchImportRecord {
+  filePath: string;
+  size: number;
+  symbol: string;
+  timeframe: string | null;
+  rows: number;
+  error: string | null;
+  importedAt: number;
+}
+
+export interface WatchImportSummary {
+  imported: { filePath: string; symbol: string; timeframe: string; rows: number }[];
+  failed: { filePath: string; symbol: string; timeframe: string | null; error: string }[];
+  text: string;
+}
+
+export interface PlaybackExportOptions {
+  format?: 'gif' | 'mp4'; // mp4 needs ffmpeg on PATH or ffmpegPath
+  filePath?: string | null;
+  width?: number;
+  height?: number;
+  fps?: number;
+  windowBars?: number;
+  barsPerFrame?: number;
+  maxFrames?: number; // beyond this, bars per frame grows instead of the range being cut
+  loop?: boolean;
+  ffmpegPath?: string;
+  colors?: { background?: string; grid?: string; up?: string; down?: string; accent?: string };
+}
+
+export interface PlaybackExportResult {
+  success: boolean;
+  canceled?: boolean;
+  filePath?: string;
+  format?: 'gif' | 'mp4';
+  frames?: number;
+  barsPerFrame?: number;
+  width?: number;
+  height?: number;
+  durationSec?: number;
+  bytes?: number;
+  elapsedMs?: number;
+  error?: string;
+}
+
+export interface PresentationStatus {
+  active: boolean;
+  windowId?: number;
+  kiosk?: boolean;
+  dimmedDisplays?: number;
+  startedAt?: number;
+}
+
+export interface KeybindingSnapshot {
+  bindings: Keybinding[];
+  feedsPaused: boolean;
+}
+
+export interface Theme {
+  name: string;
+  base: 'dark' | 'light';
+  chart: Partial<Record<'upColor' | 'downColor' | 'wickUpColor' | 'wickDownColor' | 'borderUpColor' | 'borderDownColor' | 'backgroundColor' | 'backgroundTopColor' | 'backgroundBottomColor' | 'gridColor' | 'textColor' | 'crosshairColor' | 'lineColor' | 'smaColor' | 'volumeUpColor' | 'volumeDownColor', string>>;
+  notes: string[]; // swatches for notes and drawings
+  ui: Record<string, string>; // UI theme tokens
+}
+
+export interface ThemeInfo extends Theme {
+  builtin: boolean;
+  active?: boolean;
+  updatedAt: number | null;
+}
+
+export interface JournalStatus {
+  canUndo: boolean;
+  canRedo: boolean;
+  undoDepth: number;
+  redoDepth: number;
+}
+
+export interface ChartJournalEntry {
+  id: number;
+  undone: boolean;
+  createdAt: number;
+  // Summary by default; before/after snapshots when requested with full=true
+  ops: { op: 'add' | 'update' | 'delete'; drawingId: string; type?: string; before?: Drawing | null; after?: Drawing | null; index?: number }[];
+}
+
+export type JournalStepResult = { success: boolean; state?: ChartState; entryId?: number; ops?: { op: string; drawingId: string }[]; error?: string } & Partial<JournalStatus>;
+
+// Event from the cross-window sync bus. `origin` is the publishing window's id
+// (compare with getSyncSeq().windowId to skip your own); a seq jump means missed events.
+export interface SyncEnvelope<T = any> {
+  seq: number;
+  topic: string;
+  payload: T;
+  origin: number | null;
+  at: number;
+}
+
+export interface DrawingSyncPayload {
+  sourceId: string;
+  changes: { op: 'add' | 'update' | 'delete'; id: string; item: Drawing | null }[];
+  folders?: Folder[]; // present only when the folder list changed
+}
+
+export interface StickyNote {
+  id: string;
+  text: string;
+  color: string; // #RGB, #RRGGBB or #RRGGBBAA
+  tags: string[];
+  layout: Record<string, any>; // position / size, owned by the renderer
+  symbol: string | null;
+  boardId: string | null; // null: the default board
+  archivedAt: number | null;
+  inkData: NoteInkStroke[] | null; // handwritten strokes, if any
+  recognizedText: string | null; // text read from the ink by a registered recognizer; covered by search
+  createdAt: number;
+  updatedAt: number;
+}
+
+// Note-local pixel coordinates
+export interface NoteInkStroke {
+  points: { x: number; y: number }[];
+  color?: string;
+  width?: number;
+}
+
+export interface NoteBoard {
+  id: string;
+  name: string;
+  archived: boolean;
+  noteCount: number;
+  createdAt: number;
+  updatedAt: number;
+}
+
+// Setting inkData without recognizedText clears the recognized text
+export type StickyNoteFields = Partial<Pick<StickyNote, 'text' | 'color' | 'tags' | 'layout' | 'symbol' | 'boardId' | 'inkData' | 'recognizedText'>>;
+
+// Content a note had before a change; op is what replaced it
+export interface NoteVersion extends Pick<StickyNote, 'text' | 'color' | 'tags' | 'symbol' | 'boardId' | 'inkData'> {
+  version: number;
+  at: number;
+  op: 'update' | 'delete';
+}
+
+export type StickyNoteOp =
+  | { op: 'create'; note: StickyNoteFields & { id?: string } }
+  | { op: 'update'; id: string; changes: StickyNoteFields }
+  | { op: 'delete'; id: string }
+  | { op: 'recolor'; ids: string[]; color: string }
+  | { op: 'retag'; ids: string[]; set?: string[]; add?: string[]; remove?: string[] }
+  | { op: 'archive' | 'unarchive'; ids: string[] };
+
+// Placeholders: {{date}}, {{time}}, {{weekday}}, {{symbol}}, {{session}} (RTH / ETH / Overnight / Closed),
+// {{session_day}} and any caller variable; {{name|fallback}} gives a default
+export interface NoteTemplate {
+  id: string;
+  name: string;
+  text: string;
+  color: string | null;
+  tags: string[];
+  layout: Record<string, any>;
+  placeholders: string[];
+  createdAt: number;
+  updatedAt: number;
+}
+
+export interface AttachmentInfo {
+  id: string;
+  ref: string; // redpill-attachment://<id>, loadable as an image src
+  mime: 'image/png' | 'image/jpeg';
+  width: number | null;
+  height: number | null;
+  bytes: number;
+  source: string | null; // 'clipboard', 'capture', ...
+  createdAt: number;
+  existing?: boolean; // the same bytes were already stored
+}
+
+export interface ImageSaveOptions {
+  format?: 'png' | 'jpeg';
+  quality?: number; // JPEG, 1-100
+  maxWidth?: number; // downscale wider images
+}
+
+export interface CaptureOptions extends ImageSaveOptions {
+  destination?: 'attachment' | 'clipboard' | 'both'; // default 'attachment'
+}
+
+export interface CaptureResult {
+  success: boolean;
+  width?: number;
+  height?: number;
+  destination?: CaptureOptions['destination'];
+  attachment?: AttachmentInfo; // unless destination is 'clipboard'
+  error?: string;
+}
+
+// Auto-archive: minimized notes untouched for minimizedDays, any note untouched for untouchedDays (null = off)
+export interface NoteAutoArchiveConfig {
+  enabled: boolean;
+  minimizedDays: number | null;
+  untouchedDays: number | null;
+}
+
+// Payload of 'notes' sync events: final state of every note a batch touched
+export interface NoteSyncPayload {
+  changes: { op: 'add' | 'update' | 'delete'; id: string; item: StickyNote | null }[];
+  boards?: NoteBoard[]; // present only when boards changed (archived ones included)
+}
+
+export interface DrawingTemplateInfo {
+  name: string;
+  description: string | null;
+  count: number;
+  types: string[];
+  createdAt: number;
+  updatedAt: number;
+}
+
 export interface FileStreamState {
   file: File | null; // Nullable for Electron mode
   path?: string;     // Robust Bridge path
@@ -108,84 +643,1685 @@ export interface FileStreamState {
   fileSize: number;
 }
 
-export interface Trade {
+export interface Trade {
+  id: string;
+  sourceId: string; // Links trade to specific CSV/Data source
+  symbol: string;
+  side: 'buy' | 'sell';
+  type: 'market' | 'limit' | 'stop';
+  price: number; // Entry Price
+  qty: number;
+  value: number;
+  timestamp: number;
+  status: 'filled' | 'open' | 'cancelled';
+  pnl?: number;
+  exitPrice?: number;
+  mode?: 'live' | 'simulated'; // For replay trades
+  exitTimestamp?: number;
+  commission?: number;
+  currency?: string;
+  import?: { broker: string; fills: string[] }; // from a broker statement: the fill keys it was built from
+}
+
+export interface StatementImportResult {
+  success: boolean;
+  broker?: 'ibkr' | 'generic';
+  fills?: number;
+  duplicateFills?: number; // already imported earlier
+  likelyDuplicates?: { trade: Trade; existingId: string }[]; // matches a hand-entered trade; not added
+  skipped?: { line: number; reason: string }[];
+  added?: number;
+  updated?: number | Trade[]; // earlier open trades closed by this statement (the trades on dryRun)
+  trades?: Trade[];
+  error?: string;
+}
+
+export interface SimSettings {
+  slippageBps: number; // adverse slippage on market / stop fills
+  latencyMs: number; // market-time delay before an order can fill
+  commissionPerUnit: number;
+  commissionPct: number; // of notional
+  minCommission: number;
+}
+
+export interface SimOrderRequest {
+  accountId?: string;
+  symbol: string;
+  side: 'buy' | 'sell';
+  type?: 'market' | 'limit' | 'stop';
+  qty: number;
+  limitPrice?: number;
+  stopPrice?: number;
+  source?: 'live' | 'replay'; // which prices the order fills against
+  tag?: string;
+}
+
+export interface SimOrder extends Required<Omit<SimOrderRequest, 'limitPrice' | 'stopPrice' | 'tag'>> {
+  id: string;
+  limitPrice: number | null;
+  stopPrice: number | null;
+  tag: string | null;
+  status: 'working' | 'filled' | 'cancelled';
+  createdAt: number;
+  activeAt: number;
+  filledAt: number | null;
+  fillPrice: number | null;
+  reason: string | null;
+}
+
+export interface SimFill {
+  id: string;
+  orderId: string;
+  accountId: string;
+  symbol: string;
+  side: 'buy' | 'sell';
+  qty: number;
+  price: number;
+  commission: number;
+  realizedPnl: number; // net of commission
+  source: 'live' | 'replay';
+  tag: string | null;
+  timestamp: number; // market time of the fill
+}
+
+export interface SimPosition {
+  symbol: string;
+  qty: number; // negative = short
+  avgPrice: number;
+  realizedPnl: number;
+  markPrice: number | null;
+  unrealizedPnl: number;
+  updatedAt: number;
+}
+
+export interface SimAccount {
+  id: string;
+  name: string;
+  currency: string;
+  startingCash: number;
+  cash: number;
+  settings: SimSettings;
+  createdAt: number;
+}
+
+export interface SimAccountSummary extends SimAccount {
+  positions: SimPosition[];
+  realizedPnl: number;
+  unrealizedPnl: number;
+  equity: number;
+}
+
+export interface SymbolDossier {
+  symbol: string;
+  thesis: string; // may embed redpill-attachment:// refs like notes
+  keyLevels: { price: number; kind: 'support' | 'resistance' | 'target' | 'stop' | 'entry' | 'other'; label: string | null }[]; // highest first
+  links: { url: string; title: string | null }[];
+  attachments: { id: string; caption: string | null }[]; // images from attachments:paste-image
+  files: { path: string; name: string }[]; // local files by absolute path
+  tags: string[];
+  exists: boolean; // false: nothing saved yet (an empty dossier)
+  createdAt: number | null;
+  updatedAt: number | null;
+}
+
+export interface SymbolDossierSummary {
+  symbol: string;
+  summary: string; // first line of the thesis
+  levels: number;
+  links: number;
+  files: number;
+  tags: string[];
+  updatedAt: number;
+}
+
+export interface SymbolMeta {
+  symbol: string;
+  assetClass: 'stock' | 'future' | 'forex' | 'crypto' | string;
+  tickSize: number;
+  tickValue: number; // money per tick, in `currency`
+  multiplier: number;
+  currency: string;
+  qtyStep: number;
+  minQty: number;
+  custom: boolean; // true when saved overrides apply
+}
+
+export interface PositionSizeRequest {
+  account: string | number | { equity: number; currency?: string }; // paper account id, equity, or both
+  riskPct: number;
+  entry: number;
+  stop: number;
+  symbol: string;
+  fxRate?: number; // account currency per unit of the symbol's currency
+  targets?: number[];
+}
+
+// Money in the account currency
+export interface PositionSizeResult {
+  side: 'buy' | 'sell';
+  size: number;
+  riskBudget: number;
+  riskAmount: number;
+  riskPerUnit: number;
+  riskPctActual: number;
+  stopDistance: number;
+  stopTicks: number | null;
+  notional: number;
+  currency: string;
+  rLevels: { r: number; price: number; pnl: number }[];
+  targets: { price: number; r: number | null; pnl: number }[];
+  meta: SymbolMeta;
+  warnings: string[];
+}
+
+export type LiveExportSource =
+  | { kind: 'series'; datasetId: string; bars?: number; name?: string } // last bars (default 100) plus the forming live bar
+  | { kind: 'positions'; accountId?: string | null; name?: string }; // open paper positions
+
+export interface LiveExport {
+  id: string;
+  name: string;
+  filePath: string;
+  format: 'csv' | 'xlsx'; // csv: one source; xlsx: a sheet per source
+  intervalSeconds: number;
+  enabled: boolean;
+  sources: LiveExportSource[];
+  status: {
+    running: boolean;
+    writes: number;
+    lastWriteAt: number | null;
+    lastCheckAt: number | null;
+    locked: boolean; // the file is held open (Excel); retried every interval
+    lockedSince: number | null;
+    lockedSkips: number;
+    lastError: string | null;
+  };
+}
+
+export interface PortfolioFilter {
+  sources?: ('journal' | 'sim')[];
+  accountId?: string; // paper account
+  sourceId?: string; // journal source
+  mode?: 'live' | 'simulated';
+  symbols?: string[];
+  tags?: string[];
+  from?: number;
+  to?: number;
+  startingEquity?: number;
+  baseCurrency?: string; // default: the FX setting's base currency
+}
+
+export interface PortfolioSummary {
+  trades: number;
+  wins: number;
+  losses: number;
+  winRate: number;
+  netPnl: number;
+  grossProfit: number;
+  grossLoss: number;
+  profitFactor: number | null;
+  avgWin: number;
+  avgLoss: number;
+  largestWin: number;
+  largestLoss: number;
+  expectancy: number;
+  avgR: number | null;
+}
+
+export type PrintKind = 'chart' | 'notes' | 'report' | 'diary' | 'document';
+
+// Part of a 'document' print: the print:document arguments for one kind
+export interface PrintPart {
+  kind: Exclude<PrintKind, 'document'>;
+  id?: string | string[] | null; // chart source id, or note ids
+  options?: PrintOptions;
+}
+
+export interface PrintOptions {
+  title?: string;
+  // chart: snapshot from a stored attachment, else a capture of window ('main', a window id, or the caller)
+  attachmentId?: string;
+  window?: string | number;
+  snapshot?: boolean; // false = annotations only
+  annotations?: boolean;
+  boardId?: string; // notes, when no ids are given
+  filter?: PortfolioFilter; // report
+  range?: ActivityRange; // diary
+  currency?: string;
+  // page setup; without silent the system print dialog is shown
+  silent?: boolean;
+  deviceName?: string;
+  pageSize?: 'A3' | 'A4' | 'A5' | 'Legal' | 'Letter' | 'Tabloid';
+  landscape?: boolean;
+  margins?: 'default' | 'none' | 'printableArea';
+  copies?: number;
+  pdfPath?: string; // write a PDF instead of printing
+}
+
+export type ExportSource = 'notes' | 'journal' | 'scan' | 'activity';
+export type ExportFormat = 'markdown' | 'html' | 'csv' | 'text';
+
+export interface ExportTemplate {
+  id: string;
+  name: string;
+  source: ExportSource;
+  format: ExportFormat;
+  extension: string; // without the dot; defaults to the format's usual one
+  body: string;
+  fields: string[]; // placeholder paths the body uses
+  createdAt: number;
+  updatedAt: number;
+}
+
+export type ExportTemplateDraft = Pick<ExportTemplate, 'name' | 'source' | 'format' | 'body'> & Partial<Pick<ExportTemplate, 'id' | 'extension'>>;
+
+// Source query: notes { boardId?, search?, includeArchived?, tags?, symbol?, limit? },
+// journal { from?, to?, symbols?, tags?, sourceId?, status? }, scan { screenId } or
+// { expression, universe }, activity as for getActivityTimeline
+export type ExportQuery = Record<string, unknown>;
+
+export interface PortfolioStats extends PortfolioSummary {
+  filter: PortfolioFilter;
+  baseCurrency: string | null;
+  unconverted: { id: string; source: 'journal' | 'sim'; symbol: string; currency: string; timestamp: number }[]; // left out: no FX rate
+  startingEquity: number;
+  endingEquity: number;
+  returnPct: number | null;
+  from: number | null;
+  to: number | null;
+  maxDrawdown: { amount: number; pct: number | null; peakAt: number | null; troughAt: number | null; recoveredAt: number | null };
+  basis: 'returns' | 'pnl'; // what Sharpe / Sortino were computed on
+  sharpe: number | null;
+  sortino: number | null;
+  equityCurve: { timestamp: number; equity: number; pnl: number }[];
+  daily: { day: number; pnl: number; ret: number | null }[];
+  byTag: (PortfolioSummary & { key: string })[];
+  bySymbol: (PortfolioSummary & { key: string })[];
+  bySource: (PortfolioSummary & { key: string })[];
+}
+
+export interface MonteCarloParams {
+  method?: 'bootstrap' | 'shuffle'; // with / without replacement
+  tradesPerRun?: number; // bootstrap only; defaults to the number of historical trades
+  startingEquity?: number;
+  ruinDrawdownPct?: number;
+  percentiles?: number[];
+  bandPoints?: number;
+  seed?: number;
+}
+
+// Keys are 'mean' plus one 'p<N>' per requested percentile
+export type MonteCarloDistribution = { mean: number | null } & Record<string, number | null>;
+
+export interface MonteCarloResult {
+  iterations: number;
+  tradesPerRun: number;
+  historicalTrades: number;
+  method: 'bootstrap' | 'shuffle';
+  seed: number;
+  startingEquity: number;
+  finalEquity: MonteCarloDistribution;
+  returnPct: MonteCarloDistribution;
+  maxDrawdown: MonteCarloDistribution;
+  maxDrawdownPct: MonteCarloDistribution;
+  probabilityOfProfit: number;
+  probabilityOfRuin: number;
+  ruinDrawdownPct: number;
+  bands: ({ step: number } & MonteCarloDistribution)[]; // equity confidence bands by trade number
+}
+
+// Vega and rho per 1 point (0.01), theta per calendar day
+export interface OptionGreeks {
+  delta: number;
+  gamma: number;
+  vega: number;
+  theta: number;
+  rho: number;
+}
+
+export interface OptionPriceRequest {
+  type: 'call' | 'put';
+  spot: number;
+  strike: number;
+  expiry?: number; // ms; or timeYears
+  timeYears?: number;
+  valuationTime?: number;
+  rate?: number; // continuous, 0.05 = 5%
+  dividendYield?: number;
+  volatility?: number; // annual, 0.2 = 20%; implied from marketPrice when omitted
+  marketPrice?: number;
+  style?: 'european' | 'american';
+  model?: 'black-scholes' | 'binomial'; // default: binomial for american, black-scholes otherwise
+  steps?: number;
+}
+
+export interface OptionPriceResult {
+  model: 'black-scholes' | 'binomial';
+  style: 'european' | 'american';
+  timeYears: number;
+  volatility: number;
+  price: number;
+  intrinsic: number;
+  timeValue: number;
+  greeks: OptionGreeks;
+}
+
+export interface OptionContract {
+  strike: number;
+  type: 'call' | 'put';
+  bid?: number;
+  ask?: number;
+  last?: number;
+  iv?: number | null;
+  volume?: number;
+  openInterest?: number;
+  // Filled by computeChainGreeks
+  marketPrice?: number | null;
+  price?: number | null;
+  greeks?: OptionGreeks | null;
+}
+
+export interface OptionExpiration {
+  expiry: number;
+  style?: 'european' | 'american';
+  contracts: OptionContract[];
+  timeYears?: number;
+  atmStrike?: number | null;
+  atmIv?: number | null;
+}
+
+export interface OptionChain {
+  underlying: string;
+  spot: number;
+  rate?: number;
+  dividendYield?: number;
+  valuationTime?: number;
+  expirations: OptionExpiration[];
+}
+
+// FX: rate = quote units per one base unit
+export interface FxConfig {
+  baseCurrency: string;
+  autoFetch: boolean; // fetch missing ECB reference rates
+}
+
+export interface FxRate {
+  base: string;
+  quote: string;
+  day: string; // YYYY-MM-DD (UTC)
+  rate: number;
+  source: 'manual' | 'ecb';
+  updatedAt: number;
+}
+
+export interface FxLookup {
+  rate: number;
+  day: string; // day of the rate used (latest on or before the requested one)
+  source: string;
+  via: string | null; // cross currency
+  stale: boolean;
+}
+
+// Trading session calendar (see electron/sessions.js); times are local "HH:MM",
+// a window closing at or before its open starts the previous evening
+export interface SessionCalendar {
+  id?: string;
+  name?: string;
+  timezone: string;
+  rth: { open: string; close: string };
+  eth?: { open: string; close: string };
+  weekdays?: number[]; // 0 = Sunday
+  holidays?: string[]; // YYYY-MM-DD
+  overnight?: boolean; // false: round-the-clock market, no overnight range
+}
+
+export interface SessionRange { start: number; end: number; high: number; low: number }
+
+export interface SessionDayStats {
+  day: string; // local date the session closes on
+  sessionStart: number;
+  sessionEnd: number;
+  open: number | null;
+  high: number | null;
+  low: number | null;
+  close: number | null;
+  highAt: number | null;
+  lowAt: number | null;
+  volume: number;
+  vwap: number | null;
+  bars: number;
+  openingRange: (SessionRange & { minutes: number }) | null;
+  overnight: SessionRange | null;
+  eth: (SessionRange & { vwap: number | null; volume: number }) | null;
+}
+
+// Where a bar falls relative to its day's regular session
+export type SessionSegment = 'pre' | 'regular' | 'post';
+
+export interface SegmentRange {
+  start: number; // first and last bar
+  end: number;
+  high: number;
+  low: number;
+  highAt: number;
+  lowAt: number;
+  volume: number;
+  bars: number;
+}
+
+export interface ExtendedHoursLevels {
+  calendar: { id: string; name: string | null; timezone: string };
+  days: { day: string; pre: SegmentRange | null; regular: SegmentRange | null; post: SegmentRange | null }[];
+  // previous day's after-hours plus today's pre-market
+  latest: { day: string; pre: SegmentRange | null; post: SegmentRange | null; high: number | null; low: number | null } | null;
+}
+
+export type SessionLevelKey = 'pdh' | 'pdl' | 'pdc' | 'open'; // yesterday's high / low / close, today's open
+
+export interface SessionLevelConfig {
+  enabled: boolean; // refresh the lines whenever a chart is opened with its dataset
+  levels: SessionLevelKey[];
+  calendar: string | SessionCalendar | null; // null: by the symbol's asset class
+}
+
+// Lines are horizontal_line drawings in the 'session-levels' folder with properties.sessionLevel set
+export interface SessionLevelSync {
+  datasetId: string;
+  today: { day: string; start: number; open: number | null } | null;
+  previous: { day: string; start: number; high: number | null; low: number | null; close: number | null } | null;
+  levels: { key: SessionLevelKey; label: string; price: number; time: number }[];
+  changed: boolean;
+  added: number;
+  updated: number;
+  removed: number;
+}
+
+export type SeasonalityGrouping = 'month' | 'week' | 'weekday' | 'hour';
+
+// Percentile bands are keyed p10, p25, p75, p90 (or the requested percentiles)
+export interface SeasonalityBucket {
+  bucket: number; // month 1-12, ISO week 1-53, weekday 1-7 (Mon-Sun) or hour 0-23
+  label: string;
+  count: number;
+  mean: number | null; // % return
+  median: number | null;
+  stdDev: number | null;
+  positiveRatio: number;
+  [percentile: `p${number}`]: number | null;
+}
+
+export interface SeasonalityPathPoint {
+  bucket: number;
+  label: string;
+  mean: number | null; // % compounded from the start of the cycle
+  median: number | null;
+  [percentile: `p${number}`]: number | null;
+}
+
+export interface SeasonalityResult {
+  grouping: SeasonalityGrouping;
+  timezone: string;
+  cycles: number; // finished cycles (years, weeks or days) in the path
+  bars: number;
+  from: number | null;
+  to: number | null;
+  buckets: SeasonalityBucket[];
+  path: SeasonalityPathPoint[];
+  current: { bucket: number; label: string; value: number }[] | null; // the unfinished latest cycle
+}
+
+export interface RelativeStrengthResult {
+  datasetId: string;
+  benchmarkId: string;
+  mode: 'ratio' | 'beta';
+  window?: number; // beta: returns per estimate
+  aligned: number; // points with a benchmark value
+  missing: number; // dataset bars without one
+  // ratio: [timestamp, ratio, relative (both rebased to 100)]; beta: [timestamp, beta, alpha (% per bar), correlation]
+  columns: string[];
+  rows: [number, number | null, number | null, number | null?][];
+}
+
+export type EconomicImpact = 'high' | 'medium' | 'low' | 'holiday' | 'none';
+
+export interface EconomicEvent {
+  id: string;
+  source: string; // 'forexfactory' or 'file:<name>'
+  timestamp: number;
+  allDay: boolean;
+  currency: string | null; // 'ALL' for global events
+  title: string;
+  impact: EconomicImpact;
+  actual: string | null;
+  forecast: string | null;
+  previous: string | null;
+}
+
+export type ICalKind = 'jobs' | 'events';
+
+export interface ICalExportOptions {
+  horizonDays?: number; // default 30
+  currencies?: string[]; // economic events
+  impact?: EconomicImpact | EconomicImpact[];
+  name?: string; // calendar name shown by the calendar app
+}
+
+export interface EconomicCalendarConfig {
+  provider: 'forexfactory' | null;
+  refreshHours: number;
+  warnings: { enabled: boolean; minutesBefore: number; impact: EconomicImpact[]; currencies: string[]; notify: boolean };
+}
+
+export interface EconomicEventFilter {
+  from?: number;
+  to?: number;
+  currencies?: string[];
+  symbol?: string; // derives currencies from the symbol when none are given
+  impact?: EconomicImpact | EconomicImpact[]; // a single level means "at least"
+  limit?: number;
+}
+
+// Indicator expressions, e.g. "rsi(14) < 30 and close > sma(200)" or, as a value,
+// "ema(close, 20) - ema(close, 50)" (see electron/expressions.js)
+export interface ExpressionValidation {
+  valid: boolean;
+  error?: string;
+  position?: number | null; // 0-based offset of the problem
+  indicators?: string[]; // e.g. ['rsi(14)', 'sma(200)']
+  fields?: string[];
+  lookback?: number; // bars needed
+}
+
+export interface ScreenUniverse {
+  timeframe?: string; // alone: every dataset of this timeframe
+  symbols?: string[]; // watchlist symbols (with timeframe)
+  datasets?: string[];
+}
+
+export interface ScannerScreen {
+  id: string;
+  name: string;
+  expression: string;
+  universe: ScreenUniverse;
+  lastRun: { ranAt: number; scanned: number; matched: number; symbols: string[]; errors: number } | null;
+  createdAt: number;
+  updatedAt: number;
+}
+
+export interface ScanResult {
+  screenId: string | null;
+  name: string | null;
+  expression: string;
+  ranAt: number;
+  durationMs: number;
+  scanned: number;
+  cached?: number; // datasets answered from the compute cache
+  matches: { datasetId: string; symbol: string; timestamp: number; values: Record<string, number | null> }[];
+  errors: { datasetId: string; error: string }[];
+}
+
+export type HeatmapMetric = 'change' | 'relativeVolume' | { script: string };
+
+export interface HeatmapRange {
+  timeframe: string;
+  periods?: number; // bars the change / volume window spans (default 1)
+  baseline?: number; // relativeVolume: windows averaged before it (default 20)
+}
+
+export interface Heatmap {
+  id: string | null; // set for live heatmaps
+  metric: 'change' | 'relativeVolume' | 'script';
+  script: string | null;
+  timeframe: string;
+  periods: number;
+  baseline: number | null;
+  computedAt: number | null;
+  columns: ['symbol', 'value', 'close', 'timestamp'];
+  rows: [string, number | null, number | null, number | null][];
+  min: number | null;
+  max: number | null;
+  errors: Record<string, string>;
+  durationMs?: number;
+}
+
+export interface HeatmapUpdate {
+  id: string;
+  computedAt: number;
+  cells: [string, number | null, number | null, number | null][]; // changed rows only
+  min: number | null;
+  max: number | null;
+}
+
+export interface BrokerInfo {
+  id: string;
+  name: string;
+  status: 'disconnected' | 'connected' | 'degraded';
+  mode: 'paper' | 'live';
+  message?: string | null;
+}
+
+export interface BrokerOrderRequest {
+  symbol: string;
+  side: 'buy' | 'sell';
+  type?: 'market' | 'limit' | 'stop' | 'stop_limit';
+  qty: number;
+  limitPrice?: number;
+  stopPrice?: number;
+  timeInForce?: 'day' | 'gtc' | 'ioc' | 'fok';
+  extendedHours?: boolean;
+}
+
+export interface BrokerOrder {
+  id: string;
+  broker: string;
+  clientOrderId: string;
+  symbol: string;
+  side: 'buy' | 'sell';
+  type: string;
+  qty: number | null;
+  filledQty: number;
+  avgFillPrice: number | null;
+  limitPrice: number | null;
+  stopPrice: number | null;
+  timeInForce: string;
+  status: string; // broker status: 'new', 'accepted', 'partially_filled', 'filled', 'canceled', 'replaced', 'rejected', ...
+  replacedBy?: string | null;
+  submittedAt: number | null;
+  updatedAt: number;
+}
+
+// declined: the user dismissed the native confirmation dialog
+export interface BrokerOrderResult {
+  success: boolean;
+  order?: BrokerOrder | null;
+  error?: string;
+  declined?: boolean;
+}
+
+export interface WatchlistItem {
+  symbol: string;
+  addedAt: number;
+}
+
+export interface HistorySnapshot {
+  drawings: Drawing[];
+  folders: Folder[];
+  visibleRange: { from: number; to: number } | null;
+}
+
+export interface ChartState {
+  sourceId: string;
+  timestamp: number;
+  drawings: Drawing[];
+  folders?: Folder[];
+  groups?: DrawingGroup[];
+  config: ChartConfig;
+  visibleRange: { from: number; to: number } | null;
+}
+
+// Writes to the database by another process (sync client, second instance,
+// manual edit); valid items also arrive as 'drawings' / 'notes' sync events
+export interface ExternalDatabaseChange {
+  at: number;
+  replaced: boolean; // the database file was swapped out; restart to load it
+  restartRequired?: boolean;
+  drawings: { sourceId: string; op: 'add' | 'update' | 'delete'; valid: boolean; error?: string }[];
+  notes: { id: string; op: 'add' | 'update' | 'delete'; valid: boolean; error?: string }[];
+}
+
+export interface ExternalChangeStatus {
+  running: boolean;
+  watching: boolean; // directory watch active (the poll runs regardless)
+  dataVersion: number | null;
+  lastChangeAt: number | null;
+  changesSeen: number;
+  replaced: boolean;
+}
+
+// An opened .rpshare file: the chart and the bars it was shared with, for
+// read-only viewing — nothing of it is stored
+export interface SharedChart {
+  filePath: string;
+  sourceId: string;
+  createdAt: string; // ISO-8601
+  appVersion: string | null;
+  protected: boolean; // password-protected
+  readOnly: true;
+  state: ChartState;
+  thumbnail: { dataUrl: string; width: number; height: number } | null;
+  datasets: { id: string; symbol: string; timeframe: string; bars: { timestamp: number; open: number; high: number; low: number; close: number; volume: number }[] }[];
+}
+
+// Report of the checks every chart state save runs; errors reject the save,
+// warnings describe what was normalized
+export interface ChartStateValidation {
+  valid: boolean;
+  errors: { path: string; message: string }[]; // path e.g. "drawings[3].points[1].price"
+  warnings: { path: string; message: string }[];
+  unknownTypes: string[]; // drawing types kept unchanged
+  drawings: number;
+}
+
+// JSON Patch op on a stored chart state; '@<id>' selects an array element by id
+// (e.g. "/drawings/@d-42/points/1/price"), '-' appends
+export type ChartPatchOp =
+  | { op: 'add' | 'replace' | 'test'; path: string; value: any }
+  | { op: 'remove'; path: string }
+  | { op: 'move' | 'copy'; from: string; path: string };
+
+export interface ChartLockViolation {
+  id: string; // drawing id
+  change: 'move' | 'delete';
+}
+
+export interface TabSession {
+  id: string;
+  title: string;
+  symbolId: string; // Namespace-aware ID (e.g., "FOREX_GOLD")
+  sourceId: string; // Persistent unique ID for drawings/trades
+  rawData: OHLCV[];
+  data: OHLCV[];
+  timeframe: Timeframe;
+  config: ChartConfig;
+  
+  fileState?: FileStreamState;
+  filePath?: string; 
+
+  isReplayMode: boolean;
+  isAdvancedReplayMode: boolean;
+  isReplaySelecting: boolean; 
+  replayIndex: number;
+  replayGlobalTime: number | null; 
+  simulatedPrice: number | null; 
+  isReplayPlaying: boolean;
+  replaySpeed: number; 
+  isDetached: boolean;
+  
+  // Persisted UI State
+  isMarketOverviewOpen: boolean;
+
+  visibleRange: { from: number; to: number } | null;
+  trades: Trade[];
+  drawings: Drawing[];
+  folders: Folder[];
+  undoStack: HistorySnapshot[];
+  redoStack: HistorySnapshot[];
+}
+
+export interface SanitizationStats {
+  fixedZeroes: number;
+  fixedLogic: number;
+  filledGaps: number;
+  outliers: number;
+  totalRecords: number;
+}
+
+export interface ImportProgressEvent {
+  type: 'progress';
+  taskId: string;
+  symbol: string;
+  timeframe: string;
+  filePath: string;
+  bytesProcessed: number;
+  totalBytes: number;
+  rowsParsed: number;
+  rowsRejected: number;
+  elapsedMs: number;
+  percent: number | null;
+  etaMs: number | null;
+}
+
+export interface ImportCompleteEvent {
+  taskId: string;
+  success: boolean;
+  symbol: string;
+  timeframe: string;
+  filePath: string;
+  count?: number;
+  rowsParsed?: number;
+  rowsRejected?: number;
+  errors?: { line: number; text: string; reason: string }[];
+  totalBytes?: number;
+  durationMs?: number;
+  error?: string;
+}
+
+// Sparse timestamp -> byte offset index kept for large CSV sources
+export interface TimeIndexSummary {
+  filePath: string;
+  size: number;
+  mtime: number;
+  format: 'csv' | 'mt-csv';
+  ascending: boolean;
+  firstTs: number | null;
+  lastTs: number | null;
+  rowCount: number;
+  entryCount: number;
+  builtAt: number;
+}
+
+export interface DatasetInfo {
+  id: string; // `${symbol}:${timeframe}`
+  symbol: string;
+  timeframe: string;
+  source: string;
+  rowCount: number;
+  firstTimestamp: number | null;
+  lastTimestamp: number | null;
+  meta: Record<string, any>;
+  createdAt: number;
+  updatedAt: number;
+}
+
+// Parameters for generated demo data; the resolved spec (seed included) is kept in the dataset meta
+export interface SyntheticSeriesSpec {
+  symbol?: string;
+  timeframe?: string;
+  bars?: number;
+  startPrice?: number;
+  model?: 'random-walk' | 'gbm';
+  volatility?: number; // per bar
+  trend?: number; // per-bar drift
+  gapProbability?: number;
+  gapSize?: number;
+  session?: { startHour?: number; endHour?: number; weekdays?: number[] } | null; // UTC
+  startTime?: number | null;
+  seed?: number | null;
+}
+
+// Continuous futures: contract datasets stitched at their rolls (see electron/continuousFutures.js)
+export interface ContinuousFuturesSpec {
+  contracts: (string | { datasetId: string; expiry?: number; openInterest?: [number, number][] })[];
+  symbol?: string; // default: root + '1!'
+  roll?: { rule?: 'date' | 'volume' | 'open_interest'; daysBeforeExpiry?: number; confirmDays?: number };
+  adjustment?: 'none' | 'difference' | 'ratio';
+}
+
+export interface ContinuousRoll {
+  timestamp: number; // first bar of the new contract
+  from: string;
+  to: string;
+  reason: string;
+  frontClose?: number | null;
+  nextClose?: number | null;
+  gap: number;
+  ratio: number;
+}
+
+// Overlapping histories of one instrument merged by source priority (see electron/datasetMerge.js)
+export interface DatasetMergeSpec {
+  datasets: string[];
+  priority?: string[]; // dataset ids or source names, first wins; default: the order of datasets
+  symbol?: string; // default: the top source's symbol + '-merged'
+  tolerance?: { price?: number | null; volume?: number | null }; // relative; price default 0.001, volume off
+}
+
+export interface DatasetMergeConflict {
+  timestamp: number;
+  winner: string; // dataset whose bar was kept
+  other: string;
+  fields: Partial<Record<'open' | 'high' | 'low' | 'close' | 'volume', [number, number]>>; // [kept, other]
+}
+
+export interface DatasetMergeReport {
+  symbol: string;
+  timeframe: string;
+  bars: number;
+  coverage: Record<string, number>; // bars taken from each dataset
+  overlaps: number;
+  conflicts: DatasetMergeConflict[]; // first 1000
+  conflictCount: number;
+  conflictsBySource: Record<string, number>;
+  spec: DatasetMergeSpec;
+}
+
+export interface BarValues {
+  open: number;
+  high: number;
+  low: number;
+  close: number;
+  volume: number;
+}
+
+export interface BarEdit {
+  id: number;
+  datasetId: string;
+  timestamp: number;
+  action: 'edit' | 'delete' | 'revert';
+  before: BarValues | null;
+  after: BarValues | null; // null for deletes
+  reason: string | null;
+  createdAt: number;
+  revertedAt: number | null;
+  revertOf: number | null; // a revert: the edit it undid
+}
+
+export interface BarRecompute {
+  streams: number; // indicator streams reseeded
+  derived: { id: string; rebuilt: boolean; skipped?: string; error?: string }[];
+}
+
+export interface BarsChangedEvent {
+  datasetId: string;
+  timestamps: number[];
+  derived: string[]; // derived datasets rebuilt
+}
+
+export interface DatasetRowsPage {
+  success: boolean;
+  columns?: string[]; // ['timestamp', 'open', 'high', 'low', 'close', 'volume']
+  rows?: number[][];
+  offset?: number;
+  limit?: number;
+  total?: number;
+  sort?: { column: string; direction: 'asc' | 'desc' };
+  error?: string;
+}
+
+export interface LiveBarEvent {
+  provider: string;
+  symbol: string;
+  timeframe: string;
+  bar: { timestamp: number; open: number; high: number; low: number; close: number; volume: number };
+  isClosed: boolean;
+  segment?: SessionSegment; // intraday bars, from the symbol's session calendar
+}
+
+export interface ProviderStatusEvent {
+  provider: string;
+  status: 'connecting' | 'connected' | 'degraded' | 'disconnected';
+  message: string | null;
+  timestamp: number;
+}
+
+// From the shared retry / circuit-breaker layer (REST calls only)
+export interface ProviderHealthEvent {
+  provider: string;
+  status: 'healthy' | 'degraded' | 'down'; // degraded: failing or retrying; down: circuit open
+  message: string | null; // e.g. "retrying (1/3): HTTP 503 Service Unavailable"
+  endpoint: string | null;
+  at: number;
+}
+
+export interface ProviderHealthSnapshot {
+  providers: ProviderHealthEvent[];
+  endpoints: { key: string; provider: string; state: 'closed' | 'open' | 'half-open'; failures: number; lastError: string | null; reopensAt: number | null }[];
+}
+
+export interface LiveQuoteEvent {
+  provider: string;
+  symbol: string;
+  bid: number;
+  ask: number;
+  bidSize: number;
+  askSize: number;
+  timestamp: number;
+}
+
+export interface ProviderSymbol {
+  symbol: string;
+  name?: string | null;
+  baseAsset?: string;
+  quoteAsset?: string;
+  assetClass: string;
+  exchange: string;
+}
+
+// Merged across providers and the local store, best match first
+export interface SymbolSearchResult {
+  symbol: string;
+  name: string | null;
+  exchange: string | null;
+  assetClass: string | null;
+  providers: string[];
+  local: { datasets: { id: string; timeframe: string; source: string }[] } | null;
+  score: number;
+}
+
+export interface ProviderCapabilities {
+  history: boolean;
+  live: boolean;
+  search: boolean;
+  credentials: boolean;
+  depth: boolean;
+}
+
+// Throttled top of book; [price, size] levels, best first
+export interface DepthSnapshot {
+  provider: string;
+  symbol: string;
+  bids: [number, number][];
+  asks: [number, number][];
+  bestBid: number | null;
+  bestAsk: number | null;
+  mid: number | null;
+  spread: number | null;
+  spreadBps: number | null;
+  bidSize: number;
+  askSize: number;
+  imbalance: number | null; // (bidSize - askSize) / total of the shown levels, -1..1
+  levelCount: { bids: number; asks: number };
+  synced: boolean; // false while waiting for a fresh snapshot after a sequence gap
+  sequence: number | null;
+  updatedAt: number | null;
+}
+
+export interface OrderFlowConfig {
+  enabled: boolean;
+  symbols: string[]; // empty = every symbol on the live feed
+  retentionDays: number;
+}
+
+export interface TradePrint {
+  provider: string;
+  timestamp: number;
+  price: number;
+  size: number;
+  side: 1 | -1 | 0; // aggressor: 1 buy, -1 sell, 0 unknown
+}
+
+export interface DeltaBar {
+  timestamp: number;
+  volume: number;
+  buyVolume: number;
+  sellVolume: number;
+  delta: number;
+  cumulativeDelta: number;
+  trades: number;
+  maxDelta: number; // intrabar extremes of the running delta
+  minDelta: number;
+}
+
+export interface FootprintBar {
+  timestamp: number;
+  open: number;
+  high: number;
+  low: number;
+  close: number;
+  volume: number;
+  delta: number;
+  poc: number | null; // price level with the most volume
+  levels: [number, number, number][]; // [price, sellVolume, buyVolume], high to low
+}
+
+export interface ProviderSettingField {
+  key: string;
+  label: string;
+  type: 'string' | 'url' | 'number' | 'boolean' | 'select';
+  options?: string[];
+  default: any;
+}
+
+export interface ProviderInfo {
+  id: string;
+  name: string;
+  status: ProviderStatusEvent['status'];
+  capabilities: ProviderCapabilities;
+  settings: ProviderSettingField[];
+  secrets: string[]; // secrets-store keys the provider reads
+  [key: string]: any;
+}
+
+export interface NewsItem {
+  id: string;
+  feed: string;
+  title: string;
+  link: string;
+  summary: string;
+  published: number;
+  symbols: string[];
+}
+
+export interface NewsConfig {
+  feeds: { url: string; name?: string }[];
+  symbols: string[];
+  keywords: Record<string, string[]>;
+  intervalMinutes: number;
+  retentionDays: number;
+}
+
+export type AlertCondition = 'crosses_above' | 'crosses_below' | 'above' | 'below' | 'touches' | 'expression';
+
+export interface PriceAlert {
   id: string;
-  sourceId: string; // Links trade to specific CSV/Data source
   symbol: string;
-  side: 'buy' | 'sell';
-  type: 'market' | 'limit' | 'stop';
-  price: number; // Entry Price
-  qty: number;
-  value: number;
-  timestamp: number;
-  status: 'filled' | 'open' | 'cancelled';
-  pnl?: number;
-  exitPrice?: number;
-  mode?: 'live' | 'simulated'; // For replay trades
+  condition: AlertCondition;
+  price: number | null; // drawing alerts: the level when saved; expression alerts: null
+  message: string;
+  options: {
+    webhookUrl?: string; webhook?: boolean; notify?: boolean; sound?: string | false;
+    drawing?: { sourceId: string; drawingId: string }; // level follows a saved line / ray / trend line
+    extend?: boolean; // trend lines: keep following past their end points
+    tolerance?: number; // 'touches': price distance that counts as a touch
+    expression?: string; // 'expression' alerts, e.g. "rsi(14) crosses_above 70"
+    timeframe?: string; // bars the expression runs on
+    intrabar?: boolean; // also evaluate forming bars
+    [key: string]: any;
+  };
+  enabled: boolean;
+  once: boolean;
+  triggeredAt: number | null;
+  createdAt: number;
 }
 
-export interface WatchlistItem {
+export interface AlertTriggeredEvent {
+  event: 'alert.triggered';
+  alertId: string;
   symbol: string;
-  addedAt: number;
+  condition: AlertCondition;
+  level: number | null;
+  price: number;
+  message: string;
+  expression?: string;
+  values?: Record<string, number | null>; // indicator values that triggered an expression alert
+  drawing?: { sourceId: string; drawingId: string };
+  replay?: boolean; // fired by chart replay, not the live feed
+  time: string;
+  timestamp: number;
 }
 
-export interface HistorySnapshot {
-  drawings: Drawing[];
-  folders: Folder[];
-  visibleRange: { from: number; to: number } | null;
+export interface AlertWebhookConfig {
+  enabled: boolean;
+  url: string;
+  headers: Record<string, string>;
+  retries: number;
 }
 
-export interface ChartState {
-  sourceId: string;
+export type NotifierChannel = 'telegram' | 'discord';
+
+export interface NotifierConfig {
+  telegram: { enabled: boolean; chatId: string };
+  discord: { enabled: boolean };
+}
+
+export interface AlertSound {
+  id: string;
+  name: string;
+  builtin: boolean;
+}
+
+export type SearchResultType = 'dataset' | 'file' | 'drawing' | 'folder' | 'trade' | 'alert' | 'news' | 'note' | 'dossier';
+
+export type IndicatorType = 'sma' | 'ema' | 'rsi' | 'atr' | 'stddev' | 'macd' | 'macd_signal' | 'macd_hist';
+
+export interface IndicatorJob {
+  id?: string;
+  datasetId: string;
+  indicator: IndicatorType;
+  params?: { period?: number };
+  limit?: number; // only the most recent N bars
+}
+
+// A custom series: a value expression such as "ema(close, 20) - ema(close, 50)"
+export interface SeriesJob {
+  id?: string;
+  datasetId: string;
+  series: string;
+  limit?: number;
+}
+
+export interface IndicatorJobResult {
+  id: string;
+  success: boolean;
+  series?: [number, number][]; // [timestamp, value], warm-up bars omitted
+  durationMs?: number;
+  error?: string;
+  cached?: boolean; // answered from the compute cache
+}
+
+export type ComputeCacheKind = 'indicator' | 'series' | 'scan' | 'seasonality';
+
+export interface ComputeCacheStats {
+  dir: string;
+  version: string; // hash of the compute code; entries from other versions never match
+  entries: number;
+  bytes: number;
+  maxBytes: number;
+  hits: number; // counters since launch
+  misses: number;
+  writes: number;
+  evictions: number;
+}
+
+export interface ComputeCacheEntry {
+  key: string;
+  kind: ComputeCacheKind;
+  datasetId: string;
+  params: Record<string, unknown>;
+  current: boolean; // written by the running code version
+  bytes: number;
+  createdAt: number;
+  usedAt: number;
+}
+
+export interface ComputeConfig {
+  threads: number;
+  maxThreads: number;
+  pool: { threads: number; idle: number; busy: number; queued: number } | null;
+  streams?: number;
+}
+
+// isClosed=false is a preview for the forming bar; only closed bars advance the calculator
+export interface IndicatorUpdateEvent {
+  subscriptionId: string;
+  datasetId: string;
+  indicator: IndicatorType | 'expression';
+  params: { period: number } | { source: string };
   timestamp: number;
-  drawings: Drawing[];
-  folders?: Folder[];
-  config: ChartConfig;
-  visibleRange: { from: number; to: number } | null;
+  value: number | null;
+  isClosed: boolean;
 }
 
-export interface TabSession {
+export type CrosshairIndicator = { id?: string; indicator: IndicatorType; params?: Record<string, number> } | { id?: string; series: string };
+
+export interface CrosshairOptions {
+  timeframes?: string[]; // default: every timeframe the symbol has data for
+  indicators?: CrosshairIndicator[] | Record<string, CrosshairIndicator[]>; // same list everywhere, or per timeframe
+}
+
+export interface CrosshairTimeframe {
+  timeframe: string;
+  datasetId: string;
+  bar: { timestamp: number; open: number; high: number; low: number; close: number; volume: number } | null;
+  inBar: boolean; // false when the timestamp falls in a gap after the bar
+  isLatest: boolean;
+  values: Record<string, number | null>;
+}
+
+export interface CrosshairContext {
+  symbol: string;
+  timestamp: number | null; // null = latest bar
+  timeframes: CrosshairTimeframe[]; // finest first
+}
+
+export interface CrosshairContextEvent extends Partial<CrosshairContext> {
+  subscriptionId: string;
+  error?: string;
+}
+
+export type QuickSwitchKind = 'chart' | 'symbol' | 'file';
+
+export type PluginCapability = 'market:read' | 'symbols:read' | 'storage' | 'notify';
+
+export interface PluginPanelInfo {
   id: string;
+  name: string;
+  version: string | null;
   title: string;
-  symbolId: string; // Namespace-aware ID (e.g., "FOREX_GOLD")
-  sourceId: string; // Persistent unique ID for drawings/trades
-  rawData: OHLCV[];
-  data: OHLCV[];
-  timeframe: Timeframe;
-  config: ChartConfig;
-  
-  fileState?: FileStreamState;
-  filePath?: string; 
+  requested: PluginCapability[];
+  granted: PluginCapability[];
+  open: boolean;
+}
 
-  isReplayMode: boolean;
-  isAdvancedReplayMode: boolean;
-  isReplaySelecting: boolean; 
-  replayIndex: number;
-  replayGlobalTime: number | null; 
-  simulatedPrice: number | null; 
-  isReplayPlaying: boolean;
-  replaySpeed: number; 
-  isDetached: boolean;
-  
-  // Persisted UI State
-  isMarketOverviewOpen: boolean;
+export interface QuickSwitchCandidate {
+  kind: QuickSwitchKind;
+  ref: string; // chart source id, symbol or file path
+  label: string;
+  detail: string; // source id, timeframes with data, library folder
+  opens: number;
+  lastUsed: number | null;
+  score: number;
+}
 
-  visibleRange: { from: number; to: number } | null;
-  trades: Trade[];
-  drawings: Drawing[];
-  folders: Folder[];
-  undoStack: HistorySnapshot[];
-  redoStack: HistorySnapshot[];
+export type ActivityKind = 'chart_opened' | 'note_created' | 'alert_fired' | 'trade_logged' | 'trades_imported';
+
+// { from?, to? } in epoch ms, or a local day; today when neither is given
+export interface ActivityRange {
+  from?: number;
+  to?: number;
+  day?: string; // 'YYYY-MM-DD'
+  kinds?: ActivityKind[];
+  symbol?: string;
+  limit?: number;
 }
 
-export interface SanitizationStats {
-  fixedZeroes: number;
-  fixedLogic: number;
-  filledGaps: number;
-  outliers: number;
-  totalRecords: number;
+export interface ActivityEvent {
+  id: number;
+  at: number;
+  kind: ActivityKind;
+  ref: string | null; // chart source id, note / alert / trade id, statement file name
+  symbol: string | null;
+  title: string;
+  detail: Record<string, unknown> | null;
+}
+
+export interface ActivityTimeline {
+  from: number;
+  to: number;
+  events: ActivityEvent[]; // oldest first
+  counts: Record<ActivityKind, number>;
+  firstAt: number | null;
+  lastAt: number | null;
+  symbols: { symbol: string; events: number }[]; // busiest first
+  truncated: boolean;
+}
+
+export interface SearchResult {
+  type: SearchResultType;
+  id: string;
+  symbol: string | null;
+  title: string;
+  snippet: string;
+  meta?: Record<string, any>;
+  score: number;
+}
+
+// A text-bearing drawing found inside a saved chart state
+export type DatasetAnnotationKind = 'note' | 'earnings' | 'dividend' | 'split' | 'news' | 'trade' | 'event';
+
+export interface DatasetAnnotationInput {
+  time: number;
+  endTime?: number | null; // spans (an earnings window, a halt)
+  price?: number | null;
+  kind?: DatasetAnnotationKind; // default 'note'
+  label: string;
+  text?: string | null;
+  color?: string | null; // #rrggbb
+}
+
+export interface DatasetAnnotation extends Required<DatasetAnnotationInput> {
+  id: string;
+  symbol: string;
+  createdAt: number;
+  updatedAt: number;
+}
+
+export interface DatasetAnnotationQuery {
+  from?: number;
+  to?: number;
+  kinds?: DatasetAnnotationKind[];
+  limit?: number;
+}
+
+export interface DatasetAnnotationProjection {
+  symbol: string;
+  timeframe: string;
+  snappedTo: 'bars' | 'buckets'; // stored bars of the timeframe, or plain timeframe buckets
+  annotations: (DatasetAnnotation & { barTime: number | null; barEndTime: number | null })[];
+}
+
+export interface AnnotationMatch {
+  sourceId: string; // chart state key
+  drawingId: string;
+  type: string;
+  text: string;
+  snippet: string;
+  folder: string | null;
+  time: number | null; // first anchor point
+  price: number | null;
+  endTime: number | null; // last anchor point of multi-point drawings
+  endPrice: number | null;
+}
+
+export interface PerfChannelStats {
+  channel: string;
+  calls: number;
+  errors: number;
+  avgMs: number;
+  p50Ms: number;
+  p95Ms: number;
+  maxMs: number;
+  avgArgBytes: number;
+  avgResultBytes: number;
+}
+
+// `code` on every failed command answer ({ success: false, error, code, details? }), see electron/appErrors.js
+export type AppErrorCode =
+  | 'NOT_FOUND' | 'ALREADY_EXISTS' | 'PERMISSION_DENIED' | 'INVALID_INPUT' | 'INVALID_JSON' | 'CORRUPT_DATA'
+  | 'VALIDATION_FAILED' | 'CONFLICT' | 'LOCKED' | 'UNAVAILABLE' | 'UNSUPPORTED' | 'NETWORK' | 'TIMEOUT'
+  | 'DISK_FULL' | 'DATABASE' | 'READ_ONLY' | 'CANCELED' | 'RATE_LIMITED' | 'PAYLOAD_TOO_LARGE' | 'PAYLOAD_TOO_DEEP' | 'INTERNAL';
+
+export interface AppErrorResult {
+  success: false;
+  error: string; // human-readable message
+  code: AppErrorCode;
+  details?: Record<string, any>; // e.g. { path, syscall, systemCode } for file errors
+}
+
+// Answer of any command refused by the IPC guards before its handler ran
+export interface IpcGuardRejection extends AppErrorResult {
+  code: 'PAYLOAD_TOO_LARGE' | 'PAYLOAD_TOO_DEEP' | 'RATE_LIMITED';
+  limit: number; // bytes, nesting levels or calls per second
+  bytes?: number;
+  retryAfterMs?: number; // RATE_LIMITED
+}
+
+export interface TestHarnessAPI {
+  getInfo: () => Promise<{ success: boolean; dataDir?: string; keepData?: boolean; now?: number; profile?: string; readOnly?: boolean; providers?: string[]; error?: string }>;
+  listChannels: () => Promise<{ success: boolean; channels?: string[]; error?: string }>;
+  setClock: (value: number | string) => Promise<{ success: boolean; now?: number; error?: string }>;
+  advanceClock: (ms: number) => Promise<{ success: boolean; now?: number; error?: string }>;
+  getLog: (options?: { event?: string; level?: string; limit?: number }) => Promise<{ success: boolean; entries?: { event: string; timestamp: number; level: string; data: any }[]; error?: string }>;
+  query: (sql: string, params?: any[]) => Promise<{ success: boolean; rows?: any[]; error?: string }>;
+  pushBar: (symbol: string, timeframe: string, bar: { timestamp?: number; open: number; high: number; low: number; close: number; volume?: number }, isClosed?: boolean) => Promise<{ success: boolean; bar?: any; error?: string }>;
+}
+
+export interface PerfMetrics {
+  since: number | null;
+  channels: PerfChannelStats[];
+  recent: { channel: string; durationMs: number; argBytes: number; resultBytes: number; ok: boolean; error: string | null; at: number }[];
+}
+
+export interface MemoryReport {
+  success: boolean;
+  at?: number;
+  /** Bytes, as process.memoryUsage() reports them */
+  process?: { rss: number; heapUsed: number; heapTotal: number; external: number; arrayBuffers: number };
+  /** Busy workers are listed without figures; null figures = no answer in time */
+  computeWorkers?: { threadId: number; busy: boolean; heapUsed: number | null; heapTotal: number | null; external?: number; dbOpen?: boolean }[];
+  caches?: {
+    crosshair: { windows: number; maxWindows: number; bars: number; series: number; subscriptions: number };
+    indicatorStreams: number;
+    symbolSearch: { cachedQueries: number; maxEntries: number };
+    segmentClassifiers: number;
+    liveBars: number;
+    quotes: number;
+    logBuffer: number;
+  };
+  /** On disk, not resident */
+  computeCache?: ComputeCacheStats;
+  error?: string;
+}
+
+export interface DiagnosticCheck {
+  id: string;
+  status: 'ok' | 'warn' | 'fail';
+  message: string;
+  details?: Record<string, any>;
+}
+
+export interface WorkspaceLockHolder {
+  instanceId: string;
+  hostname: string;
+  pid: number;
+  startedAt: number;
+  heartbeatAt: number;
+}
+
+// A separate workspace (own database, settings and layouts); 'default' is the original one
+export interface Profile {
+  id: string;
+  name: string;
+  createdAt: number | null;
+}
+
+export interface UsageAnalyticsConfig {
+  enabled: boolean;
+  endpoint: string; // https://
+  installId: string | null; // random, created when first enabled
+  lastUploadAt: number | null;
+}
+
+// Counts per finished UTC day (YYYY-MM-DD); nothing else is sent
+export interface UsageUploadPayload {
+  installId: string | null;
+  appVersion: string | null;
+  platform: string | null;
+  days: { day: string; counters: Record<string, number> }[];
+}
+
+export interface IntegrityRecord {
+  table: string;
+  key: string | number;
+  problem: string;
+  from?: string; // 'backup <file>' | 'trash' for restored records
+}
+
+export interface IntegrityReport {
+  status: 'ok' | 'repaired' | 'warn' | 'fail';
+  checkedAt: number;
+  ms: number;
+  sqlite: string; // 'ok' | 'reindexed' | the quick_check message
+  checked: number;
+  repaired: IntegrityRecord[];
+  reset: IntegrityRecord[];
+  removed: IntegrityRecord[];
+  problems: string[];
+}
+
+export interface IntegrityQuarantineEntry {
+  id: number;
+  table: string;
+  key: string;
+  data: string; // original column values as JSON
+  problem: string;
+  quarantinedAt: number;
+}
+
+export type CrashKind = 'main-exception' | 'unhandled-rejection' | 'renderer-gone' | 'child-gone' | 'native';
+
+export interface CrashReportSummary {
+  id: string;
+  kind: CrashKind;
+  timestamp: number;
+  message: string | null;
+  stack: string | null;
+  details: Record<string, unknown>;
+  environment: { appVersion: string; platform: string; arch: string; osRelease: string; electron: string; uptimeSec: number };
+  minidump: string | null; // file name in the crash dumps directory
+  status: 'pending';
+  logLines: number;
+}
+
+export interface CrashReport extends Omit<CrashReportSummary, 'status' | 'logLines'> {
+  logs: { event: string; level: string; timestamp: number }[]; // names only, no log data
+}
+
+export interface CrashReportConfig {
+  endpoint: string; // https://, empty = reports are never sent
+  promptOnStart: boolean;
+}
+
+export interface DiagnosticsReport {
+  generatedAt: number;
+  status: DiagnosticCheck['status'];
+  environment: Record<string, any>;
+  checks: DiagnosticCheck[];
+}
+
+export interface OrphanedChartState {
+  symbol: string;
+  bytes: number;
+  drawings: number;
+  savedAt: number | null;
+}
+
+export interface ChartTrashEntry {
+  symbol: string;
+  bytes: number;
+  reason: string;
+  deletedAt: number;
+}
+
+export interface StorageCategory {
+  id: string;
+  label: string;
+  bytes: number;
+  items?: number;
+  rows?: number;
+  bySource?: { source: string; datasets: number; rows: number }[];
+}
+
+export interface StorageBenchmarkTiming {
+  meanMs: number;
+  p50Ms: number;
+  p95Ms: number;
+  maxMs: number;
+  throughputMBps: number;
+}
+
+// How a CSV spells numbers and dates (electron/importLocale.js)
+export interface ImportLocale {
+  delimiter: ',' | ';' | '\t' | '|' | null; // null: ';' when the line has one, else ','
+  decimal: '.' | ',';
+  thousands: ',' | '.' | ' ' | "'" | null;
+  dateOrder: 'ymd' | 'dmy' | 'mdy';
+}
+
+export interface ImportProbe {
+  locale: ImportLocale;
+  ambiguous: ('decimal' | 'dateOrder')[]; // guesses worth confirming with the user
+  hasHeader: boolean;
+  header: string[] | null;
+  dateColumns: 1 | 2; // DateTime, or Date + Time
+  sample: string[][]; // first rows split into fields
+}
+
+export interface ParseBenchmarkReport {
+  rows: number;
+  iterations: number;
+  builtin: { meanMs: number; rowsPerSec: number }; // parseFloat + new Date(string)
+  fast: { meanMs: number; rowsPerSec: number };    // fastParse.js
+  speedup: number;
+  identical: boolean; // both paths produced the same values
+  aggregation: AggregateBenchmarkReport;
+}
+
+export interface AggregateBenchmarkReport {
+  bars: number;
+  window: number; // bars per range, stepped by a quarter window
+  iterations: number;
+  slice: { meanMs: number; windowsPerSec: number }; // slice + forEach / reduce
+  fast: { meanMs: number; windowsPerSec: number };  // fastAggregate.js
+  speedup: number;
+  identical: boolean;
 }
 
+export interface StorageBenchmarkReport {
+  generatedAt: number;
+  environment: { platform: string; cpus: number; dir: string };
+  options: { iterations: number; drawings: number; bars: number };
+  results: { id: 'json' | 'sqlite' | 'binary'; label: string; bytes: number; save: StorageBenchmarkTiming; load: StorageBenchmarkTiming }[];
+  fastestSave: string;
+  fastestLoad: string;
+}
+
+export interface StorageReport {
+  generatedAt: number;
+  databaseFileBytes: number;
+  totalBytes: number;
+  exact: boolean;
+  categories: StorageCategory[];
+}
+
+export interface CompactionResult {
+  beforeBytes: number;
+  afterBytes: number;
+  reclaimedBytes: number;
+  freePagesBefore: number;
+  durationMs: number;
+  compactedAt: number;
+}
+
+export interface CompactionConfig {
+  idleEnabled: boolean;
+  idleMinutes: number;
+  minFreeRatio: number;
+  minIntervalHours: number;
+}
+
+export type StoragePurgeTarget = 'news' | 'fred' | 'stale_csv' | 'csv_cache';
+
 // --- ELECTRON BRIDGE DEFINITIONS (STRICT) ---
 
 export interface IElectronAPI {
@@ -200,33 +2336,526 @@ export interface IElectronAPI {
   getInternalLibrary: () => Promise<any[]>;
   
   // Data Ingestion (Optimization)
-  getMarketData: (symbol: string, timeframe: string, filePath?: string, toTime?: number | null, limit?: number) => Promise<{ data?: any[]; format?: 'array'; error?: string }>;
+  getMarketData: (symbol: string, timeframe: string, filePath?: string, toTime?: number | null, limit?: number, options?: { segments?: SessionSegment[]; calendar?: string | SessionCalendar; tagSegments?: boolean }) => Promise<{ data?: any[]; format?: 'array'; segments?: SessionSegment[]; error?: string }>;
+  importMetaTraderHistory: (filePath?: string | null, options?: { symbol?: string; timeframe?: string; brokerOffset?: number | 'ny-close'; taskId?: string }) => Promise<{ success: boolean; canceled?: boolean; symbol?: string; timeframe?: string; count?: number; error?: string }>;
+  probeMarketFile: (filePath: string) => Promise<{ success: boolean; error?: string } & Partial<ImportProbe>>;
+  readMarketFile: (filePath: string, options?: { columns?: ('open' | 'high' | 'low' | 'close' | 'volume')[]; from?: number; to?: number; limit?: number; format?: 'csv' | 'mt-csv' | 'hst'; brokerOffset?: number | 'ny-close'; locale?: Partial<ImportLocale> }) => Promise<{ success: boolean; columns?: string[]; data?: number[][]; format?: 'array'; scanned?: number; stoppedEarly?: boolean; startOffset?: number; indexed?: boolean; error?: string }>;
+  buildTimeIndex: (filePath: string, options?: { format?: 'csv' | 'mt-csv'; brokerOffset?: number | 'ny-close'; stride?: number; force?: boolean }) => Promise<{ success: boolean; index?: TimeIndexSummary; error?: string }>;
+  deleteTimeIndex: (filePath: string) => Promise<{ success: boolean; deleted?: boolean; error?: string }>;
+  listDatasets: () => Promise<DatasetInfo[]>;
+  generateSyntheticSeries: (spec?: SyntheticSeriesSpec) => Promise<{ success: boolean; dataset?: DatasetInfo; error?: string }>;
+  buildContinuousFutures: (spec: ContinuousFuturesSpec, options?: { preview?: boolean }) => Promise<{ success: boolean; dataset?: DatasetInfo; symbol?: string; timeframe?: string; bars?: number | any[]; rolls?: ContinuousRoll[]; error?: string }>;
+  rebuildContinuousFutures: (id: string) => Promise<{ success: boolean; dataset?: DatasetInfo; rolls?: ContinuousRoll[]; bars?: number; error?: string }>;
+  mergeDatasets: (spec: DatasetMergeSpec, options?: { preview?: boolean }) => Promise<{ success: boolean; dataset?: DatasetInfo; error?: string } & Partial<DatasetMergeReport>>;
+  rebuildMergedDataset: (id: string) => Promise<{ success: boolean; dataset?: DatasetInfo; error?: string } & Partial<DatasetMergeReport>>;
+  editBar: (datasetId: string, timestamp: number, changes: Partial<BarValues>, options?: { reason?: string }) => Promise<{ success: boolean; edit?: BarEdit; bar?: BarValues & { timestamp: number }; recomputed?: BarRecompute; error?: string }>;
+  deleteBar: (datasetId: string, timestamp: number, options?: { reason?: string }) => Promise<{ success: boolean; edit?: BarEdit; recomputed?: BarRecompute; error?: string }>;
+  revertBarEdit: (editId: number, options?: { reason?: string }) => Promise<{ success: boolean; edit?: BarEdit; reverted?: BarEdit; bar?: (BarValues & { timestamp: number }) | null; recomputed?: BarRecompute; error?: string }>;
+  listBarEdits: (options?: { datasetId?: string; from?: number; to?: number; limit?: number }) => Promise<{ success: boolean; edits?: BarEdit[]; error?: string }>;
+  onBarsChanged: (callback: (event: BarsChangedEvent) => void) => () => void;
+  getRowsPage: (datasetId: string, offset: number, limit: number, sort?: { column: 'timestamp' | 'open' | 'high' | 'low' | 'close' | 'volume'; direction: 'asc' | 'desc' }) => Promise<DatasetRowsPage>;
+
+  // Watch folders (auto-import of vendor drops)
+  getWatchFolders: () => Promise<{ success: boolean; rules?: WatchFolderRule[]; watching?: number; pending?: number; error?: string }>;
+  setWatchFolders: (rules: WatchFolderRule[]) => Promise<{ success: boolean; rules?: WatchFolderRule[]; folders?: { path: string; watching: boolean; error?: string }[]; error?: string }>;
+  rescanWatchFolders: () => Promise<{ success: boolean; error?: string }>;
+  getWatchImportHistory: (limit?: number) => Promise<{ success: boolean; files?: WatchImportRecord[]; error?: string }>;
+  onWatchImportSummary: (callback: (summary: WatchImportSummary) => void) => () => void;
+
+  // Market Data Providers
+  listProviders: () => Promise<ProviderInfo[]>;
+  getProviderSettings: (id: string) => Promise<{ success: boolean; schema?: ProviderSettingField[]; values?: Record<string, any>; secrets?: { key: string; set: boolean }[]; error?: string }>;
+  setProviderSettings: (id: string, values: Record<string, any>) => Promise<{ success: boolean; values?: Record<string, any>; error?: string }>;
+  connectProvider: (id: string, config?: Record<string, any>) => Promise<{ success: boolean; error?: string; [key: string]: any }>;
+  disconnectProvider: (id: string) => Promise<{ success: boolean; error?: string }>;
+  getProviderStatus: (id: string) => Promise<{ success: boolean; status?: string; error?: string; [key: string]: any }>;
+  fetchProviderHistory: (id: string, symbol: string, timeframe: string, options?: Record<string, any>) => Promise<{ success: boolean; count?: number; dataset?: DatasetInfo; error?: string }>;
+  subscribeProvider: (id: string, symbol: string, timeframe: string, options?: Record<string, any>) => Promise<{ success: boolean; error?: string }>;
+  unsubscribeProvider: (id: string, symbol: string, timeframe: string) => Promise<{ success: boolean; error?: string }>;
+  searchProviderSymbols: (id: string, query: string) => Promise<{ success: boolean; results?: ProviderSymbol[]; error?: string }>;
+  searchSymbols: (query: string, options?: { providers?: string[]; assetClass?: string; limit?: number; local?: boolean }) => Promise<{ success: boolean; results?: SymbolSearchResult[]; errors?: Record<string, string>; error?: string }>;
+  getProviderHealth: () => Promise<ProviderHealthSnapshot>;
+  resetProviderHealth: (id?: string | null) => Promise<{ success: boolean; error?: string } & Partial<ProviderHealthSnapshot>>;
+  subscribeDepth: (providerId: string, symbol: string, options?: { levels?: number; throttleMs?: number }) => Promise<{ success: boolean; error?: string }>;
+  unsubscribeDepth: (providerId: string, symbol: string) => Promise<{ success: boolean; error?: string }>;
+  getDepthSnapshot: (providerId: string, symbol: string, levels?: number | null) => Promise<{ success: boolean; snapshot?: DepthSnapshot; error?: string }>;
+  listDepthBooks: () => Promise<{ provider: string; symbol: string; levels: number; throttleMs: number; watchers: number; synced: boolean }[]>;
+  getOrderFlowConfig: () => Promise<OrderFlowConfig>;
+  setOrderFlowConfig: (updates: Partial<OrderFlowConfig>) => Promise<{ success: boolean; config?: OrderFlowConfig; readOnly?: boolean; error?: string }>;
+  getOrderFlowStatus: () => Promise<{ symbols: Record<string, number>; buffered: number; dropped: number; lastFlushAt: number | null }>;
+  getTradePrints: (symbol: string, options?: { from?: number; to?: number; limit?: number }) => Promise<{ success: boolean; prints?: TradePrint[]; error?: string }>;
+  getDeltaBars: (symbol: string, timeframe: string, options?: { from?: number; to?: number }) => Promise<{ success: boolean; bars?: DeltaBar[]; error?: string }>;
+  getFootprint: (symbol: string, timeframe: string, options?: { from?: number; to?: number; tickSize?: number }) => Promise<{ success: boolean; tickSize?: number; bars?: FootprintBar[]; error?: string }>;
+  downloadHistory: (symbol: string, range?: '1d' | '5d' | '1mo' | '3mo' | '6mo' | '1y' | '2y' | '5y' | '10y' | 'ytd' | 'max', interval?: string) => Promise<{ success: boolean; count?: number; dataset?: DatasetInfo; error?: string }>;
+  getFredSeries: (seriesId: string, options?: { forceRefresh?: boolean; maxAgeMs?: number }) => Promise<{ success: boolean; dataset?: DatasetInfo; data?: number[][]; format?: 'array'; error?: string }>;
+
+  // News
+  getNews: (symbol?: string | null, since?: number) => Promise<{ success: boolean; items?: NewsItem[]; error?: string }>;
+  getNewsConfig: () => Promise<NewsConfig | null>;
+  configureNews: (updates: Partial<NewsConfig>) => Promise<{ success: boolean; config?: NewsConfig; error?: string }>;
+  refreshNews: () => Promise<{ success: boolean; count?: number; error?: string }>;
+
+  // Alerts
+  listAlerts: (symbol?: string | null) => Promise<PriceAlert[]>;
+  saveAlert: (alert: Partial<PriceAlert> & Pick<PriceAlert, 'symbol' | 'condition'>) => Promise<{ success: boolean; alert?: PriceAlert; error?: string }>;
+  validateAlert: (alert: Partial<PriceAlert>) => Promise<{ success: boolean; valid?: boolean; error?: string; position?: number | null }>;
+  evaluateReplayAlerts: (symbol: string, timeframe: string, timestamp: number) => Promise<{ success: boolean; hits?: ({ alertId: string; condition: AlertCondition; price: number; level: number | null; values?: Record<string, number | null>; timestamp: number })[]; error?: string }>;
+  deleteAlert: (id: string) => Promise<{ success: boolean; error?: string }>;
+  exportAlerts: (filePath?: string | null, ids?: string[] | null) => Promise<{ success: boolean; canceled?: boolean; filePath?: string; count?: number; error?: string }>;
+  // Imported alerts get new ids (idMap: file id -> new id); identical existing alerts are skipped
+  importAlerts: (filePath?: string | null, options?: { symbolMap?: Record<string, string>; sourceMap?: Record<string, string>; disabled?: boolean }) => Promise<{ success: boolean; canceled?: boolean; imported?: PriceAlert[]; duplicates?: { id: string; symbol: string; condition: AlertCondition }[]; rejected?: { id: string; symbol: string; condition: AlertCondition; error: string }[]; idMap?: Record<string, string>; error?: string }>;
+  exportWatchlist: (items: ({ symbol: string } | string)[], filePath?: string | null, options?: { name?: string }) => Promise<{ success: boolean; canceled?: boolean; filePath?: string; count?: number; error?: string }>;
+  // Returns the symbols to add: those in the file that aren't in `existing` (JSON or TradingView-style text lists)
+  importWatchlist: (filePath?: string | null, existing?: string[]) => Promise<{ success: boolean; canceled?: boolean; name?: string | null; symbols?: string[]; duplicates?: string[]; invalid?: string[]; error?: string }>;
+  getAlertWebhookConfig: () => Promise<AlertWebhookConfig>;
+  setAlertWebhookConfig: (config: Partial<AlertWebhookConfig>) => Promise<{ success: boolean; config?: AlertWebhookConfig; error?: string }>;
+  getNotifierConfig: () => Promise<NotifierConfig>;
+  setNotifierConfig: (config: Partial<NotifierConfig>) => Promise<{ success: boolean; config?: NotifierConfig; error?: string }>;
+  sendTestNotification: (channel: NotifierChannel) => Promise<{ success: boolean; error?: string }>;
+
+  // Audio
+  playSound: (soundId?: string) => Promise<{ success: boolean; error?: string }>;
+  listSounds: () => Promise<AlertSound[]>;
+  importSound: (filePath?: string | null) => Promise<{ success: boolean; canceled?: boolean; sound?: AlertSound; error?: string }>;
+  deleteSound: (soundId: string) => Promise<{ success: boolean; error?: string }>;
+
+  // Compute (worker pool)
+  computeIndicatorsBulk: (jobs: (IndicatorJob | SeriesJob)[]) => Promise<{ success: boolean; results?: IndicatorJobResult[]; durationMs?: number; error?: string; position?: number | null; expression?: string }>;
+  validateSeries: (source: string) => Promise<ExpressionValidation>;
+  getComputeConfig: () => Promise<ComputeConfig>;
+  setComputeConfig: (config: { threads: number }) => Promise<{ success: boolean; config?: ComputeConfig; error?: string }>;
+  getComputeCacheStats: () => Promise<{ success: boolean; stats?: ComputeCacheStats; error?: string }>;
+  listComputeCache: (options?: { datasetId?: string; kind?: ComputeCacheKind; limit?: number }) => Promise<{ success: boolean; entries?: ComputeCacheEntry[]; error?: string }>;
+  invalidateComputeCache: (options?: { datasetId?: string; kind?: ComputeCacheKind; key?: string; stale?: boolean }) => Promise<{ success: boolean; removed?: number; stats?: ComputeCacheStats; error?: string }>;
+  subscribeIndicator: (datasetId: string, indicator: IndicatorType | 'expression', params?: { period?: number } | { source: string }) => Promise<{ success: boolean; subscriptionId?: string; timestamp?: number | null; value?: number | null; error?: string }>;
+  unsubscribeIndicator: (subscriptionId: string) => Promise<{ success: boolean; error?: string }>;
+  onIndicatorUpdate: (callback: (update: IndicatorUpdateEvent) => void) => () => void;
+  getCrosshairContext: (symbol: string, timestamp: number | null, options?: CrosshairOptions) => Promise<{ success: boolean; context?: CrosshairContext; error?: string }>;
+  subscribeCrosshair: (symbol: string, options?: CrosshairOptions & { timestamp?: number | null }) => Promise<{ success: boolean; subscriptionId?: string; context?: CrosshairContext; error?: string }>;
+  moveCrosshair: (subscriptionId: string, timestamp: number | null) => void;
+  unsubscribeCrosshair: (subscriptionId: string) => Promise<{ success: boolean; error?: string }>;
+  onCrosshairContext: (callback: (context: CrosshairContextEvent) => void) => () => void;
+
+  // Search
+  globalSearch: (query: string, options?: { limit?: number; types?: SearchResultType[] }) => Promise<{ success: boolean; results?: SearchResult[]; error?: string }>;
+  getQuickSwitchCandidates: (prefix: string, options?: { limit?: number; kinds?: QuickSwitchKind[] }) => Promise<{ success: boolean; candidates?: QuickSwitchCandidate[]; error?: string }>;
+  recordQuickSwitchOpen: (kind: QuickSwitchKind, ref: string) => Promise<{ success: boolean; counted?: boolean; error?: string }>;
+  clearQuickSwitchHistory: () => Promise<{ success: boolean; deleted?: number; error?: string }>;
+  getActivityTimeline: (range?: ActivityRange) => Promise<{ success: boolean; timeline?: ActivityTimeline; error?: string }>;
+
+  // Plugin panels (sandboxed windows; capabilities do nothing until granted)
+  listPluginPanels: () => Promise<{ success: boolean; dir?: string; capabilities?: Record<PluginCapability, string[]>; plugins?: PluginPanelInfo[]; errors?: { dir: string; error: string }[]; error?: string }>;
+  setPluginGrants: (pluginId: string, capabilities: PluginCapability[]) => Promise<{ success: boolean; plugin?: PluginPanelInfo; error?: string }>;
+  openPluginPanel: (pluginId: string) => Promise<{ success: boolean; plugin?: PluginPanelInfo; error?: string }>;
+  closePluginPanel: (pluginId: string) => Promise<{ success: boolean; closed?: number; error?: string }>;
+  onPluginNotification: (callback: (notification: { pluginId: string; name: string; message: string; at: number }) => void) => () => void;
+  searchChartAnnotations: (query: string, options?: { sourceId?: string; limit?: number }) => Promise<{ success: boolean; results?: AnnotationMatch[]; error?: string }>;
+  rebuildAnnotationIndex: () => Promise<{ success: boolean; charts?: number; error?: string }>;
+  listDatasetAnnotations: (symbol: string, options?: DatasetAnnotationQuery) => Promise<{ success: boolean; annotations?: DatasetAnnotation[]; error?: string }>;
+  projectDatasetAnnotations: (symbol: string, timeframe: string, options?: DatasetAnnotationQuery) => Promise<{ success: boolean; error?: string } & Partial<DatasetAnnotationProjection>>;
+  createDatasetAnnotation: (input: DatasetAnnotationInput & { symbol: string }) => Promise<{ success: boolean; annotation?: DatasetAnnotation; error?: string }>;
+  updateDatasetAnnotation: (id: string, patch: Partial<DatasetAnnotationInput>) => Promise<{ success: boolean; annotation?: DatasetAnnotation; error?: string }>;
+  deleteDatasetAnnotation: (id: string) => Promise<{ success: boolean; error?: string }>;
+  onDatasetAnnotationsChanged: (callback: (change: { action: 'created' | 'updated' | 'deleted'; symbol: string; annotation: DatasetAnnotation }) => void) => () => void;
+
+  // Secrets (values are never returned to the renderer)
+  // Keys are '<provider>.<name>'; each provider can only read its own
+  setSecret: (key: string, value: string, options?: { provider?: string; expiresAt?: number | null }) => Promise<{ success: boolean; error?: string }>;
+  deleteSecret: (key: string) => Promise<{ success: boolean; error?: string }>;
+  listSecrets: () => Promise<SecretInfo[]>;
+  setSecretMeta: (key: string, updates: { provider?: string; expiresAt?: number | null }) => Promise<{ success: boolean; secret?: SecretInfo; error?: string }>;
+  testCredentials: (provider: string) => Promise<{ success: boolean; provider?: string; valid?: boolean; message?: string | null; error?: string }>;
+  getExpiringSecrets: (withinDays?: number) => Promise<{ success: boolean; secrets?: (SecretInfo & { expired: boolean })[]; error?: string }>;
+  onSecretsExpiring: (callback: (secrets: { key: string; provider: string; expiresAt: number; expired: boolean }[]) => void) => () => void;
+
+  // Outbound proxy (password goes to the secrets store, never back to the renderer)
+  getProxyConfig: () => Promise<{ success: boolean; config?: ProxyConfig; hasPassword?: boolean; error?: string }>;
+  setProxyConfig: (config: Partial<ProxyConfig>, password?: string | null) => Promise<{ success: boolean; config?: ProxyConfig; error?: string }>;
+  testProxy: (config?: Partial<ProxyConfig> | null, password?: string, url?: string) => Promise<ProxyTestResult>;
+
+  // TLS policy: per-provider certificate pins and strict-TLS mode (pin mismatches fail with code CERT_PIN_MISMATCH)
+  getTlsPolicy: () => Promise<{ success: boolean; policy: TlsPolicy }>;
+  setTlsPolicy: (policy: TlsPolicy) => Promise<{ success: boolean; policy?: TlsPolicy; error?: string }>;
+  inspectCertificates: (url: string) => Promise<{ success: boolean; host?: string; protocol?: string; authorized?: boolean; chain?: { subject: string; issuer: string; validTo: string; pin: string }[]; error?: string }>;
 
   // Persistence (SQLite/JSON Store)
   loadMasterDrawings: () => Promise<{ success: boolean; data: any; error?: string }>;
   saveMasterDrawings: (data: any) => Promise<{ success: boolean; error?: string }>;
-  getDrawingsState: (symbol: string) => Promise<any>;
-  saveDrawingState: (symbol: string, data: any) => Promise<{ success: boolean; error?: string }>;
+  getDrawingsState: (symbol: string, options?: { datasetId?: string; sessionLevels?: boolean }) => Promise<any>;
+  saveDrawingState: (symbol: string, data: any, options?: { thumbnail?: string | Uint8Array; journal?: boolean }) => Promise<{ success: boolean; thumbnailError?: string; error?: string; validation?: ChartStateValidation }>;
+  patchDrawingState: (sourceId: string, ops: ChartPatchOp[], options?: { thumbnail?: string | Uint8Array; journal?: boolean }) => Promise<{ success: boolean; error?: string; validation?: ChartStateValidation; needsFullSave?: boolean; locked?: ChartLockViolation[]; conflict?: boolean; index?: number; ops?: number }>;
+  setDrawingsLocked: (sourceId: string, drawingIds: string[], locked: boolean) => Promise<{ success: boolean; error?: string; validation?: ChartStateValidation; needsFullSave?: boolean }>;
+  setDrawingGroup: (sourceId: string, drawingIds: string[], group: Pick<DrawingGroup, 'id'> & Partial<DrawingGroup> | null) => Promise<{ success: boolean; error?: string; validation?: ChartStateValidation; needsFullSave?: boolean; locked?: ChartLockViolation[] }>;
+  setDrawingGroupLocked: (sourceId: string, groupId: string, locked: boolean) => Promise<{ success: boolean; error?: string; validation?: ChartStateValidation; needsFullSave?: boolean }>;
+  getChartThumbnail: (sourceId: string) => Promise<{ success: boolean; dataUrl?: string; width?: number; height?: number; updatedAt?: number; error?: string }>;
+  undoChartEdit: (sourceId: string) => Promise<JournalStepResult>;
+  redoChartEdit: (sourceId: string) => Promise<JournalStepResult>;
+  getChartJournal: (sourceId: string, options?: { limit?: number; full?: boolean }) => Promise<{ success: boolean; entries?: ChartJournalEntry[]; error?: string } & Partial<JournalStatus>>;
   deleteAllDrawings: (sourceId: string) => Promise<{ success: boolean; error?: string }>;
+  publishSyncEvent: (topic: string, payload: any) => Promise<{ success: boolean; seq?: number; error?: string }>;
+  // complete=false: the replay buffer no longer reaches back to `seq`, reload instead
+  getSyncEventsSince: (seq: number, topics?: string[] | null) => Promise<{ success: boolean; seq?: number; complete?: boolean; events?: SyncEnvelope[]; windowId?: number; error?: string }>;
+  getSyncSeq: () => Promise<{ seq: number; windowId: number }>;
+
+  // Sticky notes (a batch applies in one transaction: all operations or none)
+  // boardId null / omitted: the default board
+  loadStickyNotes: (boardId?: string | null) => Promise<{ success: boolean; boardId?: string | null; notes?: StickyNote[]; error?: string }>;
+  bulkUpdateNotes: (ops: StickyNoteOp[]) => Promise<{ success: boolean; applied?: number; changes?: NoteSyncPayload['changes']; seq?: number; error?: string }>;
+  // archived=false restores; archived notes leave board loads but stay searchable
+  archiveNotes: (ids: string | string[], archived?: boolean) => Promise<{ success: boolean; changes?: NoteSyncPayload['changes']; error?: string }>;
+  searchStickyNotes: (query: string, options?: { includeArchived?: boolean; archivedOnly?: boolean; boardId?: string | null; limit?: number }) => Promise<{ success: boolean; notes?: StickyNote[]; error?: string }>;
+  getNoteHistory: (noteId: string) => Promise<{ success: boolean; noteId?: string; note?: StickyNote | null; versions?: NoteVersion[]; error?: string }>;
+  restoreNoteVersion: (noteId: string, version: number) => Promise<{ success: boolean; note?: StickyNote | null; changes?: NoteSyncPayload['changes']; seq?: number; error?: string }>;
+  getNoteAutoArchive: () => Promise<NoteAutoArchiveConfig>;
+  setNoteAutoArchive: (updates: Partial<NoteAutoArchiveConfig>) => Promise<{ success: boolean; config?: NoteAutoArchiveConfig; archived?: number; error?: string }>;
+  listNoteTemplates: () => Promise<{ success: boolean; templates?: NoteTemplate[]; error?: string }>;
+  saveNoteTemplate: (template: Partial<Pick<NoteTemplate, 'id' | 'color' | 'tags' | 'layout'>> & { name: string; text: string }) => Promise<{ success: boolean; template?: NoteTemplate; error?: string }>;
+  deleteNoteTemplate: (id: string) => Promise<{ success: boolean; error?: string }>;
+  // missing: placeholders left unfilled (no value, no {{name|fallback}})
+  createNoteFromTemplate: (templateId: string, vars?: Record<string, string | number>, options?: { boardId?: string | null; calendar?: string }) => Promise<{ success: boolean; note?: StickyNote; missing?: string[]; error?: string }>;
+
+  // Attachments (image blobs; embed `ref` in note text instead of base64)
+  pasteImageAttachment: (options?: ImageSaveOptions) => Promise<{ success: boolean; attachment?: AttachmentInfo; error?: string }>;
+  getAttachment: (id: string) => Promise<{ success: boolean; attachment?: AttachmentInfo & { dataUrl: string }; error?: string }>;
+  listAttachments: (options?: { limit?: number }) => Promise<{ success: boolean; attachments?: AttachmentInfo[]; error?: string }>;
+  deleteAttachment: (id: string) => Promise<{ success: boolean; error?: string }>;
+  pruneAttachments: () => Promise<{ success: boolean; deleted?: number; bytes?: number; error?: string }>;
+  // label: 'main', a window id, or omitted for the calling window
+  captureWindow: (label?: string | number | null, options?: CaptureOptions) => Promise<CaptureResult>;
+  // rect in screen coordinates; with options.window, relative to that window's content
+  captureRegion: (rect: { x: number; y: number; width: number; height: number }, options?: CaptureOptions & { window?: string | number }) => Promise<CaptureResult & { displayId?: number }>;
+  listNoteBoards: (options?: { includeArchived?: boolean }) => Promise<{ success: boolean; boards?: NoteBoard[]; error?: string }>;
+  createNoteBoard: (name: string) => Promise<{ success: boolean; board?: NoteBoard; error?: string }>;
+  renameNoteBoard: (id: string, name: string) => Promise<{ success: boolean; board?: NoteBoard; error?: string }>;
+  archiveNoteBoard: (id: string, archived?: boolean) => Promise<{ success: boolean; board?: NoteBoard; error?: string }>;
+  importTradingViewDrawings: (filePath?: string) => Promise<{ success: boolean; canceled?: boolean; drawings?: Drawing[]; folders?: Folder[]; skipped?: Record<string, number>; total?: number; error?: string }>;
+  exportInk: (symbol: string, drawingIds: string[] | null, format: 'svg' | 'png', filePath?: string | null, options?: { scale?: number; width?: number; height?: number; background?: string }) => Promise<{ success: boolean; canceled?: boolean; filePath?: string; width?: number; height?: number; error?: string }>;
+  exportNoteInk: (noteId: string, format: 'svg' | 'png', filePath?: string | null, options?: { scale?: number; color?: string; background?: string }) => Promise<{ success: boolean; canceled?: boolean; filePath?: string; width?: number; height?: number; error?: string }>;
+  // 'redpill': open interchange schema (see electron/chartStateExport.js); 'tradingview': importable drawing export
+  exportChartState: (sourceId: string, format?: 'redpill' | 'tradingview', filePath?: string | null) => Promise<{ success: boolean; canceled?: boolean; filePath?: string; exported?: number; skipped?: Record<string, number>; error?: string }>;
+  shareChart: (sourceId: string, options?: { password?: string | null; filePath?: string | null; datasetIds?: string[]; padBars?: number; maxBars?: number }) => Promise<{ success: boolean; canceled?: boolean; filePath?: string; bytes?: number; datasets?: { id: string; bars: number }[]; protected?: boolean; error?: string }>;
+  openSharedChart: (filePath?: string | null, password?: string | null) => Promise<{ success: boolean; canceled?: boolean; share?: SharedChart; error?: string; code?: string; details?: { passwordRequired?: boolean } }>;
+  findOrphanedChartStates: (knownNames?: string[]) => Promise<{ success: boolean; orphans?: OrphanedChartState[]; error?: string }>;
+  trashOrphanedChartStates: (symbols: string[], knownNames?: string[]) => Promise<{ success: boolean; moved?: string[]; rejected?: string[]; error?: string }>;
+  listChartTrash: () => Promise<ChartTrashEntry[]>;
+  restoreChartStates: (symbols: string[]) => Promise<{ success: boolean; restored?: string[]; skipped?: string[]; error?: string }>;
+  emptyChartTrash: (olderThanDays?: number) => Promise<{ success: boolean; deleted?: number; error?: string }>;
+
+  // Drawing Templates
+  saveDrawingTemplate: (name: string, objects: Drawing[], description?: string | null) => Promise<{ success: boolean; template?: { name: string; count: number; updatedAt: number }; error?: string }>;
+  listDrawingTemplates: () => Promise<DrawingTemplateInfo[]>;
+  getDrawingTemplate: (name: string) => Promise<{ success: boolean; template?: { name: string; description: string | null; objects: Drawing[] }; error?: string }>;
+  deleteDrawingTemplate: (name: string) => Promise<{ success: boolean; error?: string }>;
+  applyDrawingTemplate: (sourceId: string, name: string, options?: { anchor?: { time?: number; price?: number } }) => Promise<{ success: boolean; state?: ChartState; added?: number; folderId?: string; error?: string }>;
+
+  // Keybindings
+  getKeybindings: () => Promise<KeybindingSnapshot>;
+  setKeybinding: (action: string, accelerator: string | null) => Promise<{ success: boolean; conflict?: { action: string; title: string; accelerator: string }; error?: string } & Partial<KeybindingSnapshot>>;
+  resetKeybindings: (action?: string | null) => Promise<{ success: boolean; error?: string } & Partial<KeybindingSnapshot>>;
+  setLiveFeedPaused: (paused: boolean) => Promise<{ success: boolean; paused: boolean }>;
+
+  // Background mode (tray + live recording)
+  getBackgroundStatus: () => Promise<BackgroundStatus>;
+  getBackgroundConfig: () => Promise<BackgroundConfig>;
+  setBackgroundConfig: (updates: Partial<BackgroundConfig>) => Promise<{ success: boolean; config?: BackgroundConfig; error?: string }>;
+
+  // Power-aware throttling
+  getPowerState: () => Promise<PowerState>;
+  setPowerOverride: (override: 'auto' | 'normal' | 'saver') => Promise<{ success: boolean; error?: string } & Partial<PowerState>>;
+  onPowerStateChanged: (callback: (state: PowerState) => void) => () => void;
+  getAppearance: () => Promise<AppearanceState & { colors: { background: string; symbol: string } }>;
+  setAppearanceConfig: (patch: Partial<AppearanceConfig>) => Promise<{ success: boolean; error?: string } & Partial<AppearanceState>>;
+  onAppearanceChanged: (callback: (state: AppearanceState) => void) => () => void;
+  getInboxStatus: () => Promise<InboxStatus>;
+  setInboxConfig: (patch: Partial<Pick<InboxStatus, 'enabled' | 'port' | 'notify'>>) => Promise<{ success: boolean; error?: string } & Partial<InboxStatus>>;
+  rotateInboxToken: () => Promise<{ success: boolean; token?: string; error?: string } & Partial<InboxStatus>>;
+  listSignals: (options?: { symbol?: string; from?: number; to?: number; unreadOnly?: boolean; limit?: number }) => Promise<{ success: boolean; signals?: InboxSignal[]; error?: string }>;
+  markSignalsRead: (ids: number[]) => Promise<{ success: boolean; updated?: number; error?: string }>;
+  deleteSignals: (ids: number[]) => Promise<{ success: boolean; deleted?: number; error?: string }>;
+  onSignalsReceived: (callback: (signals: InboxSignal[]) => void) => () => void;
+  getCompanionStatus: () => Promise<CompanionIpcStatus>;
+  setCompanionConfig: (patch: Partial<Pick<CompanionIpcStatus, 'enabled' | 'path'>>) => Promise<{ success: boolean; error?: string } & Partial<CompanionIpcStatus>>;
+  getClipboardWatchConfig: () => Promise<ClipboardWatchConfig & { running: boolean }>;
+  setClipboardWatchConfig: (patch: Partial<ClipboardWatchConfig>) => Promise<{ success: boolean; error?: string; config?: ClipboardWatchConfig; running?: boolean }>;
+  onSymbolCopied: (callback: (match: SymbolSearchResult & { copiedText: string }) => void) => () => void;
+  getIdleState: () => Promise<IdleState & { config: IdleConfig }>;
+  setIdleConfig: (patch: Partial<IdleConfig>) => Promise<{ success: boolean; error?: string; config?: IdleConfig } & Partial<IdleState>>;
+  lockWorkspace: () => Promise<{ success: boolean; alreadyLocked: boolean } & IdleState>;
+  unlockWorkspace: () => Promise<{ success: boolean } & IdleState>;
+  onUserAway: (callback: (away: { since: number; reason: IdleReason }) => void) => () => void;
+  onUserReturned: (callback: (info: { awayMs: number; reason: IdleReason; state: IdleState }) => void) => () => void;
+  onWorkspaceLocked: (callback: (lock: { since: number; reason: IdleReason }) => void) => () => void;
+  onWorkspaceUnlocked: (callback: (state: IdleState) => void) => () => void;
+
+  // Background jobs (opportunistic index building)
+  getBackgroundJobs: () => Promise<{ success: boolean } & BackgroundJobsState>;
+  cancelBackgroundJob: (id: number) => Promise<{ success: boolean; canceled: boolean }>;
+  onThumbnailsMissing: (callback: (info: { symbols: string[] }) => void) => () => void;
+
+  // Scheduler
+  listScheduledJobs: () => Promise<{ success: boolean; jobs?: ScheduledJob[]; types?: ScheduledJobType[]; error?: string }>;
+  createScheduledJob: (job: { name: string; type: ScheduledJobType; schedule: string; params?: Record<string, any>; enabled?: boolean; catchUp?: 'once' | 'skip' }) => Promise<{ success: boolean; job?: ScheduledJob; error?: string }>;
+  updateScheduledJob: (id: string, updates: Partial<Pick<ScheduledJob, 'name' | 'type' | 'schedule' | 'params' | 'enabled' | 'catchUp'>>) => Promise<{ success: boolean; job?: ScheduledJob; error?: string }>;
+  deleteScheduledJob: (id: string) => Promise<{ success: boolean; error?: string }>;
+  runScheduledJobNow: (id: string) => Promise<{ success: boolean; run?: ScheduledJobRun; error?: string }>;
+  getScheduledJobRuns: (jobId?: string | null, limit?: number) => Promise<{ success: boolean; runs?: ScheduledJobRun[]; error?: string }>;
+  onScheduledJobRun: (callback: (run: ScheduledJobRun) => void) => () => void;
+  runEndOfDay: (params?: EndOfDayParams) => Promise<{ success: boolean; result?: EndOfDayResult; error?: string }>;
+  getEndOfDay: () => Promise<{ steps: EndOfDayStep[]; last: EndOfDayResult | null }>;
+  onEndOfDayFinished: (callback: (result: EndOfDayResult) => void) => () => void;
+
+  // Historical downloads (queued, chunked, resumable)
+  listDownloads: () => Promise<{ success: boolean; jobs?: DownloadJob[]; config?: DownloadConfig; error?: string }>;
+  enqueueDownloads: (specs: { provider: string; symbol: string; timeframe: string; start: number; end?: number; options?: Record<string, any> } | { provider: string; symbol: string; timeframe: string; start: number; end?: number; options?: Record<string, any> }[]) => Promise<{ success: boolean; jobs?: DownloadJob[]; error?: string }>;
+  pauseDownload: (id: string) => Promise<{ success: boolean; job?: DownloadJob; error?: string }>;
+  resumeDownload: (id: string) => Promise<{ success: boolean; job?: DownloadJob; error?: string }>;
+  cancelDownload: (id: string) => Promise<{ success: boolean; job?: DownloadJob; error?: string }>;
+  removeDownload: (id: string) => Promise<{ success: boolean; error?: string }>;
+  clearFinishedDownloads: () => Promise<{ success: boolean; removed?: number; error?: string }>;
+  setDownloadConfig: (updates: Partial<DownloadConfig>) => Promise<{ success: boolean; config?: DownloadConfig; error?: string }>;
+  onDownloadProgress: (callback: (job: DownloadJob) => void) => () => void;
+
+  // Presentation mode (label: 'main', a window id, or omitted for the calling window).
+  // While active, alert sounds are muted; renderers should also hold back toasts.
+  enterPresentationMode: (label?: string | number | null, options?: { kiosk?: boolean; dimOtherDisplays?: boolean; dimOpacity?: number }) => Promise<{ success: boolean; error?: string } & Partial<PresentationStatus>>;
+  exitPresentationMode: () => Promise<{ success: boolean; error?: string } & Partial<PresentationStatus>>;
+  getPresentationStatus: () => Promise<PresentationStatus>;
+
+  // Command palette actions
+  listActions: () => Promise<PaletteAction[]>;
+  executeAction: (id: string, args?: Record<string, any>) => Promise<{ success: boolean; forwarded?: boolean; result?: any; error?: string }>;
+
+  // Localization (backend strings: errors, report labels)
+  getLocaleStrings: (lang?: string | null) => Promise<{ success: boolean; language?: string; strings?: Record<string, string>; error?: string }>;
+  listLanguages: () => Promise<{ current: string; languages: { code: string; name: string }[] }>;
+  setLanguage: (lang: string) => Promise<{ success: boolean; language?: string; error?: string }>;
+
+  // Themes
+  listThemes: () => Promise<ThemeInfo[]>;
+  getActiveTheme: () => Promise<{ success: boolean; theme?: ThemeInfo; chartConfig?: Partial<ChartConfig>; error?: string }>;
+  saveTheme: (theme: Theme) => Promise<{ success: boolean; theme?: Theme; error?: string }>;
+  deleteTheme: (name: string) => Promise<{ success: boolean; error?: string }>;
+  applyTheme: (name: string) => Promise<{ success: boolean; theme?: ThemeInfo; chartConfig?: Partial<ChartConfig>; error?: string }>;
+  exportTheme: (name: string, filePath?: string | null) => Promise<{ success: boolean; canceled?: boolean; filePath?: string; error?: string }>;
+  importTheme: (filePath?: string | null) => Promise<{ success: boolean; canceled?: boolean; theme?: Theme; error?: string }>;
+  onThemeApplied: (callback: (event: { theme: ThemeInfo; chartConfig: Partial<ChartConfig> }) => void) => () => void;
+
+  // Playback export (replay rendered to a file in the backend)
+  exportPlayback: (datasetId: string, range?: { from?: number; to?: number }, options?: PlaybackExportOptions) => Promise<PlaybackExportResult>;
+  onPlaybackExportProgress: (callback: (event: { datasetId: string; filePath: string; frame: number; frames: number }) => void) => () => void;
   
   // Layouts
   saveLayout: (name: string, data: any) => Promise<{ success: boolean; error?: string }>;
   loadLayout: (name: string) => Promise<{ success: boolean; data?: any; error?: string }>;
   listLayouts: () => Promise<string[]>;
+  getOnboardingState: () => Promise<{ success: boolean; error?: string } & Partial<OnboardingState>>;
+  completeOnboardingStep: (stepId: OnboardingStepId, options?: { skip?: boolean; specs?: any[]; demo?: boolean }) => Promise<{ success: boolean; error?: string } & Partial<OnboardingState>>;
+  resetOnboarding: () => Promise<{ success: boolean; error?: string } & Partial<OnboardingState>>;
+  onOnboardingChanged: (callback: (state: OnboardingState) => void) => () => void;
+  getExternalChangeStatus: () => Promise<{ success: boolean; error?: string } & Partial<ExternalChangeStatus>>;
+  checkExternalChanges: () => Promise<{ success: boolean; checked?: boolean; changes?: number; replaced?: boolean; status?: ExternalChangeStatus; error?: string }>;
+  onExternalChange: (callback: (event: ExternalDatabaseChange) => void) => () => void;
+  onOpenFile: (callback: (file: { path: string }) => void) => () => void;
+  installDemoContent: () => Promise<{ success: boolean; prefix?: string; error?: string } & Partial<DemoInstallResult>>;
+  onDemoInstalled: (callback: (result: DemoInstallResult) => void) => () => void;
+  exportSettings: (filePath?: string | null, options?: { sections?: SettingsSection[] }) => Promise<{ success: boolean; filePath?: string; bytes?: number; stripped?: string[]; canceled?: boolean; error?: string }>;
+  importSettings: (filePath?: string | null, options?: { sections?: SettingsSection[]; dryRun?: boolean }) => Promise<{ success: boolean; dryRun?: boolean; filePath?: string; plan?: SettingsImportPlan; applied?: { keys: string[]; themes: string[] }; canceled?: boolean; error?: string }>;
 
   // Trades
   getTradesBySource: (sourceId: string) => Promise<Trade[]>;
   saveTrade: (trade: Trade) => Promise<{ success: boolean; error?: string }>;
+  importBrokerStatement: (filePath: string, options?: { broker?: 'auto' | 'ibkr' | 'generic'; timezone?: string; sourceId?: string; dryRun?: boolean }) => Promise<StatementImportResult>;
+
+  // Paper trading (accountId defaults to 'default')
+  placeSimOrder: (order: SimOrderRequest) => Promise<{ success: boolean; order?: SimOrder; error?: string }>;
+  cancelSimOrder: (id: string) => Promise<{ success: boolean; order?: SimOrder; error?: string }>;
+  pushSimPrice: (symbol: string, price: number, timestamp: number) => Promise<{ success: boolean; fills?: SimFill[]; error?: string }>;
+  listSimOrders: (options?: { accountId?: string; status?: SimOrder['status']; limit?: number }) => Promise<{ success: boolean; orders?: SimOrder[]; error?: string }>;
+  listSimFills: (options?: { accountId?: string; symbol?: string; since?: number; limit?: number }) => Promise<{ success: boolean; fills?: SimFill[]; error?: string }>;
+  getSimAccount: (id?: string) => Promise<{ success: boolean; account?: SimAccountSummary; error?: string }>;
+  listSimAccounts: () => Promise<{ success: boolean; accounts?: SimAccount[]; error?: string }>;
+  createSimAccount: (options?: { name?: string; currency?: string; startingCash?: number; settings?: Partial<SimSettings> }) => Promise<{ success: boolean; account?: SimAccount; error?: string }>;
+  updateSimSettings: (id: string, settings: Partial<SimSettings>) => Promise<{ success: boolean; account?: SimAccount; error?: string }>;
+  resetSimAccount: (id?: string, startingCash?: number | null) => Promise<{ success: boolean; account?: SimAccount; error?: string }>;
+  onSimFill: (callback: (event: { fill: SimFill; order: SimOrder; position?: SimPosition; account: SimAccountSummary }) => void) => () => void;
+  onSimOrder: (callback: (order: SimOrder) => void) => () => void;
+
+  // Position sizing (shared contract math with the journal and paper trading)
+  getSymbolMeta: (symbol: string) => Promise<{ success: boolean; meta?: SymbolMeta; error?: string }>;
+  listSymbolMeta: () => Promise<{ success: boolean; items?: SymbolMeta[]; error?: string }>;
+  setSymbolMeta: (symbol: string, meta: Partial<Omit<SymbolMeta, 'symbol' | 'custom'>>) => Promise<{ success: boolean; meta?: SymbolMeta; error?: string }>;
+  deleteSymbolMeta: (symbol: string) => Promise<{ success: boolean; deleted?: boolean; meta?: SymbolMeta; error?: string }>;
+  getDossier: (symbol: string) => Promise<{ success: boolean; dossier?: SymbolDossier; error?: string }>;
+  listDossiers: () => Promise<{ success: boolean; dossiers?: SymbolDossierSummary[]; error?: string }>;
+  // Fields given replace the stored ones; a stale expectedUpdatedAt fails with code CONFLICT
+  updateDossier: (symbol: string, patch: Partial<Pick<SymbolDossier, 'thesis' | 'keyLevels' | 'links' | 'attachments' | 'files' | 'tags'>>, options?: { expectedUpdatedAt?: number | null }) => Promise<{ success: boolean; dossier?: SymbolDossier; error?: string; code?: AppErrorCode }>;
+  deleteDossier: (symbol: string) => Promise<{ success: boolean; deleted?: boolean; error?: string }>;
+  onDossierChanged: (callback: (change: { symbol: string; dossier: SymbolDossier }) => void) => () => void;
+  calcPositionSize: (request: PositionSizeRequest) => Promise<{ success: boolean; error?: string } & Partial<PositionSizeResult>>;
+
+  // Portfolio analytics (journal + paper trading)
+  getPortfolioStats: (filter?: PortfolioFilter) => Promise<{ success: boolean; stats?: PortfolioStats; error?: string }>;
+  runMonteCarlo: (input: { filter?: PortfolioFilter } | { pnls: number[] } | { trades: { pnl: number }[] }, iterations?: number, params?: MonteCarloParams) => Promise<{ success: boolean; result?: MonteCarloResult; error?: string }>;
+  listLiveExports: () => Promise<{ success: boolean; exports?: LiveExport[] }>;
+  saveLiveExport: (def: Omit<LiveExport, 'id' | 'status' | 'format' | 'intervalSeconds' | 'enabled'> & Partial<Pick<LiveExport, 'id' | 'format' | 'intervalSeconds' | 'enabled'>>) => Promise<{ success: boolean; export?: LiveExport; error?: string }>;
+  deleteLiveExport: (id: string) => Promise<{ success: boolean; error?: string }>;
+  runLiveExportNow: (id: string) => Promise<{ success: boolean; export?: LiveExport; error?: string }>;
+  exportPortfolioReport: (filter?: PortfolioFilter, options?: { filePath?: string; title?: string; currency?: string }) => Promise<{ success: boolean; filePath?: string; canceled?: boolean; error?: string }>;
+  printDocument: (kind: PrintKind, id?: string | string[] | PrintPart[] | null, options?: PrintOptions) => Promise<{ success: boolean; pdfPath?: string; canceled?: boolean; error?: string }>;
+  listPrinters: () => Promise<{ success: boolean; printers?: { name: string; displayName: string; description: string }[]; error?: string }>;
+  listExportTemplates: (options?: { source?: ExportSource }) => Promise<{ success: boolean; templates?: ExportTemplate[]; sources?: ExportSource[]; error?: string }>;
+  saveExportTemplate: (template: ExportTemplateDraft) => Promise<{ success: boolean; template?: ExportTemplate; error?: string }>;
+  deleteExportTemplate: (id: string) => Promise<{ success: boolean; deleted?: boolean; error?: string }>;
+  previewExport: (template: string | ExportTemplateDraft, query?: ExportQuery) => Promise<{ success: boolean; text?: string; truncated?: boolean; missing?: string[]; count?: number; error?: string }>;
+  renderExport: (template: string | ExportTemplateDraft, query?: ExportQuery, filePath?: string | null) => Promise<{ success: boolean; filePath?: string; count?: number; missing?: string[]; canceled?: boolean; error?: string }>;
+
+  // Options pricing
+  priceOption: (input: OptionPriceRequest) => Promise<{ success: boolean; error?: string } & Partial<OptionPriceResult>>;
+  computeChainGreeks: (chain: OptionChain, options?: { model?: OptionPriceRequest['model']; steps?: number; valuationTime?: number }) => Promise<{ success: boolean; chain?: OptionChain; error?: string }>;
+
+  // FX conversion (base-currency P&L)
+  getFxConfig: () => Promise<FxConfig>;
+  setFxConfig: (updates: Partial<FxConfig>) => Promise<{ success: boolean; config?: FxConfig; error?: string }>;
+  listFxRates: (filter?: { base?: string; quote?: string; from?: number; to?: number; limit?: number }) => Promise<{ success: boolean; rates?: FxRate[]; error?: string }>;
+  setFxRate: (base: string, quote: string, rate: number, day?: string | number) => Promise<{ success: boolean; error?: string }>;
+  deleteFxRate: (base: string, quote: string, day: string) => Promise<{ success: boolean; deleted?: boolean; error?: string }>;
+  getFxRate: (from: string, to: string, timestamp?: number) => Promise<{ success: boolean; error?: string } & Partial<FxLookup>>;
+  convertCurrency: (amount: number, from: string, to: string, timestamp?: number) => Promise<{ success: boolean; amount?: number; error?: string } & Partial<FxLookup>>;
+
+  // Session statistics (opening range, session high / low / VWAP, overnight range per day)
+  listSessionCalendars: () => Promise<(SessionCalendar & { id: string })[]>;
+  getSessionStats: (datasetId: string, options?: { calendar?: string | SessionCalendar; openingRangeMinutes?: number; from?: number; to?: number }) => Promise<{ success: boolean; datasetId?: string; calendar?: { id: string; name: string | null; timezone: string }; days?: SessionDayStats[]; error?: string }>;
+  getExtendedHoursLevels: (datasetId: string, options?: { calendar?: string | SessionCalendar; from?: number; to?: number }) => Promise<{ success: boolean; datasetId?: string; error?: string } & Partial<ExtendedHoursLevels>>;
+  getSessionLevelConfig: () => Promise<SessionLevelConfig>;
+  setSessionLevelConfig: (updates: Partial<SessionLevelConfig>) => Promise<{ success: boolean; config?: SessionLevelConfig; error?: string }>;
+  syncSessionLevels: (sourceId: string, datasetId: string, options?: Partial<Pick<SessionLevelConfig, 'levels' | 'calendar'>>) => Promise<{ success: boolean; error?: string } & Partial<SessionLevelSync>>;
+
+  // Seasonality (returns by month, week of year, weekday or hour, with average / median paths and bands)
+  computeSeasonality: (datasetId: string, grouping: SeasonalityGrouping, options?: { timezone?: string; from?: number; to?: number; percentiles?: number[] }) => Promise<{ success: boolean; datasetId?: string; result?: SeasonalityResult; cached?: boolean; error?: string }>;
+
+  // Relative strength and rolling beta against a benchmark dataset of the same timeframe
+  computeRelative: (datasetId: string, benchmarkId: string, mode: 'ratio' | 'beta', options?: { window?: number; fill?: 'previous' | 'exact'; maxStaleBars?: number; from?: number; to?: number }) => Promise<{ success: boolean; error?: string } & Partial<RelativeStrengthResult>>;
+
+  // Economic calendar (CSV / ICS import or the weekly feed; schedule with job type 'calendar.refresh')
+  getEconomicEvents: (filter?: EconomicEventFilter) => Promise<{ success: boolean; events?: EconomicEvent[]; error?: string }>;
+  importEconomicCalendar: (filePath?: string | null, options?: { format?: 'csv' | 'ics'; utcOffsetMinutes?: number; source?: string }) => Promise<{ success: boolean; canceled?: boolean; source?: string; format?: string; imported?: number; skipped?: number; error?: string }>;
+  refreshEconomicCalendar: () => Promise<{ success: boolean; source?: string; imported?: number; error?: string }>;
+  deleteEconomicEvents: (filter: { source?: string; before?: number }) => Promise<{ success: boolean; deleted?: number; error?: string }>;
+  getEconomicCalendarConfig: () => Promise<(EconomicCalendarConfig & { providers: string[] }) | null>;
+  configureEconomicCalendar: (updates: Partial<Omit<EconomicCalendarConfig, 'warnings'>> & { warnings?: Partial<EconomicCalendarConfig['warnings']> }) => Promise<{ success: boolean; config?: EconomicCalendarConfig; error?: string }>;
+  // target: file path, null for a save dialog, or { subscribe: true, filePath? } to keep the file rewritten
+  exportICalendar: (kinds?: ICalKind[], target?: string | { subscribe: true; filePath?: string } | null, options?: ICalExportOptions) => Promise<{ success: boolean; canceled?: boolean; filePath?: string; counts?: { jobs: number; events: number }; subscribed?: boolean; error?: string }>;
+  getICalSubscription: () => Promise<{ filePath: string; kinds: ICalKind[]; options: ICalExportOptions } | null>;
+  unsubscribeICalendar: () => Promise<{ success: boolean; error?: string }>;
+  onEconomicEventWarning: (callback: (warning: EconomicEvent & { event: 'calendar.warning'; minutesUntil: number }) => void) => () => void;
+  onEconomicCalendarUpdated: (callback: (update: { source: string }) => void) => () => void;
+
+  // Scanner (screens over many datasets on the compute pool; schedule with job type 'scanner.run')
+  validateExpression: (expression: string) => Promise<ExpressionValidation>;
+  listScreens: () => Promise<{ success: boolean; screens?: ScannerScreen[]; error?: string }>;
+  saveScreen: (screen: { id?: string; name: string; expression: string; universe: ScreenUniverse }) => Promise<{ success: boolean; screen?: ScannerScreen; error?: string }>;
+  deleteScreen: (id: string) => Promise<{ success: boolean; deleted?: boolean; error?: string }>;
+  runScreen: (target: string | { expression: string; universe: ScreenUniverse }) => Promise<{ success: boolean; result?: ScanResult; error?: string }>;
+  onScanResult: (callback: (result: ScanResult) => void) => () => void;
+
+  // Heatmap (one metric per watchlist symbol on the compute pool; live heatmaps update from the feed)
+  validateHeatmapScript: (script: string) => Promise<ExpressionValidation>;
+  computeHeatmap: (symbols: string[], metric: HeatmapMetric, range: HeatmapRange, options?: { live?: boolean }) => Promise<{ success: boolean; heatmap?: Heatmap; error?: string }>;
+  getHeatmap: (id: string) => Promise<{ success: boolean; heatmap?: Heatmap; error?: string }>;
+  stopHeatmap: (id: string) => Promise<{ success: boolean }>;
+  listHeatmaps: () => Promise<{ id: string; metric: string; timeframe: string; symbols: number }[]>;
+  onHeatmapUpdate: (callback: (update: HeatmapUpdate) => void) => () => void;
+
+  // Broker orders (live routing; place / modify always show a native confirmation dialog)
+  listBrokers: () => Promise<BrokerInfo[]>;
+  configureBroker: (id: string, config: { paper?: boolean }) => Promise<{ success: boolean; config?: { paper: boolean }; status?: BrokerInfo; error?: string; declined?: boolean }>;
+  testBrokerCredentials: (id: string) => Promise<{ success: boolean; valid?: boolean; message?: string | null; error?: string }>;
+  placeOrder: (brokerId: string, order: BrokerOrderRequest) => Promise<BrokerOrderResult>;
+  modifyOrder: (brokerId: string, id: string, changes: { qty?: number; limitPrice?: number; stopPrice?: number; timeInForce?: BrokerOrderRequest['timeInForce'] }) => Promise<BrokerOrderResult>;
+  cancelOrder: (brokerId: string, id: string) => Promise<BrokerOrderResult>;
+  listOrders: (brokerId: string, options?: { status?: 'open' | 'closed' | 'all'; limit?: number }) => Promise<{ success: boolean; orders?: BrokerOrder[]; error?: string }>;
+  startOrderUpdates: (brokerId: string) => Promise<{ success: boolean; error?: string }>;
+  stopOrderUpdates: (brokerId: string) => Promise<{ success: boolean; error?: string }>;
+  onOrderUpdate: (callback: (update: { broker: string; event: string; order: BrokerOrder }) => void) => () => void;
   
   // Logs & Diagnostics
   getDbStatus: () => Promise<{ connected: boolean; error?: string }>;
   sendLog: (category: string, message: string, data?: any) => void;
 
+  // Shutdown: quitting waits for every onShutdownFlush callback (a few seconds at most)
+  onShutdownFlush: (callback: () => void | Promise<void>) => () => void;
+  onShutdownProgress: (callback: (progress: { step: string; status: 'running' | 'done' | 'failed'; error?: string; ms: number }) => void) => () => void;
+  forceQuit: () => Promise<{ success: boolean }>;
+
   // Telemetry & Events
   getSystemTelemetry: () => Promise<any>;
   getGlobalState: () => Promise<any>;
+  getPerfMetrics: (options?: { recent?: number }) => Promise<PerfMetrics>;
+  getMemoryReport: () => Promise<MemoryReport>;
+  resetPerfMetrics: () => Promise<{ success: boolean }>;
+  benchmarkStorage: (options?: { iterations?: number; drawings?: number; bars?: number }) => Promise<{ success: boolean; report?: StorageBenchmarkReport; error?: string }>;
+  benchmarkParse: (options?: { rows?: number; iterations?: number }) => Promise<{ success: boolean; report?: ParseBenchmarkReport; error?: string }>;
+  testHarness: TestHarnessAPI; // --test-mode only; the calls reject in a normal run
+  runDiagnostics: () => Promise<{ success: boolean; report?: DiagnosticsReport; error?: string }>;
+  createSupportBundle: (filePath?: string | null) => Promise<{ success: boolean; canceled?: boolean; filePath?: string; bytes?: number; error?: string }>;
+  getIntegrityReport: () => Promise<IntegrityReport | null>;
+  runIntegrityScan: () => Promise<{ success: boolean; report?: IntegrityReport; error?: string }>;
+  listIntegrityQuarantine: () => Promise<{ success: boolean; entries?: IntegrityQuarantineEntry[]; error?: string }>;
+  onIntegrityScanComplete: (callback: (report: IntegrityReport) => void) => () => void;
+  listCrashReports: () => Promise<{ success: boolean; reports?: CrashReportSummary[]; error?: string }>;
+  // Exactly what submitCrashReport sends (plus the minidump, when there is one)
+  previewCrashReport: (id: string) => Promise<{ success: boolean; report?: CrashReport; error?: string }>;
+  submitCrashReport: (id: string) => Promise<{ success: boolean; error?: string }>;
+  deleteCrashReport: (id: string) => Promise<{ success: boolean; error?: string }>;
+  getCrashReportConfig: () => Promise<CrashReportConfig>;
+  configureCrashReports: (updates: Partial<CrashReportConfig>) => Promise<{ success: boolean; config?: CrashReportConfig; error?: string }>;
+
+  // Usage analytics: local counters; uploads only when enabled with an endpoint
+  recordUsage: (feature: string) => Promise<{ success: boolean }>; // lowercase id, e.g. 'ui:drawing-toolbar'
+  getUsageSummary: (options?: { days?: number }) => Promise<{ success: boolean; since?: string | null; features?: { feature: string; count: number }[]; error?: string }>;
+  previewUsageUpload: () => Promise<{ success: boolean; payload?: UsageUploadPayload; error?: string }>;
+  getUsageConfig: () => Promise<UsageAnalyticsConfig>;
+  configureUsage: (updates: Partial<Pick<UsageAnalyticsConfig, 'enabled' | 'endpoint'>>) => Promise<{ success: boolean; config?: UsageAnalyticsConfig; error?: string }>;
+  uploadUsageNow: () => Promise<{ success: boolean; uploaded?: number; skipped?: 'disabled'; error?: string }>;
+  resetUsageInstallId: () => Promise<{ success: boolean; config?: UsageAnalyticsConfig; error?: string }>;
+  clearUsage: () => Promise<{ success: boolean; deleted?: number; error?: string }>;
+  // Persistence commands answer { success: false, readOnly: true } while read-only
+  getReadOnly: () => Promise<{ readOnly: boolean }>;
+  setReadOnly: (enabled: boolean) => Promise<{ success: boolean; readOnly?: boolean; error?: string }>;
+  onReadOnlyChanged: (callback: (state: { readOnly: boolean }) => void) => () => void;
+  getWorkspaceLock: () => Promise<{ held: boolean; holder: WorkspaceLockHolder | null }>;
+  takeOverWorkspace: () => Promise<{ success: boolean; error?: string }>;
+  onWorkspaceLockLost: (callback: (info: { hostname: string; since: number }) => void) => () => void;
+  listProfiles: () => Promise<{ success: boolean; profiles?: Profile[]; activeId?: string; pickAtStartup?: boolean; error?: string }>;
+  createProfile: (name: string) => Promise<{ success: boolean; profile?: Profile; error?: string }>;
+  renameProfile: (id: string, name: string) => Promise<{ success: boolean; profile?: Profile; error?: string }>;
+  deleteProfile: (id: string) => Promise<{ success: boolean; error?: string }>;
+  switchProfile: (id: string) => Promise<{ success: boolean; relaunching?: boolean; error?: string }>;
+  configureProfiles: (updates: { pickAtStartup?: boolean }) => Promise<{ success: boolean; pickAtStartup?: boolean; error?: string }>;
+  getStorageReport: () => Promise<{ success: boolean; report?: StorageReport; error?: string }>;
+  purgeCaches: (targets: StoragePurgeTarget[]) => Promise<{ success: boolean; deleted?: Partial<Record<StoragePurgeTarget, number>>; error?: string }>;
+  deleteUnusedSounds: () => Promise<{ success: boolean; removed?: string[]; error?: string }>;
+  compactDatabase: () => Promise<{ success: boolean; error?: string } & Partial<CompactionResult>>;
+  getCompactionConfig: () => Promise<CompactionConfig & { lastCompaction: (CompactionResult & { trigger: 'manual' | 'idle' }) | null }>;
+  setCompactionConfig: (config: Partial<CompactionConfig>) => Promise<{ success: boolean; config?: CompactionConfig; error?: string }>;
   copyToClipboard: (text: string) => void; // Added
   onFolderChange: (callback: (files: any[]) => void) => () => void;
+  onLiveBar: (callback: (event: LiveBarEvent) => void) => () => void;
+  onLiveQuote: (callback: (event: LiveQuoteEvent) => void) => () => void;
+  onLiveFeedPaused: (callback: (event: { paused: boolean }) => void) => () => void;
+  onPresentationChanged: (callback: (status: PresentationStatus) => void) => () => void;
+  onSyncEvent: (callback: (envelope: SyncEnvelope) => void) => () => void;
+  onActionInvoked: (callback: (invocation: { id: string; args: Record<string, any> }) => void) => () => void;
+  onLanguageChanged: (callback: (event: { language: string; strings: Record<string, string> }) => void) => () => void;
+  onKeybindingsChanged: (callback: (snapshot: KeybindingSnapshot) => void) => () => void;
+  onKeybindingTriggered: (callback: (event: { action: string }) => void) => () => void;
+  onProviderStatus: (callback: (event: ProviderStatusEvent) => void) => () => void;
+  onProviderHealth: (callback: (event: ProviderHealthEvent) => void) => () => void;
+  onDepthSnapshot: (callback: (snapshot: DepthSnapshot) => void) => () => void;
+  onAlertTriggered: (callback: (event: AlertTriggeredEvent) => void) => () => void;
+  onImportProgress: (callback: (event: ImportProgressEvent) => void) => () => void;
+  onImportComplete: (callback: (event: ImportCompleteEvent) => void) => () => void;
+  onNewsItems: (callback: (items: NewsItem[]) => void) => () => void;
 }
 
 declare global {